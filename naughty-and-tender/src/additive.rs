@@ -0,0 +1,184 @@
+//! Additive oscillator for Naughty and Tender
+//!
+//! Builds a tone from a bank of harmonically related sines, one per partial,
+//! each with its own level. The levels come from the harmonic editor; a spectral
+//! tilt then brightens or darkens the whole spectrum at once, in dB per octave
+//! above the fundamental.
+//!
+//! Rather than running a phase accumulator per partial, the bank tracks only the
+//! fundamental's phase and generates partial `n` from partial `n - 1` with one
+//! complex multiplication (the angle-addition identities). Partials that would
+//! land above Nyquist are skipped, so the oscillator never aliases.
+//!
+//! # References
+//! - Moore, "Elements of Computer Music" (1990), additive synthesis
+//! - sin((n+1)x) = sin(nx)cos(x) + cos(nx)sin(x),
+//!   cos((n+1)x) = cos(nx)cos(x) - sin(nx)sin(x)
+//! - Tilt gain for partial n: 10^(`tilt_db` * log2(n) / 20)
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use std::f32::consts::TAU;
+
+/// Number of partials in the harmonic editor
+pub const NUM_PARTIALS: usize = 32;
+
+/// Gain of each partial after applying `tilt_db_per_octave` to `levels`
+///
+/// The result is scaled so the partials' gains sum to at most 1.0, which keeps
+/// the oscillator's output within -1.0 - 1.0 whatever the spectrum.
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::additive::{partial_gains, NUM_PARTIALS};
+///
+/// let mut levels = [0.0; NUM_PARTIALS];
+/// levels[0] = 1.0;
+/// levels[1] = 1.0;
+///
+/// // -6 dB/octave halves the second partial relative to the first
+/// let gains = partial_gains(&levels, -6.0206);
+/// assert!((gains[1] / gains[0] - 0.5).abs() < 1e-4);
+/// ```
+#[must_use]
+pub fn partial_gains(levels: &[f32; NUM_PARTIALS], tilt_db_per_octave: f32) -> [f32; NUM_PARTIALS] {
+    let mut gains: [f32; NUM_PARTIALS] = std::array::from_fn(|i| {
+        #[allow(clippy::cast_precision_loss)] // At most 32
+        let octaves = ((i + 1) as f32).log2();
+        levels[i].max(0.0) * 10.0_f32.powf(tilt_db_per_octave * octaves / 20.0)
+    });
+
+    let total: f32 = gains.iter().sum();
+    if total > 1.0 {
+        for gain in &mut gains {
+            *gain /= total;
+        }
+    }
+    gains
+}
+
+/// Band-limited bank of harmonic sine partials
+///
+/// # Real-time Safety
+/// - Fixed-size partial array, no allocations in `process()`
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::additive::{partial_gains, AdditiveOscillator, NUM_PARTIALS};
+///
+/// let mut osc = AdditiveOscillator::new(44100.0);
+/// osc.set_gains(partial_gains(&[0.5; NUM_PARTIALS], -3.0));
+/// let sample = osc.process(220.0);
+/// ```
+pub struct AdditiveOscillator {
+    /// Gain of each partial (see [`partial_gains`])
+    gains: [f32; NUM_PARTIALS],
+
+    /// Fundamental phase (0.0 - 1.0)
+    phase: f32,
+
+    /// Sample rate in Hz
+    sample_rate: f32,
+}
+
+impl AdditiveOscillator {
+    /// Create a silent oscillator
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            gains: [0.0; NUM_PARTIALS],
+            phase: 0.0,
+            sample_rate,
+        }
+    }
+
+    /// Set the gain of every partial
+    pub fn set_gains(&mut self, gains: [f32; NUM_PARTIALS]) {
+        self.gains = gains;
+    }
+
+    /// Restart from phase zero
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+
+    /// Restart the fundamental at `phase` (in cycles, wrapped into 0.0-1.0)
+    pub fn reset_to(&mut self, phase: f32) {
+        self.phase = phase - phase.floor();
+    }
+
+    /// Generate one sample at `frequency` Hz
+    #[inline]
+    pub fn process(&mut self, frequency: f32) -> f32 {
+        // Highest partial that stays below Nyquist
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Positive, then clamped
+        let audible = ((0.5 * self.sample_rate / frequency.max(1.0)) as usize).min(NUM_PARTIALS);
+
+        let (sin_1, cos_1) = (TAU * self.phase).sin_cos();
+        let (mut sin_n, mut cos_n) = (sin_1, cos_1);
+        let mut output = 0.0;
+        for &gain in &self.gains[..audible] {
+            output += gain * sin_n;
+            (sin_n, cos_n) = (sin_n * cos_1 + cos_n * sin_1, cos_n * cos_1 - sin_n * sin_1);
+        }
+
+        self.phase += frequency / self.sample_rate;
+        self.phase -= self.phase.floor();
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    fn single_partial(index: usize) -> [f32; NUM_PARTIALS] {
+        let mut levels = [0.0; NUM_PARTIALS];
+        levels[index] = 1.0;
+        levels
+    }
+
+    #[test]
+    fn test_single_partial_is_a_sine() {
+        // The third partial of 100 Hz is a 300 Hz sine
+        let mut osc = AdditiveOscillator::new(SAMPLE_RATE);
+        osc.set_gains(partial_gains(&single_partial(2), 0.0));
+        for i in 0..1000u16 {
+            let expected = (TAU * 300.0 * f32::from(i) / SAMPLE_RATE).sin();
+            assert!((osc.process(100.0) - expected).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_tilt_scales_per_octave() {
+        let gains = partial_gains(&[0.01; NUM_PARTIALS], 6.0);
+        // Partial 4 is two octaves above the fundamental
+        let ratio_db = 20.0 * (gains[3] / gains[0]).log10();
+        assert!((ratio_db - 12.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_gains_never_clip() {
+        let gains = partial_gains(&[1.0; NUM_PARTIALS], 12.0);
+        assert!(gains.iter().sum::<f32>() <= 1.0 + 1e-5);
+
+        let mut osc = AdditiveOscillator::new(SAMPLE_RATE);
+        osc.set_gains(gains);
+        for _ in 0..2000 {
+            assert!(osc.process(50.0).abs() <= 1.0 + 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_partials_above_nyquist_are_skipped() {
+        // At 10 kHz only the first two partials fit below 24 kHz
+        let mut osc = AdditiveOscillator::new(SAMPLE_RATE);
+        osc.set_gains(partial_gains(&single_partial(2), 0.0));
+        for _ in 0..100 {
+            assert!(osc.process(10_000.0).abs() < 1e-6);
+        }
+    }
+}
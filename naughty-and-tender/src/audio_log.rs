@@ -0,0 +1,399 @@
+//! Audio-thread diagnostics log for Naughty and Tender
+//!
+//! Things worth knowing about happen on the audio thread (voices stolen, stuck
+//! notes released, a blown-up filter reset, a host tempo out of range), but the
+//! audio thread can't format strings or write files. It pushes small
+//! fixed-size [`LogRecord`]s into an [`AudioLog`] instead, stamped with its
+//! sample clock; the editor drains the ring each frame, and a [`LogWriter`]
+//! turns the records into text lines for the log panel and, optionally, a log
+//! file written by the background file task.
+//!
+//! Rate limiting happens on the reading side, per kind of record: a burst of
+//! lines per window gets through, and the rest are counted and summarized in
+//! one line once the window has passed. The ring drops records when nobody
+//! reads it (the editor is closed) and counts how many, so a flood never costs
+//! the audio thread more than an atomic store.
+//!
+//! # References
+//! - Rate-limited logging: Linux `printk_ratelimit` (burst per interval,
+//!   "callbacks suppressed")
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use shared_core::events::{EventQueue, Packed};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+/// Records the ring holds between editor frames
+pub const LOG_CAPACITY: usize = 256;
+
+/// Lines of one kind written per window before the rest are suppressed
+pub const LOG_BURST: u32 = 5;
+
+/// Rate-limiting window in seconds
+pub const LOG_WINDOW_S: f64 = 1.0;
+
+/// Something that happened on the audio thread
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogEvent {
+    /// Notes took over sounding voices, with every voice busy
+    VoicesStolen { count: u32 },
+
+    /// The stuck-note watchdog released voices held past its timeout
+    StuckNotesReleased { count: u32 },
+
+    /// The output went NaN or infinite; the sub-block was silenced and this many
+    /// voices reset
+    NonFiniteRecovered { voices: u32 },
+
+    /// The host reported a tempo outside the usable range
+    TempoClamped { bpm: f32 },
+}
+
+/// Number of [`LogEvent`] kinds
+const NUM_KINDS: usize = 4;
+
+impl LogEvent {
+    /// Kind index, for packing and rate limiting
+    fn kind(self) -> usize {
+        match self {
+            Self::VoicesStolen { .. } => 0,
+            Self::StuckNotesReleased { .. } => 1,
+            Self::NonFiniteRecovered { .. } => 2,
+            Self::TempoClamped { .. } => 3,
+        }
+    }
+
+    /// What the rate limiter calls the kind when suppressing it
+    fn kind_name(kind: usize) -> &'static str {
+        ["voice steal", "stuck note", "non-finite output", "tempo"][kind]
+    }
+
+    /// Kind in the low byte, the count or value bits above
+    fn pack(self) -> u64 {
+        let payload = match self {
+            Self::VoicesStolen { count } | Self::StuckNotesReleased { count } => count,
+            Self::NonFiniteRecovered { voices } => voices,
+            Self::TempoClamped { bpm } => bpm.to_bits(),
+        };
+        self.kind() as u64 | u64::from(payload) << 32
+    }
+
+    fn unpack(word: u64) -> Self {
+        #[allow(clippy::cast_possible_truncation)] // The payload's 32 bits
+        let payload = (word >> 32) as u32;
+        match word & 0xFF {
+            0 => Self::VoicesStolen { count: payload },
+            1 => Self::StuckNotesReleased { count: payload },
+            2 => Self::NonFiniteRecovered { voices: payload },
+            _ => Self::TempoClamped {
+                bpm: f32::from_bits(payload),
+            },
+        }
+    }
+}
+
+/// An event and when it happened
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LogRecord {
+    /// Audio thread sample clock: samples processed since initialize
+    pub at_samples: u64,
+    pub event: LogEvent,
+}
+
+impl Packed for LogRecord {
+    /// Sample clock, then the packed event
+    fn pack(self) -> [u64; 2] {
+        [self.at_samples, self.event.pack()]
+    }
+
+    fn unpack([at_samples, event]: [u64; 2]) -> Self {
+        Self {
+            at_samples,
+            event: LogEvent::unpack(event),
+        }
+    }
+}
+
+/// Ring of log records from the audio thread to the editor
+///
+/// The records travel through an [`EventQueue`]: one thread pushes and one
+/// thread pops.
+///
+/// # Real-time Safety
+/// - Slots allocated once at construction
+/// - `push` only performs atomic loads and stores; when the ring is full the
+///   record is dropped and counted
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::audio_log::{AudioLog, LogEvent, LogWriter};
+///
+/// let log = AudioLog::new();
+/// log.set_sample_rate(48000.0);
+/// log.push(96000, LogEvent::VoicesStolen { count: 2 }); // Audio thread
+///
+/// let mut lines = Vec::new();
+/// LogWriter::new().drain(&log, |line| lines.push(line)); // Editor
+/// assert_eq!(lines, ["[     2.000 s] 2 voices stolen"]);
+/// ```
+pub struct AudioLog {
+    /// Records waiting for the editor
+    records: EventQueue<LogRecord>,
+
+    /// Records dropped since the last `take_dropped`
+    dropped: AtomicU64,
+
+    /// Host sample rate (`f32` bits), for timestamps
+    sample_rate: AtomicU32,
+}
+
+impl Default for AudioLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioLog {
+    /// Create an empty log of [`LOG_CAPACITY`] records at 44.1 kHz
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            records: EventQueue::new(LOG_CAPACITY),
+            dropped: AtomicU64::new(0),
+            sample_rate: AtomicU32::new(44100.0f32.to_bits()),
+        }
+    }
+
+    /// Record the host sample rate (audio thread, on initialize)
+    pub fn set_sample_rate(&self, sample_rate: f32) {
+        self.sample_rate
+            .store(sample_rate.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Host sample rate
+    #[must_use]
+    pub fn sample_rate(&self) -> f32 {
+        f32::from_bits(self.sample_rate.load(Ordering::Relaxed))
+    }
+
+    /// Add a record (audio thread); a full ring drops it
+    pub fn push(&self, at_samples: u64, event: LogEvent) {
+        if !self.records.push(LogRecord { at_samples, event }) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Take the oldest record (editor thread)
+    pub fn pop(&self) -> Option<LogRecord> {
+        self.records.pop()
+    }
+
+    /// Records dropped on a full ring since the last call
+    pub fn take_dropped(&self) -> u64 {
+        self.dropped.swap(0, Ordering::Relaxed)
+    }
+}
+
+/// Lines of one kind let through in the current window
+#[derive(Debug, Clone, Copy, Default)]
+struct RateWindow {
+    /// When the window started, in seconds on the sample clock
+    start_s: f64,
+    written: u32,
+    suppressed: u32,
+}
+
+/// Formats log records as text lines, rate limited per kind (editor thread)
+#[derive(Debug, Default)]
+pub struct LogWriter {
+    windows: [RateWindow; NUM_KINDS],
+}
+
+impl LogWriter {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Format every waiting record, passing each line to `emit`
+    ///
+    /// A kind's suppressed lines are summed up in one line by its next record
+    /// after the window, or by any record after the window.
+    pub fn drain(&mut self, log: &AudioLog, mut emit: impl FnMut(String)) {
+        let dropped = log.take_dropped();
+        if dropped > 0 {
+            emit(format!("{dropped} log records dropped (the log filled up)"));
+        }
+
+        let sample_rate = f64::from(log.sample_rate());
+        while let Some(record) = log.pop() {
+            #[allow(clippy::cast_precision_loss)] // Hours of audio still land to the sample
+            let at_s = record.at_samples as f64 / sample_rate;
+
+            // Close every window this record is past (or before: the clock
+            // restarts when the plugin is reinitialized)
+            for (kind, window) in self.windows.iter_mut().enumerate() {
+                if at_s - window.start_s >= LOG_WINDOW_S || at_s < window.start_s {
+                    if window.suppressed > 0 {
+                        emit(format!(
+                            "{} {} {} messages suppressed",
+                            timestamp(at_s),
+                            window.suppressed,
+                            LogEvent::kind_name(kind)
+                        ));
+                    }
+                    *window = RateWindow {
+                        start_s: at_s,
+                        ..RateWindow::default()
+                    };
+                }
+            }
+
+            let window = &mut self.windows[record.event.kind()];
+            if window.written < LOG_BURST {
+                window.written += 1;
+                emit(format!("{} {}", timestamp(at_s), describe(record.event)));
+            } else {
+                window.suppressed += 1;
+            }
+        }
+    }
+}
+
+/// Line prefix for a time on the sample clock
+fn timestamp(at_s: f64) -> String {
+    format!("[{at_s:10.3} s]")
+}
+
+/// What happened, in words
+fn describe(event: LogEvent) -> String {
+    let plural = |count: u32| if count == 1 { "" } else { "s" };
+    match event {
+        LogEvent::VoicesStolen { count } => format!("{count} voice{} stolen", plural(count)),
+        LogEvent::StuckNotesReleased { count } => {
+            format!("{count} stuck note{} released", plural(count))
+        }
+        LogEvent::NonFiniteRecovered { voices } => format!(
+            "Non-finite output silenced, {voices} voice{} reset",
+            plural(voices)
+        ),
+        LogEvent::TempoClamped { bpm } => format!("Host tempo {bpm} BPM out of range, clamped"),
+    }
+}
+
+/// Turns a running total into increases since the last block (audio thread)
+///
+/// For counts kept elsewhere, like the voice managers' steal counts.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TotalWatch {
+    last: u64,
+}
+
+impl TotalWatch {
+    /// Increase since the last call (0 after a reset lowered the total)
+    pub fn take_increase(&mut self, total: u64) -> u32 {
+        let increase = total.saturating_sub(self.last);
+        self.last = total;
+        u32::try_from(increase).unwrap_or(u32::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(writer: &mut LogWriter, log: &AudioLog) -> Vec<String> {
+        let mut lines = Vec::new();
+        writer.drain(log, |line| lines.push(line));
+        lines
+    }
+
+    #[test]
+    fn test_events_survive_packing() {
+        for event in [
+            LogEvent::VoicesStolen { count: u32::MAX },
+            LogEvent::StuckNotesReleased { count: 1 },
+            LogEvent::NonFiniteRecovered { voices: 3 },
+            LogEvent::TempoClamped { bpm: -1.5 },
+        ] {
+            assert_eq!(LogEvent::unpack(event.pack()), event);
+        }
+    }
+
+    #[test]
+    fn test_full_ring_drops_and_counts() {
+        let log = AudioLog::new();
+        for at_samples in 0..LOG_CAPACITY as u64 + 3 {
+            log.push(at_samples, LogEvent::NonFiniteRecovered { voices: 1 });
+        }
+        assert_eq!(log.pop().map(|record| record.at_samples), Some(0));
+
+        let mut writer = LogWriter::new();
+        let lines = drain(&mut writer, &log);
+        assert_eq!(lines[0], "3 log records dropped (the log filled up)");
+        assert_eq!(log.take_dropped(), 0);
+    }
+
+    #[test]
+    fn test_lines_rate_limited_per_kind() {
+        let log = AudioLog::new();
+        log.set_sample_rate(1000.0);
+        let mut writer = LogWriter::new();
+
+        // A steal every millisecond for a second and a half, one stuck note
+        for at_samples in 0..1500 {
+            log.push(at_samples, LogEvent::VoicesStolen { count: 1 });
+            if at_samples == 10 {
+                log.push(at_samples, LogEvent::StuckNotesReleased { count: 2 });
+            }
+            if at_samples % 200 == 0 {
+                // Keep the ring drained, as the editor does every frame
+                drain(&mut writer, &log);
+            }
+        }
+        let lines = drain(&mut writer, &log);
+        assert!(lines.is_empty(), "Still suppressing: {lines:?}");
+
+        // The next window opens with a summary of the last one
+        log.push(2000, LogEvent::VoicesStolen { count: 1 });
+        log.push(2600, LogEvent::StuckNotesReleased { count: 1 });
+        let lines = drain(&mut writer, &log);
+        assert_eq!(
+            lines,
+            [
+                "[     2.000 s] 495 voice steal messages suppressed",
+                "[     2.000 s] 1 voice stolen",
+                "[     2.600 s] 1 stuck note released",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_burst_gets_through() {
+        let log = AudioLog::new();
+        log.set_sample_rate(1000.0);
+        log.push(0, LogEvent::StuckNotesReleased { count: 2 });
+        log.push(5, LogEvent::TempoClamped { bpm: 0.0 });
+        for at_samples in 0..=u64::from(LOG_BURST) {
+            log.push(at_samples, LogEvent::VoicesStolen { count: 1 });
+        }
+
+        let lines = drain(&mut LogWriter::new(), &log);
+        assert_eq!(lines.len(), 2 + LOG_BURST as usize);
+        assert_eq!(lines[0], "[     0.000 s] 2 stuck notes released");
+        assert_eq!(
+            lines[1],
+            "[     0.005 s] Host tempo 0 BPM out of range, clamped"
+        );
+    }
+
+    #[test]
+    fn test_total_watch_reports_increases() {
+        let mut watch = TotalWatch::default();
+        assert_eq!(watch.take_increase(3), 3);
+        assert_eq!(watch.take_increase(3), 0);
+        assert_eq!(watch.take_increase(5), 2);
+        assert_eq!(watch.take_increase(0), 0);
+        assert_eq!(watch.take_increase(1), 1);
+    }
+}
@@ -0,0 +1,271 @@
+//! Audition pad for Naughty and Tender
+//!
+//! Plays a note from the editor while a pad is held, or until it's clicked
+//! again in latch mode, so a patch can be heard while it's being tweaked
+//! without a keyboard. The editor thread sends note-ons and note-offs to the
+//! audio thread through an [`EventQueue`]; the audio thread drains it at the
+//! start of each block and treats the notes like keys played over MIDI.
+//!
+//! The pad remembers the note it started and ends that one, so changing the
+//! note while it sounds retriggers rather than hanging the old note. A note-off
+//! that doesn't fit in a full queue is retried on the next frame, and closing
+//! the editor releases whatever the pad is holding.
+//!
+//! # References
+//! - Audition/preview buttons in hardware synths (Elektron "preview", Nord
+//!   "sound preview") and soft synth editors
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use std::sync::Arc;
+
+use shared_core::events::{Event, EventQueue, TimedEvent};
+
+/// Events the editor can queue ahead of the audio thread
+pub const AUDITION_QUEUE_CAPACITY: usize = 64;
+
+/// Default audition note (middle C)
+pub const DEFAULT_AUDITION_NOTE: u8 = 60;
+
+/// Default audition velocity
+pub const DEFAULT_AUDITION_VELOCITY: f32 = 0.8;
+
+/// Release velocity of audition note-offs
+const RELEASE_VELOCITY: f32 = 0.5;
+
+/// Held or latched note sent from the editor to the audio thread
+///
+/// # Real-time Safety
+/// - Only the editor thread owns the pad; it touches the audio thread through
+///   the lock-free queue alone
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::audition::{AuditionPad, AUDITION_QUEUE_CAPACITY};
+/// use shared_core::events::{Event, EventQueue};
+/// use std::sync::Arc;
+///
+/// let queue = Arc::new(EventQueue::new(AUDITION_QUEUE_CAPACITY));
+/// let mut pad = AuditionPad::new(queue.clone());
+/// pad.update(true); // Pad pressed
+/// assert!(matches!(queue.pop().map(|event| event.event), Some(Event::NoteOn { note: 60, .. })));
+/// pad.update(false); // Released
+/// assert!(matches!(queue.pop().map(|event| event.event), Some(Event::NoteOff { note: 60, .. })));
+/// ```
+pub struct AuditionPad {
+    /// Editor to audio thread notes
+    events: Arc<EventQueue>,
+
+    /// Note to play
+    note: u8,
+
+    /// Velocity 0.0 - 1.0
+    velocity: f32,
+
+    /// Whether a click latches the note on until the next click
+    latch: bool,
+
+    /// Whether the pad is latched on
+    latched: bool,
+
+    /// Note the audio thread was told to start, until it's told to end it
+    sounding: Option<u8>,
+}
+
+impl AuditionPad {
+    /// Create a pad sending to `events`, playing the default note
+    #[must_use]
+    pub fn new(events: Arc<EventQueue>) -> Self {
+        Self {
+            events,
+            note: DEFAULT_AUDITION_NOTE,
+            velocity: DEFAULT_AUDITION_VELOCITY,
+            latch: false,
+            latched: false,
+            sounding: None,
+        }
+    }
+
+    /// Note to play
+    #[must_use]
+    pub fn note(&self) -> u8 {
+        self.note
+    }
+
+    /// Set the note to play (0 - 127), retriggering on the next update if
+    /// one is sounding
+    pub fn set_note(&mut self, note: u8) {
+        self.note = note.min(127);
+    }
+
+    /// Velocity 0.0 - 1.0
+    #[must_use]
+    pub fn velocity(&self) -> f32 {
+        self.velocity
+    }
+
+    /// Set the velocity of the next note-on (0.0 - 1.0)
+    pub fn set_velocity(&mut self, velocity: f32) {
+        self.velocity = velocity.clamp(0.0, 1.0);
+    }
+
+    /// Whether a click latches the note on
+    #[must_use]
+    pub fn latch(&self) -> bool {
+        self.latch
+    }
+
+    /// Switch latch mode; switching it off releases a latched note on the
+    /// next update
+    pub fn set_latch(&mut self, latch: bool) {
+        self.latch = latch;
+        if !latch {
+            self.latched = false;
+        }
+    }
+
+    /// Pad clicked: in latch mode, toggles the note on or off
+    pub fn click(&mut self) {
+        if self.latch {
+            self.latched = !self.latched;
+        }
+    }
+
+    /// Whether a note is sounding
+    #[must_use]
+    pub fn is_sounding(&self) -> bool {
+        self.sounding.is_some()
+    }
+
+    /// Send whatever note-on or note-off brings the audio thread in line with
+    /// the pad, given whether it's held down (call once per editor frame)
+    pub fn update(&mut self, held: bool) {
+        let wanted = if self.latch { self.latched } else { held };
+        if let Some(note) = self.sounding {
+            if wanted && note == self.note {
+                return;
+            }
+            if !self.send(Event::NoteOff {
+                channel: 0,
+                note,
+                velocity: RELEASE_VELOCITY,
+            }) {
+                // Queue full: try again next frame
+                return;
+            }
+            self.sounding = None;
+        }
+
+        if wanted
+            && self.send(Event::NoteOn {
+                channel: 0,
+                note: self.note,
+                velocity: self.velocity,
+            })
+        {
+            self.sounding = Some(self.note);
+        }
+    }
+
+    /// Queue an event for the start of the audio thread's next block
+    fn send(&self, event: Event) -> bool {
+        self.events.push(TimedEvent::new(0, event))
+    }
+}
+
+impl Drop for AuditionPad {
+    /// The editor closing ends the note
+    fn drop(&mut self) {
+        self.set_latch(false);
+        self.update(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(queue: &EventQueue) -> Vec<Event> {
+        std::iter::from_fn(|| queue.pop())
+            .map(|event| event.event)
+            .collect()
+    }
+
+    fn note_on(note: u8) -> Event {
+        Event::NoteOn {
+            channel: 0,
+            note,
+            velocity: DEFAULT_AUDITION_VELOCITY,
+        }
+    }
+
+    fn note_off(note: u8) -> Event {
+        Event::NoteOff {
+            channel: 0,
+            note,
+            velocity: RELEASE_VELOCITY,
+        }
+    }
+
+    #[test]
+    fn test_pad_plays_while_held() {
+        let queue = Arc::new(EventQueue::new(AUDITION_QUEUE_CAPACITY));
+        let mut pad = AuditionPad::new(queue.clone());
+
+        pad.update(false);
+        assert!(queue.is_empty());
+        pad.update(true);
+        pad.update(true);
+        assert_eq!(drain(&queue), vec![note_on(60)]);
+        assert!(pad.is_sounding());
+
+        // A new note while held ends the old one first
+        pad.set_note(64);
+        pad.update(true);
+        pad.update(false);
+        assert_eq!(drain(&queue), vec![note_off(60), note_on(64), note_off(64)]);
+        assert!(!pad.is_sounding());
+    }
+
+    #[test]
+    fn test_latch_holds_until_clicked_again() {
+        let queue = Arc::new(EventQueue::new(AUDITION_QUEUE_CAPACITY));
+        let mut pad = AuditionPad::new(queue.clone());
+        pad.set_latch(true);
+
+        pad.click();
+        pad.update(true);
+        pad.update(false);
+        assert_eq!(drain(&queue), vec![note_on(60)]);
+
+        pad.click();
+        pad.update(true);
+        assert_eq!(drain(&queue), vec![note_off(60)]);
+
+        // Leaving latch mode releases a latched note
+        pad.click();
+        pad.update(false);
+        pad.set_latch(false);
+        pad.update(false);
+        assert_eq!(drain(&queue), vec![note_on(60), note_off(60)]);
+    }
+
+    #[test]
+    fn test_note_off_retried_and_sent_on_drop() {
+        let queue = Arc::new(EventQueue::new(1));
+        let mut pad = AuditionPad::new(queue.clone());
+
+        pad.update(true);
+        pad.update(false);
+        assert!(
+            pad.is_sounding(),
+            "Note-off can't fit until the queue drains"
+        );
+        assert_eq!(drain(&queue), vec![note_on(60)]);
+
+        pad.update(true);
+        assert!(queue.is_empty(), "Still held, nothing to send");
+        drop(pad);
+        assert_eq!(drain(&queue), vec![note_off(60)]);
+    }
+}
@@ -0,0 +1,390 @@
+//! Crash-safe autosave for Naughty and Tender
+//!
+//! The host saves the plugin's state with the project, so a host crash loses
+//! every tweak since the last project save. While the editor is open it
+//! snapshots the parameters every [`AUTOSAVE_INTERVAL_S`] seconds (only when
+//! they changed) to a file in the temp directory, in the preset format. The
+//! snapshot is written beside the real file and renamed over it, so a crash
+//! mid-write leaves the previous snapshot intact.
+//!
+//! Each instance has its own snapshot file, named by an autosave id saved with
+//! the project, and holds a lock on it for as long as it runs. Reopening the
+//! project gives an instance back its id; if no running instance holds that
+//! id's lock, the snapshot's writer is gone, and one older than this instance
+//! was left by a session that ended without cleaning up: the editor offers to
+//! restore it. An instance whose id is locked by another (a duplicated track
+//! shares its original's state) takes a fresh id. Dropping the plugin cleanly
+//! removes the snapshot its editor wrote.
+//!
+//! # References
+//! - Autosave and crash recovery in DAWs (Reaper's `.rpp-bak` and
+//!   "autosave to timestamped file", Ableton's crash recovery prompt)
+//! - Write-then-rename for atomic file replacement (POSIX `rename`)
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use std::fs::{self, File, TryLockError};
+use std::hash::BuildHasher;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::presets::{Preset, PresetError, PRESET_EXTENSION};
+
+/// Seconds between autosaves
+pub const AUTOSAVE_INTERVAL_S: f64 = 30.0;
+
+/// Name the snapshot is saved under
+pub const SNAPSHOT_NAME: &str = "Last Session";
+
+/// Fresh ids tried before giving up on claiming a snapshot file
+const CLAIM_ATTEMPTS: usize = 8;
+
+/// Directory the snapshots are kept in
+#[must_use]
+pub fn snapshot_dir() -> PathBuf {
+    std::env::temp_dir().join("naughty-and-tender")
+}
+
+/// Snapshot file of the instance with autosave id `id`
+#[must_use]
+pub fn snapshot_path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("autosave-{id:016x}.{PRESET_EXTENSION}"))
+}
+
+/// Lock file held by the instance writing the snapshot at `path`
+fn lock_path(path: &Path) -> PathBuf {
+    path.with_extension("lock")
+}
+
+/// A new random autosave id (never 0, which means none yet)
+fn fresh_id() -> u64 {
+    let seed = (SystemTime::now(), std::process::id());
+    std::collections::hash_map::RandomState::new()
+        .hash_one(seed)
+        .max(1)
+}
+
+/// The snapshot file an instance writes, and its lock
+#[derive(Debug)]
+struct Claim {
+    id: u64,
+    path: PathBuf,
+
+    /// Locked for as long as the instance runs (the OS lets go if it crashes)
+    lock: File,
+}
+
+/// One plugin instance's part in the autosave (shared with its editor)
+#[derive(Debug)]
+pub struct AutosaveSession {
+    /// When the instance was created
+    started: SystemTime,
+
+    /// Directory the snapshot files are in
+    dir: PathBuf,
+
+    /// This instance's snapshot file, once claimed
+    claim: Mutex<Option<Claim>>,
+
+    /// Whether the editor sent a snapshot to be written
+    wrote: AtomicBool,
+}
+
+impl AutosaveSession {
+    /// Create a session keeping its snapshot in [`snapshot_dir`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_dir(snapshot_dir())
+    }
+
+    /// Create a session keeping its snapshot in `dir`
+    #[must_use]
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self {
+            started: SystemTime::now(),
+            dir,
+            claim: Mutex::new(None),
+            wrote: AtomicBool::new(false),
+        }
+    }
+
+    /// Lock this instance's snapshot file, returning its autosave id
+    ///
+    /// Takes `saved_id` (the id saved with the project, 0 for none) unless a
+    /// running instance holds it, else a fresh id. Claiming again returns the
+    /// same id.
+    ///
+    /// # Errors
+    /// An I/O error if the lock file can't be created or locked.
+    pub fn claim(&self, saved_id: u64) -> io::Result<u64> {
+        let Ok(mut claim) = self.claim.lock() else {
+            return Err(io::Error::other("autosave claim poisoned"));
+        };
+        if let Some(claim) = claim.as_ref() {
+            return Ok(claim.id);
+        }
+
+        fs::create_dir_all(&self.dir)?;
+        let mut id = saved_id;
+        for _ in 0..CLAIM_ATTEMPTS {
+            if id != 0 {
+                let path = snapshot_path(&self.dir, id);
+                let lock = File::options()
+                    .create(true)
+                    .truncate(false)
+                    .write(true)
+                    .open(lock_path(&path))?;
+                match lock.try_lock() {
+                    Ok(()) => {
+                        *claim = Some(Claim { id, path, lock });
+                        return Ok(id);
+                    }
+                    // Another running instance writes this one
+                    Err(TryLockError::WouldBlock) => {}
+                    Err(TryLockError::Error(error)) => return Err(error),
+                }
+            }
+            id = fresh_id();
+        }
+        Err(io::Error::other("no free autosave id"))
+    }
+
+    /// This instance's snapshot file, once claimed
+    #[must_use]
+    pub fn path(&self) -> Option<PathBuf> {
+        let claim = self.claim.lock().ok()?;
+        claim.as_ref().map(|claim| claim.path.clone())
+    }
+
+    /// The snapshot an earlier session with this instance's id left behind
+    ///
+    /// Only a claimed instance finds one: holding the lock means its writer
+    /// is no longer running.
+    #[must_use]
+    pub fn earlier_snapshot(&self) -> Option<Snapshot> {
+        Snapshot::find(&self.path()?, self.started)
+    }
+
+    /// When the instance was created: older snapshots are an earlier session's
+    #[must_use]
+    pub fn started(&self) -> SystemTime {
+        self.started
+    }
+
+    /// Note that the editor wrote a snapshot
+    pub fn mark_written(&self) {
+        self.wrote.store(true, Ordering::Relaxed);
+    }
+
+    /// The instance is shutting down cleanly: remove its snapshot and let go
+    /// of the lock
+    pub fn end(&self) {
+        let Some(claim) = self.claim.lock().ok().and_then(|mut claim| claim.take()) else {
+            return;
+        };
+        if self.wrote.load(Ordering::Relaxed) {
+            clear_snapshot(&claim.path);
+        }
+        let _ = fs::remove_file(lock_path(&claim.path));
+    }
+}
+
+impl Default for AutosaveSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parameters found on disk from an earlier session
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub preset: Preset,
+
+    /// When the snapshot was written
+    pub saved: SystemTime,
+}
+
+impl Snapshot {
+    /// The snapshot at `path`, if it was written before `started` (this
+    /// session's own snapshots aren't offered back)
+    ///
+    /// A missing, unreadable or malformed file gives `None`.
+    #[must_use]
+    pub fn find(path: &Path, started: SystemTime) -> Option<Self> {
+        let saved = fs::metadata(path).and_then(|meta| meta.modified()).ok()?;
+        if saved >= started {
+            return None;
+        }
+        let preset = Preset::load(path).ok()?;
+        Some(Self { preset, saved })
+    }
+
+    /// Minutes between the snapshot being written and now
+    #[must_use]
+    pub fn age_minutes(&self) -> u64 {
+        self.saved
+            .elapsed()
+            .map_or(0, |elapsed| elapsed.as_secs() / 60)
+    }
+}
+
+/// Write `preset` to `path` through a temporary file, replacing any old
+/// snapshot in one step
+///
+/// # Errors
+/// [`PresetError::Io`] if the file or its directory can't be written.
+pub fn write_snapshot(preset: &Preset, path: &Path) -> Result<(), PresetError> {
+    let partial = path.with_extension("partial");
+    preset.save(&partial)?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+/// Remove the snapshot at `path` (the session ended cleanly)
+pub fn clear_snapshot(path: &Path) {
+    // Nothing to do if it was never written
+    let _ = fs::remove_file(path);
+}
+
+/// Decides when the editor saves a snapshot
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::autosave::{AutosaveTimer, AUTOSAVE_INTERVAL_S};
+///
+/// let values = || vec![("gain".to_string(), 0.5)];
+/// let mut timer = AutosaveTimer::new();
+/// assert!(timer.take_changes(0.0, values).is_some());
+/// assert!(timer.take_changes(1.0, values).is_none()); // Too soon
+/// assert!(timer.take_changes(AUTOSAVE_INTERVAL_S, values).is_none()); // Unchanged
+/// ```
+#[derive(Debug, Default)]
+pub struct AutosaveTimer {
+    /// Editor time of the last check, in seconds
+    checked_s: Option<f64>,
+
+    /// Values in the last snapshot
+    saved: Vec<(String, f32)>,
+}
+
+impl AutosaveTimer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The values to save, if a check is due at `now_s` and they changed
+    /// since the last snapshot
+    ///
+    /// `values` is only called when a check is due.
+    pub fn take_changes(
+        &mut self,
+        now_s: f64,
+        values: impl FnOnce() -> Vec<(String, f32)>,
+    ) -> Option<Vec<(String, f32)>> {
+        if self
+            .checked_s
+            .is_some_and(|checked_s| now_s - checked_s < AUTOSAVE_INTERVAL_S)
+        {
+            return None;
+        }
+        self.checked_s = Some(now_s);
+
+        let values = values();
+        if values == self.saved {
+            return None;
+        }
+        self.saved.clone_from(&values);
+        Some(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presets::PresetInfo;
+    use std::time::Duration;
+
+    fn snapshot(gain: f32) -> Preset {
+        Preset {
+            info: PresetInfo {
+                name: SNAPSHOT_NAME.to_string(),
+                ..PresetInfo::default()
+            },
+            values: vec![("gain".to_string(), gain)],
+        }
+    }
+
+    #[test]
+    fn test_snapshot_from_earlier_session_found() {
+        let dir = std::env::temp_dir().join(format!("nt-autosave-{}", std::process::id()));
+        let path = dir.join("autosave.ntpreset");
+
+        write_snapshot(&snapshot(0.25), &path).unwrap();
+        write_snapshot(&snapshot(0.75), &path).unwrap();
+        assert!(!path.with_extension("partial").exists());
+
+        let later = SystemTime::now() + Duration::from_mins(1);
+        let found = Snapshot::find(&path, later).unwrap();
+        assert_eq!(found.preset, snapshot(0.75));
+
+        // Written during this session: not offered
+        let earlier = SystemTime::now() - Duration::from_mins(1);
+        assert!(Snapshot::find(&path, earlier).is_none());
+
+        clear_snapshot(&path);
+        assert!(Snapshot::find(&path, later).is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_offered_only_once_its_writer_is_gone() {
+        let dir = std::env::temp_dir().join(format!("nt-autosave-claim-{}", std::process::id()));
+        let first = AutosaveSession::with_dir(dir.clone());
+        let id = first.claim(0).unwrap();
+        assert_eq!(first.claim(0).unwrap(), id, "Claiming again keeps the id");
+        let path = first.path().unwrap();
+        write_snapshot(&snapshot(0.5), &path).unwrap();
+        let an_hour_ago = SystemTime::now() - Duration::from_hours(1);
+        File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(an_hour_ago))
+            .unwrap();
+
+        // A copy of the running instance gets its own file
+        let copy = AutosaveSession::with_dir(dir.clone());
+        assert_ne!(copy.claim(id).unwrap(), id);
+        assert!(copy.earlier_snapshot().is_none());
+        copy.end();
+
+        // The first instance crashes (never ends): its id is free again
+        drop(first);
+        let reopened = AutosaveSession::with_dir(dir.clone());
+        assert_eq!(reopened.claim(id).unwrap(), id);
+        let found = reopened.earlier_snapshot().unwrap();
+        assert_eq!(found.preset, snapshot(0.5));
+
+        reopened.mark_written();
+        reopened.end();
+        assert!(!path.exists());
+        assert!(!lock_path(&path).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_timer_saves_changes_once_per_interval() {
+        let mut timer = AutosaveTimer::new();
+        let gain = |value: f32| move || vec![("gain".to_string(), value)];
+
+        assert_eq!(timer.take_changes(0.0, gain(0.5)), Some(gain(0.5)()));
+        assert_eq!(timer.take_changes(10.0, gain(0.6)), None, "Not due yet");
+        assert_eq!(timer.take_changes(30.0, gain(0.6)), Some(gain(0.6)()));
+        assert_eq!(timer.take_changes(60.0, gain(0.6)), None, "Unchanged");
+        assert_eq!(timer.take_changes(70.0, gain(0.7)), None);
+        assert_eq!(timer.take_changes(90.0, gain(0.7)), Some(gain(0.7)()));
+    }
+}
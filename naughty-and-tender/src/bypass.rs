@@ -0,0 +1,182 @@
+//! Host bypass for Naughty and Tender
+//!
+//! The bypass parameter is linked to the host's bypass switch. Engaging it
+//! releases every voice and fades the synth out over a few milliseconds instead
+//! of cutting it mid-cycle; once the fade is silent, the voices and the master
+//! effects are flushed, so no old delay or phaser tail comes back when the
+//! bypass is released. Releasing it fades the synth back in from that clean
+//! state.
+//!
+//! While bypassed, the main input (if the host connects one) passes straight
+//! through. The dry path has no processing and the plugin reports no latency,
+//! so the bypassed signal lines up with the rest of the session.
+//!
+//! # References
+//! - nih-plug `BoolParam::make_bypass`: VST3 `kIsBypass` / CLAP `bypass` flag
+//! - Linear fade between the processed and dry signals (correlated signals,
+//!   so no equal-power bump)
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+/// Length of the fade in and out of bypass, in milliseconds
+pub const BYPASS_FADE_MS: f32 = 10.0;
+
+/// Fade between the synth's output and the dry input around a host bypass
+///
+/// # Real-time Safety
+/// - Fixed state, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::bypass::BypassFade;
+///
+/// let mut bypass = BypassFade::new(48000.0);
+/// assert!(bypass.set_bypassed(true)); // Just engaged: release the voices
+///
+/// while !bypass.is_silent() {
+///     bypass.next_gain();
+/// }
+/// assert!(bypass.take_silence()); // Flush voices and effect tails once
+/// assert!(!bypass.take_silence());
+/// ```
+#[derive(Debug, Clone)]
+pub struct BypassFade {
+    /// Whether the host's bypass is on
+    bypassed: bool,
+
+    /// Gain of the synth's output (1.0 = active, 0.0 = bypassed)
+    gain: f32,
+
+    /// Gain change per sample while fading
+    step: f32,
+
+    /// Set when the fade-out reaches silence, until taken
+    silent: bool,
+}
+
+impl BypassFade {
+    /// Create an active (not bypassed) fade
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            bypassed: false,
+            gain: 1.0,
+            step: 1000.0 / (BYPASS_FADE_MS * sample_rate),
+            silent: false,
+        }
+    }
+
+    /// Follow the bypass parameter (once per block)
+    ///
+    /// Returns true when the bypass has just been engaged, for releasing the
+    /// voices.
+    pub fn set_bypassed(&mut self, bypassed: bool) -> bool {
+        let engaged = bypassed && !self.bypassed;
+        self.bypassed = bypassed;
+        engaged
+    }
+
+    /// Whether the host's bypass is on
+    #[must_use]
+    pub fn is_bypassed(&self) -> bool {
+        self.bypassed
+    }
+
+    /// Whether the synth is fully faded out (nothing to process)
+    #[must_use]
+    pub fn is_silent(&self) -> bool {
+        self.bypassed && self.gain <= 0.0
+    }
+
+    /// Advance one sample, returning the gain of the synth's output
+    ///
+    /// The dry input takes the rest (`1.0 - gain`).
+    #[inline]
+    pub fn next_gain(&mut self) -> f32 {
+        if self.bypassed {
+            if self.gain > 0.0 {
+                self.gain = (self.gain - self.step).max(0.0);
+                self.silent = self.gain <= 0.0;
+            }
+        } else if self.gain < 1.0 {
+            self.gain = (self.gain + self.step).min(1.0);
+        }
+        self.gain
+    }
+
+    /// Returns true once each time the fade-out reaches silence (to flush state)
+    pub fn take_silence(&mut self) -> bool {
+        std::mem::take(&mut self.silent)
+    }
+
+    /// Jump to the end of any fade in progress
+    pub fn reset(&mut self) {
+        self.gain = if self.bypassed { 0.0 } else { 1.0 };
+        self.silent = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 1000.0;
+
+    #[test]
+    #[allow(clippy::float_cmp)] // Silence is exactly zero
+    fn test_fades_out_then_flushes_once() {
+        let mut bypass = BypassFade::new(SAMPLE_RATE);
+        assert!(bypass.set_bypassed(true));
+        assert!(!bypass.set_bypassed(true), "Only the first block engages");
+
+        let gains: Vec<f32> = (0..12).map(|_| bypass.next_gain()).collect();
+        assert!(gains.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert!(
+            gains[0] > 0.8,
+            "Fade should start near full level: {gains:?}"
+        );
+        assert!(gains[8] > 0.0);
+        assert_eq!(
+            gains[10], 0.0,
+            "10 ms at 1 kHz is about 10 samples: {gains:?}"
+        );
+
+        assert!(bypass.is_silent());
+        assert!(bypass.take_silence());
+        assert!(!bypass.take_silence());
+        assert_eq!(bypass.next_gain(), 0.0);
+        assert!(
+            !bypass.take_silence(),
+            "Staying bypassed doesn't flush again"
+        );
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // Silence is exactly zero
+    fn test_release_fades_back_in() {
+        let mut bypass = BypassFade::new(SAMPLE_RATE);
+        bypass.set_bypassed(true);
+        bypass.reset();
+        assert_eq!(bypass.next_gain(), 0.0);
+
+        assert!(!bypass.set_bypassed(false));
+        let gains: Vec<f32> = (0..10).map(|_| bypass.next_gain()).collect();
+        assert!(gains[0] > 0.0 && gains[0] < 0.2);
+        assert!((gains[9] - 1.0).abs() < 1e-6, "{gains:?}");
+    }
+
+    #[test]
+    fn test_toggling_mid_fade_turns_around_smoothly() {
+        let mut bypass = BypassFade::new(SAMPLE_RATE);
+        bypass.set_bypassed(true);
+        for _ in 0..5 {
+            bypass.next_gain();
+        }
+        let halfway = bypass.next_gain();
+
+        bypass.set_bypassed(false);
+        let next = bypass.next_gain();
+        assert!((next - halfway - 0.1).abs() < 1e-6);
+        assert!(!bypass.take_silence(), "Never reached silence");
+    }
+}
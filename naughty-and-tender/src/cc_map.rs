@@ -0,0 +1,890 @@
+//! MIDI CC mapping for Naughty and Tender
+//!
+//! Any parameter can be bound to a MIDI controller, either directly or by "MIDI
+//! learn" (arm a parameter, then move a controller). A controller is a plain
+//! 7-bit CC, a 14-bit CC pair (MSB on CC 0 - 31, LSB 32 above it), or an NRPN,
+//! whose 14-bit values give cutoff and other sweep-sensitive parameters 16384
+//! steps instead of 128.
+//!
+//! Plugins can't set their own parameters from the audio thread, so the audio
+//! thread decodes incoming CCs with a [`ControllerDecoder`] and only posts the
+//! latest value of each controller to a [`CcInbox`]. The editor drains the inbox
+//! every frame and sets the mapped parameters through the host, which also lets
+//! the host record the moves as automation. The voices slew cutoff and tuning
+//! sample by sample towards each new value (see [`crate::slew`]), so a slow
+//! 14-bit sweep comes out as a smooth glide rather than frame-rate steps.
+//!
+//! Parameters are identified by their position in the editor's parameter list;
+//! the editor turns those into parameter IDs when saving the mappings.
+//!
+//! A mapping can use soft take-over: when the knob and the parameter disagree
+//! (after a preset load, automation, or a mouse edit), moves are ignored until
+//! the knob reaches the parameter's value or sweeps past it, so the parameter
+//! never jumps.
+//!
+//! # References
+//! - MIDI 1.0 Control Change messages (CC 0 - 127), 14-bit controller pairs
+//!   ("when an MSB is received, the receiver should set its concept of the LSB
+//!   to zero")
+//! - MIDI 1.0 NRPN: CC 99/98 select, CC 6/38 data entry, CC 96/97 increment
+//! - Soft take-over / "pickup" mode in hardware mixers and DAW controller setups
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Number of MIDI continuous controllers
+pub const NUM_CCS: usize = 128;
+
+/// Controllers with a 14-bit partner (MSB on 0 - 31, LSB on 32 - 63)
+pub const NUM_PAIRED_CCS: usize = 32;
+
+/// Number of NRPN parameters
+pub const NUM_NRPNS: usize = 16384;
+
+/// Number of controllers: plain CCs, then 14-bit pairs, then NRPNs
+pub const NUM_CONTROLLERS: usize = NUM_CCS + NUM_PAIRED_CCS + NUM_NRPNS;
+
+/// Largest 14-bit value
+const MAX_14BIT: u16 = 0x3FFF;
+
+/// Number of MIDI channels
+const NUM_CHANNELS: usize = 16;
+
+/// Data entry MSB
+const DATA_ENTRY_MSB: u8 = 6;
+/// Data entry LSB
+const DATA_ENTRY_LSB: u8 = 38;
+/// Data increment
+const DATA_INCREMENT: u8 = 96;
+/// Data decrement
+const DATA_DECREMENT: u8 = 97;
+/// NRPN parameter number LSB
+const NRPN_LSB: u8 = 98;
+/// NRPN parameter number MSB
+const NRPN_MSB: u8 = 99;
+/// RPN parameter number LSB
+const RPN_LSB: u8 = 100;
+/// RPN parameter number MSB
+const RPN_MSB: u8 = 101;
+
+/// Marks an inbox slot as holding a value not yet taken
+const PENDING: u64 = 1 << 32;
+
+/// How close the knob has to be to a parameter to pick it up (one controller step)
+pub const PICKUP_TOLERANCE: f32 = 1.0 / 127.0;
+
+/// A MIDI controller a parameter can follow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Controller {
+    /// 7-bit control change (0 - 127)
+    Cc(u8),
+    /// 14-bit pair, by its MSB controller (0 - 31)
+    Cc14(u8),
+    /// 14-bit non-registered parameter (0 - 16383)
+    Nrpn(u16),
+}
+
+impl Controller {
+    /// Position among every controller (also how mappings are saved)
+    #[must_use]
+    pub fn index(self) -> usize {
+        match self {
+            Self::Cc(cc) => usize::from(cc),
+            Self::Cc14(msb) => NUM_CCS + usize::from(msb),
+            Self::Nrpn(number) => NUM_CCS + NUM_PAIRED_CCS + usize::from(number),
+        }
+    }
+
+    /// Controller at a position (`None` past the last NRPN)
+    #[must_use]
+    pub fn from_index(index: usize) -> Option<Self> {
+        let nrpn = index.checked_sub(NUM_CCS + NUM_PAIRED_CCS);
+        if let Some(number) = nrpn {
+            return u16::try_from(number)
+                .ok()
+                .filter(|&number| usize::from(number) < NUM_NRPNS)
+                .map(Self::Nrpn);
+        }
+        let cc = u8::try_from(index).ok()?;
+        Some(match cc.checked_sub(128) {
+            Some(msb) => Self::Cc14(msb),
+            None => Self::Cc(cc),
+        })
+    }
+}
+
+impl fmt::Display for Controller {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cc(cc) => write!(f, "CC {cc}"),
+            Self::Cc14(msb) => write!(f, "CC {msb}/{} (14-bit)", msb + 32),
+            Self::Nrpn(number) => write!(f, "NRPN {number}"),
+        }
+    }
+}
+
+/// Which parameter numbers CC 6, 38, 96 and 97 are editing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DataTarget {
+    /// Nothing selected: they're plain controllers
+    None,
+    /// A registered parameter (pitch bend range, tuning...), not mappable
+    Rpn,
+    /// A non-registered parameter
+    Nrpn(u16),
+}
+
+/// Running controller state of one MIDI channel
+#[derive(Debug, Clone, Copy)]
+struct ChannelState {
+    /// Latest MSB of each 14-bit pair
+    msb: [u8; NUM_PAIRED_CCS],
+
+    /// Latest LSB of each 14-bit pair (reset by its MSB)
+    lsb: [u8; NUM_PAIRED_CCS],
+
+    /// Pairs that have sent an LSB, so are worth reporting at 14 bits
+    has_lsb: [bool; NUM_PAIRED_CCS],
+
+    /// Parameter number being assembled from CC 99/98 or 101/100
+    number: u16,
+
+    /// What data entry edits
+    target: DataTarget,
+
+    /// NRPN data value (14-bit)
+    data: u16,
+}
+
+impl ChannelState {
+    /// Point data entry at the parameter number assembled so far
+    ///
+    /// Number 16383 (127/127) is the "null" parameter, which deselects.
+    fn select(&mut self, non_registered: bool) {
+        self.target = match (self.number, non_registered) {
+            (MAX_14BIT, _) => DataTarget::None,
+            (number, true) => DataTarget::Nrpn(number),
+            (_, false) => DataTarget::Rpn,
+        };
+        self.data = 0;
+    }
+}
+
+impl Default for ChannelState {
+    fn default() -> Self {
+        Self {
+            msb: [0; NUM_PAIRED_CCS],
+            lsb: [0; NUM_PAIRED_CCS],
+            has_lsb: [false; NUM_PAIRED_CCS],
+            number: 0,
+            target: DataTarget::None,
+            data: 0,
+        }
+    }
+}
+
+/// Turns raw control changes into controller values (audio thread)
+///
+/// Every CC is reported as a plain 7-bit controller, so existing mappings keep
+/// working. CCs 0 - 63 are also reported as their 14-bit pair once the pair has
+/// sent an LSB: an MSB resets the LSB to zero, and an LSB on its own refines
+/// the value. Pairs are tracked per channel, so pairs on different channels, or
+/// several pairs interleaved, don't disturb each other.
+///
+/// While an NRPN is selected (CC 99/98), data entry (CC 6/38) and increment or
+/// decrement (CC 96/97) go to it instead; while an RPN is selected they're
+/// swallowed, since RPNs belong to the host and the synth's own setup.
+///
+/// # Real-time Safety
+/// - Fixed-size state, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::cc_map::{Controller, ControllerDecoder};
+///
+/// let mut decoder = ControllerDecoder::new();
+/// let [plain, fine] = decoder.decode(0, 1, 64); // Mod wheel MSB
+/// assert_eq!(plain, Some((Controller::Cc(1), 64.0 / 127.0)));
+/// assert_eq!(fine, None); // No LSB seen yet
+///
+/// let [_, fine] = decoder.decode(0, 33, 64); // Mod wheel LSB
+/// assert_eq!(fine.map(|(controller, _)| controller), Some(Controller::Cc14(1)));
+/// ```
+pub struct ControllerDecoder {
+    channels: [ChannelState; NUM_CHANNELS],
+}
+
+impl Default for ControllerDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ControllerDecoder {
+    /// Create a decoder with every pair at zero and nothing selected
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            channels: [ChannelState::default(); NUM_CHANNELS],
+        }
+    }
+
+    /// Decode one control change (value 0 - 127)
+    ///
+    /// Returns up to two (controller, normalized value) pairs: the plain CC,
+    /// and its 14-bit pair or NRPN.
+    pub fn decode(&mut self, channel: u8, cc: u8, value: u8) -> [Option<(Controller, f32)>; 2] {
+        let state = &mut self.channels[usize::from(channel) % NUM_CHANNELS];
+        let value = value & 0x7F;
+        let plain = Some((Controller::Cc(cc), f32::from(value) / 127.0));
+
+        match (cc, state.target) {
+            (NRPN_MSB | RPN_MSB, _) => {
+                state.number = u16::from(value) << 7 | (state.number & 0x7F);
+                state.select(cc == NRPN_MSB);
+                [None, None]
+            }
+            (NRPN_LSB | RPN_LSB, _) => {
+                state.number = (state.number & !0x7F) | u16::from(value);
+                state.select(cc == NRPN_LSB);
+                [None, None]
+            }
+            (
+                DATA_ENTRY_MSB | DATA_ENTRY_LSB | DATA_INCREMENT | DATA_DECREMENT,
+                DataTarget::Rpn,
+            ) => [None, None],
+            (DATA_ENTRY_MSB, DataTarget::Nrpn(number)) => {
+                state.data = u16::from(value) << 7;
+                [None, Some(nrpn_value(number, state.data))]
+            }
+            (DATA_ENTRY_LSB, DataTarget::Nrpn(number)) => {
+                state.data = (state.data & !0x7F) | u16::from(value);
+                [None, Some(nrpn_value(number, state.data))]
+            }
+            (DATA_INCREMENT, DataTarget::Nrpn(number)) => {
+                state.data = (state.data + 1).min(MAX_14BIT);
+                [None, Some(nrpn_value(number, state.data))]
+            }
+            (DATA_DECREMENT, DataTarget::Nrpn(number)) => {
+                state.data = state.data.saturating_sub(1);
+                [None, Some(nrpn_value(number, state.data))]
+            }
+            (0..=31, _) => {
+                let pair = usize::from(cc);
+                state.msb[pair] = value;
+                state.lsb[pair] = 0;
+                let fine = state.has_lsb[pair].then(|| pair_value(state, pair));
+                [plain, fine]
+            }
+            (32..=63, _) => {
+                let pair = usize::from(cc - 32);
+                state.lsb[pair] = value;
+                state.has_lsb[pair] = true;
+                [plain, Some(pair_value(state, pair))]
+            }
+            _ => [plain, None],
+        }
+    }
+}
+
+/// Value of a 14-bit pair
+fn pair_value(state: &ChannelState, pair: usize) -> (Controller, f32) {
+    let value = u16::from(state.msb[pair]) << 7 | u16::from(state.lsb[pair]);
+    #[allow(clippy::cast_possible_truncation)] // pair < 32
+    let controller = Controller::Cc14(pair as u8);
+    (controller, f32::from(value) / f32::from(MAX_14BIT))
+}
+
+/// Value of an NRPN
+fn nrpn_value(number: u16, data: u16) -> (Controller, f32) {
+    (
+        Controller::Nrpn(number),
+        f32::from(data) / f32::from(MAX_14BIT),
+    )
+}
+
+/// Latest value of every controller, posted by the audio thread
+///
+/// Only the last value per controller is kept: the editor wants where the knob
+/// is now, not every step it took to get there.
+///
+/// # Real-time Safety
+/// - Atomic slots allocated once at construction, `post` is a single relaxed store
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::cc_map::{CcInbox, Controller};
+///
+/// let inbox = CcInbox::new();
+/// inbox.post(Controller::Cc(74), 0.25);
+/// inbox.post(Controller::Cc(74), 0.5);
+///
+/// assert_eq!(
+///     inbox.drain().collect::<Vec<_>>(),
+///     vec![(Controller::Cc(74), 0.5)]
+/// );
+/// assert_eq!(inbox.drain().count(), 0);
+/// ```
+pub struct CcInbox {
+    /// Value bits (`f32`) plus the pending flag, per controller
+    slots: Box<[AtomicU64]>,
+}
+
+impl Default for CcInbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CcInbox {
+    /// Create an empty inbox
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            slots: (0..NUM_CONTROLLERS).map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+
+    /// Post a controller value (0.0 - 1.0) from the audio thread
+    pub fn post(&self, controller: Controller, value: f32) {
+        if let Some(slot) = self.slots.get(controller.index()) {
+            slot.store(PENDING | u64::from(value.to_bits()), Ordering::Relaxed);
+        }
+    }
+
+    /// Take every value posted since the last drain, in controller order
+    pub fn drain(&self) -> impl Iterator<Item = (Controller, f32)> + '_ {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            // Most slots are idle; only swap the ones holding a value
+            if slot.load(Ordering::Relaxed) & PENDING == 0 {
+                return None;
+            }
+            let packed = slot.swap(0, Ordering::Relaxed);
+            #[allow(clippy::cast_possible_truncation)] // Low 32 bits are the value
+            let value = f32::from_bits(packed as u32);
+            (packed & PENDING != 0)
+                .then(|| Controller::from_index(index))
+                .flatten()
+                .map(|controller| (controller, value))
+        })
+    }
+}
+
+/// One controller-to-parameter binding
+#[derive(Debug, Clone, Copy)]
+struct Binding {
+    controller: Controller,
+    param: usize,
+
+    /// Whether moves wait for the knob to reach the parameter
+    soft_takeover: bool,
+
+    /// The controller's latest value (normalized), once it has moved
+    last_value: Option<f32>,
+}
+
+/// Controller-to-parameter bindings, soft take-over and MIDI learn state
+///
+/// Each controller drives at most one parameter and each parameter follows at
+/// most one controller; binding replaces any previous binding on either side.
+/// Soft take-over is set per binding and starts off.
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::cc_map::{CcMap, Controller};
+///
+/// let mut map = CcMap::new();
+/// map.start_learn(3);
+///
+/// // The first controller moved while learning is bound
+/// assert!(map.learn([Controller::Cc(74)]));
+/// assert_eq!(map.cc_for(3), Some(Controller::Cc(74)));
+/// assert_eq!(map.handle_cc(Controller::Cc(74)), Some(3));
+/// ```
+pub struct CcMap {
+    bindings: Vec<Binding>,
+
+    /// Parameter waiting for a controller to move
+    learning: Option<usize>,
+}
+
+impl Default for CcMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CcMap {
+    /// Create a map with no bindings
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            bindings: Vec::new(),
+            learning: None,
+        }
+    }
+
+    /// Bind a controller to a parameter (soft take-over off)
+    pub fn bind(&mut self, controller: Controller, param: usize) {
+        self.bindings
+            .retain(|binding| binding.controller != controller && binding.param != param);
+        let position = self
+            .bindings
+            .partition_point(|binding| binding.controller.index() < controller.index());
+        self.bindings.insert(
+            position,
+            Binding {
+                controller,
+                param,
+                soft_takeover: false,
+                last_value: None,
+            },
+        );
+    }
+
+    /// Remove a parameter's binding, if any
+    pub fn unbind(&mut self, param: usize) {
+        self.bindings.retain(|binding| binding.param != param);
+    }
+
+    /// Remove every binding
+    pub fn clear(&mut self) {
+        self.bindings.clear();
+        self.learning = None;
+    }
+
+    /// Turn soft take-over on or off for a parameter's controller
+    pub fn set_soft_takeover(&mut self, param: usize, enabled: bool) {
+        if let Some(binding) = self.binding_for_param(param) {
+            binding.soft_takeover = enabled;
+        }
+    }
+
+    /// Whether a parameter's controller uses soft take-over
+    #[must_use]
+    pub fn soft_takeover(&self, param: usize) -> bool {
+        self.bindings
+            .iter()
+            .any(|binding| binding.param == param && binding.soft_takeover)
+    }
+
+    /// Decide whether a controller move should set its parameter
+    ///
+    /// `value` is the controller's new position and `current` the parameter's
+    /// value, both normalized. For stepped parameters, pass `value` snapped to
+    /// a step so the two can match exactly.
+    ///
+    /// Without soft take-over every move goes through. With it, a move goes
+    /// through once the knob is at the parameter's value, has just left it (the
+    /// knob is in control), or has swept past it.
+    pub fn take_over(&mut self, controller: Controller, value: f32, current: f32) -> bool {
+        let Some(binding) = self
+            .bindings
+            .iter_mut()
+            .find(|binding| binding.controller == controller)
+        else {
+            return false;
+        };
+        let previous = binding.last_value.replace(value);
+        if !binding.soft_takeover {
+            return true;
+        }
+
+        let near = |position: f32| (position - current).abs() <= PICKUP_TOLERANCE;
+        near(value)
+            || previous
+                .is_some_and(|previous| near(previous) || (previous < current) != (value < current))
+    }
+
+    /// Knob position of a parameter's controller while soft take-over is
+    /// waiting for it to reach `current` (the parameter's normalized value)
+    #[must_use]
+    pub fn pickup_pending(&self, param: usize, current: f32) -> Option<f32> {
+        let binding = self
+            .bindings
+            .iter()
+            .find(|binding| binding.param == param && binding.soft_takeover)?;
+        binding
+            .last_value
+            .filter(|value| (value - current).abs() > PICKUP_TOLERANCE)
+    }
+
+    /// Controller bound to a parameter
+    #[must_use]
+    pub fn cc_for(&self, param: usize) -> Option<Controller> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.param == param)
+            .map(|binding| binding.controller)
+    }
+
+    /// Parameter bound to a controller
+    #[must_use]
+    pub fn param_for(&self, controller: Controller) -> Option<usize> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.controller == controller)
+            .map(|binding| binding.param)
+    }
+
+    /// Every binding as (controller, parameter) pairs, in controller order
+    pub fn bindings(&self) -> impl Iterator<Item = (Controller, usize)> + '_ {
+        self.bindings
+            .iter()
+            .map(|binding| (binding.controller, binding.param))
+    }
+
+    /// Bind the next controller that moves to a parameter
+    pub fn start_learn(&mut self, param: usize) {
+        self.learning = Some(param);
+    }
+
+    /// Stop waiting for a controller
+    pub fn cancel_learn(&mut self) {
+        self.learning = None;
+    }
+
+    /// Parameter waiting for a controller, if any
+    #[must_use]
+    pub fn learning(&self) -> Option<usize> {
+        self.learning
+    }
+
+    /// While learning, bind the best of the controllers that moved together
+    ///
+    /// An NRPN wins over plain CCs (its selection and data entry CCs are never
+    /// reported), and a 14-bit pair wins when its MSB moved too; an LSB moving
+    /// on its own may be a separate knob that happens to sit 32 above another.
+    /// Returns true when a binding was made.
+    pub fn learn(&mut self, moved: impl IntoIterator<Item = Controller>) -> bool {
+        let Some(param) = self.learning else {
+            return false;
+        };
+
+        let mut first = None;
+        let mut msbs_moved = [false; NUM_PAIRED_CCS];
+        let mut best = None;
+        for controller in moved {
+            first.get_or_insert(controller);
+            match controller {
+                Controller::Nrpn(_) => best = best.or(Some(controller)),
+                Controller::Cc(cc) if usize::from(cc) < NUM_PAIRED_CCS => {
+                    msbs_moved[usize::from(cc)] = true;
+                }
+                Controller::Cc14(msb) if msbs_moved[usize::from(msb)] => {
+                    best = best.or(Some(controller));
+                }
+                Controller::Cc(_) | Controller::Cc14(_) => {}
+            }
+        }
+
+        // Only fall back to the pair's plain CCs when nothing better moved
+        let Some(controller) = best.or(first.filter(|first| !matches!(first, Controller::Cc14(_))))
+        else {
+            return false;
+        };
+        self.learning = None;
+        self.bind(controller, param);
+        true
+    }
+
+    /// Parameter a controller move drives, if it's bound
+    #[must_use]
+    pub fn handle_cc(&self, controller: Controller) -> Option<usize> {
+        self.param_for(controller)
+    }
+
+    fn binding_for_param(&mut self, param: usize) -> Option<&mut Binding> {
+        self.bindings
+            .iter_mut()
+            .find(|binding| binding.param == param)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_all(
+        decoder: &mut ControllerDecoder,
+        messages: &[(u8, u8)],
+    ) -> Vec<(Controller, f32)> {
+        messages
+            .iter()
+            .flat_map(|&(cc, value)| decoder.decode(0, cc, value))
+            .flatten()
+            .collect()
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // 14-bit
+    fn fine_values(events: &[(Controller, f32)], controller: Controller) -> Vec<u16> {
+        events
+            .iter()
+            .filter(|(other, _)| *other == controller)
+            .map(|(_, value)| (value * f32::from(MAX_14BIT)).round() as u16)
+            .collect()
+    }
+
+    #[test]
+    fn test_inbox_keeps_latest_value_per_controller() {
+        let inbox = CcInbox::new();
+        inbox.post(Controller::Cc(1), 0.1);
+        inbox.post(Controller::Nrpn(300), 1.0);
+        inbox.post(Controller::Cc(1), 0.9);
+        inbox.post(Controller::Cc14(1), 0.4);
+
+        assert_eq!(
+            inbox.drain().collect::<Vec<_>>(),
+            vec![
+                (Controller::Cc(1), 0.9),
+                (Controller::Cc14(1), 0.4),
+                (Controller::Nrpn(300), 1.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inbox_posts_zero() {
+        let inbox = CcInbox::new();
+        inbox.post(Controller::Cc(64), 0.0);
+        assert_eq!(
+            inbox.drain().collect::<Vec<_>>(),
+            vec![(Controller::Cc(64), 0.0)]
+        );
+    }
+
+    #[test]
+    fn test_controller_index_round_trips() {
+        for controller in [
+            Controller::Cc(0),
+            Controller::Cc(127),
+            Controller::Cc14(0),
+            Controller::Cc14(31),
+            Controller::Nrpn(0),
+            Controller::Nrpn(16383),
+        ] {
+            assert_eq!(Controller::from_index(controller.index()), Some(controller));
+        }
+        assert_eq!(Controller::from_index(NUM_CONTROLLERS), None);
+
+        // Saved 7-bit mappings keep their numbers
+        assert_eq!(Controller::from_index(74), Some(Controller::Cc(74)));
+    }
+
+    #[test]
+    fn test_14bit_pair_needs_an_lsb() {
+        let mut decoder = ControllerDecoder::new();
+        let events = decode_all(&mut decoder, &[(1, 100)]);
+        assert_eq!(events, vec![(Controller::Cc(1), 100.0 / 127.0)]);
+
+        let events = decode_all(&mut decoder, &[(33, 5)]);
+        assert_eq!(
+            fine_values(&events, Controller::Cc14(1)),
+            vec![100 << 7 | 5]
+        );
+        assert!(events.contains(&(Controller::Cc(33), 5.0 / 127.0)));
+    }
+
+    #[test]
+    fn test_msb_resets_lsb_and_lsb_refines() {
+        let mut decoder = ControllerDecoder::new();
+        let events = decode_all(
+            &mut decoder,
+            &[(7, 10), (39, 120), (39, 121), (7, 11), (39, 3)],
+        );
+        assert_eq!(
+            fine_values(&events, Controller::Cc14(7)),
+            vec![(10 << 7) + 120, (10 << 7) + 121, 11 << 7, (11 << 7) + 3]
+        );
+    }
+
+    #[test]
+    fn test_interleaved_pairs_stay_separate() {
+        let mut decoder = ControllerDecoder::new();
+        // Two knobs sending MSB, MSB, LSB, LSB, then the reverse
+        let events = decode_all(
+            &mut decoder,
+            &[(1, 20), (2, 90), (33, 7), (34, 64), (34, 65), (33, 8)],
+        );
+        assert_eq!(
+            fine_values(&events, Controller::Cc14(1)),
+            vec![20 << 7 | 7, 20 << 7 | 8]
+        );
+        assert_eq!(
+            fine_values(&events, Controller::Cc14(2)),
+            vec![(90 << 7) + 64, (90 << 7) + 65]
+        );
+
+        // The same pair on another channel has its own MSB
+        let [_, fine] = decoder.decode(1, 33, 1);
+        assert_eq!(fine, Some((Controller::Cc14(1), 1.0 / 16383.0)));
+    }
+
+    #[test]
+    fn test_nrpn_data_entry() {
+        let mut decoder = ControllerDecoder::new();
+        let events = decode_all(
+            &mut decoder,
+            &[
+                (99, 2),
+                (98, 44),
+                (6, 64),
+                (38, 1),
+                (96, 0),
+                (97, 0),
+                (97, 0),
+            ],
+        );
+        let nrpn = Controller::Nrpn((2 << 7) + 44);
+        assert_eq!(
+            fine_values(&events, nrpn),
+            vec![64 << 7, 64 << 7 | 1, 64 << 7 | 2, 64 << 7 | 1, 64 << 7]
+        );
+        assert_eq!(
+            events.len(),
+            5,
+            "Selection and data entry CCs aren't reported"
+        );
+    }
+
+    #[test]
+    fn test_rpn_swallows_data_entry() {
+        let mut decoder = ControllerDecoder::new();
+        assert_eq!(
+            decode_all(&mut decoder, &[(101, 0), (100, 0), (6, 2)]),
+            vec![]
+        );
+
+        // Until the null parameter deselects it
+        assert_eq!(
+            decode_all(&mut decoder, &[(101, 127), (100, 127), (6, 127)]),
+            vec![(Controller::Cc(6), 1.0)]
+        );
+
+        // Without a selection, data entry is an ordinary controller
+        let mut decoder = ControllerDecoder::new();
+        assert_eq!(
+            decode_all(&mut decoder, &[(6, 127)]),
+            vec![(Controller::Cc(6), 1.0)]
+        );
+    }
+
+    #[test]
+    fn test_rebinding_replaces_both_sides() {
+        let mut map = CcMap::new();
+        map.bind(Controller::Cc(1), 10);
+        map.bind(Controller::Cc(2), 10);
+        assert_eq!(map.cc_for(10), Some(Controller::Cc(2)));
+        assert_eq!(map.param_for(Controller::Cc(1)), None);
+
+        map.bind(Controller::Cc(2), 20);
+        assert_eq!(map.cc_for(10), None);
+        assert_eq!(
+            map.bindings().collect::<Vec<_>>(),
+            vec![(Controller::Cc(2), 20)]
+        );
+    }
+
+    #[test]
+    fn test_unbound_cc_is_ignored() {
+        let map = CcMap::new();
+        assert_eq!(map.handle_cc(Controller::Cc(5)), None);
+    }
+
+    #[test]
+    fn test_learn_binds_once() {
+        let mut map = CcMap::new();
+        map.start_learn(4);
+        assert!(map.learn([Controller::Cc(21)]));
+        assert_eq!(map.handle_cc(Controller::Cc(21)), Some(4));
+        assert_eq!(map.learning(), None);
+
+        // Other controllers stay unbound
+        assert!(!map.learn([Controller::Cc(22)]));
+        assert_eq!(map.handle_cc(Controller::Cc(22)), None);
+    }
+
+    #[test]
+    fn test_learn_prefers_high_resolution() {
+        let mut map = CcMap::new();
+        map.start_learn(0);
+        assert!(map.learn([Controller::Cc(1), Controller::Cc(33), Controller::Cc14(1)]));
+        assert_eq!(map.cc_for(0), Some(Controller::Cc14(1)));
+
+        // A lone knob on CC 33 is learned as itself
+        map.start_learn(1);
+        assert!(map.learn([Controller::Cc(33), Controller::Cc14(1)]));
+        assert_eq!(map.cc_for(1), Some(Controller::Cc(33)));
+
+        map.start_learn(2);
+        assert!(map.learn([Controller::Cc(7), Controller::Nrpn(500)]));
+        assert_eq!(map.cc_for(2), Some(Controller::Nrpn(500)));
+    }
+
+    #[test]
+    fn test_unbind() {
+        let mut map = CcMap::new();
+        map.bind(Controller::Cc(74), 1);
+        map.unbind(1);
+        assert_eq!(map.param_for(Controller::Cc(74)), None);
+    }
+
+    #[test]
+    fn test_without_soft_takeover_every_move_applies() {
+        let mut map = CcMap::new();
+        map.bind(Controller::Cc(1), 0);
+        assert!(map.take_over(Controller::Cc(1), 0.9, 0.1));
+        assert_eq!(map.pickup_pending(0, 0.1), None);
+    }
+
+    #[test]
+    fn test_soft_takeover_waits_for_knob_to_cross() {
+        let knob = Controller::Cc(1);
+        let mut map = CcMap::new();
+        map.bind(knob, 0);
+        map.set_soft_takeover(0, true);
+        assert!(map.soft_takeover(0));
+
+        // Knob well below the parameter, moving up towards it
+        assert!(!map.take_over(knob, 0.2, 0.5));
+        assert_eq!(map.pickup_pending(0, 0.5), Some(0.2));
+        assert!(!map.take_over(knob, 0.4, 0.5));
+
+        // Sweeping past the value picks it up, then the knob stays in control
+        assert!(map.take_over(knob, 0.55, 0.5));
+        assert!(map.take_over(knob, 0.6, 0.55));
+        assert!(map.take_over(knob, 0.3, 0.6));
+        assert_eq!(map.pickup_pending(0, 0.3), None);
+    }
+
+    #[test]
+    fn test_soft_takeover_drops_after_parameter_moves_elsewhere() {
+        let knob = Controller::Nrpn(1);
+        let mut map = CcMap::new();
+        map.bind(knob, 0);
+        map.set_soft_takeover(0, true);
+        assert!(map.take_over(knob, 0.5, 0.5), "Knob already at the value");
+
+        // Automation moved the parameter up to 0.8 since the last move
+        assert!(!map.take_over(knob, 0.52, 0.8));
+        assert!(
+            map.take_over(knob, 0.8, 0.8),
+            "Landing on the value picks up"
+        );
+    }
+
+    #[test]
+    fn test_rebinding_turns_soft_takeover_off() {
+        let mut map = CcMap::new();
+        map.bind(Controller::Cc(1), 0);
+        map.set_soft_takeover(0, true);
+        map.bind(Controller::Cc(1), 2);
+        assert!(!map.soft_takeover(2));
+
+        // Parameters without a controller can't use it
+        map.set_soft_takeover(5, true);
+        assert!(!map.soft_takeover(5));
+    }
+}
@@ -0,0 +1,160 @@
+//! MIDI input channel filter for Naughty and Tender
+//!
+//! In omni mode the synth responds to every channel; set to a channel, it
+//! ignores events on the others, so several instruments can share one MIDI
+//! stream in a multi-instrument host.
+//!
+//! Note-offs also get through for the notes they started: a note started on a
+//! channel is released by that channel's note-off even if the setting changed
+//! while it was held, so switching channels mid-performance never leaves a note
+//! hanging.
+//!
+//! # References
+//! - MIDI 1.0 specification: channel voice messages, omni on/off modes
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use crate::NUM_NOTES;
+
+/// Number of MIDI channels
+pub const NUM_CHANNELS: u8 = 16;
+
+/// Lets through the events of one MIDI channel, or of every channel
+///
+/// # Real-time Safety
+/// - Fixed-size tables, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::channel_filter::ChannelFilter;
+///
+/// let mut filter = ChannelFilter::new();
+/// filter.set_channel(Some(1)); // Channel 2 (0-based)
+///
+/// assert!(filter.note_on(1, 60));
+/// assert!(!filter.note_on(0, 62));
+/// assert!(filter.note_off(1, 60));
+/// ```
+pub struct ChannelFilter {
+    /// Channel let through, 0-based (`None` = omni)
+    channel: Option<u8>,
+
+    /// Channels (one bit each) with a note-on let through and not yet released
+    held: [u16; NUM_NOTES],
+}
+
+impl Default for ChannelFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChannelFilter {
+    /// Create a filter in omni mode
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            channel: None,
+            held: [0; NUM_NOTES],
+        }
+    }
+
+    /// Set the channel to respond to (0-based, `None` = omni)
+    pub fn set_channel(&mut self, channel: Option<u8>) {
+        self.channel = channel;
+    }
+
+    /// Whether an event on `channel` gets through (events other than notes)
+    #[must_use]
+    pub fn accepts(&self, channel: u8) -> bool {
+        self.channel.is_none_or(|selected| selected == channel)
+    }
+
+    /// Whether a note-on gets through, remembering it for its note-off
+    pub fn note_on(&mut self, channel: u8, note: u8) -> bool {
+        let accepted = self.accepts(channel);
+        if accepted {
+            *self.held(note) |= channel_bit(channel);
+        }
+        accepted
+    }
+
+    /// Whether a note-off gets through: on the selected channel, or for a note
+    /// whose note-on got through
+    pub fn note_off(&mut self, channel: u8, note: u8) -> bool {
+        let held = self.held(note);
+        let bit = channel_bit(channel);
+        let started = *held & bit != 0;
+        *held &= !bit;
+        started || self.accepts(channel)
+    }
+
+    /// Forget every held note
+    pub fn reset(&mut self) {
+        self.held = [0; NUM_NOTES];
+    }
+
+    fn held(&mut self, note: u8) -> &mut u16 {
+        &mut self.held[usize::from(note) % NUM_NOTES]
+    }
+}
+
+/// Bit of a channel in the held-note masks
+fn channel_bit(channel: u8) -> u16 {
+    1 << (channel % NUM_CHANNELS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_omni_accepts_every_channel() {
+        let mut filter = ChannelFilter::new();
+        for channel in 0..NUM_CHANNELS {
+            assert!(filter.accepts(channel));
+            assert!(filter.note_on(channel, 60));
+            assert!(filter.note_off(channel, 60));
+        }
+    }
+
+    #[test]
+    fn test_channel_ignores_other_channels() {
+        let mut filter = ChannelFilter::new();
+        filter.set_channel(Some(9));
+        assert!(filter.accepts(9));
+        assert!(!filter.accepts(0));
+        assert!(!filter.note_on(0, 60));
+        assert!(!filter.note_off(0, 60));
+    }
+
+    #[test]
+    fn test_note_off_follows_note_on_across_channel_change() {
+        let mut filter = ChannelFilter::new();
+        filter.set_channel(Some(0));
+        assert!(filter.note_on(0, 60));
+
+        // Held notes still release on their own channel, once
+        filter.set_channel(Some(1));
+        assert!(filter.note_off(0, 60));
+        assert!(!filter.note_off(0, 60));
+    }
+
+    #[test]
+    fn test_reset_forgets_held_notes() {
+        let mut filter = ChannelFilter::new();
+        filter.set_channel(Some(3));
+        assert!(filter.note_on(3, 60));
+        filter.set_channel(Some(4));
+        filter.reset();
+        assert!(!filter.note_off(3, 60));
+    }
+
+    #[test]
+    fn test_malformed_notes_dont_panic() {
+        let mut filter = ChannelFilter::new();
+        assert!(filter.note_on(0, 200));
+        assert!(filter.note_off(0, 200));
+        assert!(filter.note_off(0, u8::MAX));
+    }
+}
@@ -0,0 +1,295 @@
+//! Chord memory for Naughty and Tender
+//!
+//! In chord mode every incoming key plays a whole chord: the key itself plus a
+//! set of intervals above (or below) it. The intervals come either from the
+//! chord parameters or from a chord learned by holding it on the keyboard.
+//!
+//! Each key remembers exactly which notes it started, so releasing the key
+//! releases its whole chord even if the intervals changed in the meantime. A note
+//! shared by two held chords keeps sounding until the last of them is released.
+//!
+//! # References
+//! - Korg Poly-800 / Polysix chord memory
+//! - Sequential Prophet "chord memory" learn-by-holding workflow
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use crate::key_notes::KeyNotes;
+use crate::NUM_NOTES;
+
+/// Most notes one key can trigger (root plus intervals)
+pub const MAX_CHORD_NOTES: usize = 6;
+
+/// Number of intervals stored on top of the root
+pub const NUM_CHORD_INTERVALS: usize = MAX_CHORD_NOTES - 1;
+
+/// Chord intervals in semitones relative to the root (0 = unused)
+pub type ChordIntervals = [i8; NUM_CHORD_INTERVALS];
+
+/// Notes triggered or released by one key (unused entries are `None`)
+pub type ChordNotes = [Option<u8>; MAX_CHORD_NOTES];
+
+/// Expands keys into chords and tracks their release
+///
+/// # Real-time Safety
+/// - Fixed-size tables, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::chord::ChordMemory;
+///
+/// let mut chord = ChordMemory::new();
+/// chord.set_enabled(true);
+/// chord.set_intervals([4, 7, 0, 0, 0]); // Major triad
+///
+/// let notes: Vec<u8> = chord.note_on(60).into_iter().flatten().collect();
+/// assert_eq!(notes, vec![60, 64, 67]);
+/// ```
+pub struct ChordMemory {
+    /// Expand keys into chords (off = keys play single notes)
+    enabled: bool,
+
+    /// Current chord shape
+    intervals: ChordIntervals,
+
+    /// Notes started by each held key
+    sounding: KeyNotes<MAX_CHORD_NOTES>,
+
+    /// Capture held keys as a new chord instead of expanding them
+    learning: bool,
+
+    /// Keys currently held while learning
+    learn_held: [bool; NUM_NOTES],
+
+    /// Every key pressed since learning began (or since the last capture)
+    learn_pressed: [bool; NUM_NOTES],
+
+    /// Finished capture waiting to be collected
+    learned: Option<ChordIntervals>,
+}
+
+impl Default for ChordMemory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChordMemory {
+    /// Create a chord memory (disabled, no intervals)
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            intervals: [0; NUM_CHORD_INTERVALS],
+            sounding: KeyNotes::new(),
+            learning: false,
+            learn_held: [false; NUM_NOTES],
+            learn_pressed: [false; NUM_NOTES],
+            learned: None,
+        }
+    }
+
+    /// Turn chord expansion on or off (held chords still release correctly)
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Set the chord shape
+    pub fn set_intervals(&mut self, intervals: ChordIntervals) {
+        self.intervals = intervals;
+    }
+
+    /// Current chord shape
+    #[must_use]
+    pub fn intervals(&self) -> ChordIntervals {
+        self.intervals
+    }
+
+    /// Start or stop learning a chord from held keys
+    ///
+    /// While learning, keys play single notes. Once two or more keys have been
+    /// held together and all are released, the shape is available from
+    /// [`ChordMemory::take_learned`].
+    pub fn set_learning(&mut self, learning: bool) {
+        if learning && !self.learning {
+            self.learn_held = [false; NUM_NOTES];
+            self.learn_pressed = [false; NUM_NOTES];
+        }
+        self.learning = learning;
+    }
+
+    /// Whether held keys are being captured
+    #[must_use]
+    pub fn is_learning(&self) -> bool {
+        self.learning
+    }
+
+    /// Collect a finished capture, if any
+    pub fn take_learned(&mut self) -> Option<ChordIntervals> {
+        self.learned.take()
+    }
+
+    /// Handle a key press, returning the notes to start
+    pub fn note_on(&mut self, root: u8) -> ChordNotes {
+        let mut notes = [None; MAX_CHORD_NOTES];
+        notes[0] = Some(root);
+
+        if self.learning {
+            self.learn_held[usize::from(root)] = true;
+            self.learn_pressed[usize::from(root)] = true;
+        } else if self.enabled {
+            let mut count = 1;
+            for interval in self.intervals {
+                if interval == 0 {
+                    continue;
+                }
+                let Ok(note) = u8::try_from(i16::from(root) + i16::from(interval)) else {
+                    continue;
+                };
+                if note < 128 && !notes[..count].contains(&Some(note)) {
+                    notes[count] = Some(note);
+                    count += 1;
+                }
+            }
+        }
+
+        self.sounding.start(root, notes);
+        notes
+    }
+
+    /// Handle a key release, returning the notes to release
+    ///
+    /// Notes still sounding under another held key are left playing.
+    pub fn note_off(&mut self, root: u8) -> ChordNotes {
+        let index = usize::from(root);
+
+        if self.learning && self.learn_held[index] {
+            self.learn_held[index] = false;
+            if !self.learn_held.contains(&true) {
+                self.finish_capture();
+            }
+        }
+
+        self.sounding.release(root)
+    }
+
+    /// Forget every held key and any capture in progress
+    pub fn reset(&mut self) {
+        self.sounding.reset();
+        self.learn_held = [false; NUM_NOTES];
+        self.learn_pressed = [false; NUM_NOTES];
+        self.learned = None;
+    }
+
+    /// Turn the keys pressed during learning into intervals above the lowest
+    fn finish_capture(&mut self) {
+        let mut pressed = (0u8..128).filter(|&note| self.learn_pressed[usize::from(note)]);
+        let Some(lowest) = pressed.next() else {
+            return;
+        };
+
+        let mut intervals = [0; NUM_CHORD_INTERVALS];
+        let mut count = 0;
+        for (slot, note) in intervals.iter_mut().zip(pressed) {
+            // Keys are at most 127 apart, which always fits an i8
+            *slot = i8::try_from(note - lowest).unwrap_or(i8::MAX);
+            count += 1;
+        }
+
+        // A single key isn't a chord: keep waiting
+        if count > 0 {
+            self.learned = Some(intervals);
+        }
+        self.learn_pressed = [false; NUM_NOTES];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notes(chord: ChordNotes) -> Vec<u8> {
+        chord.into_iter().flatten().collect()
+    }
+
+    #[test]
+    fn test_disabled_plays_single_notes() {
+        let mut chord = ChordMemory::new();
+        chord.set_intervals([4, 7, 0, 0, 0]);
+
+        assert_eq!(notes(chord.note_on(60)), vec![60]);
+        assert_eq!(notes(chord.note_off(60)), vec![60]);
+    }
+
+    #[test]
+    fn test_expands_and_releases_whole_chord() {
+        let mut chord = ChordMemory::new();
+        chord.set_enabled(true);
+        chord.set_intervals([3, 7, 10, 0, 0]);
+
+        assert_eq!(notes(chord.note_on(57)), vec![57, 60, 64, 67]);
+
+        // Changing the shape doesn't strand the notes already playing
+        chord.set_intervals([4, 7, 0, 0, 0]);
+        assert_eq!(notes(chord.note_off(57)), vec![57, 60, 64, 67]);
+    }
+
+    #[test]
+    fn test_drops_out_of_range_and_duplicate_notes() {
+        let mut chord = ChordMemory::new();
+        chord.set_enabled(true);
+        chord.set_intervals([12, 12, -12, 0, 0]);
+
+        assert_eq!(notes(chord.note_on(120)), vec![120, 108]);
+        assert_eq!(notes(chord.note_on(5)), vec![5, 17]);
+    }
+
+    #[test]
+    fn test_shared_notes_release_with_last_key() {
+        let mut chord = ChordMemory::new();
+        chord.set_enabled(true);
+        chord.set_intervals([7, 0, 0, 0, 0]);
+
+        chord.note_on(60); // C + G
+        chord.note_on(67); // G + D
+
+        // G is still held by the second key
+        assert_eq!(notes(chord.note_off(60)), vec![60]);
+        assert_eq!(notes(chord.note_off(67)), vec![67, 74]);
+    }
+
+    #[test]
+    fn test_untracked_key_releases_itself() {
+        let mut chord = ChordMemory::new();
+        assert_eq!(notes(chord.note_off(42)), vec![42]);
+    }
+
+    #[test]
+    fn test_learns_held_chord() {
+        let mut chord = ChordMemory::new();
+        chord.set_learning(true);
+
+        // Keys play through while learning
+        assert_eq!(notes(chord.note_on(64)), vec![64]);
+        chord.note_on(60);
+        chord.note_on(67);
+        chord.note_on(71);
+        chord.note_off(60);
+        chord.note_off(64);
+        assert_eq!(chord.take_learned(), None, "Capture waits for every key");
+        chord.note_off(67);
+        chord.note_off(71);
+
+        assert_eq!(chord.take_learned(), Some([4, 7, 11, 0, 0]));
+        assert_eq!(chord.take_learned(), None);
+    }
+
+    #[test]
+    fn test_single_key_is_not_learned() {
+        let mut chord = ChordMemory::new();
+        chord.set_learning(true);
+        chord.note_on(60);
+        chord.note_off(60);
+        assert_eq!(chord.take_learned(), None);
+    }
+}
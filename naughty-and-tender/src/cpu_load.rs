@@ -0,0 +1,219 @@
+//! CPU load meter for Naughty and Tender
+//!
+//! Every `process()` call has a real-time budget: the length of its block in
+//! seconds. The audio thread times each call and divides by that budget, so
+//! 100% means a block takes as long to compute as it lasts to play. The host
+//! and the other plugins share the same budget, so dropouts start well before
+//! that.
+//!
+//! The readout is smoothed so it's steady enough to read while changing the
+//! polyphony or effect settings; the highest single block since the editor's
+//! last read is kept as well, for the spikes the smoothing hides. Times are
+//! wall-clock, so the audio thread being preempted counts as load too.
+//!
+//! # References
+//! - One-pole smoothing in block-sized steps: `a = 1 - e^(-block_seconds / τ)`
+//! - Peak hold between editor reads: `fetch_max` on the `f32` bits, cleared
+//!   with `swap` (as in `metering`)
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+/// Time constant of the smoothed load, in seconds
+pub const CPU_SMOOTHING_S: f32 = 0.3;
+
+/// Load of one block and the smoothed load after it (1.0 = the whole budget)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CpuReading {
+    pub block: f32,
+    pub smoothed: f32,
+}
+
+/// Times blocks against their real-time budget (audio thread)
+///
+/// # Real-time Safety
+/// - Fixed state, no allocations
+///
+/// # Example
+/// ```
+/// use std::time::Instant;
+/// use naughty_and_tender::cpu_load::{CpuLoad, CpuMeter};
+///
+/// let mut meter = CpuMeter::new(48000.0);
+/// let shared = CpuLoad::new();
+///
+/// let started = Instant::now();
+/// // ... render 512 samples ...
+/// shared.publish(meter.measure(started.elapsed(), 512));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CpuMeter {
+    /// Smoothed load so far
+    smoothed: f32,
+
+    /// Sample rate in Hz
+    sample_rate: f32,
+}
+
+impl CpuMeter {
+    /// Create a meter reading no load
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            smoothed: 0.0,
+            sample_rate,
+        }
+    }
+
+    /// Set the sample rate the budgets are worked out at
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+    }
+
+    /// Account for a block of `samples` samples that took `elapsed` to process
+    ///
+    /// Empty blocks have no budget and leave the reading unchanged.
+    pub fn measure(&mut self, elapsed: Duration, samples: usize) -> CpuReading {
+        if samples == 0 {
+            return CpuReading {
+                block: 0.0,
+                smoothed: self.smoothed,
+            };
+        }
+
+        #[allow(clippy::cast_precision_loss)] // Block sizes
+        let budget_s = samples as f32 / self.sample_rate;
+        let block = elapsed.as_secs_f32() / budget_s;
+
+        // Longer blocks move the readout further, so it settles in the same
+        // time whatever the host's buffer size
+        let coeff = 1.0 - (-budget_s / CPU_SMOOTHING_S).exp();
+        self.smoothed += (block - self.smoothed) * coeff;
+
+        CpuReading {
+            block,
+            smoothed: self.smoothed,
+        }
+    }
+
+    /// Forget the load so far
+    pub fn reset(&mut self) {
+        self.smoothed = 0.0;
+    }
+}
+
+/// CPU load shared between the audio thread and the editor
+///
+/// # Real-time Safety
+/// - `publish` only performs relaxed atomic stores and read-modify-writes
+pub struct CpuLoad {
+    /// Latest smoothed load (`f32` bits)
+    smoothed: AtomicU32,
+
+    /// Highest block load since the editor last read (`f32` bits)
+    peak: AtomicU32,
+}
+
+impl Default for CpuLoad {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CpuLoad {
+    /// Create a meter reading no load
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            smoothed: AtomicU32::new(0),
+            peak: AtomicU32::new(0),
+        }
+    }
+
+    /// Publish a block's reading (audio thread)
+    pub fn publish(&self, reading: CpuReading) {
+        self.smoothed
+            .store(reading.smoothed.to_bits(), Ordering::Relaxed);
+        // Non-negative `f32` bit patterns sort the same as the floats
+        self.peak
+            .fetch_max(reading.block.max(0.0).to_bits(), Ordering::Relaxed);
+    }
+
+    /// Smoothed load (1.0 = the whole real-time budget)
+    #[must_use]
+    pub fn load(&self) -> f32 {
+        f32::from_bits(self.smoothed.load(Ordering::Relaxed))
+    }
+
+    /// Highest block load since the last call, then reset (editor thread)
+    pub fn take_peak(&self) -> f32 {
+        f32::from_bits(self.peak.swap(0, Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    #[test]
+    fn test_block_load_is_share_of_budget() {
+        let mut meter = CpuMeter::new(SAMPLE_RATE);
+
+        // 480 samples last 10 ms
+        let reading = meter.measure(Duration::from_micros(2500), 480);
+        assert!((reading.block - 0.25).abs() < 1e-4, "{reading:?}");
+        let reading = meter.measure(Duration::from_millis(15), 480);
+        assert!((reading.block - 1.5).abs() < 1e-4, "{reading:?}");
+    }
+
+    #[test]
+    fn test_smoothing_settles_independent_of_block_size() {
+        // Load after a steady 40% for one smoothing time constant
+        let settle = |block_size: usize| {
+            let mut meter = CpuMeter::new(SAMPLE_RATE);
+            #[allow(clippy::cast_precision_loss)] // Block sizes
+            let elapsed = Duration::from_secs_f32(0.4 * block_size as f32 / SAMPLE_RATE);
+            let blocks = 14400 / block_size;
+            (0..blocks)
+                .map(|_| meter.measure(elapsed, block_size))
+                .last()
+                .unwrap()
+                .smoothed
+        };
+
+        // 1 - 1/e of the way there
+        let expected = 0.4 * (1.0 - (-1.0f32).exp());
+        for block_size in [32, 256, 2400] {
+            let smoothed = settle(block_size);
+            assert!(
+                (smoothed - expected).abs() < 0.005,
+                "{block_size}: {smoothed}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty_block_keeps_reading() {
+        let mut meter = CpuMeter::new(SAMPLE_RATE);
+        let before = meter.measure(Duration::from_millis(5), 480);
+        let empty = meter.measure(Duration::from_millis(1), 0);
+        assert!((empty.smoothed - before.smoothed).abs() < f32::EPSILON);
+        assert!(empty.block.abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_peak_held_until_read() {
+        let shared = CpuLoad::new();
+        for (block, smoothed) in [(0.2, 0.1), (0.9, 0.2), (0.3, 0.25)] {
+            shared.publish(CpuReading { block, smoothed });
+        }
+
+        assert!((shared.load() - 0.25).abs() < f32::EPSILON);
+        assert!((shared.take_peak() - 0.9).abs() < f32::EPSILON);
+        assert!(shared.take_peak().abs() < f32::EPSILON);
+    }
+}
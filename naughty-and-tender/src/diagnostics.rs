@@ -0,0 +1,343 @@
+//! Voice allocation diagnostics and MIDI activity for Naughty and Tender
+//!
+//! The audio thread publishes a snapshot of every voice slot once per block; the
+//! editor reads it back to draw the diagnostics panel. Incoming MIDI is counted
+//! the same way to drive the editor's activity LED and last-note display. Each slot is packed into a
+//! single `AtomicU64`, so the editor never sees a half-written voice and neither
+//! side ever blocks.
+//!
+//! Slot layout (low to high bits):
+//! - 0-7: MIDI note
+//! - 8-9: voice state
+//! - 10-12: envelope stage
+//! - 32-63: envelope level (`f32` bits)
+//!
+//! Voice ages are published separately; a torn age is harmless for display.
+//!
+//! # References
+//! - Lock-free GUI metering: relaxed atomics, last-writer-wins
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+use crate::envelope::EnvelopeState;
+use crate::voice::VoiceState;
+
+/// State of one voice slot at the moment it was published
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VoiceSnapshot {
+    /// MIDI note (last note played, even when idle)
+    pub note: u8,
+    pub state: VoiceState,
+    pub stage: EnvelopeState,
+    /// Notes started since this voice was triggered (0 = newest)
+    pub age: u64,
+    /// Envelope level (0.0 to 1.0)
+    pub level: f32,
+}
+
+impl Default for VoiceSnapshot {
+    fn default() -> Self {
+        Self {
+            note: 0,
+            state: VoiceState::Idle,
+            stage: EnvelopeState::Idle,
+            age: 0,
+            level: 0.0,
+        }
+    }
+}
+
+/// Lock-free voice snapshots shared between the audio thread and the editor
+///
+/// # Real-time Safety
+/// - Slots allocated once at construction
+/// - `publish` only performs relaxed atomic stores
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::diagnostics::{VoiceDiagnostics, VoiceSnapshot};
+///
+/// let diagnostics = VoiceDiagnostics::new(16);
+/// diagnostics.publish([VoiceSnapshot::default()].into_iter(), 0, 0);
+/// assert_eq!(diagnostics.snapshot().len(), 16);
+/// ```
+pub struct VoiceDiagnostics {
+    /// Packed note, state, stage and level per voice
+    slots: Box<[AtomicU64]>,
+
+    /// Age per voice
+    ages: Box<[AtomicU64]>,
+
+    /// Voices stolen since the plugin was loaded
+    steal_count: AtomicU64,
+
+    /// Voices force-released by the stuck-note watchdog since the plugin was loaded
+    stuck_release_count: AtomicU64,
+
+    /// Blocks whose non-finite output was silenced since the plugin was loaded
+    recovery_count: AtomicU64,
+
+    /// Largest per-voice limiter gain reduction in the last block, in dB (`f32` bits)
+    limiter_reduction: AtomicU32,
+}
+
+impl VoiceDiagnostics {
+    /// Create storage for `num_voices` slots (all idle)
+    #[must_use]
+    pub fn new(num_voices: usize) -> Self {
+        Self {
+            slots: (0..num_voices).map(|_| AtomicU64::new(0)).collect(),
+            ages: (0..num_voices).map(|_| AtomicU64::new(0)).collect(),
+            steal_count: AtomicU64::new(0),
+            stuck_release_count: AtomicU64::new(0),
+            recovery_count: AtomicU64::new(0),
+            limiter_reduction: AtomicU32::new(0),
+        }
+    }
+
+    /// Publish the current voice states (audio thread)
+    ///
+    /// Extra snapshots beyond the slot count are ignored.
+    pub fn publish(
+        &self,
+        voices: impl Iterator<Item = VoiceSnapshot>,
+        steal_count: u64,
+        stuck_release_count: u64,
+    ) {
+        for ((slot, age), voice) in self.slots.iter().zip(self.ages.iter()).zip(voices) {
+            slot.store(pack(&voice), Ordering::Relaxed);
+            age.store(voice.age, Ordering::Relaxed);
+        }
+        self.steal_count.store(steal_count, Ordering::Relaxed);
+        self.stuck_release_count
+            .store(stuck_release_count, Ordering::Relaxed);
+    }
+
+    /// Read back every slot (editor thread)
+    #[must_use]
+    pub fn snapshot(&self) -> Vec<VoiceSnapshot> {
+        self.slots
+            .iter()
+            .zip(self.ages.iter())
+            .map(|(slot, age)| VoiceSnapshot {
+                age: age.load(Ordering::Relaxed),
+                ..unpack(slot.load(Ordering::Relaxed))
+            })
+            .collect()
+    }
+
+    /// Voices stolen since the plugin was loaded
+    #[must_use]
+    pub fn steal_count(&self) -> u64 {
+        self.steal_count.load(Ordering::Relaxed)
+    }
+
+    /// Voices force-released by the stuck-note watchdog since the plugin was loaded
+    #[must_use]
+    pub fn stuck_release_count(&self) -> u64 {
+        self.stuck_release_count.load(Ordering::Relaxed)
+    }
+
+    /// Count a block whose NaN or infinite output was silenced (audio thread)
+    pub fn record_recovery(&self) {
+        self.recovery_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Blocks whose NaN or infinite output was silenced since the plugin was loaded
+    #[must_use]
+    pub fn recovery_count(&self) -> u64 {
+        self.recovery_count.load(Ordering::Relaxed)
+    }
+
+    /// Publish the largest per-voice limiter gain reduction in the block, in dB
+    /// (audio thread)
+    pub fn publish_limiter_reduction(&self, reduction_db: f32) {
+        self.limiter_reduction
+            .store(reduction_db.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Largest per-voice limiter gain reduction in the last block, in dB
+    #[must_use]
+    pub fn limiter_reduction_db(&self) -> f32 {
+        f32::from_bits(self.limiter_reduction.load(Ordering::Relaxed))
+    }
+}
+
+/// Incoming MIDI activity shared between the audio thread and the editor
+///
+/// The editor lights its activity LED whenever the event count changes.
+///
+/// # Real-time Safety
+/// - Relaxed atomic stores only
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::diagnostics::MidiActivity;
+///
+/// let activity = MidiActivity::new();
+/// activity.note_on(60, 0.5);
+///
+/// assert_eq!(activity.event_count(), 1);
+/// assert_eq!(activity.last_note().map(|(note, _)| note), Some(60));
+/// ```
+pub struct MidiActivity {
+    /// MIDI events received since the plugin was loaded
+    event_count: AtomicU64,
+
+    /// Last note-on: note in bits 0-7, 7-bit velocity in bits 8-15, bit 16 set once
+    /// a note has arrived
+    last_note: AtomicU32,
+}
+
+impl Default for MidiActivity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MidiActivity {
+    /// Bit marking `last_note` as valid
+    const NOTE_VALID: u32 = 1 << 16;
+
+    /// Create with no events received
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            event_count: AtomicU64::new(0),
+            last_note: AtomicU32::new(0),
+        }
+    }
+
+    /// Record a note-on (audio thread)
+    pub fn note_on(&self, note: u8, velocity: f32) {
+        // MIDI velocity resolution is all the display needs
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Clamped to 0-127
+        let velocity = (velocity.clamp(0.0, 1.0) * 127.0).round() as u32;
+        self.last_note.store(
+            Self::NOTE_VALID | (velocity << 8) | u32::from(note),
+            Ordering::Relaxed,
+        );
+        self.event();
+    }
+
+    /// Record any other MIDI event (audio thread)
+    pub fn event(&self) {
+        self.event_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// MIDI events received since the plugin was loaded
+    #[must_use]
+    pub fn event_count(&self) -> u64 {
+        self.event_count.load(Ordering::Relaxed)
+    }
+
+    /// Last note-on as (note, velocity 0.0 - 1.0), if any has arrived
+    #[must_use]
+    pub fn last_note(&self) -> Option<(u8, f32)> {
+        let packed = self.last_note.load(Ordering::Relaxed);
+        if packed & Self::NOTE_VALID == 0 {
+            return None;
+        }
+
+        #[allow(clippy::cast_possible_truncation)] // Masked to the field width
+        let note = (packed & 0xFF) as u8;
+        #[allow(clippy::cast_precision_loss)] // 7-bit value
+        let velocity = ((packed >> 8) & 0x7F) as f32 / 127.0;
+        Some((note, velocity))
+    }
+}
+
+fn pack(voice: &VoiceSnapshot) -> u64 {
+    let voice_state = match voice.state {
+        VoiceState::Idle => 0,
+        VoiceState::Active => 1,
+        VoiceState::Releasing => 2,
+    };
+    let envelope_stage = match voice.stage {
+        EnvelopeState::Idle => 0,
+        EnvelopeState::Attack => 1,
+        EnvelopeState::Decay => 2,
+        EnvelopeState::Sustain => 3,
+        EnvelopeState::Release => 4,
+    };
+    u64::from(voice.note)
+        | (voice_state << 8)
+        | (envelope_stage << 10)
+        | (u64::from(voice.level.to_bits()) << 32)
+}
+
+fn unpack(packed: u64) -> VoiceSnapshot {
+    let voice_state = match (packed >> 8) & 0b11 {
+        1 => VoiceState::Active,
+        2 => VoiceState::Releasing,
+        _ => VoiceState::Idle,
+    };
+    let envelope_stage = match (packed >> 10) & 0b111 {
+        1 => EnvelopeState::Attack,
+        2 => EnvelopeState::Decay,
+        3 => EnvelopeState::Sustain,
+        4 => EnvelopeState::Release,
+        _ => EnvelopeState::Idle,
+    };
+    #[allow(clippy::cast_possible_truncation)] // Masked to the field width
+    VoiceSnapshot {
+        note: (packed & 0xFF) as u8,
+        state: voice_state,
+        stage: envelope_stage,
+        age: 0,
+        level: f32::from_bits((packed >> 32) as u32),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let diagnostics = VoiceDiagnostics::new(2);
+        let voices = [
+            VoiceSnapshot {
+                note: 64,
+                state: VoiceState::Releasing,
+                stage: EnvelopeState::Release,
+                age: 3,
+                level: 0.25,
+            },
+            VoiceSnapshot {
+                note: 127,
+                state: VoiceState::Active,
+                stage: EnvelopeState::Sustain,
+                age: 0,
+                level: 1.0,
+            },
+        ];
+
+        diagnostics.publish(voices.into_iter(), 5, 2);
+
+        assert_eq!(diagnostics.snapshot(), voices.to_vec());
+        assert_eq!(diagnostics.steal_count(), 5);
+        assert_eq!(diagnostics.stuck_release_count(), 2);
+    }
+
+    #[test]
+    fn test_midi_activity() {
+        let activity = MidiActivity::new();
+        assert_eq!(activity.last_note(), None);
+
+        activity.note_on(64, 1.0);
+        activity.event();
+
+        assert_eq!(activity.event_count(), 2);
+        assert_eq!(activity.last_note(), Some((64, 1.0)));
+    }
+
+    #[test]
+    fn test_extra_voices_are_ignored() {
+        let diagnostics = VoiceDiagnostics::new(1);
+        diagnostics.publish([VoiceSnapshot::default(); 4].into_iter(), 0, 0);
+        assert_eq!(diagnostics.snapshot().len(), 1);
+    }
+}
@@ -4,95 +4,4539 @@
 
 use nih_plug::prelude::*;
 use nih_plug_egui::{create_egui_editor, egui, widgets, EguiState};
-use std::sync::Arc;
+use shared_core::audio_io::DitherMode;
+use shared_core::events::EventQueue;
+use shared_core::tempo::DEFAULT_TEMPO_BPM;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
 
-use crate::params::NaughtyAndTenderParams;
+use crate::additive::NUM_PARTIALS;
+use crate::audio_log::{AudioLog, LogWriter};
+use crate::audition::AuditionPad;
+use crate::autosave::{self, AutosaveSession, AutosaveTimer, Snapshot};
+use crate::cc_map::{CcInbox, CcMap, Controller};
+use crate::cpu_load::CpuLoad;
+use crate::diagnostics::{MidiActivity, VoiceDiagnostics};
+use crate::envelope::EnvelopeState;
+use crate::eq::EqSettings;
+use crate::gesture::{OpenGestures, CONTROLLER_RELEASE_S};
+use crate::metering::{peak_to_dbfs, GainStaging, MeterStage, METER_FLOOR_DB, NUM_STAGES};
+use crate::modulation::{ModDestination, ModMatrix, ModMonitor, MONITORED_LAYERS};
+use crate::morph::{MorphFollower, MorphPair, MorphSlot, MORPH_ID};
+use crate::navigation::Nudge;
+use crate::params::{LayerParams, NaughtyAndTenderParams};
+use crate::pattern::{
+    note_index_at, stretch_note, toggle_note, PatternNotes, PatternPlayhead, PatternPreset,
+    MAX_STEPS, STEPS_PER_BEAT,
+};
+use crate::presets::{self, Favorites, Preset, PresetIndex, PresetInfo};
+use crate::programs::{Program, ProgramInbox, ProgramMap};
+use crate::sampler::SampleSlot;
+use crate::scale::{Scale, ROOT_NAMES};
+use crate::scope::{spectrum_frequency_hz, ScopeCapture, ScopeFormat, SPECTRUM_FLOOR_DB};
+use crate::sequencer::{Euclid, NUM_STEPS};
+use crate::tasks::{FileRequest, FileResult, FileTask};
+use crate::theme::{Theme, ThemeKind};
+use crate::tuner::{AudioTap, Tuner, TunerReading};
+use crate::undo::{diff, UndoHistory};
+use crate::voice::{note_name, VoiceState};
+use crate::width::StereoMonitor;
+use crate::{NaughtyAndTender, NUM_VOICES};
+
+/// Sample rate used to draw filter response curves
+///
+/// The curves only need to be representative, so a fixed rate avoids sharing the
+/// host's sample rate with the GUI thread.
+const DISPLAY_SAMPLE_RATE: f32 = 48000.0;
+
+/// How long the MIDI LED stays lit after an event, in seconds
+const MIDI_LED_HOLD_S: f64 = 0.12;
+
+/// How long the CPU readout keeps showing the heaviest block, in seconds
+const CPU_PEAK_HOLD_S: f64 = 2.0;
+
+/// Smoothed CPU load above which the readout turns to the warning color
+const CPU_WARN_LOAD: f32 = 0.7;
+
+/// How long the tuner keeps showing a pitch after the signal stops, in seconds
+const TUNER_HOLD_S: f64 = 0.5;
+
+/// Deviation the tuner shows as in tune, in cents
+const TUNER_IN_TUNE_CENTS: f32 = 3.0;
+
+/// Frames of level history in the gain-staging sparklines
+const METER_HISTORY_LEN: usize = 180;
+
+/// Loudest level the gain-staging sparklines show, in dBFS (room to see overs)
+const METER_CEILING_DB: f32 = 12.0;
+
+/// Lines the log panel keeps on screen
+const LOG_PANEL_LINES: usize = 200;
+
+/// Keys that move a focused slider
+const SLIDER_KEYS: [(egui::Key, Nudge); 8] = [
+    (egui::Key::ArrowDown, Nudge::Down),
+    (egui::Key::ArrowLeft, Nudge::Down),
+    (egui::Key::ArrowUp, Nudge::Up),
+    (egui::Key::ArrowRight, Nudge::Up),
+    (egui::Key::PageDown, Nudge::PageDown),
+    (egui::Key::PageUp, Nudge::PageUp),
+    (egui::Key::Home, Nudge::Min),
+    (egui::Key::End, Nudge::Max),
+];
+
+/// Keys that move the cursor across a focused grid
+const CURSOR_KEYS: [(egui::Key, Nudge); 4] = [
+    (egui::Key::ArrowLeft, Nudge::Down),
+    (egui::Key::ArrowRight, Nudge::Up),
+    (egui::Key::Home, Nudge::Min),
+    (egui::Key::End, Nudge::Max),
+];
+
+/// Keys that move the value (or row) under a grid's cursor
+const VALUE_KEYS: [(egui::Key, Nudge); 4] = [
+    (egui::Key::ArrowDown, Nudge::Down),
+    (egui::Key::ArrowUp, Nudge::Up),
+    (egui::Key::PageDown, Nudge::PageDown),
+    (egui::Key::PageUp, Nudge::PageUp),
+];
+
+/// Audio-thread state the editor reads (meters) or feeds (inboxes)
+pub(crate) struct EditorLinks {
+    pub(crate) diagnostics: Arc<VoiceDiagnostics>,
+    pub(crate) midi_activity: Arc<MidiActivity>,
+    pub(crate) modulation: Arc<ModMonitor>,
+    pub(crate) pattern_playhead: Arc<PatternPlayhead>,
+    pub(crate) audition_events: Arc<EventQueue>,
+    pub(crate) audio_log: Arc<AudioLog>,
+    pub(crate) autosave: Arc<AutosaveSession>,
+    pub(crate) tuner_tap: Arc<AudioTap>,
+    pub(crate) gain_staging: Arc<GainStaging>,
+    pub(crate) stereo: Arc<StereoMonitor>,
+    pub(crate) cpu_load: Arc<CpuLoad>,
+    pub(crate) cc_inbox: Arc<CcInbox>,
+    pub(crate) program_inbox: Arc<ProgramInbox>,
+    pub(crate) sample_slot: Arc<SampleSlot>,
+}
+
+/// Editor tab pages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Tab {
+    #[default]
+    Oscillators,
+    Filter,
+    Envelopes,
+    Modulation,
+    Fx,
+    Global,
+    Presets,
+}
+
+impl Tab {
+    /// Every tab, in display order
+    const ALL: [Self; 7] = [
+        Self::Oscillators,
+        Self::Filter,
+        Self::Envelopes,
+        Self::Modulation,
+        Self::Fx,
+        Self::Global,
+        Self::Presets,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Oscillators => "Oscillators",
+            Self::Filter => "Filter",
+            Self::Envelopes => "Envelopes",
+            Self::Modulation => "Modulation",
+            Self::Fx => "FX",
+            Self::Global => "Global",
+            Self::Presets => "Presets",
+        }
+    }
+}
+
+/// Which layer the per-layer tabs (oscillator, filter, envelope) show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum LayerPage {
+    #[default]
+    A,
+    B,
+}
+
+/// Editor-only UI state (not saved with the plugin)
+struct EditorUiState {
+    tab: Tab,
+    layer_page: LayerPage,
+
+    /// Theme last applied to the egui context
+    applied_theme: Option<ThemeKind>,
+
+    /// MIDI activity LED in the header
+    midi_led: MidiLed,
+
+    /// CPU load readout in the header
+    cpu: CpuReadout,
+
+    /// Tuner, scope, gain staging and log on the Global tab
+    monitors: MonitorPanels,
+
+    /// Undo history of parameter gestures made in the editor
+    undo: UndoTracker,
+
+    /// MIDI CC bindings (shared with every parameter's context menu)
+    cc: RefCell<CcMapping>,
+
+    /// Voice modulation shown on the sliders it moves
+    modulation: SliderModulation,
+
+    presets: PresetBrowser,
+
+    /// Sampler engine's WAV file
+    sample: SampleLoader,
+
+    /// Morph snapshots and the position last applied
+    morph: PatchMorph,
+
+    /// Piano roll scroll position and the note being stretched
+    pattern: PatternEditor,
+
+    /// Note played from the header while the pad is held
+    audition: AuditionPad,
+
+    /// Periodic snapshots, and the offer to restore one a crashed session left
+    autosave: SessionAutosave,
+
+    /// Every parameter's ID and pointer, in param map order
+    param_list: ParamList,
+}
+
+impl EditorUiState {
+    fn new(
+        params: &NaughtyAndTenderParams,
+        executor: &AsyncExecutor<NaughtyAndTender>,
+        links: &EditorLinks,
+    ) -> Self {
+        // The host's bypass switch isn't part of the sound: presets and undo
+        // leave it alone
+        let param_list: ParamList = params
+            .param_map()
+            .into_iter()
+            .filter(|(id, _, _)| id != "bypass")
+            .map(|(id, param, _)| (id, param))
+            .collect();
+
+        Self {
+            tab: Tab::default(),
+            layer_page: LayerPage::default(),
+            applied_theme: None,
+            midi_led: MidiLed::default(),
+            cpu: CpuReadout::new(links.cpu_load.clone()),
+            monitors: MonitorPanels {
+                tuner: TunerPanel::new(links.tuner_tap.clone()),
+                scope: ScopePanel::new(FileWorker::new(executor.clone()), links.tuner_tap.clone()),
+                gain_staging: GainStagingPanel::new(links.gain_staging.clone()),
+                stereo: links.stereo.clone(),
+                log: LogPanel::new(FileWorker::new(executor.clone()), links.audio_log.clone()),
+            },
+            undo: UndoTracker::new(param_list.clone()),
+            cc: RefCell::new(CcMapping::new(params, param_list.clone())),
+            modulation: SliderModulation::new(params, links.modulation.clone()),
+            presets: PresetBrowser::new(
+                FileWorker::new(executor.clone()),
+                links.program_inbox.clone(),
+                params.program_map(),
+            ),
+            sample: SampleLoader::new(
+                FileWorker::new(executor.clone()),
+                links.sample_slot.clone(),
+                params.sample_path(),
+            ),
+            morph: PatchMorph::new(params),
+            pattern: PatternEditor::new(links.pattern_playhead.clone()),
+            audition: AuditionPad::new(links.audition_events.clone()),
+            autosave: SessionAutosave::new(
+                FileWorker::new(executor.clone()),
+                links.autosave.clone(),
+                params.autosave_id(),
+            ),
+            param_list,
+        }
+    }
+}
+
+/// Every parameter's ID and pointer, in param map order
+///
+/// The pointers stay valid for the editor's lifetime: the params outlive it.
+type ParamList = Arc<[(String, ParamPtr)]>;
+
+/// State of the header's MIDI activity LED
+#[derive(Default)]
+struct MidiLed {
+    /// MIDI event count at the last update
+    event_count: u64,
+
+    /// Editor time until which the LED stays lit
+    lit_until: f64,
+}
+
+/// State of the header's CPU load readout
+struct CpuReadout {
+    load: Arc<CpuLoad>,
+
+    /// Heaviest block load within the hold time
+    peak: f32,
+
+    /// Editor time the peak was taken
+    peak_at: f64,
+}
+
+impl CpuReadout {
+    fn new(load: Arc<CpuLoad>) -> Self {
+        Self {
+            load,
+            peak: 0.0,
+            peak_at: 0.0,
+        }
+    }
+}
+
+/// Live readouts of the audio thread on the Global tab
+struct MonitorPanels {
+    tuner: TunerPanel,
+
+    /// Waveform and spectrum of the tuner tap
+    scope: ScopePanel,
+
+    gain_staging: GainStagingPanel,
+
+    /// Correlation readouts and the mono check
+    stereo: Arc<StereoMonitor>,
+
+    /// Audio-thread diagnostics
+    log: LogPanel,
+}
+
+/// Pitch readout of the audio thread's tuner tap
+struct TunerPanel {
+    tap: Arc<AudioTap>,
+
+    tuner: Tuner,
+
+    /// Last pitch detected
+    reading: Option<TunerReading>,
+
+    /// Editor time the last pitch was detected
+    read_at: f64,
+
+    /// Whether the panel was drawn this frame (the tap only fills while it is)
+    visible: bool,
+}
+
+impl TunerPanel {
+    fn new(tap: Arc<AudioTap>) -> Self {
+        Self {
+            tap,
+            tuner: Tuner::new(),
+            reading: None,
+            read_at: 0.0,
+            visible: false,
+        }
+    }
+}
+
+/// Oscilloscope and spectrum of the tuner tap, which can be frozen and exported
+struct ScopePanel {
+    files: FileWorker,
+
+    tap: Arc<AudioTap>,
+
+    /// Capture on screen
+    capture: Option<ScopeCapture>,
+
+    /// Whether the capture on screen stays instead of following the tap
+    frozen: bool,
+
+    /// Export path typed into the editor
+    path: String,
+
+    /// How a WAV export is rounded to 16 bits
+    dither: DitherMode,
+
+    /// Result of the last export
+    status: String,
+
+    /// Whether the panel was drawn this frame (the tap only fills while it is)
+    visible: bool,
+}
+
+impl ScopePanel {
+    fn new(files: FileWorker, tap: Arc<AudioTap>) -> Self {
+        Self {
+            files,
+            tap,
+            capture: None,
+            frozen: false,
+            path: String::new(),
+            dither: DitherMode::default(),
+            status: String::new(),
+            visible: false,
+        }
+    }
+
+    /// Write the capture on screen to `path` in the background, as PNG for a
+    /// `.png` path, WAV for a `.wav` path and CSV otherwise
+    fn export(&mut self) {
+        let path = PathBuf::from(self.path.trim());
+        let Some(capture) = self.capture.clone() else {
+            return;
+        };
+        if path.as_os_str().is_empty() {
+            return;
+        }
+
+        self.status = format!("Exporting {}...", path.display());
+        self.files.run(FileTask::ExportScope {
+            capture,
+            format: ScopeFormat::from_path(&path).with_dither(self.dither),
+            path,
+        });
+    }
+
+    /// Report finished exports
+    fn poll(&mut self) {
+        while let Some(result) = self.files.next_result() {
+            let FileResult::ScopeExported { path, result } = result else {
+                continue;
+            };
+            self.status = match result {
+                Ok(()) => format!("Exported {}", path.display()),
+                Err(error) => format!("Couldn't export {}: {error}", path.display()),
+            };
+        }
+    }
+}
+
+/// Recent lines of the audio-thread log, optionally appended to a file
+struct LogPanel {
+    files: FileWorker,
+
+    log: Arc<AudioLog>,
+
+    /// Turns records into rate-limited lines
+    writer: LogWriter,
+
+    /// Recent lines, oldest first
+    lines: VecDeque<String>,
+
+    /// Whether new lines are appended to the file at `path`
+    to_file: bool,
+
+    /// Log file path typed into the editor
+    path: String,
+
+    /// Why writing to the file stopped, if it did
+    status: String,
+}
+
+impl LogPanel {
+    fn new(files: FileWorker, log: Arc<AudioLog>) -> Self {
+        Self {
+            files,
+            log,
+            writer: LogWriter::new(),
+            lines: VecDeque::with_capacity(LOG_PANEL_LINES),
+            to_file: false,
+            path: String::new(),
+            status: String::new(),
+        }
+    }
+
+    /// Take in the audio thread's new records, whether or not the panel is
+    /// open, and hand new lines to the file task
+    fn poll(&mut self) {
+        let mut fresh = Vec::new();
+        self.writer.drain(&self.log, |line| fresh.push(line));
+        let path = PathBuf::from(self.path.trim());
+        if self.to_file && !fresh.is_empty() && !path.as_os_str().is_empty() {
+            self.files.run(FileTask::AppendLog {
+                lines: fresh.clone(),
+                path,
+            });
+        }
+        for line in fresh {
+            if self.lines.len() == LOG_PANEL_LINES {
+                self.lines.pop_front();
+            }
+            self.lines.push_back(line);
+        }
+
+        while let Some(result) = self.files.next_result() {
+            let FileResult::LogAppended { path, result } = result else {
+                continue;
+            };
+            if let Err(error) = result {
+                self.status = format!("Stopped writing {}: {error}", path.display());
+                self.to_file = false;
+            }
+        }
+    }
+}
+
+/// Peak meters for each stage of the signal chain, with recent history
+struct GainStagingPanel {
+    meters: Arc<GainStaging>,
+
+    /// Peak level per frame in dBFS, oldest first, per stage
+    history: [VecDeque<f32>; NUM_STAGES],
+
+    /// Whether the panel was drawn this frame (the audio thread only meters while it is)
+    visible: bool,
+}
+
+impl GainStagingPanel {
+    fn new(meters: Arc<GainStaging>) -> Self {
+        Self {
+            meters,
+            history: std::array::from_fn(|_| VecDeque::with_capacity(METER_HISTORY_LEN)),
+            visible: false,
+        }
+    }
+}
+
+/// Groups editor parameter edits into undoable gestures
+///
+/// Parameter values are compared with a baseline once no pointer button is held.
+/// Changes that follow user input in the editor become one undo entry; changes
+/// without it (host automation, host preset loads) just move the baseline.
+struct UndoTracker {
+    params: ParamList,
+
+    /// Normalized values after the last recorded change
+    baseline: Vec<f32>,
+
+    /// User input seen since the last comparison
+    touched: bool,
+
+    history: UndoHistory,
+}
+
+impl UndoTracker {
+    fn new(params: ParamList) -> Self {
+        let mut tracker = Self {
+            params,
+            baseline: Vec::new(),
+            touched: false,
+            history: UndoHistory::default(),
+        };
+        tracker.baseline = tracker.values();
+        tracker
+    }
+
+    /// Current normalized value of every parameter
+    fn values(&self) -> Vec<f32> {
+        // SAFETY: See `ParamList`
+        self.params
+            .iter()
+            .map(|(_, param)| unsafe { param.unmodulated_normalized_value() })
+            .collect()
+    }
+
+    /// Record any finished gesture (call once per frame, after drawing)
+    fn update(&mut self, ctx: &egui::Context) {
+        let (gesture_active, user_input) = ctx.input(|input| {
+            let editing = input.pointer.any_pressed()
+                || input.pointer.any_released()
+                || input
+                    .events
+                    .iter()
+                    .any(|event| matches!(event, egui::Event::Key { .. } | egui::Event::Text(_)));
+            (input.pointer.any_down(), editing)
+        });
+        self.touched |= user_input;
+
+        // Drags are recorded as a whole once the button is released
+        if gesture_active {
+            return;
+        }
+
+        let current = self.values();
+        let changes = diff(&self.baseline, &current);
+        if !changes.is_empty() {
+            if self.touched {
+                self.history.record(changes);
+            }
+            self.baseline = current;
+        }
+        self.touched = false;
+    }
+
+    /// Undo the last gesture
+    fn undo(&mut self, setter: &ParamSetter) {
+        if let Some(values) = self.history.undo() {
+            self.apply(setter, &values);
+        }
+    }
+
+    /// Redo the last undone gesture
+    fn redo(&mut self, setter: &ParamSetter) {
+        if let Some(values) = self.history.redo() {
+            self.apply(setter, &values);
+        }
+    }
+
+    /// Set parameters through the host, as one gesture per parameter
+    fn apply(&mut self, setter: &ParamSetter, values: &[(usize, f32)]) {
+        for &(index, value) in values {
+            let Some(&(_, param)) = self.params.get(index) else {
+                continue;
+            };
+
+            // SAFETY: See `ParamList`
+            unsafe { set_param_normalized(setter, param, value) };
+            self.baseline[index] = value;
+        }
+    }
+}
+
+/// MIDI CC mappings as edited in the editor
+///
+/// Bindings are saved with the plugin by parameter ID, so they survive parameters
+/// being added or reordered.
+struct CcMapping {
+    map: CcMap,
+
+    params: ParamList,
+
+    /// Parameters a controller is moving, by position in the list (touch
+    /// gestures that end once the controller is still)
+    gestures: OpenGestures<usize>,
+
+    /// Bindings changed since they were last saved
+    dirty: bool,
+}
+
+impl CcMapping {
+    /// Load the saved bindings (IDs that no longer exist are dropped)
+    fn new(params: &NaughtyAndTenderParams, list: ParamList) -> Self {
+        let mut map = CcMap::new();
+        if let Ok(saved) = params.cc_mappings.read() {
+            for (controller, id) in saved.iter() {
+                let controller = Controller::from_index(usize::from(*controller));
+                let index = list.iter().position(|(other, _)| other == id);
+                if let (Some(controller), Some(index)) = (controller, index) {
+                    map.bind(controller, index);
+                }
+            }
+        }
+        if let Ok(takeover) = params.cc_takeover.read() {
+            for &controller in takeover.iter() {
+                let index = Controller::from_index(usize::from(controller))
+                    .and_then(|controller| map.param_for(controller));
+                if let Some(index) = index {
+                    map.set_soft_takeover(index, true);
+                }
+            }
+        }
+
+        Self {
+            map,
+            params: list,
+            gestures: OpenGestures::default(),
+            dirty: false,
+        }
+    }
+
+    /// Position of a parameter in the list
+    fn index_of(&self, param: ParamPtr) -> Option<usize> {
+        self.params.iter().position(|(_, other)| *other == param)
+    }
+
+    /// Set mapped parameters from the controllers that moved since the last frame
+    ///
+    /// Each moving controller holds its parameter's gesture open until it has
+    /// been still for [`CONTROLLER_RELEASE_S`], so the host records one
+    /// continuous touch.
+    fn apply_incoming(&mut self, inbox: &CcInbox, setter: &ParamSetter, now_s: f64) {
+        let moved: Vec<(Controller, f32)> = inbox.drain().collect();
+        self.dirty |= self
+            .map
+            .learn(moved.iter().map(|&(controller, _)| controller));
+
+        for (cc, value) in moved {
+            let Some((index, &(_, param))) = self
+                .map
+                .handle_cc(cc)
+                .and_then(|index| Some((index, self.params.get(index)?)))
+            else {
+                continue;
+            };
+
+            // SAFETY: See `ParamList`
+            let (snapped, current) = unsafe {
+                (
+                    param.preview_normalized(param.preview_plain(value)),
+                    param.unmodulated_normalized_value(),
+                )
+            };
+            if !self.map.take_over(cc, snapped, current) {
+                continue;
+            }
+
+            // SAFETY: See `ParamList`
+            unsafe {
+                if self.gestures.touch(index, now_s) {
+                    setter.raw_context.raw_begin_set_parameter(param);
+                }
+                setter
+                    .raw_context
+                    .raw_set_parameter_normalized(param, value);
+            }
+        }
+
+        for index in self.gestures.close_idle(now_s, CONTROLLER_RELEASE_S) {
+            // SAFETY: See `ParamList`
+            unsafe {
+                setter
+                    .raw_context
+                    .raw_end_set_parameter(self.params[index].1)
+            };
+        }
+    }
+
+    /// Save changed bindings to the plugin state
+    fn save(&mut self, params: &NaughtyAndTenderParams) {
+        if !self.dirty {
+            return;
+        }
+        if let (Ok(mut saved), Ok(mut takeover)) =
+            (params.cc_mappings.write(), params.cc_takeover.write())
+        {
+            *saved = self
+                .map
+                .bindings()
+                .map(|(controller, index)| {
+                    (controller_index(controller), self.params[index].0.clone())
+                })
+                .collect();
+            *takeover = self
+                .map
+                .bindings()
+                .filter(|&(_, index)| self.map.soft_takeover(index))
+                .map(|(controller, _)| controller_index(controller))
+                .collect();
+            self.dirty = false;
+        }
+    }
+}
+
+/// A controller's saved index (every index fits in 16 bits)
+fn controller_index(controller: Controller) -> u16 {
+    u16::try_from(controller.index()).unwrap_or(u16::MAX)
+}
+
+/// Sends file tasks to the host's background thread and collects their results
+struct FileWorker {
+    executor: AsyncExecutor<NaughtyAndTender>,
+    reply: mpsc::Sender<FileResult>,
+    results: mpsc::Receiver<FileResult>,
+
+    /// Tasks sent whose results haven't arrived yet
+    pending: usize,
+}
+
+impl FileWorker {
+    fn new(executor: AsyncExecutor<NaughtyAndTender>) -> Self {
+        let (reply, results) = mpsc::channel();
+        Self {
+            executor,
+            reply,
+            results,
+            pending: 0,
+        }
+    }
+
+    fn run(&mut self, task: FileTask) {
+        self.pending += 1;
+        self.executor
+            .execute_background(FileRequest::new(task, self.reply.clone()));
+    }
+
+    /// Next finished result, if any
+    fn next_result(&mut self) -> Option<FileResult> {
+        let result = self.results.try_recv().ok()?;
+        self.pending = self.pending.saturating_sub(1);
+        Some(result)
+    }
+
+    fn busy(&self) -> bool {
+        self.pending > 0
+    }
+}
+
+/// Preset browser: index, search and save form
+///
+/// Every file read and write goes through the [`FileWorker`]; results replace the
+/// index or set the parameters in a single frame.
+struct PresetBrowser {
+    /// Presets directory (`None` if it can't be determined)
+    dir: Option<PathBuf>,
+
+    index: PresetIndex,
+    favorites: Favorites,
+
+    files: FileWorker,
+
+    /// A scan has been sent and not yet finished
+    scanning: bool,
+
+    query: String,
+
+    /// Category filter (`None` = all)
+    category: Option<String>,
+
+    favorites_only: bool,
+
+    /// Metadata for saving the current sound
+    save_info: PresetInfo,
+
+    /// Comma-separated tags for saving
+    save_tags: String,
+
+    /// Result of the last load or save
+    status: String,
+
+    /// Program changes from the audio thread
+    program_inbox: Arc<ProgramInbox>,
+
+    /// Program change assignments
+    programs: ProgramMap,
+
+    /// A preset load for a program change hasn't finished yet
+    program_pending: bool,
+
+    /// Assignment form: program and preset name
+    assign_program: Program,
+    assign_preset: String,
+}
+
+impl PresetBrowser {
+    fn new(files: FileWorker, program_inbox: Arc<ProgramInbox>, programs: ProgramMap) -> Self {
+        let mut browser = Self {
+            dir: presets::presets_dir(),
+            index: PresetIndex::default(),
+            favorites: Favorites::default(),
+            files,
+            scanning: false,
+            query: String::new(),
+            category: None,
+            favorites_only: false,
+            save_info: PresetInfo::default(),
+            save_tags: String::new(),
+            status: String::new(),
+            program_inbox,
+            programs,
+            program_pending: false,
+            assign_program: Program {
+                bank: 0,
+                program: 0,
+            },
+            assign_preset: String::new(),
+        };
+        browser.rescan();
+        browser
+    }
+
+    /// Rebuild the index and favorites in the background
+    fn rescan(&mut self) {
+        let Some(dir) = self.dir.clone() else {
+            return;
+        };
+        if !self.scanning {
+            self.files.run(FileTask::ScanPresets { dir });
+            self.scanning = true;
+        }
+    }
+
+    fn toggle_favorite(&mut self, path: &Path) {
+        let Some(dir) = self.dir.clone() else {
+            return;
+        };
+        self.favorites.toggle(path);
+        self.files.run(FileTask::SaveFavorites {
+            favorites: self.favorites.clone(),
+            dir,
+        });
+    }
+
+    /// Read a preset in the background (it's applied by `poll`)
+    fn load(&mut self, path: PathBuf) {
+        self.status = format!("Loading {}...", path.display());
+        self.files.run(FileTask::LoadPreset { path });
+    }
+
+    /// Save the current sound with the metadata from the save form
+    fn save(&mut self, params: &ParamList) {
+        let Some(dir) = self.dir.clone() else {
+            return;
+        };
+
+        let mut info = self.save_info.clone();
+        info.name = info.name.trim().to_string();
+        if info.category.trim().is_empty() {
+            info.category = presets::DEFAULT_CATEGORY.to_string();
+        }
+        info.tags = self
+            .save_tags
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        let preset = Preset {
+            values: current_values(params),
+            info,
+        };
+
+        let path = dir.join(presets::file_name(&preset.info.name));
+        self.files.run(FileTask::SavePreset { preset, path });
+    }
+
+    /// Start loading the preset for the latest program change, if any
+    fn follow_program_change(&mut self) {
+        let Some(program) = self.program_inbox.take() else {
+            return;
+        };
+
+        match self.programs.resolve(program, &self.index) {
+            Some(entry) => {
+                let path = entry.path.clone();
+                self.program_pending = true;
+                self.load(path);
+            }
+            None => {
+                self.status = format!(
+                    "No preset for bank {} program {}",
+                    program.bank,
+                    program.program + 1
+                );
+                // Nothing to wait for: let the audio fade back in
+                self.program_inbox.mark_applied();
+            }
+        }
+    }
+
+    /// Apply finished file work, returning whether a preset was loaded
+    fn poll(&mut self, params: &ParamList, setter: &ParamSetter) -> bool {
+        let mut loaded = false;
+        while let Some(result) = self.files.next_result() {
+            if self.program_pending && matches!(result, FileResult::PresetLoaded { .. }) {
+                self.program_pending = false;
+                self.program_inbox.mark_applied();
+            }
+
+            match result {
+                FileResult::PresetsScanned { index, favorites } => {
+                    self.index = index;
+                    self.favorites = favorites;
+                    self.scanning = false;
+                }
+                FileResult::PresetLoaded {
+                    preset: Ok(preset), ..
+                } => {
+                    // The audio thread hands the sounding voices over first
+                    self.program_inbox.announce_load();
+                    apply_preset(&preset, params, setter);
+                    self.status = format!("Loaded {}", preset.info.name);
+                    loaded = true;
+                }
+                FileResult::PresetLoaded {
+                    path,
+                    preset: Err(error),
+                } => self.status = format!("Couldn't load {}: {error}", path.display()),
+                FileResult::PresetSaved {
+                    path,
+                    result: Ok(()),
+                } => {
+                    self.status = format!("Saved {}", path.display());
+                    self.rescan();
+                }
+                FileResult::PresetSaved {
+                    path,
+                    result: Err(error),
+                } => self.status = format!("Couldn't save {}: {error}", path.display()),
+                FileResult::FavoritesSaved(Ok(())) => {}
+                FileResult::FavoritesSaved(Err(error)) => {
+                    self.status = format!("Couldn't save favorites: {error}");
+                }
+            }
+        }
+        loaded
+    }
+}
+
+/// Every parameter's normalized value, as a preset stores them (the morph
+/// position is left out)
+fn current_values(params: &ParamList) -> Vec<(String, f32)> {
+    params
+        .iter()
+        .filter(|(id, _)| id != MORPH_ID)
+        // SAFETY: See `ParamList`
+        .map(|(id, param)| (id.clone(), unsafe { param.unmodulated_normalized_value() }))
+        .collect()
+}
+
+/// Set every parameter from a preset (ones it doesn't mention go to their default)
+///
+/// The morph position stays put, or moving it would morph the preset away.
+fn apply_preset(preset: &Preset, params: &ParamList, setter: &ParamSetter) {
+    for (id, param) in params.iter().filter(|(id, _)| id != MORPH_ID) {
+        let saved = preset.values.iter().find(|(other, _)| other == id);
+
+        // SAFETY: See `ParamList`
+        unsafe {
+            let value = saved.map_or_else(|| param.default_normalized_value(), |(_, v)| *v);
+            set_param_normalized(setter, *param, value);
+        }
+    }
+}
+
+/// Parameter snapshots written every so often, and a snapshot left by a
+/// session that crashed, offered until it's restored or dismissed
+///
+/// Nothing is written while the offer stands, so the crashed session's
+/// snapshot survives until the user decides.
+struct SessionAutosave {
+    files: FileWorker,
+
+    session: Arc<AutosaveSession>,
+
+    timer: AutosaveTimer,
+
+    /// The claim on the snapshot file hasn't finished yet (or failed)
+    looking: bool,
+
+    /// Earlier session's snapshot
+    offer: Option<Snapshot>,
+
+    /// Why the last autosave failed, if it did
+    status: String,
+}
+
+impl SessionAutosave {
+    fn new(mut files: FileWorker, session: Arc<AutosaveSession>, saved_id: u64) -> Self {
+        files.run(FileTask::FindSnapshot {
+            session: session.clone(),
+            saved_id,
+        });
+        Self {
+            files,
+            session,
+            timer: AutosaveTimer::new(),
+            looking: true,
+            offer: None,
+            status: String::new(),
+        }
+    }
+
+    /// Take in finished file work and send a snapshot if one is due
+    fn poll(&mut self, params: &NaughtyAndTenderParams, list: &ParamList, now_s: f64) {
+        while let Some(result) = self.files.next_result() {
+            match result {
+                FileResult::SnapshotFound(Ok((id, found))) => {
+                    params.set_autosave_id(id);
+                    self.looking = false;
+                    self.offer = found;
+                }
+                FileResult::SnapshotFound(Err(error)) => {
+                    self.status = format!("Couldn't autosave: {error}");
+                }
+                FileResult::Autosaved(Ok(())) => self.status.clear(),
+                FileResult::Autosaved(Err(error)) => {
+                    self.status = format!("Couldn't autosave: {error}");
+                }
+                _ => {}
+            }
+        }
+        if self.looking || self.offer.is_some() {
+            return;
+        }
+        let Some(path) = self.session.path() else {
+            return;
+        };
+
+        if let Some(values) = self.timer.take_changes(now_s, || current_values(list)) {
+            let preset = Preset {
+                info: PresetInfo {
+                    name: autosave::SNAPSHOT_NAME.to_string(),
+                    ..PresetInfo::default()
+                },
+                values,
+            };
+            self.files.run(FileTask::Autosave { preset, path });
+            self.session.mark_written();
+        }
+    }
+
+    /// Set the parameters to the offered snapshot
+    fn restore(&mut self, params: &ParamList, setter: &ParamSetter) {
+        if let Some(snapshot) = self.offer.take() {
+            apply_preset(&snapshot.preset, params, setter);
+        }
+    }
+}
+
+/// Piano roll view of the built-in pattern
+struct PatternEditor {
+    playhead: Arc<PatternPlayhead>,
+
+    /// Note on the bottom row
+    low_note: u8,
+
+    /// Start step and note of the note a drag is stretching
+    stretching: Option<(usize, u8)>,
+
+    /// Step and note of the keyboard cursor
+    cursor: (usize, u8),
+}
+
+impl PatternEditor {
+    fn new(playhead: Arc<PatternPlayhead>) -> Self {
+        Self {
+            playhead,
+            low_note: 48,
+            stretching: None,
+            cursor: (0, 60),
+        }
+    }
+}
+
+/// Patch morph: the two snapshots, and the editor setting parameters between them
+struct PatchMorph {
+    pair: MorphPair,
+    follower: MorphFollower,
+
+    /// Parameters the glide is moving, by position in the list (their gestures
+    /// end with the glide)
+    gestures: OpenGestures<usize>,
+}
+
+impl PatchMorph {
+    fn new(params: &NaughtyAndTenderParams) -> Self {
+        // Opening the editor mustn't overwrite edits made since the last morph
+        Self {
+            pair: params.morph_pair(),
+            follower: MorphFollower::new(params.morph.value()),
+            gestures: OpenGestures::default(),
+        }
+    }
+
+    /// Store the current sound as a snapshot
+    fn store(&mut self, slot: MorphSlot, params: &NaughtyAndTenderParams, param_list: &ParamList) {
+        let snapshot = param_list
+            .iter()
+            // SAFETY: See `ParamList`
+            .map(|(id, param)| (id.clone(), unsafe { param.unmodulated_normalized_value() }))
+            .collect();
+        self.pair.set(slot, snapshot);
+        params.set_morph_snapshot(slot, self.pair.get(slot));
+    }
+
+    /// Follow the morph parameter, returning whether a glide is still under way
+    #[allow(clippy::float_cmp)] // Only parameters already at their value are skipped
+    fn update(
+        &mut self,
+        position: f32,
+        elapsed_s: f32,
+        param_list: &ParamList,
+        setter: &ParamSetter,
+    ) -> bool {
+        if !self.pair.is_ready() {
+            self.follower.sync(position);
+            return false;
+        }
+        let Some(applied) = self.follower.update(position, elapsed_s) else {
+            return false;
+        };
+
+        for (index, (id, param)) in param_list.iter().enumerate() {
+            // SAFETY: See `ParamList`
+            unsafe {
+                let stepped = param.step_count().is_some();
+                if let Some(value) = self.pair.value(id, applied, stepped) {
+                    if value != param.unmodulated_normalized_value() {
+                        if self.gestures.touch(index, 0.0) {
+                            setter.raw_context.raw_begin_set_parameter(*param);
+                        }
+                        setter
+                            .raw_context
+                            .raw_set_parameter_normalized(*param, value);
+                    }
+                }
+            }
+        }
+
+        let gliding = self.follower.is_gliding(position);
+        if !gliding {
+            for index in self.gestures.close_all() {
+                // SAFETY: See `ParamList`
+                unsafe {
+                    setter
+                        .raw_context
+                        .raw_end_set_parameter(param_list[index].1)
+                };
+            }
+        }
+        gliding
+    }
+}
+
+/// Sampler engine's file loading
+struct SampleLoader {
+    files: FileWorker,
+
+    /// Where loaded samples go (shared with the audio thread)
+    slot: Arc<SampleSlot>,
+
+    /// Path typed into the editor
+    path: String,
+
+    /// Result of the last load
+    status: String,
+}
+
+impl SampleLoader {
+    fn new(files: FileWorker, slot: Arc<SampleSlot>, path: String) -> Self {
+        Self {
+            files,
+            slot,
+            path,
+            status: String::new(),
+        }
+    }
+
+    /// Decode the WAV file at `path` in the background
+    fn load(&mut self) {
+        let path = PathBuf::from(self.path.trim());
+        if path.as_os_str().is_empty() {
+            return;
+        }
+
+        self.status = format!("Loading {}...", path.display());
+        self.files.run(FileTask::LoadSample {
+            path,
+            slot: self.slot.clone(),
+        });
+    }
+
+    /// Report finished loads, saving the path of a successful one
+    fn poll(&mut self, params: &NaughtyAndTenderParams) {
+        while let Some(result) = self.files.next_result() {
+            let FileResult::SampleLoaded { path, result } = result else {
+                continue;
+            };
+            match result {
+                Ok(()) => {
+                    params.set_sample_path(&path.to_string_lossy());
+                    self.status = format!("Loaded {}", path.display());
+                }
+                Err(error) => self.status = format!("Couldn't load {}: {error}", path.display()),
+            }
+        }
+    }
+}
+
+/// Sliders that mod matrix destinations land on, and the voice modulation
+/// shown on them
+struct SliderModulation {
+    monitor: Arc<ModMonitor>,
+
+    /// Parameter, the destination landing on it and the layers whose voices
+    /// move it (the pointers stay valid like those of a `ParamList`)
+    targets: Vec<(ParamPtr, ModDestination, Range<usize>)>,
+}
+
+impl SliderModulation {
+    fn new(params: &NaughtyAndTenderParams, monitor: Arc<ModMonitor>) -> Self {
+        let layer_b = &params.layer_b;
+        let targets = ModDestination::ALL
+            .into_iter()
+            .flat_map(|destination| {
+                let sliders: Vec<(ParamPtr, Range<usize>)> = match destination {
+                    ModDestination::None => Vec::new(),
+                    // Fine tune is shared by both layers
+                    ModDestination::Pitch => {
+                        vec![(params.fine_tune_cents.as_ptr(), 0..MONITORED_LAYERS)]
+                    }
+                    ModDestination::Cutoff => vec![
+                        (params.filter_cutoff_hz.as_ptr(), 0..1),
+                        (layer_b.filter_cutoff_hz.as_ptr(), 1..MONITORED_LAYERS),
+                    ],
+                    ModDestination::Level => vec![
+                        (params.layer_a_level.as_ptr(), 0..1),
+                        (layer_b.level.as_ptr(), 1..MONITORED_LAYERS),
+                    ],
+                };
+                sliders
+                    .into_iter()
+                    .map(move |(param, layers)| (param, destination, layers))
+            })
+            .collect();
+        Self { monitor, targets }
+    }
+}
+
+/// What every parameter control needs: the host setter, the CC mappings and
+/// the voice modulation to show
+struct ParamUi<'a> {
+    setter: &'a ParamSetter<'a>,
+    cc: &'a RefCell<CcMapping>,
+    modulation: &'a SliderModulation,
+
+    /// Mod matrix routing this frame
+    mod_matrix: ModMatrix,
+}
+
+/// Set a parameter from its pointer, as one complete gesture
+///
+/// # Safety
+/// `param` must point at one of the plugin's parameters.
+unsafe fn set_param_normalized(setter: &ParamSetter, param: ParamPtr, normalized: f32) {
+    setter.raw_context.raw_begin_set_parameter(param);
+    setter
+        .raw_context
+        .raw_set_parameter_normalized(param, normalized);
+    setter.raw_context.raw_end_set_parameter(param);
+}
 
 /// Create the plugin editor
 pub(crate) fn create(
     params: Arc<NaughtyAndTenderParams>,
+    async_executor: AsyncExecutor<NaughtyAndTender>,
+    links: EditorLinks,
     editor_state: Arc<EguiState>,
 ) -> Option<Box<dyn Editor>> {
+    let state = EditorUiState::new(&params, &async_executor, &links);
+    let EditorLinks {
+        diagnostics,
+        midi_activity,
+        cc_inbox,
+        ..
+    } = links;
+
     create_egui_editor(
         editor_state,
-        (),
-        |_, ()| {},
-        move |egui_ctx, setter, _state| {
-            egui::CentralPanel::default().show(egui_ctx, |ui| {
-                ui.heading("Naughty and Tender");
-                ui.add_space(10.0);
+        state,
+        |_, _| {},
+        move |egui_ctx, setter, state| {
+            // Restyle only when the saved theme changes
+            let theme_kind = params.theme_kind();
+            let theme = theme_kind.theme();
+            if state.applied_theme != Some(theme_kind) {
+                theme.apply(egui_ctx);
+                state.applied_theme = Some(theme_kind);
+            }
 
+            // Ctrl+Shift+Z first, so Ctrl+Z doesn't swallow it
+            let (undo, redo) = egui_ctx.input_mut(|input| {
+                let redo = input.consume_key(
+                    egui::Modifiers {
+                        shift: true,
+                        ..egui::Modifiers::COMMAND
+                    },
+                    egui::Key::Z,
+                );
+                let undo = input.consume_key(egui::Modifiers::COMMAND, egui::Key::Z);
+                (undo, redo)
+            });
+            if undo {
+                state.undo.undo(setter);
+            } else if redo {
+                state.undo.redo(setter);
+            }
+
+            let now_s = egui_ctx.input(|input| input.time);
+            state.cc.get_mut().apply_incoming(&cc_inbox, setter, now_s);
+
+            // A loaded preset is undoable like any other edit
+            state.presets.follow_program_change();
+            if state.presets.poll(&state.param_list, setter) {
+                state.undo.touched = true;
+            }
+            state.sample.poll(&params);
+            state.monitors.scope.poll();
+            state.monitors.log.poll();
+            state.autosave.poll(&params, &state.param_list, now_s);
+            if state.presets.files.busy()
+                || state.sample.files.busy()
+                || state.autosave.files.busy()
+                || state.monitors.scope.files.busy()
+                || state.monitors.log.files.busy()
+            {
+                egui_ctx.request_repaint();
+            }
+
+            let frame_s = egui_ctx.input(|input| input.stable_dt);
+            if state
+                .morph
+                .update(params.morph.value(), frame_s, &state.param_list, setter)
+            {
+                egui_ctx.request_repaint();
+            }
+
+            let cx = ParamUi {
+                setter,
+                cc: &state.cc,
+                modulation: &state.modulation,
+                mod_matrix: params.mod_matrix(),
+            };
+
+            state.monitors.tuner.visible = false;
+            state.monitors.scope.visible = false;
+            state.monitors.gain_staging.visible = false;
+            egui::CentralPanel::default().show(egui_ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("Naughty and Tender");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.menu_button("Settings", |ui| {
+                            ui.label("Theme");
+                            for kind in ThemeKind::ALL {
+                                if ui.radio(kind == theme_kind, kind.name()).clicked() {
+                                    params.set_theme_kind(kind);
+                                }
+                            }
+                        });
+                        if ui
+                            .add_enabled(state.undo.history.can_redo(), egui::Button::new("Redo"))
+                            .on_hover_text("Redo (Ctrl+Shift+Z)")
+                            .clicked()
+                        {
+                            state.undo.redo(setter);
+                        }
+                        if ui
+                            .add_enabled(state.undo.history.can_undo(), egui::Button::new("Undo"))
+                            .on_hover_text("Undo (Ctrl+Z)")
+                            .clicked()
+                        {
+                            state.undo.undo(setter);
+                        }
+                        draw_midi_indicator(ui, theme, &midi_activity, &mut state.midi_led);
+                        draw_cpu_readout(ui, &mut state.cpu);
+                    });
+                });
                 ui.label("MIDI Synthesizer - Phase 2: Synthesis Active!");
-                ui.add_space(20.0);
+                draw_audition_pad(ui, theme, &mut state.audition);
+                if draw_session_restore(ui, &mut state.autosave, &state.param_list, setter) {
+                    state.undo.touched = true;
+                }
+                ui.add_space(theme.section_spacing * 0.5);
+
+                // Tab bar
+                ui.horizontal(|ui| {
+                    for tab in Tab::ALL {
+                        ui.selectable_value(&mut state.tab, tab, tab.name());
+                    }
+                });
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| match state.tab {
+                    Tab::Oscillators => {
+                        layer_selector(ui, theme, &mut state.layer_page);
+                        draw_oscillators_tab(
+                            ui,
+                            &params,
+                            &cx,
+                            theme,
+                            state.layer_page,
+                            &mut state.sample,
+                        );
+                    }
+                    Tab::Filter => {
+                        layer_selector(ui, theme, &mut state.layer_page);
+                        draw_filter_tab(ui, &params, &cx, theme, state.layer_page, &diagnostics);
+                    }
+                    Tab::Envelopes => {
+                        layer_selector(ui, theme, &mut state.layer_page);
+                        draw_envelopes_tab(ui, &params, &cx, theme, state.layer_page);
+                    }
+                    Tab::Modulation => {
+                        draw_modulation_tab(ui, &params, &cx, theme, &mut state.pattern);
+                    }
+                    Tab::Fx => draw_fx_tab(ui, &params, &cx, theme),
+                    Tab::Global => {
+                        draw_global_tab(ui, &params, &cx, theme, &diagnostics, &mut state.monitors)
+                    }
+                    Tab::Presets => {
+                        draw_presets_tab(
+                            ui,
+                            &params,
+                            &cx,
+                            theme,
+                            &mut state.presets,
+                            &mut state.morph,
+                            &state.param_list,
+                        );
+                    }
+                });
+            });
+
+            let monitors = &state.monitors;
+            monitors
+                .tuner
+                .tap
+                .set_listening(monitors.tuner.visible || monitors.scope.visible);
+            monitors
+                .gain_staging
+                .meters
+                .set_listening(monitors.gain_staging.visible);
+            state.undo.update(egui_ctx);
+            state.cc.get_mut().save(&params);
+        },
+    )
+}
+
+/// MIDI-in LED and the last note received
+///
+/// Laid out right to left, so the LED ends up left of the note.
+fn draw_midi_indicator(
+    ui: &mut egui::Ui,
+    theme: &Theme,
+    activity: &MidiActivity,
+    led: &mut MidiLed,
+) {
+    let now = ui.input(|input| input.time);
+    let event_count = activity.event_count();
+    if event_count != led.event_count {
+        led.event_count = event_count;
+        led.lit_until = now + MIDI_LED_HOLD_S;
+    }
+
+    let last_note = activity.last_note().map_or_else(
+        || "-".to_string(),
+        |(note, velocity)| format!("{} ({:.0})", note_name(note), velocity * 127.0),
+    );
+    ui.label(last_note)
+        .on_hover_text("Last note received (velocity)");
+
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+    let color = if now < led.lit_until {
+        theme.accent
+    } else {
+        theme.plot_muted
+    };
+    ui.painter().circle_filled(rect.center(), 5.0, color);
+    response.on_hover_text("MIDI input activity");
+
+    // Poll the audio thread for new events
+    ui.ctx()
+        .request_repaint_after(std::time::Duration::from_millis(30));
+}
+
+/// Audition pad with its note, velocity and latch settings: plays the patch
+/// without a keyboard
+fn draw_audition_pad(ui: &mut egui::Ui, theme: &Theme, pad: &mut AuditionPad) {
+    ui.horizontal(|ui| {
+        let (rect, response) =
+            ui.allocate_exact_size(egui::vec2(64.0, 20.0), egui::Sense::click_and_drag());
+        if response.clicked() {
+            pad.click();
+        }
+        // Space held on the focused pad plays it too
+        let key_held = response.has_focus() && ui.input(|input| input.key_down(egui::Key::Space));
+        pad.update(response.is_pointer_button_down_on() || key_held);
+
+        let fill = if pad.is_sounding() {
+            theme.accent
+        } else {
+            theme.plot_muted
+        };
+        ui.painter().rect_filled(rect, 3.0, fill);
+        ui.painter().text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "Audition",
+            egui::FontId::proportional(theme.body_size),
+            ui.visuals().strong_text_color(),
+        );
+        focus_marker(ui, &response);
+        response.widget_info(|| {
+            egui::WidgetInfo::selected(
+                egui::WidgetType::Button,
+                true,
+                pad.is_sounding(),
+                "Audition",
+            )
+        });
+        response.on_hover_text(if pad.latch() {
+            "Click to start or stop the note"
+        } else {
+            "Hold to play the note"
+        });
+
+        let mut latch = pad.latch();
+        if ui
+            .checkbox(&mut latch, "Latch")
+            .on_hover_text("Keep the note playing until the pad is clicked again")
+            .changed()
+        {
+            pad.set_latch(latch);
+        }
+
+        ui.label("Note");
+        let mut note = pad.note();
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Clamped to 0 - 127
+        let format_note = |note: f64, _: std::ops::RangeInclusive<usize>| note_name(note as u8);
+        if ui
+            .add(
+                egui::DragValue::new(&mut note)
+                    .clamp_range(0..=127)
+                    .custom_formatter(format_note),
+            )
+            .changed()
+        {
+            pad.set_note(note);
+        }
+
+        ui.label("Velocity");
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // 0 - 127
+        let mut velocity = (pad.velocity() * 127.0).round() as u8;
+        if ui
+            .add(egui::DragValue::new(&mut velocity).clamp_range(1..=127))
+            .changed()
+        {
+            pad.set_velocity(f32::from(velocity) / 127.0);
+        }
+    });
+}
+
+/// Smoothed CPU load of the audio thread, with the recent peak on hover
+///
+/// Repaints along with the MIDI indicator.
+fn draw_cpu_readout(ui: &mut egui::Ui, readout: &mut CpuReadout) {
+    let now = ui.input(|input| input.time);
+    let peak = readout.load.take_peak();
+    if peak >= readout.peak || now - readout.peak_at > CPU_PEAK_HOLD_S {
+        readout.peak = peak;
+        readout.peak_at = now;
+    }
+
+    let load = readout.load.load();
+    let text = format!("CPU {:.0}%", load * 100.0);
+    let response = if load > CPU_WARN_LOAD {
+        ui.colored_label(ui.visuals().warn_fg_color, text)
+    } else {
+        ui.label(text)
+    };
+    response.on_hover_text(format!(
+        "Share of each block's real-time budget spent processing it (peak {:.0}%)",
+        readout.peak * 100.0
+    ));
+}
+
+/// Layer A / Layer B switch for the per-layer tabs
+fn layer_selector(ui: &mut egui::Ui, theme: &Theme, page: &mut LayerPage) {
+    ui.horizontal(|ui| {
+        ui.selectable_value(page, LayerPage::A, "Layer A");
+        ui.selectable_value(page, LayerPage::B, "Layer B");
+    });
+    ui.add_space(theme.row_spacing);
+}
+
+/// Titled group with a consistent heading and spacing
+fn section(
+    ui: &mut egui::Ui,
+    theme: &Theme,
+    title: &str,
+    add_contents: impl FnOnce(&mut egui::Ui),
+) {
+    ui.group(|ui| {
+        ui.heading(title);
+        ui.add_space(theme.row_spacing);
+        add_contents(ui);
+    });
+    ui.add_space(theme.section_spacing);
+}
+
+/// Two-column grid of labelled parameter rows (see [`param_row`])
+fn param_grid(ui: &mut egui::Ui, theme: &Theme, id: &str, add_rows: impl FnOnce(&mut egui::Ui)) {
+    egui::Grid::new(id)
+        .num_columns(2)
+        .spacing([theme.item_spacing * 1.5, theme.row_spacing])
+        .show(ui, add_rows);
+}
+
+/// Label and slider for one parameter, as a row of a [`param_grid`]
+///
+/// Both show the description and the parameter's range on hover.
+fn param_row<P: Param>(ui: &mut egui::Ui, label: &str, description: &str, param: &P, cx: &ParamUi) {
+    let tooltip = param_tooltip(description, param);
+    let label = ui.label(label).on_hover_text(&tooltip);
+    param_slider(ui, param, cx)
+        .labelled_by(label.id)
+        .on_hover_text(tooltip);
+    ui.end_row();
+}
+
+/// Time with its tempo-sync switch and note division, as a row of a [`param_grid`]
+fn synced_row(
+    ui: &mut egui::Ui,
+    label: &str,
+    description: &str,
+    time: &FloatParam,
+    sync: &BoolParam,
+    division: &IntParam,
+    cx: &ParamUi,
+) {
+    let label = ui
+        .label(label)
+        .on_hover_text(param_tooltip(description, time));
+    ui.horizontal(|ui| {
+        param_slider(ui, time, cx)
+            .labelled_by(label.id)
+            .on_hover_text(param_tooltip(description, time));
+        param_slider(ui, sync, cx)
+            .on_hover_text("Lock the time to a note length at the host tempo");
+        param_slider(ui, division, cx)
+            .on_hover_text(param_tooltip("Note length used while synced", division));
+    });
+    ui.end_row();
+}
+
+/// Slider for one parameter, with its right-click menu and keyboard control
+///
+/// Screen readers get the parameter's name and value.
+fn param_slider<P: Param>(ui: &mut egui::Ui, param: &P, cx: &ParamUi) -> egui::Response {
+    let response = ui.add(widgets::ParamSlider::for_param(param, cx.setter));
+    mod_indicator(ui, &response, param, cx);
+    if response.has_focus() {
+        hold_arrow_keys(ui, &response);
+        if let Some((nudge, fine)) = pressed_nudge(ui, &SLIDER_KEYS) {
+            nudge_param(param, cx.setter, nudge, fine);
+        }
+    }
+    focus_marker(ui, &response);
+    response.widget_info(|| {
+        let normalized = param.unmodulated_normalized_value();
+        let mut info = egui::WidgetInfo::slider(true, f64::from(normalized), param.name());
+        info.current_text_value = Some(param.normalized_value_to_string(normalized, true));
+        info
+    });
+    pickup_indicator(ui, &response, param, cx);
+    param_menu(&response, param, cx);
+    response
+}
+
+/// Band across a slider covering its parameter's values after modulation,
+/// while a routed destination moves it on any of its layer's voices
+fn mod_indicator<P: Param>(ui: &egui::Ui, response: &egui::Response, param: &P, cx: &ParamUi) {
+    let param = param.as_ptr();
+    let modulation = cx.modulation;
+    for (_, destination, layers) in modulation
+        .targets
+        .iter()
+        .filter(|(target, destination, _)| *target == param && cx.mod_matrix.targets(*destination))
+    {
+        let Some(range) = layers
+            .clone()
+            .filter_map(|layer| modulation.monitor.range(layer))
+            .reduce(|mut range, other| {
+                range.include(other.low);
+                range.include(other.high);
+                range
+            })
+            .filter(|range| range.moves(*destination))
+        else {
+            continue;
+        };
+
+        // SAFETY: See `SliderModulation`
+        let (low, high) = unsafe {
+            let plain = param.unmodulated_plain_value();
+            (
+                param.preview_normalized(range.low.apply(*destination, plain)),
+                param.preview_normalized(range.high.apply(*destination, plain)),
+            )
+        };
+        let rect = response.rect;
+        let x = |normalized: f32| rect.left() + normalized.clamp(0.0, 1.0) * rect.width();
+        let (left, right) = (x(low.min(high)), x(low.max(high)));
+        let color = ui.visuals().hyperlink_color;
+        ui.painter().rect_filled(
+            egui::Rect::from_x_y_ranges(left..=right.max(left + 2.0), rect.y_range()),
+            0.0,
+            color.linear_multiply(0.4),
+        );
+
+        // Follow the voices while they move
+        ui.ctx()
+            .request_repaint_after(std::time::Duration::from_millis(30));
+    }
+}
+
+/// Move a parameter from the keyboard, as one complete gesture
+#[allow(clippy::float_cmp)] // Unchanged means exactly the same value
+fn nudge_param<P: Param>(param: &P, setter: &ParamSetter, nudge: Nudge, fine: bool) {
+    let current = param.unmodulated_normalized_value();
+    let normalized = nudge.apply(current, param.step_count(), fine);
+    if normalized != current {
+        setter.begin_set_parameter(param);
+        setter.set_parameter_normalized(param, normalized);
+        setter.end_set_parameter(param);
+    }
+}
+
+/// Nudge for the first of `keys` pressed this frame, and whether Shift asks
+/// for the fine step
+fn pressed_nudge(ui: &egui::Ui, keys: &[(egui::Key, Nudge)]) -> Option<(Nudge, bool)> {
+    ui.input(|input| {
+        let (_, nudge) = keys.iter().find(|(key, _)| input.key_pressed(*key))?;
+        Some((*nudge, input.modifiers.shift))
+    })
+}
+
+/// Keep a focused control's arrow keys from moving the focus on
+fn hold_arrow_keys(ui: &egui::Ui, response: &egui::Response) {
+    ui.memory_mut(|memory| {
+        memory.set_focus_lock_filter(
+            response.id,
+            egui::EventFilter {
+                horizontal_arrows: true,
+                vertical_arrows: true,
+                ..egui::EventFilter::default()
+            },
+        );
+    });
+}
+
+/// Whether Space or Enter was pressed on a focused control (egui reports it
+/// as a click, at wherever the pointer happens to be)
+fn keyboard_click(ui: &egui::Ui, response: &egui::Response) -> bool {
+    response.has_focus()
+        && ui.input(|input| {
+            input.key_pressed(egui::Key::Space) || input.key_pressed(egui::Key::Enter)
+        })
+}
+
+/// Underline a focused control that doesn't show focus itself
+fn focus_marker(ui: &egui::Ui, response: &egui::Response) {
+    if response.has_focus() {
+        ui.painter().line_segment(
+            [response.rect.left_bottom(), response.rect.right_bottom()],
+            ui.visuals().selection.stroke,
+        );
+    }
+}
+
+/// Keyboard cursor of a focused bar graph with `len` bars: Left and Right
+/// (Home and End) pick a bar, which is returned while the graph has focus
+fn bar_cursor(ui: &egui::Ui, response: &egui::Response, len: usize) -> Option<usize> {
+    if !response.has_focus() {
+        return None;
+    }
+    hold_arrow_keys(ui, response);
+    let id = response.id.with("cursor");
+    let mut index = ui.data(|data| data.get_temp::<usize>(id)).unwrap_or(0);
+    if let Some((nudge, _)) = pressed_nudge(ui, &CURSOR_KEYS) {
+        index = nudge.move_index(index, len);
+        ui.data_mut(|data| data.insert_temp(id, index));
+    }
+    Some(index.min(len - 1))
+}
+
+/// Mark the bar under a graph's keyboard cursor
+fn cursor_marker(painter: &egui::Painter, ui: &egui::Ui, left: f32, right: f32, bottom: f32) {
+    painter.rect_filled(
+        egui::Rect::from_min_max(egui::pos2(left, bottom - 3.0), egui::pos2(right, bottom)),
+        0.0,
+        ui.visuals().selection.stroke.color,
+    );
+}
+
+/// Dot in a slider's corner while soft take-over waits for its controller
+///
+/// The slider's right-click menu shows where the controller is.
+fn pickup_indicator<P: Param>(ui: &egui::Ui, response: &egui::Response, param: &P, cx: &ParamUi) {
+    let cc = cx.cc.borrow();
+    let Some(index) = cc.index_of(param.as_ptr()) else {
+        return;
+    };
+    let current = param.unmodulated_normalized_value();
+    if cc.map.pickup_pending(index, current).is_some() {
+        let corner = response.rect.right_top() + egui::vec2(-4.0, 4.0);
+        ui.painter()
+            .circle_filled(corner, 3.0, ui.visuals().warn_fg_color);
+    }
+}
+
+/// Right-click menu: reset to default, type in a value, and MIDI CC binding
+fn param_menu<P: Param>(response: &egui::Response, param: &P, cx: &ParamUi) {
+    response.context_menu(|ui| {
+        // Typed text lives in egui's memory until it's submitted
+        let entry_id = response.id.with("value_entry");
+
+        if ui.button("Reset to Default").clicked() {
+            cx.setter.begin_set_parameter(param);
+            cx.setter.set_parameter(param, param.default_plain_value());
+            cx.setter.end_set_parameter(param);
+            ui.data_mut(|data| data.remove::<String>(entry_id));
+            ui.close_menu();
+        }
+
+        let mut entry = ui
+            .data_mut(|data| data.get_temp::<String>(entry_id))
+            .unwrap_or_else(|| {
+                param.normalized_value_to_string(param.unmodulated_normalized_value(), false)
+            });
+        let submitted = ui
+            .horizontal(|ui| {
+                ui.label("Value");
+                let edit = ui.text_edit_singleline(&mut entry);
+                edit.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter))
+            })
+            .inner;
+        if submitted {
+            // Unparseable text leaves the parameter alone
+            if let Some(normalized) = param.string_to_normalized_value(&entry) {
+                cx.setter.begin_set_parameter(param);
+                cx.setter.set_parameter_normalized(param, normalized);
+                cx.setter.end_set_parameter(param);
+            }
+            ui.data_mut(|data| data.remove::<String>(entry_id));
+            ui.close_menu();
+        } else {
+            ui.data_mut(|data| data.insert_temp(entry_id, entry));
+        }
+
+        ui.separator();
+
+        let mut cc = cx.cc.borrow_mut();
+        let Some(index) = cc.index_of(param.as_ptr()) else {
+            return;
+        };
+        if cc.map.learning() == Some(index) {
+            ui.label("Move a MIDI controller...");
+            if ui.button("Cancel MIDI Learn").clicked() {
+                cc.map.cancel_learn();
+                ui.close_menu();
+            }
+        } else if let Some(controller) = cc.map.cc_for(index) {
+            ui.label(format!("Mapped to {controller}"));
+            let mut takeover = cc.map.soft_takeover(index);
+            if ui
+                .checkbox(&mut takeover, "Soft Take-Over")
+                .on_hover_text("Ignore the controller until it reaches the current value")
+                .changed()
+            {
+                cc.map.set_soft_takeover(index, takeover);
+                cc.dirty = true;
+            }
+            if let Some(knob) = cc
+                .map
+                .pickup_pending(index, param.unmodulated_normalized_value())
+            {
+                ui.label(format!(
+                    "Waiting for pickup: controller at {}",
+                    param.normalized_value_to_string(knob, true)
+                ));
+            }
+            if ui.button("Unbind CC").clicked() {
+                cc.map.unbind(index);
+                cc.dirty = true;
+                ui.close_menu();
+            }
+        } else if ui.button("MIDI Learn").clicked() {
+            cc.map.start_learn(index);
+            ui.close_menu();
+        }
+    });
+}
+
+/// Hover text for a parameter: its description and full range
+fn param_tooltip<P: Param>(description: &str, param: &P) -> String {
+    format!(
+        "{description}\n\nRange: {} to {}",
+        param.normalized_value_to_string(0.0, true),
+        param.normalized_value_to_string(1.0, true)
+    )
+}
+
+/// Oscillator and engine settings of one layer
+fn draw_oscillators_tab(
+    ui: &mut egui::Ui,
+    params: &NaughtyAndTenderParams,
+    cx: &ParamUi,
+    theme: &Theme,
+    page: LayerPage,
+    sample: &mut SampleLoader,
+) {
+    match page {
+        LayerPage::A => {
+            section(ui, theme, "Oscillator", |ui| {
+                param_grid(ui, theme, "oscillator_a", |ui| {
+                    param_row(
+                        ui,
+                        "Waveform",
+                        "Oscillator shape for layer A",
+                        &params.waveform,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Sine Quality",
+                        "Exact computes every sine sample; the tables are cheaper with error far below hearing (both layers)",
+                        &params.sine_quality,
+                        cx,
+                    );
+                    synced_row(
+                        ui,
+                        "Glide",
+                        "Time to slide between notes",
+                        &params.glide_ms,
+                        &params.glide_sync,
+                        &params.glide_division,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Glide Mode",
+                        "Constant Time takes the glide time for any interval; Constant Rate takes it per octave",
+                        &params.glide_mode,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Glide Curve",
+                        "Linear moves evenly; Exponential starts fast and eases into the note",
+                        &params.glide_curve,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Glissando",
+                        "Step through every semitone, or through the Scale section's scale, instead of sliding",
+                        &params.glissando,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Free Phase",
+                        "Continue the waveform across notes instead of restarting it at each note",
+                        &params.free_running_phase,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Start Phase",
+                        "Where the waveform starts at each note; 90° starts a sine at its peak for a harder attack",
+                        &params.start_phase,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Random Phase",
+                        "Start each note at a random point in the waveform, for a less mechanical attack",
+                        &params.random_phase,
+                        cx,
+                    );
+                });
+            });
+
+            section(ui, theme, "Engine", |ui| {
+                param_grid(ui, theme, "engine_a", |ui| {
+                    param_row(
+                        ui,
+                        "Engine",
+                        "Sound source: the oscillator, the plucked-string model, a sample or additive partials",
+                        &params.engine,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Excitation",
+                        "What plucks the string: a noise burst or the oscillator",
+                        &params.string_excitation,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Damping",
+                        "High-frequency loss per string round trip; higher sounds duller",
+                        &params.string_damping,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "String Decay",
+                        "Time for a plucked string to fade away",
+                        &params.string_decay_ms,
+                        cx,
+                    );
+                });
+            });
 
-                // Oscillator section
-                ui.group(|ui| {
-                    ui.heading("Oscillator");
-                    ui.add_space(5.0);
+            section(ui, theme, "Sample", |ui| {
+                draw_sample_file(ui, theme, sample);
+                ui.add_space(theme.row_spacing);
 
-                    ui.label("Waveform");
-                    ui.add(widgets::ParamSlider::for_param(&params.waveform, setter));
+                param_grid(ui, theme, "sample_a", |ui| {
+                    param_row(
+                        ui,
+                        "Root Note",
+                        "Note at which the sample plays at its recorded pitch",
+                        &params.sample_root,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Start",
+                        "Where playback starts, as a share of the sample length",
+                        &params.sample_start,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Loop",
+                        "Repeat between the loop points instead of stopping at the end",
+                        &params.sample_loop,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Loop Start",
+                        "Start of the looped region, as a share of the sample length",
+                        &params.sample_loop_start,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Loop End",
+                        "End of the looped region, as a share of the sample length",
+                        &params.sample_loop_end,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Interpolation",
+                        "Reading between stored samples: linear is lighter, cubic is smoother",
+                        &params.sample_interpolation,
+                        cx,
+                    );
                 });
+            });
 
-                ui.add_space(15.0);
+            section(ui, theme, "Granular", |ui| {
+                param_grid(ui, theme, "granular_a", |ui| {
+                    param_row(
+                        ui,
+                        "Granular",
+                        "Play the sample as a cloud of short overlapping grains",
+                        &params.granular,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Size",
+                        "Length of each grain; short grains buzz, long ones blur",
+                        &params.grain_size_ms,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Density",
+                        "Grains started per second",
+                        &params.grain_density,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Position",
+                        "Where in the sample grains are read from",
+                        &params.grain_position,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Jitter",
+                        "Random scatter of each grain's position",
+                        &params.grain_jitter,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Pitch Spread",
+                        "Random detune of each grain, in semitones either way",
+                        &params.grain_pitch_spread,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Window",
+                        "Fade shape of each grain",
+                        &params.grain_window,
+                        cx,
+                    );
+                });
+            });
 
-                // ADSR Envelope section
-                ui.group(|ui| {
-                    ui.heading("Envelope (ADSR)");
-                    ui.add_space(5.0);
+            section(ui, theme, "Additive", |ui| {
+                draw_harmonic_editor(ui, params, cx.setter, theme);
+                ui.label("Drag to set partial levels, right-click to silence a partial");
+                ui.add_space(theme.row_spacing);
 
-                    ui.label("Attack");
-                    ui.add(widgets::ParamSlider::for_param(&params.attack_ms, setter));
+                param_grid(ui, theme, "additive_a", |ui| {
+                    param_row(
+                        ui,
+                        "Tilt",
+                        "Brighten or darken the spectrum, in dB per octave above the fundamental",
+                        &params.additive_tilt,
+                        cx,
+                    );
+                });
+            });
+        }
+        LayerPage::B => {
+            let layer = &params.layer_b;
+            section(ui, theme, "Oscillator", |ui| {
+                param_grid(ui, theme, "oscillator_b", |ui| {
+                    param_row(
+                        ui,
+                        "Waveform",
+                        "Oscillator shape for layer B",
+                        &layer.waveform,
+                        cx,
+                    );
+                });
+                ui.label("Glide, drive and modulation are shared with layer A");
+            });
+        }
+    }
+}
+
+/// WAV file path, load button and details of the loaded sample
+fn draw_sample_file(ui: &mut egui::Ui, theme: &Theme, sample: &mut SampleLoader) {
+    ui.horizontal(|ui| {
+        let response =
+            ui.add(egui::TextEdit::singleline(&mut sample.path).hint_text("Path to a WAV file"));
+        let entered =
+            response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+        if ui.button("Load").clicked() || entered {
+            sample.load();
+        }
+    });
 
-                    ui.add_space(5.0);
+    match sample.slot.current() {
+        Some(loaded) => ui.label(format!(
+            "{:.2} s at {} Hz",
+            loaded.duration_s(),
+            loaded.sample_rate()
+        )),
+        None => ui.colored_label(theme.plot_muted, "No sample loaded"),
+    };
+    if !sample.status.is_empty() {
+        ui.label(&sample.status);
+    }
+}
 
-                    ui.label("Decay");
-                    ui.add(widgets::ParamSlider::for_param(&params.decay_ms, setter));
+/// Voice filter of one layer, and the voice limiter both layers share
+fn draw_filter_tab(
+    ui: &mut egui::Ui,
+    params: &NaughtyAndTenderParams,
+    cx: &ParamUi,
+    theme: &Theme,
+    page: LayerPage,
+    diagnostics: &VoiceDiagnostics,
+) {
+    let (enabled, mode, cutoff, resonance, envelope) = match page {
+        LayerPage::A => (
+            &params.filter_enabled,
+            &params.filter_mode,
+            &params.filter_cutoff_hz,
+            &params.filter_resonance,
+            &params.filter_env,
+        ),
+        LayerPage::B => {
+            let layer: &LayerParams = &params.layer_b;
+            (
+                &layer.filter_enabled,
+                &layer.filter_mode,
+                &layer.filter_cutoff_hz,
+                &layer.filter_resonance,
+                &layer.filter_env,
+            )
+        }
+    };
 
-                    ui.add_space(5.0);
+    section(ui, theme, "Filter", |ui| {
+        param_grid(ui, theme, "filter", |ui| {
+            param_row(
+                ui,
+                "Enabled",
+                "Run the voice filter (off = bypassed)",
+                enabled,
+                cx,
+            );
+            param_row(
+                ui,
+                "Mode",
+                "Filter response: low-pass, high-pass or band-pass",
+                mode,
+                cx,
+            );
+            param_row(
+                ui,
+                "Cutoff",
+                "Filter cutoff frequency (the mod matrix can sweep it in octaves)",
+                cutoff,
+                cx,
+            );
+            param_row(
+                ui,
+                "Resonance",
+                "Emphasis around the cutoff; high values ring",
+                resonance,
+                cx,
+            );
+            param_row(
+                ui,
+                "Precision",
+                "High runs the filters in 64-bit arithmetic, which stays accurate at very low \
+                 cutoffs with high resonance (shared by both layers)",
+                &params.filter_precision,
+                cx,
+            );
+            param_row(
+                ui,
+                "Latch",
+                "Sounding notes keep the filter settings they started with; changes reach the next notes (shared by both layers)",
+                &params.latch_filter,
+                cx,
+            );
+        });
+    });
 
-                    ui.label("Sustain");
-                    ui.add(widgets::ParamSlider::for_param(&params.sustain_level, setter));
+    section(ui, theme, "Filter Envelope", |ui| {
+        param_grid(ui, theme, "filter_env", |ui| {
+            param_row(
+                ui,
+                "Attack",
+                "Time for the envelope to rise to its peak",
+                &envelope.attack_ms,
+                cx,
+            );
+            param_row(
+                ui,
+                "Decay",
+                "Time to fall from the peak to the sustain level",
+                &envelope.decay_ms,
+                cx,
+            );
+            param_row(
+                ui,
+                "Sustain",
+                "Envelope level while the key is held",
+                &envelope.sustain_level,
+                cx,
+            );
+            param_row(
+                ui,
+                "Release",
+                "Time to fall back after the key is released",
+                &envelope.release_ms,
+                cx,
+            );
+            param_row(
+                ui,
+                "Amount",
+                "Octaves the envelope moves the cutoff at its peak (negative sweeps down)",
+                &envelope.amount,
+                cx,
+            );
+            param_row(
+                ui,
+                "Invert",
+                "Sweep the cutoff the other way",
+                &envelope.invert,
+                cx,
+            );
+            param_row(
+                ui,
+                "Velocity",
+                "How much softer notes shrink the amount",
+                &envelope.velocity,
+                cx,
+            );
+        });
+    });
 
-                    ui.add_space(5.0);
+    section(ui, theme, "Voice Limiter", |ui| {
+        param_grid(ui, theme, "voice_limiter", |ui| {
+            param_row(
+                ui,
+                "Enabled",
+                "Limit each voice after its filter so resonance can't ring past the ceiling",
+                &params.voice_limiter,
+                cx,
+            );
+            param_row(
+                ui,
+                "Ceiling",
+                "Highest peak a voice can reach, with a soft knee below it",
+                &params.voice_limiter_ceiling_db,
+                cx,
+            );
+        });
+        ui.label(format!(
+            "Gain reduction: {:.1} dB",
+            diagnostics.limiter_reduction_db()
+        ));
+    });
+}
 
-                    ui.label("Release");
-                    ui.add(widgets::ParamSlider::for_param(&params.release_ms, setter));
+/// Amplitude envelope of one layer
+fn draw_envelopes_tab(
+    ui: &mut egui::Ui,
+    params: &NaughtyAndTenderParams,
+    cx: &ParamUi,
+    theme: &Theme,
+    page: LayerPage,
+) {
+    match page {
+        LayerPage::A => section(ui, theme, "Envelope (ADSR)", |ui| {
+            param_grid(ui, theme, "envelope_a", |ui| {
+                synced_row(
+                    ui,
+                    "Attack",
+                    "Time to rise from silence to full level",
+                    &params.attack_ms,
+                    &params.attack_sync,
+                    &params.attack_division,
+                    cx,
+                );
+                synced_row(
+                    ui,
+                    "Decay",
+                    "Time to fall from full level to the sustain level",
+                    &params.decay_ms,
+                    &params.decay_sync,
+                    &params.decay_division,
+                    cx,
+                );
+                param_row(
+                    ui,
+                    "Sustain",
+                    "Level held while the key is down, after the decay",
+                    &params.sustain_level,
+                    cx,
+                );
+                synced_row(
+                    ui,
+                    "Release",
+                    "Time to fade out after the key is released",
+                    &params.release_ms,
+                    &params.release_sync,
+                    &params.release_division,
+                    cx,
+                );
+                param_row(ui, "Release Velocity", "How note-off velocity shapes the release: positive makes fast releases shorter", &params.release_velocity, cx);
+                param_row(ui, "Latch", "Sounding notes keep the envelope they started with; changes reach the next notes (shared by both layers)", &params.latch_envelope, cx);
+            });
+            ui.add_space(theme.row_spacing);
+            // Synced times drawn at the default tempo (the editor doesn't see the host's)
+            draw_envelope_graph(
+                ui,
+                theme,
+                [
+                    params.attack_time_ms(DEFAULT_TEMPO_BPM),
+                    params.decay_time_ms(DEFAULT_TEMPO_BPM),
+                    params.release_time_ms(DEFAULT_TEMPO_BPM),
+                ],
+                params.sustain_level.value(),
+            );
+        }),
+        LayerPage::B => {
+            let layer = &params.layer_b;
+            section(ui, theme, "Envelope (ADSR)", |ui| {
+                param_grid(ui, theme, "envelope_b", |ui| {
+                    param_row(
+                        ui,
+                        "Attack",
+                        "Time to rise from silence to full level",
+                        &layer.attack_ms,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Decay",
+                        "Time to fall from full level to the sustain level",
+                        &layer.decay_ms,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Sustain",
+                        "Level held while the key is down, after the decay",
+                        &layer.sustain_level,
+                        cx,
+                    );
+                    param_row(
+                        ui,
+                        "Release",
+                        "Time to fade out after the key is released",
+                        &layer.release_ms,
+                        cx,
+                    );
+                    param_row(ui, "Latch", "Sounding notes keep the envelope they started with; changes reach the next notes (shared by both layers)", &params.latch_envelope, cx);
                 });
+                ui.add_space(theme.row_spacing);
+                draw_envelope_graph(
+                    ui,
+                    theme,
+                    [
+                        layer.attack_ms.value(),
+                        layer.decay_ms.value(),
+                        layer.release_ms.value(),
+                    ],
+                    layer.sustain_level.value(),
+                );
+            });
+        }
+    }
+}
+
+/// Step sequencer, random source, mod matrix, and the note processing: humanize,
+/// scale quantization and chord memory
+fn draw_modulation_tab(
+    ui: &mut egui::Ui,
+    params: &NaughtyAndTenderParams,
+    cx: &ParamUi,
+    theme: &Theme,
+    pattern: &mut PatternEditor,
+) {
+    section(ui, theme, "Step Sequencer", |ui| {
+        param_grid(ui, theme, "sequencer", |ui| {
+            param_row(
+                ui,
+                "Rate",
+                "Length of each step, relative to the host tempo",
+                &params.seq_division,
+                cx,
+            );
+            param_row(
+                ui,
+                "Slew",
+                "Glide time between step values",
+                &params.seq_slew_ms,
+                cx,
+            );
+            param_row(
+                ui,
+                "Euclidean",
+                "Gate the steps with pulses spread as evenly as they go over a number of \
+                 steps, instead of their own gates; changes take over at the next step",
+                &params.seq_euclid,
+                cx,
+            );
+            param_row(
+                ui,
+                "Steps",
+                "Length of the Euclidean sequence",
+                &params.seq_euclid_steps,
+                cx,
+            );
+            param_row(
+                ui,
+                "Pulses",
+                "Gated steps in the Euclidean sequence",
+                &params.seq_euclid_pulses,
+                cx,
+            );
+            param_row(
+                ui,
+                "Rotation",
+                "Steps the Euclidean rhythm starts late by",
+                &params.seq_euclid_rotation,
+                cx,
+            );
+        });
+        ui.add_space(theme.row_spacing);
+
+        draw_step_grid(ui, params, cx.setter, theme);
+        ui.label("Drag to set step values, right-click to toggle gates");
+    });
+
+    section(ui, theme, "Random (S&H)", |ui| {
+        param_grid(ui, theme, "random", |ui| {
+            synced_row(
+                ui,
+                "Rate",
+                "How often a new random value is picked",
+                &params.rand_rate_hz,
+                &params.rand_sync,
+                &params.rand_division,
+                cx,
+            );
+            param_row(
+                ui,
+                "Slew",
+                "Smoothing between random values (0 = hard steps)",
+                &params.rand_slew_ms,
+                cx,
+            );
+        });
+    });
+
+    section(ui, theme, "Sidechain Follower", |ui| {
+        param_grid(ui, theme, "sidechain", |ui| {
+            param_row(
+                ui,
+                "Attack",
+                "How quickly the follower rises with the sidechain level",
+                &params.sidechain_attack_ms,
+                cx,
+            );
+            param_row(
+                ui,
+                "Release",
+                "How quickly the follower falls when the sidechain gets quieter",
+                &params.sidechain_release_ms,
+                cx,
+            );
+            param_row(
+                ui,
+                "Gain",
+                "Boost for quiet sidechain signals before the follower",
+                &params.sidechain_gain_db,
+                cx,
+            );
+        });
+        ui.label(
+            "Route the plugin's sidechain input in your host, then pick Sidechain as a mod source",
+        );
+    });
+
+    section(ui, theme, "Mod Matrix", |ui| {
+        for slot in &params.mod_slots {
+            ui.horizontal(|ui| {
+                param_slider(ui, &slot.source, cx).on_hover_text("Modulation source for this slot");
+                ui.label("→");
+                param_slider(ui, &slot.destination, cx)
+                    .on_hover_text("Parameter this slot modulates");
+                param_slider(ui, &slot.amount, cx).on_hover_text(param_tooltip(
+                    "Modulation depth; negative values invert the source",
+                    &slot.amount,
+                ));
+            });
+        }
+        ui.label(
+            "Pitch follows each voice's glide and bend; Pitch → Cutoff at 100% tracks the keys",
+        );
+        ui.add_space(theme.row_spacing);
+
+        param_grid(ui, theme, "control_rate", |ui| {
+            param_row(
+                ui,
+                "Control Rate",
+                "Samples between modulation updates, smoothed in between; lower is more precise, higher saves CPU",
+                &params.control_divisor,
+                cx,
+            );
+        });
+    });
+
+    section(ui, theme, "Humanize", |ui| {
+        param_grid(ui, theme, "humanize", |ui| {
+            param_row(
+                ui,
+                "Humanize",
+                "Loosen up incoming notes with random timing and velocity",
+                &params.humanize,
+                cx,
+            );
+            param_row(
+                ui,
+                "Timing",
+                "Longest random delay added to each note (note lengths are kept)",
+                &params.humanize_timing_ms,
+                cx,
+            );
+            param_row(
+                ui,
+                "Velocity",
+                "Largest random change to each note's velocity, up or down",
+                &params.humanize_velocity,
+                cx,
+            );
+        });
+    });
+
+    section(ui, theme, "Expression", |ui| {
+        param_grid(ui, theme, "expression", |ui| {
+            param_row(
+                ui,
+                "Curve",
+                "How the expression pedal (CC 11) sets the output level",
+                &params.expression_curve,
+                cx,
+            );
+        });
+    });
+
+    section(ui, theme, "Scale", |ui| {
+        param_grid(ui, theme, "scale", |ui| {
+            param_row(
+                ui,
+                "Quantize",
+                "Snap incoming keys to the scale before chords and voices",
+                &params.scale_quantize,
+                cx,
+            );
+            param_row(
+                ui,
+                "Root",
+                "Key the scale starts on",
+                &params.scale_root,
+                cx,
+            );
+            param_row(ui, "Scale", "Notes keys snap to", &params.scale, cx);
+            param_row(
+                ui,
+                "Snap",
+                "Move out-of-scale keys to the nearest note, or to the note below",
+                &params.scale_snap,
+                cx,
+            );
+        });
+        ui.add_space(theme.row_spacing);
+
+        ui.label("Degrees");
+        draw_scale_degrees(ui, params, cx.setter);
+    });
 
-                ui.add_space(15.0);
+    section(ui, theme, "Chord", |ui| {
+        param_grid(ui, theme, "chord", |ui| {
+            param_row(
+                ui,
+                "Mode",
+                "Off, play the interval chord, or play the learned chord",
+                &params.chord_mode,
+                cx,
+            );
+            param_row(
+                ui,
+                "Learn",
+                "Capture the next chord held on the keyboard",
+                &params.chord_learn,
+                cx,
+            );
+            param_row(
+                ui,
+                "MIDI Out",
+                "Send every note a key plays, chord and octaver included, to the host to \
+                 drive other instruments",
+                &params.midi_out,
+                cx,
+            );
+        });
+        ui.add_space(theme.row_spacing);
 
-                // Master section
-                ui.group(|ui| {
-                    ui.heading("Master");
-                    ui.add_space(5.0);
+        ui.label("Intervals");
+        ui.horizontal(|ui| {
+            for note in &params.chord_notes {
+                param_slider(ui, &note.interval, cx).on_hover_text(param_tooltip(
+                    "Semitones from the played key (0 = unused)",
+                    &note.interval,
+                ));
+            }
+        });
+        ui.add_space(theme.row_spacing);
 
-                    ui.label("Gain");
-                    ui.add(widgets::ParamSlider::for_param(&params.gain, setter));
+        let learned = params
+            .learned_chord
+            .read()
+            .map(|intervals| {
+                intervals
+                    .iter()
+                    .filter(|interval| **interval != 0)
+                    .map(|interval| format!("{interval:+}"))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .unwrap_or_default();
+        ui.label(format!(
+            "Learned: {}",
+            if learned.is_empty() { "none" } else { &learned }
+        ));
+        ui.label("With Learn on, hold a chord and release it to capture it");
+    });
 
-                    ui.add_space(5.0);
+    section(ui, theme, "Strum", |ui| {
+        param_grid(ui, theme, "strum", |ui| {
+            param_row(
+                ui,
+                "Enable",
+                "Spread notes that start together, chord notes included, like a strummed guitar",
+                &params.strum,
+                cx,
+            );
+            param_row(
+                ui,
+                "Time",
+                "Time from the first note of a strum to the last",
+                &params.strum_time_ms,
+                cx,
+            );
+            param_row(
+                ui,
+                "Direction",
+                "Lowest note first, highest note first, or switching every strum",
+                &params.strum_direction,
+                cx,
+            );
+        });
+    });
+
+    section(ui, theme, "Octaver", |ui| {
+        param_grid(ui, theme, "octaver", |ui| {
+            param_row(
+                ui,
+                "Enable",
+                "Layer every note, chord notes included, with the octave and fifth above",
+                &params.octaver,
+                cx,
+            );
+            param_row(
+                ui,
+                "Octave",
+                "Velocity of the octave above, relative to the note (0% = off)",
+                &params.octaver_octave_level,
+                cx,
+            );
+            param_row(
+                ui,
+                "Fifth",
+                "Velocity of the fifth above, relative to the note (0% = off)",
+                &params.octaver_fifth_level,
+                cx,
+            );
+        });
+    });
+
+    section(ui, theme, "Pattern", |ui| {
+        param_grid(ui, theme, "pattern", |ui| {
+            param_row(
+                ui,
+                "Enable",
+                "Play the pattern while the host transport runs, through humanize, scale, \
+                 chord and strum like played notes",
+                &params.pattern,
+                cx,
+            );
+            param_row(
+                ui,
+                "Bars",
+                "Length of the pattern loop, in bars of sixteenth-note steps in the host's \
+                 time signature (4/4 if it reports none)",
+                &params.pattern_bars,
+                cx,
+            );
+            param_row(
+                ui,
+                "Velocity",
+                "Velocity of every pattern note",
+                &params.pattern_velocity,
+                cx,
+            );
+            param_row(
+                ui,
+                "Swing",
+                "Delay the second step of each pair: 50% is straight, 66% a triplet \
+                 shuffle, 75% the hardest",
+                &params.pattern_swing,
+                cx,
+            );
+        });
+        ui.add_space(theme.row_spacing);
+
+        draw_piano_roll(ui, params, theme, pattern);
+        ui.label("Click a cell to add or remove a note, drag to set its length");
+    });
+}
+
+/// Master effect chain: order, drive, phaser and EQ, then the pump
+fn draw_fx_tab(ui: &mut egui::Ui, params: &NaughtyAndTenderParams, cx: &ParamUi, theme: &Theme) {
+    section(ui, theme, "Effect Chain", |ui| {
+        param_grid(ui, theme, "fx_chain", |ui| {
+            param_row(
+                ui,
+                "Bypass All",
+                "Bypass the whole master effect chain",
+                &params.fx_bypass,
+                cx,
+            );
+            param_row(
+                ui,
+                "DC Blocker",
+                "Remove the offset asymmetric drive can add, which wastes headroom and clicks",
+                &params.dc_blocker,
+                cx,
+            );
+        });
+        ui.add_space(theme.row_spacing);
+
+        ui.horizontal(|ui| {
+            param_slider(ui, &params.fx_slot_1, cx)
+                .on_hover_text("Effect in position 1 of the master chain");
+            ui.label("→");
+            param_slider(ui, &params.fx_slot_2, cx)
+                .on_hover_text("Effect in position 2 of the master chain");
+            ui.label("→");
+            param_slider(ui, &params.fx_slot_3, cx)
+                .on_hover_text("Effect in position 3 of the master chain");
+        });
+    });
+
+    section(ui, theme, "Drive", |ui| {
+        param_grid(ui, theme, "drive", |ui| {
+            param_row(
+                ui,
+                "Placement",
+                "Where the waveshaper runs: off, per voice or on the master bus",
+                &params.drive_placement,
+                cx,
+            );
+            param_row(
+                ui,
+                "Curve",
+                "Waveshaper transfer curve",
+                &params.drive_curve,
+                cx,
+            );
+            param_row(
+                ui,
+                "Amount",
+                "Gain into the waveshaper",
+                &params.drive_db,
+                cx,
+            );
+            param_row(
+                ui,
+                "Tone",
+                "Low-pass tone filter cutoff around the waveshaper",
+                &params.drive_tone_hz,
+                cx,
+            );
+            param_row(
+                ui,
+                "Tone Position",
+                "Tone filter before or after the waveshaper",
+                &params.drive_tone_position,
+                cx,
+            );
+            param_row(
+                ui,
+                "Oversampling",
+                "Oversampling factor; higher reduces aliasing at more CPU cost",
+                &params.drive_oversampling,
+                cx,
+            );
+            param_row(
+                ui,
+                "Mix",
+                "Dry/wet balance of the drive",
+                &params.drive_mix,
+                cx,
+            );
+        });
+    });
+
+    section(ui, theme, "Phaser", |ui| {
+        param_grid(ui, theme, "phaser", |ui| {
+            param_row(ui, "Enabled", "Run the phaser", &params.phaser_enabled, cx);
+            param_row(ui, "Rate", "Sweep LFO speed", &params.phaser_rate_hz, cx);
+            param_row(
+                ui,
+                "Depth",
+                "How far the notches sweep",
+                &params.phaser_depth,
+                cx,
+            );
+            param_row(
+                ui,
+                "Stages",
+                "Number of all-pass stages; more stages make more notches",
+                &params.phaser_stages,
+                cx,
+            );
+            param_row(
+                ui,
+                "Feedback",
+                "Output fed back into the stages for a sharper, ringing sweep",
+                &params.phaser_feedback,
+                cx,
+            );
+            param_row(
+                ui,
+                "Mix",
+                "Dry/wet balance of the phaser",
+                &params.phaser_mix,
+                cx,
+            );
+        });
+    });
+
+    section(ui, theme, "Master EQ", |ui| {
+        param_grid(ui, theme, "eq_switches", |ui| {
+            param_row(ui, "Enabled", "Run the master EQ", &params.eq_enabled, cx);
+            param_row(ui, "Mix", "Dry/wet balance of the EQ", &params.eq_mix, cx);
+        });
+        ui.add_space(theme.row_spacing);
+
+        draw_eq_response(ui, theme, &params.eq_settings());
+        ui.add_space(theme.row_spacing);
+
+        param_grid(ui, theme, "eq_bands", |ui| {
+            param_row(
+                ui,
+                "Low Freq",
+                "Low shelf corner frequency",
+                &params.eq_low_freq,
+                cx,
+            );
+            param_row(
+                ui,
+                "Low Gain",
+                "Low shelf boost or cut",
+                &params.eq_low_gain_db,
+                cx,
+            );
+            param_row(
+                ui,
+                "Mid Freq",
+                "Mid peak center frequency",
+                &params.eq_mid_freq,
+                cx,
+            );
+            param_row(
+                ui,
+                "Mid Gain",
+                "Mid peak boost or cut",
+                &params.eq_mid_gain_db,
+                cx,
+            );
+            param_row(
+                ui,
+                "Mid Q",
+                "Mid peak width; higher is narrower",
+                &params.eq_mid_q,
+                cx,
+            );
+            param_row(
+                ui,
+                "High Freq",
+                "High shelf corner frequency",
+                &params.eq_high_freq,
+                cx,
+            );
+            param_row(
+                ui,
+                "High Gain",
+                "High shelf boost or cut",
+                &params.eq_high_gain_db,
+                cx,
+            );
+        });
+    });
+
+    section(ui, theme, "Pump", |ui| {
+        param_grid(ui, theme, "pump", |ui| {
+            param_row(
+                ui,
+                "Enabled",
+                "Duck the master output on every division, like sidechain compression",
+                &params.pump_enabled,
+                cx,
+            );
+            param_row(
+                ui,
+                "Rate",
+                "Length of each pump cycle, locked to the host grid",
+                &params.pump_division,
+                cx,
+            );
+            param_row(
+                ui,
+                "Depth",
+                "How far the output ducks",
+                &params.pump_depth,
+                cx,
+            );
+            param_row(
+                ui,
+                "Shape",
+                "How the level recovers: a slow swell or a quick, punchy snap back",
+                &params.pump_shape,
+                cx,
+            );
+        });
+    });
+}
+
+/// Layer routing, master output, diagnostics and status
+fn draw_global_tab(
+    ui: &mut egui::Ui,
+    params: &NaughtyAndTenderParams,
+    cx: &ParamUi,
+    theme: &Theme,
+    diagnostics: &VoiceDiagnostics,
+    monitors: &mut MonitorPanels,
+) {
+    section(ui, theme, "Layers", |ui| {
+        param_grid(ui, theme, "layers", |ui| {
+            param_row(
+                ui,
+                "Mode",
+                "Which layers each note plays: A only, both, a keyboard split or a velocity switch",
+                &params.layer_mode,
+                cx,
+            );
+            param_row(
+                ui,
+                "Split",
+                "Lowest note played by layer B in split mode",
+                &params.split_note,
+                cx,
+            );
+            param_row(
+                ui,
+                "Velocity Split",
+                "Lowest velocity played by layer B in velocity mode",
+                &params.velocity_split,
+                cx,
+            );
+            param_row(
+                ui,
+                "Layer A Level",
+                "Output level of layer A",
+                &params.layer_a_level,
+                cx,
+            );
+            param_row(
+                ui,
+                "Layer B Level",
+                "Output level of layer B",
+                &params.layer_b.level,
+                cx,
+            );
+        });
+    });
+
+    section(ui, theme, "Audio Input", |ui| {
+        param_grid(ui, theme, "audio_input", |ui| {
+            param_row(
+                ui,
+                "Mode",
+                "Off, gated by notes through the voices, or always through layer A's filter and drive",
+                &params.input_mode,
+                cx,
+            );
+            param_row(
+                ui,
+                "Gain",
+                "Level of the incoming audio",
+                &params.input_gain,
+                cx,
+            );
+            param_row(
+                ui,
+                "Mix",
+                "In gated mode, blend from the voice engine to the input",
+                &params.input_mix,
+                cx,
+            );
+        });
+    });
+
+    section(ui, theme, "Master", |ui| {
+        param_grid(ui, theme, "master", |ui| {
+            param_row(ui, "Gain", "Master output gain", &params.gain, cx);
+            param_row(
+                ui,
+                "Master Tune",
+                "Pitch of A4 that every note is tuned to (440 Hz is standard, 415 Hz baroque)",
+                &params.master_tune_hz,
+                cx,
+            );
+            param_row(
+                ui,
+                "Fine Tune",
+                "Tuning offset on top of the master tune, in cents",
+                &params.fine_tune_cents,
+                cx,
+            );
+            param_row(
+                ui,
+                "Bend Range",
+                "Pitch change at full pitch bend, in semitones",
+                &params.bend_range,
+                cx,
+            );
+            param_row(
+                ui,
+                "MIDI Channel",
+                "Channel the synth responds to; Omni responds to every channel",
+                &params.midi_channel,
+                cx,
+            );
+            param_row(
+                ui,
+                "Active Voices",
+                "Voices currently sounding (read-only)",
+                &params.voice_count,
+                cx,
+            );
+            param_row(
+                ui,
+                "Bass Reserve",
+                "Never steal the lowest held note, so bass lines survive dense chords",
+                &params.bass_reserve,
+                cx,
+            );
+            param_row(
+                ui,
+                "Allocation",
+                "How notes pick a voice: first idle, round robin (next idle in turn) or strict rotation",
+                &params.voice_allocation,
+                cx,
+            );
+            param_row(
+                ui,
+                "Stealing",
+                "Which voice a note takes when all are busy: the oldest (releasing first) or the quietest",
+                &params.voice_stealing,
+                cx,
+            );
+            param_row(
+                ui,
+                "Same Note",
+                "Repeating a sounding note: restart it, stack another voice, or start a new voice and let the old one release",
+                &params.same_note,
+                cx,
+            );
+        });
+    });
+
+    section(ui, theme, "Stereo", |ui| {
+        param_grid(ui, theme, "stereo", |ui| {
+            param_row(
+                ui,
+                "Width",
+                "Stereo width after the effects: 0% is mono, 100% leaves the mix as it is, 200% doubles the side",
+                &params.stereo_width,
+                cx,
+            );
+            param_row(
+                ui,
+                "Width Limiter",
+                "Turn the side down when the mix gets so wide it would cancel in mono",
+                &params.width_limit,
+                cx,
+            );
+            param_row(
+                ui,
+                "Min Correlation",
+                "Correlation the limiter keeps the output above: 0 keeps the side from outweighing the mid, higher is narrower",
+                &params.min_correlation,
+                cx,
+            );
+        });
+        ui.add_space(theme.row_spacing);
+
+        draw_stereo(ui, &monitors.stereo);
+    });
+
+    section(ui, theme, "Choke Groups", |ui| {
+        param_grid(ui, theme, "choke", |ui| {
+            param_row(
+                ui,
+                "Policy",
+                "How a note cuts off the rest of its group: a fast fade, or a release at a tenth of its time",
+                &params.choke_policy,
+                cx,
+            );
+        });
+        ui.add_space(theme.row_spacing);
+
+        ui.label("Ranges");
+        for range in &params.choke_ranges {
+            ui.horizontal(|ui| {
+                param_slider(ui, &range.low, cx)
+                    .on_hover_text(param_tooltip("Lowest note of the range", &range.low));
+                param_slider(ui, &range.high, cx)
+                    .on_hover_text(param_tooltip("Highest note of the range", &range.high));
+                param_slider(ui, &range.group, cx).on_hover_text(param_tooltip(
+                    "Notes of one group cut each other off, like open and closed hi-hats",
+                    &range.group,
+                ));
+            });
+        }
+    });
+
+    // Voice allocation diagnostics
+    egui::CollapsingHeader::new("Voice Diagnostics").show(ui, |ui| {
+        param_grid(ui, theme, "watchdog", |ui| {
+            param_row(
+                ui,
+                "Stuck Note Timeout",
+                "Force-release notes held longer than this (Off = never)",
+                &params.stuck_timeout_s,
+                cx,
+            );
+        });
+        ui.add_space(theme.row_spacing);
+
+        draw_voice_diagnostics(ui, theme, diagnostics);
+        // Keep the panel live while it's open
+        ui.ctx().request_repaint();
+    });
+
+    // Pitch of the output (or the input in Always mode)
+    egui::CollapsingHeader::new("Tuner").show(ui, |ui| {
+        draw_tuner(ui, theme, &mut monitors.tuner);
+        ui.ctx().request_repaint();
+    });
+
+    // Waveform and spectrum of what the tuner hears
+    egui::CollapsingHeader::new("Scope").show(ui, |ui| {
+        draw_scope(ui, theme, &mut monitors.scope);
+        if !monitors.scope.frozen {
+            ui.ctx().request_repaint();
+        }
+    });
+
+    // Levels through the signal chain
+    egui::CollapsingHeader::new("Gain Staging").show(ui, |ui| {
+        draw_gain_staging(ui, theme, &mut monitors.gain_staging);
+        ui.ctx().request_repaint();
+    });
+
+    // Voice steals, stuck notes and recoveries on the audio thread
+    egui::CollapsingHeader::new("Log").show(ui, |ui| {
+        draw_log(ui, &mut monitors.log);
+        ui.ctx().request_repaint();
+    });
+    ui.add_space(theme.section_spacing);
+
+    section(ui, theme, "Status", |ui| {
+        ui.label("✅ Plugin loaded successfully");
+        ui.label("✅ MIDI synthesis active");
+        ui.label("✅ Polyphonic voice management (16 voices per layer)");
+        ui.label("✅ 4 waveforms available");
+        ui.label("✅ Full ADSR envelope control");
+    });
+}
 
-                    ui.label("Active Voices");
-                    ui.add(widgets::ParamSlider::for_param(
-                        &params.voice_count,
-                        setter,
-                    ));
+/// Morph position and the two snapshots it moves between
+fn draw_morph_section(
+    ui: &mut egui::Ui,
+    params: &NaughtyAndTenderParams,
+    cx: &ParamUi,
+    theme: &Theme,
+    morph: &mut PatchMorph,
+    param_list: &ParamList,
+) {
+    section(ui, theme, "Morph", |ui| {
+        param_grid(ui, theme, "morph", |ui| {
+            param_row(
+                ui,
+                "Morph",
+                "Position between snapshots A and B; stepped settings switch halfway",
+                &params.morph,
+                cx,
+            );
+        });
+        ui.add_space(theme.row_spacing);
+
+        ui.horizontal(|ui| {
+            for (slot, name) in [(MorphSlot::A, "A"), (MorphSlot::B, "B")] {
+                if ui
+                    .button(format!("Store {name}"))
+                    .on_hover_text(format!("Store the current sound as snapshot {name}"))
+                    .clicked()
+                {
+                    morph.store(slot, params, param_list);
+                }
+                ui.label(if morph.pair.get(slot).is_empty() {
+                    "empty"
+                } else {
+                    "stored"
                 });
+            }
+        });
+        if morph.pair.is_ready() {
+            ui.label("Voice settings and gain follow the morph: store again to keep edits");
+        } else {
+            ui.label("Store both snapshots to morph between them");
+        }
+    });
+}
 
-                ui.add_space(15.0);
+/// Patch morph, preset browser (search, category, favorites; double-click to load) and save form
+fn draw_presets_tab(
+    ui: &mut egui::Ui,
+    params: &NaughtyAndTenderParams,
+    cx: &ParamUi,
+    theme: &Theme,
+    browser: &mut PresetBrowser,
+    morph: &mut PatchMorph,
+    param_list: &ParamList,
+) {
+    draw_morph_section(ui, params, cx, theme, morph, param_list);
 
-                // Status information
-                ui.group(|ui| {
-                    ui.label("Status");
-                    ui.add_space(5.0);
+    let Some(dir) = browser.dir.clone() else {
+        section(ui, theme, "Presets", |ui| {
+            ui.label("No presets folder: the home directory couldn't be found");
+        });
+        return;
+    };
 
-                    ui.label("✅ Plugin loaded successfully");
-                    ui.label("✅ MIDI synthesis active");
-                    ui.label("✅ Polyphonic voice management (16 voices)");
-                    ui.label("✅ 4 waveforms available");
-                    ui.label("✅ Full ADSR envelope control");
+    section(ui, theme, "Browser", |ui| {
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut browser.query).hint_text("Search"));
+
+            egui::ComboBox::from_id_source("preset_category")
+                .selected_text(browser.category.as_deref().unwrap_or("All"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut browser.category, None, "All");
+                    for category in browser.index.categories() {
+                        ui.selectable_value(
+                            &mut browser.category,
+                            Some(category.to_string()),
+                            category,
+                        );
+                    }
                 });
+
+            ui.checkbox(&mut browser.favorites_only, "Favorites");
+
+            if ui
+                .add_enabled(!browser.scanning, egui::Button::new("Rescan"))
+                .clicked()
+            {
+                browser.rescan();
+            }
+        });
+        ui.add_space(theme.row_spacing);
+
+        if browser.scanning {
+            ui.label("Scanning presets...");
+        }
+
+        // Clicks are collected first: the list borrows the index
+        let mut toggled = None;
+        let mut load = None;
+        egui::ScrollArea::vertical()
+            .max_height(240.0)
+            .show(ui, |ui| {
+                let entries = browser
+                    .index
+                    .filter(&browser.query, browser.category.as_deref())
+                    .filter(|entry| {
+                        !browser.favorites_only || browser.favorites.contains(&entry.path)
+                    });
+
+                for entry in entries {
+                    ui.horizontal(|ui| {
+                        let favorite = browser.favorites.contains(&entry.path);
+                        let star = egui::RichText::new(if favorite { "★" } else { "☆" })
+                            .color(theme.accent);
+                        if ui
+                            .add(egui::Button::new(star).frame(false))
+                            .on_hover_text("Toggle favorite")
+                            .clicked()
+                        {
+                            toggled = Some(entry.path.clone());
+                        }
+
+                        let label = if entry.info.tags.is_empty() {
+                            format!("{} ({})", entry.info.name, entry.info.category)
+                        } else {
+                            format!(
+                                "{} ({}) - {}",
+                                entry.info.name,
+                                entry.info.category,
+                                entry.info.tags.join(", ")
+                            )
+                        };
+                        if ui
+                            .selectable_label(false, label)
+                            .on_hover_text("Double-click to load")
+                            .double_clicked()
+                        {
+                            load = Some(entry.path.clone());
+                        }
+                    });
+                }
             });
-        },
-    )
+
+        if let Some(path) = toggled {
+            browser.toggle_favorite(&path);
+        }
+        if let Some(path) = load {
+            browser.load(path);
+        }
+
+        if !browser.status.is_empty() {
+            ui.add_space(theme.row_spacing);
+            ui.label(&browser.status);
+        }
+    });
+
+    section(ui, theme, "Save Current Sound", |ui| {
+        param_grid(ui, theme, "preset_save", |ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut browser.save_info.name);
+            ui.end_row();
+
+            ui.label("Category");
+            ui.text_edit_singleline(&mut browser.save_info.category);
+            ui.end_row();
+
+            ui.label("Tags");
+            ui.add(egui::TextEdit::singleline(&mut browser.save_tags).hint_text("Comma separated"));
+            ui.end_row();
+        });
+        ui.add_space(theme.row_spacing);
+
+        let has_name = !browser.save_info.name.trim().is_empty();
+        if ui
+            .add_enabled(has_name, egui::Button::new("Save Preset"))
+            .clicked()
+        {
+            browser.save(param_list);
+        }
+        ui.label(format!("Folder: {}", dir.display()));
+    });
+
+    section(ui, theme, "Program Change", |ui| {
+        param_grid(ui, theme, "program_change", |ui| {
+            param_row(
+                ui,
+                "Transition",
+                "What sounding notes do when a preset loads: Fade and Reset Voices dip the output, Fade Voices and Let Ring play new notes at once",
+                &params.program_transition,
+                cx,
+            );
+        });
+        ui.add_space(theme.row_spacing);
+
+        ui.horizontal(|ui| {
+            ui.label("Bank");
+            ui.add(egui::DragValue::new(&mut browser.assign_program.bank).clamp_range(0..=16383));
+            ui.label("Program");
+            // Shown 1-128, as on most controllers
+            let mut program = browser.assign_program.program + 1;
+            ui.add(egui::DragValue::new(&mut program).clamp_range(1..=128));
+            browser.assign_program.program = program - 1;
+
+            let selected = if browser.assign_preset.is_empty() {
+                "Choose preset"
+            } else {
+                browser.assign_preset.as_str()
+            };
+            egui::ComboBox::from_id_source("program_preset")
+                .selected_text(selected.to_string())
+                .show_ui(ui, |ui| {
+                    for entry in browser.index.entries() {
+                        let name = &entry.info.name;
+                        ui.selectable_value(&mut browser.assign_preset, name.clone(), name);
+                    }
+                });
+
+            if ui
+                .add_enabled(
+                    !browser.assign_preset.is_empty(),
+                    egui::Button::new("Assign"),
+                )
+                .clicked()
+            {
+                browser
+                    .programs
+                    .assign(browser.assign_program, &browser.assign_preset);
+                params.set_program_map(&browser.programs);
+            }
+        });
+        ui.add_space(theme.row_spacing);
+
+        let mut removed = None;
+        for (program, name) in browser.programs.assignments() {
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "Bank {} Program {}: {name}",
+                    program.bank,
+                    program.program + 1
+                ));
+                if ui.small_button("Remove").clicked() {
+                    removed = Some(*program);
+                }
+            });
+        }
+        if let Some(program) = removed {
+            browser.programs.remove(program);
+            params.set_program_map(&browser.programs);
+        }
+        ui.label("Unassigned programs load presets in browser order, 128 per bank");
+    });
+}
+
+/// ADSR shape, each stage in its theme color
+///
+/// `times_ms` are the attack, decay and release times. Stages get widths in
+/// proportion to the square roots of their times, so a short attack next to a
+/// long release stays visible; the sustain is drawn at a fixed width.
+fn draw_envelope_graph(ui: &mut egui::Ui, theme: &Theme, times_ms: [f32; 3], sustain: f32) {
+    const WIDTH: f32 = 360.0;
+    const HEIGHT: f32 = 80.0;
+    const SUSTAIN_WIDTH: f32 = 0.2;
+
+    let (rect, _response) = ui.allocate_exact_size(egui::vec2(WIDTH, HEIGHT), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, theme.plot_background);
+
+    let [attack, decay, release] = times_ms.map(|ms| ms.max(0.0).sqrt());
+    let scale = (1.0 - SUSTAIN_WIDTH) / (attack + decay + release).max(f32::EPSILON);
+    let inner = rect.shrink(4.0);
+    let x = |fraction: f32| inner.left() + fraction * inner.width();
+    let y = |level: f32| inner.bottom() - level.clamp(0.0, 1.0) * inner.height();
+
+    let attack_end = attack * scale;
+    let decay_end = attack_end + decay * scale;
+    let sustain_end = decay_end + SUSTAIN_WIDTH;
+    let segments = [
+        (EnvelopeState::Attack, (0.0, 0.0), (attack_end, 1.0)),
+        (
+            EnvelopeState::Decay,
+            (attack_end, 1.0),
+            (decay_end, sustain),
+        ),
+        (
+            EnvelopeState::Sustain,
+            (decay_end, sustain),
+            (sustain_end, sustain),
+        ),
+        (EnvelopeState::Release, (sustain_end, sustain), (1.0, 0.0)),
+    ];
+    for (stage, (x0, y0), (x1, y1)) in segments {
+        painter.line_segment(
+            [egui::pos2(x(x0), y(y0)), egui::pos2(x(x1), y(y1))],
+            egui::Stroke::new(2.5, theme.stage_color(stage)),
+        );
+    }
+
+    // Stage initials under each segment, so color isn't the only cue
+    for (stage, (x0, _), (x1, _)) in segments {
+        let label = match stage {
+            EnvelopeState::Attack => "A",
+            EnvelopeState::Decay => "D",
+            EnvelopeState::Sustain => "S",
+            _ => "R",
+        };
+        painter.text(
+            egui::pos2(x((x0 + x1) * 0.5), inner.bottom()),
+            egui::Align2::CENTER_BOTTOM,
+            label,
+            egui::FontId::proportional(theme.body_size * 0.8),
+            theme.stage_color(stage),
+        );
+    }
+}
+
+/// Table of every voice slot: note, state, envelope stage, age and level
+fn draw_voice_diagnostics(ui: &mut egui::Ui, theme: &Theme, diagnostics: &VoiceDiagnostics) {
+    ui.label(format!("Voices stolen: {}", diagnostics.steal_count()));
+    ui.label(format!(
+        "Stuck notes released: {}",
+        diagnostics.stuck_release_count()
+    ));
+    ui.label(format!(
+        "Non-finite blocks silenced: {}",
+        diagnostics.recovery_count()
+    ))
+    .on_hover_text("Blocks where a voice or effect blew up to NaN or infinity and was reset");
+    ui.add_space(theme.row_spacing);
+
+    egui::Grid::new("voice_diagnostics")
+        .striped(true)
+        .show(ui, |ui| {
+            for header in ["Slot", "Note", "State", "Stage", "Age", "Level"] {
+                ui.strong(header);
+            }
+            ui.end_row();
+
+            for (index, voice) in diagnostics.snapshot().iter().enumerate() {
+                let idle = voice.state == VoiceState::Idle;
+
+                // Layer A's slots are published first
+                if index < NUM_VOICES {
+                    ui.label(format!("A{}", index + 1));
+                } else {
+                    ui.label(format!("B{}", index + 1 - NUM_VOICES));
+                }
+                ui.label(if idle { "-".to_string() } else { note_name(voice.note) });
+                ui.label(match voice.state {
+                    VoiceState::Idle => "Idle",
+                    VoiceState::Active => "Active",
+                    VoiceState::Releasing => "Releasing",
+                });
+                ui.colored_label(
+                    theme.stage_color(voice.stage),
+                    match voice.stage {
+                        EnvelopeState::Idle => "-",
+                        EnvelopeState::Attack => "Attack",
+                        EnvelopeState::Decay => "Decay",
+                        EnvelopeState::Sustain => "Sustain",
+                        EnvelopeState::Release => "Release",
+                    },
+                );
+                ui.label(if idle { "-".to_string() } else { voice.age.to_string() });
+                ui.add(
+                    egui::ProgressBar::new(voice.level)
+                        .desired_width(80.0)
+                        .fill(theme.accent),
+                );
+                ui.end_row();
+            }
+        });
+}
+
+/// Detected note, cents meter and frequency of the tuner tap
+fn draw_tuner(ui: &mut egui::Ui, theme: &Theme, panel: &mut TunerPanel) {
+    const WIDTH: f32 = 360.0;
+    const HEIGHT: f32 = 24.0;
+
+    panel.visible = true;
+    let now = ui.input(|input| input.time);
+    if let Some(reading) = panel.tuner.update(&panel.tap) {
+        panel.reading = Some(reading);
+        panel.read_at = now;
+    } else if now - panel.read_at > TUNER_HOLD_S {
+        panel.reading = None;
+    }
+
+    let Some(reading) = panel.reading else {
+        ui.label("No pitch: play a note (or feed the input in Always mode)");
+        return;
+    };
+    let in_tune = reading.cents.abs() <= TUNER_IN_TUNE_CENTS;
+
+    ui.horizontal(|ui| {
+        ui.heading(note_name(reading.note));
+        ui.label(format!("{:+.1} cents", reading.cents));
+        ui.label(format!("{:.2} Hz", reading.frequency_hz));
+    });
+
+    // Cents meter: -50 at the left, in tune in the middle, +50 at the right
+    let (rect, _response) = ui.allocate_exact_size(egui::vec2(WIDTH, HEIGHT), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, theme.plot_background);
+
+    let center_x = rect.center().x;
+    let in_tune_width = TUNER_IN_TUNE_CENTS / 50.0 * rect.width() * 0.5;
+    painter.rect_filled(
+        egui::Rect::from_x_y_ranges(
+            center_x - in_tune_width..=center_x + in_tune_width,
+            rect.y_range(),
+        ),
+        0.0,
+        theme.plot_muted,
+    );
+
+    let needle_x = center_x + reading.cents / 50.0 * rect.width() * 0.5;
+    let color = if in_tune {
+        theme.accent
+    } else {
+        ui.visuals().warn_fg_color
+    };
+    painter.line_segment(
+        [
+            egui::pos2(needle_x, rect.top()),
+            egui::pos2(needle_x, rect.bottom()),
+        ],
+        egui::Stroke::new(3.0, color),
+    );
+}
+
+/// Triggered waveform and log-frequency spectrum of the tuner tap, with freeze
+/// and export controls
+/// Offer to restore a crashed session's snapshot, returning whether it was
+/// restored
+fn draw_session_restore(
+    ui: &mut egui::Ui,
+    autosave: &mut SessionAutosave,
+    params: &ParamList,
+    setter: &ParamSetter,
+) -> bool {
+    if !autosave.status.is_empty() {
+        ui.label(&autosave.status);
+    }
+    let Some(age_minutes) = autosave.offer.as_ref().map(Snapshot::age_minutes) else {
+        return false;
+    };
+
+    let mut restored = false;
+    ui.horizontal(|ui| {
+        ui.label(format!(
+            "The last session ended unexpectedly; its sound was autosaved {age_minutes} min ago."
+        ));
+        if ui
+            .button("Restore Last Session")
+            .on_hover_text("Set every parameter to the autosaved snapshot (undoable)")
+            .clicked()
+        {
+            autosave.restore(params, setter);
+            restored = true;
+        }
+        if ui
+            .button("Dismiss")
+            .on_hover_text("Keep the current sound; the snapshot is replaced by the next autosave")
+            .clicked()
+        {
+            autosave.offer = None;
+        }
+    });
+    restored
+}
+
+fn draw_log(ui: &mut egui::Ui, panel: &mut LogPanel) {
+    ui.horizontal(|ui| {
+        if ui
+            .checkbox(&mut panel.to_file, "Write to file")
+            .on_hover_text("Append new lines to the file at this path")
+            .changed()
+        {
+            panel.status.clear();
+        }
+        ui.add(egui::TextEdit::singleline(&mut panel.path).hint_text("Log file path"));
+        if ui.button("Clear").clicked() {
+            panel.lines.clear();
+        }
+    });
+    if !panel.status.is_empty() {
+        ui.label(&panel.status);
+    }
+
+    egui::ScrollArea::vertical()
+        .max_height(160.0)
+        .stick_to_bottom(true)
+        .show(ui, |ui| {
+            if panel.lines.is_empty() {
+                ui.weak("Nothing logged");
+            }
+            for line in &panel.lines {
+                ui.monospace(line);
+            }
+        });
+}
+
+fn draw_scope(ui: &mut egui::Ui, theme: &Theme, panel: &mut ScopePanel) {
+    const WIDTH: f32 = 360.0;
+    const HEIGHT: f32 = 80.0;
+
+    panel.visible = true;
+    if !panel.frozen || panel.capture.is_none() {
+        panel.capture = Some(ScopeCapture::capture(&panel.tap));
+    }
+
+    ui.horizontal(|ui| {
+        ui.toggle_value(&mut panel.frozen, "Freeze")
+            .on_hover_text("Hold the current capture on screen");
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut panel.path)
+                .hint_text("Export path (.csv, .png or .wav)"),
+        );
+        let entered =
+            response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+        if ui
+            .button("Export")
+            .on_hover_text(
+                "Save the samples and spectrum as CSV, both plots as a PNG image, or the \
+                 samples as a 16-bit WAV file",
+            )
+            .clicked()
+            || entered
+        {
+            panel.export();
+        }
+        let selected = DitherMode::ALL
+            .iter()
+            .position(|&dither| dither == panel.dither);
+        egui::ComboBox::from_id_source("scope_dither")
+            .selected_text(DitherMode::NAMES[selected.unwrap_or_default()])
+            .show_ui(ui, |ui| {
+                for (dither, name) in DitherMode::ALL.into_iter().zip(DitherMode::NAMES) {
+                    ui.selectable_value(&mut panel.dither, dither, name);
+                }
+            })
+            .response
+            .on_hover_text("Dither for WAV exports, rounding the samples to 16 bits");
+    });
+
+    let Some(capture) = &panel.capture else {
+        return;
+    };
+
+    // Waveform, from the trigger point
+    let (rect, _response) = ui.allocate_exact_size(egui::vec2(WIDTH, HEIGHT), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, theme.plot_background);
+    painter.line_segment(
+        [rect.left_center(), rect.right_center()],
+        egui::Stroke::new(1.0, theme.plot_muted),
+    );
+    let view = capture.view();
+    #[allow(clippy::cast_precision_loss)] // View lengths are small
+    let points: Vec<egui::Pos2> = view
+        .iter()
+        .enumerate()
+        .map(|(index, sample)| {
+            egui::pos2(
+                rect.left() + index as f32 / view.len() as f32 * rect.width(),
+                rect.center().y - sample.clamp(-1.0, 1.0) * rect.height() * 0.5,
+            )
+        })
+        .collect();
+    painter.add(egui::Shape::line(
+        points,
+        egui::Stroke::new(1.5, theme.accent),
+    ));
+
+    // Spectrum, log frequency across with lines at 100 Hz, 1 kHz and 10 kHz
+    let (rect, _response) = ui.allocate_exact_size(egui::vec2(WIDTH, HEIGHT), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, theme.plot_background);
+    let nyquist = capture.sample_rate() / 2.0;
+    let span = (nyquist / SPECTRUM_MIN_HZ).ln();
+    for frequency_hz in [100.0_f32, 1000.0, 10000.0] {
+        let x = rect.left() + (frequency_hz / SPECTRUM_MIN_HZ).ln() / span * rect.width();
+        painter.line_segment(
+            [egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())],
+            egui::Stroke::new(1.0, theme.plot_muted),
+        );
+    }
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )] // Pixel positions
+    let points: Vec<egui::Pos2> = (0..=WIDTH as usize)
+        .map(|x| {
+            let position = x as f32 / WIDTH;
+            let level_db = capture.level_at_db(spectrum_frequency_hz(position, nyquist));
+            let height = (level_db / SPECTRUM_FLOOR_DB).clamp(0.0, 1.0);
+            egui::pos2(
+                rect.left() + position * rect.width(),
+                rect.top() + height * rect.height(),
+            )
+        })
+        .collect();
+    painter.add(egui::Shape::line(
+        points,
+        egui::Stroke::new(1.5, theme.accent),
+    ));
+
+    if !panel.status.is_empty() {
+        ui.label(&panel.status);
+    }
+}
+
+/// Mono check switch, output correlation and width limiter reduction
+fn draw_stereo(ui: &mut egui::Ui, stereo: &StereoMonitor) {
+    ui.horizontal(|ui| {
+        let mut mono = stereo.mono_check();
+        if ui
+            .checkbox(&mut mono, "Mono Check")
+            .on_hover_text("Fold the output to mono to hear how the mix sums (not saved)")
+            .changed()
+        {
+            stereo.set_mono_check(mono);
+        }
+
+        let correlation = stereo.correlation();
+        let text = format!("Correlation {correlation:+.2}");
+        if correlation < 0.0 {
+            ui.colored_label(ui.visuals().warn_fg_color, text);
+        } else {
+            ui.label(text);
+        }
+        ui.label(format!("Side -{:.1} dB", stereo.side_reduction_db()));
+    });
+
+    // Poll the audio thread for new readouts
+    ui.ctx()
+        .request_repaint_after(std::time::Duration::from_millis(30));
+}
+
+/// Peak level, history sparkline and clip count for each stage of the signal chain
+fn draw_gain_staging(ui: &mut egui::Ui, theme: &Theme, panel: &mut GainStagingPanel) {
+    const WIDTH: f32 = 240.0;
+    const HEIGHT: f32 = 28.0;
+
+    panel.visible = true;
+    for (history, peak) in panel.history.iter_mut().zip(panel.meters.take_peaks()) {
+        if history.len() == METER_HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(peak_to_dbfs(peak));
+    }
+
+    let warn = ui.visuals().warn_fg_color;
+    let db_to_y = |rect: egui::Rect, db: f32| {
+        let t = (db - METER_FLOOR_DB) / (METER_CEILING_DB - METER_FLOOR_DB);
+        rect.bottom() - t.clamp(0.0, 1.0) * rect.height()
+    };
+
+    egui::Grid::new("gain_staging")
+        .num_columns(4)
+        .spacing([theme.item_spacing * 1.5, theme.row_spacing])
+        .show(ui, |ui| {
+            for ((stage, name), history) in MeterStage::ALL
+                .into_iter()
+                .zip(MeterStage::NAMES)
+                .zip(&panel.history)
+            {
+                ui.label(name);
+
+                let level = history.back().copied().unwrap_or(METER_FLOOR_DB);
+                if level <= METER_FLOOR_DB {
+                    ui.label("-inf dBFS");
+                } else if level > 0.0 {
+                    ui.colored_label(warn, format!("{level:+.1} dBFS"));
+                } else {
+                    ui.label(format!("{level:.1} dBFS"));
+                }
+
+                let (rect, _response) =
+                    ui.allocate_exact_size(egui::vec2(WIDTH, HEIGHT), egui::Sense::hover());
+                let painter = ui.painter_at(rect);
+                painter.rect_filled(rect, 2.0, theme.plot_background);
+
+                // 0 dBFS: anything above it clips
+                let full_scale_y = db_to_y(rect, 0.0);
+                painter.line_segment(
+                    [
+                        egui::pos2(rect.left(), full_scale_y),
+                        egui::pos2(rect.right(), full_scale_y),
+                    ],
+                    egui::Stroke::new(1.0, warn),
+                );
+
+                // Newest level at the right edge
+                #[allow(clippy::cast_precision_loss)] // Short history
+                let points: Vec<egui::Pos2> = {
+                    let step = rect.width() / (METER_HISTORY_LEN - 1) as f32;
+                    let start = rect.right() - step * history.len().saturating_sub(1) as f32;
+                    history
+                        .iter()
+                        .enumerate()
+                        .map(|(i, &db)| egui::pos2(start + step * i as f32, db_to_y(rect, db)))
+                        .collect()
+                };
+                painter.add(egui::Shape::line(
+                    points,
+                    egui::Stroke::new(1.5, theme.accent),
+                ));
+
+                let clips = panel.meters.clip_count(stage);
+                if clips > 0 {
+                    ui.colored_label(warn, format!("{clips} clipped"))
+                        .on_hover_text("Samples over 0 dBFS since the plugin was loaded");
+                } else {
+                    ui.label("No clipping");
+                }
+                ui.end_row();
+            }
+        });
+}
+
+/// Notes of the current scale from the root up, clickable for the custom scale
+fn draw_scale_degrees(ui: &mut egui::Ui, params: &NaughtyAndTenderParams, setter: &ParamSetter) {
+    let mask = params.scale_mask();
+    let root = usize::from(params.scale_root());
+    let custom =
+        Scale::from_index(usize::try_from(params.scale.value()).unwrap_or(0)) == Scale::Custom;
+
+    ui.horizontal(|ui| {
+        ui.add_enabled_ui(custom, |ui| {
+            for (index, degree) in params.scale_degrees.iter().enumerate() {
+                let name = ROOT_NAMES[(root + index) % ROOT_NAMES.len()];
+                let selected = mask & (1 << index) != 0;
+                let response = ui
+                    .selectable_label(selected, name)
+                    .on_hover_text("Pick the notes of the custom scale");
+                if response.clicked() {
+                    setter.begin_set_parameter(&degree.enabled);
+                    setter.set_parameter(&degree.enabled, !degree.enabled.value());
+                    setter.end_set_parameter(&degree.enabled);
+                }
+            }
+        });
+    });
+}
+
+/// Gestures a drag widget has open, kept in egui's memory between frames
+fn drag_gestures(ui: &egui::Ui, response: &egui::Response) -> OpenGestures<usize> {
+    ui.data_mut(|data| data.get_temp(response.id))
+        .unwrap_or_default()
+}
+
+/// Gestures to end: all of them once the primary button is up (a click begins
+/// and ends in the same frame)
+fn drag_released(response: &egui::Response, gestures: &mut OpenGestures<usize>) -> Vec<usize> {
+    if response.dragged_by(egui::PointerButton::Primary) {
+        Vec::new()
+    } else {
+        gestures.close_all()
+    }
+}
+
+/// Editable step grid: one bar per step, drag to set values, right-click to toggle gates.
+/// In Euclidean mode the bars show the rhythm's gates, and steps past its length are dimmed
+fn draw_step_grid(
+    ui: &mut egui::Ui,
+    params: &NaughtyAndTenderParams,
+    setter: &ParamSetter,
+    theme: &Theme,
+) {
+    const WIDTH: f32 = 360.0;
+    const HEIGHT: f32 = 80.0;
+
+    let (rect, response) =
+        ui.allocate_exact_size(egui::vec2(WIDTH, HEIGHT), egui::Sense::click_and_drag());
+    let painter = ui.painter_at(rect);
+
+    painter.rect_filled(rect, 2.0, theme.plot_background);
+
+    #[allow(clippy::cast_precision_loss)] // NUM_STEPS is tiny
+    let step_width = rect.width() / NUM_STEPS as f32;
+
+    // From the keyboard: arrows pick and move a step, Space or Enter toggles its gate
+    let keyboard_click = keyboard_click(ui, &response);
+    let cursor = bar_cursor(ui, &response, NUM_STEPS);
+    if let Some(index) = cursor {
+        let step = &params.seq_steps[index];
+        if let Some((nudge, fine)) = pressed_nudge(ui, &VALUE_KEYS) {
+            nudge_param(&step.value, setter, nudge, fine);
+        }
+        if keyboard_click {
+            setter.begin_set_parameter(&step.gate);
+            setter.set_parameter(&step.gate, !step.gate.value());
+            setter.end_set_parameter(&step.gate);
+        }
+        response.widget_info(|| {
+            egui::WidgetInfo::labeled(
+                egui::WidgetType::Other,
+                true,
+                format!(
+                    "Step {} of {NUM_STEPS}: {}, gate {}",
+                    index + 1,
+                    step.value,
+                    if step.gate.value() { "on" } else { "off" }
+                ),
+            )
+        });
+    }
+
+    // Edit the step under the pointer; a drag across steps is one gesture per step
+    let mut gestures = drag_gestures(ui, &response);
+    if let Some(pointer) = response.interact_pointer_pos().filter(|_| !keyboard_click) {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Clamped to the grid
+        let index = (((pointer.x - rect.left()) / step_width).max(0.0) as usize).min(NUM_STEPS - 1);
+        let step = &params.seq_steps[index];
+
+        if response.secondary_clicked() {
+            setter.begin_set_parameter(&step.gate);
+            setter.set_parameter(&step.gate, !step.gate.value());
+            setter.end_set_parameter(&step.gate);
+        } else if response.clicked() || response.dragged_by(egui::PointerButton::Primary) {
+            let value = ((rect.bottom() - pointer.y) / rect.height()).clamp(0.0, 1.0);
+            if gestures.touch(index, 0.0) {
+                setter.begin_set_parameter(&step.value);
+            }
+            setter.set_parameter(&step.value, value);
+        }
+    }
+    for index in drag_released(&response, &mut gestures) {
+        setter.end_set_parameter(&params.seq_steps[index].value);
+    }
+    ui.data_mut(|data| data.insert_temp(response.id, gestures));
+
+    let euclid = params.seq_euclid();
+    let euclid_gates = euclid.map(Euclid::gates);
+    let length = euclid.map_or(NUM_STEPS, Euclid::length);
+    for (i, step) in params.seq_steps.iter().enumerate() {
+        #[allow(clippy::cast_precision_loss)] // NUM_STEPS is tiny
+        let left = rect.left() + i as f32 * step_width;
+        let top = rect.bottom() - step.value.value() * rect.height();
+        let gate = euclid_gates.map_or(step.gate.value(), |gates| gates[i]);
+        let color = if i >= length {
+            theme.panel
+        } else if gate {
+            theme.accent
+        } else {
+            theme.plot_muted
+        };
+
+        painter.rect_filled(
+            egui::Rect::from_min_max(
+                egui::pos2(left + 1.0, top),
+                egui::pos2(left + step_width - 1.0, rect.bottom()),
+            ),
+            1.0,
+            color,
+        );
+        if cursor == Some(i) {
+            cursor_marker(
+                &painter,
+                ui,
+                left + 1.0,
+                left + step_width - 1.0,
+                rect.bottom(),
+            );
+        }
+    }
+}
+
+/// Pattern piano roll: a row per note, a column per sixteenth step. Click to
+/// add or remove a note, drag from a note to stretch it.
+fn draw_piano_roll(
+    ui: &mut egui::Ui,
+    params: &NaughtyAndTenderParams,
+    theme: &Theme,
+    editor: &mut PatternEditor,
+) {
+    const WIDTH: f32 = 480.0;
+    const ROW_HEIGHT: f32 = 9.0;
+    const ROWS: u8 = 24;
+
+    ui.horizontal(|ui| {
+        if ui.button("Octave Down").clicked() {
+            editor.low_note = editor.low_note.saturating_sub(12);
+        }
+        if ui.button("Octave Up").clicked() {
+            editor.low_note = (editor.low_note + 12).min(128 - ROWS);
+        }
+        ui.label(format!(
+            "{} - {}",
+            note_name(editor.low_note),
+            note_name(editor.low_note + ROWS - 1)
+        ));
+        if ui.button("Clear").clicked() {
+            params.set_pattern_notes(&PatternNotes::new());
+        }
+        ui.menu_button("Preset", |ui| {
+            for (preset, name) in PatternPreset::ALL.into_iter().zip(PatternPreset::NAMES) {
+                if ui.button(name).clicked() {
+                    let bar_steps = editor.playhead.bar_steps();
+                    let notes = preset.notes(editor.cursor.1, bar_steps, params.pattern_bars());
+                    params.set_pattern_notes(&notes);
+                    ui.close_menu();
+                }
+            }
+        })
+        .response
+        .on_hover_text("Replace the pattern with a rhythm on the cursor's note");
+    });
+
+    let bar_steps = editor.playhead.bar_steps();
+    let steps = (params.pattern_bars() * bar_steps).min(MAX_STEPS);
+    let (rect, response) = ui.allocate_exact_size(
+        egui::vec2(WIDTH, ROW_HEIGHT * f32::from(ROWS)),
+        egui::Sense::click_and_drag(),
+    );
+    let painter = ui.painter_at(rect);
+
+    // Edit a copy, saved back once changed
+    let mut notes = params.pattern_notes();
+    let mut edited = false;
+
+    // From the keyboard: arrows move a cursor cell (scrolling to keep it in
+    // view), Space or Enter adds or removes a note there
+    let keyboard_click = keyboard_click(ui, &response);
+    if response.has_focus() {
+        hold_arrow_keys(ui, &response);
+        let (step, note) = &mut editor.cursor;
+        if let Some((nudge, _)) = pressed_nudge(ui, &CURSOR_KEYS) {
+            *step = nudge.move_index(*step, steps);
+        }
+        if let Some((nudge, _)) = pressed_nudge(ui, &VALUE_KEYS) {
+            #[allow(clippy::cast_possible_truncation)] // At most 127
+            let moved = nudge.move_index(usize::from(*note), 128) as u8;
+            *note = moved;
+        }
+        *step = (*step).min(steps - 1);
+        if *note < editor.low_note {
+            editor.low_note = *note;
+        } else if *note >= editor.low_note + ROWS {
+            editor.low_note = *note + 1 - ROWS;
+        }
+        if keyboard_click {
+            toggle_note(&mut notes, *step, *note);
+            edited = true;
+        }
+        response.widget_info(|| {
+            let (step, note) = editor.cursor;
+            let state = if note_index_at(&notes, step, note).is_some() {
+                "note"
+            } else {
+                "empty"
+            };
+            egui::WidgetInfo::labeled(
+                egui::WidgetType::Other,
+                true,
+                format!("Pattern step {}, {}: {state}", step + 1, note_name(note)),
+            )
+        });
+    }
+
+    #[allow(clippy::cast_precision_loss)] // At most MAX_STEPS
+    let step_width = rect.width() / steps as f32;
+    #[allow(clippy::cast_precision_loss)] // Grid positions
+    let step_x = |step: usize| rect.left() + step as f32 * step_width;
+    let row_top = |note: u8| rect.bottom() - f32::from(note - editor.low_note + 1) * ROW_HEIGHT;
+    let cell_at = |pos: egui::Pos2| {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Clamped to the grid
+        let step = (((pos.x - rect.left()) / step_width).max(0.0) as usize).min(steps - 1);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Clamped to the grid
+        let row = (((rect.bottom() - pos.y) / ROW_HEIGHT).max(0.0) as u8).min(ROWS - 1);
+        (step, editor.low_note + row)
+    };
+
+    if response.drag_started_by(egui::PointerButton::Primary) {
+        // From the cell pressed, not where the drag got going
+        if let Some(origin) = ui.input(|input| input.pointer.press_origin()) {
+            let (step, note) = cell_at(origin);
+            let start =
+                note_index_at(&notes, step, note).map_or(step, |index| usize::from(notes[index].0));
+            editor.stretching = Some((start, note));
+        }
+    }
+    if response.dragged_by(egui::PointerButton::Primary) {
+        if let (Some((start, note)), Some(pointer)) =
+            (editor.stretching, response.interact_pointer_pos())
+        {
+            stretch_note(&mut notes, start, note, cell_at(pointer).0);
+            edited = true;
+        }
+    } else {
+        editor.stretching = None;
+    }
+    if response.clicked() && !keyboard_click {
+        if let Some(pointer) = response.interact_pointer_pos() {
+            let (step, note) = cell_at(pointer);
+            toggle_note(&mut notes, step, note);
+            edited = true;
+        }
+    }
+    if edited {
+        params.set_pattern_notes(&notes);
+    }
+
+    // Black-key rows shaded, then the playhead, bar and beat lines, and the notes
+    painter.rect_filled(rect, 2.0, theme.plot_background);
+    for note in editor.low_note..editor.low_note + ROWS {
+        if matches!(note % 12, 1 | 3 | 6 | 8 | 10) {
+            let top = row_top(note);
+            painter.rect_filled(
+                egui::Rect::from_x_y_ranges(rect.x_range(), top..=top + ROW_HEIGHT),
+                0.0,
+                theme.panel,
+            );
+        }
+    }
+    if let Some(step) = editor.playhead.step().filter(|&step| step < steps) {
+        painter.rect_filled(
+            egui::Rect::from_x_y_ranges(step_x(step)..=step_x(step + 1), rect.y_range()),
+            0.0,
+            theme.plot_muted,
+        );
+    }
+    for step in (0..steps).filter(|step| step % bar_steps % STEPS_PER_BEAT == 0) {
+        let width = if step % bar_steps == 0 { 1.5 } else { 0.5 };
+        painter.line_segment(
+            [
+                egui::pos2(step_x(step), rect.top()),
+                egui::pos2(step_x(step), rect.bottom()),
+            ],
+            egui::Stroke::new(width, theme.plot_muted),
+        );
+    }
+    for &(start, note, length) in &notes {
+        let start = usize::from(start);
+        let visible = (editor.low_note..editor.low_note + ROWS).contains(&note);
+        if visible && start < steps {
+            let end = (start + usize::from(length)).min(steps);
+            let top = row_top(note);
+            painter.rect_filled(
+                egui::Rect::from_min_max(
+                    egui::pos2(step_x(start) + 1.0, top + 1.0),
+                    egui::pos2(step_x(end) - 1.0, top + ROW_HEIGHT - 1.0),
+                ),
+                1.0,
+                theme.accent,
+            );
+        }
+    }
+    if response.has_focus() {
+        let (step, note) = editor.cursor;
+        let bottom = row_top(note) + ROW_HEIGHT;
+        cursor_marker(&painter, ui, step_x(step), step_x(step + 1), bottom);
+    }
+}
+
+/// Editable harmonic spectrum: one bar per additive partial, drag to set levels,
+/// right-click to silence. Ticks show each level after the spectral tilt.
+fn draw_harmonic_editor(
+    ui: &mut egui::Ui,
+    params: &NaughtyAndTenderParams,
+    setter: &ParamSetter,
+    theme: &Theme,
+) {
+    const WIDTH: f32 = 360.0;
+    const HEIGHT: f32 = 80.0;
+
+    let (rect, response) =
+        ui.allocate_exact_size(egui::vec2(WIDTH, HEIGHT), egui::Sense::click_and_drag());
+    let painter = ui.painter_at(rect);
+
+    painter.rect_filled(rect, 2.0, theme.plot_background);
+
+    #[allow(clippy::cast_precision_loss)] // NUM_PARTIALS is tiny
+    let bar_width = rect.width() / NUM_PARTIALS as f32;
+
+    // From the keyboard: arrows pick a partial and move its level
+    let keyboard_click = keyboard_click(ui, &response);
+    let cursor = bar_cursor(ui, &response, NUM_PARTIALS);
+    if let Some(index) = cursor {
+        let level = &params.partials[index].level;
+        if let Some((nudge, fine)) = pressed_nudge(ui, &VALUE_KEYS) {
+            nudge_param(level, setter, nudge, fine);
+        }
+        response.widget_info(|| {
+            egui::WidgetInfo::slider(
+                true,
+                f64::from(level.value()),
+                format!("Partial {} level", index + 1),
+            )
+        });
+    }
+
+    // Edit the partial under the pointer; a drag across partials is one gesture
+    // per partial
+    let mut gestures = drag_gestures(ui, &response);
+    if let Some(pointer) = response.interact_pointer_pos().filter(|_| !keyboard_click) {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Clamped to the graph
+        let index =
+            (((pointer.x - rect.left()) / bar_width).max(0.0) as usize).min(NUM_PARTIALS - 1);
+        let level = &params.partials[index].level;
+
+        if response.secondary_clicked() {
+            setter.begin_set_parameter(level);
+            setter.set_parameter(level, 0.0);
+            setter.end_set_parameter(level);
+        } else if response.clicked() || response.dragged_by(egui::PointerButton::Primary) {
+            let value = ((rect.bottom() - pointer.y) / rect.height()).clamp(0.0, 1.0);
+            if gestures.touch(index, 0.0) {
+                setter.begin_set_parameter(level);
+            }
+            setter.set_parameter(level, value);
+        }
+    }
+    for index in drag_released(&response, &mut gestures) {
+        setter.end_set_parameter(&params.partials[index].level);
+    }
+    ui.data_mut(|data| data.insert_temp(response.id, gestures));
+
+    let tilt = params.additive_tilt.value();
+    for (i, partial) in params.partials.iter().enumerate() {
+        #[allow(clippy::cast_precision_loss)] // NUM_PARTIALS is tiny
+        let left = rect.left() + i as f32 * bar_width;
+        let level = partial.level.value();
+
+        painter.rect_filled(
+            egui::Rect::from_min_max(
+                egui::pos2(left + 1.0, rect.bottom() - level * rect.height()),
+                egui::pos2(left + bar_width - 1.0, rect.bottom()),
+            ),
+            1.0,
+            theme.accent,
+        );
+        if cursor == Some(i) {
+            cursor_marker(
+                &painter,
+                ui,
+                left + 1.0,
+                left + bar_width - 1.0,
+                rect.bottom(),
+            );
+        }
+
+        #[allow(clippy::cast_precision_loss)] // NUM_PARTIALS is tiny
+        let octaves = ((i + 1) as f32).log2();
+        let tilted = (level * 10.0_f32.powf(tilt * octaves / 20.0)).min(1.0);
+        let y = rect.bottom() - tilted * rect.height();
+        painter.line_segment(
+            [
+                egui::pos2(left + 1.0, y),
+                egui::pos2(left + bar_width - 1.0, y),
+            ],
+            egui::Stroke::new(1.5, theme.plot_muted),
+        );
+    }
+}
+
+/// Draw the combined EQ magnitude response on a log-frequency axis (20 Hz - 20 kHz, ±18 dB)
+fn draw_eq_response(ui: &mut egui::Ui, theme: &Theme, settings: &EqSettings) {
+    const WIDTH: f32 = 360.0;
+    const HEIGHT: f32 = 90.0;
+    const DB_RANGE: f32 = 18.0;
+    const NUM_POINTS: usize = 128;
+
+    let (rect, _response) =
+        ui.allocate_exact_size(egui::vec2(WIDTH, HEIGHT), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+
+    painter.rect_filled(rect, 2.0, theme.plot_background);
+
+    // 0 dB reference line
+    let center_y = rect.center().y;
+    painter.line_segment(
+        [egui::pos2(rect.left(), center_y), egui::pos2(rect.right(), center_y)],
+        egui::Stroke::new(1.0, theme.plot_muted),
+    );
+
+    let (log_min, log_max) = (20.0f32.log10(), 20000.0f32.log10());
+    #[allow(clippy::cast_precision_loss)] // Small point counts
+    let points: Vec<egui::Pos2> = (0..NUM_POINTS)
+        .map(|i| {
+            let t = i as f32 / (NUM_POINTS - 1) as f32;
+            let frequency = 10.0f32.powf(log_min + t * (log_max - log_min));
+            let db = settings
+                .magnitude_db(DISPLAY_SAMPLE_RATE, frequency)
+                .clamp(-DB_RANGE, DB_RANGE);
+
+            egui::pos2(
+                rect.left() + t * rect.width(),
+                center_y - (db / DB_RANGE) * (rect.height() * 0.5),
+            )
+        })
+        .collect();
+
+    painter.add(egui::Shape::line(
+        points,
+        egui::Stroke::new(1.5, theme.accent),
+    ));
 }
@@ -0,0 +1,1611 @@
+//! Host-independent audio engine for Naughty and Tender
+//!
+//! Everything the plugin does to render a block lives here, behind plain types.
+//! Once per block the plugin reads its parameters into [`EngineSettings`] and
+//! the host's playhead into a [`HostTransport`], then hands [`Engine::process`]
+//! its audio buffers and an [`EngineContext`]: the host's note events come in
+//! through it and notes for the MIDI output go back out. Nothing here knows
+//! about the plugin format, so tests drive the same render path a host does.
+//!
+//! Rendering runs sample by sample: at each sample the engine takes the host's
+//! events due, feeds the notes through the channel filter, humanizer, scale,
+//! chord memory, strummer and octaver, renders one frame from each layer and
+//! runs it through the master chain, DC blocker, width limiter, gain, pump and
+//! bypass fade. Given the same settings, the output doesn't depend on how the
+//! host sizes its blocks.
+//!
+//! # References
+//! - nih-plug `Plugin::process`, `ProcessContext` and `ProcessStatus`: the host
+//!   side this engine is driven from
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use std::sync::Arc;
+
+use shared_core::dc_blocker::DcBlocker;
+use shared_core::effects::phaser::MIN_STAGES;
+use shared_core::events::{Event, EventQueue};
+use shared_core::mid_side;
+use shared_core::tempo::{NoteDivision, DEFAULT_TEMPO_BPM};
+
+use crate::audio_log::{AudioLog, LogEvent, TotalWatch};
+use crate::audition::AUDITION_QUEUE_CAPACITY;
+use crate::bypass::BypassFade;
+use crate::cc_map::{CcInbox, Controller, ControllerDecoder};
+use crate::channel_filter::ChannelFilter;
+use crate::chord::{ChordIntervals, ChordMemory};
+use crate::diagnostics::{MidiActivity, VoiceDiagnostics};
+use crate::eq::EqSettings;
+use crate::expression::{ExpressionCurve, ExpressionInput};
+use crate::follower::EnvelopeFollower;
+use crate::humanize::{Humanizer, KeyEvent};
+use crate::input::{InputMode, InputProcessor};
+use crate::layers::{LayerMode, LayerRouter};
+use crate::master_fx::{
+    self, MasterChain, MasterEffect, DRIVE_SLOT, EQ_SLOT, NUM_SLOTS, PHASER_SLOT,
+};
+use crate::metering::{GainStaging, MeterStage, StagePeaks};
+use crate::midi_out::MidiOutNotes;
+use crate::modulation::{ModMonitor, ModOffsets, ModSourceValues};
+use crate::note_expression::{ExpressionTable, NoteExpression};
+use crate::octaver::Octaver;
+use crate::pattern::{PatternPlayer, PatternPlayhead, STRAIGHT_SWING};
+use crate::pitch_bend;
+use crate::poly_mod::{PolyModTable, PolyOffsets, PolyTarget};
+#[cfg(feature = "debug-outputs")]
+use crate::probe::NUM_PROBES;
+use crate::programs::{BankSelect, PatchFade, ProgramInbox, ProgramTransition};
+use crate::pump::Pump;
+use crate::sampler::SampleSlot;
+use crate::scale::{ScaleMask, ScaleQuantizer, SnapMode};
+use crate::sequencer::{Euclid, Step, StepSequencer, NUM_STEPS};
+use crate::slew::{Slew, GAIN_SLEW_MS};
+use crate::strum::{StrumDirection, Strummer};
+use crate::sub_block::BlockScratch;
+use crate::tuner::AudioTap;
+use crate::voice::{
+    fallback_voice_id, ChokePolicy, ChokeRange, SameNotePolicy, VoiceAllocation, VoiceManager,
+    VoiceParams, VoiceStealing, VoiceTag, NUM_CHOKE_RANGES,
+};
+use crate::width::{self, StereoMonitor, WidthLimiter};
+
+/// Maximum polyphony per layer
+pub const NUM_VOICES: usize = 16;
+
+/// Host tempos the synced times accept, in BPM; others are clamped (and logged)
+const MIN_HOST_TEMPO_BPM: f32 = 10.0;
+const MAX_HOST_TEMPO_BPM: f32 = 999.0;
+
+/// A note event from the host, without its timing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HostEvent {
+    NoteOn {
+        voice_id: Option<i32>,
+        channel: u8,
+        note: u8,
+        velocity: f32,
+    },
+    NoteOff {
+        voice_id: Option<i32>,
+        channel: u8,
+        note: u8,
+        velocity: f32,
+    },
+    /// Cut the key's voices at once
+    Choke {
+        voice_id: Option<i32>,
+        channel: u8,
+        note: u8,
+    },
+    /// Control change, value 0.0 - 1.0
+    Cc {
+        channel: u8,
+        cc: u8,
+        value: f32,
+    },
+    /// Pitch wheel, value 0.0 - 1.0 (0.5 = centered)
+    PitchBend {
+        channel: u8,
+        value: f32,
+    },
+    /// Note expression gain of one voice
+    PolyVolume {
+        voice_id: Option<i32>,
+        channel: u8,
+        note: u8,
+        gain: f32,
+    },
+    /// Note expression pan of one voice (-1.0 - 1.0)
+    PolyPan {
+        voice_id: Option<i32>,
+        channel: u8,
+        note: u8,
+        pan: f32,
+    },
+    /// Note expression tuning of one voice, in semitones
+    PolyTuning {
+        voice_id: Option<i32>,
+        channel: u8,
+        note: u8,
+        tuning: f32,
+    },
+    /// Host poly modulation of one voice
+    PolyModulation {
+        voice_id: i32,
+        poly_modulation_id: u32,
+        normalized_offset: f32,
+    },
+    /// A poly-modulated parameter moved
+    MonoAutomation,
+    ProgramChange {
+        channel: u8,
+        program: u8,
+    },
+    /// Anything else (it still lights the MIDI activity LED)
+    Other {
+        channel: Option<u8>,
+    },
+}
+
+impl HostEvent {
+    /// MIDI channel the event is on, if it has one
+    #[must_use]
+    pub fn channel(self) -> Option<u8> {
+        match self {
+            Self::NoteOn { channel, .. }
+            | Self::NoteOff { channel, .. }
+            | Self::Choke { channel, .. }
+            | Self::Cc { channel, .. }
+            | Self::PitchBend { channel, .. }
+            | Self::PolyVolume { channel, .. }
+            | Self::PolyPan { channel, .. }
+            | Self::PolyTuning { channel, .. }
+            | Self::ProgramChange { channel, .. } => Some(channel),
+            Self::Other { channel } => channel,
+            Self::PolyModulation { .. } | Self::MonoAutomation => None,
+        }
+    }
+}
+
+/// An event the engine sends back to the host
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputEvent {
+    /// A note for the MIDI output
+    NoteOn {
+        channel: u8,
+        note: u8,
+        velocity: f32,
+    },
+    NoteOff {
+        channel: u8,
+        note: u8,
+        velocity: f32,
+    },
+    /// The last voice carrying a host voice id ended
+    VoiceTerminated {
+        voice_id: i32,
+        channel: u8,
+        note: u8,
+    },
+}
+
+/// What the engine needs from the host during a block
+pub trait EngineContext {
+    /// Next incoming event and its sample offset, in timing order
+    fn next_event(&mut self) -> Option<(u32, HostEvent)>;
+
+    /// Send an event to the host at a sample offset
+    fn send_event(&mut self, timing: u32, event: OutputEvent);
+
+    /// Voice offsets for a voice's host poly modulation (normalized offsets by
+    /// [`PolyTarget`]), against the parameters' current values
+    fn poly_offsets(&self, normalized: &PolyOffsets) -> ModOffsets;
+
+    /// Take one sample of the debug probes, newest layer A voice
+    #[cfg(feature = "debug-outputs")]
+    fn probe(&mut self, _sample: usize, _taps: [f32; NUM_PROBES]) {}
+}
+
+/// The host's playhead at the start of a block
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HostTransport {
+    pub playing: bool,
+
+    /// Song position in quarter notes, if the host reports one
+    pub pos_beats: Option<f64>,
+
+    /// Bar number and the position its bar started at, in quarter notes
+    pub bar: Option<(i32, f64)>,
+
+    /// Numerator and denominator
+    pub time_signature: (i32, i32),
+}
+
+impl Default for HostTransport {
+    fn default() -> Self {
+        Self {
+            playing: false,
+            pos_beats: None,
+            bar: None,
+            time_signature: (4, 4),
+        }
+    }
+}
+
+/// What the host should do once a block is rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockStatus {
+    /// Keep calling, the output follows the input
+    Normal,
+    /// Silent after this many more samples unless new notes arrive
+    Tail(u32),
+    /// Keep calling even without input
+    KeepAlive,
+}
+
+/// Everything the engine reads from the parameters, once per block
+///
+/// Values are in plain units, with switched-off sections already at their
+/// neutral settings (a disabled pump at zero depth, say).
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::struct_excessive_bools)] // Independent settings, not a state machine
+pub struct EngineSettings {
+    /// Host bypass switch
+    pub bypassed: bool,
+
+    /// Output gain (linear) and how expression scales it
+    pub gain: f32,
+    pub expression_curve: ExpressionCurve,
+
+    /// Host tempo, clamped (see [`Engine::host_tempo`])
+    pub tempo_bpm: f32,
+
+    /// Voice settings of each layer
+    pub layer_a: VoiceParams,
+    pub layer_b: VoiceParams,
+
+    /// Layer routing and mix
+    pub layer_mode: LayerMode,
+    pub split_note: u8,
+    pub velocity_split: f32,
+    pub layer_a_level: f32,
+    pub layer_b_level: f32,
+
+    /// Voice allocation, shared by both layers
+    pub release_velocity: f32,
+    pub stuck_timeout_ms: f32,
+    pub bass_reserve: bool,
+    pub allocation: VoiceAllocation,
+    pub stealing: VoiceStealing,
+    pub same_note_policy: SameNotePolicy,
+    pub choke_ranges: [ChokeRange; NUM_CHOKE_RANGES],
+    pub choke_policy: ChokePolicy,
+
+    /// Master chain: drive on the master bus (with layer A's shaper
+    /// settings), the phaser and the EQ
+    pub master_drive: bool,
+    pub phaser_enabled: bool,
+    pub phaser_rate_hz: f32,
+    pub phaser_depth: f32,
+    pub phaser_feedback: f32,
+    pub phaser_stages: usize,
+    pub phaser_mix: f32,
+    pub eq_enabled: bool,
+    pub eq: EqSettings,
+    pub eq_mix: f32,
+    pub fx_order: [usize; NUM_SLOTS],
+    pub fx_bypass: bool,
+
+    /// DC blocker, stereo width and the width limiter
+    pub dc_blocker: bool,
+    pub stereo_width: f32,
+    pub width_limit: bool,
+    pub min_correlation: f32,
+
+    /// Step sequencer
+    pub seq_steps: [Step; NUM_STEPS],
+    pub seq_euclid: Option<Euclid>,
+    pub seq_division: NoteDivision,
+    pub seq_slew_ms: f32,
+
+    /// Built-in pattern
+    pub pattern: bool,
+    pub pattern_bars: usize,
+    pub pattern_swing: f32,
+    pub pattern_velocity: f32,
+
+    /// Pump (zero depth when off)
+    pub pump_division: NoteDivision,
+    pub pump_depth: f32,
+    pub pump_shape: f32,
+
+    /// Channel notes are accepted on (`None` = omni)
+    pub midi_channel: Option<u8>,
+
+    /// Humanization (zero when off)
+    pub humanize_timing_ms: f32,
+    pub humanize_velocity: f32,
+
+    /// Scale quantization
+    pub scale_quantize: bool,
+    pub scale_root: u8,
+    pub scale_mask: ScaleMask,
+    pub scale_snap: SnapMode,
+
+    /// Chord memory: whether it's on, its intervals (`None` keeps the last
+    /// ones) and whether held keys are being learned
+    pub chord: bool,
+    pub chord_intervals: Option<ChordIntervals>,
+    pub chord_learn: bool,
+
+    /// Strum (zero time when off)
+    pub strum_time_ms: f32,
+    pub strum_direction: StrumDirection,
+
+    /// Octaver levels (zero when off)
+    pub octave_level: f32,
+    pub fifth_level: f32,
+
+    /// External input (off without a main input); in [`InputMode::Always`]
+    /// it runs through layer A's filter and drive
+    pub input_mode: InputMode,
+    pub input_gain: f32,
+
+    /// Sidechain envelope follower
+    pub sidechain_attack_ms: f32,
+    pub sidechain_release_ms: f32,
+    pub sidechain_gain_db: f32,
+
+    /// Whether generated notes go to the MIDI output
+    pub midi_out: bool,
+
+    /// What program changes do to sounding notes
+    pub program_transition: ProgramTransition,
+}
+
+impl Default for EngineSettings {
+    fn default() -> Self {
+        Self {
+            bypassed: false,
+            gain: 1.0,
+            expression_curve: ExpressionCurve::default(),
+            tempo_bpm: DEFAULT_TEMPO_BPM,
+            layer_a: VoiceParams::default(),
+            layer_b: VoiceParams::default(),
+            layer_mode: LayerMode::Single,
+            split_note: 60,
+            velocity_split: 0.5,
+            layer_a_level: 1.0,
+            layer_b_level: 1.0,
+            release_velocity: 0.0,
+            stuck_timeout_ms: 0.0,
+            bass_reserve: false,
+            allocation: VoiceAllocation::default(),
+            stealing: VoiceStealing::default(),
+            same_note_policy: SameNotePolicy::default(),
+            choke_ranges: [ChokeRange::default(); NUM_CHOKE_RANGES],
+            choke_policy: ChokePolicy::default(),
+            master_drive: false,
+            phaser_enabled: false,
+            phaser_rate_hz: 0.5,
+            phaser_depth: 0.5,
+            phaser_feedback: 0.0,
+            phaser_stages: MIN_STAGES,
+            phaser_mix: 0.5,
+            eq_enabled: false,
+            eq: EqSettings::default(),
+            eq_mix: 1.0,
+            fx_order: std::array::from_fn(|slot| slot),
+            fx_bypass: false,
+            dc_blocker: true,
+            stereo_width: mid_side::UNITY_WIDTH,
+            width_limit: false,
+            min_correlation: width::DEFAULT_MIN_CORRELATION,
+            seq_steps: [Step::default(); NUM_STEPS],
+            seq_euclid: None,
+            seq_division: NoteDivision::Sixteenth,
+            seq_slew_ms: 0.0,
+            pattern: false,
+            pattern_bars: 1,
+            pattern_swing: STRAIGHT_SWING,
+            pattern_velocity: 0.8,
+            pump_division: NoteDivision::Quarter,
+            pump_depth: 0.0,
+            pump_shape: 0.5,
+            midi_channel: None,
+            humanize_timing_ms: 0.0,
+            humanize_velocity: 0.0,
+            scale_quantize: false,
+            scale_root: 0,
+            scale_mask: 0xFFF,
+            scale_snap: SnapMode::default(),
+            chord: false,
+            chord_intervals: None,
+            chord_learn: false,
+            strum_time_ms: 0.0,
+            strum_direction: StrumDirection::default(),
+            octave_level: 0.0,
+            fifth_level: 0.0,
+            input_mode: InputMode::Off,
+            input_gain: 1.0,
+            sidechain_attack_ms: 10.0,
+            sidechain_release_ms: 100.0,
+            sidechain_gain_db: 0.0,
+            midi_out: false,
+            program_transition: ProgramTransition::default(),
+        }
+    }
+}
+
+/// State the engine shares with the editor
+#[derive(Clone)]
+pub struct EngineLinks {
+    /// Voice snapshots for the diagnostics panel
+    pub diagnostics: Arc<VoiceDiagnostics>,
+
+    /// Incoming MIDI for the activity LED
+    pub midi_activity: Arc<MidiActivity>,
+
+    /// Each layer's range of voice modulation, for the sliders
+    pub modulation: Arc<ModMonitor>,
+
+    /// Pattern step playing, for the piano roll
+    pub pattern_playhead: Arc<PatternPlayhead>,
+
+    /// Notes from the audition pad
+    pub audition_events: Arc<EventQueue>,
+
+    /// Output (or effect-mode input) for the tuner and scope
+    pub tuner_tap: Arc<AudioTap>,
+
+    /// Signal chain levels for the gain-staging meters
+    pub gain_staging: Arc<GainStaging>,
+
+    /// Correlation readouts and the mono check
+    pub stereo: Arc<StereoMonitor>,
+
+    /// Audio-thread diagnostics for the log
+    pub audio_log: Arc<AudioLog>,
+
+    /// Latest MIDI CC values, applied to mapped parameters by the editor
+    pub cc_inbox: Arc<CcInbox>,
+
+    /// Latest program change, turned into a preset load by the editor
+    pub program_inbox: Arc<ProgramInbox>,
+
+    /// Sample for the sampler engine, loaded off the audio thread
+    pub sample_slot: Arc<SampleSlot>,
+}
+
+impl Default for EngineLinks {
+    fn default() -> Self {
+        Self {
+            diagnostics: Arc::new(VoiceDiagnostics::new(NUM_VOICES * 2)),
+            midi_activity: Arc::new(MidiActivity::new()),
+            modulation: Arc::new(ModMonitor::new()),
+            pattern_playhead: Arc::new(PatternPlayhead::new()),
+            audition_events: Arc::new(EventQueue::new(AUDITION_QUEUE_CAPACITY)),
+            tuner_tap: Arc::new(AudioTap::new()),
+            gain_staging: Arc::new(GainStaging::new()),
+            stereo: Arc::new(StereoMonitor::new()),
+            audio_log: Arc::new(AudioLog::new()),
+            cc_inbox: Arc::new(CcInbox::new()),
+            program_inbox: Arc::new(ProgramInbox::new()),
+            sample_slot: Arc::new(SampleSlot::new()),
+        }
+    }
+}
+
+/// The synth's whole render path: note handling, both layers and the master bus
+///
+/// # Real-time Safety
+/// - [`Engine::process`] doesn't allocate, as long as the host keeps to the
+///   largest block size given to [`Engine::initialize`]
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::engine::{
+///     Engine, EngineContext, EngineSettings, HostEvent, HostTransport, OutputEvent,
+/// };
+/// use naughty_and_tender::modulation::ModOffsets;
+/// use naughty_and_tender::poly_mod::PolyOffsets;
+///
+/// /// One note at the start of the block
+/// struct OneNote(Option<(u32, HostEvent)>);
+///
+/// impl EngineContext for OneNote {
+///     fn next_event(&mut self) -> Option<(u32, HostEvent)> {
+///         self.0.take()
+///     }
+///     fn send_event(&mut self, _timing: u32, _event: OutputEvent) {}
+///     fn poly_offsets(&self, _normalized: &PolyOffsets) -> ModOffsets {
+///         ModOffsets::default()
+///     }
+/// }
+///
+/// let mut engine = Engine::new(48000.0);
+/// engine.initialize(48000.0, 256, false);
+/// let note = HostEvent::NoteOn { voice_id: None, channel: 0, note: 60, velocity: 0.8 };
+/// let (mut left, mut right) = ([0.0; 256], [0.0; 256]);
+/// engine.process(
+///     &EngineSettings::default(),
+///     &HostTransport::default(),
+///     &mut [&mut left, &mut right],
+///     None,
+///     &mut OneNote(Some((0, note))),
+/// );
+/// assert!(left.iter().any(|sample| sample.abs() > 0.01));
+/// ```
+pub struct Engine {
+    sample_rate: f32,
+
+    /// Voice pools of the two layers
+    layer_a: VoiceManager,
+    layer_b: VoiceManager,
+    layer_router: LayerRouter,
+    master_chain: MasterChain,
+
+    /// Strips DC offset from the master bus after the effect chain
+    dc_blocker: DcBlocker,
+
+    /// The master chain and DC blocker again for the right channel (voices
+    /// panned by note expressions make the bus stereo)
+    master_chain_right: MasterChain,
+    dc_blocker_right: DcBlocker,
+
+    /// Keeps the master's stereo correlation above a floor
+    width_limiter: WidthLimiter,
+
+    /// Tempo-synced ducking of the master output
+    pump: Pump,
+
+    /// Output gain slewed towards the setting, which only changes per block
+    gain: Slew,
+    sequencer: StepSequencer,
+
+    /// Built-in note pattern, played along with the host transport
+    pattern: PatternPlayer,
+    channel_filter: ChannelFilter,
+    humanizer: Humanizer,
+    scale: ScaleQuantizer,
+    chord: ChordMemory,
+    strummer: Strummer,
+    octaver: Octaver,
+
+    /// Host polyphonic modulation of each voice id
+    poly_mod: PolyModTable,
+
+    /// Host note expressions of each voice id
+    note_expressions: ExpressionTable,
+
+    /// Notes sent to the MIDI output that haven't been released yet
+    midi_out_notes: MidiOutNotes,
+
+    /// Follows the sidechain input's level for the mod matrix
+    follower: EnvelopeFollower,
+
+    /// Filter and drive for the main input in `InputMode::Always`
+    input: InputProcessor,
+
+    /// Whether the host's layout has a main input
+    has_main_input: bool,
+
+    /// State shared with the editor
+    links: EngineLinks,
+
+    /// Samples processed since initialize, to timestamp log records and key
+    /// sub-blocks to
+    sample_clock: u64,
+
+    /// The output of each block, checked and blended per sub-block
+    scratch: BlockScratch,
+
+    /// Voice steals and stuck-note releases already logged
+    steal_watch: TotalWatch,
+    stuck_watch: TotalWatch,
+
+    /// Pairs 14-bit CCs and assembles NRPNs for the CC inbox
+    controllers: ControllerDecoder,
+
+    /// Expression and breath, for the output level and the mod matrix
+    expression: ExpressionInput,
+
+    /// Bank for the next program change
+    bank_select: BankSelect,
+
+    /// Output fade around program changes
+    patch_fade: PatchFade,
+
+    /// Preset loads the voices have been handed over for
+    patch_loads: u32,
+
+    /// Fade around the host's bypass switch
+    bypass: BypassFade,
+
+    /// Sample slot generation the voices were last given
+    sample_generation: u32,
+}
+
+impl Engine {
+    /// Create an engine with no block scratch yet (see [`Engine::initialize`])
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            sample_rate,
+            layer_a: VoiceManager::new(sample_rate, NUM_VOICES),
+            layer_b: VoiceManager::new(sample_rate, NUM_VOICES),
+            layer_router: LayerRouter::new(),
+            master_chain: master_fx::master_chain(sample_rate),
+            dc_blocker: DcBlocker::new(sample_rate),
+            master_chain_right: master_fx::master_chain(sample_rate),
+            dc_blocker_right: DcBlocker::new(sample_rate),
+            width_limiter: WidthLimiter::new(sample_rate),
+            pump: Pump::new(sample_rate),
+            gain: Slew::new(sample_rate, GAIN_SLEW_MS, 1.0),
+            sequencer: StepSequencer::new(sample_rate),
+            pattern: PatternPlayer::new(sample_rate),
+            channel_filter: ChannelFilter::new(),
+            humanizer: Humanizer::new(sample_rate),
+            scale: ScaleQuantizer::new(),
+            chord: ChordMemory::new(),
+            strummer: Strummer::new(sample_rate),
+            octaver: Octaver::new(),
+            poly_mod: PolyModTable::new(),
+            note_expressions: ExpressionTable::new(),
+            midi_out_notes: MidiOutNotes::new(),
+            follower: EnvelopeFollower::new(sample_rate),
+            input: InputProcessor::new(sample_rate),
+            has_main_input: false,
+            links: EngineLinks::default(),
+            sample_clock: 0,
+            scratch: BlockScratch::new(),
+            steal_watch: TotalWatch::default(),
+            stuck_watch: TotalWatch::default(),
+            controllers: ControllerDecoder::new(),
+            expression: ExpressionInput::new(sample_rate),
+            bank_select: BankSelect::default(),
+            patch_fade: PatchFade::new(sample_rate),
+            patch_loads: 0,
+            bypass: BypassFade::new(sample_rate),
+            sample_generation: 0,
+        }
+    }
+
+    /// Get ready to render at `sample_rate`, in blocks of up to
+    /// `max_block_size` samples (allocates)
+    pub fn initialize(&mut self, sample_rate: f32, max_block_size: usize, has_main_input: bool) {
+        self.sample_rate = sample_rate;
+        self.layer_a = VoiceManager::new(sample_rate, NUM_VOICES);
+        self.layer_b = VoiceManager::new(sample_rate, NUM_VOICES);
+        self.master_chain = master_fx::master_chain(sample_rate);
+        self.dc_blocker.set_sample_rate(sample_rate);
+        self.master_chain_right = master_fx::master_chain(sample_rate);
+        self.dc_blocker_right.set_sample_rate(sample_rate);
+        self.width_limiter.set_sample_rate(sample_rate);
+        self.pump.set_sample_rate(sample_rate);
+        self.gain.set_sample_rate(sample_rate);
+        self.sequencer = StepSequencer::new(sample_rate);
+        self.pattern.set_sample_rate(sample_rate);
+        self.humanizer.set_sample_rate(sample_rate);
+        self.strummer.set_sample_rate(sample_rate);
+        self.expression.set_sample_rate(sample_rate);
+        self.follower = EnvelopeFollower::new(sample_rate);
+        self.input = InputProcessor::new(sample_rate);
+        self.patch_fade = PatchFade::new(sample_rate);
+        self.bypass = BypassFade::new(sample_rate);
+        self.has_main_input = has_main_input;
+        self.links.tuner_tap.set_sample_rate(sample_rate);
+        self.links.audio_log.set_sample_rate(sample_rate);
+        self.sample_clock = 0;
+        self.scratch.allocate(max_block_size);
+
+        // The new voices need the current sample too
+        self.sample_generation = 0;
+    }
+
+    /// Silence everything and forget held notes and fades
+    pub fn reset(&mut self) {
+        self.clear_voices_and_tails();
+        self.patch_fade.reset();
+        self.bypass.reset();
+    }
+
+    /// State shared with the editor
+    #[must_use]
+    pub fn links(&self) -> &EngineLinks {
+        &self.links
+    }
+
+    /// Whether the host's layout has a main input
+    #[must_use]
+    pub fn has_main_input(&self) -> bool {
+        self.has_main_input
+    }
+
+    /// The host's tempo, clamped to the range synced times accept (a tempo out
+    /// of range is logged)
+    #[must_use]
+    pub fn host_tempo(&self, tempo: Option<f64>) -> f32 {
+        #[allow(clippy::cast_possible_truncation)] // Tempo fits comfortably in f32
+        let tempo_bpm = tempo.map_or(DEFAULT_TEMPO_BPM, |tempo| tempo as f32);
+        if (MIN_HOST_TEMPO_BPM..=MAX_HOST_TEMPO_BPM).contains(&tempo_bpm) {
+            return tempo_bpm;
+        }
+
+        self.links
+            .audio_log
+            .push(self.sample_clock, LogEvent::TempoClamped { bpm: tempo_bpm });
+        if tempo_bpm.is_nan() {
+            DEFAULT_TEMPO_BPM
+        } else {
+            tempo_bpm.clamp(MIN_HOST_TEMPO_BPM, MAX_HOST_TEMPO_BPM)
+        }
+    }
+
+    /// A chord learned from held keys since the last call
+    pub fn take_learned_chord(&mut self) -> Option<ChordIntervals> {
+        self.chord.take_learned()
+    }
+
+    /// Replace the built-in pattern's notes, as (start step, note, length in
+    /// steps) triples
+    pub fn set_pattern_notes(&mut self, notes: &[(u8, u8, u8)]) {
+        self.pattern.set_notes(notes);
+    }
+
+    /// Render one block
+    ///
+    /// `main` holds the main input on the way in (when the host has one) and
+    /// the output on the way out; `sidechain` is the sidechain input, if any.
+    #[allow(clippy::too_many_lines, clippy::similar_names)] // One pass, in signal order
+    pub fn process(
+        &mut self,
+        settings: &EngineSettings,
+        transport: &HostTransport,
+        main: &mut [&mut [f32]],
+        sidechain: Option<&[&mut [f32]]>,
+        context: &mut impl EngineContext,
+    ) -> BlockStatus {
+        let num_samples = main.first().map_or(0, |channel| channel.len());
+
+        // Host bypass: once the fade-out is silent, flush the voices and effect
+        // tails, then pass the main input through until the bypass is released
+        let bypass_engaged = self.bypass.set_bypassed(settings.bypassed);
+        if self.bypass.take_silence() {
+            // Notes sent to the MIDI output end with the synth's own
+            for (channel, note) in self.midi_out_notes.release_all() {
+                let note_off = OutputEvent::NoteOff {
+                    channel,
+                    note,
+                    velocity: 0.0,
+                };
+                context.send_event(0, note_off);
+            }
+            self.clear_voices_and_tails();
+        }
+        if self.bypass.is_silent() {
+            // Notes played while bypassed would start voices nobody hears
+            while context.next_event().is_some() {}
+            while self.links.audition_events.pop().is_some() {}
+            if !self.has_main_input {
+                for channel_samples in main.iter_mut() {
+                    channel_samples.fill(0.0);
+                }
+                // Voices and effect tails were flushed, nothing left to ring out
+                return BlockStatus::Tail(0);
+            }
+            return BlockStatus::Normal;
+        }
+
+        self.begin_block(settings, transport, context);
+        let mono_check = self.links.stereo.mono_check();
+        let input_mode = settings.input_mode;
+        let program_transition = settings.program_transition;
+        let patches_applied = self.links.program_inbox.applied();
+
+        // The tuner and scope hear the input in effect mode, the synth otherwise
+        let tuner_listening = self.links.tuner_tap.is_listening();
+
+        // Gain-staging peaks, merged into the meters after the block
+        let metering = self.links.gain_staging.is_listening();
+        let mut stage_peaks = StagePeaks::default();
+
+        // Voices wind down under the bypass fade
+        if bypass_engaged {
+            self.layer_a.release_all();
+            self.layer_b.release_all();
+        }
+
+        // Process sample by sample (for sample-accurate MIDI). Given the same
+        // settings, the output doesn't depend on how the host sizes its blocks:
+        // anything with timing of its own (notes, the stuck-note watchdog, synced
+        // cycles) runs on sample or control-tick boundaries, never block ones.
+        // What the editor feeds in and the output check run per sub-block, so
+        // an 8192-sample block reacts as quickly as a small one. The output is
+        // gathered in scratch allocated for the host's largest block; the rest
+        // is a fixed-size value per voice or stage
+        let mut next_event = context.next_event();
+        self.scratch.begin(self.sample_clock, num_samples);
+        for sample_idx in 0..num_samples {
+            #[allow(clippy::cast_possible_truncation)] // Audio buffer size never exceeds u32
+            let timing = sample_idx as u32;
+
+            // Handle host events at this sample
+            while let Some((event_timing, event)) = next_event {
+                if event_timing > timing {
+                    break;
+                }
+                self.handle_event(event, settings, patches_applied, context);
+                next_event = context.next_event();
+            }
+
+            // Audition pad notes arrive between sub-blocks and start with the next one
+            if self.scratch.starts_sub_block(sample_idx) {
+                while let Some(timed) = self.links.audition_events.pop() {
+                    match timed.event {
+                        Event::NoteOn {
+                            channel,
+                            note,
+                            velocity,
+                        } => self.humanizer.note_on(note, velocity, channel, None),
+                        Event::NoteOff {
+                            channel,
+                            note,
+                            velocity,
+                        } => self.humanizer.note_off(note, velocity, channel, None),
+                        // The pad only plays notes
+                        Event::Cc { .. } => {}
+                    }
+                }
+            }
+
+            // Pattern notes join the keys played over MIDI
+            while let Some(event) = self.pattern.pop_due() {
+                match event {
+                    KeyEvent::NoteOn {
+                        note,
+                        velocity,
+                        channel,
+                        voice_id,
+                    } => self.humanizer.note_on(note, velocity, channel, voice_id),
+                    KeyEvent::NoteOff {
+                        note,
+                        velocity,
+                        channel,
+                        voice_id,
+                    } => self.humanizer.note_off(note, velocity, channel, voice_id),
+                }
+            }
+            self.pattern.advance();
+
+            // Update global modulation sources (per-voice sources are filled in by
+            // each voice) before any note starts, so a note starting on this sample
+            // starts from this sample's values
+            let sidechain_sample =
+                sidechain.map_or(0.0, |channels| channel_mean(channels, sample_idx));
+            self.expression.process();
+            let mod_sources = ModSourceValues {
+                step_sequencer: self.sequencer.process(),
+                sidechain: self.follower.process(sidechain_sample),
+                expression: self.expression.expression(),
+                breath: self.expression.breath(),
+                ..ModSourceValues::default()
+            };
+            self.layer_a.set_mod_sources(mod_sources);
+            self.layer_b.set_mod_sources(mod_sources);
+
+            // Notes once humanization has delayed them: each key can start a whole
+            // chord, and the notes that start together are strummed
+            while let Some(event) = self.humanizer.pop_due() {
+                match event {
+                    KeyEvent::NoteOn {
+                        note,
+                        velocity,
+                        channel,
+                        voice_id,
+                    } => {
+                        let note = self.scale.note_on(note);
+                        for chord_note in self.chord.note_on(note).into_iter().flatten() {
+                            self.strummer
+                                .note_on(chord_note, velocity, channel, voice_id);
+                        }
+                    }
+                    KeyEvent::NoteOff {
+                        note,
+                        velocity,
+                        channel,
+                        voice_id,
+                    } => {
+                        let note = self.scale.note_off(note);
+                        for chord_note in self.chord.note_off(note).into_iter().flatten() {
+                            self.strummer
+                                .note_off(chord_note, velocity, channel, voice_id);
+                        }
+                    }
+                }
+            }
+            self.humanizer.advance();
+
+            // Notes once strummed, each on one or both layers
+            while let Some(event) = self.strummer.pop_due() {
+                self.play_key_event(event, timing, settings.midi_out, context);
+            }
+            self.strummer.advance();
+
+            // External input, read before the output overwrites it
+            let input_sample = if input_mode == InputMode::Off {
+                0.0
+            } else {
+                channel_mean(main, sample_idx) * settings.input_gain
+            };
+            if input_mode == InputMode::Gated {
+                self.layer_a.set_input(input_sample);
+                self.layer_b.set_input(input_sample);
+            }
+
+            // Generate one stereo sample from each layer (voices sit at their
+            // note expression pan) and mix them
+            let (mut layer_a_left, mut layer_a_right) = ([0.0f32], [0.0f32]);
+            let (mut layer_b_left, mut layer_b_right) = ([0.0f32], [0.0f32]);
+            self.layer_a
+                .process_stereo(&mut layer_a_left, &mut layer_a_right);
+            self.layer_b
+                .process_stereo(&mut layer_b_left, &mut layer_b_right);
+
+            // Debug probes follow the newest layer A voice
+            #[cfg(feature = "debug-outputs")]
+            context.probe(sample_idx, self.layer_a.probe().values());
+
+            // A voice id ends with the last voice carrying it, on either layer
+            while let Some(ended) = self
+                .layer_a
+                .pop_ended_voice()
+                .or_else(|| self.layer_b.pop_ended_voice())
+            {
+                if !self.layer_a.carries_voice_id(ended.voice_id)
+                    && !self.layer_b.carries_voice_id(ended.voice_id)
+                {
+                    self.poly_mod.remove(ended.voice_id);
+                    self.note_expressions.remove(ended.voice_id);
+                    let terminated = OutputEvent::VoiceTerminated {
+                        voice_id: ended.voice_id,
+                        channel: ended.channel,
+                        note: ended.note,
+                    };
+                    context.send_event(timing, terminated);
+                }
+            }
+            let mut mix = [
+                layer_a_left[0] * settings.layer_a_level + layer_b_left[0] * settings.layer_b_level,
+                layer_a_right[0] * settings.layer_a_level
+                    + layer_b_right[0] * settings.layer_b_level,
+            ];
+            if input_mode == InputMode::Always {
+                let input = self.input.process(input_sample);
+                mix = mix.map(|sample| sample + input);
+            }
+
+            // Master insert chain and DC blocker on each side, stereo width and
+            // the width limiter, then master gain and the pump
+            let (left, right) = mid_side::apply_width(
+                self.dc_blocker.process(self.master_chain.process(mix[0])),
+                self.dc_blocker_right
+                    .process(self.master_chain_right.process(mix[1])),
+                settings.stereo_width,
+            );
+            let pre_gain = self.width_limiter.process([left, right]);
+            let expression_gain = settings.expression_curve.gain(self.expression.expression());
+            let mut output_gain =
+                self.gain.process(settings.gain) * expression_gain * self.pump.process();
+
+            // Program change fade; the reset policy silences old notes at the bottom
+            if self.patch_fade.is_active() {
+                output_gain *= self.patch_fade.process(patches_applied);
+                if self.patch_fade.take_silence() && program_transition == ProgramTransition::Reset
+                {
+                    self.layer_a.reset();
+                    self.layer_b.reset();
+                }
+            }
+            let mut output_frame = pre_gain.map(|sample| sample * output_gain);
+            if mono_check {
+                output_frame = width::fold_to_mono(output_frame);
+            }
+
+            if metering {
+                for ((mix, pre_gain), output) in mix.iter().zip(&pre_gain).zip(&output_frame) {
+                    stage_peaks.add(MeterStage::VoiceMix, *mix);
+                    stage_peaks.add(MeterStage::PreGain, *pre_gain);
+                    stage_peaks.add(MeterStage::Output, *output);
+                }
+            }
+            if tuner_listening {
+                self.links
+                    .tuner_tap
+                    .push(if input_mode == InputMode::Always {
+                        input_sample
+                    } else {
+                        0.5 * (output_frame[0] + output_frame[1])
+                    });
+            }
+
+            // Stage the output, to be blended with the dry input around a host
+            // bypass once its sub-block is checked
+            self.scratch
+                .write(sample_idx, output_frame, self.bypass.next_gain());
+            let Some(sub_block) = self.scratch.end_of_sub_block(sample_idx) else {
+                continue;
+            };
+
+            // A blown-up filter stays at NaN or infinity until it's reset, and
+            // would latch the output there: silence the sub-block and reset
+            // whatever made it
+            let output_finite = self.scratch.is_finite(sub_block.clone());
+            debug_assert!(output_finite, "Non-finite sample in the output");
+            if !output_finite {
+                let voices =
+                    self.layer_a.reset_non_finite_voices() + self.layer_b.reset_non_finite_voices();
+                self.links.audio_log.push(
+                    self.sample_clock + sub_block.start as u64,
+                    LogEvent::NonFiniteRecovered {
+                        voices: u32::try_from(voices).unwrap_or(u32::MAX),
+                    },
+                );
+                self.master_chain.reset();
+                self.master_chain_right.reset();
+                self.dc_blocker.reset();
+                self.dc_blocker_right.reset();
+                self.width_limiter.reset();
+                self.input.reset();
+                self.scratch.silence(sub_block.clone());
+                self.links.diagnostics.record_recovery();
+            }
+            self.scratch.blend(sub_block, main, self.has_main_input);
+        }
+
+        if metering {
+            self.links.gain_staging.publish(&stage_peaks);
+        }
+        self.end_block(num_samples);
+
+        // Let the host suspend processing once the releases and effect tails have
+        // rung out. Held notes, humanized or strummed notes still on their way and a
+        // running pattern keep it running; with the input always mixed in, the host
+        // goes by its level.
+        if self.humanizer.pending_count() > 0
+            || self.strummer.pending_count() > 0
+            || self.pattern.current_step().is_some()
+        {
+            return BlockStatus::KeepAlive;
+        }
+        match (self.layer_a.tail_samples(), self.layer_b.tail_samples()) {
+            (Some(_), Some(_)) if input_mode == InputMode::Always => BlockStatus::Normal,
+            (Some(layer_a_tail), Some(layer_b_tail)) => BlockStatus::Tail(
+                layer_a_tail
+                    .max(layer_b_tail)
+                    .saturating_add(self.master_chain.tail_samples()),
+            ),
+            _ => BlockStatus::KeepAlive,
+        }
+    }
+
+    /// Silence every voice and effect tail and forget held notes
+    fn clear_voices_and_tails(&mut self) {
+        self.layer_a.reset();
+        self.layer_b.reset();
+        self.master_chain.reset();
+        self.dc_blocker.reset();
+        self.master_chain_right.reset();
+        self.dc_blocker_right.reset();
+        self.width_limiter.reset();
+        self.pump.reset();
+        self.sequencer.reset();
+        self.pattern.reset();
+        self.follower.reset();
+        self.input.reset();
+        self.channel_filter.reset();
+        self.humanizer.reset();
+        self.scale.reset();
+        self.chord.reset();
+        self.strummer.reset();
+        self.octaver.reset();
+        self.poly_mod.reset();
+        self.note_expressions.reset();
+        self.layer_router.reset();
+    }
+
+    /// Hand a block's settings on, and lock the synced parts to the host's
+    /// playhead
+    #[allow(clippy::too_many_lines)] // Every section's settings, in signal order
+    fn begin_block(
+        &mut self,
+        settings: &EngineSettings,
+        transport: &HostTransport,
+        context: &impl EngineContext,
+    ) {
+        let tempo_bpm = settings.tempo_bpm;
+
+        // Sampler: pick up a newly loaded sample (its playback settings travel
+        // with the other voice settings below)
+        if let Some(sample) = self.links.sample_slot.fetch(&mut self.sample_generation) {
+            self.layer_a.set_sample(Some(&sample));
+        }
+
+        // Update master chains: effect settings, bypass, order and mix
+        // (bypass changes crossfade inside the chain, so they never click)
+        for chain in [&mut self.master_chain, &mut self.master_chain_right] {
+            for effect in chain.slots_mut() {
+                match effect {
+                    MasterEffect::Drive(shaper) => shaper.set_settings(settings.layer_a.waveshaper),
+                    MasterEffect::Phaser(phaser) => {
+                        phaser.set_rate_hz(settings.phaser_rate_hz);
+                        phaser.set_depth(settings.phaser_depth);
+                        phaser.set_feedback(settings.phaser_feedback);
+                        phaser.set_stages(settings.phaser_stages);
+                        phaser.set_mix(settings.phaser_mix);
+                    }
+                    // Coefficients are only recomputed when a band changes
+                    MasterEffect::Eq(eq) => eq.set_settings(settings.eq),
+                }
+            }
+            chain.set_bypassed(DRIVE_SLOT, !settings.master_drive);
+            chain.set_bypassed(PHASER_SLOT, !settings.phaser_enabled);
+            chain.set_bypassed(EQ_SLOT, !settings.eq_enabled);
+            chain.set_order(settings.fx_order);
+            chain.set_mix(EQ_SLOT, settings.eq_mix);
+            chain.set_chain_bypassed(settings.fx_bypass);
+        }
+        self.dc_blocker.set_bypassed(!settings.dc_blocker);
+        self.dc_blocker_right.set_bypassed(!settings.dc_blocker);
+        self.width_limiter.set_enabled(settings.width_limit);
+        self.width_limiter
+            .set_min_correlation(settings.min_correlation);
+
+        // Voice allocation, shared by both layers
+        for manager in [&mut self.layer_a, &mut self.layer_b] {
+            manager.set_release_velocity_amount(settings.release_velocity);
+            manager.set_stuck_timeout_ms(settings.stuck_timeout_ms);
+            manager.set_bass_reserve(settings.bass_reserve);
+            manager.set_allocation(settings.allocation);
+            manager.set_stealing(settings.stealing);
+            manager.set_same_note_policy(settings.same_note_policy);
+            manager.set_choke_ranges(&settings.choke_ranges);
+            manager.set_choke_policy(settings.choke_policy);
+        }
+
+        // Layer routing
+        self.layer_router.set_mode(settings.layer_mode);
+        self.layer_router.set_split_note(settings.split_note);
+        self.layer_router
+            .set_velocity_split(settings.velocity_split);
+
+        // Step sequencer: tempo-synced, and locked to the host playhead while playing
+        let step_division = settings.seq_division;
+        self.sequencer.set_steps(settings.seq_steps);
+        self.sequencer.set_euclid(settings.seq_euclid);
+        self.sequencer
+            .set_step_length_ms(step_division.duration_ms(tempo_bpm));
+        self.sequencer.set_slew_ms(settings.seq_slew_ms);
+        if let (true, Some(position_beats)) = (transport.playing, transport.pos_beats) {
+            self.sequencer
+                .sync_to_beats(position_beats, f64::from(step_division.beats()));
+        }
+
+        // Pattern: plays from the host's song position while the transport runs
+        let pattern_playing = settings.pattern && transport.playing;
+        self.pattern.set_bars(settings.pattern_bars);
+        let (numerator, denominator) = transport.time_signature;
+        self.pattern.set_time_signature(numerator, denominator);
+        self.pattern.set_swing(settings.pattern_swing);
+        self.pattern.set_velocity(settings.pattern_velocity);
+        self.pattern.set_tempo(tempo_bpm);
+        match (pattern_playing, transport.pos_beats, transport.bar) {
+            (true, Some(position_beats), Some((bar_number, bar_start_beats))) => {
+                self.pattern
+                    .sync_to_bar(bar_number, position_beats - bar_start_beats);
+            }
+            (true, Some(position_beats), None) => self.pattern.sync_to_beats(position_beats),
+            _ => {}
+        }
+        self.pattern.set_playing(pattern_playing);
+
+        // Pump: ducks on every division, on the host grid while playing
+        let pump_division = settings.pump_division;
+        self.pump
+            .set_period_ms(pump_division.duration_ms(tempo_bpm));
+        self.pump.set_depth(settings.pump_depth);
+        self.pump.set_shape(settings.pump_shape);
+        if let (true, Some(position_beats)) = (transport.playing, transport.pos_beats) {
+            self.pump
+                .sync_to_beats(position_beats, f64::from(pump_division.beats()));
+        }
+
+        // Only the selected MIDI channel gets through (every channel in omni mode)
+        self.channel_filter.set_channel(settings.midi_channel);
+
+        // Humanization delays and jitters notes before anything else sees them
+        self.humanizer.set_timing_ms(settings.humanize_timing_ms);
+        self.humanizer
+            .set_velocity_amount(settings.humanize_velocity);
+
+        // Scale quantization, ahead of chord memory so chords build on the snapped key
+        self.scale.set_enabled(settings.scale_quantize);
+        self.scale
+            .set_scale(settings.scale_root, settings.scale_mask);
+        self.scale.set_snap_mode(settings.scale_snap);
+
+        // Chord memory
+        self.chord.set_learning(settings.chord_learn);
+        if let Some(intervals) = settings.chord_intervals {
+            self.chord.set_intervals(intervals);
+        }
+        self.chord.set_enabled(settings.chord);
+
+        // Strum: spreads the notes that start together, chord notes included
+        self.strummer.set_time_ms(settings.strum_time_ms);
+        self.strummer.set_direction(settings.strum_direction);
+
+        // Octaver: levels apply to notes started from here on
+        self.octaver
+            .set_levels(settings.octave_level, settings.fifth_level);
+
+        // External input in `InputMode::Always`: layer A's filter and drive
+        let layer_a = &settings.layer_a;
+        self.input.set_filter(
+            layer_a.filter_enabled,
+            layer_a.filter_mode,
+            layer_a.filter_cutoff_hz,
+            layer_a.filter_resonance,
+        );
+        self.input.set_filter_precision(layer_a.filter_precision);
+        self.input
+            .set_waveshaper(layer_a.waveshaper_enabled, layer_a.waveshaper);
+
+        // A preset that's loading reaches the voices with these settings: first
+        // let the transition pin or fade the ones already sounding
+        let patch_loads = self.links.program_inbox.loads();
+        if patch_loads != self.patch_loads {
+            self.patch_loads = patch_loads;
+            hand_over_voices(&mut self.layer_a, settings.program_transition);
+            hand_over_voices(&mut self.layer_b, settings.program_transition);
+        }
+
+        // Voice settings: passed on to the voices only when something changed
+        self.layer_a.set_params(&settings.layer_a);
+        self.layer_b.set_params(&settings.layer_b);
+
+        // Host poly modulation is relative to the parameters, which may have moved
+        for (voice_id, normalized) in self.poly_mod.voices() {
+            let offsets = context.poly_offsets(&normalized);
+            apply_poly_modulation(voice_id, offsets, &mut self.layer_a, &mut self.layer_b);
+        }
+
+        // Sidechain envelope follower (the input is absent in hosts without sidechain routing)
+        self.follower.set_attack_ms(settings.sidechain_attack_ms);
+        self.follower.set_release_ms(settings.sidechain_release_ms);
+        self.follower.set_gain_db(settings.sidechain_gain_db);
+    }
+
+    /// Handle one host event that's due
+    #[allow(clippy::too_many_lines)] // One arm per event
+    fn handle_event(
+        &mut self,
+        event: HostEvent,
+        settings: &EngineSettings,
+        patches_applied: u32,
+        context: &impl EngineContext,
+    ) {
+        // Events on other channels are dropped before anything sees them
+        let accepted = match event {
+            HostEvent::NoteOn { channel, note, .. } => self.channel_filter.note_on(channel, note),
+            HostEvent::NoteOff { channel, note, .. } => self.channel_filter.note_off(channel, note),
+            _ => event
+                .channel()
+                .is_none_or(|channel| self.channel_filter.accepts(channel)),
+        };
+        if !accepted {
+            return;
+        }
+
+        let midi_activity = &self.links.midi_activity;
+        match event {
+            HostEvent::NoteOn {
+                voice_id,
+                channel,
+                note,
+                velocity,
+            } => {
+                midi_activity.note_on(note, velocity);
+                self.humanizer.note_on(note, velocity, channel, voice_id);
+
+                // Its expressions arrive after it, and start from neutral
+                let expression_id = voice_id.unwrap_or_else(|| fallback_voice_id(note, channel));
+                self.note_expressions.remove(expression_id);
+            }
+            HostEvent::NoteOff {
+                voice_id,
+                channel,
+                note,
+                velocity,
+            } => {
+                midi_activity.event();
+                self.humanizer.note_off(note, velocity, channel, voice_id);
+            }
+            HostEvent::Choke {
+                voice_id,
+                channel,
+                note,
+            } => {
+                midi_activity.event();
+
+                // Cut the voices now; the key's note-off still goes through
+                // so chords, strums and octave layers let go of it
+                self.layer_a.choke_voices(note, channel, voice_id);
+                self.layer_b.choke_voices(note, channel, voice_id);
+                self.humanizer.note_off(note, 0.0, channel, voice_id);
+            }
+            HostEvent::Cc { channel, cc, value } => {
+                midi_activity.event();
+
+                // Bank select only sets the bank for the next program change
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // 0-127
+                let value_7bit = (value * 127.0).round() as u8;
+                if !self.bank_select.handle_cc(cc, value_7bit) {
+                    let decoded = self.controllers.decode(channel, cc, value_7bit);
+                    for (controller, value) in decoded.into_iter().flatten() {
+                        // Still mappable like any other controller
+                        if let Controller::Cc(cc) | Controller::Cc14(cc) = controller {
+                            self.expression.handle_cc(cc, value);
+                        }
+                        self.links.cc_inbox.post(controller, value);
+                    }
+                }
+            }
+            HostEvent::PitchBend { value, .. } => {
+                midi_activity.event();
+
+                // Both layers follow the wheel; each voice slews it
+                let bend = pitch_bend::bend_from_normalized(value);
+                self.layer_a.set_pitch_bend(bend);
+                self.layer_b.set_pitch_bend(bend);
+            }
+            HostEvent::PolyVolume {
+                voice_id,
+                channel,
+                note,
+                gain,
+            } => self.apply_note_expression(
+                voice_id.unwrap_or_else(|| fallback_voice_id(note, channel)),
+                NoteExpression::Volume(gain),
+            ),
+            HostEvent::PolyPan {
+                voice_id,
+                channel,
+                note,
+                pan,
+            } => self.apply_note_expression(
+                voice_id.unwrap_or_else(|| fallback_voice_id(note, channel)),
+                NoteExpression::Pan(pan),
+            ),
+            HostEvent::PolyTuning {
+                voice_id,
+                channel,
+                note,
+                tuning,
+            } => self.apply_note_expression(
+                voice_id.unwrap_or_else(|| fallback_voice_id(note, channel)),
+                NoteExpression::Tuning(tuning),
+            ),
+            HostEvent::PolyModulation {
+                voice_id,
+                poly_modulation_id,
+                normalized_offset,
+            } => {
+                let target = PolyTarget::from_id(poly_modulation_id);
+                let normalized = target
+                    .and_then(|target| self.poly_mod.set(voice_id, target, normalized_offset));
+                if let Some(normalized) = normalized {
+                    let offsets = context.poly_offsets(&normalized);
+                    apply_poly_modulation(voice_id, offsets, &mut self.layer_a, &mut self.layer_b);
+                }
+            }
+            HostEvent::MonoAutomation => {
+                // A poly-modulated parameter moved: rebase every voice on it
+                for (voice_id, normalized) in self.poly_mod.voices() {
+                    let offsets = context.poly_offsets(&normalized);
+                    apply_poly_modulation(voice_id, offsets, &mut self.layer_a, &mut self.layer_b);
+                }
+            }
+            HostEvent::ProgramChange { program, .. } => {
+                midi_activity.event();
+                self.links
+                    .program_inbox
+                    .post(self.bank_select.program(program));
+                if settings.program_transition.dips_output() {
+                    self.patch_fade.start(patches_applied);
+                }
+            }
+            HostEvent::Other { .. } => midi_activity.event(),
+        }
+    }
+
+    /// Start or release a key's notes once strummed, each on one or both layers
+    fn play_key_event(
+        &mut self,
+        event: KeyEvent,
+        timing: u32,
+        midi_out: bool,
+        context: &mut impl EngineContext,
+    ) {
+        match event {
+            KeyEvent::NoteOn {
+                note,
+                velocity,
+                channel,
+                voice_id,
+            } => {
+                // Octave and fifth layers play on the note's layers, every
+                // voice tagged with the host's voice id (or its own), and
+                // go to the MIDI output with it
+                let layers = self.layer_router.note_on(note, velocity);
+                let stack = self.octaver.note_on(note, velocity);
+                for (stack_note, stack_velocity) in stack.into_iter().flatten() {
+                    if midi_out {
+                        self.midi_out_notes.note_on(channel, stack_note);
+                        let note_on = OutputEvent::NoteOn {
+                            channel,
+                            note: stack_note,
+                            velocity: stack_velocity,
+                        };
+                        context.send_event(timing, note_on);
+                    }
+
+                    let tag = VoiceTag::new(stack_note, channel, voice_id);
+                    if layers.a {
+                        self.layer_a.note_on_tagged(stack_note, stack_velocity, tag);
+                    }
+                    if layers.b {
+                        self.layer_b.note_on_tagged(stack_note, stack_velocity, tag);
+                    }
+
+                    // Expressions that arrived before the voice started
+                    if let Some(expression) = self.note_expressions.get(tag.voice_id) {
+                        self.layer_a.set_expression(tag.voice_id, expression);
+                        self.layer_b.set_expression(tag.voice_id, expression);
+                    }
+                }
+
+                // Poly modulation that arrived while the note was delayed
+                if let Some(voice_id) = voice_id {
+                    if let Some(normalized) = self.poly_mod.get(voice_id) {
+                        let offsets = context.poly_offsets(&normalized);
+                        apply_poly_modulation(
+                            voice_id,
+                            offsets,
+                            &mut self.layer_a,
+                            &mut self.layer_b,
+                        );
+                    }
+                }
+            }
+            KeyEvent::NoteOff {
+                note,
+                velocity,
+                channel,
+                voice_id,
+            } => {
+                // Releases every note started, on the layers it started on
+                // and on the MIDI output
+                let layers = self.layer_router.note_off(note);
+                let stack = self.octaver.note_off(note);
+                for stack_note in stack.into_iter().flatten() {
+                    if self.midi_out_notes.note_off(channel, stack_note) {
+                        let note_off = OutputEvent::NoteOff {
+                            channel,
+                            note: stack_note,
+                            velocity,
+                        };
+                        context.send_event(timing, note_off);
+                    }
+                    if layers.a {
+                        self.layer_a.note_off_voice(stack_note, velocity, voice_id);
+                    }
+                    if layers.b {
+                        self.layer_b.note_off_voice(stack_note, velocity, voice_id);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Apply a note expression to both layers, keeping it for voices yet to start
+    fn apply_note_expression(&mut self, voice_id: i32, expression: NoteExpression) {
+        if let Some(values) = self.note_expressions.apply(voice_id, expression) {
+            self.layer_a.set_expression(voice_id, values);
+            self.layer_b.set_expression(voice_id, values);
+        }
+    }
+
+    /// Publish the block's voice states, modulation ranges and log records
+    fn end_block(&mut self, num_samples: usize) {
+        let links = &self.links;
+        links.stereo.publish(&self.width_limiter);
+
+        // Publish voice states for the diagnostics panel (layer A slots first)
+        let steal_count = self.layer_a.steal_count() + self.layer_b.steal_count();
+        let stuck_release_count =
+            self.layer_a.stuck_release_count() + self.layer_b.stuck_release_count();
+        links.diagnostics.publish(
+            self.layer_a.snapshots().chain(self.layer_b.snapshots()),
+            steal_count,
+            stuck_release_count,
+        );
+        links.modulation.publish(0, self.layer_a.matrix_range());
+        links.modulation.publish(1, self.layer_b.matrix_range());
+
+        // Steals and stuck-note releases this block go to the log
+        let steals = self.steal_watch.take_increase(steal_count);
+        if steals > 0 {
+            links
+                .audio_log
+                .push(self.sample_clock, LogEvent::VoicesStolen { count: steals });
+        }
+        let stuck_releases = self.stuck_watch.take_increase(stuck_release_count);
+        if stuck_releases > 0 {
+            links.audio_log.push(
+                self.sample_clock,
+                LogEvent::StuckNotesReleased {
+                    count: stuck_releases,
+                },
+            );
+        }
+        self.sample_clock += num_samples as u64;
+
+        links.diagnostics.publish_limiter_reduction(
+            self.layer_a
+                .take_limiter_reduction_db()
+                .max(self.layer_b.take_limiter_reduction_db()),
+        );
+
+        links
+            .pattern_playhead
+            .publish(self.pattern.current_step(), self.pattern.bar_steps());
+    }
+}
+
+/// Mean of a sample across audio channels (mono sum)
+fn channel_mean(channels: &[&mut [f32]], index: usize) -> f32 {
+    #[allow(clippy::cast_precision_loss)] // Channel counts are tiny
+    let num_channels = channels.len().max(1) as f32;
+    channels.iter().map(|channel| channel[index]).sum::<f32>() / num_channels
+}
+
+/// Pin or fade a layer's sounding voices for a preset load, as the transition says
+fn hand_over_voices(voices: &mut VoiceManager, transition: ProgramTransition) {
+    if transition.pins_voices() {
+        voices.pin_sounding_voices();
+    }
+    if transition.fades_voices() {
+        voices.fade_out_voices();
+    }
+}
+
+/// Apply a voice's host poly modulation to both layers
+///
+/// Pitch and level are shared; the cutoff parameter is layer A's, so layer B
+/// keeps its own cutoff.
+fn apply_poly_modulation(
+    voice_id: i32,
+    offsets: ModOffsets,
+    layer_a: &mut VoiceManager,
+    layer_b: &mut VoiceManager,
+) {
+    layer_a.set_poly_offsets(voice_id, offsets);
+    layer_b.set_poly_offsets(
+        voice_id,
+        ModOffsets {
+            cutoff_octaves: 0.0,
+            ..offsets
+        },
+    );
+}
@@ -0,0 +1,235 @@
+//! Master EQ module for Naughty and Tender
+//!
+//! Three-band equalizer on the master bus: low shelf, parametric mid, high shelf.
+//! Each band is a single RBJ biquad from `shared_core::biquad`, run in series.
+//!
+//! # References
+//! - RBJ Audio EQ Cookbook (shelving and peaking designs)
+//! - Total response in dB = sum of the individual band responses in dB
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use shared_core::biquad::{Biquad, BiquadCoefficients};
+
+/// Q used for both shelves (Butterworth-like, no overshoot)
+pub const SHELF_Q: f32 = 0.707;
+
+/// Settings for all three EQ bands
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EqSettings {
+    /// Low shelf corner frequency in Hz
+    pub low_freq: f32,
+    /// Low shelf gain in dB
+    pub low_gain_db: f32,
+    /// Mid band center frequency in Hz
+    pub mid_freq: f32,
+    /// Mid band gain in dB
+    pub mid_gain_db: f32,
+    /// Mid band Q (higher = narrower)
+    pub mid_q: f32,
+    /// High shelf corner frequency in Hz
+    pub high_freq: f32,
+    /// High shelf gain in dB
+    pub high_gain_db: f32,
+}
+
+impl Default for EqSettings {
+    fn default() -> Self {
+        Self {
+            low_freq: 120.0,
+            low_gain_db: 0.0,
+            mid_freq: 1000.0,
+            mid_gain_db: 0.0,
+            mid_q: 0.707,
+            high_freq: 8000.0,
+            high_gain_db: 0.0,
+        }
+    }
+}
+
+impl EqSettings {
+    /// Coefficients for (low, mid, high) bands at the given sample rate
+    #[must_use]
+    pub fn coefficients(
+        &self,
+        sample_rate: f32,
+    ) -> (BiquadCoefficients, BiquadCoefficients, BiquadCoefficients) {
+        (
+            BiquadCoefficients::low_shelf(sample_rate, self.low_freq, SHELF_Q, self.low_gain_db),
+            BiquadCoefficients::peaking(sample_rate, self.mid_freq, self.mid_q, self.mid_gain_db),
+            BiquadCoefficients::high_shelf(sample_rate, self.high_freq, SHELF_Q, self.high_gain_db),
+        )
+    }
+
+    /// Combined magnitude response in dB at `frequency` (used for the editor curve)
+    #[must_use]
+    pub fn magnitude_db(&self, sample_rate: f32, frequency: f32) -> f32 {
+        let (low, mid, high) = self.coefficients(sample_rate);
+        low.magnitude_db(sample_rate, frequency)
+            + mid.magnitude_db(sample_rate, frequency)
+            + high.magnitude_db(sample_rate, frequency)
+    }
+}
+
+/// Three-band master EQ
+///
+/// # Real-time Safety
+/// - Three biquads of fixed state, no allocations
+/// - Coefficients only recomputed when settings change
+pub struct MasterEq {
+    low: Biquad,
+    mid: Biquad,
+    high: Biquad,
+
+    /// Settings the current coefficients were computed from
+    settings: EqSettings,
+
+    /// Sample rate in Hz
+    sample_rate: f32,
+}
+
+impl MasterEq {
+    /// Create a new (flat) master EQ
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        let mut eq = Self {
+            low: Biquad::default(),
+            mid: Biquad::default(),
+            high: Biquad::default(),
+            settings: EqSettings::default(),
+            sample_rate,
+        };
+        eq.update_coefficients();
+        eq
+    }
+
+    /// Apply new band settings (no-op if unchanged)
+    pub fn set_settings(&mut self, settings: EqSettings) {
+        if settings != self.settings {
+            self.settings = settings;
+            self.update_coefficients();
+        }
+    }
+
+    /// Current band settings
+    #[must_use]
+    pub fn settings(&self) -> EqSettings {
+        self.settings
+    }
+
+    /// Process one sample through all three bands
+    #[inline]
+    pub fn process(&mut self, input: f32) -> f32 {
+        let low = self.low.process(input);
+        let mid = self.mid.process(low);
+        self.high.process(mid)
+    }
+
+    /// Clear all filter state
+    pub fn reset(&mut self) {
+        self.low.reset();
+        self.mid.reset();
+        self.high.reset();
+    }
+
+    fn update_coefficients(&mut self) {
+        let (low, mid, high) = self.settings.coefficients(self.sample_rate);
+        self.low.set_coefficients(low);
+        self.mid.set_coefficients(mid);
+        self.high.set_coefficients(high);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 44100.0;
+
+    // Helper to measure steady-state gain of a sine through the EQ
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )] // Short test lengths
+    fn measure_gain_db(eq: &mut MasterEq, frequency: f32) -> f32 {
+        let omega = 2.0 * std::f32::consts::PI * frequency / SAMPLE_RATE;
+        let mut peak_in = 0.0f32;
+        let mut peak_out = 0.0f32;
+
+        for n in 0..(SAMPLE_RATE as usize / 2) {
+            let input = (omega * n as f32).sin();
+            let output = eq.process(input);
+
+            // Skip the settling time
+            if n > SAMPLE_RATE as usize / 4 {
+                peak_in = peak_in.max(input.abs());
+                peak_out = peak_out.max(output.abs());
+            }
+        }
+
+        20.0 * (peak_out / peak_in).log10()
+    }
+
+    #[test]
+    fn test_flat_eq_is_transparent() {
+        let mut eq = MasterEq::new(SAMPLE_RATE);
+
+        for frequency in [50.0, 1000.0, 10000.0] {
+            let gain = measure_gain_db(&mut eq, frequency);
+            assert!(
+                gain.abs() < 0.1,
+                "Flat EQ should be unity at {frequency} Hz, got {gain} dB"
+            );
+        }
+    }
+
+    #[test]
+    fn test_mid_band_boost() {
+        let mut eq = MasterEq::new(SAMPLE_RATE);
+        eq.set_settings(EqSettings {
+            mid_freq: 1000.0,
+            mid_gain_db: 6.0,
+            mid_q: 1.0,
+            ..EqSettings::default()
+        });
+
+        let gain = measure_gain_db(&mut eq, 1000.0);
+        assert!(
+            (gain - 6.0).abs() < 0.3,
+            "Expected ~6 dB at the mid frequency, got {gain}"
+        );
+    }
+
+    #[test]
+    fn test_shelves_affect_their_ends_of_the_spectrum() {
+        let settings = EqSettings {
+            low_gain_db: -12.0,
+            high_gain_db: 6.0,
+            ..EqSettings::default()
+        };
+
+        assert!(settings.magnitude_db(SAMPLE_RATE, 30.0) < -10.0);
+        assert!(settings.magnitude_db(SAMPLE_RATE, 18000.0) > 5.0);
+        assert!(settings.magnitude_db(SAMPLE_RATE, 1000.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_extreme_settings_are_stable() {
+        let mut eq = MasterEq::new(SAMPLE_RATE);
+        eq.set_settings(EqSettings {
+            low_freq: 20.0,
+            low_gain_db: 15.0,
+            mid_freq: 20000.0,
+            mid_gain_db: 15.0,
+            mid_q: 10.0,
+            high_freq: 22000.0,
+            high_gain_db: -15.0,
+        });
+
+        for n in 0..10000 {
+            let input = if n % 100 == 0 { 1.0 } else { 0.0 };
+            assert!(eq.process(input).is_finite());
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! Expression and breath controllers for Naughty and Tender
+//!
+//! Expression (CC 11) and breath (CC 2) are the performance controllers of
+//! pedal and wind-controller players. Both become global modulation sources for
+//! the mod matrix, so breath can open the filter or swell the level; expression
+//! also drives the output level through a fixed curve by default, like a volume
+//! pedal, without any routing set up.
+//!
+//! Incoming values are smoothed over a few milliseconds, since 7-bit steps on a
+//! slow breath swell are otherwise audible as zipper noise. A 14-bit pair
+//! (CC 43 / CC 34 LSBs) is used when the controller sends one.
+//!
+//! Expression starts at full (MIDI's default for CC 11), so the synth isn't
+//! silent before a pedal is touched; breath starts at zero.
+//!
+//! # References
+//! - MIDI 1.0 Control Change: CC 2 Breath Controller, CC 11 Expression
+//! - Yamaha WX / Akai EWI wind controllers: breath on CC 2 (or CC 11)
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+/// Breath controller CC
+pub const BREATH_CC: u8 = 2;
+
+/// Expression CC
+pub const EXPRESSION_CC: u8 = 11;
+
+/// Smoothing time for incoming controller steps, in milliseconds
+const SMOOTHING_MS: f32 = 5.0;
+
+/// Level range of the exponential curve: expression at zero is this far down,
+/// then silence
+const EXPONENTIAL_RANGE_DB: f32 = 40.0;
+
+/// How expression sets the output level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExpressionCurve {
+    /// Expression is only a mod matrix source
+    Off,
+    /// Gain follows the pedal directly
+    Linear,
+    /// Even steps in dB across the pedal's travel, like a volume pedal
+    #[default]
+    Exponential,
+}
+
+impl ExpressionCurve {
+    /// Every curve, in parameter index order
+    pub const ALL: [Self; 3] = [Self::Off, Self::Linear, Self::Exponential];
+
+    /// Display names, in parameter index order
+    pub const NAMES: [&'static str; 3] = ["Off", "Linear", "Exponential"];
+
+    /// Curve at a parameter index (out-of-range falls back to `Exponential`)
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or_default()
+    }
+
+    /// Output gain for an expression value (0.0 - 1.0)
+    #[must_use]
+    pub fn gain(self, expression: f32) -> f32 {
+        let expression = expression.clamp(0.0, 1.0);
+        match self {
+            Self::Off => 1.0,
+            Self::Linear => expression,
+            Self::Exponential if expression <= 0.0 => 0.0,
+            Self::Exponential => {
+                let db = (expression - 1.0) * EXPONENTIAL_RANGE_DB;
+                10.0f32.powf(db / 20.0)
+            }
+        }
+    }
+}
+
+/// One smoothed controller value
+#[derive(Debug, Clone, Copy)]
+struct SmoothedController {
+    target: f32,
+    value: f32,
+}
+
+impl SmoothedController {
+    fn new(value: f32) -> Self {
+        Self {
+            target: value,
+            value,
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, coeff: f32) -> f32 {
+        self.value += (self.target - self.value) * coeff;
+        self.value
+    }
+}
+
+/// Smoothed expression and breath values
+///
+/// # Real-time Safety
+/// - Fixed state, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::expression::{ExpressionInput, EXPRESSION_CC};
+///
+/// let mut input = ExpressionInput::new(48000.0);
+/// assert!(input.handle_cc(EXPRESSION_CC, 0.0));
+/// for _ in 0..4800 {
+///     input.process();
+/// }
+/// assert!(input.expression() < 0.01);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExpressionInput {
+    expression: SmoothedController,
+    breath: SmoothedController,
+
+    /// Lag filter coefficient
+    coeff: f32,
+}
+
+impl ExpressionInput {
+    /// Create an input with expression at full and breath at zero
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        let mut input = Self {
+            expression: SmoothedController::new(1.0),
+            breath: SmoothedController::new(0.0),
+            coeff: 1.0,
+        };
+        input.set_sample_rate(sample_rate);
+        input
+    }
+
+    /// Set sample rate
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.coeff = 1.0 - (-1.0 / (SMOOTHING_MS / 1000.0 * sample_rate)).exp();
+    }
+
+    /// Follow a controller (normalized value), returning whether it was
+    /// expression or breath
+    pub fn handle_cc(&mut self, cc: u8, value: f32) -> bool {
+        let controller = match cc {
+            EXPRESSION_CC => &mut self.expression,
+            BREATH_CC => &mut self.breath,
+            _ => return false,
+        };
+        controller.target = value.clamp(0.0, 1.0);
+        true
+    }
+
+    /// Advance the smoothing by one sample
+    #[inline]
+    pub fn process(&mut self) {
+        self.expression.process(self.coeff);
+        self.breath.process(self.coeff);
+    }
+
+    /// Smoothed expression (0.0 - 1.0)
+    #[must_use]
+    pub fn expression(&self) -> f32 {
+        self.expression.value
+    }
+
+    /// Smoothed breath (0.0 - 1.0)
+    #[must_use]
+    pub fn breath(&self) -> f32 {
+        self.breath.value
+    }
+
+    /// Back to expression at full and breath at zero (new session or reset)
+    pub fn reset(&mut self) {
+        self.expression = SmoothedController::new(1.0);
+        self.breath = SmoothedController::new(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    #[test]
+    #[allow(clippy::float_cmp)] // Defaults are exact
+    fn test_defaults_leave_the_synth_audible() {
+        let input = ExpressionInput::new(SAMPLE_RATE);
+        assert_eq!(input.expression(), 1.0);
+        assert_eq!(input.breath(), 0.0);
+        for curve in ExpressionCurve::ALL {
+            assert!((curve.gain(input.expression()) - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_steps_are_smoothed() {
+        let mut input = ExpressionInput::new(SAMPLE_RATE);
+        assert!(input.handle_cc(BREATH_CC, 1.0));
+        assert!(!input.handle_cc(1, 1.0), "Mod wheel isn't handled here");
+
+        input.process();
+        let first = input.breath();
+        assert!(
+            first > 0.0 && first < 0.05,
+            "Should glide, not jump: {first}"
+        );
+
+        // Five time constants later it's all but there
+        for _ in 0..1200 {
+            input.process();
+        }
+        assert!(input.breath() > 0.99);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // The curve endpoints are exact
+    fn test_exponential_curve_is_even_in_db() {
+        let curve = ExpressionCurve::Exponential;
+        let db = |expression: f32| 20.0 * curve.gain(expression).log10();
+        assert!((db(0.5) + 20.0).abs() < 1e-3);
+        assert!((db(0.75) - db(0.5) - 10.0).abs() < 1e-3);
+        assert_eq!(curve.gain(0.0), 0.0, "Pedal at heel is silent");
+
+        assert!((ExpressionCurve::Linear.gain(0.25) - 0.25).abs() < 1e-6);
+        assert_eq!(ExpressionCurve::Off.gain(0.0), 1.0);
+    }
+}
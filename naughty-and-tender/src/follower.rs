@@ -0,0 +1,175 @@
+//! Sidechain envelope follower for Naughty and Tender
+//!
+//! Tracks the level of the auxiliary (sidechain) audio input and turns it into a
+//! global modulation source (0.0 to 1.0), so the synth can react to external
+//! audio: a drum loop opening the filter, a vocal ducking the level, and so on.
+//!
+//! The input is rectified and smoothed by a one-pole filter with separate attack
+//! and release times, then scaled by an input gain and clamped.
+//!
+//! # References
+//! - Peak detector with attack/release ballistics, as in analog compressors
+//! - One-pole smoothing: `y += (x - y) * (1 - e^(-1 / (time * sample_rate)))`
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+/// Attack/release envelope follower
+///
+/// # Real-time Safety
+/// - Fixed state, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::follower::EnvelopeFollower;
+///
+/// let mut follower = EnvelopeFollower::new(48000.0);
+/// follower.set_attack_ms(5.0);
+/// follower.set_release_ms(150.0);
+/// follower.set_gain_db(6.0);
+/// let modulation = follower.process(0.3);
+/// ```
+pub struct EnvelopeFollower {
+    /// Smoothed, rectified input level
+    level: f32,
+
+    /// Smoothing coefficient while the level rises
+    attack_coeff: f32,
+
+    /// Smoothing coefficient while the level falls
+    release_coeff: f32,
+
+    /// Linear gain applied to the level before clamping
+    gain: f32,
+
+    /// Sample rate in Hz
+    sample_rate: f32,
+}
+
+impl EnvelopeFollower {
+    /// Create a follower with 10 ms attack, 100 ms release and unity gain
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        let mut follower = Self {
+            level: 0.0,
+            attack_coeff: 1.0,
+            release_coeff: 1.0,
+            gain: 1.0,
+            sample_rate,
+        };
+        follower.set_attack_ms(10.0);
+        follower.set_release_ms(100.0);
+        follower
+    }
+
+    /// Set the time to follow a rising level, in milliseconds
+    pub fn set_attack_ms(&mut self, attack_ms: f32) {
+        self.attack_coeff = smoothing_coeff(attack_ms, self.sample_rate);
+    }
+
+    /// Set the time to follow a falling level, in milliseconds
+    pub fn set_release_ms(&mut self, release_ms: f32) {
+        self.release_coeff = smoothing_coeff(release_ms, self.sample_rate);
+    }
+
+    /// Set the input gain in dB (boosts quiet sidechain signals)
+    pub fn set_gain_db(&mut self, gain_db: f32) {
+        self.gain = 10.0_f32.powf(gain_db / 20.0);
+    }
+
+    /// Follow one input sample and return the modulation value (0.0 to 1.0)
+    #[inline]
+    pub fn process(&mut self, input: f32) -> f32 {
+        let rectified = input.abs();
+        let coeff = if rectified > self.level {
+            self.attack_coeff
+        } else {
+            self.release_coeff
+        };
+        self.level += (rectified - self.level) * coeff;
+
+        self.value()
+    }
+
+    /// Current modulation value (0.0 to 1.0)
+    #[must_use]
+    pub fn value(&self) -> f32 {
+        (self.level * self.gain).min(1.0)
+    }
+
+    /// Drop back to silence
+    pub fn reset(&mut self) {
+        self.level = 0.0;
+    }
+}
+
+/// One-pole coefficient for a time constant in milliseconds (0 = instant)
+fn smoothing_coeff(time_ms: f32, sample_rate: f32) -> f32 {
+    if time_ms <= 0.0 {
+        1.0
+    } else {
+        1.0 - (-1.0 / (time_ms / 1000.0 * sample_rate)).exp()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 1000.0;
+
+    #[test]
+    fn test_follows_level_of_bipolar_signal() {
+        let mut follower = EnvelopeFollower::new(SAMPLE_RATE);
+        follower.set_attack_ms(0.0);
+        follower.set_release_ms(1000.0);
+
+        // A full-scale square wave reads as a steady full level
+        for i in 0..100 {
+            let input = if i % 2 == 0 { 1.0 } else { -1.0 };
+            follower.process(input);
+        }
+        assert!(follower.value() > 0.99);
+    }
+
+    #[test]
+    fn test_attack_faster_than_release() {
+        let mut follower = EnvelopeFollower::new(SAMPLE_RATE);
+        follower.set_attack_ms(5.0);
+        follower.set_release_ms(200.0);
+
+        for _ in 0..20 {
+            follower.process(1.0);
+        }
+        let attacked = follower.value();
+        assert!(attacked > 0.95, "attack should reach the level quickly");
+
+        for _ in 0..20 {
+            follower.process(0.0);
+        }
+        assert!(follower.value() > 0.85, "release should fall slowly");
+    }
+
+    #[test]
+    fn test_gain_boosts_and_clamps() {
+        let mut follower = EnvelopeFollower::new(SAMPLE_RATE);
+        follower.set_attack_ms(0.0);
+        follower.process(0.25);
+        assert!((follower.value() - 0.25).abs() < 1e-6);
+
+        follower.set_gain_db(6.0206);
+        assert!((follower.value() - 0.5).abs() < 1e-4);
+
+        follower.set_gain_db(24.0);
+        assert!((follower.value() - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_reset_silences() {
+        let mut follower = EnvelopeFollower::new(SAMPLE_RATE);
+        for _ in 0..100 {
+            follower.process(1.0);
+        }
+        follower.reset();
+        assert!(follower.value().abs() < f32::EPSILON);
+    }
+}
@@ -0,0 +1,131 @@
+//! Host automation gestures for Naughty and Tender
+//!
+//! Hosts record automation between a parameter's begin and end gesture calls:
+//! in touch mode they write for as long as the gesture is open, and many merge
+//! everything in one gesture into a single undo step. A value set without a
+//! gesture around it, or a drag sent as one short gesture per frame, records as
+//! a burst of disconnected points.
+//!
+//! [`OpenGestures`] tracks which parameters a widget or controller has a gesture
+//! open for, so each begins once when it's first touched and ends once when the
+//! drag is released. Editor widgets that drag across several parameters (the
+//! step sequencer grid, the harmonic editor) open one gesture per parameter they
+//! pass over and end them all together. MIDI controllers have no release, so
+//! their gestures end once the controller has been still for a moment, the way
+//! touch-sensitive control surfaces behave.
+//!
+//! # References
+//! - CLAP `params` extension: `CLAP_EVENT_PARAM_GESTURE_BEGIN` / `_END`
+//! - VST3 `IComponentHandler::beginEdit` / `endEdit`
+//! - Touch, latch and write automation modes in DAWs
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+/// How long a MIDI controller has to be still before its gesture ends, in seconds
+pub const CONTROLLER_RELEASE_S: f64 = 0.5;
+
+/// Parameters with a gesture open, with when each was last touched
+///
+/// Keys are whatever identifies a parameter to the owner (an index into the
+/// editor's parameter list, a step number, ...).
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::gesture::OpenGestures;
+///
+/// let mut gestures = OpenGestures::default();
+/// assert!(gestures.touch(3, 0.0)); // Begin the gesture, then set the value
+/// assert!(!gestures.touch(3, 0.1)); // Already open: just set the value
+/// assert_eq!(gestures.close_all(), vec![3]); // End it on release
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpenGestures<K> {
+    /// Open gestures and the time each was last touched, in seconds
+    open: Vec<(K, f64)>,
+}
+
+impl<K: Copy + PartialEq> OpenGestures<K> {
+    /// Touch a parameter at `now_s`, returning whether its gesture has to begin
+    pub fn touch(&mut self, key: K, now_s: f64) -> bool {
+        if let Some((_, touched)) = self.open.iter_mut().find(|(other, _)| *other == key) {
+            *touched = now_s;
+            false
+        } else {
+            self.open.push((key, now_s));
+            true
+        }
+    }
+
+    /// Whether a parameter's gesture is open
+    #[must_use]
+    pub fn is_open(&self, key: K) -> bool {
+        self.open.iter().any(|(other, _)| *other == key)
+    }
+
+    /// Whether any gesture is open
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.open.is_empty()
+    }
+
+    /// Close every gesture, returning the parameters whose gestures have to end
+    pub fn close_all(&mut self) -> Vec<K> {
+        self.open.drain(..).map(|(key, _)| key).collect()
+    }
+
+    /// Close the gestures untouched for `idle_s` seconds, returning the
+    /// parameters whose gestures have to end
+    pub fn close_idle(&mut self, now_s: f64, idle_s: f64) -> Vec<K> {
+        let mut closed = Vec::new();
+        self.open.retain(|&(key, touched)| {
+            let idle = now_s - touched >= idle_s;
+            if idle {
+                closed.push(key);
+            }
+            !idle
+        });
+        closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_each_parameter_begins_once() {
+        let mut gestures = OpenGestures::default();
+        assert!(gestures.touch(1, 0.0));
+        assert!(gestures.touch(2, 0.0));
+        assert!(!gestures.touch(1, 0.1));
+        assert!(gestures.is_open(2));
+        assert!(!gestures.is_open(3));
+    }
+
+    #[test]
+    fn test_release_ends_every_gesture_once() {
+        let mut gestures = OpenGestures::default();
+        for step in [4, 5, 6, 5, 4] {
+            gestures.touch(step, 0.0);
+        }
+        assert_eq!(gestures.close_all(), vec![4, 5, 6]);
+        assert!(gestures.is_empty());
+        assert!(gestures.close_all().is_empty());
+
+        // The next drag begins again
+        assert!(gestures.touch(4, 1.0));
+    }
+
+    #[test]
+    fn test_idle_controllers_release() {
+        let mut gestures = OpenGestures::default();
+        gestures.touch(10, 0.0);
+        gestures.touch(11, 0.0);
+        gestures.touch(11, 0.4);
+
+        assert!(gestures.close_idle(0.3, CONTROLLER_RELEASE_S).is_empty());
+        assert_eq!(gestures.close_idle(0.6, CONTROLLER_RELEASE_S), vec![10]);
+        assert!(gestures.is_open(11));
+        assert_eq!(gestures.close_idle(1.0, CONTROLLER_RELEASE_S), vec![11]);
+    }
+}
@@ -0,0 +1,334 @@
+//! Granular texture mode for the sample engine
+//!
+//! Instead of playing the sample from start to end, a granular voice plays many
+//! short, overlapping fragments ("grains") of it. Each grain reads from around
+//! a chosen position, fades in and out through a window so it doesn't click,
+//! and can be randomly detuned. Dense clouds of grains smear the sample into
+//! pads and textures; sparse ones sound like stutters.
+//!
+//! Grains live in a fixed pool per voice; when the pool is full, new grains are
+//! skipped until one finishes.
+//!
+//! # References
+//! - Roads, "Microsound" (2001), asynchronous granular synthesis
+//! - Truax, "Real-Time Granular Synthesis with a Digital Signal Processor" (1988)
+//! - Overlap-compensated gain: 1 / sqrt(density * grain length)
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use std::f32::consts::TAU;
+
+use crate::sampler::Interpolation;
+use shared_core::noise::NoiseGenerator;
+
+/// Most grains sounding at once per voice
+pub const MAX_GRAINS: usize = 64;
+
+/// Amplitude envelope of each grain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GrainWindow {
+    /// Raised cosine: smooth, the classic grain shape
+    #[default]
+    Hann,
+    /// Linear fade in and out: slightly more present
+    Triangle,
+    /// Short fades around a flat top: punchy, keeps transients
+    Trapezoid,
+}
+
+impl GrainWindow {
+    /// Every window, in parameter index order
+    pub const ALL: [Self; 3] = [Self::Hann, Self::Triangle, Self::Trapezoid];
+
+    /// Display names, in parameter index order
+    pub const NAMES: [&'static str; 3] = ["Hann", "Triangle", "Trapezoid"];
+
+    /// Window at a parameter index (out-of-range falls back to `Hann`)
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or_default()
+    }
+
+    /// Window gain at a point through the grain (0.0 - 1.0)
+    #[must_use]
+    pub fn gain(self, t: f32) -> f32 {
+        match self {
+            Self::Hann => 0.5 - 0.5 * (TAU * t).cos(),
+            Self::Triangle => 1.0 - (2.0 * t - 1.0).abs(),
+            // Quarter-length fades
+            Self::Trapezoid => (t.min(1.0 - t) * 4.0).min(1.0),
+        }
+    }
+}
+
+/// Grain cloud settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GranularSettings {
+    /// Grain length in milliseconds
+    pub size_ms: f32,
+    /// Grains started per second
+    pub density_hz: f32,
+    /// Centre read position (fraction of the sample length, 0.0 - 1.0)
+    pub position: f32,
+    /// Random position scatter (1.0 = up to half the sample length either way)
+    pub jitter: f32,
+    /// Random detune per grain, up to this many semitones either way
+    pub pitch_spread: f32,
+    pub window: GrainWindow,
+}
+
+impl Default for GranularSettings {
+    fn default() -> Self {
+        Self {
+            size_ms: 80.0,
+            density_hz: 20.0,
+            position: 0.25,
+            jitter: 0.1,
+            pitch_spread: 0.0,
+            window: GrainWindow::Hann,
+        }
+    }
+}
+
+/// One playing grain
+#[derive(Debug, Clone, Copy, Default)]
+struct Grain {
+    active: bool,
+
+    /// Read position in frames
+    position: f64,
+
+    /// Detune applied on top of the voice's playback rate
+    rate_scale: f64,
+
+    /// Samples played so far
+    age: u32,
+
+    /// Total length in samples
+    length: u32,
+}
+
+/// Per-voice granular renderer over a sample's frames
+///
+/// # Real-time Safety
+/// - Fixed-size grain pool, no allocations in `process()`
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::granular::GrainCloud;
+/// use naughty_and_tender::sampler::Interpolation;
+///
+/// let frames = vec![0.5; 44100];
+/// let mut cloud = GrainCloud::new(44100.0, 1);
+/// cloud.trigger();
+/// let sample = cloud.process(&frames, 1.0, Interpolation::Linear);
+/// ```
+pub struct GrainCloud {
+    grains: [Grain; MAX_GRAINS],
+
+    /// Random source for position jitter and pitch spread
+    noise: NoiseGenerator,
+
+    settings: GranularSettings,
+
+    /// Samples until the next grain starts
+    countdown: f32,
+
+    /// Output gain compensating for grain overlap
+    gain: f32,
+
+    /// Sample rate in Hz
+    sample_rate: f32,
+}
+
+impl GrainCloud {
+    /// Create an empty cloud whose randomness uses `seed`
+    #[must_use]
+    pub fn new(sample_rate: f32, seed: u32) -> Self {
+        let mut cloud = Self {
+            grains: [Grain::default(); MAX_GRAINS],
+            noise: NoiseGenerator::new(seed.wrapping_add(1).wrapping_mul(0x9E37_79B9)),
+            settings: GranularSettings::default(),
+            countdown: 0.0,
+            gain: 1.0,
+            sample_rate,
+        };
+        cloud.set_settings(GranularSettings::default());
+        cloud
+    }
+
+    pub fn set_settings(&mut self, settings: GranularSettings) {
+        self.settings = settings;
+
+        let overlap = settings.density_hz.max(0.0) * settings.size_ms.max(0.0) / 1000.0;
+        self.gain = 1.0 / overlap.max(1.0).sqrt();
+    }
+
+    /// Stop every grain and start a new one on the next sample
+    pub fn trigger(&mut self) {
+        self.reset();
+    }
+
+    /// Stop every grain
+    pub fn reset(&mut self) {
+        for grain in &mut self.grains {
+            grain.active = false;
+        }
+        self.countdown = 0.0;
+    }
+
+    /// Number of grains currently sounding
+    #[must_use]
+    pub fn active_grains(&self) -> usize {
+        self.grains.iter().filter(|grain| grain.active).count()
+    }
+
+    /// Generate one sample from `frames`
+    ///
+    /// `rate` is the voice's playback rate in frames per output sample (1.0 plays
+    /// the sample at its recorded pitch).
+    #[inline]
+    pub fn process(&mut self, frames: &[f32], rate: f32, interpolation: Interpolation) -> f32 {
+        if frames.is_empty() {
+            return 0.0;
+        }
+
+        self.countdown -= 1.0;
+        if self.countdown <= 0.0 {
+            self.spawn(frames.len());
+            self.countdown += self.sample_rate / self.settings.density_hz.max(0.1);
+        }
+
+        #[allow(clippy::cast_precision_loss)] // Sample lengths are well below 2^52
+        let length = frames.len() as f64;
+        let window = self.settings.window;
+        let mut output = 0.0;
+        for grain in self.grains.iter_mut().filter(|grain| grain.active) {
+            #[allow(clippy::cast_precision_loss)] // Grain lengths are well below 2^24
+            let t = grain.age as f32 / grain.length as f32;
+            output += interpolation.read(frames, grain.position) * window.gain(t);
+
+            // Grains wrap around the sample rather than running off its end
+            grain.position = (grain.position + f64::from(rate) * grain.rate_scale) % length;
+            grain.age += 1;
+            grain.active = grain.age < grain.length;
+        }
+
+        output * self.gain
+    }
+
+    /// Start a grain in a free slot (skipped if the pool is full)
+    fn spawn(&mut self, frames: usize) {
+        let Some(grain) = self.grains.iter_mut().find(|grain| !grain.active) else {
+            return;
+        };
+
+        let settings = self.settings;
+        let position = settings.position + settings.jitter * 0.5 * self.noise.next_bipolar();
+        let detune = settings.pitch_spread * self.noise.next_bipolar();
+
+        #[allow(clippy::cast_precision_loss)] // Sample lengths are well below 2^52
+        let frames = frames as f64;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Positive, bounded
+        let length = (settings.size_ms.max(1.0) / 1000.0 * self.sample_rate).max(1.0) as u32;
+
+        *grain = Grain {
+            active: true,
+            position: f64::from(position).rem_euclid(1.0) * frames,
+            rate_scale: f64::from((detune / 12.0).exp2()),
+            age: 0,
+            length,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 1000.0;
+
+    fn cloud(settings: GranularSettings) -> GrainCloud {
+        let mut cloud = GrainCloud::new(SAMPLE_RATE, 3);
+        cloud.set_settings(settings);
+        cloud.trigger();
+        cloud
+    }
+
+    #[test]
+    fn test_windows_fade_to_silence() {
+        for window in GrainWindow::ALL {
+            assert!(window.gain(0.0).abs() < 1e-6, "{window:?} starts silent");
+            assert!(
+                (window.gain(0.5) - 1.0).abs() < 1e-6,
+                "{window:?} peaks mid-grain"
+            );
+            assert!(window.gain(1.0).abs() < 1e-6, "{window:?} ends silent");
+        }
+    }
+
+    #[test]
+    fn test_density_sets_grain_count() {
+        // 100 ms grains at 50 per second overlap 5 deep
+        let mut cloud = cloud(GranularSettings {
+            size_ms: 100.0,
+            density_hz: 50.0,
+            ..GranularSettings::default()
+        });
+        let frames = vec![1.0; 1000];
+        for _ in 0..510 {
+            cloud.process(&frames, 1.0, Interpolation::Linear);
+        }
+        assert_eq!(cloud.active_grains(), 5);
+    }
+
+    #[test]
+    fn test_pool_limits_grains() {
+        let mut cloud = cloud(GranularSettings {
+            size_ms: 1000.0,
+            density_hz: 1000.0,
+            ..GranularSettings::default()
+        });
+        let frames = vec![1.0; 1000];
+        for _ in 0..200 {
+            cloud.process(&frames, 1.0, Interpolation::Linear);
+        }
+        assert_eq!(cloud.active_grains(), MAX_GRAINS);
+    }
+
+    #[test]
+    fn test_grains_read_from_position() {
+        // Without jitter every grain reads the frame at the position
+        let mut frames = vec![0.0; 100];
+        frames[50] = 1.0;
+        let mut cloud = cloud(GranularSettings {
+            size_ms: 2.0,
+            density_hz: 1.0,
+            position: 0.5,
+            jitter: 0.0,
+            ..GranularSettings::default()
+        });
+
+        // The window is silent on the grain's first sample and fully open on its second
+        let output: Vec<f32> = (0..2)
+            .map(|_| cloud.process(&frames, 0.0, Interpolation::Linear))
+            .collect();
+        assert!(output[1] > 0.5);
+    }
+
+    #[test]
+    fn test_output_stays_bounded() {
+        let mut cloud = cloud(GranularSettings {
+            size_ms: 200.0,
+            density_hz: 100.0,
+            jitter: 1.0,
+            pitch_spread: 12.0,
+            ..GranularSettings::default()
+        });
+        let frames: Vec<f32> = (0..1000u16).map(|i| (f32::from(i) * 0.1).sin()).collect();
+        for _ in 0..2000 {
+            let sample = cloud.process(&frames, 1.5, Interpolation::Cubic);
+            assert!(sample.is_finite() && sample.abs() < 10.0);
+        }
+    }
+}
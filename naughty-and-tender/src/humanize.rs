@@ -0,0 +1,337 @@
+//! Timing and velocity humanization for Naughty and Tender
+//!
+//! Loosens up sequenced or quantized MIDI before it reaches the synth: each
+//! note-on is delayed by a random few milliseconds and its velocity nudged up or
+//! down at random. A note-off is delayed by the same amount as its note-on, so
+//! notes keep their length, and every key's events stay in the order they came.
+//!
+//! Notes can only be delayed, never played early, since the events arrive when
+//! they're due. Delays are counted in samples, so a delayed note can land
+//! anywhere in a later block, sample-accurately.
+//!
+//! # References
+//! - "Humanize" in hardware sequencers and DAWs (MPC, Cubase, Logic): random
+//!   timing and velocity offsets within a set range
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use crate::key_queue::KeyQueue;
+use crate::NUM_NOTES;
+use shared_core::noise::NoiseGenerator;
+
+/// Longest timing offset, in milliseconds
+pub const MAX_TIMING_MS: f32 = 50.0;
+
+/// Most note events waiting at once (far more than a 50 ms window ever holds)
+pub const MAX_PENDING: usize = 1024;
+
+/// Quietest velocity jitter can push a note-on to (a zero velocity would be silent)
+const MIN_VELOCITY: f32 = 1.0 / 127.0;
+
+/// A note event once its delay is up, with the host's voice id if it sent one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyEvent {
+    NoteOn {
+        note: u8,
+        velocity: f32,
+        channel: u8,
+        voice_id: Option<i32>,
+    },
+    NoteOff {
+        note: u8,
+        velocity: f32,
+        channel: u8,
+        voice_id: Option<i32>,
+    },
+}
+
+impl KeyEvent {
+    /// Note the event is for
+    #[must_use]
+    pub fn note(self) -> u8 {
+        match self {
+            Self::NoteOn { note, .. } | Self::NoteOff { note, .. } => note,
+        }
+    }
+}
+
+/// Delays note events by a random offset and jitters note-on velocities
+///
+/// Every incoming note event goes in with [`Humanizer::note_on`] or
+/// [`Humanizer::note_off`]; once per sample, [`Humanizer::pop_due`] hands back the
+/// events due, then [`Humanizer::advance`] moves to the next sample. With both
+/// amounts at zero, events come back out the same sample they went in.
+///
+/// # Real-time Safety
+/// - Queue allocated once at construction, never grows
+/// - When the queue is short of room, note-ons are dropped; their note-offs
+///   always fit
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::humanize::{Humanizer, KeyEvent};
+///
+/// let mut humanizer = Humanizer::new(48000.0);
+/// humanizer.set_timing_ms(10.0);
+/// humanizer.note_on(60, 0.8, 0, None);
+///
+/// // Somewhere in the next 10 ms
+/// let mut waited = 0;
+/// let event = loop {
+///     if let Some(event) = humanizer.pop_due() {
+///         break event;
+///     }
+///     humanizer.advance();
+///     waited += 1;
+/// };
+/// assert!(waited <= 480);
+/// assert!(matches!(event, KeyEvent::NoteOn { note: 60, .. }));
+/// ```
+pub struct Humanizer {
+    /// Sample rate in Hz
+    sample_rate: f32,
+
+    /// Longest note-on delay in milliseconds (0 = on time)
+    timing_ms: f32,
+
+    /// Largest velocity change either way (0.0 - 1.0)
+    velocity_amount: f32,
+
+    /// Events waiting for their delay
+    queue: KeyQueue,
+
+    /// Delay given to each key's latest note-on, in samples
+    key_delay: [u64; NUM_NOTES],
+
+    /// Source of the random offsets
+    rng: NoiseGenerator,
+}
+
+impl Humanizer {
+    /// Create a humanizer with both amounts at zero
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            sample_rate,
+            timing_ms: 0.0,
+            velocity_amount: 0.0,
+            queue: KeyQueue::new(MAX_PENDING),
+            key_delay: [0; NUM_NOTES],
+            rng: NoiseGenerator::new(0x4855_4d41),
+        }
+    }
+
+    /// Set sample rate (forgets pending events)
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.reset();
+    }
+
+    /// Set the longest note-on delay in milliseconds (0 - [`MAX_TIMING_MS`])
+    pub fn set_timing_ms(&mut self, timing_ms: f32) {
+        self.timing_ms = timing_ms.clamp(0.0, MAX_TIMING_MS);
+    }
+
+    /// Set the largest velocity change either way (0.0 - 1.0)
+    pub fn set_velocity_amount(&mut self, amount: f32) {
+        self.velocity_amount = amount.clamp(0.0, 1.0);
+    }
+
+    /// Queue a note-on with a random delay and velocity
+    pub fn note_on(&mut self, note: u8, velocity: f32, channel: u8, voice_id: Option<i32>) {
+        let jitter = self.velocity_amount * self.rng.next_bipolar();
+        let velocity = (velocity + jitter).clamp(MIN_VELOCITY, 1.0);
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // 0 to 50 ms
+        let max_delay = (self.timing_ms / 1000.0 * self.sample_rate) as u64;
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            clippy::cast_precision_loss
+        )]
+        let delay = (self.rng.next_unipolar() * max_delay as f32) as u64;
+
+        self.key_delay[usize::from(note)] = delay;
+        self.queue.push(
+            delay,
+            KeyEvent::NoteOn {
+                note,
+                velocity,
+                channel,
+                voice_id,
+            },
+        );
+    }
+
+    /// Queue a note-off, delayed as much as the key's note-on
+    pub fn note_off(&mut self, note: u8, velocity: f32, channel: u8, voice_id: Option<i32>) {
+        let delay = self.key_delay[usize::from(note)];
+        self.queue.push(
+            delay,
+            KeyEvent::NoteOff {
+                note,
+                velocity,
+                channel,
+                voice_id,
+            },
+        );
+    }
+
+    /// Next event due at the current sample, in arrival order
+    pub fn pop_due(&mut self) -> Option<KeyEvent> {
+        self.queue.pop_due()
+    }
+
+    /// Move on to the next sample
+    #[inline]
+    pub fn advance(&mut self) {
+        self.queue.advance();
+    }
+
+    /// Number of events waiting
+    #[must_use]
+    pub fn pending_count(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Forget every pending event
+    pub fn reset(&mut self) {
+        self.queue.reset();
+        self.key_delay = [0; NUM_NOTES];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    /// Run for a number of samples, collecting (sample, event)
+    fn run(humanizer: &mut Humanizer, samples: usize, events: &mut Vec<(u64, KeyEvent)>) {
+        for _ in 0..samples {
+            while let Some(event) = humanizer.pop_due() {
+                events.push((humanizer.queue.clock(), event));
+            }
+            humanizer.advance();
+        }
+    }
+
+    /// Run until the queue empties, collecting (sample, event)
+    fn drain(humanizer: &mut Humanizer, events: &mut Vec<(u64, KeyEvent)>) {
+        while humanizer.pending_count() > 0 {
+            run(humanizer, 1, events);
+        }
+    }
+
+    #[test]
+    fn test_zero_amounts_pass_events_straight_through() {
+        let mut humanizer = Humanizer::new(SAMPLE_RATE);
+        humanizer.note_on(60, 0.7, 3, Some(11));
+        humanizer.note_off(60, 0.2, 3, Some(11));
+
+        assert_eq!(
+            humanizer.pop_due(),
+            Some(KeyEvent::NoteOn {
+                note: 60,
+                velocity: 0.7,
+                channel: 3,
+                voice_id: Some(11)
+            })
+        );
+        assert_eq!(
+            humanizer.pop_due(),
+            Some(KeyEvent::NoteOff {
+                note: 60,
+                velocity: 0.2,
+                channel: 3,
+                voice_id: Some(11)
+            })
+        );
+        assert_eq!(humanizer.pop_due(), None);
+    }
+
+    #[test]
+    fn test_delays_stay_within_range_and_vary() {
+        let mut humanizer = Humanizer::new(SAMPLE_RATE);
+        humanizer.set_timing_ms(20.0);
+
+        let mut delays = Vec::new();
+        for note in 0..64 {
+            humanizer.note_on(note, 1.0, 0, None);
+            let mut events = Vec::new();
+            drain(&mut humanizer, &mut events);
+            delays.push(events[0].0);
+            humanizer.reset();
+        }
+
+        assert!(delays.iter().all(|&delay| delay <= 960), "{delays:?}");
+        let spread = delays.iter().max().unwrap() - delays.iter().min().unwrap();
+        assert!(
+            spread > 480,
+            "Delays should cover most of the range: {delays:?}"
+        );
+    }
+
+    #[test]
+    fn test_key_order_preserved() {
+        let mut humanizer = Humanizer::new(SAMPLE_RATE);
+        humanizer.set_timing_ms(30.0);
+
+        // Quick repeats of one key, much shorter than the delays
+        let mut order = Vec::new();
+        for _ in 0..20 {
+            humanizer.note_on(64, 1.0, 0, None);
+            run(&mut humanizer, 50, &mut order);
+            humanizer.note_off(64, 1.0, 0, None);
+            run(&mut humanizer, 50, &mut order);
+        }
+        drain(&mut humanizer, &mut order);
+
+        assert_eq!(order.len(), 40);
+        for pair in order.chunks(2) {
+            assert!(matches!(pair[0].1, KeyEvent::NoteOn { .. }));
+            assert!(matches!(pair[1].1, KeyEvent::NoteOff { .. }));
+        }
+        assert!(order.windows(2).all(|pair| pair[1].0 >= pair[0].0));
+    }
+
+    #[test]
+    fn test_note_length_preserved() {
+        let mut humanizer = Humanizer::new(SAMPLE_RATE);
+        humanizer.set_timing_ms(30.0);
+
+        for note in 40..50 {
+            let mut events = Vec::new();
+            humanizer.note_on(note, 1.0, 0, None);
+            run(&mut humanizer, 500, &mut events);
+            humanizer.note_off(note, 1.0, 0, None);
+            drain(&mut humanizer, &mut events);
+
+            assert_eq!(events.len(), 2);
+            assert_eq!(events[1].0 - events[0].0, 500, "{events:?}");
+        }
+    }
+
+    #[test]
+    fn test_velocity_jitter_stays_in_range() {
+        let mut humanizer = Humanizer::new(SAMPLE_RATE);
+        humanizer.set_velocity_amount(0.3);
+
+        let mut velocities = Vec::new();
+        for velocity in [0.0, 0.5, 1.0] {
+            for _ in 0..100 {
+                humanizer.note_on(60, velocity, 0, None);
+                if let Some(KeyEvent::NoteOn { velocity, .. }) = humanizer.pop_due() {
+                    velocities.push(velocity);
+                }
+            }
+        }
+
+        assert_eq!(velocities.len(), 300);
+        assert!(velocities.iter().all(|v| (MIN_VELOCITY..=1.0).contains(v)));
+        let middle = &velocities[100..200];
+        assert!(middle.iter().all(|v| (v - 0.5).abs() <= 0.3 + 1e-6));
+        assert!(middle.iter().any(|v| (v - 0.5).abs() > 0.1));
+    }
+}
@@ -0,0 +1,166 @@
+//! External audio input for Naughty and Tender
+//!
+//! Turns the synth into a filter/effect box for the host's audio. The main
+//! input is summed to mono (like the voices) and used in one of two ways:
+//! - Gated: the input is blended into every voice in place of its engine, so each
+//!   held note opens its own filter, drive and envelope on the input
+//! - Always: the input runs through a copy of layer A's filter and drive without
+//!   needing notes, then joins the synth in the master chain
+//!
+//! In both modes the master effects (drive, phaser, EQ) follow as usual.
+//!
+//! # References
+//! - Korg MS-20 / Moog modular external signal input into the filter
+//! - nih-plug `AudioIOLayout` main input ports
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use shared_core::effects::waveshaper::{Waveshaper, WaveshaperSettings};
+use shared_core::svf::{FilterPrecision, StateVariableFilter, SvfMode};
+
+/// What the plugin does with its main audio input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputMode {
+    /// Input ignored: a plain synth
+    #[default]
+    Off,
+    /// Input fed through the voices, heard only while notes play
+    Gated,
+    /// Input always filtered and driven, notes or not
+    Always,
+}
+
+impl InputMode {
+    /// Every mode, in parameter index order
+    pub const ALL: [Self; 3] = [Self::Off, Self::Gated, Self::Always];
+
+    /// Display names, in parameter index order
+    pub const NAMES: [&'static str; 3] = ["Off", "Gated", "Always"];
+
+    /// Mode at a parameter index (out-of-range falls back to `Off`)
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or_default()
+    }
+}
+
+/// Filter and drive for the input in `InputMode::Always`
+///
+/// Signal flow: input → filter → drive
+///
+/// # Real-time Safety
+/// - All components pre-allocated, no allocations in `process()`
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::input::InputProcessor;
+/// use shared_core::svf::SvfMode;
+///
+/// let mut input = InputProcessor::new(48000.0);
+/// input.set_filter(true, SvfMode::LowPass, 800.0, 0.4);
+/// let sample = input.process(0.5);
+/// ```
+pub struct InputProcessor {
+    filter: StateVariableFilter,
+
+    /// Whether the filter is in the signal path
+    filter_enabled: bool,
+
+    shaper: Waveshaper,
+
+    /// Whether the drive is in the signal path
+    shaper_enabled: bool,
+}
+
+impl InputProcessor {
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            filter: StateVariableFilter::new(sample_rate),
+            filter_enabled: false,
+            shaper: Waveshaper::new(sample_rate),
+            shaper_enabled: false,
+        }
+    }
+
+    /// Enable or bypass the filter and update its settings
+    pub fn set_filter(&mut self, enabled: bool, mode: SvfMode, cutoff_hz: f32, resonance: f32) {
+        if enabled && !self.filter_enabled {
+            self.filter.reset();
+        }
+        self.filter_enabled = enabled;
+        self.filter.set_mode(mode);
+        self.filter.set_cutoff_hz(cutoff_hz);
+        self.filter.set_resonance(resonance);
+    }
+
+    /// Set the filter arithmetic
+    pub fn set_filter_precision(&mut self, precision: FilterPrecision) {
+        self.filter.set_precision(precision);
+    }
+
+    /// Enable or bypass the drive and update its settings
+    pub fn set_waveshaper(&mut self, enabled: bool, settings: WaveshaperSettings) {
+        if enabled && !self.shaper_enabled {
+            self.shaper.reset();
+        }
+        self.shaper_enabled = enabled;
+        self.shaper.set_settings(settings);
+    }
+
+    /// Process one input sample
+    #[inline]
+    pub fn process(&mut self, input: f32) -> f32 {
+        let audio = if self.filter_enabled {
+            self.filter.process(input)
+        } else {
+            input
+        };
+
+        if self.shaper_enabled {
+            self.shaper.process(audio)
+        } else {
+            audio
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.filter.reset();
+        self.shaper.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    #[test]
+    fn test_bypassed_processor_passes_input_through() {
+        let mut input = InputProcessor::new(SAMPLE_RATE);
+        for sample in [0.25, -0.5, 1.0] {
+            assert!((input.process(sample) - sample).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_lowpass_removes_high_frequencies() {
+        let mut input = InputProcessor::new(SAMPLE_RATE);
+        input.set_filter(true, SvfMode::LowPass, 200.0, 0.0);
+
+        // Nyquist-rate square wave: all energy far above the cutoff
+        let peak = (0..4800)
+            .map(|i| input.process(if i % 2 == 0 { 1.0 } else { -1.0 }))
+            .skip(480)
+            .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        assert!(peak < 0.05, "peak {peak}");
+    }
+
+    #[test]
+    fn test_index_lookup_falls_back_to_off() {
+        assert_eq!(InputMode::from_index(2), InputMode::Always);
+        assert_eq!(InputMode::from_index(9), InputMode::Off);
+        assert_eq!(InputMode::ALL.len(), InputMode::NAMES.len());
+    }
+}
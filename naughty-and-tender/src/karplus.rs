@@ -0,0 +1,449 @@
+//! Karplus-Strong plucked string module for Naughty and Tender
+//!
+//! This module implements a physical-modeling string: a short excitation burst is written
+//! into a tuned delay line, and the line's output is low-pass filtered and fed back into
+//! itself. Each trip around the loop loses a little high-frequency energy, which is exactly
+//! how a real plucked string behaves.
+//!
+//! # References
+//! - Karplus & Strong, "Digital Synthesis of Plucked-String and Drum Timbres" (1983)
+//! - Jaffe & Smith, "Extensions of the Karplus-Strong Plucked-String Algorithm" (1983)
+//! - Loop delay = `sample_rate` / frequency (minus the loop filter's own delay)
+//! - Loop gain per period = 10^(-3 * period / `decay_samples`) for a -60 dB decay time
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use crate::oscillators::{NaiveOscillator, Oscillator, WaveformType};
+use shared_core::noise::NoiseGenerator;
+
+/// Lowest frequency the delay line can be tuned to (just below MIDI note 0, ~8.18 Hz)
+///
+/// The delay buffer is sized for this period at construction so `pluck()` never allocates.
+pub const MIN_FREQUENCY_HZ: f32 = 8.0;
+
+/// Source used to excite the string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExcitationType {
+    /// White noise burst - the classic bright, "plucky" attack
+    Noise,
+    /// One period of the voice's oscillator waveform - softer and more tonal
+    Oscillator,
+}
+
+/// Karplus-Strong string model
+///
+/// # Real-time Safety
+/// - Delay line pre-allocated to the longest possible period in `new()`
+/// - No allocations in `pluck()` or `process()`
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::karplus::KarplusStrong;
+///
+/// let mut string = KarplusStrong::new(44100.0);
+/// string.pluck(220.0);
+/// let sample = string.process();
+/// ```
+pub struct KarplusStrong {
+    /// Circular delay line (length covers the period of `MIN_FREQUENCY_HZ`)
+    delay_line: Vec<f32>,
+
+    /// Current write position in the delay line
+    write_pos: usize,
+
+    /// Loop delay in samples (fractional, read with linear interpolation)
+    delay_samples: f32,
+
+    /// Sample rate in Hz
+    sample_rate: f32,
+
+    /// Loop filter amount (0.0 = bright, 1.0 = darkest two-point average)
+    damping: f32,
+
+    /// Time for the string to decay by 60 dB, in samples
+    decay_samples: f32,
+
+    /// Feedback gain applied on each trip around the loop (derived from decay and period)
+    loop_gain: f32,
+
+    /// Previous delay line output (one-sample memory of the loop filter)
+    last_output: f32,
+
+    /// Excitation source
+    excitation: ExcitationType,
+
+    /// Waveform used for `ExcitationType::Oscillator`
+    excitation_waveform: WaveformType,
+
+    /// Frequency of the current pluck (for the oscillator excitation)
+    frequency: f32,
+
+    /// Oscillator used for tonal excitation
+    oscillator: NaiveOscillator,
+
+    /// Noise source used for noise excitation
+    noise: NoiseGenerator,
+}
+
+impl KarplusStrong {
+    /// Create a new string model
+    ///
+    /// # Arguments
+    /// * `sample_rate` - Sample rate in Hz
+    ///
+    /// # Default Settings
+    /// - Excitation: Noise
+    /// - Damping: 50%
+    /// - Decay: 2000ms
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Positive, bounded length
+    pub fn new(sample_rate: f32) -> Self {
+        let max_period = (sample_rate / MIN_FREQUENCY_HZ).ceil() as usize + 2;
+
+        let mut string = Self {
+            delay_line: vec![0.0; max_period],
+            write_pos: 0,
+            delay_samples: 1.0,
+            sample_rate,
+            damping: 0.5,
+            decay_samples: 0.0,
+            loop_gain: 1.0,
+            last_output: 0.0,
+            excitation: ExcitationType::Noise,
+            excitation_waveform: WaveformType::Sawtooth,
+            frequency: 0.0,
+            oscillator: NaiveOscillator::new(sample_rate),
+            noise: NoiseGenerator::default(),
+        };
+
+        string.set_decay_ms(2000.0);
+        string
+    }
+
+    /// Set the excitation source
+    pub fn set_excitation(&mut self, excitation: ExcitationType) {
+        self.excitation = excitation;
+    }
+
+    /// Set the waveform used by the oscillator excitation
+    pub fn set_excitation_waveform(&mut self, waveform: WaveformType) {
+        self.excitation_waveform = waveform;
+    }
+
+    /// Set loop damping (0.0 to 1.0)
+    ///
+    /// Higher damping removes more high frequencies per trip around the loop,
+    /// giving a darker, more muted string.
+    pub fn set_damping(&mut self, damping: f32) {
+        self.damping = damping.clamp(0.0, 1.0);
+        self.update_tuning();
+    }
+
+    /// Set the -60 dB decay time in milliseconds
+    pub fn set_decay_ms(&mut self, decay_ms: f32) {
+        self.decay_samples = (decay_ms.max(1.0) / 1000.0) * self.sample_rate;
+        self.update_loop_gain();
+    }
+
+    /// Pluck the string at a given frequency
+    ///
+    /// Fills one period of the delay line with the excitation, as in the original
+    /// algorithm, so the string sounds from the very next sample.
+    ///
+    /// # Arguments
+    /// * `frequency` - Fundamental frequency in Hz
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Bounded by buffer length
+    pub fn pluck(&mut self, frequency: f32) {
+        self.frequency = frequency.clamp(MIN_FREQUENCY_HZ, self.sample_rate * 0.5);
+        self.oscillator.set_frequency(self.frequency);
+        self.update_tuning();
+
+        self.delay_line.fill(0.0);
+        self.write_pos = 0;
+        self.last_output = 0.0;
+        self.oscillator.reset();
+
+        // The first read happens `delay_samples` behind the write head, i.e. at the end
+        // of the buffer, so the burst is written just before position zero.
+        let len = self.delay_line.len();
+        let burst = self.delay_samples.ceil() as usize + 1;
+        for i in 0..burst {
+            let sample = self.excitation_sample();
+            self.delay_line[len - burst + i] = sample;
+        }
+    }
+
+    /// Process one sample of the string
+    ///
+    /// # Returns
+    /// String output sample (roughly -1.0 to 1.0)
+    #[inline]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn process(&mut self) -> f32 {
+        let len = self.delay_line.len();
+
+        // Read the delayed sample with linear interpolation for fine tuning
+        let read_pos = self.write_pos as f32 - self.delay_samples + len as f32;
+        let index = read_pos.floor();
+        let frac = read_pos - index;
+        let i0 = (index as usize) % len;
+        let i1 = (i0 + 1) % len;
+        let delayed = self.delay_line[i0] + (self.delay_line[i1] - self.delay_line[i0]) * frac;
+
+        // Loop filter: blend between the current and previous sample.
+        // At full damping this is the classic two-point average (gain cos(πf/fs)).
+        let blend = self.damping * 0.5;
+        let filtered = (1.0 - blend) * delayed + blend * self.last_output;
+        self.last_output = delayed;
+
+        self.delay_line[self.write_pos] = filtered * self.loop_gain;
+        self.write_pos = (self.write_pos + 1) % len;
+
+        delayed
+    }
+
+    /// Reset the string to silence
+    pub fn reset(&mut self) {
+        self.delay_line.fill(0.0);
+        self.write_pos = 0;
+        self.last_output = 0.0;
+        self.oscillator.reset();
+    }
+
+    /// Length of the pre-allocated delay line in samples
+    #[must_use]
+    pub fn max_delay_samples(&self) -> usize {
+        self.delay_line.len()
+    }
+
+    /// Generate the next excitation sample
+    fn excitation_sample(&mut self) -> f32 {
+        match self.excitation {
+            ExcitationType::Noise => self.noise.next_bipolar(),
+            ExcitationType::Oscillator => self.oscillator.process(self.excitation_waveform),
+        }
+    }
+
+    /// Recompute the delay length for the current frequency and damping
+    ///
+    /// The loop filter adds `damping / 2` samples of delay at low frequencies,
+    /// so it is subtracted from the line length to keep the string in tune.
+    fn update_tuning(&mut self) {
+        if self.frequency <= 0.0 {
+            return;
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let max_delay = (self.delay_line.len() - 2) as f32;
+        let period = self.sample_rate / self.frequency;
+        self.delay_samples = (period - self.damping * 0.5).clamp(1.0, max_delay);
+        self.update_loop_gain();
+    }
+
+    /// Recompute per-sample feedback gain so the string decays 60 dB in `decay_samples`
+    fn update_loop_gain(&mut self) {
+        // The signal passes through the gain once per period
+        let period = self.delay_samples.max(1.0);
+        self.loop_gain = 10.0f32.powf(-3.0 * period / self.decay_samples).min(1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 44100.0;
+
+    // Helper to calculate RMS of a signal
+    #[allow(clippy::cast_precision_loss)] // Test buffers are short
+    fn calculate_rms(samples: &[f32]) -> f32 {
+        let sum_squares: f32 = samples.iter().map(|s| s * s).sum();
+        (sum_squares / samples.len() as f32).sqrt()
+    }
+
+    // Helper to find the lag (in samples) with the strongest autocorrelation
+    fn autocorrelation_peak(samples: &[f32], min_lag: usize, max_lag: usize) -> usize {
+        (min_lag..max_lag)
+            .map(|lag| {
+                let sum: f32 = samples
+                    .iter()
+                    .zip(&samples[lag..])
+                    .map(|(a, b)| a * b)
+                    .sum();
+                (lag, sum)
+            })
+            .fold((min_lag, f32::NEG_INFINITY), |best, cur| {
+                if cur.1 > best.1 {
+                    cur
+                } else {
+                    best
+                }
+            })
+            .0
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)] // Delay lengths are short
+    fn test_string_creation() {
+        let string = KarplusStrong::new(SAMPLE_RATE);
+
+        // Delay line must hold the period of the lowest supported frequency
+        assert!(string.max_delay_samples() as f32 >= SAMPLE_RATE / MIN_FREQUENCY_HZ);
+    }
+
+    #[test]
+    fn test_silent_before_pluck() {
+        let mut string = KarplusStrong::new(SAMPLE_RATE);
+
+        for _ in 0..1000 {
+            assert!(
+                string.process().abs() < 1e-9,
+                "String should be silent until plucked"
+            );
+        }
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)] // Lags are short
+    fn test_pluck_pitch_accuracy() {
+        let mut string = KarplusStrong::new(SAMPLE_RATE);
+        string.set_damping(1.0);
+        string.pluck(220.0);
+
+        // Skip the excitation burst, then look at the periodicity
+        for _ in 0..1000 {
+            string.process();
+        }
+        let samples: Vec<f32> = (0..4096).map(|_| string.process()).collect();
+
+        let expected_period = SAMPLE_RATE / 220.0; // ~200.45 samples
+        let lag = autocorrelation_peak(&samples, 100, 300);
+        assert!(
+            (lag as f32 - expected_period).abs() < 1.5,
+            "Expected period ~{expected_period}, got {lag}"
+        );
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Short test lengths
+    fn test_string_decays() {
+        let mut string = KarplusStrong::new(SAMPLE_RATE);
+        string.set_decay_ms(500.0);
+        string.pluck(440.0);
+
+        let early: Vec<f32> = (0..4410).map(|_| string.process()).collect();
+        for _ in 0..(SAMPLE_RATE * 0.5) as usize {
+            string.process();
+        }
+        let late: Vec<f32> = (0..4410).map(|_| string.process()).collect();
+
+        let early_rms = calculate_rms(&early);
+        let late_rms = calculate_rms(&late);
+        assert!(
+            late_rms < early_rms * 0.1,
+            "String should decay by well over 20 dB after its decay time: {early_rms} -> {late_rms}"
+        );
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Short test lengths
+    fn test_longer_decay_rings_longer() {
+        let mut short = KarplusStrong::new(SAMPLE_RATE);
+        let mut long = KarplusStrong::new(SAMPLE_RATE);
+        short.set_decay_ms(200.0);
+        long.set_decay_ms(4000.0);
+        short.pluck(330.0);
+        long.pluck(330.0);
+
+        for _ in 0..(SAMPLE_RATE * 0.3) as usize {
+            short.process();
+            long.process();
+        }
+
+        let short_tail: Vec<f32> = (0..2048).map(|_| short.process()).collect();
+        let long_tail: Vec<f32> = (0..2048).map(|_| long.process()).collect();
+        assert!(calculate_rms(&long_tail) > calculate_rms(&short_tail) * 2.0);
+    }
+
+    #[test]
+    fn test_damping_darkens_tone() {
+        // Measure high-frequency content as the energy of the first difference
+        fn brightness(samples: &[f32]) -> f32 {
+            let diff: Vec<f32> = samples.windows(2).map(|w| w[1] - w[0]).collect();
+            calculate_rms(&diff) / calculate_rms(samples)
+        }
+
+        let mut bright = KarplusStrong::new(SAMPLE_RATE);
+        let mut dark = KarplusStrong::new(SAMPLE_RATE);
+        bright.set_damping(0.0);
+        dark.set_damping(1.0);
+        bright.pluck(220.0);
+        dark.pluck(220.0);
+
+        for _ in 0..4410 {
+            bright.process();
+            dark.process();
+        }
+
+        let bright_samples: Vec<f32> = (0..4096).map(|_| bright.process()).collect();
+        let dark_samples: Vec<f32> = (0..4096).map(|_| dark.process()).collect();
+        assert!(brightness(&dark_samples) < brightness(&bright_samples));
+    }
+
+    #[test]
+    fn test_oscillator_excitation_produces_audio() {
+        let mut string = KarplusStrong::new(SAMPLE_RATE);
+        string.set_excitation(ExcitationType::Oscillator);
+        string.set_excitation_waveform(WaveformType::Triangle);
+        string.pluck(220.0);
+
+        let samples: Vec<f32> = (0..4410).map(|_| string.process()).collect();
+        assert!(
+            calculate_rms(&samples) > 0.01,
+            "Oscillator excitation should ring"
+        );
+    }
+
+    #[test]
+    fn test_extreme_frequencies_are_stable() {
+        let mut string = KarplusStrong::new(96000.0);
+        let capacity = string.max_delay_samples();
+
+        for frequency in [1.0, MIN_FREQUENCY_HZ, 8.18, 12543.0, 48000.0, 100_000.0] {
+            string.pluck(frequency);
+            for _ in 0..2000 {
+                let sample = string.process();
+                assert!(
+                    sample.is_finite(),
+                    "Output should be finite at {frequency} Hz"
+                );
+                assert!(
+                    sample.abs() < 4.0,
+                    "Output should stay bounded at {frequency} Hz"
+                );
+            }
+        }
+
+        // Plucking never reallocates the delay line
+        assert_eq!(string.max_delay_samples(), capacity);
+    }
+
+    #[test]
+    fn test_reset_silences_string() {
+        let mut string = KarplusStrong::new(SAMPLE_RATE);
+        string.pluck(440.0);
+        for _ in 0..100 {
+            string.process();
+        }
+
+        string.reset();
+
+        for _ in 0..1000 {
+            assert!(string.process().abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Notes started per key for Naughty and Tender
+//!
+//! Chord memory and the octaver both turn one key into several notes. Each key
+//! remembers exactly which notes it started, so releasing it releases those
+//! notes even if the chord shape or the octaver levels changed in the meantime.
+//! A note that another held key also started keeps sounding until the last of
+//! them is released.
+//!
+//! # References
+//! - Korg Poly-800 / Polysix chord memory: a held chord released as a unit
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use crate::NUM_NOTES;
+
+/// Notes started by one key (unused entries are `None`)
+pub type StartedNotes<const N: usize> = [Option<u8>; N];
+
+/// Notes each held key started, up to `N` per key
+///
+/// # Real-time Safety
+/// - Fixed-size table, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::key_notes::KeyNotes;
+///
+/// let mut keys = KeyNotes::<2>::new();
+/// keys.start(60, [Some(60), Some(67)]);
+/// keys.start(67, [Some(67), Some(74)]);
+///
+/// // G is still sounding under the second key
+/// assert_eq!(keys.release(60), [Some(60), None]);
+/// assert_eq!(keys.release(67), [Some(67), Some(74)]);
+/// ```
+pub struct KeyNotes<const N: usize> {
+    /// Notes started by each held key
+    sounding: [StartedNotes<N>; NUM_NOTES],
+}
+
+impl<const N: usize> Default for KeyNotes<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> KeyNotes<N> {
+    /// Create with no keys held
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            sounding: [[None; N]; NUM_NOTES],
+        }
+    }
+
+    /// Remember the notes a key started
+    pub fn start(&mut self, key: u8, notes: StartedNotes<N>) {
+        self.sounding[usize::from(key) % NUM_NOTES] = notes;
+    }
+
+    /// Forget a released key, returning the notes to release
+    ///
+    /// Notes still sounding under another held key are left out. A key that
+    /// wasn't tracked (pressed before a reset, say) releases its own note.
+    pub fn release(&mut self, key: u8) -> StartedNotes<N> {
+        let slot = &mut self.sounding[usize::from(key) % NUM_NOTES];
+        let mut released = std::mem::replace(slot, [None; N]);
+        if released.iter().all(Option::is_none) {
+            if let Some(first) = released.first_mut() {
+                *first = Some(key);
+            }
+        }
+
+        for entry in &mut released {
+            if let Some(n) = *entry {
+                let shared = self.sounding.iter().any(|other| other.contains(&Some(n)));
+                if shared {
+                    *entry = None;
+                }
+            }
+        }
+        released
+    }
+
+    /// Forget every held key
+    pub fn reset(&mut self) {
+        self.sounding = [[None; N]; NUM_NOTES];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_untracked_key_releases_itself() {
+        let mut keys = KeyNotes::<3>::new();
+        assert_eq!(keys.release(42), [Some(42), None, None]);
+    }
+
+    #[test]
+    fn test_reset_forgets_held_keys() {
+        let mut keys = KeyNotes::<2>::new();
+        keys.start(60, [Some(60), Some(72)]);
+        keys.start(48, [Some(48), Some(60)]);
+        keys.reset();
+        assert_eq!(keys.release(48), [Some(48), None]);
+    }
+}
@@ -0,0 +1,222 @@
+//! Delayed note events for Naughty and Tender
+//!
+//! The humanizer and the strummer both hold note events back for a number of
+//! samples. They share this queue: each event waits until its delay is up, and
+//! an event never overtakes an earlier one for the same note, so a note-off
+//! can't be handed back before its note-on.
+//!
+//! The queue has a fixed size. When it runs short of room, note-ons are dropped
+//! but note-offs are not: a slot is kept for the note-off of every note-on let
+//! in, so a note that starts always gets its release.
+//!
+//! # References
+//! - Delayed MIDI event queues in sequencer playback engines (events stamped
+//!   with the sample they're due at)
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use crate::humanize::KeyEvent;
+use crate::NUM_NOTES;
+
+/// A delayed event and the sample it's due at
+#[derive(Debug, Clone, Copy)]
+struct Pending {
+    due: u64,
+    event: KeyEvent,
+}
+
+/// Note events waiting for their delay, each note's in the order they came
+///
+/// Events go in with [`KeyQueue::push`]; once per sample, [`KeyQueue::pop_due`]
+/// hands back the events due, then [`KeyQueue::advance`] moves to the next
+/// sample.
+///
+/// # Real-time Safety
+/// - Allocated once at construction, never grows
+/// - When the queue is short of room, note-ons are dropped; room is always kept
+///   for the note-off of every note-on let in
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::humanize::KeyEvent;
+/// use naughty_and_tender::key_queue::KeyQueue;
+///
+/// let mut queue = KeyQueue::new(16);
+/// let on = KeyEvent::NoteOn { note: 60, velocity: 0.8, channel: 0, voice_id: None };
+/// let off = KeyEvent::NoteOff { note: 60, velocity: 0.0, channel: 0, voice_id: None };
+/// queue.push(2, on);
+/// queue.push(0, off);
+///
+/// // The note-off waits for its note-on
+/// assert!(queue.pop_due().is_none());
+/// queue.advance();
+/// queue.advance();
+/// assert_eq!(queue.pop_due(), Some(on));
+/// assert_eq!(queue.pop_due(), Some(off));
+/// ```
+pub struct KeyQueue {
+    /// Events waiting for their delay, in arrival order
+    pending: Vec<Pending>,
+
+    /// Most events waiting at once
+    capacity: usize,
+
+    /// Samples since construction
+    clock: u64,
+
+    /// When each note's latest event is due (keeps each note's events in order)
+    key_due: [u64; NUM_NOTES],
+
+    /// Note-ons let in per note whose note-off hasn't come yet
+    open: [usize; NUM_NOTES],
+
+    /// Slots kept for those note-offs (the sum of `open`)
+    reserved: usize,
+}
+
+impl KeyQueue {
+    /// Create an empty queue holding up to `capacity` events
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            pending: Vec::with_capacity(capacity),
+            capacity,
+            clock: 0,
+            key_due: [0; NUM_NOTES],
+            open: [0; NUM_NOTES],
+            reserved: 0,
+        }
+    }
+
+    /// Queue an event `delay` samples from now, returning whether it went in
+    ///
+    /// A note-on only goes in if there's room left for its note-off too.
+    pub fn push(&mut self, delay: u64, event: KeyEvent) -> bool {
+        let key = usize::from(event.note()) % NUM_NOTES;
+        let room = self.capacity - self.pending.len();
+        match event {
+            KeyEvent::NoteOn { .. } => {
+                if room < self.reserved + 2 {
+                    return false;
+                }
+                self.open[key] += 1;
+                self.reserved += 1;
+            }
+            KeyEvent::NoteOff { .. } if self.open[key] > 0 => {
+                // Uses the slot kept for it
+                self.open[key] -= 1;
+                self.reserved -= 1;
+            }
+            KeyEvent::NoteOff { .. } => {
+                if room <= self.reserved {
+                    return false;
+                }
+            }
+        }
+
+        // Never ahead of an earlier event for the same note
+        let due = (self.clock + delay).max(self.key_due[key]);
+        self.key_due[key] = due;
+        self.pending.push(Pending { due, event });
+        true
+    }
+
+    /// Next event due at the current sample, in arrival order
+    pub fn pop_due(&mut self) -> Option<KeyEvent> {
+        let index = self
+            .pending
+            .iter()
+            .position(|pending| pending.due <= self.clock)?;
+        Some(self.pending.remove(index).event)
+    }
+
+    /// Move on to the next sample
+    #[inline]
+    pub fn advance(&mut self) {
+        self.clock += 1;
+    }
+
+    /// Samples since construction or the last reset
+    #[must_use]
+    pub fn clock(&self) -> u64 {
+        self.clock
+    }
+
+    /// Number of events waiting
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Whether no events are waiting
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Forget every pending event and every note let in
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.clock = 0;
+        self.key_due = [0; NUM_NOTES];
+        self.open = [0; NUM_NOTES];
+        self.reserved = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note_on(note: u8) -> KeyEvent {
+        KeyEvent::NoteOn {
+            note,
+            velocity: 1.0,
+            channel: 0,
+            voice_id: None,
+        }
+    }
+
+    fn note_off(note: u8) -> KeyEvent {
+        KeyEvent::NoteOff {
+            note,
+            velocity: 0.0,
+            channel: 0,
+            voice_id: None,
+        }
+    }
+
+    #[test]
+    fn test_full_queue_keeps_room_for_note_offs() {
+        let mut queue = KeyQueue::new(8);
+        let started: Vec<u8> = (60..70)
+            .filter(|&note| queue.push(100, note_on(note)))
+            .collect();
+        assert_eq!(started.len(), 4);
+        assert!(
+            !queue.push(100, note_off(90)),
+            "stray note-off took a kept slot"
+        );
+
+        for &note in &started {
+            assert!(queue.push(0, note_off(note)));
+        }
+        assert_eq!(queue.len(), 8);
+    }
+
+    #[test]
+    fn test_note_off_never_overtakes_note_on() {
+        let mut queue = KeyQueue::new(8);
+        queue.push(5, note_on(60));
+        queue.push(0, note_off(60));
+
+        let mut order = Vec::new();
+        while !queue.is_empty() {
+            while let Some(event) = queue.pop_due() {
+                order.push((queue.clock(), event));
+            }
+            queue.advance();
+        }
+        assert_eq!(order, vec![(5, note_on(60)), (5, note_off(60))]);
+    }
+}
@@ -0,0 +1,226 @@
+//! Layer routing for Naughty and Tender
+//!
+//! The synth has two complete layers (A and B), each with its own voice pool,
+//! oscillator, envelope and filter settings. The router decides which layer(s)
+//! each incoming note plays:
+//! - Single: layer A only
+//! - Layer: both layers on every note
+//! - Split: notes below the split point play A, the rest play B
+//! - Velocity: soft notes play A, hard notes play B
+//!
+//! The router remembers where every note started, so note-offs always reach the
+//! right layer even after the mode, split point or velocity threshold changes.
+//!
+//! # References
+//! - Roland JD-800 / Yamaha DX7II layer, split and velocity-switch performance modes
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use crate::NUM_NOTES;
+
+/// How notes are shared between the two layers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerMode {
+    Single,
+    Layer,
+    Split,
+    Velocity,
+}
+
+impl LayerMode {
+    /// Every mode, in parameter index order
+    pub const ALL: [Self; 4] = [Self::Single, Self::Layer, Self::Split, Self::Velocity];
+
+    /// Display names, in parameter index order
+    pub const NAMES: [&'static str; 4] = ["Single", "Layer", "Split", "Velocity"];
+
+    /// Mode at a parameter index (out-of-range falls back to `Single`)
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or(Self::Single)
+    }
+}
+
+/// Which layers a note plays on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LayerSet {
+    pub a: bool,
+    pub b: bool,
+}
+
+impl LayerSet {
+    /// Layer A only
+    pub const A: Self = Self { a: true, b: false };
+
+    /// Layer B only
+    pub const B: Self = Self { a: false, b: true };
+
+    /// Both layers
+    pub const BOTH: Self = Self { a: true, b: true };
+
+    /// Neither layer
+    pub const NONE: Self = Self { a: false, b: false };
+}
+
+/// Routes notes to layers and tracks where they started
+///
+/// # Real-time Safety
+/// - Fixed-size note table, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::layers::{LayerMode, LayerRouter, LayerSet};
+///
+/// let mut router = LayerRouter::new();
+/// router.set_mode(LayerMode::Split);
+/// router.set_split_note(60);
+///
+/// assert_eq!(router.note_on(48, 1.0), LayerSet::A);
+/// assert_eq!(router.note_on(72, 1.0), LayerSet::B);
+/// ```
+pub struct LayerRouter {
+    mode: LayerMode,
+
+    /// First note of layer B in split mode
+    split_note: u8,
+
+    /// Lowest velocity that plays layer B in velocity mode
+    velocity_split: f32,
+
+    /// Layers each note is currently playing on
+    started: [LayerSet; NUM_NOTES],
+}
+
+impl Default for LayerRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LayerRouter {
+    /// Create a router in single mode, split at middle C, velocity split at 0.5
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            mode: LayerMode::Single,
+            split_note: 60,
+            velocity_split: 0.5,
+            started: [LayerSet::NONE; NUM_NOTES],
+        }
+    }
+
+    /// Set the routing mode (affects new notes only)
+    pub fn set_mode(&mut self, mode: LayerMode) {
+        self.mode = mode;
+    }
+
+    /// Set the first note played by layer B in split mode
+    pub fn set_split_note(&mut self, note: u8) {
+        self.split_note = note;
+    }
+
+    /// Set the lowest velocity (0.0 - 1.0) played by layer B in velocity mode
+    pub fn set_velocity_split(&mut self, velocity: f32) {
+        self.velocity_split = velocity.clamp(0.0, 1.0);
+    }
+
+    /// Layers a new note should play on
+    pub fn note_on(&mut self, note: u8, velocity: f32) -> LayerSet {
+        let layers = match self.mode {
+            LayerMode::Layer => LayerSet::BOTH,
+            LayerMode::Split if note >= self.split_note => LayerSet::B,
+            LayerMode::Velocity if velocity >= self.velocity_split => LayerSet::B,
+            LayerMode::Single | LayerMode::Split | LayerMode::Velocity => LayerSet::A,
+        };
+
+        // A retrigger may land on the other layer: release both later
+        let started = &mut self.started[usize::from(note)];
+        started.a |= layers.a;
+        started.b |= layers.b;
+
+        layers
+    }
+
+    /// Layers a note-off should release
+    pub fn note_off(&mut self, note: u8) -> LayerSet {
+        let started = std::mem::take(&mut self.started[usize::from(note)]);
+        if started == LayerSet::NONE {
+            // Not tracked (e.g. started before a reset): release everywhere
+            LayerSet::BOTH
+        } else {
+            started
+        }
+    }
+
+    /// Forget every playing note
+    pub fn reset(&mut self) {
+        self.started = [LayerSet::NONE; NUM_NOTES];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_and_layer_modes() {
+        let mut router = LayerRouter::new();
+        assert_eq!(router.note_on(60, 1.0), LayerSet::A);
+
+        router.set_mode(LayerMode::Layer);
+        assert_eq!(router.note_on(62, 1.0), LayerSet::BOTH);
+    }
+
+    #[test]
+    fn test_split_point_belongs_to_layer_b() {
+        let mut router = LayerRouter::new();
+        router.set_mode(LayerMode::Split);
+        router.set_split_note(60);
+
+        assert_eq!(router.note_on(59, 1.0), LayerSet::A);
+        assert_eq!(router.note_on(60, 1.0), LayerSet::B);
+    }
+
+    #[test]
+    fn test_velocity_switch() {
+        let mut router = LayerRouter::new();
+        router.set_mode(LayerMode::Velocity);
+        router.set_velocity_split(0.7);
+
+        assert_eq!(router.note_on(60, 0.4), LayerSet::A);
+        assert_eq!(router.note_on(62, 0.9), LayerSet::B);
+    }
+
+    #[test]
+    fn test_note_off_follows_note_on_after_mode_change() {
+        let mut router = LayerRouter::new();
+        router.set_mode(LayerMode::Split);
+        router.note_on(72, 1.0);
+
+        router.set_mode(LayerMode::Single);
+        assert_eq!(router.note_off(72), LayerSet::B);
+    }
+
+    #[test]
+    fn test_retrigger_on_other_layer_releases_both() {
+        let mut router = LayerRouter::new();
+        router.set_mode(LayerMode::Velocity);
+        router.note_on(60, 0.2);
+        router.note_on(60, 0.9);
+
+        assert_eq!(router.note_off(60), LayerSet::BOTH);
+    }
+
+    #[test]
+    fn test_untracked_note_off_releases_everywhere() {
+        let mut router = LayerRouter::new();
+        assert_eq!(router.note_off(60), LayerSet::BOTH);
+    }
+
+    #[test]
+    fn test_mode_names_match() {
+        assert_eq!(LayerMode::ALL.len(), LayerMode::NAMES.len());
+        assert_eq!(LayerMode::from_index(2), LayerMode::Split);
+        assert_eq!(LayerMode::from_index(99), LayerMode::Single);
+    }
+}
@@ -9,24 +9,125 @@
 #![warn(clippy::pedantic)]
 
 use nih_plug::prelude::*;
-use std::sync::Arc;
+use std::sync::{Arc, RwLockReadGuard};
+use std::time::Instant;
 
 mod editor;
 mod params;
+mod theme;
 
 // Phase 2 modules - will be implemented to make tests pass
+pub mod additive;
+pub mod audio_log;
+pub mod audition;
+pub mod autosave;
+pub mod bypass;
+pub mod cc_map;
+pub mod channel_filter;
+pub mod chord;
+pub mod cpu_load;
+pub mod diagnostics;
+pub mod engine;
 pub mod envelope;
+pub mod eq;
+pub mod expression;
+pub mod follower;
+pub mod gesture;
+pub mod granular;
+pub mod humanize;
+pub mod input;
+pub mod karplus;
+pub mod key_notes;
+pub mod key_queue;
+pub mod layers;
+pub mod limiter;
+pub mod master_fx;
+pub mod metering;
+pub mod midi_out;
+pub mod modulation;
+pub mod morph;
+pub mod navigation;
+pub mod note_expression;
+pub mod octaver;
 pub mod oscillators;
+pub mod pattern;
+pub mod pitch_bend;
+pub mod poly_mod;
+pub mod presets;
+#[cfg(feature = "debug-outputs")]
+pub mod probe;
+pub mod programs;
+pub mod pump;
+pub mod random;
+pub mod sampler;
+pub mod scale;
+pub mod scope;
+pub mod sequencer;
+pub mod slew;
+pub mod strum;
+pub mod sub_block;
+pub mod synth_voice;
+pub mod tasks;
+pub mod tuner;
+pub mod undo;
 pub mod voice;
-
-use params::NaughtyAndTenderParams;
-use voice::VoiceManager;
+pub mod width;
+
+use autosave::AutosaveSession;
+use cpu_load::{CpuLoad, CpuMeter};
+use engine::{
+    BlockStatus, Engine, EngineContext, EngineSettings, HostEvent, HostTransport, OutputEvent,
+    NUM_VOICES,
+};
+use input::InputMode;
+use modulation::ModOffsets;
+use morph::Snapshot;
+use params::{ChordMode, DrivePlacement, NaughtyAndTenderParams};
+use poly_mod::PolyOffsets;
+use tasks::{FileRequest, FileResult, FileTask};
+use voice::{VoiceParams, CROSSFADE_TAILS};
+
+/// Number of MIDI notes
+pub const NUM_NOTES: usize = 128;
+
+/// Voices that can carry a host voice id at once: every voice of both layers,
+/// octave and fifth notes from the octaver included (they sound on the layers'
+/// voices, tagged like the note that started them), plus each layer's tails
+/// playing out restarted notes
+const MAX_VOICE_CAPACITY: usize = 2 * (NUM_VOICES + CROSSFADE_TAILS);
+
+/// Aux output ports: a stereo port per debug probe with the `debug-outputs`
+/// feature, none without
+#[cfg(feature = "debug-outputs")]
+const AUX_OUTPUT_PORTS: &[NonZeroU32] = &[new_nonzero_u32(2); probe::NUM_PROBES];
+#[cfg(not(feature = "debug-outputs"))]
+const AUX_OUTPUT_PORTS: &[NonZeroU32] = &[];
+
+/// Names of the aux output ports
+#[cfg(feature = "debug-outputs")]
+const AUX_OUTPUT_NAMES: &[&str] = &probe::PROBE_NAMES;
+#[cfg(not(feature = "debug-outputs"))]
+const AUX_OUTPUT_NAMES: &[&str] = &[];
 
 /// The main plugin struct
 pub struct NaughtyAndTender {
     params: Arc<NaughtyAndTenderParams>,
     sample_rate: f32,
-    voice_manager: Option<VoiceManager>,
+
+    /// Everything that renders a block, fed from the parameters
+    engine: Engine,
+
+    /// Times each block against its real-time budget
+    cpu_meter: CpuMeter,
+
+    /// Smoothed processing load for the editor's CPU readout
+    cpu_load: Arc<CpuLoad>,
+
+    /// When this instance started and whether its editor autosaved
+    autosave: Arc<AutosaveSession>,
+
+    /// Sample path last loaded when initializing (to restore saved sessions once)
+    restored_sample_path: String,
 }
 
 impl Default for NaughtyAndTender {
@@ -34,11 +135,22 @@ impl Default for NaughtyAndTender {
         Self {
             params: Arc::new(NaughtyAndTenderParams::default()),
             sample_rate: 44100.0,
-            voice_manager: None, // Will be initialized in initialize()
+            engine: Engine::new(44100.0), // Sized for the host in initialize()
+            cpu_meter: CpuMeter::new(44100.0),
+            cpu_load: Arc::new(CpuLoad::new()),
+            autosave: Arc::new(AutosaveSession::new()),
+            restored_sample_path: String::new(),
         }
     }
 }
 
+impl Drop for NaughtyAndTender {
+    /// A clean shutdown leaves no snapshot to offer the next session
+    fn drop(&mut self) {
+        self.autosave.end();
+    }
+}
+
 impl Plugin for NaughtyAndTender {
     const NAME: &'static str = "Naughty and Tender";
     const VENDOR: &'static str = "Col Cavanaugh";
@@ -46,39 +158,92 @@ impl Plugin for NaughtyAndTender {
     const EMAIL: &'static str = "colcavanaugh@users.noreply.github.com";
     const VERSION: &'static str = env!("CARGO_PKG_VERSION");
 
-    // Audio I/O configuration: stereo output, no input
-    const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[AudioIOLayout {
-        main_input_channels: None,
-        main_output_channels: NonZeroU32::new(2),
-        aux_input_ports: &[],
-        aux_output_ports: &[],
-        names: PortNames::const_default(),
-    }];
+    // Audio I/O configuration: stereo output with a stereo input for the
+    // input modes and a sidechain input for the envelope follower; the smaller
+    // layouts are for hosts that can't route one or the other
+    const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[
+        AudioIOLayout {
+            main_input_channels: NonZeroU32::new(2),
+            main_output_channels: NonZeroU32::new(2),
+            aux_input_ports: &[new_nonzero_u32(2)],
+            aux_output_ports: AUX_OUTPUT_PORTS,
+            names: PortNames {
+                aux_inputs: &["Sidechain"],
+                aux_outputs: AUX_OUTPUT_NAMES,
+                ..PortNames::const_default()
+            },
+        },
+        AudioIOLayout {
+            main_input_channels: NonZeroU32::new(2),
+            main_output_channels: NonZeroU32::new(2),
+            aux_input_ports: &[],
+            aux_output_ports: AUX_OUTPUT_PORTS,
+            names: PortNames {
+                aux_outputs: AUX_OUTPUT_NAMES,
+                ..PortNames::const_default()
+            },
+        },
+        AudioIOLayout {
+            main_input_channels: None,
+            main_output_channels: NonZeroU32::new(2),
+            aux_input_ports: &[],
+            aux_output_ports: AUX_OUTPUT_PORTS,
+            names: PortNames {
+                aux_outputs: AUX_OUTPUT_NAMES,
+                ..PortNames::const_default()
+            },
+        },
+    ];
 
-    // This is a synthesizer that responds to MIDI
-    const MIDI_INPUT: MidiConfig = MidiConfig::Basic;
-    const MIDI_OUTPUT: MidiConfig = MidiConfig::None;
+    // This is a synthesizer that responds to MIDI, and can pass on the notes it plays
+    const MIDI_INPUT: MidiConfig = MidiConfig::MidiCCs;
+    const MIDI_OUTPUT: MidiConfig = MidiConfig::Basic;
 
     const SAMPLE_ACCURATE_AUTOMATION: bool = true;
 
     type SysExMessage = ();
-    type BackgroundTask = ();
+    type BackgroundTask = FileRequest;
 
     fn params(&self) -> Arc<dyn Params> {
         self.params.clone()
     }
 
+    fn task_executor(&mut self) -> TaskExecutor<Self> {
+        // File work for the editor (presets and samples)
+        Box::new(FileRequest::run)
+    }
+
     fn initialize(
         &mut self,
-        _audio_io_layout: &AudioIOLayout,
+        audio_io_layout: &AudioIOLayout,
         buffer_config: &BufferConfig,
         _context: &mut impl InitContext<Self>,
     ) -> bool {
-        // Initialize voice manager with 16 voices
-        const NUM_VOICES: usize = 16;
+        // Initialize the engine with 16 voices per layer
 
         self.sample_rate = buffer_config.sample_rate;
-        self.voice_manager = Some(VoiceManager::new(self.sample_rate, NUM_VOICES));
+        self.engine.initialize(
+            self.sample_rate,
+            buffer_config.max_buffer_size as usize,
+            audio_io_layout.main_input_channels.is_some(),
+        );
+        self.cpu_meter.set_sample_rate(self.sample_rate);
+
+        // Restore the saved session's sample (initialization may block on files)
+        let sample_path = self.params.sample_path();
+        if !sample_path.is_empty() && sample_path != self.restored_sample_path {
+            let task = FileTask::LoadSample {
+                path: sample_path.clone().into(),
+                slot: self.engine.links().sample_slot.clone(),
+            };
+            if let FileResult::SampleLoaded {
+                result: Err(error), ..
+            } = task.run()
+            {
+                nih_log!("Couldn't load sample {sample_path}: {error}");
+            }
+            self.restored_sample_path = sample_path;
+        }
 
         nih_log!("Naughty and Tender initialized");
         nih_log!("Sample rate: {}", self.sample_rate);
@@ -91,110 +256,552 @@ impl Plugin for NaughtyAndTender {
     fn reset(&mut self) {
         nih_log!("Plugin reset");
 
-        // Reset voice manager
-        if let Some(vm) = &mut self.voice_manager {
-            vm.reset();
-        }
+        self.engine.reset();
     }
 
     fn process(
         &mut self,
         buffer: &mut Buffer,
-        _aux: &mut AuxiliaryBuffers,
+        aux: &mut AuxiliaryBuffers,
         context: &mut impl ProcessContext<Self>,
     ) -> ProcessStatus {
-        // Get voice manager (return if not initialized)
-        let Some(voice_manager) = &mut self.voice_manager else {
-            // Not initialized yet - output silence
-            for channel_samples in buffer.as_slice() {
+        let started = Instant::now();
+
+        // Debug probe ports start silent, for blocks that end early
+        #[cfg(feature = "debug-outputs")]
+        for port in aux.outputs.iter_mut() {
+            for channel_samples in port.as_slice() {
                 channel_samples.fill(0.0);
             }
-            return ProcessStatus::Normal;
-        };
+        }
 
-        // Get parameters
-        let gain = self.params.gain.value();
-        let waveform_int = self.params.waveform.value();
-        let attack_ms = self.params.attack_ms.value();
-        let decay_ms = self.params.decay_ms.value();
-        let sustain_level = self.params.sustain_level.value();
-        let release_ms = self.params.release_ms.value();
-
-        // Convert waveform int to enum
-        use oscillators::WaveformType;
-        let waveform = match waveform_int {
-            0 => WaveformType::Sine,
-            1 => WaveformType::Sawtooth,
-            2 => WaveformType::Square,
-            3 => WaveformType::Triangle,
-            _ => WaveformType::Sine, // Default fallback
+        // The host's playhead, for the synced times and the pattern
+        let transport = context.transport();
+        let tempo_bpm = self.engine.host_tempo(transport.tempo);
+        let host_transport = HostTransport {
+            playing: transport.playing,
+            pos_beats: transport.pos_beats(),
+            bar: transport.bar_number().zip(transport.bar_start_pos_beats()),
+            time_signature: (
+                transport.time_sig_numerator.unwrap_or(4),
+                transport.time_sig_denominator.unwrap_or(4),
+            ),
         };
 
-        // Update voice manager with current parameters
-        voice_manager.set_waveform(waveform);
-        voice_manager.set_attack_ms(attack_ms);
-        voice_manager.set_decay_ms(decay_ms);
-        voice_manager.set_sustain_level(sustain_level);
-        voice_manager.set_release_ms(release_ms);
+        // Store any freshly learned chord (persisted with the plugin state)
+        // before chord mode picks its intervals
+        if let Ok(mut learned) = self.params.learned_chord.try_write() {
+            if let Some(intervals) = self.engine.take_learned_chord() {
+                *learned = intervals;
+            }
+        }
+        if let Ok(notes) = self.params.pattern_notes.try_read() {
+            self.engine.set_pattern_notes(&notes);
+        }
+        let settings = engine_settings(&self.params, tempo_bpm, self.engine.has_main_input());
 
-        // Process MIDI events
-        let mut next_event = context.next_event();
+        // The engine renders into the main buffer, in place of the input
         let num_samples = buffer.samples();
+        let sidechain = aux.inputs.first().map(Buffer::as_slice_immutable);
+        let mut plugin_context = PluginContext {
+            context,
+            params: &self.params,
+            probes: &mut *aux.outputs,
+        };
+        let status = self.engine.process(
+            &settings,
+            &host_transport,
+            buffer.as_slice(),
+            sidechain,
+            &mut plugin_context,
+        );
+
+        // Time spent on the block, against its real-time budget
+        self.cpu_load.publish(self.cpu_meter.measure(started.elapsed(), num_samples));
+        status.into()
+    }
+
+    fn editor(&mut self, async_executor: AsyncExecutor<Self>) -> Option<Box<dyn Editor>> {
+        let links = self.engine.links();
+        editor::create(
+            self.params.clone(),
+            async_executor,
+            editor::EditorLinks {
+                diagnostics: links.diagnostics.clone(),
+                midi_activity: links.midi_activity.clone(),
+                modulation: links.modulation.clone(),
+                pattern_playhead: links.pattern_playhead.clone(),
+                audition_events: links.audition_events.clone(),
+                audio_log: links.audio_log.clone(),
+                autosave: self.autosave.clone(),
+                tuner_tap: links.tuner_tap.clone(),
+                gain_staging: links.gain_staging.clone(),
+                stereo: links.stereo.clone(),
+                cpu_load: self.cpu_load.clone(),
+                cc_inbox: links.cc_inbox.clone(),
+                program_inbox: links.program_inbox.clone(),
+                sample_slot: links.sample_slot.clone(),
+            },
+            self.params.editor_state.clone(),
+        )
+    }
+}
+
+/// The host's side of a block, as the engine sees it
+struct PluginContext<'a, 'b, C> {
+    context: &'a mut C,
+    params: &'a NaughtyAndTenderParams,
 
-        // Process sample by sample (for sample-accurate MIDI)
-        for sample_idx in 0..num_samples {
-            // Handle MIDI events at this sample
-            while let Some(event) = next_event {
-                #[allow(clippy::cast_possible_truncation)] // Audio buffer size never exceeds u32
-                if event.timing() > sample_idx as u32 {
-                    break;
-                }
-
-                match event {
-                    NoteEvent::NoteOn {
-                        timing: _,
-                        voice_id: _,
-                        channel: _,
-                        note,
-                        velocity,
-                    } => {
-                        // Convert velocity from 0-1 range
-                        voice_manager.note_on(note, velocity);
-                    }
-                    NoteEvent::NoteOff {
-                        timing: _,
-                        voice_id: _,
-                        channel: _,
-                        note,
-                        velocity: _,
-                    } => {
-                        voice_manager.note_off(note);
-                    }
-                    _ => {}
-                }
-
-                next_event = context.next_event();
+    /// Debug probe ports
+    #[cfg_attr(not(feature = "debug-outputs"), allow(dead_code))]
+    probes: &'a mut [Buffer<'b>],
+}
+
+impl<C: ProcessContext<NaughtyAndTender>> EngineContext for PluginContext<'_, '_, C> {
+    fn next_event(&mut self) -> Option<(u32, HostEvent)> {
+        let event = self.context.next_event()?;
+        let host_event = match event {
+            NoteEvent::NoteOn {
+                voice_id,
+                channel,
+                note,
+                velocity,
+                ..
+            } => HostEvent::NoteOn {
+                voice_id,
+                channel,
+                note,
+                velocity,
+            },
+            NoteEvent::NoteOff {
+                voice_id,
+                channel,
+                note,
+                velocity,
+                ..
+            } => HostEvent::NoteOff {
+                voice_id,
+                channel,
+                note,
+                velocity,
+            },
+            NoteEvent::Choke {
+                voice_id,
+                channel,
+                note,
+                ..
+            } => HostEvent::Choke {
+                voice_id,
+                channel,
+                note,
+            },
+            NoteEvent::MidiCC {
+                channel, cc, value, ..
+            } => HostEvent::Cc { channel, cc, value },
+            NoteEvent::MidiPitchBend { channel, value, .. } => {
+                HostEvent::PitchBend { channel, value }
             }
+            NoteEvent::PolyVolume {
+                voice_id,
+                channel,
+                note,
+                gain,
+                ..
+            } => HostEvent::PolyVolume {
+                voice_id,
+                channel,
+                note,
+                gain,
+            },
+            NoteEvent::PolyPan {
+                voice_id,
+                channel,
+                note,
+                pan,
+                ..
+            } => HostEvent::PolyPan {
+                voice_id,
+                channel,
+                note,
+                pan,
+            },
+            NoteEvent::PolyTuning {
+                voice_id,
+                channel,
+                note,
+                tuning,
+                ..
+            } => HostEvent::PolyTuning {
+                voice_id,
+                channel,
+                note,
+                tuning,
+            },
+            NoteEvent::PolyModulation {
+                voice_id,
+                poly_modulation_id,
+                normalized_offset,
+                ..
+            } => HostEvent::PolyModulation {
+                voice_id,
+                poly_modulation_id,
+                normalized_offset,
+            },
+            NoteEvent::MonoAutomation { .. } => HostEvent::MonoAutomation,
+            NoteEvent::MidiProgramChange {
+                channel, program, ..
+            } => HostEvent::ProgramChange { channel, program },
+            _ => HostEvent::Other {
+                channel: event.channel(),
+            },
+        };
+        Some((event.timing(), host_event))
+    }
 
-            // Generate one sample from voice manager
-            let mut mono_sample = [0.0f32];
-            voice_manager.process(&mut mono_sample);
+    fn send_event(&mut self, timing: u32, event: OutputEvent) {
+        self.context.send_event(match event {
+            OutputEvent::NoteOn {
+                channel,
+                note,
+                velocity,
+            } => NoteEvent::NoteOn {
+                timing,
+                voice_id: None,
+                channel,
+                note,
+                velocity,
+            },
+            OutputEvent::NoteOff {
+                channel,
+                note,
+                velocity,
+            } => NoteEvent::NoteOff {
+                timing,
+                voice_id: None,
+                channel,
+                note,
+                velocity,
+            },
+            OutputEvent::VoiceTerminated {
+                voice_id,
+                channel,
+                note,
+            } => NoteEvent::VoiceTerminated {
+                timing,
+                voice_id: Some(voice_id),
+                channel,
+                note,
+            },
+        });
+    }
 
-            // Apply master gain
-            let output_sample = mono_sample[0] * gain;
+    fn poly_offsets(&self, normalized: &PolyOffsets) -> ModOffsets {
+        self.params.poly_offsets(normalized)
+    }
 
-            // Write to stereo output (duplicate mono to both channels)
-            let output = buffer.as_slice();
-            for channel_samples in output {
-                channel_samples[sample_idx] = output_sample;
+    #[cfg(feature = "debug-outputs")]
+    fn probe(&mut self, sample: usize, taps: [f32; probe::NUM_PROBES]) {
+        for (port, tap) in self.probes.iter_mut().zip(taps) {
+            for channel_samples in port.as_slice() {
+                channel_samples[sample] = tap;
             }
         }
+    }
+}
+
+impl From<BlockStatus> for ProcessStatus {
+    fn from(status: BlockStatus) -> Self {
+        match status {
+            BlockStatus::Normal => Self::Normal,
+            BlockStatus::Tail(samples) => Self::Tail(samples),
+            BlockStatus::KeepAlive => Self::KeepAlive,
+        }
+    }
+}
+
+/// The engine's settings for a block, read from the parameters
+#[allow(
+    clippy::too_many_lines,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)] // One read per parameter; integer ranges are 0-127 and 4-8
+fn engine_settings(
+    params: &NaughtyAndTenderParams,
+    tempo_bpm: f32,
+    has_main_input: bool,
+) -> EngineSettings {
+    use karplus::ExcitationType;
+    use voice::VoiceEngine;
+
+    // Voice settings and gain play at their patch morph blend
+    let morph = Morphed::new(params);
+
+    // Envelope and glide times, converted from note divisions where synced
+    let attack_ms = params.attack_time_ms(tempo_bpm);
+    let decay_ms = params.decay_time_ms(tempo_bpm);
+    let release_ms = params.release_time_ms(tempo_bpm);
+    let glide_ms = params.glide_time_ms(tempo_bpm);
+
+    // Convert engine ints to enums
+    let engine = match morph.value(&params.engine, "engine") {
+        1 => VoiceEngine::KarplusStrong,
+        2 => VoiceEngine::Sampler,
+        3 => VoiceEngine::Additive,
+        _ => VoiceEngine::Oscillator,
+    };
+    let string_excitation = match morph.value(&params.string_excitation, "ks_excitation") {
+        1 => ExcitationType::Oscillator,
+        _ => ExcitationType::Noise,
+    };
+
+    // Drive runs per voice or on the master bus
+    let drive_placement = params.drive_placement();
+
+    // External input: through the voices (gated) or layer A's filter and drive (always)
+    let input_mode = if has_main_input {
+        params.input_mode()
+    } else {
+        InputMode::Off
+    };
+    let input_mix = if input_mode == InputMode::Gated {
+        morph.value(&params.input_mix, "input_mix")
+    } else {
+        0.0
+    };
+
+    // Voice settings: one snapshot per layer
+    let layer_a = VoiceParams {
+        engine,
+        waveform: waveform_type(morph.value(&params.waveform, "waveform")),
+        sine_mode: params.sine_mode(),
+        attack_ms,
+        decay_ms,
+        sustain_level: morph.value(&params.sustain_level, "sustain"),
+        release_ms,
+        glide_ms,
+        glide_mode: params.glide_mode(),
+        glide_curve: params.glide_curve(),
+        // A scale glissando steps through the Scale section's scale,
+        // whether or not keys are quantized to it
+        glissando: params.glissando(),
+        glissando_root: params.scale_root(),
+        glissando_mask: params.scale_mask(),
+        free_running_phase: morph.value(&params.free_running_phase, "free_phase"),
+        start_phase_degrees: morph.value(&params.start_phase, "start_phase"),
+        random_phase: morph.value(&params.random_phase, "rand_phase"),
+        string_excitation,
+        string_damping: morph.value(&params.string_damping, "ks_damping"),
+        string_decay_ms: morph.value(&params.string_decay_ms, "ks_decay"),
+        sample_interpolation: params.sample_interpolation(),
+        sample_loop: morph.value(&params.sample_loop, "smp_loop"),
+        sample_loop_start: morph.value(&params.sample_loop_start, "smp_loop_start"),
+        sample_loop_end: morph.value(&params.sample_loop_end, "smp_loop_end"),
+        sample_start: morph.value(&params.sample_start, "smp_start"),
+        sample_root_note: morph.value(&params.sample_root, "smp_root") as u8,
+        sample_granular: morph.value(&params.granular, "grain_on"),
+        granular: params.granular_settings(),
+        // Partial levels from the harmonic editor, shaped by the tilt
+        additive_gains: additive::partial_gains(
+            &params.partial_levels(),
+            morph.value(&params.additive_tilt, "add_tilt"),
+        ),
+        waveshaper_enabled: drive_placement == DrivePlacement::Voice,
+        waveshaper: params.waveshaper_settings(),
+        filter_enabled: morph.value(&params.filter_enabled, "filter_on"),
+        filter_mode: params.filter_mode(),
+        filter_cutoff_hz: morph.value(&params.filter_cutoff_hz, "filter_cutoff"),
+        filter_resonance: morph.value(&params.filter_resonance, "filter_res"),
+        filter_envelope: params.filter_env.settings(),
+        filter_precision: params.filter_precision(),
+        limiter_enabled: morph.value(&params.voice_limiter, "vlim_on"),
+        limiter_ceiling_db: morph.value(&params.voice_limiter_ceiling_db, "vlim_ceiling"),
+        // Tempo-synced or free-running
+        random_rate_hz: params.random_rate_hz(tempo_bpm),
+        random_slew_ms: morph.value(&params.rand_slew_ms, "rand_slew"),
+        mod_matrix: params.mod_matrix(),
+        control_divisor: params.control_divisor(),
+        a4_hz: morph.value(&params.master_tune_hz, "master_tune"),
+        fine_tune_cents: morph.value(&params.fine_tune_cents, "fine_tune"),
+        bend_range: morph.value(&params.bend_range, "bend_range"),
+        input_mix,
+        latch: params.latch_groups(),
+    };
+
+    // Layer B: its own oscillator, envelope and filter; glide, phase mode,
+    // drive, modulation and the random source are shared with layer A
+    let layer_b_params = &params.layer_b;
+    let layer_b = VoiceParams {
+        engine: VoiceEngine::Oscillator,
+        waveform: waveform_type(morph.value(&layer_b_params.waveform, "b_waveform")),
+        attack_ms: morph.value(&layer_b_params.attack_ms, "b_attack"),
+        decay_ms: morph.value(&layer_b_params.decay_ms, "b_decay"),
+        sustain_level: morph.value(&layer_b_params.sustain_level, "b_sustain"),
+        release_ms: morph.value(&layer_b_params.release_ms, "b_release"),
+        filter_enabled: morph.value(&layer_b_params.filter_enabled, "b_filter_on"),
+        filter_mode: layer_b_params.filter_mode(),
+        filter_cutoff_hz: morph.value(&layer_b_params.filter_cutoff_hz, "b_filter_cutoff"),
+        filter_resonance: morph.value(&layer_b_params.filter_resonance, "b_filter_res"),
+        filter_envelope: layer_b_params.filter_env.settings(),
+        ..layer_a
+    };
+
+    // Chord memory: the learned chord is read back once stored
+    let (chord, chord_intervals) = match params.chord_mode() {
+        ChordMode::Off => (false, None),
+        ChordMode::Intervals => (true, Some(params.chord_intervals())),
+        ChordMode::Learned => {
+            let learned = params.learned_chord.try_read().ok().map(|learned| *learned);
+            (true, learned)
+        }
+    };
+
+    // Switched-off sections run at their neutral settings
+    let humanize = params.humanize.value();
+    let octaver = params.octaver.value();
+
+    EngineSettings {
+        bypassed: params.bypass.value(),
+        gain: morph.value(&params.gain, "gain"),
+        expression_curve: params.expression_curve(),
+        tempo_bpm,
+        layer_a,
+        layer_b,
+        layer_mode: params.layer_mode(),
+        split_note: params.split_note.value() as u8,
+        velocity_split: params.velocity_split.value(),
+        layer_a_level: params.layer_a_level.value(),
+        layer_b_level: layer_b_params.level.value(),
+        release_velocity: params.release_velocity.value(),
+        stuck_timeout_ms: params.stuck_timeout_s.value() * 1000.0,
+        bass_reserve: params.bass_reserve.value(),
+        allocation: params.voice_allocation(),
+        stealing: params.voice_stealing(),
+        same_note_policy: params.same_note_policy(),
+        choke_ranges: params.choke_ranges(),
+        choke_policy: params.choke_policy(),
+        master_drive: drive_placement == DrivePlacement::Master,
+        phaser_enabled: params.phaser_enabled.value(),
+        phaser_rate_hz: params.phaser_rate_hz.value(),
+        phaser_depth: params.phaser_depth.value(),
+        phaser_feedback: params.phaser_feedback.value(),
+        phaser_stages: params.phaser_stages.value() as usize,
+        phaser_mix: params.phaser_mix.value(),
+        eq_enabled: params.eq_enabled.value(),
+        eq: params.eq_settings(),
+        eq_mix: params.eq_mix.value(),
+        fx_order: params.fx_order(),
+        fx_bypass: params.fx_bypass.value(),
+        dc_blocker: params.dc_blocker.value(),
+        stereo_width: params.stereo_width.value(),
+        width_limit: params.width_limit.value(),
+        min_correlation: params.min_correlation.value(),
+        seq_steps: params.seq_steps(),
+        seq_euclid: params.seq_euclid(),
+        seq_division: params.seq_step_division(),
+        seq_slew_ms: params.seq_slew_ms.value(),
+        pattern: params.pattern.value(),
+        pattern_bars: params.pattern_bars(),
+        pattern_swing: params.pattern_swing.value(),
+        pattern_velocity: params.pattern_velocity.value(),
+        pump_division: params.pump_cycle_division(),
+        pump_depth: if params.pump_enabled.value() {
+            params.pump_depth.value()
+        } else {
+            0.0
+        },
+        pump_shape: params.pump_shape.value(),
+        midi_channel: params.midi_channel(),
+        humanize_timing_ms: if humanize {
+            params.humanize_timing_ms.value()
+        } else {
+            0.0
+        },
+        humanize_velocity: if humanize {
+            params.humanize_velocity.value()
+        } else {
+            0.0
+        },
+        scale_quantize: params.scale_quantize.value(),
+        scale_root: params.scale_root(),
+        scale_mask: params.scale_mask(),
+        scale_snap: params.scale_snap(),
+        chord,
+        chord_intervals,
+        chord_learn: params.chord_learn.value(),
+        strum_time_ms: if params.strum.value() {
+            params.strum_time_ms.value()
+        } else {
+            0.0
+        },
+        strum_direction: params.strum_direction(),
+        octave_level: if octaver {
+            params.octaver_octave_level.value()
+        } else {
+            0.0
+        },
+        fifth_level: if octaver {
+            params.octaver_fifth_level.value()
+        } else {
+            0.0
+        },
+        input_mode,
+        input_gain: params.input_gain.value(),
+        sidechain_attack_ms: params.sidechain_attack_ms.value(),
+        sidechain_release_ms: params.sidechain_release_ms.value(),
+        sidechain_gain_db: params.sidechain_gain_db.value(),
+        // Generated notes go out only while MIDI out is on, but every note sent
+        // out is released, so switching it off never leaves notes hanging
+        midi_out: params.midi_out.value(),
+        // Program changes fade around the patch switch unless set to instant
+        program_transition: params.program_transition(),
+    }
+}
 
-        ProcessStatus::Normal
+/// Waveform for a waveform parameter value
+/// Parameter values with the patch morph applied
+///
+/// Parameters both snapshots hold play at their blend at the morph position,
+/// the rest at their own value. A block that finds a snapshot being stored
+/// plays the parameters as they are.
+struct Morphed<'a> {
+    /// Snapshots A and B, if both are stored
+    snapshots: Option<(RwLockReadGuard<'a, Snapshot>, RwLockReadGuard<'a, Snapshot>)>,
+    position: f32,
+}
+
+impl<'a> Morphed<'a> {
+    fn new(params: &'a NaughtyAndTenderParams) -> Self {
+        let snapshots = match (params.morph_a.try_read(), params.morph_b.try_read()) {
+            (Ok(a), Ok(b)) if !a.is_empty() && !b.is_empty() => Some((a, b)),
+            _ => None,
+        };
+        Self {
+            snapshots,
+            position: params.morph.value(),
+        }
+    }
+
+    /// Value of `param`, whose parameter ID is `id`
+    fn value<P: Param>(&self, param: &P, id: &str) -> P::Plain {
+        let stepped = param.step_count().is_some();
+        let blend = self
+            .snapshots
+            .as_ref()
+            .and_then(|(a, b)| morph::snapshot_value(a, b, id, self.position, stepped));
+        match blend {
+            Some(normalized) => param.preview_plain(normalized),
+            None => param.modulated_plain_value(),
+        }
     }
+}
 
-    fn editor(&mut self, _async_executor: AsyncExecutor<Self>) -> Option<Box<dyn Editor>> {
-        editor::create(self.params.clone(), self.params.editor_state.clone())
+fn waveform_type(index: i32) -> oscillators::WaveformType {
+    use oscillators::WaveformType;
+    match index {
+        1 => WaveformType::Sawtooth,
+        2 => WaveformType::Square,
+        3 => WaveformType::Triangle,
+        _ => WaveformType::Sine, // Default fallback
     }
 }
 
@@ -209,6 +816,13 @@ impl ClapPlugin for NaughtyAndTender {
         ClapFeature::Synthesizer,
         ClapFeature::Stereo,
     ];
+
+    // Cutoff, fine tune and gain can be modulated per voice
+    #[allow(clippy::cast_possible_truncation)] // MAX_VOICE_CAPACITY is tiny
+    const CLAP_POLY_MODULATION_CONFIG: Option<PolyModulationConfig> = Some(PolyModulationConfig {
+        max_voice_capacity: MAX_VOICE_CAPACITY as u32,
+        supports_overlapping_voices: true,
+    });
 }
 
 impl Vst3Plugin for NaughtyAndTender {
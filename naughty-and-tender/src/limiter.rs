@@ -0,0 +1,209 @@
+//! Per-voice soft-knee peak limiter for Naughty and Tender
+//!
+//! A filter driven into self-oscillation can ring far louder than the note that
+//! excited it. Limiting each voice straight after its filter keeps one screaming
+//! resonant peak from flattening every other voice, as clipping the mixed bus
+//! would, and quiet voices pass through untouched.
+//!
+//! The detector is a peak follower with an instant attack and an exponential
+//! release, so the output never exceeds the ceiling without any lookahead
+//! delay. The gain computer has a soft knee centered on the ceiling: gain
+//! reduction eases in over the knee, and the knee's top lands exactly on the
+//! ceiling. Below the knee the limiter does no per-sample math beyond the
+//! follower, which keeps it cheap enough to run in every voice.
+//!
+//! # References
+//! - Giannoulis, Massberg, Reiss, "Digital Dynamic Range Compressor Design - A
+//!   Tutorial and Analysis" (JAES 2012): soft-knee gain computer, peak detector
+//! - `y_dB = x_dB - (x_dB - T + W/2)² / 2W` inside the knee, `T` above it
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+/// Width of the soft knee in dB, centered on the ceiling
+pub const LIMITER_KNEE_DB: f32 = 6.0;
+
+/// Time constant of the gain recovery, in milliseconds
+pub const LIMITER_RELEASE_MS: f32 = 50.0;
+
+/// Default ceiling in dBFS
+pub const DEFAULT_CEILING_DB: f32 = -6.0;
+
+/// Gain reduction in dB for a peak `over_db` above (or below) the ceiling
+///
+/// Nothing below the knee; above it, everything over the ceiling.
+#[must_use]
+pub fn gain_reduction_db(over_db: f32) -> f32 {
+    let half_knee = LIMITER_KNEE_DB / 2.0;
+    if over_db <= -half_knee {
+        0.0
+    } else if over_db >= half_knee {
+        over_db
+    } else {
+        (over_db + half_knee).powi(2) / (2.0 * LIMITER_KNEE_DB)
+    }
+}
+
+/// Soft-knee peak limiter for one voice
+///
+/// # Real-time Safety
+/// - Fixed state, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::limiter::SoftLimiter;
+///
+/// let mut limiter = SoftLimiter::new(48000.0);
+/// limiter.set_ceiling_db(-6.0);
+/// assert!(limiter.process(4.0) <= 0.5012);
+/// assert!(limiter.take_max_reduction_db() > 17.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SoftLimiter {
+    /// Ceiling in dBFS
+    ceiling_db: f32,
+
+    /// Linear level where the knee starts
+    knee_start: f32,
+
+    /// Peak follower output
+    envelope: f32,
+
+    /// Per-sample envelope decay
+    release_coeff: f32,
+
+    /// Largest gain reduction since the last `take_max_reduction_db`, in dB
+    max_reduction_db: f32,
+}
+
+impl SoftLimiter {
+    /// Create a limiter at the default ceiling
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        let mut limiter = Self {
+            ceiling_db: DEFAULT_CEILING_DB,
+            knee_start: 0.0,
+            envelope: 0.0,
+            release_coeff: 0.0,
+            max_reduction_db: 0.0,
+        };
+        limiter.set_ceiling_db(DEFAULT_CEILING_DB);
+        limiter.set_sample_rate(sample_rate);
+        limiter
+    }
+
+    /// Set the sample rate in Hz
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.release_coeff = (-1.0 / (LIMITER_RELEASE_MS / 1000.0 * sample_rate).max(1.0)).exp();
+    }
+
+    /// Set the ceiling in dBFS
+    pub fn set_ceiling_db(&mut self, ceiling_db: f32) {
+        self.ceiling_db = ceiling_db;
+        self.knee_start = 10.0_f32.powf((ceiling_db - LIMITER_KNEE_DB / 2.0) / 20.0);
+    }
+
+    /// Limit one sample
+    #[inline]
+    pub fn process(&mut self, input: f32) -> f32 {
+        self.envelope = input.abs().max(self.envelope * self.release_coeff);
+        if self.envelope <= self.knee_start {
+            return input;
+        }
+
+        let over_db = 20.0 * self.envelope.log10() - self.ceiling_db;
+        let reduction_db = gain_reduction_db(over_db);
+        self.max_reduction_db = self.max_reduction_db.max(reduction_db);
+        input * 10.0_f32.powf(-reduction_db / 20.0)
+    }
+
+    /// Largest gain reduction since the last call, in dB (0.0 = none)
+    pub fn take_max_reduction_db(&mut self) -> f32 {
+        std::mem::take(&mut self.max_reduction_db)
+    }
+
+    /// Clear the detector (call on note-on)
+    pub fn reset(&mut self) {
+        self.envelope = 0.0;
+        self.max_reduction_db = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::TAU;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    #[test]
+    #[allow(clippy::cast_precision_loss, clippy::float_cmp)] // Exact outside the knee, small inputs
+    fn test_knee_is_continuous_and_ends_on_the_ceiling() {
+        let half_knee = LIMITER_KNEE_DB / 2.0;
+        assert_eq!(gain_reduction_db(-half_knee - 1.0), 0.0);
+        assert!(gain_reduction_db(-half_knee + 1e-3) < 1e-6);
+        assert!((gain_reduction_db(half_knee - 1e-3) - half_knee).abs() < 1e-3);
+        assert_eq!(gain_reduction_db(10.0), 10.0);
+
+        // Output level never falls as the input rises
+        let mut previous = f32::MIN;
+        for step in 0..200 {
+            let over = -6.0 + step as f32 * 0.06;
+            let output = over - gain_reduction_db(over);
+            assert!(output >= previous - 1e-6);
+            assert!(output <= 1e-6, "{over} dB over comes out {output} dB over");
+            previous = output;
+        }
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss, clippy::float_cmp)] // Untouched samples, short test
+    fn test_quiet_signal_passes_untouched() {
+        let mut limiter = SoftLimiter::new(SAMPLE_RATE);
+        limiter.set_ceiling_db(-6.0);
+        for i in 0..4800 {
+            let input = 0.3 * (TAU * 440.0 * i as f32 / SAMPLE_RATE).sin();
+            assert_eq!(limiter.process(input), input);
+        }
+        assert_eq!(limiter.take_max_reduction_db(), 0.0);
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss, clippy::float_cmp)] // Reset readings, short test
+    fn test_resonant_peak_held_under_ceiling() {
+        let mut limiter = SoftLimiter::new(SAMPLE_RATE);
+        limiter.set_ceiling_db(-12.0);
+        let ceiling = 10.0_f32.powf(-12.0 / 20.0);
+
+        let mut peak: f32 = 0.0;
+        for i in 0..48000 {
+            let input = 8.0 * (TAU * 1200.0 * i as f32 / SAMPLE_RATE).sin();
+            peak = peak.max(limiter.process(input).abs());
+        }
+        assert!(peak <= ceiling * 1.0001, "Peak {peak} over {ceiling}");
+        assert!(peak > ceiling * 0.9, "Limited too hard: {peak}");
+
+        // 8.0 is 18 dB over full scale, 30 dB over the ceiling
+        let reduction = limiter.take_max_reduction_db();
+        assert!((reduction - 30.06).abs() < 0.1, "{reduction}");
+        assert_eq!(limiter.take_max_reduction_db(), 0.0);
+    }
+
+    #[test]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::float_cmp
+    )] // Unity gain is exact, short test lengths
+    fn test_gain_recovers_after_peak() {
+        let mut limiter = SoftLimiter::new(SAMPLE_RATE);
+        limiter.set_ceiling_db(-6.0);
+        limiter.process(4.0);
+
+        // Still ducked right after, back to unity within a few time constants
+        assert!(limiter.process(0.1) < 0.1);
+        for _ in 0..(LIMITER_RELEASE_MS / 1000.0 * SAMPLE_RATE) as usize * 5 {
+            limiter.process(0.0);
+        }
+        assert_eq!(limiter.process(0.1), 0.1);
+    }
+}
@@ -0,0 +1,156 @@
+//! Master insert chain for Naughty and Tender
+//!
+//! The voice mix runs through a `shared_core` [`EffectChain`] with one slot per
+//! master effect. Each slot can be bypassed (with a click-free crossfade), and the
+//! processing order comes from one "which effect goes here" parameter per chain
+//! position.
+//!
+//! Default order: Drive → Phaser → EQ
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use crate::eq::MasterEq;
+use shared_core::effects::{Effect, EffectChain, Phaser, Waveshaper};
+
+/// Slot holding the master drive
+pub const DRIVE_SLOT: usize = 0;
+/// Slot holding the phaser
+pub const PHASER_SLOT: usize = 1;
+/// Slot holding the three-band EQ
+pub const EQ_SLOT: usize = 2;
+/// Number of slots in the master chain
+pub const NUM_SLOTS: usize = 3;
+
+/// Display names, indexed by slot
+pub const SLOT_NAMES: [&str; NUM_SLOTS] = ["Drive", "Phaser", "EQ"];
+
+/// Effects available on the master bus
+pub enum MasterEffect {
+    Drive(Waveshaper),
+    Phaser(Phaser),
+    Eq(MasterEq),
+}
+
+impl Effect for MasterEffect {
+    #[inline]
+    fn process(&mut self, input: f32) -> f32 {
+        match self {
+            Self::Drive(shaper) => shaper.process(input),
+            Self::Phaser(phaser) => phaser.process(input),
+            Self::Eq(eq) => eq.process(input),
+        }
+    }
+
+    fn reset(&mut self) {
+        match self {
+            Self::Drive(shaper) => shaper.reset(),
+            Self::Phaser(phaser) => phaser.reset(),
+            Self::Eq(eq) => eq.reset(),
+        }
+    }
+
+    fn tail_samples(&self) -> u32 {
+        match self {
+            Self::Phaser(phaser) => phaser.tail_samples(),
+            // Their filters settle within a few milliseconds
+            Self::Drive(_) | Self::Eq(_) => 0,
+        }
+    }
+}
+
+/// The master insert chain
+pub type MasterChain = EffectChain<MasterEffect, NUM_SLOTS>;
+
+/// Create the master chain with every effect in its default slot
+#[must_use]
+pub fn master_chain(sample_rate: f32) -> MasterChain {
+    EffectChain::new(
+        [
+            MasterEffect::Drive(Waveshaper::new(sample_rate)),
+            MasterEffect::Phaser(Phaser::new(sample_rate)),
+            MasterEffect::Eq(MasterEq::new(sample_rate)),
+        ],
+        sample_rate,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eq::EqSettings;
+
+    const SAMPLE_RATE: f32 = 44100.0;
+
+    #[test]
+    fn test_slots_hold_matching_effects() {
+        let chain = master_chain(SAMPLE_RATE);
+        assert!(matches!(
+            chain.slot(DRIVE_SLOT),
+            Some(MasterEffect::Drive(_))
+        ));
+        assert!(matches!(
+            chain.slot(PHASER_SLOT),
+            Some(MasterEffect::Phaser(_))
+        ));
+        assert!(matches!(chain.slot(EQ_SLOT), Some(MasterEffect::Eq(_))));
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)] // Short test lengths
+    fn test_fully_bypassed_chain_is_transparent() {
+        let mut chain = master_chain(SAMPLE_RATE);
+        for slot in 0..NUM_SLOTS {
+            chain.set_bypassed(slot, true);
+        }
+        // Skip the bypass fades
+        chain.reset();
+
+        for n in 0..1000 {
+            let input = (n as f32 * 0.05).sin();
+            assert!((chain.process(input) - input).abs() < 1e-7);
+        }
+    }
+
+    #[test]
+    fn test_tail_comes_from_phaser_unless_bypassed() {
+        let mut chain = master_chain(SAMPLE_RATE);
+        assert!(chain.tail_samples() > 0);
+
+        chain.set_bypassed(PHASER_SLOT, true);
+        chain.reset();
+        assert_eq!(chain.tail_samples(), 0);
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)] // Short test lengths
+    fn test_order_matters_for_nonlinear_slots() {
+        // EQ boost before the drive clips harder than EQ boost after it
+        let render = |order: [usize; NUM_SLOTS]| {
+            let mut chain = master_chain(SAMPLE_RATE);
+            chain.set_bypassed(PHASER_SLOT, true);
+            chain.set_order(order);
+            for slot in chain.slots_mut() {
+                if let MasterEffect::Eq(eq) = slot {
+                    eq.set_settings(EqSettings {
+                        mid_gain_db: 12.0,
+                        ..EqSettings::default()
+                    });
+                }
+            }
+
+            let omega = 2.0 * std::f32::consts::PI * 1000.0 / SAMPLE_RATE;
+            (0..4410)
+                .map(|n| chain.process(0.5 * (omega * n as f32).sin()))
+                .collect::<Vec<f32>>()
+        };
+
+        let drive_first = render([DRIVE_SLOT, PHASER_SLOT, EQ_SLOT]);
+        let eq_first = render([EQ_SLOT, PHASER_SLOT, DRIVE_SLOT]);
+
+        let peak = |samples: &[f32]| samples.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+        assert!(
+            peak(&drive_first) > peak(&eq_first),
+            "Boosting after the drive should leave a louder peak than boosting into it"
+        );
+    }
+}
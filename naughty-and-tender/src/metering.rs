@@ -0,0 +1,212 @@
+//! Gain-staging meters for Naughty and Tender
+//!
+//! Peak levels at each stage of the signal chain, so the editor can show where a
+//! signal gets hot enough to clip:
+//! - Voice mix: both layers (and the input in Always mode), into the master chain
+//! - Pre-gain: after the master effects, before the master gain
+//! - Output: after the master gain, as the host receives it
+//!
+//! The plugin has no output limiter, so anything over 0 dBFS at the output
+//! clips in the host.
+//!
+//! The audio thread raises each stage's peak with an atomic `fetch_max` once per
+//! block; the editor swaps the peaks back to zero every frame, so each reading is
+//! the loudest sample since the last one. Non-negative `f32` bit patterns sort
+//! the same as the floats, which is what makes `fetch_max` on the bits work.
+//!
+//! # References
+//! - Peak hold between editor reads: `fetch_max` on non-negative `f32` bits,
+//!   cleared with `swap`
+//! - dBFS: `20·log10(|x|)`, 0 dBFS = full scale (±1.0)
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+/// Quietest level the meters show, in dBFS
+pub const METER_FLOOR_DB: f32 = -60.0;
+
+/// A point in the signal chain with its own meter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeterStage {
+    VoiceMix,
+    PreGain,
+    Output,
+}
+
+impl MeterStage {
+    /// Every stage, in signal-flow order
+    pub const ALL: [Self; 3] = [Self::VoiceMix, Self::PreGain, Self::Output];
+
+    /// Display names, in signal-flow order
+    pub const NAMES: [&'static str; 3] = ["Voice Mix", "Pre-Gain", "Output"];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Number of metered stages
+pub const NUM_STAGES: usize = MeterStage::ALL.len();
+
+/// Peak of a block at every stage (audio thread)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StagePeaks {
+    peaks: [f32; NUM_STAGES],
+    clips: [u64; NUM_STAGES],
+}
+
+impl StagePeaks {
+    /// Track one sample at a stage
+    #[inline]
+    pub fn add(&mut self, stage: MeterStage, sample: f32) {
+        let level = sample.abs();
+        let index = stage.index();
+        self.peaks[index] = self.peaks[index].max(level);
+        if level > 1.0 {
+            self.clips[index] += 1;
+        }
+    }
+}
+
+/// Peak levels shared between the audio thread and the editor
+///
+/// # Real-time Safety
+/// - Fixed-size atomics, no allocations
+/// - `publish` only performs relaxed atomic read-modify-writes
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::metering::{GainStaging, MeterStage, StagePeaks};
+///
+/// let meters = GainStaging::new();
+/// let mut block = StagePeaks::default();
+/// block.add(MeterStage::Output, -0.5);
+/// meters.publish(&block);
+///
+/// assert_eq!(meters.take_peaks()[2], 0.5);
+/// assert_eq!(meters.take_peaks()[2], 0.0); // Reset by the read
+/// ```
+pub struct GainStaging {
+    /// Loudest sample since the editor last read, per stage (`f32` bits)
+    peaks: [AtomicU32; NUM_STAGES],
+
+    /// Samples over 0 dBFS since the plugin was loaded, per stage
+    clips: [AtomicU64; NUM_STAGES],
+
+    /// Whether the editor is showing the meters (the audio thread skips them otherwise)
+    listening: AtomicBool,
+}
+
+impl Default for GainStaging {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GainStaging {
+    /// Create silent meters, not listening
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            peaks: std::array::from_fn(|_| AtomicU32::new(0)),
+            clips: std::array::from_fn(|_| AtomicU64::new(0)),
+            listening: AtomicBool::new(false),
+        }
+    }
+
+    /// Start or stop metering (editor thread)
+    pub fn set_listening(&self, listening: bool) {
+        self.listening.store(listening, Ordering::Relaxed);
+    }
+
+    /// Whether the editor wants levels (check once per block)
+    #[must_use]
+    pub fn is_listening(&self) -> bool {
+        self.listening.load(Ordering::Relaxed)
+    }
+
+    /// Merge a block's peaks (audio thread)
+    pub fn publish(&self, block: &StagePeaks) {
+        for ((peak, clips), (&level, &count)) in self
+            .peaks
+            .iter()
+            .zip(&self.clips)
+            .zip(block.peaks.iter().zip(&block.clips))
+        {
+            peak.fetch_max(level.to_bits(), Ordering::Relaxed);
+            if count > 0 {
+                clips.fetch_add(count, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Loudest sample per stage since the last call, then reset (editor thread)
+    pub fn take_peaks(&self) -> [f32; NUM_STAGES] {
+        std::array::from_fn(|i| f32::from_bits(self.peaks[i].swap(0, Ordering::Relaxed)))
+    }
+
+    /// Samples over 0 dBFS at a stage since the plugin was loaded
+    #[must_use]
+    pub fn clip_count(&self, stage: MeterStage) -> u64 {
+        self.clips[stage.index()].load(Ordering::Relaxed)
+    }
+}
+
+/// Peak level in dBFS, floored at [`METER_FLOOR_DB`]
+#[inline]
+#[must_use]
+pub fn peak_to_dbfs(peak: f32) -> f32 {
+    if peak > 0.0 {
+        (20.0 * peak.log10()).max(METER_FLOOR_DB)
+    } else {
+        METER_FLOOR_DB
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)] // Peaks are stored, not computed
+    fn test_peaks_merge_across_blocks_until_read() {
+        let meters = GainStaging::new();
+
+        let mut first = StagePeaks::default();
+        first.add(MeterStage::VoiceMix, 0.25);
+        first.add(MeterStage::VoiceMix, -0.75);
+        meters.publish(&first);
+
+        let mut second = StagePeaks::default();
+        second.add(MeterStage::VoiceMix, 0.5);
+        second.add(MeterStage::PreGain, 0.1);
+        meters.publish(&second);
+
+        assert_eq!(meters.take_peaks(), [0.75, 0.1, 0.0]);
+        assert_eq!(meters.take_peaks(), [0.0; NUM_STAGES]);
+    }
+
+    #[test]
+    fn test_clips_counted_per_stage() {
+        let meters = GainStaging::new();
+        let mut block = StagePeaks::default();
+        for sample in [0.9, 1.2, -1.5, 1.0] {
+            block.add(MeterStage::PreGain, sample);
+        }
+        meters.publish(&block);
+        meters.publish(&block);
+
+        assert_eq!(meters.clip_count(MeterStage::PreGain), 4);
+        assert_eq!(meters.clip_count(MeterStage::Output), 0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // The floor is returned as is
+    fn test_dbfs_conversion() {
+        assert!(peak_to_dbfs(1.0).abs() < 1e-6);
+        assert!((peak_to_dbfs(0.5) + 6.02).abs() < 0.01);
+        assert_eq!(peak_to_dbfs(0.0), METER_FLOOR_DB);
+        assert_eq!(peak_to_dbfs(1e-6), METER_FLOOR_DB);
+    }
+}
@@ -0,0 +1,144 @@
+//! MIDI output note tracking for Naughty and Tender
+//!
+//! The notes the synth plays (after the chord, strum and octaver stages) can be
+//! sent to the host's MIDI output to drive other instruments. Every note sent
+//! out is tracked until it's released, per channel and note, so a note-off only
+//! goes out for a note that is sounding there, and a bypass or a switched-off
+//! MIDI output can release whatever is still held on the channel it was sent
+//! on.
+//!
+//! Notes are counted rather than flagged: the same note started twice on a
+//! channel (a chord and its octave layer landing on one key, say) takes two
+//! note-offs, matching how receiving instruments stack repeated note-ons.
+//!
+//! # References
+//! - MIDI 1.0 specification: note on/off, channel voice messages
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use crate::channel_filter::NUM_CHANNELS;
+use crate::NUM_NOTES;
+
+/// Notes sent to the MIDI output and not yet released
+///
+/// # Real-time Safety
+/// - Fixed-size tables, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::midi_out::MidiOutNotes;
+///
+/// let mut notes = MidiOutNotes::new();
+/// notes.note_on(2, 60);
+///
+/// assert!(!notes.note_off(0, 60)); // Never sent on channel 1
+/// assert!(notes.note_off(2, 60));
+/// assert!(!notes.note_off(2, 60)); // Already released
+/// ```
+pub struct MidiOutNotes {
+    /// Note-ons sent and not yet released, per channel and note
+    held: [[u8; NUM_NOTES]; NUM_CHANNELS as usize],
+}
+
+impl Default for MidiOutNotes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MidiOutNotes {
+    /// Create with nothing held
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            held: [[0; NUM_NOTES]; NUM_CHANNELS as usize],
+        }
+    }
+
+    /// Remember a note-on sent out
+    pub fn note_on(&mut self, channel: u8, note: u8) {
+        let held = self.count(channel, note);
+        *held = held.saturating_add(1);
+    }
+
+    /// Whether a note-off should go out: releases one of the note's note-ons
+    /// on the channel, if any is held
+    pub fn note_off(&mut self, channel: u8, note: u8) -> bool {
+        let held = self.count(channel, note);
+        let sent = *held > 0;
+        *held = held.saturating_sub(1);
+        sent
+    }
+
+    /// Whether anything is held
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.held.iter().flatten().all(|&count| count == 0)
+    }
+
+    /// Release everything held, yielding `(channel, note)` once per note-on
+    ///
+    /// Notes are forgotten as they're yielded, so run the iterator to the end.
+    pub fn release_all(&mut self) -> impl Iterator<Item = (u8, u8)> + '_ {
+        (0..NUM_CHANNELS)
+            .zip(&mut self.held)
+            .flat_map(|(channel, notes)| {
+                (0u8..).zip(notes).flat_map(move |(note, count)| {
+                    std::iter::repeat_n((channel, note), usize::from(std::mem::take(count)))
+                })
+            })
+    }
+
+    /// Forget every held note (without releasing them)
+    pub fn reset(&mut self) {
+        self.held = [[0; NUM_NOTES]; NUM_CHANNELS as usize];
+    }
+
+    fn count(&mut self, channel: u8, note: u8) -> &mut u8 {
+        &mut self.held[usize::from(channel % NUM_CHANNELS)][usize::from(note) % NUM_NOTES]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_note_ons_take_as_many_note_offs() {
+        let mut notes = MidiOutNotes::new();
+        notes.note_on(0, 60);
+        notes.note_on(0, 60);
+
+        assert!(notes.note_off(0, 60));
+        assert!(!notes.is_empty(), "One note-on is still sounding");
+        assert!(notes.note_off(0, 60));
+        assert!(!notes.note_off(0, 60));
+        assert!(notes.is_empty());
+    }
+
+    #[test]
+    fn test_channels_are_tracked_apart() {
+        let mut notes = MidiOutNotes::new();
+        notes.note_on(3, 64);
+
+        assert!(!notes.note_off(0, 64));
+        assert!(!notes.note_off(4, 64));
+        assert!(notes.note_off(3, 64));
+    }
+
+    #[test]
+    fn test_release_all_releases_each_note_on_its_channel() {
+        let mut notes = MidiOutNotes::new();
+        notes.note_on(0, 60);
+        notes.note_on(9, 36);
+        notes.note_on(9, 36);
+        notes.note_on(15, 127);
+        assert!(notes.note_off(0, 60));
+        notes.note_on(2, 60);
+
+        let released: Vec<(u8, u8)> = notes.release_all().collect();
+        assert_eq!(released, vec![(2, 60), (9, 36), (9, 36), (15, 127)]);
+        assert!(notes.is_empty());
+        assert_eq!(notes.release_all().count(), 0);
+    }
+}
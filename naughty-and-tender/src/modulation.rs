@@ -0,0 +1,641 @@
+//! Modulation matrix for Naughty and Tender
+//!
+//! A fixed number of slots, each routing one source to one destination with a
+//! bipolar amount. Every voice holds a copy of the matrix and evaluates it
+//! itself, so per-voice sources can be added alongside global ones.
+//!
+//! Modulation runs at control rate: every few samples (the control divisor, 16
+//! by default) a voice advances its control sources (random, filter envelope)
+//! and evaluates the matrix, then ramps linearly to the result at audio rate.
+//! The ramps trail the sources by one control period (0.3 ms at the default,
+//! 48 kHz); in exchange the matrix costs a fraction of its per-sample price at
+//! high polyphony.
+//!
+//! Destination scaling (amount = 1.0, source = 1.0):
+//! - Pitch: +`PITCH_RANGE_SEMITONES`
+//! - Cutoff: +`CUTOFF_RANGE_OCTAVES`
+//! - Level: +100% (gain 2.0); level never goes below silence
+//!
+//! Once per block the audio thread publishes the range of offsets across each
+//! layer's sounding voices to a [`ModMonitor`]. The editor marks that range on
+//! every slider whose parameter a routed destination lands on.
+//!
+//! The pitch source is each voice's sounding pitch as it glides and bends,
+//! relative to `PITCH_SOURCE_CENTER`, scaled so that pitch → cutoff at 1.0
+//! moves the cutoff an octave per octave played: full filter key tracking that
+//! follows slides instead of jumping to the next key.
+//!
+//! # References
+//! - Oberheim Matrix-6/12: slot-based source → destination routing
+//! - Csound's k-rate/a-rate split: control signals computed every `ksmps`
+//!   samples
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// Number of routing slots
+pub const NUM_MOD_SLOTS: usize = 4;
+
+/// Pitch offset in semitones at full modulation
+pub const PITCH_RANGE_SEMITONES: f32 = 24.0;
+
+/// Filter cutoff offset in octaves at full modulation
+pub const CUTOFF_RANGE_OCTAVES: f32 = 5.0;
+
+/// Layers a [`ModMonitor`] follows (A and B)
+pub const MONITORED_LAYERS: usize = 2;
+
+/// Smallest offset worth showing: a cent, a hundredth of an octave, 1% of level
+const VISIBLE_OFFSET: f32 = 0.01;
+
+/// Default samples per control-rate update
+pub const DEFAULT_CONTROL_DIVISOR: u32 = 16;
+
+/// Longest control period in samples (1 = every sample)
+pub const MAX_CONTROL_DIVISOR: u32 = 64;
+
+/// Pitch (MIDI note) at which the pitch source is 0.0: middle C
+pub const PITCH_SOURCE_CENTER: f32 = 60.0;
+
+/// Semitones from the center at which the pitch source reaches ±1.0 (so it
+/// tracks one-to-one when routed to the cutoff)
+pub const PITCH_SOURCE_RANGE_SEMITONES: f32 = CUTOFF_RANGE_OCTAVES * 12.0;
+
+/// Modulation source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModSource {
+    /// Slot unused
+    None,
+    /// Global 16-step sequencer (0.0 to 1.0)
+    StepSequencer,
+    /// Per-voice sample-and-hold random (-1.0 to 1.0)
+    Random,
+    /// Envelope of the sidechain audio input (0.0 to 1.0)
+    Sidechain,
+    /// Expression controller, CC 11 (0.0 to 1.0)
+    Expression,
+    /// Breath controller, CC 2 (0.0 to 1.0)
+    Breath,
+    /// Per-voice sounding pitch (see [`pitch_source`])
+    Pitch,
+}
+
+impl ModSource {
+    /// Every source, in parameter index order
+    pub const ALL: [Self; 7] = [
+        Self::None,
+        Self::StepSequencer,
+        Self::Random,
+        Self::Sidechain,
+        Self::Expression,
+        Self::Breath,
+        Self::Pitch,
+    ];
+
+    /// Display names, in parameter index order
+    pub const NAMES: [&'static str; 7] = [
+        "None",
+        "Step Seq",
+        "Random",
+        "Sidechain",
+        "Expression",
+        "Breath",
+        "Pitch",
+    ];
+
+    /// Source at a parameter index (out-of-range falls back to `None`)
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or(Self::None)
+    }
+}
+
+/// Modulation destination
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModDestination {
+    /// Slot unused
+    None,
+    /// Oscillator pitch
+    Pitch,
+    /// Voice filter cutoff
+    Cutoff,
+    /// Voice output level
+    Level,
+}
+
+impl ModDestination {
+    /// Every destination, in parameter index order
+    pub const ALL: [Self; 4] = [Self::None, Self::Pitch, Self::Cutoff, Self::Level];
+
+    /// Display names, in parameter index order
+    pub const NAMES: [&'static str; 4] = ["None", "Pitch", "Cutoff", "Level"];
+
+    /// Destination at a parameter index (out-of-range falls back to `None`)
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or(Self::None)
+    }
+}
+
+/// One routing: source × amount → destination
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModSlot {
+    pub source: ModSource,
+    pub destination: ModDestination,
+    /// Bipolar depth (-1.0 to 1.0)
+    pub amount: f32,
+}
+
+impl Default for ModSlot {
+    fn default() -> Self {
+        Self {
+            source: ModSource::None,
+            destination: ModDestination::None,
+            amount: 0.0,
+        }
+    }
+}
+
+/// Current value of every modulation source
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ModSourceValues {
+    /// Step sequencer output (0.0 to 1.0)
+    pub step_sequencer: f32,
+    /// Per-voice random output (-1.0 to 1.0), filled in by each voice
+    pub random: f32,
+    /// Sidechain envelope follower output (0.0 to 1.0)
+    pub sidechain: f32,
+    /// Smoothed expression controller (0.0 to 1.0)
+    pub expression: f32,
+    /// Smoothed breath controller (0.0 to 1.0)
+    pub breath: f32,
+    /// Per-voice sounding pitch (0.0 at middle C), filled in by each voice
+    pub pitch: f32,
+}
+
+impl ModSourceValues {
+    /// Value of one source
+    #[inline]
+    #[must_use]
+    pub fn get(&self, source: ModSource) -> f32 {
+        match source {
+            ModSource::None => 0.0,
+            ModSource::StepSequencer => self.step_sequencer,
+            ModSource::Random => self.random,
+            ModSource::Sidechain => self.sidechain,
+            ModSource::Expression => self.expression,
+            ModSource::Breath => self.breath,
+            ModSource::Pitch => self.pitch,
+        }
+    }
+}
+
+/// Pitch source value for a sounding pitch in semitones (fractional MIDI note)
+///
+/// 0.0 at middle C, ±1.0 `PITCH_SOURCE_RANGE_SEMITONES` either side of it.
+#[inline]
+#[must_use]
+pub fn pitch_source(pitch: f32) -> f32 {
+    (pitch - PITCH_SOURCE_CENTER) / PITCH_SOURCE_RANGE_SEMITONES
+}
+
+/// Summed modulation, scaled into each destination's units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModOffsets {
+    /// Pitch offset in semitones
+    pub pitch_semitones: f32,
+    /// Filter cutoff offset in octaves
+    pub cutoff_octaves: f32,
+    /// Output gain multiplier (1.0 = unmodulated)
+    pub level: f32,
+}
+
+impl Default for ModOffsets {
+    fn default() -> Self {
+        Self {
+            pitch_semitones: 0.0,
+            cutoff_octaves: 0.0,
+            level: 1.0,
+        }
+    }
+}
+
+impl ModOffsets {
+    /// Plain value of a parameter a destination lands on, after these offsets:
+    /// cents (pitch), Hz (cutoff) or linear level
+    ///
+    /// # Example
+    /// ```
+    /// use naughty_and_tender::modulation::{ModDestination, ModOffsets};
+    ///
+    /// let offsets = ModOffsets {
+    ///     cutoff_octaves: 1.0,
+    ///     ..ModOffsets::default()
+    /// };
+    /// assert_eq!(offsets.apply(ModDestination::Cutoff, 1000.0), 2000.0);
+    /// assert_eq!(offsets.apply(ModDestination::Pitch, 5.0), 5.0);
+    /// ```
+    #[must_use]
+    pub fn apply(&self, destination: ModDestination, plain: f32) -> f32 {
+        match destination {
+            ModDestination::None => plain,
+            ModDestination::Pitch => plain + 100.0 * self.pitch_semitones,
+            ModDestination::Cutoff => plain * self.cutoff_octaves.exp2(),
+            ModDestination::Level => plain * self.level,
+        }
+    }
+
+    /// How far a destination is moved from unmodulated, in its own units
+    #[must_use]
+    pub fn offset(&self, destination: ModDestination) -> f32 {
+        match destination {
+            ModDestination::None => 0.0,
+            ModDestination::Pitch => self.pitch_semitones,
+            ModDestination::Cutoff => self.cutoff_octaves,
+            ModDestination::Level => self.level - 1.0,
+        }
+    }
+}
+
+/// Slot-based modulation matrix
+///
+/// # Real-time Safety
+/// - Fixed slot array, `Copy`, no allocations
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ModMatrix {
+    pub slots: [ModSlot; NUM_MOD_SLOTS],
+}
+
+impl ModMatrix {
+    /// Whether any slot routes anywhere (lets voices skip evaluation)
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.slots.iter().any(|slot| {
+            slot.source != ModSource::None
+                && slot.destination != ModDestination::None
+                && slot.amount.abs() > f32::EPSILON
+        })
+    }
+
+    /// Whether any slot routes a source to `destination`
+    #[must_use]
+    pub fn targets(&self, destination: ModDestination) -> bool {
+        destination != ModDestination::None
+            && self.slots.iter().any(|slot| {
+                slot.source != ModSource::None
+                    && slot.destination == destination
+                    && slot.amount.abs() > f32::EPSILON
+            })
+    }
+
+    /// Sum every slot into destination offsets
+    #[inline]
+    #[must_use]
+    pub fn evaluate(&self, sources: &ModSourceValues) -> ModOffsets {
+        let mut pitch = 0.0;
+        let mut cutoff = 0.0;
+        let mut level = 0.0;
+
+        for slot in &self.slots {
+            let value = sources.get(slot.source) * slot.amount;
+            match slot.destination {
+                ModDestination::None => {}
+                ModDestination::Pitch => pitch += value,
+                ModDestination::Cutoff => cutoff += value,
+                ModDestination::Level => level += value,
+            }
+        }
+
+        ModOffsets {
+            pitch_semitones: pitch * PITCH_RANGE_SEMITONES,
+            cutoff_octaves: cutoff * CUTOFF_RANGE_OCTAVES,
+            level: (1.0 + level).max(0.0),
+        }
+    }
+}
+
+/// Lowest and highest modulation across a layer's sounding voices
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModRange {
+    pub low: ModOffsets,
+    pub high: ModOffsets,
+}
+
+impl ModRange {
+    /// Range covering a single voice
+    #[must_use]
+    pub fn new(offsets: ModOffsets) -> Self {
+        Self {
+            low: offsets,
+            high: offsets,
+        }
+    }
+
+    /// Widen the range to cover another voice
+    pub fn include(&mut self, offsets: ModOffsets) {
+        self.low.pitch_semitones = self.low.pitch_semitones.min(offsets.pitch_semitones);
+        self.low.cutoff_octaves = self.low.cutoff_octaves.min(offsets.cutoff_octaves);
+        self.low.level = self.low.level.min(offsets.level);
+        self.high.pitch_semitones = self.high.pitch_semitones.max(offsets.pitch_semitones);
+        self.high.cutoff_octaves = self.high.cutoff_octaves.max(offsets.cutoff_octaves);
+        self.high.level = self.high.level.max(offsets.level);
+    }
+
+    /// Whether any voice moves a destination far enough to show
+    #[must_use]
+    pub fn moves(&self, destination: ModDestination) -> bool {
+        self.low.offset(destination).abs() >= VISIBLE_OFFSET
+            || self.high.offset(destination).abs() >= VISIBLE_OFFSET
+    }
+}
+
+/// Each layer's range of modulation, shared with the editor for the sliders
+/// it moves
+///
+/// # Real-time Safety
+/// - Relaxed atomic loads and stores only, once per block
+#[derive(Debug)]
+pub struct ModMonitor {
+    /// Per layer: low then high pitch, cutoff and level offsets (`f32` bits)
+    offsets: [[AtomicU32; 6]; MONITORED_LAYERS],
+
+    /// Per layer: whether a voice is sounding
+    sounding: [AtomicBool; MONITORED_LAYERS],
+}
+
+impl Default for ModMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ModMonitor {
+    /// No voice sounding on any layer
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            offsets: std::array::from_fn(|_| std::array::from_fn(|_| AtomicU32::new(0))),
+            sounding: std::array::from_fn(|_| AtomicBool::new(false)),
+        }
+    }
+
+    /// Publish a layer's range of modulation, or `None` while none of its
+    /// voices sound (audio thread)
+    pub fn publish(&self, layer: usize, range: Option<ModRange>) {
+        let (Some(slots), Some(sounding)) = (self.offsets.get(layer), self.sounding.get(layer))
+        else {
+            return;
+        };
+        if let Some(ModRange { low, high }) = range {
+            let values = [
+                low.pitch_semitones,
+                low.cutoff_octaves,
+                low.level,
+                high.pitch_semitones,
+                high.cutoff_octaves,
+                high.level,
+            ];
+            for (slot, value) in slots.iter().zip(values) {
+                slot.store(value.to_bits(), Ordering::Relaxed);
+            }
+        }
+        sounding.store(range.is_some(), Ordering::Relaxed);
+    }
+
+    /// A layer's range of modulation, if any of its voices sound (editor
+    /// thread)
+    #[must_use]
+    pub fn range(&self, layer: usize) -> Option<ModRange> {
+        if !self.sounding.get(layer)?.load(Ordering::Relaxed) {
+            return None;
+        }
+        let [low_pitch, low_cutoff, low_level, high_pitch, high_cutoff, high_level] = self.offsets
+            [layer]
+            .each_ref()
+            .map(|slot| f32::from_bits(slot.load(Ordering::Relaxed)));
+        Some(ModRange {
+            low: ModOffsets {
+                pitch_semitones: low_pitch,
+                cutoff_octaves: low_cutoff,
+                level: low_level,
+            },
+            high: ModOffsets {
+                pitch_semitones: high_pitch,
+                cutoff_octaves: high_cutoff,
+                level: high_level,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix_with(slot: ModSlot) -> ModMatrix {
+        let mut matrix = ModMatrix::default();
+        matrix.slots[0] = slot;
+        matrix
+    }
+
+    #[test]
+    fn test_empty_matrix_is_neutral() {
+        let matrix = ModMatrix::default();
+        assert!(!matrix.is_active());
+
+        let offsets = matrix.evaluate(&ModSourceValues {
+            step_sequencer: 1.0,
+            ..ModSourceValues::default()
+        });
+        assert_eq!(offsets, ModOffsets::default());
+    }
+
+    #[test]
+    fn test_pitch_routing_scales_to_semitones() {
+        let matrix = matrix_with(ModSlot {
+            source: ModSource::StepSequencer,
+            destination: ModDestination::Pitch,
+            amount: 0.5,
+        });
+        assert!(matrix.is_active());
+
+        let offsets = matrix.evaluate(&ModSourceValues {
+            step_sequencer: 1.0,
+            ..ModSourceValues::default()
+        });
+        assert!((offsets.pitch_semitones - 12.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_slots_sum_and_level_never_goes_negative() {
+        let mut matrix = ModMatrix::default();
+        for slot in &mut matrix.slots {
+            *slot = ModSlot {
+                source: ModSource::StepSequencer,
+                destination: ModDestination::Level,
+                amount: -1.0,
+            };
+        }
+
+        let offsets = matrix.evaluate(&ModSourceValues {
+            step_sequencer: 1.0,
+            ..ModSourceValues::default()
+        });
+        assert!(offsets.level.abs() < 1e-7, "Level should clamp at silence");
+    }
+
+    #[test]
+    fn test_random_routes_to_cutoff() {
+        let matrix = matrix_with(ModSlot {
+            source: ModSource::Random,
+            destination: ModDestination::Cutoff,
+            amount: 1.0,
+        });
+
+        let offsets = matrix.evaluate(&ModSourceValues {
+            random: -0.5,
+            ..ModSourceValues::default()
+        });
+        assert!((offsets.cutoff_octaves + 2.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_targets_follow_the_routed_slots() {
+        let matrix = matrix_with(ModSlot {
+            source: ModSource::Random,
+            destination: ModDestination::Cutoff,
+            amount: 0.5,
+        });
+        assert!(matrix.targets(ModDestination::Cutoff));
+        assert!(!matrix.targets(ModDestination::Pitch));
+        assert!(!matrix.targets(ModDestination::None));
+        assert!(!ModMatrix::default().targets(ModDestination::Cutoff));
+    }
+
+    #[test]
+    fn test_offsets_move_their_parameters() {
+        let offsets = ModOffsets {
+            pitch_semitones: -0.5,
+            cutoff_octaves: -2.0,
+            level: 1.5,
+        };
+        assert!((offsets.apply(ModDestination::Pitch, 10.0) + 40.0).abs() < 1e-4);
+        assert!((offsets.apply(ModDestination::Cutoff, 800.0) - 200.0).abs() < 1e-3);
+        assert!((offsets.apply(ModDestination::Level, 0.5) - 0.75).abs() < 1e-6);
+        assert!((offsets.offset(ModDestination::Level) - 0.5).abs() < 1e-6);
+        assert!(offsets.offset(ModDestination::None).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_range_covers_every_voice() {
+        let mut range = ModRange::new(ModOffsets {
+            cutoff_octaves: 1.0,
+            ..ModOffsets::default()
+        });
+        range.include(ModOffsets {
+            cutoff_octaves: -0.5,
+            level: 1.25,
+            ..ModOffsets::default()
+        });
+        assert!((range.low.cutoff_octaves + 0.5).abs() < 1e-6);
+        assert!((range.high.cutoff_octaves - 1.0).abs() < 1e-6);
+        assert!((range.high.level - 1.25).abs() < 1e-6);
+        assert!(range.moves(ModDestination::Cutoff));
+        assert!(range.moves(ModDestination::Level));
+        assert!(!range.moves(ModDestination::Pitch));
+
+        // Too little to see
+        let still = ModRange::new(ModOffsets {
+            pitch_semitones: 0.001,
+            ..ModOffsets::default()
+        });
+        assert!(!ModDestination::ALL.iter().any(|&dest| still.moves(dest)));
+    }
+
+    #[test]
+    fn test_monitor_snapshot_holds_each_layers_range() {
+        let monitor = ModMonitor::new();
+        assert_eq!(monitor.range(0), None);
+
+        let mut range = ModRange::new(ModOffsets {
+            pitch_semitones: -2.0,
+            cutoff_octaves: 1.25,
+            level: 0.5,
+        });
+        range.include(ModOffsets {
+            pitch_semitones: 3.0,
+            cutoff_octaves: -1.0,
+            level: 1.5,
+        });
+        monitor.publish(1, Some(range));
+        assert_eq!(monitor.range(0), None);
+        assert_eq!(monitor.range(1), Some(range));
+
+        // The voices stop; out-of-range layers are ignored
+        monitor.publish(1, None);
+        monitor.publish(MONITORED_LAYERS, Some(range));
+        assert_eq!(monitor.range(1), None);
+        assert_eq!(monitor.range(MONITORED_LAYERS), None);
+    }
+
+    #[test]
+    fn test_sidechain_routes_to_cutoff() {
+        let matrix = matrix_with(ModSlot {
+            source: ModSource::Sidechain,
+            destination: ModDestination::Cutoff,
+            amount: 0.4,
+        });
+
+        let offsets = matrix.evaluate(&ModSourceValues {
+            sidechain: 0.5,
+            ..ModSourceValues::default()
+        });
+        assert!((offsets.cutoff_octaves - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_index_lookups_fall_back_to_none() {
+        assert_eq!(ModSource::from_index(1), ModSource::StepSequencer);
+        assert_eq!(ModSource::from_index(99), ModSource::None);
+        assert_eq!(ModDestination::from_index(3), ModDestination::Level);
+        assert_eq!(ModDestination::ALL.len(), ModDestination::NAMES.len());
+        assert_eq!(ModSource::ALL.len(), ModSource::NAMES.len());
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // The center is exactly zero
+    fn test_pitch_to_cutoff_tracks_one_to_one() {
+        let matrix = matrix_with(ModSlot {
+            source: ModSource::Pitch,
+            destination: ModDestination::Cutoff,
+            amount: 1.0,
+        });
+        assert_eq!(pitch_source(PITCH_SOURCE_CENTER), 0.0);
+
+        // An octave and a half up (mid-glide, say) opens the cutoff as far
+        for (pitch, octaves) in [(78.0, 1.5), (48.0, -1.0)] {
+            let offsets = matrix.evaluate(&ModSourceValues {
+                pitch: pitch_source(pitch),
+                ..ModSourceValues::default()
+            });
+            assert!((offsets.cutoff_octaves - octaves).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_breath_routes_to_cutoff() {
+        let matrix = matrix_with(ModSlot {
+            source: ModSource::Breath,
+            destination: ModDestination::Cutoff,
+            amount: 0.4,
+        });
+
+        let offsets = matrix.evaluate(&ModSourceValues {
+            breath: 0.5,
+            expression: 1.0,
+            ..ModSourceValues::default()
+        });
+        assert!((offsets.cutoff_octaves - 0.2 * CUTOFF_RANGE_OCTAVES).abs() < 1e-5);
+    }
+}
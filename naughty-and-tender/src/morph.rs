@@ -0,0 +1,275 @@
+//! Patch morphing for Naughty and Tender
+//!
+//! Two sounds are stored as snapshots A and B (every parameter's normalized
+//! value, by ID, as in a preset), and one morph parameter moves between them:
+//! continuous parameters are interpolated, stepped ones (waveforms, modes,
+//! switches) flip from A to B halfway.
+//!
+//! The morph parameter itself is an ordinary parameter, so the host can
+//! automate it. The audio thread applies the morph to the voice settings and
+//! the output gain every block, whether or not the editor is open: each of
+//! those parameters both snapshots hold plays at its blend instead of its own
+//! value. The voices slew cutoff and tuning and the engine slews the gain
+//! sample by sample (see [`crate::slew`]), so a sudden automation jump morphs
+//! those without clicks. While both snapshots are stored, an edit to one of
+//! those parameters is only heard once it's stored in a snapshot.
+//!
+//! While the editor is open it also glides the parameters themselves towards
+//! each new morph position, setting them through the host (plugins can't set
+//! their own parameters from the audio thread), so the knobs follow the morph
+//! and the rest of the parameters morph along.
+//!
+//! The morph position isn't part of either snapshot, and presets leave it
+//! alone: it's a performance control, not part of a sound.
+//!
+//! # References
+//! - Preset morphing in software synths (Alchemy's morph pad, Omnisphere's Orb):
+//!   interpolate continuous values, switch discrete ones
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+/// Parameter ID of the morph position
+pub const MORPH_ID: &str = "morph";
+
+/// Time a full A-to-B morph glides over, however fast the position moved
+pub const MORPH_GLIDE_MS: f32 = 100.0;
+
+/// Every parameter's normalized value, by parameter ID
+pub type Snapshot = Vec<(String, f32)>;
+
+/// One of the two morph snapshots
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MorphSlot {
+    A,
+    B,
+}
+
+/// Normalized value `position` of the way from `a` to `b`
+///
+/// Stepped parameters have no values in between: they take A's value below the
+/// halfway point and B's from it on.
+#[must_use]
+pub fn morph_value(a: f32, b: f32, position: f32, stepped: bool) -> f32 {
+    let position = position.clamp(0.0, 1.0);
+    if stepped {
+        if position < 0.5 {
+            a
+        } else {
+            b
+        }
+    } else {
+        a + (b - a) * position
+    }
+}
+
+/// The two sounds being morphed between
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::morph::{MorphPair, MorphSlot};
+///
+/// let mut pair = MorphPair::default();
+/// pair.set(MorphSlot::A, vec![("cutoff".to_string(), 0.2)]);
+/// pair.set(MorphSlot::B, vec![("cutoff".to_string(), 0.6)]);
+///
+/// let value = pair.value("cutoff", 0.5, false).unwrap();
+/// assert!((value - 0.4).abs() < 1e-6);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MorphPair {
+    a: Snapshot,
+    b: Snapshot,
+}
+
+impl MorphPair {
+    /// Rebuild from saved snapshots (empty = not stored yet)
+    #[must_use]
+    pub fn from_saved(a: Snapshot, b: Snapshot) -> Self {
+        Self { a, b }
+    }
+
+    /// Store a snapshot (the morph position itself is left out)
+    pub fn set(&mut self, slot: MorphSlot, mut snapshot: Snapshot) {
+        snapshot.retain(|(id, _)| id != MORPH_ID);
+        match slot {
+            MorphSlot::A => self.a = snapshot,
+            MorphSlot::B => self.b = snapshot,
+        }
+    }
+
+    /// A stored snapshot (empty if not stored yet)
+    #[must_use]
+    pub fn get(&self, slot: MorphSlot) -> &Snapshot {
+        match slot {
+            MorphSlot::A => &self.a,
+            MorphSlot::B => &self.b,
+        }
+    }
+
+    /// Whether both snapshots are stored
+    #[must_use]
+    pub fn is_ready(&self) -> bool {
+        !self.a.is_empty() && !self.b.is_empty()
+    }
+
+    /// A parameter's morphed value, if both snapshots have it
+    #[must_use]
+    pub fn value(&self, id: &str, position: f32, stepped: bool) -> Option<f32> {
+        snapshot_value(&self.a, &self.b, id, position, stepped)
+    }
+}
+
+/// A parameter's normalized value `position` of the way from snapshot `a` to
+/// `b`, if both have it
+///
+/// # Real-time Safety
+/// - No allocations or locks (one pass over each snapshot)
+#[must_use]
+pub fn snapshot_value(
+    a: &Snapshot,
+    b: &Snapshot,
+    id: &str,
+    position: f32,
+    stepped: bool,
+) -> Option<f32> {
+    if id == MORPH_ID {
+        return None;
+    }
+    let a = lookup(a, id)?;
+    let b = lookup(b, id)?;
+    Some(morph_value(a, b, position, stepped))
+}
+
+/// Value of a parameter in a snapshot
+fn lookup(snapshot: &Snapshot, id: &str) -> Option<f32> {
+    snapshot
+        .iter()
+        .find(|(other, _)| other == id)
+        .map(|(_, value)| *value)
+}
+
+/// Glides the applied morph position towards the morph parameter
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::morph::MorphFollower;
+///
+/// let mut follower = MorphFollower::new(0.0);
+/// assert_eq!(follower.update(0.0, 0.016), None); // Nothing to apply
+///
+/// let applied = follower.update(1.0, 0.016).unwrap();
+/// assert!(applied > 0.0 && applied < 1.0); // Gliding
+/// ```
+#[derive(Debug, Clone)]
+pub struct MorphFollower {
+    /// Position the parameters were last set to
+    applied: f32,
+}
+
+impl MorphFollower {
+    /// Start at `position`, as if it had already been applied
+    #[must_use]
+    pub fn new(position: f32) -> Self {
+        Self { applied: position }
+    }
+
+    /// Glide towards `target` for `elapsed_s` seconds, returning the position
+    /// to apply if it moved
+    #[allow(clippy::float_cmp)] // Exact arrival ends the glide
+    pub fn update(&mut self, target: f32, elapsed_s: f32) -> Option<f32> {
+        let target = target.clamp(0.0, 1.0);
+        if target == self.applied {
+            return None;
+        }
+
+        let max_step = (elapsed_s * 1000.0 / MORPH_GLIDE_MS).max(0.0);
+        let distance = target - self.applied;
+        self.applied = if distance.abs() <= max_step {
+            target
+        } else {
+            self.applied + max_step.copysign(distance)
+        };
+        Some(self.applied)
+    }
+
+    /// Whether a glide is still under way towards `target`
+    #[must_use]
+    #[allow(clippy::float_cmp)] // Exact arrival ends the glide
+    pub fn is_gliding(&self, target: f32) -> bool {
+        target.clamp(0.0, 1.0) != self.applied
+    }
+
+    /// Take `position` as applied without setting anything
+    pub fn sync(&mut self, position: f32) {
+        self.applied = position.clamp(0.0, 1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(values: &[(&str, f32)]) -> Snapshot {
+        values
+            .iter()
+            .map(|(id, value)| ((*id).to_string(), *value))
+            .collect()
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // Snapshot values are exact
+    fn test_continuous_values_interpolate() {
+        assert_eq!(morph_value(0.2, 0.6, 0.0, false), 0.2);
+        assert_eq!(morph_value(0.2, 0.6, 1.0, false), 0.6);
+        assert!((morph_value(0.2, 0.6, 0.25, false) - 0.3).abs() < 1e-6);
+        assert_eq!(morph_value(0.2, 0.6, 2.0, false), 0.6);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // Snapshot values are exact
+    fn test_stepped_values_switch_halfway() {
+        assert_eq!(morph_value(0.0, 1.0, 0.49, true), 0.0);
+        assert_eq!(morph_value(0.0, 1.0, 0.5, true), 1.0);
+    }
+
+    #[test]
+    fn test_pair_needs_both_snapshots() {
+        let mut pair = MorphPair::default();
+        pair.set(MorphSlot::A, snapshot(&[("gain", 0.5), ("only_a", 0.1)]));
+        assert!(!pair.is_ready());
+        assert_eq!(pair.value("gain", 0.5, false), None);
+
+        pair.set(MorphSlot::B, snapshot(&[("gain", 1.0), (MORPH_ID, 0.7)]));
+        assert!(pair.is_ready());
+        assert_eq!(pair.value("gain", 1.0, false), Some(1.0));
+        assert_eq!(pair.value("only_a", 0.5, false), None);
+
+        // The morph position is never stored or morphed
+        assert_eq!(pair.get(MorphSlot::B).len(), 1);
+        assert_eq!(pair.value(MORPH_ID, 0.5, false), None);
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // A handful of frames
+    fn test_follower_glides_to_jumps() {
+        let mut follower = MorphFollower::new(0.0);
+        let frame_seconds = 0.01;
+        let frames = (MORPH_GLIDE_MS / 1000.0 / frame_seconds).round() as usize;
+
+        let mut previous = 0.0;
+        for _ in 0..frames - 1 {
+            let applied = follower.update(1.0, frame_seconds).unwrap();
+            assert!(applied > previous && applied < 1.0);
+            previous = applied;
+        }
+        assert!(follower.is_gliding(1.0));
+        assert_eq!(follower.update(1.0, frame_seconds), Some(1.0));
+        assert!(!follower.is_gliding(1.0));
+        assert_eq!(follower.update(1.0, frame_seconds), None);
+
+        // Small moves land in one frame
+        assert_eq!(follower.update(0.95, frame_seconds), Some(0.95));
+        follower.sync(0.3);
+        assert_eq!(follower.update(0.3, frame_seconds), None);
+    }
+}
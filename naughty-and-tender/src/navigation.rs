@@ -0,0 +1,143 @@
+//! Keyboard control of the editor for Naughty and Tender
+//!
+//! Every control can be reached with Tab (egui moves focus between
+//! interactive widgets), and a focused parameter moves with the keyboard:
+//! arrows by a small step (a finer one with Shift), Page Up and Page Down by a
+//! large one, Home and End to either end. Stepped parameters (switches,
+//! choices, whole numbers) move a whole step at a time. The editor reads the
+//! keys and applies the nudge as one complete gesture, so host automation and
+//! undo see it like any other edit.
+//!
+//! # References
+//! - WAI-ARIA Authoring Practices, slider pattern (arrow, Page Up/Down, Home/End)
+//! - egui focus handling and `WidgetInfo` (what AccessKit screen readers read)
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+/// Arrow-key step of a continuous parameter, normalized
+pub const STEP: f32 = 0.01;
+
+/// Shift+arrow step of a continuous parameter, normalized
+pub const FINE_STEP: f32 = 0.001;
+
+/// Steps in one Page Up or Page Down
+pub const PAGE_STEPS: f32 = 10.0;
+
+/// Keyboard move of a focused control
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Nudge {
+    /// Arrow down or left
+    Down,
+
+    /// Arrow up or right
+    Up,
+
+    PageDown,
+    PageUp,
+
+    /// Home
+    Min,
+
+    /// End
+    Max,
+}
+
+impl Nudge {
+    /// Normalized value after the nudge
+    ///
+    /// `step_count` is the parameter's number of steps (`None` when it's
+    /// continuous); `fine` asks for the finer step, which only continuous
+    /// parameters have.
+    ///
+    /// # Example
+    /// ```
+    /// use naughty_and_tender::navigation::Nudge;
+    ///
+    /// assert!((Nudge::Up.apply(0.5, None, false) - 0.51).abs() < 1e-6);
+    /// assert_eq!(Nudge::Up.apply(0.0, Some(4), false), 0.25); // One of four steps
+    /// assert_eq!(Nudge::Max.apply(0.3, None, false), 1.0);
+    /// ```
+    #[must_use]
+    pub fn apply(self, normalized: f32, step_count: Option<usize>, fine: bool) -> f32 {
+        #[allow(clippy::cast_precision_loss)] // Step counts are small
+        let step = match step_count {
+            Some(steps) if steps > 0 => 1.0 / steps as f32,
+            _ if fine => FINE_STEP,
+            _ => STEP,
+        };
+        let moved = match self {
+            Self::Down => normalized - step,
+            Self::Up => normalized + step,
+            Self::PageDown => normalized - step * PAGE_STEPS,
+            Self::PageUp => normalized + step * PAGE_STEPS,
+            Self::Min => 0.0,
+            Self::Max => 1.0,
+        };
+
+        // Stepped values land on a step even if they started between two
+        match step_count {
+            Some(steps) if steps > 0 => {
+                #[allow(clippy::cast_precision_loss)] // Step counts are small
+                let steps = steps as f32;
+                ((moved * steps).round() / steps).clamp(0.0, 1.0)
+            }
+            _ => moved.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Index after the nudge, moving through `len` items (a cursor in a
+    /// grid); a page moves by [`PAGE_STEPS`] items
+    #[must_use]
+    pub fn move_index(self, index: usize, len: usize) -> usize {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // A small constant
+        let page = PAGE_STEPS as usize;
+        let last = len.saturating_sub(1);
+        match self {
+            Self::Down => index.saturating_sub(1),
+            Self::Up => index + 1,
+            Self::PageDown => index.saturating_sub(page),
+            Self::PageUp => index + page,
+            Self::Min => 0,
+            Self::Max => last,
+        }
+        .min(last)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)] // Clamped ends are exact
+    fn test_continuous_steps() {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-6;
+        assert!(close(Nudge::Down.apply(0.5, None, false), 0.49));
+        assert!(close(Nudge::Down.apply(0.5, None, true), 0.499));
+        assert!(close(Nudge::PageUp.apply(0.5, None, false), 0.6));
+        assert_eq!(Nudge::PageUp.apply(0.95, None, false), 1.0);
+        assert_eq!(Nudge::Min.apply(0.5, None, true), 0.0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // Stepped values land exactly
+    fn test_stepped_values_stay_on_steps() {
+        // A switch: 0.0 or 1.0, fine or not
+        assert_eq!(Nudge::Up.apply(0.0, Some(1), true), 1.0);
+        assert_eq!(Nudge::Down.apply(1.0, Some(1), false), 0.0);
+
+        // Off-step values snap after moving
+        assert_eq!(Nudge::Up.apply(0.3, Some(4), false), 0.5);
+        assert_eq!(Nudge::PageDown.apply(0.75, Some(4), false), 0.0);
+    }
+
+    #[test]
+    fn test_cursor_stays_in_grid() {
+        assert_eq!(Nudge::Up.move_index(3, 16), 4);
+        assert_eq!(Nudge::Up.move_index(15, 16), 15);
+        assert_eq!(Nudge::Down.move_index(0, 16), 0);
+        assert_eq!(Nudge::PageUp.move_index(12, 16), 15);
+        assert_eq!(Nudge::Max.move_index(2, 16), 15);
+        assert_eq!(Nudge::Max.move_index(0, 0), 0);
+    }
+}
@@ -0,0 +1,218 @@
+//! CLAP note expressions for Naughty and Tender
+//!
+//! CLAP hosts can shape each note on its own after it starts: its volume, its
+//! pan and its tuning, addressed by voice id (or by key when the host has no
+//! ids). This works without MPE, since every note is addressed directly rather
+//! than through a channel of its own.
+//!
+//! The latest values are kept per voice id until the voice ends, since an
+//! expression often arrives on the same sample as its note-on, before the note
+//! has made it through humanization, chords and strumming to a voice. Voices
+//! fold volume and tuning into their control-rate modulation and ramp pan at
+//! the same rate, so stepped expression curves don't zipper.
+//!
+//! Pan uses a balance law: a centered voice plays at full level in both
+//! channels, as an unpanned voice always has, and panning turns the other
+//! channel down.
+//!
+//! # References
+//! - CLAP `note_expression` extension: volume, pan and tuning
+//! - Balance (rather than constant-power) panning in stereo mixer channels
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+/// Most voice ids with expression values at once (values for more are dropped)
+pub const MAX_EXPRESSION_VOICES: usize = 64;
+
+/// Highest volume expression gain (CLAP's +12 dB)
+pub const MAX_EXPRESSION_GAIN: f32 = 4.0;
+
+/// Largest tuning expression, in semitones
+pub const MAX_EXPRESSION_TUNING: f32 = 120.0;
+
+/// One note expression event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoteExpression {
+    /// Gain multiplier (1.0 = unchanged, up to [`MAX_EXPRESSION_GAIN`])
+    Volume(f32),
+    /// Stereo position (-1.0 = left, 0.0 = center, 1.0 = right)
+    Pan(f32),
+    /// Pitch offset in semitones
+    Tuning(f32),
+}
+
+/// Every note expression of one voice
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExpressionValues {
+    /// Gain multiplier (1.0 = unchanged)
+    pub gain: f32,
+    /// Stereo position (-1.0 = left, 1.0 = right)
+    pub pan: f32,
+    /// Pitch offset in semitones
+    pub tuning: f32,
+}
+
+impl Default for ExpressionValues {
+    fn default() -> Self {
+        Self {
+            gain: 1.0,
+            pan: 0.0,
+            tuning: 0.0,
+        }
+    }
+}
+
+impl ExpressionValues {
+    /// Take on one expression event, clamped to its range
+    pub fn apply(&mut self, expression: NoteExpression) {
+        match expression {
+            NoteExpression::Volume(gain) => self.gain = gain.clamp(0.0, MAX_EXPRESSION_GAIN),
+            NoteExpression::Pan(pan) => self.pan = pan.clamp(-1.0, 1.0),
+            NoteExpression::Tuning(tuning) => {
+                self.tuning = tuning.clamp(-MAX_EXPRESSION_TUNING, MAX_EXPRESSION_TUNING);
+            }
+        }
+    }
+}
+
+/// Left and right gains for a pan position (balance law, 1.0 each at center)
+#[must_use]
+pub fn pan_gains(pan: f32) -> (f32, f32) {
+    let pan = pan.clamp(-1.0, 1.0);
+    ((1.0 - pan).min(1.0), (1.0 + pan).min(1.0))
+}
+
+/// Latest note expression values per voice id
+///
+/// # Real-time Safety
+/// - Fixed-size tables, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::note_expression::{ExpressionTable, NoteExpression};
+///
+/// let mut table = ExpressionTable::new();
+/// let values = table.apply(3, NoteExpression::Pan(-0.5)).unwrap();
+/// assert_eq!(values.pan, -0.5);
+/// assert_eq!(values.gain, 1.0);
+/// ```
+pub struct ExpressionTable {
+    /// Voice id of each entry (`None` = free)
+    voice_ids: [Option<i32>; MAX_EXPRESSION_VOICES],
+
+    /// Values of each entry
+    values: [ExpressionValues; MAX_EXPRESSION_VOICES],
+}
+
+impl Default for ExpressionTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExpressionTable {
+    /// Create an empty table
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            voice_ids: [None; MAX_EXPRESSION_VOICES],
+            values: [ExpressionValues::default(); MAX_EXPRESSION_VOICES],
+        }
+    }
+
+    /// Apply an expression to a voice, returning all its values
+    ///
+    /// Returns `None` when the table is full and the voice has no entry yet.
+    pub fn apply(&mut self, voice_id: i32, expression: NoteExpression) -> Option<ExpressionValues> {
+        let index = if let Some(index) = self.find(voice_id) {
+            index
+        } else {
+            let free = self.voice_ids.iter().position(Option::is_none)?;
+            self.voice_ids[free] = Some(voice_id);
+            self.values[free] = ExpressionValues::default();
+            free
+        };
+        self.values[index].apply(expression);
+        Some(self.values[index])
+    }
+
+    /// Values of a voice, if the host has sent it any expression
+    #[must_use]
+    pub fn get(&self, voice_id: i32) -> Option<ExpressionValues> {
+        self.find(voice_id).map(|index| self.values[index])
+    }
+
+    /// Forget a voice (call when it ends, or a new note takes its id)
+    pub fn remove(&mut self, voice_id: i32) {
+        if let Some(index) = self.find(voice_id) {
+            self.voice_ids[index] = None;
+        }
+    }
+
+    /// Forget every voice
+    pub fn reset(&mut self) {
+        self.voice_ids = [None; MAX_EXPRESSION_VOICES];
+    }
+
+    fn find(&self, voice_id: i32) -> Option<usize> {
+        self.voice_ids.iter().position(|&id| id == Some(voice_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pan_gains_keep_center_at_full_level() {
+        assert_eq!(pan_gains(0.0), (1.0, 1.0));
+        assert_eq!(pan_gains(-1.0), (1.0, 0.0));
+        assert_eq!(pan_gains(1.0), (0.0, 1.0));
+        assert_eq!(pan_gains(0.5), (0.5, 1.0));
+        assert_eq!(pan_gains(2.0), (0.0, 1.0));
+    }
+
+    #[test]
+    fn test_expressions_clamp_to_range() {
+        let mut values = ExpressionValues::default();
+        values.apply(NoteExpression::Volume(10.0));
+        values.apply(NoteExpression::Pan(-3.0));
+        values.apply(NoteExpression::Tuning(-500.0));
+        assert_eq!(
+            values,
+            ExpressionValues {
+                gain: MAX_EXPRESSION_GAIN,
+                pan: -1.0,
+                tuning: -MAX_EXPRESSION_TUNING,
+            }
+        );
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // Values are stored as sent
+    fn test_table_keeps_values_per_voice() {
+        let mut table = ExpressionTable::new();
+        table.apply(1, NoteExpression::Tuning(0.5));
+        table.apply(2, NoteExpression::Volume(0.25));
+        let values = table.apply(1, NoteExpression::Pan(1.0)).unwrap();
+        assert_eq!(values.tuning, 0.5);
+        assert_eq!(values.pan, 1.0);
+        assert_eq!(table.get(2).map(|values| values.gain), Some(0.25));
+
+        // A new note with the same id starts from neutral
+        table.remove(1);
+        assert_eq!(table.get(1), None);
+        let values = table.apply(1, NoteExpression::Volume(0.5)).unwrap();
+        assert_eq!(values.tuning, 0.0);
+    }
+
+    #[test]
+    fn test_full_table_drops_new_voices() {
+        let mut table = ExpressionTable::new();
+        for voice_id in (0..).take(MAX_EXPRESSION_VOICES) {
+            assert!(table.apply(voice_id, NoteExpression::Pan(0.1)).is_some());
+        }
+        assert!(table.apply(-1, NoteExpression::Pan(0.1)).is_none());
+        assert!(table.apply(0, NoteExpression::Pan(0.2)).is_some());
+    }
+}
@@ -0,0 +1,178 @@
+//! Octaver for Naughty and Tender
+//!
+//! Layers every note with the octave and/or the fifth above it, each on a voice
+//! of its own at a set fraction of the note's velocity. The extra notes live and
+//! die with the note that started them: releasing it releases them too, even if
+//! the levels changed in the meantime.
+//!
+//! It sits after chord memory, so every note of a chord gets stacked. A stacked
+//! note that another held note is also sounding keeps playing until the last of
+//! them is released, the same as notes shared between chords.
+//!
+//! # References
+//! - Organ couplers and the 2⅔' (twelfth) drawbar: octave and fifth reinforcement
+//! - Boss OC-2 / EHX POG: octave layers mixed in at independent levels
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use crate::key_notes::KeyNotes;
+
+/// Most notes one note can sound (itself, the octave and the fifth)
+pub const MAX_STACK_NOTES: usize = 3;
+
+/// Notes to start for one note with their velocities (unused entries are `None`)
+pub type StackNotes = [Option<(u8, f32)>; MAX_STACK_NOTES];
+
+/// Notes to release for one note (unused entries are `None`)
+pub type StackReleases = [Option<u8>; MAX_STACK_NOTES];
+
+/// Semitones to the octave above
+const OCTAVE: u8 = 12;
+
+/// Semitones to the fifth above
+const FIFTH: u8 = 7;
+
+/// Stacks notes with their octave and fifth and tracks their release
+///
+/// # Real-time Safety
+/// - Fixed-size tables, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::octaver::Octaver;
+///
+/// let mut octaver = Octaver::new();
+/// octaver.set_levels(0.5, 0.0);
+///
+/// let notes: Vec<(u8, f32)> = octaver.note_on(60, 0.8).into_iter().flatten().collect();
+/// assert_eq!(notes, vec![(60, 0.8), (72, 0.4)]);
+/// ```
+pub struct Octaver {
+    /// Velocity of the octave above, relative to the note (0 = off)
+    octave_level: f32,
+
+    /// Velocity of the fifth above, relative to the note (0 = off)
+    fifth_level: f32,
+
+    /// Notes started by each sounding note
+    sounding: KeyNotes<MAX_STACK_NOTES>,
+}
+
+impl Default for Octaver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Octaver {
+    /// Create an octaver that adds nothing
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            octave_level: 0.0,
+            fifth_level: 0.0,
+            sounding: KeyNotes::new(),
+        }
+    }
+
+    /// Set the octave and fifth levels (0.0 - 1.0 of the note's velocity, 0 = off)
+    pub fn set_levels(&mut self, octave_level: f32, fifth_level: f32) {
+        self.octave_level = octave_level.clamp(0.0, 1.0);
+        self.fifth_level = fifth_level.clamp(0.0, 1.0);
+    }
+
+    /// Handle a note-on, returning the notes to start
+    pub fn note_on(&mut self, note: u8, velocity: f32) -> StackNotes {
+        let mut notes = [None; MAX_STACK_NOTES];
+        notes[0] = Some((note, velocity));
+
+        let mut count = 1;
+        for (interval, level) in [(OCTAVE, self.octave_level), (FIFTH, self.fifth_level)] {
+            let stacked = note + interval;
+            if level > 0.0 && stacked < 128 {
+                notes[count] = Some((stacked, velocity * level));
+                count += 1;
+            }
+        }
+
+        self.sounding
+            .start(note, notes.map(|entry| entry.map(|(note, _)| note)));
+        notes
+    }
+
+    /// Handle a note-off, returning the notes to release
+    ///
+    /// Notes still sounding under another note are left playing.
+    pub fn note_off(&mut self, note: u8) -> StackReleases {
+        self.sounding.release(note)
+    }
+
+    /// Forget every sounding note
+    pub fn reset(&mut self) {
+        self.sounding.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started(notes: StackNotes) -> Vec<(u8, f32)> {
+        notes.into_iter().flatten().collect()
+    }
+
+    fn released(notes: StackReleases) -> Vec<u8> {
+        notes.into_iter().flatten().collect()
+    }
+
+    #[test]
+    fn test_off_plays_single_notes() {
+        let mut octaver = Octaver::new();
+        assert_eq!(started(octaver.note_on(60, 1.0)), vec![(60, 1.0)]);
+        assert_eq!(released(octaver.note_off(60)), vec![60]);
+    }
+
+    #[test]
+    fn test_stacks_octave_and_fifth_at_their_levels() {
+        let mut octaver = Octaver::new();
+        octaver.set_levels(0.5, 0.25);
+        assert_eq!(
+            started(octaver.note_on(48, 0.8)),
+            vec![(48, 0.8), (60, 0.4), (55, 0.2)]
+        );
+
+        // Released together, even after the levels change
+        octaver.set_levels(0.0, 0.0);
+        assert_eq!(released(octaver.note_off(48)), vec![48, 60, 55]);
+    }
+
+    #[test]
+    fn test_drops_notes_above_range() {
+        let mut octaver = Octaver::new();
+        octaver.set_levels(1.0, 1.0);
+        assert_eq!(
+            started(octaver.note_on(120, 1.0)),
+            vec![(120, 1.0), (127, 1.0)]
+        );
+        assert_eq!(released(octaver.note_off(120)), vec![120, 127]);
+    }
+
+    #[test]
+    fn test_shared_notes_release_with_last_note() {
+        let mut octaver = Octaver::new();
+        octaver.set_levels(1.0, 0.0);
+
+        // C3's octave is the C4 that's also played
+        octaver.note_on(48, 1.0);
+        octaver.note_on(60, 1.0);
+        assert_eq!(released(octaver.note_off(48)), vec![48]);
+        assert_eq!(released(octaver.note_off(60)), vec![60, 72]);
+    }
+
+    #[test]
+    fn test_untracked_note_releases_itself() {
+        let mut octaver = Octaver::new();
+        octaver.set_levels(1.0, 1.0);
+        assert_eq!(released(octaver.note_off(64)), vec![64]);
+    }
+}
@@ -5,7 +5,67 @@
 
 use nih_plug::prelude::*;
 use nih_plug_egui::EguiState;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
+
+use crate::additive::NUM_PARTIALS;
+use crate::channel_filter::NUM_CHANNELS;
+use crate::chord::{ChordIntervals, NUM_CHORD_INTERVALS};
+use crate::envelope::FilterEnvelopeSettings;
+use crate::eq::EqSettings;
+use crate::expression::ExpressionCurve;
+use crate::granular::{GrainWindow, GranularSettings};
+use crate::humanize::MAX_TIMING_MS;
+use crate::input::InputMode;
+use crate::layers::LayerMode;
+use crate::limiter::DEFAULT_CEILING_DB;
+use crate::master_fx::{DRIVE_SLOT, EQ_SLOT, NUM_SLOTS, PHASER_SLOT, SLOT_NAMES};
+use crate::modulation::{
+    ModDestination, ModMatrix, ModOffsets, ModSlot, ModSource, DEFAULT_CONTROL_DIVISOR,
+    NUM_MOD_SLOTS,
+};
+use crate::morph::{MorphPair, MorphSlot, Snapshot};
+use crate::oscillators::SineMode;
+use crate::pattern::{PatternNotes, MAX_SWING, STRAIGHT_SWING};
+use crate::pitch_bend::{DEFAULT_BEND_RANGE, MAX_BEND_RANGE};
+use crate::poly_mod::{PolyOffsets, PolyTarget};
+use crate::programs::{ProgramMap, ProgramTransition};
+use crate::scale::{Scale, ScaleMask, SnapMode, NUM_DEGREES, ROOT_NAMES};
+use crate::sampler::{Interpolation, DEFAULT_ROOT_NOTE};
+use crate::sequencer::{Euclid, Step, NUM_STEPS};
+use crate::strum::{StrumDirection, MAX_STRUM_MS};
+use crate::theme::ThemeKind;
+use crate::voice::{
+    note_name, ChokePolicy, ChokeRange, GlideCurve, GlideMode, Glissando, SameNotePolicy,
+    LatchGroups, VoiceAllocation, VoiceStealing, MAX_CHOKE_GROUP, NUM_CHOKE_RANGES,
+    STANDARD_A4_HZ,
+};
+use crate::width::DEFAULT_MIN_CORRELATION;
+use shared_core::effects::waveshaper::{ShaperCurve, TonePosition, WaveshaperSettings};
+use shared_core::mid_side::{MAX_WIDTH, UNITY_WIDTH};
+use shared_core::oversampling::OversamplingFactor;
+use shared_core::svf::{FilterPrecision, SvfMode};
+use shared_core::tempo::NoteDivision;
+
+/// Where the waveshaper runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrivePlacement {
+    Off,
+    /// One shaper per voice, directly after the sound source
+    Voice,
+    /// A single shaper on the master bus
+    Master,
+}
+
+/// Where chord mode takes its intervals from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChordMode {
+    /// Keys play single notes
+    Off,
+    /// Intervals from the chord note parameters
+    Intervals,
+    /// The chord last learned from held keys
+    Learned,
+}
 
 /// All plugin parameters
 #[derive(Params)]
@@ -14,10 +74,64 @@ pub struct NaughtyAndTenderParams {
     #[persist = "editor-state"]
     pub editor_state: Arc<EguiState>,
 
+    /// Editor theme preset name
+    #[persist = "editor-theme"]
+    pub editor_theme: RwLock<String>,
+
+    /// MIDI CC mappings as (controller index, parameter ID) pairs
+    ///
+    /// Plain CCs keep their own numbers as indices (see `cc_map::Controller`).
+    #[persist = "cc-mappings"]
+    pub cc_mappings: RwLock<Vec<(u16, String)>>,
+
+    /// Controller indices whose mappings use soft take-over
+    #[persist = "cc-takeover"]
+    pub cc_takeover: RwLock<Vec<u16>>,
+
+    /// Program change assignments as (bank, program, preset name) triples
+    #[persist = "program-map"]
+    pub program_map: RwLock<Vec<(u16, u8, String)>>,
+
+    /// WAV file played by the sampler engine (empty = none)
+    #[persist = "sample-path"]
+    pub sample_path: RwLock<String>,
+
+    /// Morph snapshot A as (parameter ID, normalized value) pairs (empty = not stored)
+    #[persist = "morph-a"]
+    pub morph_a: RwLock<Snapshot>,
+
+    /// Morph snapshot B
+    #[persist = "morph-b"]
+    pub morph_b: RwLock<Snapshot>,
+
+    /// Built-in pattern as (start step, note, length in steps) triples
+    #[persist = "pattern-notes"]
+    pub pattern_notes: RwLock<PatternNotes>,
+
+    /// Names this instance's autosave snapshot file (0 = not claimed yet)
+    #[persist = "autosave-id"]
+    pub autosave_id: RwLock<u64>,
+
     /// Master gain control (in dB)
     #[id = "gain"]
     pub gain: FloatParam,
 
+    /// Pitch reference: frequency of A4 in Hz
+    #[id = "master_tune"]
+    pub master_tune_hz: FloatParam,
+
+    /// Fine tuning on top of the pitch reference, in cents
+    #[id = "fine_tune"]
+    pub fine_tune_cents: FloatParam,
+
+    /// Pitch change at full pitch bend, in semitones
+    #[id = "bend_range"]
+    pub bend_range: FloatParam,
+
+    /// Host bypass: fade the synth out and pass the input through
+    #[id = "bypass"]
+    pub bypass: BoolParam,
+
     /// Number of active voices (read-only display parameter)
     #[id = "voices"]
     pub voice_count: IntParam,
@@ -27,15 +141,148 @@ pub struct NaughtyAndTenderParams {
     #[id = "waveform"]
     pub waveform: IntParam,
 
+    /// How both layers compute the sine (see `SineMode::NAMES`)
+    #[id = "sine_quality"]
+    pub sine_quality: IntParam,
+
+    /// Glide (portamento) time in milliseconds, 0 = off
+    #[id = "glide"]
+    pub glide_ms: FloatParam,
+
+    /// Sync glide time to host tempo
+    #[id = "glide_sync"]
+    pub glide_sync: BoolParam,
+
+    /// Glide time as a note division (used when synced)
+    #[id = "glide_div"]
+    pub glide_division: IntParam,
+
+    /// Glide time per glide or per octave (see `GlideMode::NAMES`)
+    #[id = "glide_mode"]
+    pub glide_mode: IntParam,
+
+    /// Shape of the glide (see `GlideCurve::NAMES`)
+    #[id = "glide_curve"]
+    pub glide_curve: IntParam,
+
+    /// Step through notes while gliding (see `Glissando::NAMES`)
+    #[id = "glissando"]
+    pub glissando: IntParam,
+
+    /// Keep oscillator phase running across notes instead of restarting it
+    #[id = "free_phase"]
+    pub free_running_phase: BoolParam,
+
+    /// Oscillator phase each note starts at, in degrees
+    #[id = "start_phase"]
+    pub start_phase: FloatParam,
+
+    /// Start each note at a random oscillator phase instead
+    #[id = "rand_phase"]
+    pub random_phase: BoolParam,
+
+    // Engine parameters
+    /// Voice engine (0=Oscillator, 1=Karplus-Strong, 2=Sample, 3=Additive)
+    #[id = "engine"]
+    pub engine: IntParam,
+
+    /// Karplus-Strong excitation source (0=Noise, 1=Oscillator)
+    #[id = "ks_excitation"]
+    pub string_excitation: IntParam,
+
+    /// Karplus-Strong loop damping (0.0 - 1.0)
+    #[id = "ks_damping"]
+    pub string_damping: FloatParam,
+
+    /// Karplus-Strong decay time (-60 dB) in milliseconds
+    #[id = "ks_decay"]
+    pub string_decay_ms: FloatParam,
+
+    /// Note at which the sample plays at its recorded pitch
+    #[id = "smp_root"]
+    pub sample_root: IntParam,
+
+    /// Sample playback start (fraction of the sample length)
+    #[id = "smp_start"]
+    pub sample_start: FloatParam,
+
+    /// Loop the sample between the loop points (otherwise one-shot)
+    #[id = "smp_loop"]
+    pub sample_loop: BoolParam,
+
+    /// Loop start (fraction of the sample length)
+    #[id = "smp_loop_start"]
+    pub sample_loop_start: FloatParam,
+
+    /// Loop end (fraction of the sample length)
+    #[id = "smp_loop_end"]
+    pub sample_loop_end: FloatParam,
+
+    /// Sample interpolation (0=Linear, 1=Cubic)
+    #[id = "smp_interp"]
+    pub sample_interpolation: IntParam,
+
+    /// Play the sample as a cloud of grains
+    #[id = "grain_on"]
+    pub granular: BoolParam,
+
+    /// Grain length in milliseconds
+    #[id = "grain_size"]
+    pub grain_size_ms: FloatParam,
+
+    /// Grains started per second
+    #[id = "grain_density"]
+    pub grain_density: FloatParam,
+
+    /// Grain read position (fraction of the sample length)
+    #[id = "grain_pos"]
+    pub grain_position: FloatParam,
+
+    /// Random grain position scatter (0.0 - 1.0)
+    #[id = "grain_jitter"]
+    pub grain_jitter: FloatParam,
+
+    /// Random grain detune in semitones
+    #[id = "grain_spread"]
+    pub grain_pitch_spread: FloatParam,
+
+    /// Grain window (0=Hann, 1=Triangle, 2=Trapezoid)
+    #[id = "grain_window"]
+    pub grain_window: IntParam,
+
+    /// Level of each additive partial (the harmonic editor's bars)
+    #[nested(array, group = "Partial")]
+    pub partials: [PartialParams; NUM_PARTIALS],
+
+    /// Additive spectral tilt in dB per octave
+    #[id = "add_tilt"]
+    pub additive_tilt: FloatParam,
+
     // ADSR Envelope parameters
     /// Attack time in milliseconds
     #[id = "attack"]
     pub attack_ms: FloatParam,
 
+    /// Sync attack time to host tempo
+    #[id = "attack_sync"]
+    pub attack_sync: BoolParam,
+
+    /// Attack time as a note division (used when synced)
+    #[id = "attack_div"]
+    pub attack_division: IntParam,
+
     /// Decay time in milliseconds
     #[id = "decay"]
     pub decay_ms: FloatParam,
 
+    /// Sync decay time to host tempo
+    #[id = "decay_sync"]
+    pub decay_sync: BoolParam,
+
+    /// Decay time as a note division (used when synced)
+    #[id = "decay_div"]
+    pub decay_division: IntParam,
+
     /// Sustain level (0.0 - 1.0)
     #[id = "sustain"]
     pub sustain_level: FloatParam,
@@ -43,75 +290,1087 @@ pub struct NaughtyAndTenderParams {
     /// Release time in milliseconds
     #[id = "release"]
     pub release_ms: FloatParam,
+
+    /// Sync release time to host tempo
+    #[id = "release_sync"]
+    pub release_sync: BoolParam,
+
+    /// Release time as a note division (used when synced)
+    #[id = "release_div"]
+    pub release_division: IntParam,
+
+    /// How much note-off velocity shortens (fast) or lengthens (slow) the release
+    #[id = "release_vel"]
+    pub release_velocity: FloatParam,
+
+    /// Sounding notes keep the envelope times they started with
+    #[id = "latch_env"]
+    pub latch_envelope: BoolParam,
+
+    // Master effect chain
+    /// Bypass the whole master chain
+    #[id = "fx_bypass"]
+    pub fx_bypass: BoolParam,
+
+    /// Remove DC offset from the master bus (after the chain, not bypassed with it)
+    #[id = "dc_block"]
+    pub dc_blocker: BoolParam,
+
+    /// Master stereo width after the effects (1.0 = as is, 0.0 = mono)
+    #[id = "width"]
+    pub stereo_width: FloatParam,
+
+    /// Turn the master's side down when its correlation falls below the floor
+    #[id = "width_lim"]
+    pub width_limit: BoolParam,
+
+    /// Lowest stereo correlation the width limiter allows (+1 = mono)
+    #[id = "min_corr"]
+    pub min_correlation: FloatParam,
+
+    /// Effect in the first chain position (0=Drive, 1=Phaser, 2=EQ)
+    #[id = "fx_slot_1"]
+    pub fx_slot_1: IntParam,
+
+    /// Effect in the second chain position
+    #[id = "fx_slot_2"]
+    pub fx_slot_2: IntParam,
+
+    /// Effect in the third chain position
+    #[id = "fx_slot_3"]
+    pub fx_slot_3: IntParam,
+
+    // Phaser parameters
+    /// Phaser insert on/off
+    #[id = "phaser_on"]
+    pub phaser_enabled: BoolParam,
+
+    /// Phaser LFO rate in Hz
+    #[id = "phaser_rate"]
+    pub phaser_rate_hz: FloatParam,
+
+    /// Phaser sweep depth (0.0 - 1.0)
+    #[id = "phaser_depth"]
+    pub phaser_depth: FloatParam,
+
+    /// Phaser feedback (-0.95 - 0.95)
+    #[id = "phaser_fb"]
+    pub phaser_feedback: FloatParam,
+
+    /// Number of phaser all-pass stages (4 - 8)
+    #[id = "phaser_stages"]
+    pub phaser_stages: IntParam,
+
+    /// Phaser dry/wet mix (0.0 - 1.0)
+    #[id = "phaser_mix"]
+    pub phaser_mix: FloatParam,
+
+    // Drive (waveshaper) parameters
+    /// Drive placement (0=Off, 1=Per Voice, 2=Master)
+    #[id = "drive_place"]
+    pub drive_placement: IntParam,
+
+    /// Drive curve (0=Tanh, 1=Hard Clip, 2=Foldback, 3=Diode)
+    #[id = "drive_curve"]
+    pub drive_curve: IntParam,
+
+    /// Drive amount in dB
+    #[id = "drive_db"]
+    pub drive_db: FloatParam,
+
+    /// Drive tone filter cutoff in Hz
+    #[id = "drive_tone"]
+    pub drive_tone_hz: FloatParam,
+
+    /// Drive tone filter position (0=Pre, 1=Post)
+    #[id = "drive_tone_pos"]
+    pub drive_tone_position: IntParam,
+
+    /// Drive dry/wet mix (0.0 - 1.0)
+    #[id = "drive_mix"]
+    pub drive_mix: FloatParam,
+
+    /// Drive oversampling (0=Off, 1=2x, 2=4x)
+    #[id = "drive_os"]
+    pub drive_oversampling: IntParam,
+
+    // Master EQ parameters
+    /// Master EQ on/off
+    #[id = "eq_on"]
+    pub eq_enabled: BoolParam,
+
+    /// Master EQ dry/wet mix (0.0 - 1.0)
+    #[id = "eq_mix"]
+    pub eq_mix: FloatParam,
+
+    /// Low shelf frequency in Hz
+    #[id = "eq_low_freq"]
+    pub eq_low_freq: FloatParam,
+
+    /// Low shelf gain in dB
+    #[id = "eq_low_gain"]
+    pub eq_low_gain_db: FloatParam,
+
+    /// Mid band center frequency in Hz
+    #[id = "eq_mid_freq"]
+    pub eq_mid_freq: FloatParam,
+
+    /// Mid band gain in dB
+    #[id = "eq_mid_gain"]
+    pub eq_mid_gain_db: FloatParam,
+
+    /// Mid band Q
+    #[id = "eq_mid_q"]
+    pub eq_mid_q: FloatParam,
+
+    /// High shelf frequency in Hz
+    #[id = "eq_high_freq"]
+    pub eq_high_freq: FloatParam,
+
+    /// High shelf gain in dB
+    #[id = "eq_high_gain"]
+    pub eq_high_gain_db: FloatParam,
+
+    // Pump (tempo-synced ducking) parameters
+    /// Master pump on/off
+    #[id = "pump_on"]
+    pub pump_enabled: BoolParam,
+
+    /// How far the output ducks on each division (0.0 - 1.0)
+    #[id = "pump_depth"]
+    pub pump_depth: FloatParam,
+
+    /// Recovery shape (0.0 = linear swell, 1.0 = quick snap back)
+    #[id = "pump_shape"]
+    pub pump_shape: FloatParam,
+
+    /// Pump cycle length as a note division
+    #[id = "pump_div"]
+    pub pump_division: IntParam,
+
+    // Voice filter parameters
+    /// Per-voice filter on/off
+    #[id = "filter_on"]
+    pub filter_enabled: BoolParam,
+
+    /// Filter response (0=Low Pass, 1=High Pass, 2=Band Pass)
+    #[id = "filter_mode"]
+    pub filter_mode: IntParam,
+
+    /// Filter cutoff in Hz (before modulation)
+    #[id = "filter_cutoff"]
+    pub filter_cutoff_hz: FloatParam,
+
+    /// Filter resonance (0.0 - 1.0)
+    #[id = "filter_res"]
+    pub filter_resonance: FloatParam,
+
+    /// Filter envelope
+    #[nested(group = "Filter Envelope")]
+    pub filter_env: FilterEnvParams,
+
+    /// Filter arithmetic for both layers and the input (0=Standard, 1=High)
+    #[id = "filter_prec"]
+    pub filter_precision: IntParam,
+
+    /// Sounding notes keep the filter settings they started with
+    #[id = "latch_filter"]
+    pub latch_filter: BoolParam,
+
+    /// Per-voice peak limiter after the filter on/off
+    #[id = "vlim_on"]
+    pub voice_limiter: BoolParam,
+
+    /// Per-voice limiter ceiling in dBFS
+    #[id = "vlim_ceiling"]
+    pub voice_limiter_ceiling_db: FloatParam,
+
+    // Random (sample-and-hold) modulation source parameters
+    /// Free-running clock rate in Hz
+    #[id = "rand_rate"]
+    pub rand_rate_hz: FloatParam,
+
+    /// Sync the clock to host tempo
+    #[id = "rand_sync"]
+    pub rand_sync: BoolParam,
+
+    /// Clock rate as a note division (used when synced)
+    #[id = "rand_div"]
+    pub rand_division: IntParam,
+
+    /// Slew between held values in milliseconds
+    #[id = "rand_slew"]
+    pub rand_slew_ms: FloatParam,
+
+    // Step sequencer parameters
+    /// Step length as a note division
+    #[id = "seq_rate"]
+    pub seq_division: IntParam,
+
+    /// Slew between steps in milliseconds
+    #[id = "seq_slew"]
+    pub seq_slew_ms: FloatParam,
+
+    /// Gate the steps with a Euclidean rhythm instead of their own gates
+    #[id = "seq_euclid"]
+    pub seq_euclid: BoolParam,
+
+    /// Euclidean sequence length in steps
+    #[id = "seq_eu_steps"]
+    pub seq_euclid_steps: IntParam,
+
+    /// Euclidean onsets, spread over the steps
+    #[id = "seq_eu_pulses"]
+    pub seq_euclid_pulses: IntParam,
+
+    /// Steps the Euclidean rhythm starts late by
+    #[id = "seq_eu_rot"]
+    pub seq_euclid_rotation: IntParam,
+
+    /// Per-step value and gate
+    #[nested(array, group = "Step")]
+    pub seq_steps: [StepParams; NUM_STEPS],
+
+    // Sidechain envelope follower
+    /// Time to follow a rising sidechain level, in milliseconds
+    #[id = "sc_attack"]
+    pub sidechain_attack_ms: FloatParam,
+
+    /// Time to follow a falling sidechain level, in milliseconds
+    #[id = "sc_release"]
+    pub sidechain_release_ms: FloatParam,
+
+    /// Sidechain input gain in dB
+    #[id = "sc_gain"]
+    pub sidechain_gain_db: FloatParam,
+
+    // External audio input
+    /// What happens to the main input (see `InputMode::NAMES`)
+    #[id = "input_mode"]
+    pub input_mode: IntParam,
+
+    /// Main input gain
+    #[id = "input_gain"]
+    pub input_gain: FloatParam,
+
+    /// Blend from the engine to the input in gated mode (0.0 - 1.0)
+    #[id = "input_mix"]
+    pub input_mix: FloatParam,
+
+    // Modulation matrix
+    /// Source, destination and amount of each routing slot
+    #[nested(array, group = "Mod Slot")]
+    pub mod_slots: [ModSlotParams; NUM_MOD_SLOTS],
+
+    /// Samples between modulation updates (1 = every sample)
+    #[id = "ctrl_rate"]
+    pub control_divisor: IntParam,
+
+    // Chord memory
+    /// Chord mode (0=Off, 1=Intervals, 2=Learned)
+    #[id = "chord_mode"]
+    pub chord_mode: IntParam,
+
+    /// Capture the next chord held on the keyboard
+    #[id = "chord_learn"]
+    pub chord_learn: BoolParam,
+
+    /// Send the notes each key plays (chord and octaver included) to the MIDI output
+    #[id = "midi_out"]
+    pub midi_out: BoolParam,
+
+    /// MIDI channel the synth responds to (0 = omni, 1-16)
+    #[id = "midi_channel"]
+    pub midi_channel: IntParam,
+
+    // MIDI program change
+    /// How sounding notes handle a preset load (see `ProgramTransition::NAMES`)
+    #[id = "pc_transition"]
+    pub program_transition: IntParam,
+
+    // Patch morph
+    /// Position between morph snapshots A and B (0.0 - 1.0)
+    #[id = "morph"]
+    pub morph: FloatParam,
+
+    /// Interval of each extra chord note
+    #[nested(array, group = "Chord Note")]
+    pub chord_notes: [ChordNoteParams; NUM_CHORD_INTERVALS],
+
+    // Strum
+    /// Spread notes that start together across the strum time
+    #[id = "strum"]
+    pub strum: BoolParam,
+
+    /// Time from the first note of a strum to the last
+    #[id = "strum_time"]
+    pub strum_time_ms: FloatParam,
+
+    /// Order the notes of a strum start in (see `StrumDirection::NAMES`)
+    #[id = "strum_dir"]
+    pub strum_direction: IntParam,
+
+    // Octaver
+    /// Layer each note with its octave and fifth on extra voices
+    #[id = "octaver"]
+    pub octaver: BoolParam,
+
+    /// Velocity of the octave above, relative to the note (0 = off)
+    #[id = "oct_octave"]
+    pub octaver_octave_level: FloatParam,
+
+    /// Velocity of the fifth above, relative to the note (0 = off)
+    #[id = "oct_fifth"]
+    pub octaver_fifth_level: FloatParam,
+
+    // Humanization
+    /// Randomize incoming note timing and velocity
+    #[id = "humanize"]
+    pub humanize: BoolParam,
+
+    /// Longest random delay added to a note, in milliseconds
+    #[id = "hum_timing"]
+    pub humanize_timing_ms: FloatParam,
+
+    /// Largest random change to a note's velocity, either way
+    #[id = "hum_velocity"]
+    pub humanize_velocity: FloatParam,
+
+    // Pattern sequencer
+    /// Play the built-in pattern while the host transport runs
+    #[id = "pat_on"]
+    pub pattern: BoolParam,
+
+    /// Pattern length in bars
+    #[id = "pat_bars"]
+    pub pattern_bars: IntParam,
+
+    /// Velocity of every pattern note
+    #[id = "pat_vel"]
+    pub pattern_velocity: FloatParam,
+
+    /// Where the second step of each pair starts (50% = straight)
+    #[id = "pat_swing"]
+    pub pattern_swing: FloatParam,
+
+    // Performance controllers
+    /// How expression (CC 11) sets the output level (see `ExpressionCurve::NAMES`)
+    #[id = "expr_curve"]
+    pub expression_curve: IntParam,
+
+    // Scale quantization
+    /// Snap incoming keys to the scale
+    #[id = "scale_on"]
+    pub scale_quantize: BoolParam,
+
+    /// Root of the scale (see `ROOT_NAMES`)
+    #[id = "scale_root"]
+    pub scale_root: IntParam,
+
+    /// Scale keys snap to (see `Scale::NAMES`)
+    #[id = "scale"]
+    pub scale: IntParam,
+
+    /// Which way out-of-scale keys move (see `SnapMode::NAMES`)
+    #[id = "scale_snap"]
+    pub scale_snap: IntParam,
+
+    /// Degrees of the custom scale, from the root up
+    #[nested(array, group = "Scale Degree")]
+    pub scale_degrees: [ScaleDegreeParams; NUM_DEGREES],
+
+    // Voice safeguards
+    /// Force-release notes held longer than this, in seconds (0 = off)
+    #[id = "stuck_timeout"]
+    pub stuck_timeout_s: FloatParam,
+
+    /// Never steal the voice playing the lowest held note
+    #[id = "bass_reserve"]
+    pub bass_reserve: BoolParam,
+
+    /// How new notes pick a voice (see `VoiceAllocation::NAMES`)
+    #[id = "voice_alloc"]
+    pub voice_allocation: IntParam,
+
+    /// Which voice a note takes when all are busy (see `VoiceStealing::NAMES`)
+    #[id = "voice_steal"]
+    pub voice_stealing: IntParam,
+
+    /// What repeating a sounding note does (see `SameNotePolicy::NAMES`)
+    #[id = "same_note"]
+    pub same_note: IntParam,
+
+    /// How a note cuts off the rest of its choke group (see `ChokePolicy::NAMES`)
+    #[id = "choke_policy"]
+    pub choke_policy: IntParam,
+
+    /// Note ranges assigned to choke groups
+    #[nested(array, group = "Choke Range")]
+    pub choke_ranges: [ChokeRangeParams; NUM_CHOKE_RANGES],
+
+    // Layers
+    /// How notes are shared between layers (see `LayerMode::NAMES`)
+    #[id = "layer_mode"]
+    pub layer_mode: IntParam,
+
+    /// First note played by layer B in split mode
+    #[id = "split_note"]
+    pub split_note: IntParam,
+
+    /// Lowest velocity played by layer B in velocity mode
+    #[id = "vel_split"]
+    pub velocity_split: FloatParam,
+
+    /// Layer A output level (0.0 - 1.0)
+    #[id = "layer_a_level"]
+    pub layer_a_level: FloatParam,
+
+    /// Layer B's own oscillator, envelope, filter and level
+    #[nested(id_prefix = "b", group = "Layer B")]
+    pub layer_b: LayerParams,
+
+    /// Last chord learned from held keys (written by the audio thread)
+    #[persist = "learned-chord"]
+    pub learned_chord: RwLock<ChordIntervals>,
 }
 
-impl Default for NaughtyAndTenderParams {
-    fn default() -> Self {
-        Self {
-            editor_state: EguiState::from_size(600, 500),
+/// Value and gate of one sequencer step
+#[derive(Params)]
+pub struct StepParams {
+    /// Step value (0.0 - 1.0)
+    #[id = "value"]
+    pub value: FloatParam,
 
-            gain: FloatParam::new(
-                "Gain",
-                util::db_to_gain(0.0),
-                FloatRange::Skewed {
-                    min: util::db_to_gain(-30.0),
-                    max: util::db_to_gain(6.0),
-                    factor: FloatRange::gain_skew_factor(-30.0, 6.0),
-                },
+    /// Step gate on/off
+    #[id = "gate"]
+    pub gate: BoolParam,
+}
+
+impl StepParams {
+    fn new(index: usize) -> Self {
+        let number = index + 1;
+        Self {
+            value: FloatParam::new(
+                format!("Step {number} Value"),
+                0.0,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
             )
-            .with_smoother(SmoothingStyle::Logarithmic(50.0))
-            .with_unit(" dB")
-            .with_value_to_string(formatters::v2s_f32_gain_to_db(2))
-            .with_string_to_value(formatters::s2v_f32_gain_to_db()),
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+            gate: BoolParam::new(format!("Step {number} Gate"), true),
+        }
+    }
+}
 
-            voice_count: IntParam::new("Voices", 0, IntRange::Linear { min: 0, max: 16 })
-                .with_value_to_string(Arc::new(|value| format!("{value}")))
-                .non_automatable(),
+/// Level of one additive partial
+#[derive(Params)]
+pub struct PartialParams {
+    /// Partial level (0.0 - 1.0)
+    #[id = "partial"]
+    pub level: FloatParam,
+}
 
-            // Oscillator parameters
-            waveform: IntParam::new(
-                "Waveform",
-                0, // Default to Sine
-                IntRange::Linear { min: 0, max: 3 },
+impl PartialParams {
+    fn new(index: usize) -> Self {
+        let number = index + 1;
+        // Default spectrum: a sawtooth's 1/n rolloff
+        #[allow(clippy::cast_precision_loss)] // At most 32
+        let default = 1.0 / number as f32;
+        Self {
+            level: FloatParam::new(
+                format!("Partial {number}"),
+                default,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
             )
-            .with_value_to_string(Arc::new(|value| {
-                match value {
-                    0 => "Sine".to_string(),
-                    1 => "Sawtooth".to_string(),
-                    2 => "Square".to_string(),
-                    3 => "Triangle".to_string(),
-                    _ => "Unknown".to_string(),
-                }
-            }))
-            .with_string_to_value(Arc::new(|string| {
-                match string {
-                    "Sine" => Some(0),
-                    "Sawtooth" => Some(1),
-                    "Square" => Some(2),
-                    "Triangle" => Some(3),
-                    _ => None,
-                }
-            })),
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+        }
+    }
+}
 
-            // ADSR Envelope parameters
-            attack_ms: FloatParam::new(
-                "Attack",
-                10.0,
+/// Sound-shaping parameters of an additional layer
+///
+/// Glide, drive, modulation and the random source are shared with layer A.
+#[derive(Params)]
+pub struct LayerParams {
+    /// Waveform type (0=Sine, 1=Sawtooth, 2=Square, 3=Triangle)
+    #[id = "waveform"]
+    pub waveform: IntParam,
+
+    /// Layer output level (0.0 - 1.0)
+    #[id = "level"]
+    pub level: FloatParam,
+
+    /// Attack time in milliseconds
+    #[id = "attack"]
+    pub attack_ms: FloatParam,
+
+    /// Decay time in milliseconds
+    #[id = "decay"]
+    pub decay_ms: FloatParam,
+
+    /// Sustain level (0.0 - 1.0)
+    #[id = "sustain"]
+    pub sustain_level: FloatParam,
+
+    /// Release time in milliseconds
+    #[id = "release"]
+    pub release_ms: FloatParam,
+
+    /// Per-voice filter on/off
+    #[id = "filter_on"]
+    pub filter_enabled: BoolParam,
+
+    /// Filter response (0=Low Pass, 1=High Pass, 2=Band Pass)
+    #[id = "filter_mode"]
+    pub filter_mode: IntParam,
+
+    /// Filter cutoff in Hz (before modulation)
+    #[id = "filter_cutoff"]
+    pub filter_cutoff_hz: FloatParam,
+
+    /// Filter resonance (0.0 - 1.0)
+    #[id = "filter_res"]
+    pub filter_resonance: FloatParam,
+
+    /// Filter envelope
+    #[nested(group = "Filter Envelope")]
+    pub filter_env: FilterEnvParams,
+}
+
+impl LayerParams {
+    fn new(name: &str) -> Self {
+        Self {
+            waveform: choice_param(
+                &format!("{name} Waveform"),
+                1, // Default to Sawtooth, to contrast with layer A's sine
+                &["Sine", "Sawtooth", "Square", "Triangle"],
+            ),
+            level: unit_param(&format!("{name} Level"), 1.0),
+            attack_ms: time_ms_param(&format!("{name} Attack"), 10.0, 2000.0),
+            decay_ms: time_ms_param(&format!("{name} Decay"), 100.0, 2000.0),
+            sustain_level: unit_param(&format!("{name} Sustain"), 0.7),
+            release_ms: time_ms_param(&format!("{name} Release"), 300.0, 5000.0),
+            filter_enabled: BoolParam::new(format!("{name} Filter"), false),
+            filter_mode: choice_param(
+                &format!("{name} Filter Mode"),
+                0,
+                &["Low Pass", "High Pass", "Band Pass"],
+            ),
+            filter_cutoff_hz: FloatParam::new(
+                format!("{name} Filter Cutoff"),
+                1000.0,
                 FloatRange::Skewed {
-                    min: 0.1,
-                    max: 2000.0,
+                    min: 20.0,
+                    max: 20000.0,
                     factor: FloatRange::skew_factor(-2.0),
                 },
             )
-            .with_smoother(SmoothingStyle::Linear(10.0))
-            .with_unit(" ms")
-            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            .with_smoother(SmoothingStyle::Logarithmic(20.0))
+            .with_value_to_string(formatters::v2s_f32_hz_then_khz(0))
+            .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
+            filter_resonance: unit_param(&format!("{name} Filter Resonance"), 0.0),
+            filter_env: FilterEnvParams::new(&format!("{name} Filter Env")),
+        }
+    }
 
-            decay_ms: FloatParam::new(
-                "Decay",
-                100.0,
-                FloatRange::Skewed {
-                    min: 0.1,
+    /// Current filter response
+    pub fn filter_mode(&self) -> SvfMode {
+        svf_mode(self.filter_mode.value())
+    }
+}
+
+/// ADSR and depth of a layer's filter envelope
+#[derive(Params)]
+pub struct FilterEnvParams {
+    /// Attack time in milliseconds
+    #[id = "fenv_attack"]
+    pub attack_ms: FloatParam,
+
+    /// Decay time in milliseconds
+    #[id = "fenv_decay"]
+    pub decay_ms: FloatParam,
+
+    /// Sustain level (0.0 - 1.0)
+    #[id = "fenv_sustain"]
+    pub sustain_level: FloatParam,
+
+    /// Release time in milliseconds
+    #[id = "fenv_release"]
+    pub release_ms: FloatParam,
+
+    /// Cutoff shift at the envelope's peak, in octaves (-8 to 8)
+    #[id = "fenv_amount"]
+    pub amount: FloatParam,
+
+    /// Flip the envelope to sweep the other way
+    #[id = "fenv_invert"]
+    pub invert: BoolParam,
+
+    /// How much velocity scales the amount (0.0 - 1.0)
+    #[id = "fenv_vel"]
+    pub velocity: FloatParam,
+}
+
+impl FilterEnvParams {
+    fn new(name: &str) -> Self {
+        Self {
+            attack_ms: time_ms_param(&format!("{name} Attack"), 10.0, 2000.0),
+            decay_ms: time_ms_param(&format!("{name} Decay"), 300.0, 5000.0),
+            sustain_level: unit_param(&format!("{name} Sustain"), 0.0),
+            release_ms: time_ms_param(&format!("{name} Release"), 300.0, 5000.0),
+            amount: FloatParam::new(
+                format!("{name} Amount"),
+                0.0,
+                FloatRange::Linear {
+                    min: -8.0,
+                    max: 8.0,
+                },
+            )
+            .with_unit(" oct")
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+            invert: BoolParam::new(format!("{name} Invert"), false),
+            velocity: unit_param(&format!("{name} Velocity"), 0.0),
+        }
+    }
+
+    /// Current filter envelope settings (invert folded into the amount)
+    pub fn settings(&self) -> FilterEnvelopeSettings {
+        let amount = self.amount.value();
+        FilterEnvelopeSettings {
+            attack_ms: self.attack_ms.value(),
+            decay_ms: self.decay_ms.value(),
+            sustain_level: self.sustain_level.value(),
+            release_ms: self.release_ms.value(),
+            amount_octaves: if self.invert.value() { -amount } else { amount },
+            velocity_amount: self.velocity.value(),
+        }
+    }
+}
+
+/// One degree of the custom scale
+#[derive(Params)]
+pub struct ScaleDegreeParams {
+    /// Whether the degree is in the scale
+    #[id = "degree"]
+    pub enabled: BoolParam,
+}
+
+impl ScaleDegreeParams {
+    fn new(index: usize) -> Self {
+        // Default custom scale: major
+        let major = Scale::Major.mask(0);
+        Self {
+            enabled: BoolParam::new(
+                format!("Scale Degree {}", ROOT_NAMES[index]),
+                major & (1 << index) != 0,
+            ),
+        }
+    }
+}
+
+/// One note range of the choke groups
+#[derive(Params)]
+pub struct ChokeRangeParams {
+    /// Lowest note of the range
+    #[id = "choke_low"]
+    pub low: IntParam,
+
+    /// Highest note of the range
+    #[id = "choke_high"]
+    pub high: IntParam,
+
+    /// Choke group the range belongs to (0 = none)
+    #[id = "choke_group"]
+    pub group: IntParam,
+}
+
+impl ChokeRangeParams {
+    fn new(index: usize) -> Self {
+        let note = |name: String| {
+            IntParam::new(name, 0, IntRange::Linear { min: 0, max: 127 }).with_value_to_string(
+                Arc::new(|value| {
+                    u8::try_from(value).map_or_else(|_| "Unknown".to_string(), note_name)
+                }),
+            )
+        };
+        Self {
+            low: note(format!("Choke Range {} Low", index + 1)),
+            high: note(format!("Choke Range {} High", index + 1)),
+            group: IntParam::new(
+                format!("Choke Range {} Group", index + 1),
+                0,
+                IntRange::Linear {
+                    min: 0,
+                    max: i32::from(MAX_CHOKE_GROUP),
+                },
+            )
+            .with_value_to_string(Arc::new(|value| {
+                if value == 0 {
+                    "Off".to_string()
+                } else {
+                    format!("Group {value}")
+                }
+            })),
+        }
+    }
+}
+
+/// One extra note of the chord
+#[derive(Params)]
+pub struct ChordNoteParams {
+    /// Semitones from the played key (0 = unused)
+    #[id = "interval"]
+    pub interval: IntParam,
+}
+
+impl ChordNoteParams {
+    fn new(index: usize) -> Self {
+        // Default shape: major triad
+        let default = match index {
+            0 => 4,
+            1 => 7,
+            _ => 0,
+        };
+        Self {
+            interval: IntParam::new(
+                format!("Chord Note {}", index + 1),
+                default,
+                IntRange::Linear { min: -24, max: 24 },
+            )
+            .with_value_to_string(Arc::new(|value| {
+                if value == 0 {
+                    "Off".to_string()
+                } else {
+                    format!("{value:+} st")
+                }
+            })),
+        }
+    }
+}
+
+/// One modulation matrix routing
+#[derive(Params)]
+pub struct ModSlotParams {
+    /// Modulation source (see `ModSource::NAMES`)
+    #[id = "src"]
+    pub source: IntParam,
+
+    /// Modulation destination (see `ModDestination::NAMES`)
+    #[id = "dest"]
+    pub destination: IntParam,
+
+    /// Bipolar modulation depth (-1.0 - 1.0)
+    #[id = "amount"]
+    pub amount: FloatParam,
+}
+
+impl ModSlotParams {
+    fn new(index: usize) -> Self {
+        let number = index + 1;
+        Self {
+            source: choice_param(&format!("Mod {number} Source"), 0, &ModSource::NAMES),
+            destination: choice_param(
+                &format!("Mod {number} Destination"),
+                0,
+                &ModDestination::NAMES,
+            ),
+            amount: FloatParam::new(
+                format!("Mod {number} Amount"),
+                0.0,
+                FloatRange::Linear {
+                    min: -1.0,
+                    max: 1.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(20.0))
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+        }
+    }
+}
+
+impl Default for NaughtyAndTenderParams {
+    fn default() -> Self {
+        Self {
+            editor_state: EguiState::from_size(600, 500),
+            editor_theme: RwLock::new(ThemeKind::default().name().to_string()),
+            cc_mappings: RwLock::new(Vec::new()),
+            cc_takeover: RwLock::new(Vec::new()),
+            program_map: RwLock::new(Vec::new()),
+            sample_path: RwLock::new(String::new()),
+            morph_a: RwLock::new(Vec::new()),
+            morph_b: RwLock::new(Vec::new()),
+            pattern_notes: RwLock::new(Vec::new()),
+            autosave_id: RwLock::new(0),
+
+            gain: FloatParam::new(
+                "Gain",
+                util::db_to_gain(0.0),
+                FloatRange::Skewed {
+                    min: util::db_to_gain(-30.0),
+                    max: util::db_to_gain(6.0),
+                    factor: FloatRange::gain_skew_factor(-30.0, 6.0),
+                },
+            )
+            .with_smoother(SmoothingStyle::Logarithmic(50.0))
+            .with_poly_modulation_id(PolyTarget::Level.id())
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_gain_to_db(2))
+            .with_string_to_value(formatters::s2v_f32_gain_to_db()),
+            master_tune_hz: FloatParam::new(
+                "Master Tune",
+                STANDARD_A4_HZ,
+                FloatRange::Linear {
+                    min: 415.0,
+                    max: 466.0,
+                },
+            )
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            fine_tune_cents: FloatParam::new(
+                "Fine Tune",
+                0.0,
+                FloatRange::Linear {
+                    min: -50.0,
+                    max: 50.0,
+                },
+            )
+            .with_poly_modulation_id(PolyTarget::Pitch.id())
+            .with_unit(" ct")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            bend_range: FloatParam::new(
+                "Bend Range",
+                DEFAULT_BEND_RANGE,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: MAX_BEND_RANGE,
+                },
+            )
+            .with_step_size(1.0)
+            .with_unit(" st")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+            bypass: BoolParam::new("Bypass", false).make_bypass(),
+
+            voice_count: IntParam::new("Voices", 0, IntRange::Linear { min: 0, max: 16 })
+                .with_value_to_string(Arc::new(|value| format!("{value}")))
+                .non_automatable(),
+
+            // Oscillator parameters
+            waveform: IntParam::new(
+                "Waveform",
+                0, // Default to Sine
+                IntRange::Linear { min: 0, max: 3 },
+            )
+            .with_value_to_string(Arc::new(|value| {
+                match value {
+                    0 => "Sine".to_string(),
+                    1 => "Sawtooth".to_string(),
+                    2 => "Square".to_string(),
+                    3 => "Triangle".to_string(),
+                    _ => "Unknown".to_string(),
+                }
+            }))
+            .with_string_to_value(Arc::new(|string| {
+                match string {
+                    "Sine" => Some(0),
+                    "Sawtooth" => Some(1),
+                    "Square" => Some(2),
+                    "Triangle" => Some(3),
+                    _ => None,
+                }
+            })),
+            sine_quality: choice_param("Sine Quality", 0, &SineMode::NAMES),
+
+            glide_ms: FloatParam::new(
+                "Glide",
+                0.0,
+                FloatRange::Skewed {
+                    min: 0.0,
+                    max: 5000.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(10.0))
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+
+            glide_sync: BoolParam::new("Glide Sync", false),
+            glide_division: division_param("Glide Division", NoteDivision::Sixteenth),
+            glide_mode: choice_param("Glide Mode", 0, &GlideMode::NAMES),
+            glide_curve: choice_param("Glide Curve", 0, &GlideCurve::NAMES),
+            glissando: choice_param("Glissando", 0, &Glissando::NAMES),
+            free_running_phase: BoolParam::new("Free-Running Phase", false),
+            start_phase: FloatParam::new(
+                "Start Phase",
+                0.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 360.0,
+                },
+            )
+            .with_unit("°")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+            random_phase: BoolParam::new("Random Phase", false),
+
+            // Engine parameters
+            engine: IntParam::new(
+                "Engine",
+                0, // Default to Oscillator
+                IntRange::Linear { min: 0, max: 3 },
+            )
+            .with_value_to_string(Arc::new(|value| {
+                match value {
+                    0 => "Oscillator".to_string(),
+                    1 => "Karplus-Strong".to_string(),
+                    2 => "Sample".to_string(),
+                    3 => "Additive".to_string(),
+                    _ => "Unknown".to_string(),
+                }
+            }))
+            .with_string_to_value(Arc::new(|string| {
+                match string {
+                    "Oscillator" => Some(0),
+                    "Karplus-Strong" => Some(1),
+                    "Sample" => Some(2),
+                    "Additive" => Some(3),
+                    _ => None,
+                }
+            })),
+
+            string_excitation: IntParam::new(
+                "Excitation",
+                0, // Default to Noise
+                IntRange::Linear { min: 0, max: 1 },
+            )
+            .with_value_to_string(Arc::new(|value| {
+                match value {
+                    0 => "Noise".to_string(),
+                    1 => "Oscillator".to_string(),
+                    _ => "Unknown".to_string(),
+                }
+            }))
+            .with_string_to_value(Arc::new(|string| {
+                match string {
+                    "Noise" => Some(0),
+                    "Oscillator" => Some(1),
+                    _ => None,
+                }
+            })),
+
+            string_damping: FloatParam::new(
+                "Damping",
+                0.5,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(10.0))
+            .with_unit("")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            string_decay_ms: FloatParam::new(
+                "String Decay",
+                2000.0,
+                FloatRange::Skewed {
+                    min: 50.0,
+                    max: 10000.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(10.0))
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+
+            sample_root: IntParam::new(
+                "Sample Root",
+                i32::from(DEFAULT_ROOT_NOTE),
+                IntRange::Linear { min: 0, max: 127 },
+            )
+            .with_value_to_string(Arc::new(|value| {
+                u8::try_from(value).map_or_else(|_| "Unknown".to_string(), note_name)
+            })),
+            sample_start: unit_param("Sample Start", 0.0),
+            sample_loop: BoolParam::new("Sample Loop", false),
+            sample_loop_start: unit_param("Loop Start", 0.0),
+            sample_loop_end: unit_param("Loop End", 1.0),
+            sample_interpolation: choice_param("Interpolation", 0, &Interpolation::NAMES),
+
+            granular: BoolParam::new("Granular", false),
+            grain_size_ms: FloatParam::new(
+                "Grain Size",
+                80.0,
+                FloatRange::Skewed {
+                    min: 5.0,
+                    max: 500.0,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(10.0))
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            grain_density: FloatParam::new(
+                "Grain Density",
+                20.0,
+                FloatRange::Skewed {
+                    min: 1.0,
+                    max: 200.0,
+                    factor: FloatRange::skew_factor(-1.5),
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(10.0))
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            grain_position: unit_param("Grain Position", 0.25),
+            grain_jitter: unit_param("Grain Jitter", 0.1),
+            grain_pitch_spread: FloatParam::new(
+                "Grain Pitch Spread",
+                0.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 12.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(10.0))
+            .with_unit(" st")
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+            grain_window: choice_param("Grain Window", 0, &GrainWindow::NAMES),
+
+            partials: std::array::from_fn(PartialParams::new),
+            additive_tilt: FloatParam::new(
+                "Spectral Tilt",
+                0.0,
+                FloatRange::Linear {
+                    min: -12.0,
+                    max: 12.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(10.0))
+            .with_unit(" dB/oct")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+
+            // ADSR Envelope parameters
+            attack_ms: FloatParam::new(
+                "Attack",
+                10.0,
+                FloatRange::Skewed {
+                    min: 0.1,
+                    max: 2000.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(10.0))
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+
+            attack_sync: BoolParam::new("Attack Sync", false),
+            attack_division: division_param("Attack Division", NoteDivision::Sixteenth),
+
+            decay_ms: FloatParam::new(
+                "Decay",
+                100.0,
+                FloatRange::Skewed {
+                    min: 0.1,
                     max: 2000.0,
                     factor: FloatRange::skew_factor(-2.0),
                 },
@@ -120,6 +1379,9 @@ impl Default for NaughtyAndTenderParams {
             .with_unit(" ms")
             .with_value_to_string(formatters::v2s_f32_rounded(1)),
 
+            decay_sync: BoolParam::new("Decay Sync", false),
+            decay_division: division_param("Decay Division", NoteDivision::Eighth),
+
             sustain_level: FloatParam::new(
                 "Sustain",
                 0.7,
@@ -145,6 +1407,1019 @@ impl Default for NaughtyAndTenderParams {
             .with_smoother(SmoothingStyle::Linear(10.0))
             .with_unit(" ms")
             .with_value_to_string(formatters::v2s_f32_rounded(1)),
-        }
-    }
+
+            release_sync: BoolParam::new("Release Sync", false),
+            release_division: division_param("Release Division", NoteDivision::Quarter),
+
+            release_velocity: FloatParam::new(
+                "Release Velocity",
+                0.0,
+                FloatRange::Linear {
+                    min: -1.0,
+                    max: 1.0,
+                },
+            )
+            .with_unit("")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            latch_envelope: BoolParam::new("Latch Envelope", false),
+
+            // Master effect chain
+            fx_bypass: BoolParam::new("FX Bypass", false),
+            dc_blocker: BoolParam::new("DC Blocker", true),
+            stereo_width: FloatParam::new(
+                "Stereo Width",
+                UNITY_WIDTH,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: MAX_WIDTH,
+                },
+            )
+            .with_unit("")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            width_limit: BoolParam::new("Width Limiter", false),
+            min_correlation: FloatParam::new(
+                "Min Correlation",
+                DEFAULT_MIN_CORRELATION,
+                FloatRange::Linear { min: -0.5, max: 0.9 },
+            )
+            .with_step_size(0.01)
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+            fx_slot_1: choice_param("FX Slot 1", DRIVE_SLOT, &SLOT_NAMES),
+            fx_slot_2: choice_param("FX Slot 2", PHASER_SLOT, &SLOT_NAMES),
+            fx_slot_3: choice_param("FX Slot 3", EQ_SLOT, &SLOT_NAMES),
+
+            // Phaser parameters
+            phaser_enabled: BoolParam::new("Phaser", false),
+
+            phaser_rate_hz: FloatParam::new(
+                "Phaser Rate",
+                0.5,
+                FloatRange::Skewed {
+                    min: 0.05,
+                    max: 10.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+
+            phaser_depth: FloatParam::new(
+                "Phaser Depth",
+                0.7,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(20.0))
+            .with_unit("")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            phaser_feedback: FloatParam::new(
+                "Phaser Feedback",
+                0.0,
+                FloatRange::Linear {
+                    min: -0.95,
+                    max: 0.95,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(20.0))
+            .with_unit("")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            phaser_stages: IntParam::new("Phaser Stages", 4, IntRange::Linear { min: 4, max: 8 }),
+
+            phaser_mix: FloatParam::new(
+                "Phaser Mix",
+                0.5,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(20.0))
+            .with_unit("")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            // Drive parameters
+            drive_placement: choice_param("Drive", 0, &["Off", "Per Voice", "Master"]),
+            drive_curve: choice_param(
+                "Drive Curve",
+                0,
+                &["Tanh", "Hard Clip", "Foldback", "Diode"],
+            ),
+
+            drive_db: FloatParam::new(
+                "Drive Amount",
+                12.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 36.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(20.0))
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+
+            drive_tone_hz: FloatParam::new(
+                "Drive Tone",
+                8000.0,
+                FloatRange::Skewed {
+                    min: 200.0,
+                    max: 20000.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_smoother(SmoothingStyle::Logarithmic(20.0))
+            .with_value_to_string(formatters::v2s_f32_hz_then_khz(0))
+            .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
+
+            drive_tone_position: choice_param("Drive Tone Position", 1, &["Pre", "Post"]),
+
+            drive_mix: FloatParam::new(
+                "Drive Mix",
+                1.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(20.0))
+            .with_unit("")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            drive_oversampling: choice_param("Drive Oversampling", 1, &["Off", "2x", "4x"]),
+
+            // Master EQ parameters
+            eq_enabled: BoolParam::new("EQ", true),
+
+            eq_mix: FloatParam::new(
+                "EQ Mix",
+                1.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(20.0))
+            .with_unit("")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            eq_low_freq: eq_freq_param("Low Freq", 120.0, 20.0, 1000.0),
+            eq_low_gain_db: eq_gain_param("Low Gain"),
+            eq_mid_freq: eq_freq_param("Mid Freq", 1000.0, 100.0, 10000.0),
+            eq_mid_gain_db: eq_gain_param("Mid Gain"),
+            eq_mid_q: FloatParam::new(
+                "Mid Q",
+                0.707,
+                FloatRange::Skewed {
+                    min: 0.3,
+                    max: 10.0,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            )
+            .with_smoother(SmoothingStyle::Logarithmic(20.0))
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+            eq_high_freq: eq_freq_param("High Freq", 8000.0, 1000.0, 20000.0),
+            eq_high_gain_db: eq_gain_param("High Gain"),
+
+            // Pump parameters
+            pump_enabled: BoolParam::new("Pump", false),
+
+            pump_depth: FloatParam::new(
+                "Pump Depth",
+                0.6,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            )
+            .with_unit("")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            pump_shape: FloatParam::new(
+                "Pump Shape",
+                0.5,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            )
+            .with_unit("")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            pump_division: division_param("Pump Division", NoteDivision::Quarter),
+
+            // Voice filter parameters
+            filter_enabled: BoolParam::new("Filter", false),
+            filter_mode: choice_param("Filter Mode", 0, &["Low Pass", "High Pass", "Band Pass"]),
+
+            filter_cutoff_hz: FloatParam::new(
+                "Filter Cutoff",
+                1000.0,
+                FloatRange::Skewed {
+                    min: 20.0,
+                    max: 20000.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_smoother(SmoothingStyle::Logarithmic(20.0))
+            .with_poly_modulation_id(PolyTarget::Cutoff.id())
+            .with_value_to_string(formatters::v2s_f32_hz_then_khz(0))
+            .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
+
+            filter_resonance: FloatParam::new(
+                "Filter Resonance",
+                0.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(20.0))
+            .with_unit("")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            filter_env: FilterEnvParams::new("Filter Env"),
+            filter_precision: choice_param("Filter Precision", 0, &["Standard", "High"]),
+            latch_filter: BoolParam::new("Latch Filter", false),
+
+            voice_limiter: BoolParam::new("Voice Limiter", false),
+            voice_limiter_ceiling_db: FloatParam::new(
+                "Voice Limiter Ceiling",
+                DEFAULT_CEILING_DB,
+                FloatRange::Linear {
+                    min: -24.0,
+                    max: 0.0,
+                },
+            )
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+
+            // Random modulation source parameters
+            rand_rate_hz: FloatParam::new(
+                "Random Rate",
+                4.0,
+                FloatRange::Skewed {
+                    min: 0.1,
+                    max: 50.0,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            )
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+
+            rand_sync: BoolParam::new("Random Sync", false),
+            rand_division: division_param("Random Division", NoteDivision::Eighth),
+
+            rand_slew_ms: FloatParam::new(
+                "Random Slew",
+                0.0,
+                FloatRange::Skewed {
+                    min: 0.0,
+                    max: 1000.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+
+            // Step sequencer parameters
+            seq_division: division_param("Step Rate", NoteDivision::Sixteenth),
+
+            seq_slew_ms: FloatParam::new(
+                "Step Slew",
+                0.0,
+                FloatRange::Skewed {
+                    min: 0.0,
+                    max: 1000.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+
+            seq_euclid: BoolParam::new("Step Euclidean", false),
+            seq_euclid_steps: IntParam::new(
+                "Euclidean Steps",
+                16,
+                IntRange::Linear {
+                    min: 1,
+                    max: 16, // NUM_STEPS
+                },
+            ),
+            seq_euclid_pulses: IntParam::new(
+                "Euclidean Pulses",
+                4,
+                IntRange::Linear { min: 0, max: 16 },
+            ),
+            seq_euclid_rotation: IntParam::new(
+                "Euclidean Rotation",
+                0,
+                IntRange::Linear { min: 0, max: 15 },
+            ),
+
+            seq_steps: std::array::from_fn(StepParams::new),
+
+            // Sidechain envelope follower
+            sidechain_attack_ms: time_ms_param("Sidechain Attack", 10.0, 500.0),
+            sidechain_release_ms: time_ms_param("Sidechain Release", 100.0, 2000.0),
+            sidechain_gain_db: FloatParam::new(
+                "Sidechain Gain",
+                0.0,
+                FloatRange::Linear {
+                    min: -12.0,
+                    max: 24.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(10.0))
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+
+            // External audio input
+            input_mode: choice_param("Input Mode", 0, &InputMode::NAMES),
+            input_gain: FloatParam::new(
+                "Input Gain",
+                util::db_to_gain(0.0),
+                FloatRange::Skewed {
+                    min: util::db_to_gain(-24.0),
+                    max: util::db_to_gain(12.0),
+                    factor: FloatRange::gain_skew_factor(-24.0, 12.0),
+                },
+            )
+            .with_smoother(SmoothingStyle::Logarithmic(50.0))
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_gain_to_db(2))
+            .with_string_to_value(formatters::s2v_f32_gain_to_db()),
+            input_mix: unit_param("Input Mix", 1.0),
+
+            // Modulation matrix
+            mod_slots: std::array::from_fn(ModSlotParams::new),
+            control_divisor: IntParam::new(
+                "Control Rate",
+                16, // DEFAULT_CONTROL_DIVISOR
+                IntRange::Linear { min: 1, max: 64 },
+            )
+            .with_unit(" samples"),
+
+            // Chord memory
+            chord_mode: choice_param("Chord Mode", 0, &["Off", "Intervals", "Learned"]),
+            chord_learn: BoolParam::new("Chord Learn", false),
+            midi_out: BoolParam::new("MIDI Out", false),
+            midi_channel: IntParam::new(
+                "MIDI Channel",
+                0,
+                IntRange::Linear {
+                    min: 0,
+                    max: i32::from(NUM_CHANNELS),
+                },
+            )
+            .with_value_to_string(Arc::new(|value| {
+                if value == 0 {
+                    "Omni".to_string()
+                } else {
+                    format!("Ch {value}")
+                }
+            })),
+            program_transition: choice_param(
+                "Program Change Transition",
+                0,
+                &ProgramTransition::NAMES,
+            ),
+
+            // Patch morph
+            morph: FloatParam::new(
+                "Morph",
+                0.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            )
+            .with_unit("")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            chord_notes: std::array::from_fn(ChordNoteParams::new),
+            learned_chord: RwLock::new([0; NUM_CHORD_INTERVALS]),
+
+            // Strum
+            strum: BoolParam::new("Strum", false),
+            strum_time_ms: FloatParam::new(
+                "Strum Time",
+                30.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: MAX_STRUM_MS,
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            strum_direction: choice_param("Strum Direction", 0, &StrumDirection::NAMES),
+
+            // Octaver
+            octaver: BoolParam::new("Octaver", false),
+            octaver_octave_level: unit_param("Octaver Octave", 0.5),
+            octaver_fifth_level: unit_param("Octaver Fifth", 0.0),
+
+            // Humanization
+            humanize: BoolParam::new("Humanize", false),
+            humanize_timing_ms: FloatParam::new(
+                "Humanize Timing",
+                10.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: MAX_TIMING_MS,
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            humanize_velocity: unit_param("Humanize Velocity", 0.1),
+
+            // Pattern sequencer
+            pattern: BoolParam::new("Pattern", false),
+            pattern_bars: IntParam::new(
+                "Pattern Bars",
+                1,
+                IntRange::Linear {
+                    min: 1,
+                    max: 4, // MAX_BARS
+                },
+            ),
+            pattern_velocity: unit_param("Pattern Velocity", 0.8),
+            pattern_swing: FloatParam::new(
+                "Pattern Swing",
+                STRAIGHT_SWING,
+                FloatRange::Linear {
+                    min: STRAIGHT_SWING,
+                    max: MAX_SWING,
+                },
+            )
+            .with_unit("")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            // Performance controllers
+            expression_curve: choice_param("Expression Curve", 2, &ExpressionCurve::NAMES),
+
+            // Scale quantization
+            scale_quantize: BoolParam::new("Scale Quantize", false),
+            scale_root: choice_param("Scale Root", 0, &ROOT_NAMES),
+            scale: choice_param("Scale", 0, &Scale::NAMES),
+            scale_snap: choice_param("Scale Snap", 0, &SnapMode::NAMES),
+            scale_degrees: std::array::from_fn(ScaleDegreeParams::new),
+
+            // Layers
+            layer_mode: choice_param("Layer Mode", 0, &LayerMode::NAMES),
+            split_note: IntParam::new("Split Note", 60, IntRange::Linear { min: 0, max: 127 })
+                .with_value_to_string(Arc::new(|value| {
+                    u8::try_from(value).map_or_else(|_| "Unknown".to_string(), note_name)
+                })),
+            velocity_split: unit_param("Velocity Split", 0.5),
+            layer_a_level: unit_param("Layer A Level", 1.0),
+            layer_b: LayerParams::new("Layer B"),
+
+            // Voice safeguards
+            stuck_timeout_s: FloatParam::new(
+                "Stuck Note Timeout",
+                0.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 600.0,
+                },
+            )
+            .with_step_size(1.0)
+            .with_value_to_string(Arc::new(|value| {
+                if value < 1.0 {
+                    "Off".to_string()
+                } else {
+                    format!("{value:.0} s")
+                }
+            })),
+            bass_reserve: BoolParam::new("Bass Reserve", false),
+            voice_allocation: choice_param("Voice Allocation", 0, &VoiceAllocation::NAMES),
+            voice_stealing: choice_param("Voice Stealing", 0, &VoiceStealing::NAMES),
+            same_note: choice_param("Same Note", 0, &SameNotePolicy::NAMES),
+            choke_policy: choice_param("Choke Policy", 0, &ChokePolicy::NAMES),
+            choke_ranges: std::array::from_fn(ChokeRangeParams::new),
+        }
+    }
+}
+
+impl NaughtyAndTenderParams {
+    /// Current master EQ settings (shared by the audio thread and the response curve)
+    pub fn eq_settings(&self) -> EqSettings {
+        EqSettings {
+            low_freq: self.eq_low_freq.value(),
+            low_gain_db: self.eq_low_gain_db.value(),
+            mid_freq: self.eq_mid_freq.value(),
+            mid_gain_db: self.eq_mid_gain_db.value(),
+            mid_q: self.eq_mid_q.value(),
+            high_freq: self.eq_high_freq.value(),
+            high_gain_db: self.eq_high_gain_db.value(),
+        }
+    }
+
+    /// Master chain processing order as slot indices (normalized by the chain)
+    #[allow(clippy::cast_sign_loss)] // Choice parameters are never negative
+    pub fn fx_order(&self) -> [usize; NUM_SLOTS] {
+        [
+            self.fx_slot_1.value() as usize,
+            self.fx_slot_2.value() as usize,
+            self.fx_slot_3.value() as usize,
+        ]
+    }
+
+    /// Glide time in ms, resolved against the host tempo when synced
+    pub fn glide_time_ms(&self, tempo_bpm: f32) -> f32 {
+        synced_ms(&self.glide_ms, &self.glide_sync, &self.glide_division, tempo_bpm)
+    }
+
+    /// Current glide mode
+    pub fn glide_mode(&self) -> GlideMode {
+        GlideMode::from_index(usize::try_from(self.glide_mode.value()).unwrap_or(0))
+    }
+
+    /// Current glide curve
+    pub fn glide_curve(&self) -> GlideCurve {
+        GlideCurve::from_index(usize::try_from(self.glide_curve.value()).unwrap_or(0))
+    }
+
+    /// Current way of computing the sine
+    pub fn sine_mode(&self) -> SineMode {
+        SineMode::from_index(usize::try_from(self.sine_quality.value()).unwrap_or(0))
+    }
+
+    /// Current glissando mode
+    pub fn glissando(&self) -> Glissando {
+        Glissando::from_index(usize::try_from(self.glissando.value()).unwrap_or(0))
+    }
+
+    /// Attack time in ms, resolved against the host tempo when synced
+    pub fn attack_time_ms(&self, tempo_bpm: f32) -> f32 {
+        synced_ms(&self.attack_ms, &self.attack_sync, &self.attack_division, tempo_bpm)
+    }
+
+    /// Decay time in ms, resolved against the host tempo when synced
+    pub fn decay_time_ms(&self, tempo_bpm: f32) -> f32 {
+        synced_ms(&self.decay_ms, &self.decay_sync, &self.decay_division, tempo_bpm)
+    }
+
+    /// Release time in ms, resolved against the host tempo when synced
+    pub fn release_time_ms(&self, tempo_bpm: f32) -> f32 {
+        synced_ms(&self.release_ms, &self.release_sync, &self.release_division, tempo_bpm)
+    }
+
+    /// Current filter response
+    pub fn filter_mode(&self) -> SvfMode {
+        svf_mode(self.filter_mode.value())
+    }
+
+    /// Current filter arithmetic
+    pub fn filter_precision(&self) -> FilterPrecision {
+        match self.filter_precision.value() {
+            1 => FilterPrecision::Double,
+            _ => FilterPrecision::Single,
+        }
+    }
+
+    /// Settings sounding voices keep from their note on
+    pub fn latch_groups(&self) -> LatchGroups {
+        LatchGroups {
+            envelope: self.latch_envelope.value(),
+            filter: self.latch_filter.value(),
+        }
+    }
+
+    /// Current external input mode
+    pub fn input_mode(&self) -> InputMode {
+        InputMode::from_index(usize::try_from(self.input_mode.value()).unwrap_or(0))
+    }
+
+    /// Current voice allocation mode
+    pub fn voice_allocation(&self) -> VoiceAllocation {
+        VoiceAllocation::from_index(usize::try_from(self.voice_allocation.value()).unwrap_or(0))
+    }
+
+    /// Current voice stealing mode
+    pub fn voice_stealing(&self) -> VoiceStealing {
+        VoiceStealing::from_index(usize::try_from(self.voice_stealing.value()).unwrap_or(0))
+    }
+
+    /// Current same-note policy
+    pub fn same_note_policy(&self) -> SameNotePolicy {
+        SameNotePolicy::from_index(usize::try_from(self.same_note.value()).unwrap_or(0))
+    }
+
+    /// Current choke policy
+    pub fn choke_policy(&self) -> ChokePolicy {
+        ChokePolicy::from_index(usize::try_from(self.choke_policy.value()).unwrap_or(0))
+    }
+
+    /// Current choke group note ranges
+    pub fn choke_ranges(&self) -> [ChokeRange; NUM_CHOKE_RANGES] {
+        let note = |param: &IntParam| u8::try_from(param.value()).unwrap_or(0);
+        std::array::from_fn(|i| {
+            let range = &self.choke_ranges[i];
+            ChokeRange {
+                low: note(&range.low),
+                high: note(&range.high),
+                group: note(&range.group),
+            }
+        })
+    }
+
+    /// MIDI channel to respond to, 0-based (`None` = omni)
+    pub fn midi_channel(&self) -> Option<u8> {
+        u8::try_from(self.midi_channel.value() - 1).ok()
+    }
+
+    /// A voice's host poly modulation as pitch, cutoff and level offsets
+    ///
+    /// Each normalized offset moves its parameter from the current value; the
+    /// difference is what the voice adds on top of its own settings.
+    pub fn poly_offsets(&self, normalized: &PolyOffsets) -> ModOffsets {
+        let offset = |target: PolyTarget| normalized[target as usize];
+        let cutoff = self.filter_cutoff_hz.preview_modulated(offset(PolyTarget::Cutoff));
+        let cents = self.fine_tune_cents.preview_modulated(offset(PolyTarget::Pitch));
+        let gain = self.gain.preview_modulated(offset(PolyTarget::Level));
+        ModOffsets {
+            pitch_semitones: (cents - self.fine_tune_cents.value()) / 100.0,
+            cutoff_octaves: (cutoff / self.filter_cutoff_hz.value()).log2(),
+            level: gain / self.gain.value(),
+        }
+    }
+
+    /// Current strum direction
+    pub fn strum_direction(&self) -> StrumDirection {
+        StrumDirection::from_index(usize::try_from(self.strum_direction.value()).unwrap_or(0))
+    }
+
+    /// Current layer routing mode
+    pub fn layer_mode(&self) -> LayerMode {
+        LayerMode::from_index(usize::try_from(self.layer_mode.value()).unwrap_or(0))
+    }
+
+    /// Random source clock rate in Hz, resolved against the host tempo when synced
+    pub fn random_rate_hz(&self, tempo_bpm: f32) -> f32 {
+        if self.rand_sync.value() {
+            NoteDivision::from_index(usize::try_from(self.rand_division.value()).unwrap_or(0))
+                .rate_hz(tempo_bpm)
+        } else {
+            self.rand_rate_hz.value()
+        }
+    }
+
+    /// Step length as a note division
+    pub fn seq_step_division(&self) -> NoteDivision {
+        NoteDivision::from_index(usize::try_from(self.seq_division.value()).unwrap_or(0))
+    }
+
+    /// Pump cycle length as a note division
+    pub fn pump_cycle_division(&self) -> NoteDivision {
+        NoteDivision::from_index(usize::try_from(self.pump_division.value()).unwrap_or(0))
+    }
+
+    /// Current sequencer steps
+    pub fn seq_steps(&self) -> [Step; NUM_STEPS] {
+        std::array::from_fn(|i| Step {
+            value: self.seq_steps[i].value.value(),
+            gate: self.seq_steps[i].gate.value(),
+        })
+    }
+
+    /// Euclidean rhythm gating the sequencer, if it's on
+    pub fn seq_euclid(&self) -> Option<Euclid> {
+        self.seq_euclid.value().then(|| Euclid {
+            steps: usize::try_from(self.seq_euclid_steps.value()).unwrap_or(NUM_STEPS),
+            pulses: usize::try_from(self.seq_euclid_pulses.value()).unwrap_or(0),
+            rotation: usize::try_from(self.seq_euclid_rotation.value()).unwrap_or(0),
+        })
+    }
+
+    /// Pattern length in bars
+    pub fn pattern_bars(&self) -> usize {
+        usize::try_from(self.pattern_bars.value()).unwrap_or(1)
+    }
+
+    /// Current modulation routing
+    pub fn mod_matrix(&self) -> ModMatrix {
+        ModMatrix {
+            slots: std::array::from_fn(|i| {
+                let slot = &self.mod_slots[i];
+                ModSlot {
+                    source: ModSource::from_index(usize::try_from(slot.source.value()).unwrap_or(0)),
+                    destination: ModDestination::from_index(
+                        usize::try_from(slot.destination.value()).unwrap_or(0),
+                    ),
+                    amount: slot.amount.value(),
+                }
+            }),
+        }
+    }
+
+    /// Samples between modulation updates
+    pub fn control_divisor(&self) -> u32 {
+        u32::try_from(self.control_divisor.value()).unwrap_or(DEFAULT_CONTROL_DIVISOR)
+    }
+
+    /// Current chord mode
+    pub fn chord_mode(&self) -> ChordMode {
+        match self.chord_mode.value() {
+            1 => ChordMode::Intervals,
+            2 => ChordMode::Learned,
+            _ => ChordMode::Off,
+        }
+    }
+
+    /// Current scale root pitch class (0 = C)
+    pub fn scale_root(&self) -> u8 {
+        u8::try_from(self.scale_root.value()).unwrap_or(0)
+    }
+
+    /// Degrees of the current scale, from the custom degrees when set to Custom
+    pub fn scale_mask(&self) -> ScaleMask {
+        let custom = self
+            .scale_degrees
+            .iter()
+            .enumerate()
+            .filter(|(_, degree)| degree.enabled.value())
+            .fold(0, |mask, (index, _)| mask | 1 << index);
+        Scale::from_index(usize::try_from(self.scale.value()).unwrap_or(0)).mask(custom)
+    }
+
+    /// Current scale snap mode
+    pub fn scale_snap(&self) -> SnapMode {
+        SnapMode::from_index(usize::try_from(self.scale_snap.value()).unwrap_or(0))
+    }
+
+    /// Current expression to output level curve
+    pub fn expression_curve(&self) -> ExpressionCurve {
+        ExpressionCurve::from_index(usize::try_from(self.expression_curve.value()).unwrap_or(0))
+    }
+
+    /// Chord intervals from the chord note parameters
+    pub fn chord_intervals(&self) -> ChordIntervals {
+        std::array::from_fn(|i| i8::try_from(self.chord_notes[i].interval.value()).unwrap_or(0))
+    }
+
+    /// Saved editor theme preset
+    pub(crate) fn theme_kind(&self) -> ThemeKind {
+        self.editor_theme
+            .read()
+            .map(|name| ThemeKind::from_name(&name))
+            .unwrap_or_default()
+    }
+
+    /// Save the editor theme preset
+    pub(crate) fn set_theme_kind(&self, kind: ThemeKind) {
+        if let Ok(mut name) = self.editor_theme.write() {
+            *name = kind.name().to_string();
+        }
+    }
+
+    /// Current sample interpolation
+    pub fn sample_interpolation(&self) -> Interpolation {
+        Interpolation::from_index(usize::try_from(self.sample_interpolation.value()).unwrap_or(0))
+    }
+
+    /// Current grain cloud settings
+    pub fn granular_settings(&self) -> GranularSettings {
+        GranularSettings {
+            size_ms: self.grain_size_ms.value(),
+            density_hz: self.grain_density.value(),
+            position: self.grain_position.value(),
+            jitter: self.grain_jitter.value(),
+            pitch_spread: self.grain_pitch_spread.value(),
+            window: GrainWindow::from_index(usize::try_from(self.grain_window.value()).unwrap_or(0)),
+        }
+    }
+
+    /// Current additive partial levels
+    pub fn partial_levels(&self) -> [f32; NUM_PARTIALS] {
+        std::array::from_fn(|i| self.partials[i].level.value())
+    }
+
+    /// Saved sample file path (empty = none)
+    pub(crate) fn sample_path(&self) -> String {
+        self.sample_path
+            .read()
+            .map(|path| path.clone())
+            .unwrap_or_default()
+    }
+
+    /// Save the sample file path
+    pub(crate) fn set_sample_path(&self, path: &str) {
+        if let Ok(mut saved) = self.sample_path.write() {
+            *saved = path.to_string();
+        }
+    }
+
+    /// Current program change transition
+    pub fn program_transition(&self) -> ProgramTransition {
+        ProgramTransition::from_index(usize::try_from(self.program_transition.value()).unwrap_or(0))
+    }
+
+    /// Saved program change assignments
+    pub(crate) fn program_map(&self) -> ProgramMap {
+        self.program_map
+            .read()
+            .map(|saved| ProgramMap::from_saved(&saved))
+            .unwrap_or_default()
+    }
+
+    /// Save the program change assignments
+    pub(crate) fn set_program_map(&self, map: &ProgramMap) {
+        if let Ok(mut saved) = self.program_map.write() {
+            *saved = map.to_saved();
+        }
+    }
+
+    /// Saved morph snapshots
+    pub(crate) fn morph_pair(&self) -> MorphPair {
+        let read = |saved: &RwLock<Snapshot>| {
+            saved
+                .read()
+                .map(|snapshot| snapshot.clone())
+                .unwrap_or_default()
+        };
+        MorphPair::from_saved(read(&self.morph_a), read(&self.morph_b))
+    }
+
+    /// Save one morph snapshot
+    pub(crate) fn set_morph_snapshot(&self, slot: MorphSlot, snapshot: &Snapshot) {
+        let saved = match slot {
+            MorphSlot::A => &self.morph_a,
+            MorphSlot::B => &self.morph_b,
+        };
+        if let Ok(mut saved) = saved.write() {
+            saved.clone_from(snapshot);
+        }
+    }
+
+    /// Saved pattern notes
+    pub(crate) fn pattern_notes(&self) -> PatternNotes {
+        self.pattern_notes
+            .read()
+            .map(|notes| notes.clone())
+            .unwrap_or_default()
+    }
+
+    /// Save the pattern notes
+    pub(crate) fn set_pattern_notes(&self, notes: &PatternNotes) {
+        if let Ok(mut saved) = self.pattern_notes.write() {
+            saved.clone_from(notes);
+        }
+    }
+
+    /// Saved autosave id (0 = none)
+    pub(crate) fn autosave_id(&self) -> u64 {
+        self.autosave_id.read().map(|id| *id).unwrap_or_default()
+    }
+
+    /// Save the autosave id
+    pub(crate) fn set_autosave_id(&self, id: u64) {
+        if let Ok(mut saved) = self.autosave_id.write() {
+            *saved = id;
+        }
+    }
+
+    /// Current drive placement
+    pub fn drive_placement(&self) -> DrivePlacement {
+        match self.drive_placement.value() {
+            1 => DrivePlacement::Voice,
+            2 => DrivePlacement::Master,
+            _ => DrivePlacement::Off,
+        }
+    }
+
+    /// Current waveshaper settings (used for both per-voice and master placement)
+    pub fn waveshaper_settings(&self) -> WaveshaperSettings {
+        WaveshaperSettings {
+            curve: match self.drive_curve.value() {
+                1 => ShaperCurve::HardClip,
+                2 => ShaperCurve::Foldback,
+                3 => ShaperCurve::AsymmetricDiode,
+                _ => ShaperCurve::Tanh,
+            },
+            drive_db: self.drive_db.value(),
+            tone_hz: self.drive_tone_hz.value(),
+            tone_position: match self.drive_tone_position.value() {
+                0 => TonePosition::Pre,
+                _ => TonePosition::Post,
+            },
+            mix: self.drive_mix.value(),
+            oversampling: match self.drive_oversampling.value() {
+                1 => OversamplingFactor::X2,
+                2 => OversamplingFactor::X4,
+                _ => OversamplingFactor::None,
+            },
+        }
+    }
+}
+
+/// Filter response for a filter mode parameter value
+fn svf_mode(value: i32) -> SvfMode {
+    match value {
+        1 => SvfMode::HighPass,
+        2 => SvfMode::BandPass,
+        _ => SvfMode::LowPass,
+    }
+}
+
+/// Free-running time, or the division's length at `tempo_bpm` when synced
+fn synced_ms(time_ms: &FloatParam, sync: &BoolParam, division: &IntParam, tempo_bpm: f32) -> f32 {
+    if sync.value() {
+        NoteDivision::from_index(usize::try_from(division.value()).unwrap_or(0))
+            .duration_ms(tempo_bpm)
+    } else {
+        time_ms.value()
+    }
+}
+
+/// Note division choice (1/64 - 1/1)
+fn division_param(name: &str, default: NoteDivision) -> IntParam {
+    let default = NoteDivision::ALL
+        .iter()
+        .position(|division| *division == default)
+        .unwrap_or(0);
+    choice_param(name, default, &NoteDivision::NAMES)
+}
+
+/// Stepped parameter that displays (and parses) one of a fixed list of names
+fn choice_param(name: &str, default: usize, options: &'static [&'static str]) -> IntParam {
+    let max = i32::try_from(options.len().saturating_sub(1)).unwrap_or(0);
+    let default = i32::try_from(default).unwrap_or(0);
+
+    IntParam::new(name, default, IntRange::Linear { min: 0, max })
+        .with_value_to_string(Arc::new(move |value| {
+            usize::try_from(value)
+                .ok()
+                .and_then(|index| options.get(index))
+                .map_or_else(|| "Unknown".to_string(), ToString::to_string)
+        }))
+        .with_string_to_value(Arc::new(move |string| {
+            options
+                .iter()
+                .position(|option| *option == string)
+                .and_then(|index| i32::try_from(index).ok())
+        }))
+}
+
+/// Smoothed 0-100% parameter
+fn unit_param(name: &str, default: f32) -> FloatParam {
+    FloatParam::new(name, default, FloatRange::Linear { min: 0.0, max: 1.0 })
+        .with_smoother(SmoothingStyle::Linear(10.0))
+        .with_unit("")
+        .with_value_to_string(formatters::v2s_f32_percentage(0))
+        .with_string_to_value(formatters::s2v_f32_percentage())
+}
+
+/// Envelope time parameter in milliseconds (0.1 ms - `max_ms`)
+fn time_ms_param(name: &str, default: f32, max_ms: f32) -> FloatParam {
+    FloatParam::new(
+        name,
+        default,
+        FloatRange::Skewed {
+            min: 0.1,
+            max: max_ms,
+            factor: FloatRange::skew_factor(-2.0),
+        },
+    )
+    .with_smoother(SmoothingStyle::Linear(10.0))
+    .with_unit(" ms")
+    .with_value_to_string(formatters::v2s_f32_rounded(1))
+}
+
+/// Frequency parameter for an EQ band
+fn eq_freq_param(name: &str, default: f32, min: f32, max: f32) -> FloatParam {
+    FloatParam::new(
+        name,
+        default,
+        FloatRange::Skewed {
+            min,
+            max,
+            factor: FloatRange::skew_factor(-2.0),
+        },
+    )
+    .with_smoother(SmoothingStyle::Logarithmic(20.0))
+    .with_value_to_string(formatters::v2s_f32_hz_then_khz(0))
+    .with_string_to_value(formatters::s2v_f32_hz_then_khz())
+}
+
+/// Gain parameter (±15 dB) for an EQ band
+fn eq_gain_param(name: &str) -> FloatParam {
+    FloatParam::new(name, 0.0, FloatRange::Linear { min: -15.0, max: 15.0 })
+        .with_smoother(SmoothingStyle::Linear(20.0))
+        .with_unit(" dB")
+        .with_value_to_string(formatters::v2s_f32_rounded(1))
 }
@@ -0,0 +1,909 @@
+//! Built-in pattern sequencer for Naughty and Tender
+//!
+//! A one to four bar loop of notes on a sixteenth-note grid, drawn in the
+//! editor's piano roll and played along with the host transport. Pattern notes
+//! join the notes played over MIDI before humanization, so scale snapping,
+//! chords, strumming and the layers all apply to them too.
+//!
+//! The pattern is stored as (start step, note, length in steps) triples with
+//! the plugin state. The audio thread copies it into a fixed-size array each
+//! block, and counts its own samples between blocks like the step sequencer,
+//! re-locking to the song position only when the host's playhead jumps. Every
+//! note it starts it also ends, counted in steps, so editing or stopping the
+//! pattern never leaves a note hanging.
+//!
+//! Bars follow the host's time signature: a bar of 3/4 is twelve steps, 7/8
+//! fourteen, and the loop restarts on the host's bar lines. Hosts that don't
+//! report one get 4/4. The loop is never longer than [`MAX_STEPS`], so four
+//! long bars may be cut short.
+//!
+//! Swing delays every second step of each pair within the bar, MPC style: at
+//! 50% the pair is split evenly, at 66% it plays as a triplet, at 75% the
+//! second step starts a dotted sixteenth after the first. A change of swing waits for
+//! the next pair, so a step is never played twice or skipped.
+//!
+//! Presets fill the pattern with a rhythm on one note (eighths, a note every
+//! three steps, or dotted eighths), started over on each bar line so they fit
+//! whatever time signature the host reports.
+//!
+//! # References
+//! - Piano-roll editors (Cubase Key Editor, FL Studio Piano Roll)
+//! - Step length: `steps per sample = BPM / 60 · STEPS_PER_BEAT / sample_rate`
+//! - Roger Linn, MPC60 swing (50% - 75% of a pair of sixteenths)
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::humanize::KeyEvent;
+use crate::NUM_NOTES;
+use shared_core::tempo::relock_position;
+
+/// Grid steps per quarter note (sixteenth notes)
+pub const STEPS_PER_BEAT: usize = 4;
+
+/// Grid steps per bar
+pub const STEPS_PER_BAR: usize = STEPS_PER_BEAT * 4;
+
+/// Longest pattern, in bars
+pub const MAX_BARS: usize = 4;
+
+/// Grid steps in the longest pattern
+pub const MAX_STEPS: usize = STEPS_PER_BAR * MAX_BARS;
+
+/// Swing that splits each pair of steps evenly
+pub const STRAIGHT_SWING: f32 = 0.5;
+
+/// Heaviest swing: the second step of a pair starts three quarters through it
+pub const MAX_SWING: f32 = 0.75;
+
+/// Most notes a pattern holds
+pub const MAX_PATTERN_NOTES: usize = 256;
+
+/// Release velocity of pattern note-offs
+const RELEASE_VELOCITY: f32 = 0.5;
+
+/// A saved pattern: (start step, MIDI note, length in steps) for each note
+pub type PatternNotes = Vec<(u8, u8, u8)>;
+
+/// Grid steps in a bar of `numerator`/`denominator` (1 - [`MAX_STEPS`])
+///
+/// Signatures that make no sense count as 4/4.
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::pattern::bar_steps;
+///
+/// assert_eq!(bar_steps(4, 4), 16);
+/// assert_eq!(bar_steps(3, 4), 12);
+/// assert_eq!(bar_steps(7, 8), 14);
+/// ```
+#[must_use]
+pub fn bar_steps(numerator: i32, denominator: i32) -> usize {
+    if numerator <= 0 || denominator <= 0 {
+        return STEPS_PER_BAR;
+    }
+    // A whole note is four quarter notes
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )] // Small positive counts, clamped
+    let steps = (f64::from(numerator) * (4 * STEPS_PER_BEAT) as f64 / f64::from(denominator))
+        .round()
+        .min(MAX_STEPS as f64) as usize;
+    steps.max(1)
+}
+
+/// Index of the note sounding at a grid cell, if any
+#[must_use]
+pub fn note_index_at(notes: &[(u8, u8, u8)], step: usize, note: u8) -> Option<usize> {
+    notes.iter().position(|&(start, other, length)| {
+        let start = usize::from(start);
+        other == note && (start..start + usize::from(length)).contains(&step)
+    })
+}
+
+/// Click on a grid cell: remove the note sounding there, or add a one-step
+/// note if there isn't one (and the pattern isn't full)
+pub fn toggle_note(notes: &mut PatternNotes, step: usize, note: u8) {
+    if let Some(index) = note_index_at(notes, step, note) {
+        notes.remove(index);
+    } else if let (Ok(step), true) = (u8::try_from(step), notes.len() < MAX_PATTERN_NOTES) {
+        if usize::from(step) < MAX_STEPS {
+            notes.push((step, note, 1));
+        }
+    }
+}
+
+/// Drag from a grid cell: stretch the note starting there (adding it if
+/// needed) to end on `end_step`, at least one step long
+pub fn stretch_note(notes: &mut PatternNotes, step: usize, note: u8, end_step: usize) {
+    let Ok(start) = u8::try_from(step) else {
+        return;
+    };
+    #[allow(clippy::cast_possible_truncation)] // At most MAX_STEPS
+    let length = (end_step.saturating_sub(step) + 1).min(MAX_STEPS - step.min(MAX_STEPS)) as u8;
+
+    let existing = notes
+        .iter()
+        .position(|&(other_start, other, _)| (other_start, other) == (start, note));
+    match existing {
+        Some(index) => notes[index].2 = length.max(1),
+        None if notes.len() < MAX_PATTERN_NOTES && step < MAX_STEPS => {
+            notes.push((start, note, length.max(1)));
+        }
+        None => {}
+    }
+}
+
+/// Rhythm presets that fill a pattern with one note
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternPreset {
+    /// A one-step note on every second step
+    Eighths,
+    /// A one-step note on every third step, starting over on each bar line
+    ThreeStep,
+    /// Dotted eighths grouped 3 + 3 + 2 within each half note, held to the
+    /// next note
+    Dotted,
+}
+
+impl PatternPreset {
+    /// Every preset, in menu order
+    pub const ALL: [Self; 3] = [Self::Eighths, Self::ThreeStep, Self::Dotted];
+
+    /// Display names, in menu order
+    pub const NAMES: [&'static str; 3] = ["Eighths", "3-Step", "Dotted"];
+
+    /// Pattern of the preset on one note, over `bars` bars of `bar_steps` steps
+    ///
+    /// Each bar starts the rhythm over, so odd time signatures cut the last
+    /// group of a bar short rather than drifting across the bar line.
+    ///
+    /// # Example
+    /// ```
+    /// use naughty_and_tender::pattern::{bar_steps, PatternPreset};
+    ///
+    /// let notes = PatternPreset::ThreeStep.notes(60, bar_steps(3, 4), 1);
+    /// assert_eq!(notes, vec![(0, 60, 1), (3, 60, 1), (6, 60, 1), (9, 60, 1)]);
+    /// ```
+    #[must_use]
+    pub fn notes(self, note: u8, bar_steps: usize, bars: usize) -> PatternNotes {
+        let bar_steps = bar_steps.clamp(1, MAX_STEPS);
+        let steps = (bar_steps * bars.clamp(1, MAX_BARS)).min(MAX_STEPS);
+        let starts_note = |step: usize| match self {
+            Self::Eighths => step.is_multiple_of(2),
+            Self::ThreeStep => step.is_multiple_of(3),
+            Self::Dotted => matches!(step % 8, 0 | 3 | 6),
+        };
+
+        let mut notes = PatternNotes::new();
+        for bar_start in (0..steps).step_by(bar_steps) {
+            let bar_end = (bar_start + bar_steps).min(steps);
+            for start in (bar_start..bar_end).filter(|step| starts_note(step - bar_start)) {
+                let length = match self {
+                    Self::Eighths | Self::ThreeStep => 1,
+                    Self::Dotted => {
+                        (start + 1..bar_end)
+                            .find(|step| starts_note(step - bar_start))
+                            .unwrap_or(bar_end)
+                            - start
+                    }
+                };
+                #[allow(clippy::cast_possible_truncation)] // At most MAX_STEPS
+                notes.push((start as u8, note, length as u8));
+            }
+        }
+        notes.truncate(MAX_PATTERN_NOTES);
+        notes
+    }
+}
+
+/// Step the pattern is playing and the bar length, shared with the editor for
+/// its playhead and bar lines
+///
+/// # Real-time Safety
+/// - Two atomics, written once per block
+#[derive(Debug)]
+pub struct PatternPlayhead {
+    /// Step index, or `u32::MAX` while stopped
+    step: AtomicU32,
+
+    /// Grid steps in a bar of the host's time signature
+    bar_steps: AtomicU32,
+}
+
+impl Default for PatternPlayhead {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PatternPlayhead {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            step: AtomicU32::new(u32::MAX),
+            #[allow(clippy::cast_possible_truncation)] // A small constant
+            bar_steps: AtomicU32::new(STEPS_PER_BAR as u32),
+        }
+    }
+
+    /// Publish the step playing and the bar length (audio thread)
+    pub fn publish(&self, step: Option<usize>, bar_steps: usize) {
+        let step = step.and_then(|step| u32::try_from(step).ok());
+        self.step.store(step.unwrap_or(u32::MAX), Ordering::Relaxed);
+        let bar_steps = u32::try_from(bar_steps).unwrap_or(u32::MAX);
+        self.bar_steps.store(bar_steps, Ordering::Relaxed);
+    }
+
+    /// Step playing, if the pattern is running (editor thread)
+    #[must_use]
+    pub fn step(&self) -> Option<usize> {
+        let step = self.step.load(Ordering::Relaxed);
+        (step != u32::MAX).then_some(step as usize)
+    }
+
+    /// Grid steps in a bar (editor thread)
+    #[must_use]
+    pub fn bar_steps(&self) -> usize {
+        (self.bar_steps.load(Ordering::Relaxed) as usize).clamp(1, MAX_STEPS)
+    }
+}
+
+/// One note of the pattern
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct PatternNote {
+    step: usize,
+    note: u8,
+    length: u8,
+}
+
+/// Plays the pattern as note events, in time with the host
+///
+/// Once per sample, [`PatternPlayer::pop_due`] hands back the events due, then
+/// [`PatternPlayer::advance`] moves to the next sample, like the humanizer.
+/// Positions are in straight steps; swing only moves where the second step of
+/// each pair starts.
+///
+/// # Real-time Safety
+/// - Fixed-size note array; the event list is allocated once at construction
+///   and never grows
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::humanize::KeyEvent;
+/// use naughty_and_tender::pattern::PatternPlayer;
+///
+/// let mut player = PatternPlayer::new(48000.0);
+/// player.set_notes(&[(0, 60, 2), (4, 67, 1)]);
+/// player.set_tempo(120.0);
+/// player.sync_to_beats(0.0);
+/// player.set_playing(true);
+/// assert!(matches!(player.pop_due(), Some(KeyEvent::NoteOn { note: 60, .. })));
+/// player.advance();
+/// ```
+pub struct PatternPlayer {
+    notes: [PatternNote; MAX_PATTERN_NOTES],
+
+    /// Notes in use at the start of `notes`
+    num_notes: usize,
+
+    /// Pattern length in bars
+    bars: usize,
+
+    /// Grid steps in a bar
+    bar_steps: usize,
+
+    /// Pattern length in steps (`bars` of `bar_steps`, at most `MAX_STEPS`)
+    length_steps: usize,
+
+    /// Where the second step of each pair starts, as a fraction of the pair
+    swing: f64,
+
+    /// Swing to take over at the start of the next pair
+    next_swing: f64,
+
+    /// Velocity of every note (0.0 - 1.0)
+    velocity: f32,
+
+    /// Sample rate in Hz
+    sample_rate: f32,
+
+    /// Position at the last tempo change, loop or jump, in steps
+    anchor: f64,
+
+    /// Samples played since the anchor
+    anchor_samples: u64,
+
+    /// Tempo in steps per minute
+    steps_per_minute: f64,
+
+    /// Position advance per sample, in steps
+    increment: f64,
+
+    /// Following the transport
+    playing: bool,
+
+    /// Steps left for each note the pattern is holding (0 = not held)
+    remaining: [usize; NUM_NOTES],
+
+    /// Events due at the current sample
+    due: Vec<KeyEvent>,
+
+    /// Events of `due` already handed out
+    popped: usize,
+}
+
+impl PatternPlayer {
+    /// Create a stopped player with an empty one-bar pattern
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        let mut player = Self {
+            notes: [PatternNote::default(); MAX_PATTERN_NOTES],
+            num_notes: 0,
+            bars: 1,
+            bar_steps: STEPS_PER_BAR,
+            length_steps: STEPS_PER_BAR,
+            swing: f64::from(STRAIGHT_SWING),
+            next_swing: f64::from(STRAIGHT_SWING),
+            velocity: 0.8,
+            sample_rate,
+            anchor: 0.0,
+            anchor_samples: 0,
+            steps_per_minute: 0.0,
+            increment: 0.0,
+            playing: false,
+            remaining: [0; NUM_NOTES],
+            // Every held note can end and every pattern note start in one step
+            due: Vec::with_capacity(NUM_NOTES + 2 * MAX_PATTERN_NOTES),
+            popped: 0,
+        };
+        player.set_tempo(120.0);
+        player
+    }
+
+    /// Set sample rate (stops the pattern)
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.increment = self.steps_per_minute / (60.0 * f64::from(sample_rate));
+        self.reset();
+    }
+
+    /// Replace the pattern (notes past `MAX_PATTERN_NOTES` are left out)
+    pub fn set_notes(&mut self, notes: &[(u8, u8, u8)]) {
+        self.num_notes = notes.len().min(MAX_PATTERN_NOTES);
+        for (slot, &(step, note, length)) in self.notes.iter_mut().zip(notes) {
+            *slot = PatternNote {
+                step: usize::from(step),
+                note: note.min(127),
+                length,
+            };
+        }
+    }
+
+    /// Set the pattern length in bars (1 - [`MAX_BARS`])
+    pub fn set_bars(&mut self, bars: usize) {
+        self.bars = bars.clamp(1, MAX_BARS);
+        self.update_length();
+    }
+
+    /// Set the host's time signature (call every block; see [`bar_steps`])
+    pub fn set_time_signature(&mut self, numerator: i32, denominator: i32) {
+        self.bar_steps = bar_steps(numerator, denominator);
+        self.update_length();
+    }
+
+    /// Grid steps in a bar
+    #[must_use]
+    pub fn bar_steps(&self) -> usize {
+        self.bar_steps
+    }
+
+    /// Set the swing ([`STRAIGHT_SWING`] - [`MAX_SWING`]); while playing it
+    /// takes over at the start of the next pair of steps
+    pub fn set_swing(&mut self, swing: f32) {
+        self.next_swing = f64::from(swing.clamp(STRAIGHT_SWING, MAX_SWING));
+        if !self.playing {
+            self.swing = self.next_swing;
+        }
+    }
+
+    /// Set the velocity of every note (0.0 - 1.0)
+    pub fn set_velocity(&mut self, velocity: f32) {
+        self.velocity = velocity.clamp(0.0, 1.0);
+    }
+
+    /// Set the tempo in beats per minute (call every block; only a change
+    /// moves the anchor)
+    #[allow(clippy::float_cmp)] // Exact changes only, to keep the sample count
+    pub fn set_tempo(&mut self, tempo_bpm: f32) {
+        #[allow(clippy::cast_precision_loss)] // STEPS_PER_BEAT is tiny
+        let steps_per_minute = f64::from(tempo_bpm.max(1.0)) * STEPS_PER_BEAT as f64;
+        if steps_per_minute != self.steps_per_minute {
+            self.set_position(self.position());
+            self.steps_per_minute = steps_per_minute;
+            self.increment = steps_per_minute / (60.0 * f64::from(self.sample_rate));
+        }
+    }
+
+    /// Lock the playhead to the host's song position (call every block while
+    /// the transport runs; it only jumps when the song position has moved away)
+    ///
+    /// # Arguments
+    /// * `position_beats` - Song position in quarter notes
+    pub fn sync_to_beats(&mut self, position_beats: f64) {
+        #[allow(clippy::cast_precision_loss)] // Steps are tiny counts
+        let (length, steps_per_beat) = (self.length_steps as f64, STEPS_PER_BEAT as f64);
+        let target = (position_beats * steps_per_beat).rem_euclid(length);
+        if let Some(position) = relock_position(self.position(), target, length, self.increment) {
+            self.set_position(position);
+        }
+    }
+
+    /// Lock the playhead to the host's bar lines, which stay right across
+    /// changes of time signature (use instead of
+    /// [`PatternPlayer::sync_to_beats`] when the host reports its bars)
+    ///
+    /// # Arguments
+    /// * `bar_number` - Bar the song position is in, from 0
+    /// * `beats_into_bar` - Quarter notes since that bar started
+    #[allow(clippy::cast_precision_loss)] // Steps are tiny counts
+    pub fn sync_to_bar(&mut self, bar_number: i32, beats_into_bar: f64) {
+        let loop_bars = i64::try_from(self.length_steps.div_ceil(self.bar_steps)).unwrap_or(1);
+        let bar = i64::from(bar_number).rem_euclid(loop_bars) as f64;
+        let length = self.length_steps as f64;
+        let target = (bar * self.bar_steps as f64 + beats_into_bar * STEPS_PER_BEAT as f64)
+            .rem_euclid(length);
+        if let Some(position) = relock_position(self.position(), target, length, self.increment) {
+            self.set_position(position);
+        }
+    }
+
+    /// Start or stop following the transport
+    ///
+    /// Starting right on a step plays it at once; starting partway through
+    /// waits for the next one. Stopping releases every note held.
+    pub fn set_playing(&mut self, playing: bool) {
+        if playing == self.playing {
+            return;
+        }
+        self.playing = playing;
+        if playing {
+            let step = self.current_position_step();
+            if self.position() - self.step_start(step) < self.increment {
+                self.queue_step(step);
+            }
+        } else {
+            self.release_all();
+        }
+    }
+
+    /// Step playing, or `None` while stopped
+    #[must_use]
+    pub fn current_step(&self) -> Option<usize> {
+        self.playing.then(|| self.current_position_step())
+    }
+
+    /// Next event due at the current sample, note-offs first
+    pub fn pop_due(&mut self) -> Option<KeyEvent> {
+        let event = self.due.get(self.popped).copied()?;
+        self.popped += 1;
+        Some(event)
+    }
+
+    /// Move on to the next sample
+    #[inline]
+    pub fn advance(&mut self) {
+        self.due.clear();
+        self.popped = 0;
+        if !self.playing {
+            return;
+        }
+
+        let step = self.current_position_step();
+        #[allow(clippy::cast_precision_loss)] // At most MAX_STEPS
+        let length = self.length_steps as f64;
+        self.anchor_samples += 1;
+        let position = self.position();
+        if position >= length {
+            self.set_position(position - length);
+        }
+
+        let next = self.current_position_step();
+        if next != step {
+            self.queue_step(next);
+
+            // The first step of a pair starts before any swing would move the
+            // second, so the swing can change here without moving the step
+            if !self.is_second_of_pair(next) {
+                self.swing = self.next_swing;
+            }
+        }
+    }
+
+    /// Stop and forget everything pending (held notes are dropped, not released)
+    pub fn reset(&mut self) {
+        self.playing = false;
+        self.swing = self.next_swing;
+        self.set_position(0.0);
+        self.remaining = [0; NUM_NOTES];
+        self.due.clear();
+        self.popped = 0;
+    }
+
+    /// Position in steps (0.0 to `length_steps`)
+    ///
+    /// Counted in whole samples from the anchor rather than summed sample by
+    /// sample, so steps start on the same samples however long it plays.
+    fn position(&self) -> f64 {
+        #[allow(clippy::cast_precision_loss)] // Far below 2^52 samples
+        let samples = self.anchor_samples as f64;
+        self.anchor + samples * self.steps_per_minute / (60.0 * f64::from(self.sample_rate))
+    }
+
+    /// Move the anchor to a position, in steps
+    fn set_position(&mut self, position: f64) {
+        self.anchor = position;
+        self.anchor_samples = 0;
+    }
+
+    /// Recompute the length from the bars and the bar length, keeping the
+    /// position within it
+    fn update_length(&mut self) {
+        let length_steps = (self.bars * self.bar_steps).min(MAX_STEPS);
+        if length_steps != self.length_steps {
+            self.length_steps = length_steps;
+            #[allow(clippy::cast_precision_loss)] // At most MAX_STEPS
+            self.set_position(self.position().rem_euclid(length_steps as f64));
+        }
+    }
+
+    /// Step the position is in, with the second step of each pair in the bar
+    /// starting late by the swing
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )] // 0 to length
+    fn current_position_step(&self) -> usize {
+        let position = self.position().max(0.0);
+        let bar_steps = self.bar_steps as f64;
+        let bar = (position / bar_steps).floor();
+        let in_bar = position - bar * bar_steps;
+        let pair = (in_bar / 2.0).floor();
+        let second = in_bar - 2.0 * pair >= 2.0 * self.swing;
+        let step = bar as usize * self.bar_steps + 2 * pair as usize + usize::from(second);
+        step.min(self.length_steps - 1)
+    }
+
+    /// Whether a step is the second of a pair in its bar (the one swing delays)
+    fn is_second_of_pair(&self, step: usize) -> bool {
+        (step % self.bar_steps) % 2 == 1
+    }
+
+    /// Position a step starts at, in steps
+    #[allow(clippy::cast_precision_loss)] // At most MAX_STEPS
+    fn step_start(&self, step: usize) -> f64 {
+        if self.is_second_of_pair(step) {
+            step as f64 - 1.0 + 2.0 * self.swing
+        } else {
+            step as f64
+        }
+    }
+
+    /// Queue the events of a new step: notes whose time is up end, then the
+    /// step's notes start (ending themselves first if still held)
+    fn queue_step(&mut self, step: usize) {
+        for note in 0..NUM_NOTES {
+            if self.remaining[note] > 0 {
+                self.remaining[note] -= 1;
+                if self.remaining[note] == 0 {
+                    #[allow(clippy::cast_possible_truncation)] // Below 128
+                    self.push(KeyEvent::NoteOff {
+                        note: note as u8,
+                        velocity: RELEASE_VELOCITY,
+                        channel: 0,
+                        voice_id: None,
+                    });
+                }
+            }
+        }
+
+        for index in 0..self.num_notes {
+            let PatternNote { note, length, .. } = self.notes[index];
+            if self.notes[index].step != step || length == 0 {
+                continue;
+            }
+            let key = usize::from(note);
+            if std::mem::take(&mut self.remaining[key]) > 0 {
+                self.push(KeyEvent::NoteOff {
+                    note,
+                    velocity: RELEASE_VELOCITY,
+                    channel: 0,
+                    voice_id: None,
+                });
+            }
+            self.push(KeyEvent::NoteOn {
+                note,
+                velocity: self.velocity,
+                channel: 0,
+                voice_id: None,
+            });
+            self.remaining[key] = usize::from(length).min(self.length_steps);
+        }
+    }
+
+    /// Note-off for every held note
+    fn release_all(&mut self) {
+        for note in 0..NUM_NOTES {
+            if std::mem::take(&mut self.remaining[note]) > 0 {
+                #[allow(clippy::cast_possible_truncation)] // Below 128
+                self.push(KeyEvent::NoteOff {
+                    note: note as u8,
+                    velocity: RELEASE_VELOCITY,
+                    channel: 0,
+                    voice_id: None,
+                });
+            }
+        }
+    }
+
+    fn push(&mut self, event: KeyEvent) {
+        if self.due.len() < self.due.capacity() {
+            self.due.push(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    /// At 120 BPM a sixteenth is 6000 samples
+    const STEP_SAMPLES: usize = 6000;
+
+    /// Run for a number of samples, collecting (sample, event)
+    fn run(player: &mut PatternPlayer, samples: usize) -> Vec<(usize, KeyEvent)> {
+        let mut events = Vec::new();
+        for sample in 0..samples {
+            while let Some(event) = player.pop_due() {
+                events.push((sample, event));
+            }
+            player.advance();
+        }
+        events
+    }
+
+    fn started_player(notes: &[(u8, u8, u8)]) -> PatternPlayer {
+        let mut player = PatternPlayer::new(SAMPLE_RATE);
+        player.set_notes(notes);
+        player.set_tempo(120.0);
+        player.sync_to_beats(0.0);
+        player.set_playing(true);
+        player
+    }
+
+    fn summary(events: &[(usize, KeyEvent)]) -> Vec<(usize, bool, u8)> {
+        events
+            .iter()
+            .map(|&(sample, event)| {
+                (
+                    sample,
+                    matches!(event, KeyEvent::NoteOn { .. }),
+                    event.note(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_notes_start_and_end_on_their_steps() {
+        let mut player = started_player(&[(0, 60, 2), (4, 67, 1), (15, 72, 1)]);
+        let events = run(&mut player, STEP_SAMPLES * STEPS_PER_BAR + 10);
+
+        assert_eq!(
+            summary(&events),
+            vec![
+                (0, true, 60),
+                (2 * STEP_SAMPLES, false, 60),
+                (4 * STEP_SAMPLES, true, 67),
+                (5 * STEP_SAMPLES, false, 67),
+                (15 * STEP_SAMPLES, true, 72),
+                // The bar loops: the last note ends as the first starts again
+                (16 * STEP_SAMPLES, false, 72),
+                (16 * STEP_SAMPLES, true, 60),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_longer_pattern_and_retrigger() {
+        // Note 60 every bar held for two bars: each repeat cuts the last short
+        let mut player = started_player(&[(0, 60, 32), (16, 60, 32)]);
+        player.set_bars(2);
+        let events = run(&mut player, STEP_SAMPLES * 32 + 10);
+
+        assert_eq!(
+            summary(&events),
+            vec![
+                (0, true, 60),
+                (16 * STEP_SAMPLES, false, 60),
+                (16 * STEP_SAMPLES, true, 60),
+                (32 * STEP_SAMPLES, false, 60),
+                (32 * STEP_SAMPLES, true, 60),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stopping_releases_held_notes() {
+        let mut player = started_player(&[(0, 60, 8), (0, 64, 8)]);
+        run(&mut player, STEP_SAMPLES);
+
+        // Notes removed while playing still end
+        player.set_notes(&[]);
+        player.set_playing(false);
+        let events = run(&mut player, STEP_SAMPLES * 16);
+        assert_eq!(summary(&events), vec![(0, false, 60), (0, false, 64)]);
+        assert_eq!(player.current_step(), None);
+    }
+
+    #[test]
+    fn test_follows_song_position() {
+        let mut player = PatternPlayer::new(SAMPLE_RATE);
+        player.set_notes(&[(6, 62, 1)]);
+        player.set_bars(1);
+
+        // Starting mid-step waits for the next step, which is step 6 at beat 1.25 + ε
+        player.sync_to_beats(4.0 + 1.4);
+        player.set_playing(true);
+        assert_eq!(player.current_step(), Some(5));
+        let events = run(&mut player, STEP_SAMPLES);
+        assert_eq!(summary(&events), vec![(2400, true, 62)]);
+
+        // Starting right on a step plays it at once
+        player.reset();
+        player.sync_to_beats(1.5);
+        player.set_playing(true);
+        assert_eq!(summary(&run(&mut player, 1)), vec![(0, true, 62)]);
+    }
+
+    #[test]
+    fn test_swing_delays_the_second_step_of_each_pair() {
+        let mut player = PatternPlayer::new(SAMPLE_RATE);
+        player.set_notes(&[(0, 60, 1), (1, 62, 1), (2, 64, 1), (3, 65, 1)]);
+        player.set_swing(MAX_SWING);
+        player.set_playing(true);
+        let events = run(&mut player, STEP_SAMPLES * 4);
+
+        // The first steps run long and the second ones short
+        assert_eq!(
+            summary(&events),
+            vec![
+                (0, true, 60),
+                (9000, false, 60),
+                (9000, true, 62),
+                (2 * STEP_SAMPLES, false, 62),
+                (2 * STEP_SAMPLES, true, 64),
+                (2 * STEP_SAMPLES + 9000, false, 64),
+                (2 * STEP_SAMPLES + 9000, true, 65),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_swing_changes_wait_for_the_next_pair() {
+        let mut player = started_player(&[(1, 62, 1), (3, 65, 1)]);
+
+        // Into step 1, then swing hard: step 1 isn't played again
+        let mut events = run(&mut player, STEP_SAMPLES + 100);
+        player.set_swing(MAX_SWING);
+        events.extend(
+            run(&mut player, 3 * STEP_SAMPLES)
+                .into_iter()
+                .map(|(sample, event)| (sample + STEP_SAMPLES + 100, event)),
+        );
+        assert_eq!(
+            summary(&events),
+            vec![
+                (STEP_SAMPLES, true, 62),
+                (2 * STEP_SAMPLES, false, 62),
+                (2 * STEP_SAMPLES + 9000, true, 65),
+                (4 * STEP_SAMPLES, false, 65),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bars_follow_the_time_signature() {
+        assert_eq!(bar_steps(6, 8), 12);
+        assert_eq!(bar_steps(5, 16), 5);
+        assert_eq!(bar_steps(0, 4), STEPS_PER_BAR);
+        assert_eq!(bar_steps(99, 1), MAX_STEPS);
+
+        // A bar of 3/4 loops after twelve steps
+        let mut player = started_player(&[(0, 60, 1)]);
+        player.set_time_signature(3, 4);
+        let events = run(&mut player, STEP_SAMPLES * 12 + 10);
+        assert_eq!(
+            summary(&events),
+            vec![
+                (0, true, 60),
+                (STEP_SAMPLES, false, 60),
+                (12 * STEP_SAMPLES, true, 60),
+            ]
+        );
+
+        // Four bars of 7/4 are cut short to fit; the host's bar number picks
+        // the bar of the loop
+        player.set_bars(4);
+        player.set_time_signature(7, 4);
+        player.sync_to_bar(5, 0.5);
+        assert_eq!(player.current_step(), Some(58));
+        player.set_time_signature(7, 8);
+        player.set_bars(2);
+        player.sync_to_bar(3, 0.5);
+        assert_eq!(player.current_step(), Some(16));
+    }
+
+    #[test]
+    fn test_grid_editing() {
+        let mut notes = PatternNotes::new();
+        toggle_note(&mut notes, 3, 60);
+        assert_eq!(notes, vec![(3, 60, 1)]);
+
+        stretch_note(&mut notes, 3, 60, 6);
+        assert_eq!(notes, vec![(3, 60, 4)]);
+        assert_eq!(note_index_at(&notes, 6, 60), Some(0));
+        assert_eq!(note_index_at(&notes, 7, 60), None);
+        assert_eq!(note_index_at(&notes, 4, 61), None);
+
+        // Clicking anywhere on a note removes it
+        toggle_note(&mut notes, 5, 60);
+        assert!(notes.is_empty());
+
+        // Dragging back past the start leaves one step; never past the end
+        stretch_note(&mut notes, 10, 48, 2);
+        stretch_note(&mut notes, 60, 50, 200);
+        toggle_note(&mut notes, MAX_STEPS, 50);
+        assert_eq!(notes, vec![(10, 48, 1), (60, 50, 4)]);
+    }
+
+    #[test]
+    fn test_presets_follow_the_bar() {
+        let dotted = PatternPreset::Dotted.notes(48, bar_steps(4, 4), 1);
+        assert_eq!(
+            dotted,
+            vec![
+                (0, 48, 3),
+                (3, 48, 3),
+                (6, 48, 2),
+                (8, 48, 3),
+                (11, 48, 3),
+                (14, 48, 2)
+            ]
+        );
+
+        // 7/8 bars cut the last group short, and each bar starts over
+        let dotted = PatternPreset::Dotted.notes(48, bar_steps(7, 8), 2);
+        let starts: Vec<u8> = dotted.iter().map(|&(start, ..)| start).collect();
+        assert_eq!(starts, vec![0, 3, 6, 8, 11, 14, 17, 20, 22, 25]);
+        assert_eq!(dotted[4], (11, 48, 3));
+        assert_eq!(dotted[9], (25, 48, 3));
+
+        let three_step = PatternPreset::ThreeStep.notes(60, bar_steps(4, 4), 2);
+        let starts: Vec<u8> = three_step.iter().map(|&(start, ..)| start).collect();
+        assert_eq!(starts, vec![0, 3, 6, 9, 12, 15, 16, 19, 22, 25, 28, 31]);
+
+        // Never past the longest pattern
+        let eighths = PatternPreset::Eighths.notes(60, bar_steps(15, 4), MAX_BARS);
+        assert_eq!(eighths.len(), MAX_STEPS / 2);
+        assert!(eighths
+            .iter()
+            .all(|&(start, _, length)| usize::from(start + length) <= MAX_STEPS));
+    }
+}
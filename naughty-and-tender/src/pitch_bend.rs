@@ -0,0 +1,222 @@
+//! Pitch bend for Naughty and Tender
+//!
+//! MIDI pitch bend is a 14-bit value with its center at 8192, so there are
+//! 8192 steps below center but only 8191 above. Each side is scaled on its own,
+//! so both extremes reach exactly a full bend and center is exactly no bend.
+//!
+//! Every voice slews its bend on the way in: each new value is reached in a
+//! straight line over a few milliseconds, however far it jumped. Controllers
+//! that only send a handful of coarse steps (7-bit wheels, touch strips, DAW
+//! automation at block rate) glide instead of zippering, and fine steps still
+//! arrive within the same short time. Notes start at the current bend without
+//! gliding in from center.
+//!
+//! # References
+//! - MIDI 1.0 Pitch Bend Change: 14-bit value, center 0x2000
+//! - RP-018 / RPN 0: pitch bend sensitivity, 2 semitones by default
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+/// 14-bit pitch bend value for no bend
+pub const BEND_CENTER: u16 = 8192;
+
+/// Highest 14-bit pitch bend value
+pub const BEND_MAX: u16 = 16383;
+
+/// Default bend range in semitones (MIDI's default sensitivity)
+pub const DEFAULT_BEND_RANGE: f32 = 2.0;
+
+/// Widest bend range in semitones
+pub const MAX_BEND_RANGE: f32 = 24.0;
+
+/// Time to reach each new bend value, in milliseconds
+pub const BEND_SLEW_MS: f32 = 5.0;
+
+/// Bend from a 14-bit value (-1.0 = full down, 0.0 = center, 1.0 = full up)
+#[must_use]
+pub fn bend_from_14bit(value: u16) -> f32 {
+    let value = value.min(BEND_MAX);
+    if value < BEND_CENTER {
+        (f32::from(value) - f32::from(BEND_CENTER)) / f32::from(BEND_CENTER)
+    } else {
+        f32::from(value - BEND_CENTER) / f32::from(BEND_MAX - BEND_CENTER)
+    }
+}
+
+/// Bend from a normalized value (0.0 - 1.0, as the host delivers it)
+///
+/// The value is snapped back to its 14-bit step first, so the host's
+/// `8192 / 16383` is center rather than slightly sharp.
+#[must_use]
+pub fn bend_from_normalized(value: f32) -> f32 {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Clamped to 0-16383
+    let value = (value.clamp(0.0, 1.0) * f32::from(BEND_MAX)).round() as u16;
+    bend_from_14bit(value)
+}
+
+/// Per-voice slew of the incoming bend
+///
+/// # Real-time Safety
+/// - Fixed state, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::pitch_bend::BendSlew;
+///
+/// let mut slew = BendSlew::new(48000.0);
+/// let first = slew.process(1.0);
+/// assert!(first > 0.0 && first < 0.01);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BendSlew {
+    /// Bend being slewed towards
+    target: f32,
+
+    /// Slewed bend
+    value: f32,
+
+    /// Change per sample until the target is reached
+    step: f32,
+
+    /// Samples each new target takes to reach
+    slew_samples: f32,
+}
+
+impl BendSlew {
+    /// Create a slew resting at center
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        let mut slew = Self {
+            target: 0.0,
+            value: 0.0,
+            step: 0.0,
+            slew_samples: 1.0,
+        };
+        slew.set_sample_rate(sample_rate);
+        slew
+    }
+
+    /// Set the rate `process()` is called at
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.slew_samples = (BEND_SLEW_MS / 1000.0 * sample_rate).max(1.0);
+    }
+
+    /// Move one sample towards `target`, returning the slewed bend
+    #[inline]
+    #[allow(clippy::float_cmp)] // Exact changes only, to restart the ramp
+    pub fn process(&mut self, target: f32) -> f32 {
+        if target != self.target {
+            self.target = target;
+            self.step = (target - self.value) / self.slew_samples;
+        }
+
+        let remaining = self.target - self.value;
+        if remaining.abs() <= self.step.abs() {
+            self.value = self.target;
+        } else {
+            self.value += self.step;
+        }
+        self.value
+    }
+
+    /// Slewed bend as of the last `process()`
+    #[must_use]
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Jump straight to `target` (call on note-on)
+    pub fn snap(&mut self, target: f32) {
+        self.target = target;
+        self.value = target;
+        self.step = 0.0;
+    }
+
+    /// Back to center
+    pub fn reset(&mut self) {
+        self.snap(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    #[test]
+    #[allow(clippy::float_cmp)] // Extremes and center are exact
+    fn test_14bit_extremes_and_center() {
+        assert_eq!(bend_from_14bit(0), -1.0);
+        assert_eq!(bend_from_14bit(BEND_CENTER), 0.0);
+        assert_eq!(bend_from_14bit(BEND_MAX), 1.0);
+        assert_eq!(bend_from_14bit(u16::MAX), 1.0);
+
+        // One step either side of center is the same size to within the
+        // asymmetry of the 14-bit range
+        let down = -bend_from_14bit(BEND_CENTER - 1);
+        let up = bend_from_14bit(BEND_CENTER + 1);
+        assert!((down - up).abs() < 1e-7, "{down} vs {up}");
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // Extremes and center are exact
+    fn test_normalized_extremes_and_center() {
+        assert_eq!(bend_from_normalized(0.0), -1.0);
+        assert_eq!(bend_from_normalized(0.5), 0.0);
+        assert_eq!(bend_from_normalized(1.0), 1.0);
+        assert_eq!(bend_from_normalized(-0.5), -1.0);
+        assert_eq!(bend_from_normalized(1.5), 1.0);
+
+        // What the host sends for a centered wheel
+        let center = f32::from(BEND_CENTER) / f32::from(BEND_MAX);
+        assert_eq!(bend_from_normalized(center), 0.0);
+    }
+
+    #[test]
+    fn test_halfway_is_symmetric() {
+        let down = bend_from_14bit(BEND_CENTER / 2);
+        let up = bend_from_14bit(BEND_CENTER + (BEND_MAX - BEND_CENTER) / 2);
+        assert!((down + 0.5).abs() < 1e-3, "{down}");
+        assert!((up - 0.5).abs() < 1e-3, "{up}");
+    }
+
+    #[test]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss,
+        clippy::float_cmp
+    )] // Exact landing, short test lengths
+    fn test_slew_reaches_any_step_in_slew_time() {
+        let slew_samples = (BEND_SLEW_MS / 1000.0 * SAMPLE_RATE) as usize;
+        for step in [1.0 / 64.0, 1.0, -2.0] {
+            let mut slew = BendSlew::new(SAMPLE_RATE);
+            slew.snap(0.5);
+            let target = 0.5 + step;
+
+            let mut previous = slew.process(target);
+            for _ in 1..slew_samples - 1 {
+                let value = slew.process(target);
+                // No jump bigger than an even share of the step
+                assert!((value - previous).abs() <= step.abs() / slew_samples as f32 * 1.01);
+                previous = value;
+            }
+            assert!((previous - target).abs() > 1e-6, "Arrived early");
+            for _ in 0..2 {
+                previous = slew.process(target);
+            }
+            assert_eq!(previous, target, "Should land exactly on the target");
+        }
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // Snapped values are exact
+    fn test_snap_skips_slew() {
+        let mut slew = BendSlew::new(SAMPLE_RATE);
+        slew.snap(-1.0);
+        assert_eq!(slew.process(-1.0), -1.0);
+        slew.reset();
+        assert_eq!(slew.process(0.0), 0.0);
+    }
+}
@@ -0,0 +1,198 @@
+//! Host polyphonic modulation for Naughty and Tender
+//!
+//! CLAP hosts (Bitwig and others) can modulate a parameter separately for every
+//! note they started, addressed by voice id. Filter cutoff, fine tune and gain
+//! accept it; the host sends each voice's offset as a normalized amount, which
+//! is kept here per voice id until the voice ends.
+//!
+//! The offsets are turned into plain pitch, cutoff and level offsets against
+//! the parameters' current values, and voices add them to their modulation
+//! matrix result at control rate, so they ramp in without zippering. An offset
+//! can arrive before its voice starts (a humanized or strummed note): the voice
+//! picks it up when it does.
+//!
+//! # References
+//! - CLAP `param_mod` events and the `voice-info` extension
+//! - nih-plug `poly_mod_synth` example: per-voice normalized offsets, rebased on
+//!   mono automation
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+/// Number of polyphonically modulated parameters
+pub const NUM_POLY_TARGETS: usize = 3;
+
+/// Most voice ids with offsets at once (offsets for more are dropped)
+pub const MAX_POLY_VOICES: usize = 64;
+
+/// Normalized offsets of one voice, by [`PolyTarget`] index
+pub type PolyOffsets = [f32; NUM_POLY_TARGETS];
+
+/// Parameter a host can modulate per voice
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolyTarget {
+    /// Filter cutoff (layer A)
+    Cutoff,
+    /// Fine tune
+    Pitch,
+    /// Master gain
+    Level,
+}
+
+impl PolyTarget {
+    /// Every target, in poly modulation id order
+    pub const ALL: [Self; NUM_POLY_TARGETS] = [Self::Cutoff, Self::Pitch, Self::Level];
+
+    /// Target of a host poly modulation id
+    #[must_use]
+    pub fn from_id(poly_modulation_id: u32) -> Option<Self> {
+        Self::ALL
+            .get(usize::try_from(poly_modulation_id).ok()?)
+            .copied()
+    }
+
+    /// Poly modulation id the parameter is registered with
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)] // NUM_POLY_TARGETS ids
+    pub const fn id(self) -> u32 {
+        self as u32
+    }
+}
+
+/// Normalized poly modulation offsets per voice id
+///
+/// # Real-time Safety
+/// - Fixed-size tables, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::poly_mod::{PolyModTable, PolyTarget};
+///
+/// let mut table = PolyModTable::new();
+/// table.set(7, PolyTarget::Cutoff, 0.25);
+/// assert_eq!(table.get(7), Some([0.25, 0.0, 0.0]));
+///
+/// table.remove(7);
+/// assert_eq!(table.get(7), None);
+/// ```
+pub struct PolyModTable {
+    /// Voice id of each entry (`None` = free)
+    voice_ids: [Option<i32>; MAX_POLY_VOICES],
+
+    /// Offsets of each entry
+    offsets: [PolyOffsets; MAX_POLY_VOICES],
+}
+
+impl Default for PolyModTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PolyModTable {
+    /// Create an empty table
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            voice_ids: [None; MAX_POLY_VOICES],
+            offsets: [[0.0; NUM_POLY_TARGETS]; MAX_POLY_VOICES],
+        }
+    }
+
+    /// Set a voice's offset for one target, returning all its offsets
+    ///
+    /// Returns `None` when the table is full and the voice has no entry yet.
+    pub fn set(
+        &mut self,
+        voice_id: i32,
+        target: PolyTarget,
+        normalized_offset: f32,
+    ) -> Option<PolyOffsets> {
+        let index = if let Some(index) = self.find(voice_id) {
+            index
+        } else {
+            let free = self.voice_ids.iter().position(Option::is_none)?;
+            self.voice_ids[free] = Some(voice_id);
+            self.offsets[free] = [0.0; NUM_POLY_TARGETS];
+            free
+        };
+        self.offsets[index][target as usize] = normalized_offset;
+        Some(self.offsets[index])
+    }
+
+    /// Offsets of a voice, if the host has modulated it
+    #[must_use]
+    pub fn get(&self, voice_id: i32) -> Option<PolyOffsets> {
+        self.find(voice_id).map(|index| self.offsets[index])
+    }
+
+    /// Forget a voice (call when it ends)
+    pub fn remove(&mut self, voice_id: i32) {
+        if let Some(index) = self.find(voice_id) {
+            self.voice_ids[index] = None;
+        }
+    }
+
+    /// Every modulated voice with its offsets
+    pub fn voices(&self) -> impl Iterator<Item = (i32, PolyOffsets)> + '_ {
+        self.voice_ids
+            .iter()
+            .zip(&self.offsets)
+            .filter_map(|(voice_id, offsets)| Some(((*voice_id)?, *offsets)))
+    }
+
+    /// Forget every voice
+    pub fn reset(&mut self) {
+        self.voice_ids = [None; MAX_POLY_VOICES];
+    }
+
+    fn find(&self, voice_id: i32) -> Option<usize> {
+        self.voice_ids.iter().position(|&id| id == Some(voice_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_ids_round_trip() {
+        for target in PolyTarget::ALL {
+            assert_eq!(PolyTarget::from_id(target.id()), Some(target));
+        }
+        let past_the_end = u32::try_from(NUM_POLY_TARGETS).unwrap();
+        assert_eq!(PolyTarget::from_id(past_the_end), None);
+    }
+
+    #[test]
+    fn test_offsets_kept_per_voice() {
+        let mut table = PolyModTable::new();
+        table.set(1, PolyTarget::Pitch, 0.5);
+        table.set(2, PolyTarget::Level, -0.25);
+        assert_eq!(table.set(1, PolyTarget::Cutoff, 0.1), Some([0.1, 0.5, 0.0]));
+        assert_eq!(table.get(2), Some([0.0, 0.0, -0.25]));
+
+        let mut voices: Vec<_> = table.voices().collect();
+        voices.sort_by_key(|(voice_id, _)| *voice_id);
+        assert_eq!(voices, vec![(1, [0.1, 0.5, 0.0]), (2, [0.0, 0.0, -0.25])]);
+    }
+
+    #[test]
+    fn test_removed_voice_starts_fresh() {
+        let mut table = PolyModTable::new();
+        table.set(3, PolyTarget::Cutoff, 0.7);
+        table.remove(3);
+        assert_eq!(table.set(3, PolyTarget::Level, 0.2), Some([0.0, 0.0, 0.2]));
+    }
+
+    #[test]
+    fn test_full_table_drops_new_voices() {
+        let mut table = PolyModTable::new();
+        for voice_id in (0..).take(MAX_POLY_VOICES) {
+            assert!(table.set(voice_id, PolyTarget::Pitch, 0.1).is_some());
+        }
+        assert_eq!(table.set(-1, PolyTarget::Pitch, 0.1), None);
+
+        // Voices already in the table can still change
+        assert!(table.set(0, PolyTarget::Level, 0.1).is_some());
+    }
+}
@@ -0,0 +1,502 @@
+//! Presets for Naughty and Tender
+//!
+//! A preset is a small text file: metadata (name, category, tags) followed by one
+//! `parameter-id = normalized value` line per parameter. Plain text keeps presets
+//! diffable and easy to write by hand:
+//!
+//! ```text
+//! name = Glass Pad
+//! category = Pad
+//! tags = soft, slow attack
+//!
+//! [params]
+//! gain = 0.5
+//! attack = 0.31
+//! ```
+//!
+//! Parameters are stored by ID, so presets survive parameters being added or
+//! reordered; parameters a preset doesn't mention load at their defaults.
+//!
+//! The [`PresetIndex`] holds the metadata of every preset in the presets directory
+//! for the browser. Building it reads every file, so it belongs off the GUI thread.
+//!
+//! # References
+//! - nih-plug parameter IDs (the same IDs the host uses for automation)
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use std::collections::BTreeSet;
+use std::fmt::{self, Write as _};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// File extension of preset files
+pub const PRESET_EXTENSION: &str = "ntpreset";
+
+/// Category of presets that don't name one
+pub const DEFAULT_CATEGORY: &str = "Uncategorized";
+
+/// File in the presets directory listing the favorite presets
+const FAVORITES_FILE: &str = "favorites.txt";
+
+/// Header of the parameter section
+const PARAMS_SECTION: &str = "[params]";
+
+/// Why a preset couldn't be read or written
+#[derive(Debug)]
+pub enum PresetError {
+    Io(io::Error),
+    /// Malformed line (1-based line number)
+    Parse {
+        line: usize,
+        reason: &'static str,
+    },
+}
+
+impl fmt::Display for PresetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Parse { line, reason } => write!(f, "line {line}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for PresetError {}
+
+impl From<io::Error> for PresetError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// Preset metadata shown in the browser
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresetInfo {
+    pub name: String,
+    pub category: String,
+    pub tags: Vec<String>,
+}
+
+impl Default for PresetInfo {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            category: DEFAULT_CATEGORY.to_string(),
+            tags: Vec::new(),
+        }
+    }
+}
+
+/// A complete preset: metadata and normalized parameter values
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::presets::Preset;
+///
+/// let preset = Preset::parse("name = Init\n[params]\ngain = 0.5\n").unwrap();
+/// assert_eq!(preset.info.name, "Init");
+/// assert_eq!(preset.values, vec![("gain".to_string(), 0.5)]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Preset {
+    pub info: PresetInfo,
+
+    /// (parameter ID, normalized value) pairs
+    pub values: Vec<(String, f32)>,
+}
+
+impl Preset {
+    /// Parse a preset file's contents
+    ///
+    /// Unknown metadata keys are ignored; parameter values are clamped to 0.0 - 1.0.
+    ///
+    /// # Errors
+    /// [`PresetError::Parse`] for a line that isn't `key = value`, or a parameter
+    /// value that isn't a number.
+    pub fn parse(text: &str) -> Result<Self, PresetError> {
+        let mut preset = Self::default();
+        let mut in_params = false;
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line == PARAMS_SECTION {
+                in_params = true;
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(PresetError::Parse {
+                    line: index + 1,
+                    reason: "expected `key = value`",
+                });
+            };
+            let (key, value) = (key.trim(), value.trim());
+
+            if in_params {
+                let value: f32 = value.parse().map_err(|_| PresetError::Parse {
+                    line: index + 1,
+                    reason: "parameter value is not a number",
+                })?;
+                preset.values.push((key.to_string(), value.clamp(0.0, 1.0)));
+            } else {
+                match key {
+                    "name" => preset.info.name = value.to_string(),
+                    "category" if !value.is_empty() => preset.info.category = value.to_string(),
+                    "tags" => {
+                        preset.info.tags = value
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|tag| !tag.is_empty())
+                            .map(str::to_string)
+                            .collect();
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(preset)
+    }
+
+    /// Preset file contents
+    #[must_use]
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "name = {}\ncategory = {}\ntags = {}\n\n{PARAMS_SECTION}\n",
+            self.info.name,
+            self.info.category,
+            self.info.tags.join(", ")
+        );
+        for (id, value) in &self.values {
+            let _ = writeln!(text, "{id} = {value}");
+        }
+        text
+    }
+
+    /// Read a preset file (an unnamed preset takes the file's name)
+    ///
+    /// # Errors
+    /// [`PresetError::Io`] if the file can't be read, or a parse error.
+    pub fn load(path: &Path) -> Result<Self, PresetError> {
+        let mut preset = Self::parse(&fs::read_to_string(path)?)?;
+        if preset.info.name.is_empty() {
+            preset.info.name = file_stem(path);
+        }
+        Ok(preset)
+    }
+
+    /// Write the preset, creating missing directories
+    ///
+    /// # Errors
+    /// [`PresetError::Io`] if the file or its directory can't be written.
+    pub fn save(&self, path: &Path) -> Result<(), PresetError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_text())?;
+        Ok(())
+    }
+}
+
+/// One preset in the index
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresetEntry {
+    pub info: PresetInfo,
+    pub path: PathBuf,
+}
+
+impl PresetEntry {
+    /// Whether the name, category or a tag contains `query` (ignoring case)
+    #[must_use]
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        query.is_empty()
+            || self.info.name.to_lowercase().contains(&query)
+            || self.info.category.to_lowercase().contains(&query)
+            || self
+                .info
+                .tags
+                .iter()
+                .any(|tag| tag.to_lowercase().contains(&query))
+    }
+}
+
+/// Metadata of every preset in a directory tree, sorted by category then name
+#[derive(Debug, Clone, Default)]
+pub struct PresetIndex {
+    entries: Vec<PresetEntry>,
+}
+
+impl PresetIndex {
+    /// Read every preset under `dir` (unreadable files are skipped)
+    #[must_use]
+    pub fn scan(dir: &Path) -> Self {
+        let mut entries = Vec::new();
+        let mut pending = vec![dir.to_path_buf()];
+
+        while let Some(dir) = pending.pop() {
+            let Ok(read_dir) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for path in read_dir.flatten().map(|entry| entry.path()) {
+                if path.is_dir() {
+                    pending.push(path);
+                } else if path.extension().is_some_and(|ext| ext == PRESET_EXTENSION) {
+                    if let Ok(preset) = Preset::load(&path) {
+                        entries.push(PresetEntry {
+                            info: preset.info,
+                            path,
+                        });
+                    }
+                }
+            }
+        }
+
+        Self::from_entries(entries)
+    }
+
+    /// Build an index from entries (sorted by category, then name)
+    #[must_use]
+    pub fn from_entries(mut entries: Vec<PresetEntry>) -> Self {
+        entries.sort_by(|a, b| {
+            (&a.info.category, a.info.name.to_lowercase())
+                .cmp(&(&b.info.category, b.info.name.to_lowercase()))
+        });
+        Self { entries }
+    }
+
+    /// Every preset
+    #[must_use]
+    pub fn entries(&self) -> &[PresetEntry] {
+        &self.entries
+    }
+
+    /// Distinct categories, sorted
+    #[must_use]
+    pub fn categories(&self) -> Vec<&str> {
+        let categories: BTreeSet<&str> = self
+            .entries
+            .iter()
+            .map(|entry| entry.info.category.as_str())
+            .collect();
+        categories.into_iter().collect()
+    }
+
+    /// Presets matching a search query, optionally limited to one category
+    pub fn filter<'a>(
+        &'a self,
+        query: &'a str,
+        category: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a PresetEntry> + 'a {
+        self.entries.iter().filter(move |entry| {
+            category.is_none_or(|category| entry.info.category == category) && entry.matches(query)
+        })
+    }
+}
+
+/// Favorite presets, saved as one path per line in the presets directory
+#[derive(Debug, Clone, Default)]
+pub struct Favorites {
+    paths: BTreeSet<PathBuf>,
+}
+
+impl Favorites {
+    /// Read the favorites list (missing file = no favorites)
+    #[must_use]
+    pub fn load(dir: &Path) -> Self {
+        let paths = fs::read_to_string(dir.join(FAVORITES_FILE))
+            .map(|text| {
+                text.lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(|line| dir.join(line))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { paths }
+    }
+
+    /// Write the favorites list, relative to the presets directory
+    ///
+    /// # Errors
+    /// [`PresetError::Io`] if the file can't be written.
+    pub fn save(&self, dir: &Path) -> Result<(), PresetError> {
+        let mut text = String::new();
+        for path in &self.paths {
+            let _ = writeln!(text, "{}", path.strip_prefix(dir).unwrap_or(path).display());
+        }
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join(FAVORITES_FILE), text)?;
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn contains(&self, path: &Path) -> bool {
+        self.paths.contains(path)
+    }
+
+    /// Add or remove a favorite
+    pub fn toggle(&mut self, path: &Path) {
+        if !self.paths.remove(path) {
+            self.paths.insert(path.to_path_buf());
+        }
+    }
+}
+
+/// Default presets directory for the current user
+///
+/// - Windows: `%APPDATA%\Naughty and Tender\Presets`
+/// - macOS: `~/Library/Audio/Presets/Naughty and Tender`
+/// - Linux: `$XDG_DATA_HOME/naughty-and-tender/presets` (or `~/.local/share/...`)
+#[must_use]
+pub fn presets_dir() -> Option<PathBuf> {
+    let env_dir = |name: &str| std::env::var_os(name).map(PathBuf::from);
+
+    if cfg!(target_os = "windows") {
+        env_dir("APPDATA").map(|dir| dir.join("Naughty and Tender").join("Presets"))
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME").map(|dir| dir.join("Library/Audio/Presets").join("Naughty and Tender"))
+    } else {
+        env_dir("XDG_DATA_HOME")
+            .or_else(|| env_dir("HOME").map(|dir| dir.join(".local/share")))
+            .map(|dir| dir.join("naughty-and-tender").join("presets"))
+    }
+}
+
+/// File name for a new preset, with characters that aren't safe in file names
+/// replaced
+#[must_use]
+pub fn file_name(name: &str) -> String {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let stem = if stem.is_empty() { "Untitled" } else { &stem };
+    format!("{stem}.{PRESET_EXTENSION}")
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, category: &str, tags: &[&str]) -> PresetEntry {
+        PresetEntry {
+            info: PresetInfo {
+                name: name.to_string(),
+                category: category.to_string(),
+                tags: tags.iter().map(|tag| (*tag).to_string()).collect(),
+            },
+            path: PathBuf::from(file_name(name)),
+        }
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let preset = Preset {
+            info: PresetInfo {
+                name: "Glass Pad".to_string(),
+                category: "Pad".to_string(),
+                tags: vec!["soft".to_string(), "slow attack".to_string()],
+            },
+            values: vec![("gain".to_string(), 0.5), ("attack".to_string(), 0.25)],
+        };
+
+        assert_eq!(Preset::parse(&preset.to_text()).unwrap(), preset);
+    }
+
+    #[test]
+    fn test_missing_metadata_uses_defaults() {
+        let preset = Preset::parse("# comment\n[params]\ngain = 1.5\n").unwrap();
+        assert_eq!(preset.info.category, DEFAULT_CATEGORY);
+        assert!(preset.info.tags.is_empty());
+        assert_eq!(preset.values, vec![("gain".to_string(), 1.0)], "Clamped");
+    }
+
+    #[test]
+    fn test_parse_errors_report_line() {
+        let error = Preset::parse("name = X\n[params]\ngain = loud\n").unwrap_err();
+        assert!(matches!(error, PresetError::Parse { line: 3, .. }));
+
+        let error = Preset::parse("just words\n").unwrap_err();
+        assert!(matches!(error, PresetError::Parse { line: 1, .. }));
+    }
+
+    #[test]
+    fn test_search_matches_name_category_and_tags() {
+        let pad = entry("Glass Pad", "Pad", &["Airy"]);
+        assert!(pad.matches("glass"));
+        assert!(pad.matches("PAD"));
+        assert!(pad.matches("air"));
+        assert!(pad.matches("  "));
+        assert!(!pad.matches("bass"));
+    }
+
+    #[test]
+    fn test_index_sorts_and_filters() {
+        let index = PresetIndex::from_entries(vec![
+            entry("Sub", "Bass", &[]),
+            entry("glass", "Pad", &[]),
+            entry("Acid", "Bass", &["303"]),
+        ]);
+
+        let names: Vec<&str> = index
+            .entries()
+            .iter()
+            .map(|entry| entry.info.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Acid", "Sub", "glass"]);
+        assert_eq!(index.categories(), vec!["Bass", "Pad"]);
+        assert_eq!(index.filter("", Some("Bass")).count(), 2);
+        assert_eq!(index.filter("303", None).count(), 1);
+    }
+
+    #[test]
+    fn test_scan_and_favorites() {
+        let dir = std::env::temp_dir().join(format!("nt-presets-{}", std::process::id()));
+        let preset = Preset {
+            info: PresetInfo {
+                name: "Pluck".to_string(),
+                ..PresetInfo::default()
+            },
+            values: Vec::new(),
+        };
+        let path = dir.join("Keys").join(file_name("Pluck"));
+        preset.save(&path).unwrap();
+        fs::write(dir.join("notes.txt"), "not a preset").unwrap();
+
+        let index = PresetIndex::scan(&dir);
+        assert_eq!(index.entries().len(), 1);
+        assert_eq!(index.entries()[0].path, path);
+
+        let mut favorites = Favorites::default();
+        favorites.toggle(&path);
+        favorites.save(&dir).unwrap();
+        assert!(Favorites::load(&dir).contains(&path));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_name_is_sanitized() {
+        assert_eq!(file_name("Bass: Sub/Low"), "Bass_ Sub_Low.ntpreset");
+        assert_eq!(file_name(""), "Untitled.ntpreset");
+    }
+}
@@ -0,0 +1,49 @@
+//! Debug probe outputs for Naughty and Tender
+//!
+//! Built with the `debug-outputs` feature, the plugin has extra auxiliary
+//! output ports carrying signals from inside a voice, so they can be recorded
+//! and inspected in the DAW next to the main output: the engine's output going
+//! into the voice filter, the filter's output, and the voice's finished output
+//! (after the envelope, before panning).
+//!
+//! The probed voice is the most recently started voice of layer A, so playing
+//! one note at a time probes that note. Each port is stereo with the same mono
+//! signal on both channels, which every host routes without fuss. Without the
+//! feature the ports, the taps and their per-sample stores don't exist.
+//!
+//! # References
+//! - Test points on analog synth voice boards; "probe" outputs in modular
+//!   environments (Reaktor, VCV Rack's scope taps)
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+/// Number of probe ports
+pub const NUM_PROBES: usize = 3;
+
+/// Aux output port names, in [`ProbeTaps::values`] order
+pub const PROBE_NAMES: [&str; NUM_PROBES] =
+    ["Probe Pre-Filter", "Probe Post-Filter", "Probe Voice"];
+
+/// Signals inside one voice at its last processed sample
+///
+/// # Real-time Safety
+/// - Plain values, copied per sample
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ProbeTaps {
+    /// Engine output (with any gated input mixed in), into the voice filter
+    pub pre_filter: f32,
+
+    /// Voice filter output (the pre-filter signal with the filter off)
+    pub post_filter: f32,
+
+    /// Voice output: after the limiter, drive, envelope and fades, before pan
+    pub voice: f32,
+}
+
+impl ProbeTaps {
+    /// Every tap, in port order
+    #[must_use]
+    pub fn values(&self) -> [f32; NUM_PROBES] {
+        [self.pre_filter, self.post_filter, self.voice]
+    }
+}
@@ -0,0 +1,602 @@
+//! MIDI program change preset switching for Naughty and Tender
+//!
+//! A controller's program change, optionally preceded by bank select (CC 0 and
+//! CC 32), picks a preset. As with CC mapping, the audio thread can't load presets
+//! itself: it posts the program to a [`ProgramInbox`], and the editor looks the
+//! preset up and loads it. Program changes are only followed while the editor is
+//! open.
+//!
+//! The [`ProgramMap`] turns programs into presets: explicit assignments first,
+//! then the presets in browser order, 128 per bank (bank 0 program 0 is the
+//! first preset).
+//!
+//! So a mid-note switch doesn't click, the audio thread fades its output out
+//! ([`PatchFade`]), waits for the editor to apply the new patch (or gives up
+//! after a short timeout), then fades back in.
+//!
+//! The voice transitions skip the fade and deal with the sounding voices
+//! instead, for every preset load (the editor announces each one through the
+//! inbox): they either fade out quickly or ring on with the settings they
+//! started with, while new notes play the new patch straight away.
+//!
+//! # References
+//! - MIDI 1.0 Program Change and Bank Select (CC 0 MSB, CC 32 LSB)
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::presets::{PresetEntry, PresetIndex};
+
+/// Programs in one bank
+pub const PROGRAMS_PER_BANK: usize = 128;
+
+/// Bank select MSB controller
+pub const BANK_SELECT_MSB: u8 = 0;
+
+/// Bank select LSB controller
+pub const BANK_SELECT_LSB: u8 = 32;
+
+/// Length of the fade out and back in, in milliseconds
+const FADE_MS: f32 = 10.0;
+
+/// Longest wait at silence for the editor to apply a patch, in milliseconds
+const WAIT_TIMEOUT_MS: f32 = 250.0;
+
+/// Marks the inbox as holding a program not yet taken
+const PENDING: u32 = 1 << 31;
+
+/// A bank and a program within it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Program {
+    /// 14-bit bank number (MSB * 128 + LSB)
+    pub bank: u16,
+    pub program: u8,
+}
+
+impl Program {
+    /// Position in the preset list when no assignment covers this program
+    #[must_use]
+    pub fn index(self) -> usize {
+        usize::from(self.bank) * PROGRAMS_PER_BANK + usize::from(self.program)
+    }
+}
+
+/// What happens to sounding notes when the patch changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProgramTransition {
+    /// Fade out, switch, fade back in; held notes carry on with the new patch
+    #[default]
+    Fade,
+    /// Fade out, stop every voice, switch, fade back in (a preset loaded from
+    /// the browser fades the voices out quickly instead)
+    Reset,
+    /// Switch immediately (may click)
+    Instant,
+    /// Sounding voices fade out quickly with their old settings; new notes
+    /// play the new patch at once
+    FadeVoices,
+    /// Sounding voices ring on with their old settings until they end; new
+    /// notes play the new patch
+    LetRing,
+}
+
+impl ProgramTransition {
+    /// Every transition, in parameter index order
+    pub const ALL: [Self; 5] = [
+        Self::Fade,
+        Self::Reset,
+        Self::Instant,
+        Self::FadeVoices,
+        Self::LetRing,
+    ];
+
+    /// Display names, in parameter index order
+    pub const NAMES: [&'static str; 5] =
+        ["Fade", "Reset Voices", "Instant", "Fade Voices", "Let Ring"];
+
+    /// Transition at a parameter index (out-of-range falls back to `Fade`)
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or_default()
+    }
+
+    /// Whether a program change fades the whole output around the switch
+    #[must_use]
+    pub fn dips_output(self) -> bool {
+        matches!(self, Self::Fade | Self::Reset)
+    }
+
+    /// Whether sounding voices keep their settings through a preset load
+    #[must_use]
+    pub fn pins_voices(self) -> bool {
+        matches!(self, Self::Reset | Self::FadeVoices | Self::LetRing)
+    }
+
+    /// Whether sounding voices fade out when a preset loads
+    #[must_use]
+    pub fn fades_voices(self) -> bool {
+        matches!(self, Self::Reset | Self::FadeVoices)
+    }
+}
+
+/// Running bank select state from CC 0 and CC 32
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::programs::{BankSelect, Program};
+///
+/// let mut bank = BankSelect::default();
+/// bank.handle_cc(0, 1);
+/// bank.handle_cc(32, 2);
+/// assert_eq!(bank.program(5), Program { bank: 130, program: 5 });
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BankSelect {
+    msb: u8,
+    lsb: u8,
+}
+
+impl BankSelect {
+    /// Track a controller (value 0 - 127), returning whether it was bank select
+    pub fn handle_cc(&mut self, cc: u8, value: u8) -> bool {
+        match cc {
+            BANK_SELECT_MSB => self.msb = value & 0x7F,
+            BANK_SELECT_LSB => self.lsb = value & 0x7F,
+            _ => return false,
+        }
+        true
+    }
+
+    /// A program in the currently selected bank
+    #[must_use]
+    pub fn program(self, program: u8) -> Program {
+        Program {
+            bank: u16::from(self.msb) << 7 | u16::from(self.lsb),
+            program: program & 0x7F,
+        }
+    }
+}
+
+/// Latest program change, posted by the audio thread for the editor
+///
+/// Also counts the patches the editor has applied, so the audio thread knows
+/// when to fade back in, and the preset loads it's about to apply, so the
+/// voices can be handed over first.
+///
+/// # Real-time Safety
+/// - Atomics only; `post` and `applied` are single relaxed operations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::programs::{Program, ProgramInbox};
+///
+/// let inbox = ProgramInbox::new();
+/// inbox.post(Program { bank: 0, program: 3 });
+/// assert_eq!(inbox.take(), Some(Program { bank: 0, program: 3 }));
+/// assert_eq!(inbox.take(), None);
+/// ```
+#[derive(Debug, Default)]
+pub struct ProgramInbox {
+    /// Bank, program and the pending flag
+    pending: AtomicU32,
+
+    /// Patches applied by the editor (wrapping count)
+    applied: AtomicU32,
+
+    /// Preset loads announced by the editor (wrapping count)
+    loads: AtomicU32,
+}
+
+impl ProgramInbox {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Post a program change from the audio thread (replaces any untaken one)
+    pub fn post(&self, program: Program) {
+        let packed = PENDING | u32::from(program.bank) << 8 | u32::from(program.program);
+        self.pending.store(packed, Ordering::Relaxed);
+    }
+
+    /// Take the latest program change, if any
+    pub fn take(&self) -> Option<Program> {
+        let packed = self.pending.swap(0, Ordering::Relaxed);
+        #[allow(clippy::cast_possible_truncation)] // Masked to the packed fields
+        let program = Program {
+            bank: (packed >> 8 & 0x3FFF) as u16,
+            program: (packed & 0x7F) as u8,
+        };
+        (packed & PENDING != 0).then_some(program)
+    }
+
+    /// Report that the patch for the last program change is in place
+    pub fn mark_applied(&self) {
+        self.applied.fetch_add(1, Ordering::Release);
+    }
+
+    /// Number of patches applied so far
+    #[must_use]
+    pub fn applied(&self) -> u32 {
+        self.applied.load(Ordering::Acquire)
+    }
+
+    /// Report that a preset's values are about to be set (editor thread,
+    /// before the first one)
+    pub fn announce_load(&self) {
+        self.loads.fetch_add(1, Ordering::Release);
+    }
+
+    /// Number of preset loads announced so far
+    #[must_use]
+    pub fn loads(&self) -> u32 {
+        self.loads.load(Ordering::Acquire)
+    }
+}
+
+/// Explicit program-to-preset assignments (the bank map)
+///
+/// Presets are assigned by name, so the map survives rescans and renamed files.
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::programs::{Program, ProgramMap};
+///
+/// let mut map = ProgramMap::default();
+/// let program = Program { bank: 1, program: 0 };
+/// map.assign(program, "Glass Pad");
+/// assert_eq!(map.preset_for(program), Some("Glass Pad"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProgramMap {
+    /// Sorted by program
+    assignments: Vec<(Program, String)>,
+}
+
+impl ProgramMap {
+    /// Build a map from saved (bank, program, preset name) triples
+    #[must_use]
+    pub fn from_saved(saved: &[(u16, u8, String)]) -> Self {
+        let mut map = Self::default();
+        for (bank, program, name) in saved {
+            map.assign(
+                Program {
+                    bank: *bank,
+                    program: *program,
+                },
+                name,
+            );
+        }
+        map
+    }
+
+    /// Assignments as (bank, program, preset name) triples for saving
+    #[must_use]
+    pub fn to_saved(&self) -> Vec<(u16, u8, String)> {
+        self.assignments
+            .iter()
+            .map(|(program, name)| (program.bank, program.program, name.clone()))
+            .collect()
+    }
+
+    /// Assign a preset to a program (replacing any previous assignment)
+    pub fn assign(&mut self, program: Program, preset: &str) {
+        match self.assignments.binary_search_by_key(&program, |(p, _)| *p) {
+            Ok(i) => self.assignments[i].1 = preset.to_string(),
+            Err(i) => self.assignments.insert(i, (program, preset.to_string())),
+        }
+    }
+
+    /// Remove a program's assignment
+    pub fn remove(&mut self, program: Program) {
+        self.assignments.retain(|(p, _)| *p != program);
+    }
+
+    /// Every assignment, in program order
+    #[must_use]
+    pub fn assignments(&self) -> &[(Program, String)] {
+        &self.assignments
+    }
+
+    /// Preset name explicitly assigned to a program
+    #[must_use]
+    pub fn preset_for(&self, program: Program) -> Option<&str> {
+        self.assignments
+            .iter()
+            .find_map(|(p, name)| (*p == program).then_some(name.as_str()))
+    }
+
+    /// Preset a program selects: its assignment, else the preset at its position
+    #[must_use]
+    pub fn resolve<'a>(&self, program: Program, index: &'a PresetIndex) -> Option<&'a PresetEntry> {
+        match self.preset_for(program) {
+            Some(name) => index.entries().iter().find(|entry| entry.info.name == name),
+            None => index.entries().get(program.index()),
+        }
+    }
+}
+
+/// Output fade around a patch change
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FadeStage {
+    Idle,
+    Out,
+    /// Silent until the editor applies the patch
+    Waiting,
+    In,
+}
+
+/// Gain ramp that hides a patch change (see the module docs)
+///
+/// # Real-time Safety
+/// - Fixed state, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::programs::PatchFade;
+///
+/// let mut fade = PatchFade::new(48000.0);
+/// fade.start(0);
+/// let gain = fade.process(0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PatchFade {
+    stage: FadeStage,
+    gain: f32,
+
+    /// Gain change per sample while fading
+    step: f32,
+
+    /// Applied-patch count when the fade started
+    generation: u32,
+
+    /// Samples left before giving up on the editor
+    timeout: u32,
+
+    /// Longest wait in samples
+    max_wait: u32,
+
+    /// Set when the fade reaches silence, until taken
+    silent: bool,
+}
+
+impl PatchFade {
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Positive, bounded
+        let max_wait = (WAIT_TIMEOUT_MS / 1000.0 * sample_rate) as u32;
+        Self {
+            stage: FadeStage::Idle,
+            gain: 1.0,
+            step: 1000.0 / (FADE_MS * sample_rate),
+            generation: 0,
+            timeout: 0,
+            max_wait,
+            silent: false,
+        }
+    }
+
+    /// Fade out for a patch change; `applied` is the inbox's current count
+    pub fn start(&mut self, applied: u32) {
+        self.generation = applied;
+        self.stage = FadeStage::Out;
+    }
+
+    /// Whether a fade is in progress
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.stage != FadeStage::Idle
+    }
+
+    /// Returns true once each time the fade reaches silence (to reset voices)
+    pub fn take_silence(&mut self) -> bool {
+        std::mem::take(&mut self.silent)
+    }
+
+    /// Advance one sample, returning the output gain
+    ///
+    /// `applied` is the inbox's current count of applied patches.
+    #[inline]
+    pub fn process(&mut self, applied: u32) -> f32 {
+        match self.stage {
+            FadeStage::Idle => {}
+            FadeStage::Out => {
+                self.gain -= self.step;
+                if self.gain <= 0.0 {
+                    self.gain = 0.0;
+                    self.stage = FadeStage::Waiting;
+                    self.timeout = self.max_wait;
+                    self.silent = true;
+                }
+            }
+            FadeStage::Waiting => {
+                self.timeout = self.timeout.saturating_sub(1);
+                if applied != self.generation || self.timeout == 0 {
+                    self.stage = FadeStage::In;
+                }
+            }
+            FadeStage::In => {
+                self.gain += self.step;
+                if self.gain >= 1.0 {
+                    self.gain = 1.0;
+                    self.stage = FadeStage::Idle;
+                }
+            }
+        }
+        self.gain
+    }
+
+    /// Cancel any fade and return to full gain
+    pub fn reset(&mut self) {
+        self.stage = FadeStage::Idle;
+        self.gain = 1.0;
+        self.silent = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presets::PresetInfo;
+    use std::path::PathBuf;
+
+    const SAMPLE_RATE: f32 = 1000.0;
+
+    fn index(names: &[&str]) -> PresetIndex {
+        PresetIndex::from_entries(
+            names
+                .iter()
+                .map(|name| PresetEntry {
+                    info: PresetInfo {
+                        name: (*name).to_string(),
+                        ..PresetInfo::default()
+                    },
+                    path: PathBuf::from(format!("{name}.ntpreset")),
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_inbox_keeps_latest_program() {
+        let inbox = ProgramInbox::new();
+        inbox.post(Program {
+            bank: 3,
+            program: 1,
+        });
+        inbox.post(Program {
+            bank: 16383,
+            program: 127,
+        });
+        assert_eq!(
+            inbox.take(),
+            Some(Program {
+                bank: 16383,
+                program: 127
+            })
+        );
+        assert_eq!(inbox.take(), None);
+    }
+
+    #[test]
+    fn test_bank_select_ignores_other_ccs() {
+        let mut bank = BankSelect::default();
+        assert!(!bank.handle_cc(7, 100));
+        assert!(bank.handle_cc(BANK_SELECT_MSB, 2));
+        assert_eq!(bank.program(0).bank, 256);
+    }
+
+    #[test]
+    fn test_unassigned_programs_follow_browser_order() {
+        let presets = index(&["A", "B", "C"]);
+        let map = ProgramMap::default();
+        let second = map.resolve(
+            Program {
+                bank: 0,
+                program: 1,
+            },
+            &presets,
+        );
+        assert_eq!(second.map(|entry| entry.info.name.as_str()), Some("B"));
+        assert!(map
+            .resolve(
+                Program {
+                    bank: 1,
+                    program: 0
+                },
+                &presets
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn test_assignment_overrides_position() {
+        let presets = index(&["A", "B", "C"]);
+        let mut map = ProgramMap::default();
+        let program = Program {
+            bank: 0,
+            program: 0,
+        };
+        map.assign(program, "C");
+        assert_eq!(
+            map.resolve(program, &presets)
+                .map(|entry| entry.info.name.as_str()),
+            Some("C")
+        );
+
+        map.remove(program);
+        assert_eq!(
+            map.resolve(program, &presets)
+                .map(|entry| entry.info.name.as_str()),
+            Some("A")
+        );
+    }
+
+    #[test]
+    fn test_map_round_trips_through_saved_form() {
+        let mut map = ProgramMap::default();
+        map.assign(
+            Program {
+                bank: 2,
+                program: 9,
+            },
+            "Keys",
+        );
+        map.assign(
+            Program {
+                bank: 0,
+                program: 4,
+            },
+            "Bass",
+        );
+        assert_eq!(ProgramMap::from_saved(&map.to_saved()), map);
+        assert_eq!(map.assignments()[0].1, "Bass");
+    }
+
+    #[test]
+    fn test_fade_waits_for_patch() {
+        let mut fade = PatchFade::new(SAMPLE_RATE);
+        fade.start(0);
+
+        // 10 ms fade out at 1 kHz
+        for _ in 0..10 {
+            fade.process(0);
+        }
+        assert!(fade.process(0).abs() < f32::EPSILON);
+        assert!(fade.take_silence());
+        assert!(!fade.take_silence());
+
+        // Still silent until the patch is applied
+        for _ in 0..50 {
+            assert!(fade.process(0).abs() < f32::EPSILON);
+        }
+        for _ in 0..12 {
+            fade.process(1);
+        }
+        assert!((fade.process(1) - 1.0).abs() < f32::EPSILON);
+        assert!(!fade.is_active());
+    }
+
+    #[test]
+    fn test_voice_transitions_skip_the_fade() {
+        for transition in [ProgramTransition::FadeVoices, ProgramTransition::LetRing] {
+            assert!(!transition.dips_output(), "{transition:?}");
+            assert!(transition.pins_voices(), "{transition:?}");
+        }
+        assert!(!ProgramTransition::LetRing.fades_voices());
+        assert!(!ProgramTransition::Fade.pins_voices());
+        assert_eq!(ProgramTransition::from_index(4), ProgramTransition::LetRing);
+        assert_eq!(ProgramTransition::ALL.len(), ProgramTransition::NAMES.len());
+    }
+
+    #[test]
+    fn test_fade_gives_up_without_editor() {
+        let mut fade = PatchFade::new(SAMPLE_RATE);
+        fade.start(0);
+        for _ in 0..300 {
+            fade.process(0);
+        }
+        assert!((fade.process(0) - 1.0).abs() < f32::EPSILON);
+    }
+}
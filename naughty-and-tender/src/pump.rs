@@ -0,0 +1,290 @@
+//! Tempo-synced ducking ("auto-pump") for Naughty and Tender
+//!
+//! Emulates the classic sidechain pump without a sidechain: the master output
+//! is ducked at the start of every beat division and swells back up before the
+//! next, as if a kick drum on the grid were compressing the mix.
+//!
+//! Each cycle dips over a few milliseconds (so the dip never clicks), then
+//! recovers for the rest of the division. Shape sets how the recovery goes: a
+//! straight ramp at 0, a quick snap back that leaves a short, punchy dip at 1.
+//!
+//! While the host is playing, the cycle is locked to its song position, so the
+//! dips land on the grid; stopped, it free-runs at the host tempo.
+//!
+//! # References
+//! - Sidechain compression "pumping" in house and EDM production
+//! - LFO-based volume shapers (Cableguys `VolumeShaper`, Xfer `LFOTool`)
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use shared_core::tempo::relock_position;
+
+/// Time to dip at the start of each cycle, in milliseconds
+pub const PUMP_ATTACK_MS: f32 = 2.0;
+
+/// Time for depth changes (and switching on or off) to ramp in, in milliseconds
+pub const PUMP_DEPTH_RAMP_MS: f32 = 20.0;
+
+/// Steepest recovery curve (at shape 1.0)
+const MAX_CURVE: f32 = 8.0;
+
+/// Tempo-synced ducking gain for the master output
+///
+/// # Real-time Safety
+/// - Fixed state, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::pump::Pump;
+///
+/// let mut pump = Pump::new(48000.0);
+/// pump.set_period_ms(500.0); // 1/4 at 120 BPM
+/// pump.set_depth(0.8);
+/// let gain = pump.process();
+/// assert!((0.0..=1.0).contains(&gain));
+/// ```
+pub struct Pump {
+    /// Position in the current cycle (0.0 to 1.0)
+    phase: f64,
+
+    /// Phase advance per sample
+    increment: f64,
+
+    /// Fraction of the cycle spent dipping
+    attack_fraction: f32,
+
+    /// Depth being ramped towards (0.0 - 1.0)
+    depth_target: f32,
+
+    /// Ramped depth
+    depth: f32,
+
+    /// Largest depth change per sample
+    depth_step: f32,
+
+    /// Recovery curve exponent (1.0 = linear)
+    curve: f32,
+
+    /// Sample rate in Hz
+    sample_rate: f32,
+}
+
+impl Pump {
+    /// Create a pump at zero depth (1/4 at 120 BPM)
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        let mut pump = Self {
+            phase: 0.0,
+            increment: 0.0,
+            attack_fraction: 0.0,
+            depth_target: 0.0,
+            depth: 0.0,
+            depth_step: 1.0,
+            curve: 1.0,
+            sample_rate,
+        };
+        pump.set_sample_rate(sample_rate);
+        pump
+    }
+
+    /// Set the sample rate in Hz
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.depth_step = 1.0 / (PUMP_DEPTH_RAMP_MS / 1000.0 * sample_rate).max(1.0);
+        self.set_period_ms(500.0);
+    }
+
+    /// Set how long each cycle lasts (usually a tempo-synced note division)
+    pub fn set_period_ms(&mut self, period_ms: f32) {
+        let period_samples = (period_ms / 1000.0 * self.sample_rate).max(1.0);
+        self.increment = 1.0 / f64::from(period_samples);
+        self.attack_fraction = (PUMP_ATTACK_MS / period_ms.max(f32::EPSILON)).min(0.25);
+    }
+
+    /// Set how far the output ducks (0.0 = not at all, 1.0 = to silence)
+    pub fn set_depth(&mut self, depth: f32) {
+        self.depth_target = depth.clamp(0.0, 1.0);
+    }
+
+    /// Set the recovery shape (0.0 = linear swell, 1.0 = quick snap back)
+    pub fn set_shape(&mut self, shape: f32) {
+        self.curve = 1.0 + shape.clamp(0.0, 1.0) * (MAX_CURVE - 1.0);
+    }
+
+    /// Lock the cycle to the host's song position (call every block; it only
+    /// jumps when the song position has moved away)
+    ///
+    /// # Arguments
+    /// * `position_beats` - Song position in quarter notes
+    /// * `period_beats` - Length of one cycle in quarter notes
+    pub fn sync_to_beats(&mut self, position_beats: f64, period_beats: f64) {
+        if period_beats > 0.0 {
+            let target = (position_beats / period_beats).rem_euclid(1.0);
+            if let Some(phase) = relock_position(self.phase, target, 1.0, self.increment) {
+                self.phase = phase;
+            }
+        }
+    }
+
+    /// Whether the pump is ducking at all (or still ramping out)
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.depth > 0.0 || self.depth_target > 0.0
+    }
+
+    /// Generate the next output gain (1.0 = no ducking)
+    #[inline]
+    pub fn process(&mut self) -> f32 {
+        let depth_change = self.depth_target - self.depth;
+        self.depth += depth_change.clamp(-self.depth_step, self.depth_step);
+
+        #[allow(clippy::cast_possible_truncation)] // Phase is 0..1
+        let duck = self.duck(self.phase as f32);
+        self.phase += self.increment;
+        if self.phase >= 1.0 {
+            self.phase -= 1.0;
+        }
+
+        1.0 - self.depth * duck
+    }
+
+    /// Back to the start of a cycle
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+        self.depth = self.depth_target;
+    }
+
+    /// How far down the output is at `phase` (0.0 = not ducked, 1.0 = fully)
+    fn duck(&self, phase: f32) -> f32 {
+        if phase < self.attack_fraction {
+            phase / self.attack_fraction
+        } else {
+            let recovery = (phase - self.attack_fraction) / (1.0 - self.attack_fraction);
+            (1.0 - recovery).max(0.0).powf(self.curve)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    /// Gains of one full 500 ms cycle at full depth
+    fn cycle(shape: f32) -> Vec<f32> {
+        let mut pump = Pump::new(SAMPLE_RATE);
+        pump.set_period_ms(500.0);
+        pump.set_depth(1.0);
+        pump.set_shape(shape);
+        pump.reset();
+        (0..24000).map(|_| pump.process()).collect()
+    }
+
+    #[test]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::float_cmp
+    )] // Full gain is exact, short test lengths
+    fn test_dips_without_clicking_then_recovers() {
+        let gains = cycle(0.0);
+        assert_eq!(gains[0], 1.0, "Cycle starts undipped");
+
+        // Fully down after the attack, back up by the end of the cycle
+        let attack_samples = (PUMP_ATTACK_MS / 1000.0 * SAMPLE_RATE) as usize;
+        assert!(gains[attack_samples + 1] < 0.01);
+        assert!(gains[23999] > 0.99);
+
+        let largest_step = gains
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).abs())
+            .fold(0.0, f32::max);
+        assert!(largest_step < 0.02, "Step of {largest_step}");
+    }
+
+    #[test]
+    fn test_shape_shortens_the_dip() {
+        let linear = cycle(0.0);
+        let punchy = cycle(1.0);
+        assert!((linear[12000] - 0.5).abs() < 0.01, "{}", linear[12000]);
+        assert!(punchy[6000] > 0.8, "{}", punchy[6000]);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // Full gain is exact
+    fn test_sync_locks_dip_to_the_beat() {
+        let mut pump = Pump::new(SAMPLE_RATE);
+        pump.set_period_ms(500.0);
+        pump.set_depth(1.0);
+        pump.reset();
+
+        // Halfway through a quarter-note cycle, then on the next beat
+        pump.sync_to_beats(2.5, 1.0);
+        assert!((pump.process() - 0.5).abs() < 0.01);
+        pump.sync_to_beats(3.0, 1.0);
+        assert_eq!(pump.process(), 1.0);
+        for _ in 0..200 {
+            pump.process();
+        }
+        assert!(pump.process() < 0.01);
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)] // Short test lengths
+    fn test_synced_output_ignores_block_size() {
+        // The host reports the playhead at the start of every block, and at
+        // 123 BPM it drifts from the pump's own count by a fraction of a sample
+        let render = |block_size: usize| {
+            let mut pump = Pump::new(SAMPLE_RATE);
+            pump.set_period_ms(60_000.0 / 123.0);
+            pump.set_depth(1.0);
+            pump.reset();
+
+            let beats_per_sample = 123.0 / 60.0 / f64::from(SAMPLE_RATE);
+            let mut gains = Vec::new();
+            for start in (0..48000).step_by(block_size) {
+                pump.sync_to_beats(0.3 + start as f64 * beats_per_sample, 1.0);
+                let end = (start + block_size).min(48000);
+                gains.extend((start..end).map(|_| pump.process()));
+            }
+            gains
+        };
+
+        let reference = render(1024);
+        for block_size in [1, 17, 64] {
+            assert!(
+                render(block_size) == reference,
+                "Block size {block_size} renders differently"
+            );
+        }
+    }
+
+    #[test]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::float_cmp
+    )] // Full gain is exact, short test lengths
+    fn test_depth_ramps_in_and_out() {
+        let mut pump = Pump::new(SAMPLE_RATE);
+        pump.set_period_ms(500.0);
+        assert!(!pump.is_active());
+        assert_eq!(pump.process(), 1.0);
+
+        pump.set_depth(1.0);
+        assert!(pump.is_active());
+        for _ in 0..200 {
+            pump.process();
+        }
+        let ramping = pump.process();
+        assert!(ramping > 0.5 && ramping < 1.0, "{ramping}");
+
+        pump.set_depth(0.0);
+        for _ in 0..=(PUMP_DEPTH_RAMP_MS / 1000.0 * SAMPLE_RATE) as usize {
+            pump.process();
+        }
+        assert!(!pump.is_active());
+        assert_eq!(pump.process(), 1.0);
+    }
+}
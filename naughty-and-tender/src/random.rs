@@ -0,0 +1,209 @@
+//! Sample-and-hold random modulation source for Naughty and Tender
+//!
+//! On every clock tick a new random value (-1.0 to 1.0) is picked and held until
+//! the next tick. An optional slew (lag) glides between held values, turning the
+//! classic stepped S&H into smooth random wandering.
+//!
+//! Each voice owns its own source with its own seed, so a chord gets a different
+//! random value per note. A new value is also picked at every note-on.
+//!
+//! # References
+//! - Buchla 266 / ARP 2600 sample-and-hold on a noise source
+//! - One-pole lag: `y += (x - y) * (1 - e^(-1 / (time * sample_rate)))`
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use shared_core::noise::NoiseGenerator;
+
+/// Clocked sample-and-hold random source
+///
+/// # Real-time Safety
+/// - Fixed state, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::random::SampleAndHold;
+///
+/// let mut random = SampleAndHold::new(48000.0, 7);
+/// random.set_rate_hz(8.0);
+/// random.set_slew_ms(20.0);
+/// let modulation = random.process();
+/// ```
+pub struct SampleAndHold {
+    noise: NoiseGenerator,
+
+    /// Clock phase (0.0 to 1.0)
+    phase: f32,
+
+    /// Clock phase advance per sample
+    increment: f32,
+
+    /// Value picked at the last tick
+    held: f32,
+
+    /// Slewed output
+    output: f32,
+
+    /// Lag filter coefficient (1.0 = no slew)
+    slew_coeff: f32,
+
+    /// Slew time in milliseconds (kept to recompute the coefficient)
+    slew_ms: f32,
+
+    /// Sample rate in Hz
+    sample_rate: f32,
+}
+
+impl SampleAndHold {
+    /// Create a new source ticking at 4 Hz
+    ///
+    /// The seed is scrambled first, so neighbouring seeds (e.g. voice indices)
+    /// give unrelated sequences from the very first value.
+    #[must_use]
+    pub fn new(sample_rate: f32, seed: u32) -> Self {
+        let mut random = Self {
+            noise: NoiseGenerator::new(seed.wrapping_add(1).wrapping_mul(0x9E37_79B9)),
+            phase: 0.0,
+            increment: 0.0,
+            held: 0.0,
+            output: 0.0,
+            slew_coeff: 1.0,
+            slew_ms: 0.0,
+            sample_rate,
+        };
+        random.set_rate_hz(4.0);
+        random
+    }
+
+    /// Set clock rate in Hz (tempo-synced rates are converted by the caller)
+    pub fn set_rate_hz(&mut self, rate_hz: f32) {
+        self.increment = rate_hz.max(0.0) / self.sample_rate;
+    }
+
+    /// Set slew time in milliseconds (0 = hard steps)
+    pub fn set_slew_ms(&mut self, slew_ms: f32) {
+        self.slew_ms = slew_ms;
+        self.slew_coeff = if slew_ms <= 0.0 {
+            1.0
+        } else {
+            1.0 - (-1.0 / (slew_ms / 1000.0 * self.sample_rate)).exp()
+        };
+    }
+
+    /// Change the rate `process()` is called at, keeping the clock rate and slew
+    /// time (e.g. to run at control rate)
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.increment *= self.sample_rate / sample_rate;
+        self.sample_rate = sample_rate;
+        self.set_slew_ms(self.slew_ms);
+    }
+
+    /// Pick a new value and restart the clock (call on note-on)
+    pub fn trigger(&mut self) {
+        self.phase = 0.0;
+        self.held = self.noise.next_bipolar();
+        if self.slew_coeff >= 1.0 {
+            self.output = self.held;
+        }
+    }
+
+    /// Generate the next modulation value (-1.0 to 1.0)
+    #[inline]
+    pub fn process(&mut self) -> f32 {
+        self.phase += self.increment;
+        if self.phase >= 1.0 {
+            self.phase -= 1.0;
+            self.held = self.noise.next_bipolar();
+        }
+
+        self.output += (self.held - self.output) * self.slew_coeff;
+        self.output
+    }
+
+    /// Clear the held value and clock
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+        self.held = 0.0;
+        self.output = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    #[test]
+    fn test_holds_between_ticks() {
+        let mut random = SampleAndHold::new(SAMPLE_RATE, 1);
+        random.set_rate_hz(10.0); // New value every 4800 samples
+        random.trigger();
+
+        let first = random.process();
+        for _ in 0..4000 {
+            assert!((random.process() - first).abs() < 1e-7, "Value should hold");
+        }
+
+        for _ in 0..1000 {
+            random.process();
+        }
+        assert!(
+            (random.process() - first).abs() > 1e-4,
+            "Value should change after a tick"
+        );
+    }
+
+    #[test]
+    fn test_output_is_bipolar_and_varied() {
+        let mut random = SampleAndHold::new(SAMPLE_RATE, 3);
+        random.set_rate_hz(1000.0);
+
+        let values: Vec<f32> = (0..48000).map(|_| random.process()).collect();
+        assert!(values.iter().all(|v| (-1.0..=1.0).contains(v)));
+        assert!(values.iter().any(|v| *v < -0.5) && values.iter().any(|v| *v > 0.5));
+    }
+
+    #[test]
+    fn test_slew_limits_jumps() {
+        let mut random = SampleAndHold::new(SAMPLE_RATE, 5);
+        random.set_rate_hz(50.0);
+        random.set_slew_ms(10.0);
+
+        let mut previous = random.process();
+        for _ in 0..48000 {
+            let value = random.process();
+            assert!(
+                (value - previous).abs() < 0.01,
+                "Slewed output should not jump"
+            );
+            previous = value;
+        }
+    }
+
+    #[test]
+    fn test_different_seeds_differ() {
+        let mut a = SampleAndHold::new(SAMPLE_RATE, 1);
+        let mut b = SampleAndHold::new(SAMPLE_RATE, 2);
+        a.trigger();
+        b.trigger();
+        assert!((a.process() - b.process()).abs() > 1e-4);
+    }
+
+    #[test]
+    fn test_control_rate_keeps_clock_rate() {
+        let mut random = SampleAndHold::new(SAMPLE_RATE, 9);
+        random.set_rate_hz(10.0);
+        random.set_sample_rate(SAMPLE_RATE / 16.0); // New value every 300 calls
+        random.trigger();
+
+        let first = random.process();
+        for _ in 0..290 {
+            assert!((random.process() - first).abs() < 1e-7, "Value should hold");
+        }
+        for _ in 0..20 {
+            random.process();
+        }
+        assert!((random.process() - first).abs() > 1e-4);
+    }
+}
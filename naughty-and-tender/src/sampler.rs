@@ -0,0 +1,783 @@
+//! Sample playback for Naughty and Tender
+//!
+//! The sampler engine plays a WAV file instead of an oscillator waveform. Each
+//! voice reads the sample at a rate set by the played pitch relative to the root
+//! note, so higher notes play faster and higher, like a tape or classic sampler.
+//! Playback starts at an adjustable offset and either stops at the end (one-shot)
+//! or cycles between two loop points. In granular mode the sample is played as a
+//! cloud of short grains instead (see `granular`).
+//!
+//! WAV files are decoded off the audio thread (see `tasks`) and handed over
+//! through a [`SampleSlot`]; voices share the decoded frames through an `Arc`.
+//!
+//! # References
+//! - Playback rate = (f / `f_root`) * (`file_rate` / `output_rate`)
+//! - Catmull-Rom (4-point cubic Hermite) interpolation
+//! - Microsoft RIFF WAVE format: `fmt ` and `data` chunks, PCM and IEEE float
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::granular::{GrainCloud, GranularSettings};
+use crate::voice::midi_note_to_frequency;
+
+/// Note at which a sample plays at its recorded pitch, unless set otherwise
+pub const DEFAULT_ROOT_NOTE: u8 = 60;
+
+/// Shortest loop, in frames (shorter loops are widened)
+const MIN_LOOP_FRAMES: f64 = 2.0;
+
+/// WAV format tags
+const FORMAT_PCM: u16 = 1;
+const FORMAT_FLOAT: u16 = 3;
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Why a WAV file couldn't be loaded
+#[derive(Debug)]
+pub enum WavError {
+    Io(io::Error),
+    /// Not a WAV file, or a damaged one
+    Invalid(&'static str),
+    /// A valid WAV file in an encoding the sampler doesn't decode
+    Unsupported(String),
+}
+
+impl fmt::Display for WavError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Invalid(reason) => write!(f, "not a valid WAV file: {reason}"),
+            Self::Unsupported(format) => write!(f, "unsupported WAV encoding: {format}"),
+        }
+    }
+}
+
+impl std::error::Error for WavError {}
+
+impl From<io::Error> for WavError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// Sample interpolation between stored frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// Straight line between neighbours: cheap, slightly dull when pitched down
+    #[default]
+    Linear,
+    /// Curve through four neighbours: smoother, fewer artifacts
+    Cubic,
+}
+
+impl Interpolation {
+    /// Every mode, in parameter index order
+    pub const ALL: [Self; 2] = [Self::Linear, Self::Cubic];
+
+    /// Display names, in parameter index order
+    pub const NAMES: [&'static str; 2] = ["Linear", "Cubic"];
+
+    /// Mode at a parameter index (out-of-range falls back to `Linear`)
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or_default()
+    }
+
+    /// Read frames at a fractional position (edge frames are held beyond the ends)
+    #[inline]
+    #[must_use]
+    pub fn read(self, frames: &[f32], position: f64) -> f32 {
+        match self {
+            Self::Linear => read_linear(frames, position),
+            Self::Cubic => read_cubic(frames, position),
+        }
+    }
+}
+
+/// Decoded mono sample
+///
+/// Multi-channel files are mixed down to mono when loaded.
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::sampler::SampleData;
+///
+/// let sample = SampleData::new(vec![0.0, 0.5, 1.0], 44100.0);
+/// assert_eq!(sample.len(), 3);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleData {
+    frames: Vec<f32>,
+
+    /// Sample rate the file was recorded at, in Hz
+    sample_rate: f32,
+}
+
+impl SampleData {
+    #[must_use]
+    pub fn new(frames: Vec<f32>, sample_rate: f32) -> Self {
+        Self {
+            frames,
+            sample_rate,
+        }
+    }
+
+    /// Read and decode a WAV file
+    ///
+    /// # Errors
+    /// [`WavError::Io`] if the file can't be read, otherwise as [`Self::from_wav`].
+    pub fn load(path: &Path) -> Result<Self, WavError> {
+        Self::from_wav(&fs::read(path)?)
+    }
+
+    /// Decode a WAV file held in memory
+    ///
+    /// Supports 8, 16, 24 and 32-bit integer PCM and 32 and 64-bit float, with any
+    /// number of channels.
+    ///
+    /// # Errors
+    /// [`WavError::Invalid`] if the data isn't a well-formed WAV file;
+    /// [`WavError::Unsupported`] for other encodings (compressed formats).
+    pub fn from_wav(bytes: &[u8]) -> Result<Self, WavError> {
+        if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return Err(WavError::Invalid("missing RIFF/WAVE header"));
+        }
+
+        let mut format = None;
+        let mut data = None;
+        let mut rest = &bytes[12..];
+        while rest.len() >= 8 {
+            let id = &rest[0..4];
+            let size = usize::try_from(read_u32(&rest[4..8])).unwrap_or(usize::MAX);
+            let body = rest
+                .get(8..8usize.saturating_add(size))
+                // Tolerate a truncated final data chunk (common from crashed recorders)
+                .or_else(|| (id == b"data").then(|| &rest[8..]))
+                .ok_or(WavError::Invalid("chunk runs past the end of the file"))?;
+
+            match id {
+                b"fmt " => format = Some(WavFormat::parse(body)?),
+                b"data" => data = Some(body),
+                _ => {}
+            }
+
+            // Chunks are padded to an even length
+            let next = 8 + body.len() + body.len() % 2;
+            rest = rest.get(next..).unwrap_or_default();
+        }
+
+        let format = format.ok_or(WavError::Invalid("no fmt chunk"))?;
+        let data = data.ok_or(WavError::Invalid("no data chunk"))?;
+        Ok(Self::new(format.decode(data), format.sample_rate))
+    }
+
+    /// Frames, one per sample period
+    #[must_use]
+    pub fn frames(&self) -> &[f32] {
+        &self.frames
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Recorded sample rate in Hz
+    #[must_use]
+    pub fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    /// Length in seconds
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // Display only
+    pub fn duration_s(&self) -> f32 {
+        self.frames.len() as f32 / self.sample_rate
+    }
+}
+
+/// Sample encoding from a `fmt ` chunk
+struct WavFormat {
+    /// `FORMAT_PCM` or `FORMAT_FLOAT` (extensible files are resolved to these)
+    tag: u16,
+    channels: usize,
+    sample_rate: f32,
+    bits: u16,
+}
+
+impl WavFormat {
+    fn parse(chunk: &[u8]) -> Result<Self, WavError> {
+        if chunk.len() < 16 {
+            return Err(WavError::Invalid("fmt chunk too short"));
+        }
+
+        let mut tag = read_u16(&chunk[0..2]);
+        if tag == FORMAT_EXTENSIBLE {
+            // The real format is the first two bytes of the sub-format GUID
+            tag = chunk
+                .get(24..26)
+                .map(read_u16)
+                .ok_or(WavError::Invalid("extensible fmt chunk too short"))?;
+        }
+
+        #[allow(clippy::cast_precision_loss)] // Audio sample rates are well below 2^24
+        let sample_rate = read_u32(&chunk[4..8]) as f32;
+        let format = Self {
+            tag,
+            channels: usize::from(read_u16(&chunk[2..4])),
+            sample_rate,
+            bits: read_u16(&chunk[14..16]),
+        };
+
+        if format.channels == 0 || format.sample_rate <= 0.0 {
+            return Err(WavError::Invalid("no channels or zero sample rate"));
+        }
+        match (format.tag, format.bits) {
+            (FORMAT_PCM, 8 | 16 | 24 | 32) | (FORMAT_FLOAT, 32 | 64) => Ok(format),
+            (FORMAT_PCM | FORMAT_FLOAT, bits) => {
+                Err(WavError::Unsupported(format!("{bits}-bit samples")))
+            }
+            (tag, _) => Err(WavError::Unsupported(format!("format tag {tag:#06x}"))),
+        }
+    }
+
+    /// Decode interleaved data, mixing all channels to mono
+    fn decode(&self, data: &[u8]) -> Vec<f32> {
+        let bytes_per_sample = usize::from(self.bits / 8);
+        let frame_bytes = bytes_per_sample * self.channels;
+
+        #[allow(clippy::cast_precision_loss)] // Channel counts are tiny
+        let channel_scale = 1.0 / self.channels as f32;
+
+        data.chunks_exact(frame_bytes)
+            .map(|frame| {
+                frame
+                    .chunks_exact(bytes_per_sample)
+                    .map(|sample| self.decode_sample(sample))
+                    .sum::<f32>()
+                    * channel_scale
+            })
+            .collect()
+    }
+
+    /// One sample as -1.0 to 1.0
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)] // Intended
+    fn decode_sample(&self, bytes: &[u8]) -> f32 {
+        match (self.tag, self.bits) {
+            (FORMAT_FLOAT, 32) => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            (FORMAT_FLOAT, _) => {
+                let mut raw = [0; 8];
+                raw.copy_from_slice(&bytes[..8]);
+                f64::from_le_bytes(raw) as f32
+            }
+            // 8-bit WAV is unsigned, centred on 128
+            (_, 8) => (f32::from(bytes[0]) - 128.0) / 128.0,
+            (_, 16) => f32::from(i16::from_le_bytes([bytes[0], bytes[1]])) / 32768.0,
+            // Shift 24-bit samples to the top of an i32 so the sign is kept
+            (_, 24) => {
+                i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) as f32 / 2_147_483_648.0
+            }
+            _ => {
+                i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32
+                    / 2_147_483_648.0
+            }
+        }
+    }
+}
+
+fn read_u16(bytes: &[u8]) -> u16 {
+    u16::from_le_bytes([bytes[0], bytes[1]])
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// The loaded sample, handed from the loader to the audio thread
+///
+/// The loader replaces the sample; the audio thread checks a generation counter
+/// each block and only then takes the (non-blocking) lock to pick it up. Replaced
+/// samples are kept here until no voice holds them, so the audio thread never
+/// frees sample memory.
+///
+/// # Real-time Safety
+/// - `fetch` is one atomic load, plus a `try_lock` and an `Arc` clone on change
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::sampler::{SampleData, SampleSlot};
+/// use std::sync::Arc;
+///
+/// let slot = SampleSlot::new();
+/// let mut seen = 0;
+/// assert!(slot.fetch(&mut seen).is_none());
+///
+/// slot.set(Arc::new(SampleData::new(vec![0.0; 4], 44100.0)));
+/// assert!(slot.fetch(&mut seen).is_some());
+/// assert!(slot.fetch(&mut seen).is_none());
+/// ```
+#[derive(Debug, Default)]
+pub struct SampleSlot {
+    current: Mutex<Option<Arc<SampleData>>>,
+
+    /// Bumped on every `set`
+    generation: AtomicU32,
+
+    /// Replaced samples that voices may still be playing
+    retired: Mutex<Vec<Arc<SampleData>>>,
+}
+
+impl SampleSlot {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the sample (not on the audio thread)
+    pub fn set(&self, sample: Arc<SampleData>) {
+        let previous = self
+            .current
+            .lock()
+            .map(|mut current| current.replace(sample))
+            .unwrap_or_default();
+
+        if let Ok(mut retired) = self.retired.lock() {
+            // Free samples nobody plays any more, keep the one just replaced
+            retired.retain(|sample| Arc::strong_count(sample) > 1);
+            retired.extend(previous);
+        }
+        self.generation.fetch_add(1, Ordering::Release);
+    }
+
+    /// The current sample, if any
+    #[must_use]
+    pub fn current(&self) -> Option<Arc<SampleData>> {
+        self.current.lock().ok().and_then(|current| current.clone())
+    }
+
+    /// The sample, if it changed since generation `seen` (which is then updated)
+    ///
+    /// If the loader holds the lock the change is picked up on a later call.
+    pub fn fetch(&self, seen: &mut u32) -> Option<Arc<SampleData>> {
+        let generation = self.generation.load(Ordering::Acquire);
+        if generation == *seen {
+            return None;
+        }
+
+        let current = self.current.try_lock().ok()?;
+        *seen = generation;
+        current.clone()
+    }
+}
+
+/// Pitched, looping sample reader for one voice
+///
+/// # Real-time Safety
+/// - No allocations; the sample is shared and never freed here while in use
+/// - The grain pool is allocated inline at construction
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::sampler::{SampleData, SamplePlayer};
+/// use std::sync::Arc;
+///
+/// let mut player = SamplePlayer::new(44100.0);
+/// player.set_sample(Some(Arc::new(SampleData::new(vec![0.25; 100], 44100.0))));
+/// player.trigger();
+///
+/// // At the root note the sample plays back unchanged
+/// assert_eq!(player.process(player.root_frequency()), 0.25);
+/// ```
+pub struct SamplePlayer {
+    sample: Option<Arc<SampleData>>,
+
+    /// Read position in frames
+    position: f64,
+
+    /// Whether playback is running (one-shots stop at the end)
+    playing: bool,
+
+    interpolation: Interpolation,
+
+    looping: bool,
+
+    /// Loop points as fractions of the sample length (0.0 - 1.0)
+    loop_start: f32,
+    loop_end: f32,
+
+    /// Where playback starts, as a fraction of the sample length (0.0 - 1.0)
+    start_offset: f32,
+
+    /// Frequency at which the sample plays at its recorded pitch, in Hz
+    root_frequency: f32,
+
+    /// Play grains instead of reading straight through
+    granular: bool,
+
+    grains: GrainCloud,
+
+    /// Output sample rate in Hz
+    sample_rate: f32,
+}
+
+impl SamplePlayer {
+    /// Create a player with no sample
+    ///
+    /// # Default Settings
+    /// - One-shot, linear interpolation, full-length loop points
+    /// - Root note: middle C (`DEFAULT_ROOT_NOTE`)
+    /// - Granular mode off
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        Self::with_seed(sample_rate, 0)
+    }
+
+    /// Create a player whose grain randomness uses `seed`
+    #[must_use]
+    pub fn with_seed(sample_rate: f32, seed: u32) -> Self {
+        let mut player = Self {
+            sample: None,
+            position: 0.0,
+            playing: false,
+            interpolation: Interpolation::Linear,
+            looping: false,
+            loop_start: 0.0,
+            loop_end: 1.0,
+            start_offset: 0.0,
+            root_frequency: 0.0,
+            granular: false,
+            grains: GrainCloud::new(sample_rate, seed),
+            sample_rate,
+        };
+        player.set_root_note(DEFAULT_ROOT_NOTE);
+        player
+    }
+
+    /// Replace the sample (stops playback)
+    pub fn set_sample(&mut self, sample: Option<Arc<SampleData>>) {
+        self.sample = sample;
+        self.reset();
+    }
+
+    /// Whether a sample is loaded
+    #[must_use]
+    pub fn has_sample(&self) -> bool {
+        self.sample
+            .as_ref()
+            .is_some_and(|sample| !sample.is_empty())
+    }
+
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolation = interpolation;
+    }
+
+    /// Set looping and the loop points (fractions of the sample length)
+    pub fn set_loop(&mut self, looping: bool, start: f32, end: f32) {
+        self.looping = looping;
+        self.loop_start = start.clamp(0.0, 1.0);
+        self.loop_end = end.clamp(0.0, 1.0);
+    }
+
+    /// Set where playback starts (fraction of the sample length, next trigger)
+    pub fn set_start_offset(&mut self, offset: f32) {
+        self.start_offset = offset.clamp(0.0, 1.0);
+    }
+
+    /// Switch granular mode and update the grain settings
+    pub fn set_granular(&mut self, granular: bool, settings: GranularSettings) {
+        self.granular = granular;
+        self.grains.set_settings(settings);
+    }
+
+    /// Set the MIDI note at which the sample plays at its recorded pitch
+    pub fn set_root_note(&mut self, note: u8) {
+        self.root_frequency = midi_note_to_frequency(note);
+    }
+
+    #[must_use]
+    pub fn root_frequency(&self) -> f32 {
+        self.root_frequency
+    }
+
+    /// Start playback from the start offset (or a new grain cloud)
+    pub fn trigger(&mut self) {
+        let Some(sample) = &self.sample else {
+            return;
+        };
+        #[allow(clippy::cast_precision_loss)] // Sample lengths are well below 2^52
+        let length = sample.len() as f64;
+        self.position = f64::from(self.start_offset) * length;
+        self.playing = !sample.is_empty();
+        self.grains.trigger();
+    }
+
+    /// Stop playback
+    pub fn reset(&mut self) {
+        self.playing = false;
+        self.position = 0.0;
+        self.grains.reset();
+    }
+
+    /// Whether the player is still producing sound
+    #[must_use]
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Generate one sample, playing at `frequency` relative to the root note
+    ///
+    /// Granular voices play until released; the loop and start settings don't
+    /// apply to them.
+    #[inline]
+    pub fn process(&mut self, frequency: f32) -> f32 {
+        if !self.playing {
+            return 0.0;
+        }
+        let Some(sample) = &self.sample else {
+            return 0.0;
+        };
+
+        let frames = sample.frames();
+        let rate = frequency / self.root_frequency * sample.sample_rate() / self.sample_rate;
+        if self.granular {
+            return self.grains.process(frames, rate, self.interpolation);
+        }
+
+        let output = self.interpolation.read(frames, self.position);
+        self.position += f64::from(rate.max(0.0));
+
+        #[allow(clippy::cast_precision_loss)] // Sample lengths are well below 2^52
+        let length = frames.len() as f64;
+        if self.looping {
+            let (start, end) = self.loop_frames(length);
+            if self.position >= end {
+                self.position = start + (self.position - end) % (end - start);
+            }
+        } else if self.position >= length {
+            self.playing = false;
+        }
+
+        output
+    }
+
+    /// Loop region in whole frames, widened to `MIN_LOOP_FRAMES` if the points cross
+    fn loop_frames(&self, length: f64) -> (f64, f64) {
+        let start = (f64::from(self.loop_start) * length)
+            .round()
+            .min(length - MIN_LOOP_FRAMES)
+            .max(0.0);
+        let end = (f64::from(self.loop_end) * length)
+            .round()
+            .clamp(start + MIN_LOOP_FRAMES, length.max(start + MIN_LOOP_FRAMES));
+        (start, end)
+    }
+}
+
+/// Frame at a (possibly out of range) index, holding the edge frames
+#[inline]
+fn frame_at(frames: &[f32], index: isize) -> f32 {
+    let last = frames.len().saturating_sub(1);
+    let index = usize::try_from(index).unwrap_or(0).min(last);
+    frames.get(index).copied().unwrap_or(0.0)
+}
+
+/// Linear interpolation at a fractional frame position
+#[inline]
+#[allow(clippy::cast_possible_truncation)] // Positions are well inside isize
+fn read_linear(frames: &[f32], position: f64) -> f32 {
+    let index = position.floor();
+    let fraction = (position - index) as f32;
+    let index = index as isize;
+
+    let a = frame_at(frames, index);
+    let b = frame_at(frames, index + 1);
+    a + (b - a) * fraction
+}
+
+/// Catmull-Rom interpolation at a fractional frame position
+#[inline]
+#[allow(clippy::cast_possible_truncation)] // Positions are well inside isize
+fn read_cubic(frames: &[f32], position: f64) -> f32 {
+    let index = position.floor();
+    let t = (position - index) as f32;
+    let index = index as isize;
+
+    let y0 = frame_at(frames, index - 1);
+    let y1 = frame_at(frames, index);
+    let y2 = frame_at(frames, index + 1);
+    let y3 = frame_at(frames, index + 2);
+
+    let c1 = 0.5 * (y2 - y0);
+    let c2 = y0 - 2.5 * y1 + 2.0 * y2 - 0.5 * y3;
+    let c3 = 0.5 * (y3 - y0) + 1.5 * (y1 - y2);
+    ((c3 * t + c2) * t + c1) * t + y1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal WAV file around raw sample bytes
+    fn wav(tag: u16, channels: u16, bits: u16, data: &[u8]) -> Vec<u8> {
+        let mut fmt = Vec::new();
+        fmt.extend_from_slice(&tag.to_le_bytes());
+        fmt.extend_from_slice(&channels.to_le_bytes());
+        fmt.extend_from_slice(&22050u32.to_le_bytes());
+        fmt.extend_from_slice(&0u32.to_le_bytes()); // Byte rate (unused)
+        fmt.extend_from_slice(&(channels * bits / 8).to_le_bytes());
+        fmt.extend_from_slice(&bits.to_le_bytes());
+
+        let mut file = b"RIFF\0\0\0\0WAVE".to_vec();
+        for (id, body) in [
+            (b"fmt ", &fmt[..]),
+            (b"LIST", &[1, 2, 3][..]),
+            (b"data", data),
+        ] {
+            file.extend_from_slice(id);
+            file.extend_from_slice(&u32::try_from(body.len()).unwrap().to_le_bytes());
+            file.extend_from_slice(body);
+            if body.len() % 2 == 1 {
+                file.push(0);
+            }
+        }
+        file
+    }
+
+    fn player(frames: Vec<f32>) -> SamplePlayer {
+        let mut player = SamplePlayer::new(100.0);
+        player.set_sample(Some(Arc::new(SampleData::new(frames, 100.0))));
+        player
+    }
+
+    #[test]
+    fn test_decode_16_bit_stereo_to_mono() {
+        let data: Vec<u8> = [16384i16, -16384, 32767, 32767]
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+        let sample = SampleData::from_wav(&wav(FORMAT_PCM, 2, 16, &data)).unwrap();
+
+        assert!((sample.sample_rate() - 22050.0).abs() < f32::EPSILON);
+        assert_eq!(sample.len(), 2);
+        assert!(sample.frames()[0].abs() < 1e-6);
+        assert!((sample.frames()[1] - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_decode_24_bit_and_float() {
+        // -0.5 in 24-bit, then 0.5 in 24-bit
+        let sample =
+            SampleData::from_wav(&wav(FORMAT_PCM, 1, 24, &[0, 0, 0xC0, 0, 0, 0x40])).unwrap();
+        assert_eq!(sample.frames(), &[-0.5, 0.5]);
+
+        let data = 0.25f32.to_le_bytes();
+        let sample = SampleData::from_wav(&wav(FORMAT_FLOAT, 1, 32, &data)).unwrap();
+        assert_eq!(sample.frames(), &[0.25]);
+    }
+
+    #[test]
+    fn test_rejects_bad_files() {
+        assert!(matches!(
+            SampleData::from_wav(b"not a wav"),
+            Err(WavError::Invalid(_))
+        ));
+        assert!(matches!(
+            SampleData::from_wav(&wav(2, 1, 4, &[0; 8])),
+            Err(WavError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_pitch_sets_playback_rate() {
+        let mut player = player((0..100u8).map(f32::from).collect());
+        player.trigger();
+
+        // An octave up reads every other frame
+        let octave_up = player.root_frequency() * 2.0;
+        let read: Vec<f32> = (0..3).map(|_| player.process(octave_up)).collect();
+        assert_eq!(read, vec![0.0, 2.0, 4.0]);
+    }
+
+    #[test]
+    fn test_one_shot_stops_at_end() {
+        let mut player = player(vec![1.0; 10]);
+        player.trigger();
+        let root = player.root_frequency();
+
+        let sounding = (0..20).filter(|_| player.process(root) != 0.0).count();
+        assert_eq!(sounding, 10);
+        assert!(!player.is_playing());
+    }
+
+    #[test]
+    fn test_loop_wraps_between_points() {
+        let mut player = player((0..10u8).map(f32::from).collect());
+        player.set_loop(true, 0.5, 0.8);
+        player.trigger();
+        let root = player.root_frequency();
+
+        let read: Vec<f32> = (0..12).map(|_| player.process(root)).collect();
+        assert_eq!(
+            read,
+            vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 5.0, 6.0, 7.0, 5.0]
+        );
+        assert!(player.is_playing());
+    }
+
+    #[test]
+    fn test_start_offset() {
+        let mut player = player((0..10u8).map(f32::from).collect());
+        player.set_start_offset(0.5);
+        player.trigger();
+        assert!((player.process(player.root_frequency()) - 5.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_granular_mode_keeps_playing() {
+        let mut player = player(vec![1.0; 10]);
+        player.set_granular(true, GranularSettings::default());
+        player.trigger();
+        let root = player.root_frequency();
+
+        // Far longer than the sample: grains wrap around it
+        let sounding = (0..1000).filter(|_| player.process(root) != 0.0).count();
+        assert!(sounding > 500);
+        assert!(player.is_playing());
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // Frames are read back exactly
+    fn test_interpolation_passes_through_frames() {
+        let frames = [0.0, 1.0, 0.0, -1.0];
+        for (position, &expected) in (0u8..).zip(&frames) {
+            assert_eq!(read_linear(&frames, f64::from(position)), expected);
+            assert_eq!(read_cubic(&frames, f64::from(position)), expected);
+        }
+        assert_eq!(read_linear(&frames, 0.5), 0.5);
+        assert!(read_cubic(&frames, 0.5) > 0.5, "Cubic follows the curve");
+    }
+
+    #[test]
+    fn test_replaced_sample_outlives_voices() {
+        let slot = SampleSlot::new();
+        let mut seen = 0;
+        slot.set(Arc::new(SampleData::new(vec![1.0], 100.0)));
+        let playing = slot.fetch(&mut seen).unwrap();
+
+        slot.set(Arc::new(SampleData::new(vec![2.0], 100.0)));
+        // The slot still holds the old sample, so dropping the voice's copy frees nothing
+        assert_eq!(Arc::strong_count(&playing), 2);
+        assert_eq!(slot.fetch(&mut seen).unwrap().frames(), &[2.0]);
+    }
+}
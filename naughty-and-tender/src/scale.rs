@@ -0,0 +1,362 @@
+//! Scale quantization for Naughty and Tender
+//!
+//! Snaps incoming keys to a scale before they reach chord memory and the voices,
+//! so every key plays something in key while jamming. A scale is a 12-bit mask
+//! of the degrees above the root; keys outside it move to the nearest degree, or
+//! to the degree below.
+//!
+//! Each key remembers the note it was snapped to, so its note-off releases that
+//! note even if the scale or root changed while it was held. Two keys that snap
+//! to the same note play it like the same key pressed twice (see
+//! `SameNotePolicy`).
+//!
+//! # References
+//! - Scale masks as pitch-class sets (Forte), rooted at the key
+//! - Hardware "scale" modes: Novation Launchpad, Ableton Push, Elektron keyboards
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use crate::NUM_NOTES;
+
+/// Degrees of a scale above its root, bit `n` = `n` semitones up
+pub type ScaleMask = u16;
+
+/// Mask with every degree in the octave (the chromatic scale)
+pub const FULL_MASK: ScaleMask = 0x0FFF;
+
+/// Degrees in an octave
+pub const NUM_DEGREES: usize = 12;
+
+/// Pitch class names, for the root (0 = C)
+pub const ROOT_NAMES: [&str; NUM_DEGREES] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Scale keys are snapped to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scale {
+    #[default]
+    Major,
+    NaturalMinor,
+    HarmonicMinor,
+    MelodicMinor,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    Locrian,
+    MajorPentatonic,
+    MinorPentatonic,
+    Blues,
+    /// Degrees picked one by one
+    Custom,
+}
+
+impl Scale {
+    /// Every scale, in parameter index order
+    pub const ALL: [Self; 13] = [
+        Self::Major,
+        Self::NaturalMinor,
+        Self::HarmonicMinor,
+        Self::MelodicMinor,
+        Self::Dorian,
+        Self::Phrygian,
+        Self::Lydian,
+        Self::Mixolydian,
+        Self::Locrian,
+        Self::MajorPentatonic,
+        Self::MinorPentatonic,
+        Self::Blues,
+        Self::Custom,
+    ];
+
+    /// Display names, in parameter index order
+    pub const NAMES: [&'static str; 13] = [
+        "Major",
+        "Natural Minor",
+        "Harmonic Minor",
+        "Melodic Minor",
+        "Dorian",
+        "Phrygian",
+        "Lydian",
+        "Mixolydian",
+        "Locrian",
+        "Major Pentatonic",
+        "Minor Pentatonic",
+        "Blues",
+        "Custom",
+    ];
+
+    /// Scale at a parameter index (out-of-range falls back to `Major`)
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or_default()
+    }
+
+    /// Degrees of the scale, using `custom` for [`Scale::Custom`]
+    #[must_use]
+    pub fn mask(self, custom: ScaleMask) -> ScaleMask {
+        let degrees: &[u8] = match self {
+            Self::Major => &[0, 2, 4, 5, 7, 9, 11],
+            Self::NaturalMinor => &[0, 2, 3, 5, 7, 8, 10],
+            Self::HarmonicMinor => &[0, 2, 3, 5, 7, 8, 11],
+            Self::MelodicMinor => &[0, 2, 3, 5, 7, 9, 11],
+            Self::Dorian => &[0, 2, 3, 5, 7, 9, 10],
+            Self::Phrygian => &[0, 1, 3, 5, 7, 8, 10],
+            Self::Lydian => &[0, 2, 4, 6, 7, 9, 11],
+            Self::Mixolydian => &[0, 2, 4, 5, 7, 9, 10],
+            Self::Locrian => &[0, 1, 3, 5, 6, 8, 10],
+            Self::MajorPentatonic => &[0, 2, 4, 7, 9],
+            Self::MinorPentatonic => &[0, 3, 5, 7, 10],
+            Self::Blues => &[0, 3, 5, 6, 7, 10],
+            Self::Custom => return custom & FULL_MASK,
+        };
+        degrees.iter().fold(0, |mask, &degree| mask | 1 << degree)
+    }
+}
+
+/// Which way an out-of-scale key moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SnapMode {
+    /// The closest scale degree (ties go up)
+    #[default]
+    Nearest,
+    /// The scale degree below
+    Down,
+}
+
+impl SnapMode {
+    /// Every mode, in parameter index order
+    pub const ALL: [Self; 2] = [Self::Nearest, Self::Down];
+
+    /// Display names, in parameter index order
+    pub const NAMES: [&'static str; 2] = ["Nearest", "Down"];
+
+    /// Mode at a parameter index (out-of-range falls back to `Nearest`)
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or_default()
+    }
+}
+
+/// Snaps keys to a scale and tracks what each held key plays
+///
+/// # Real-time Safety
+/// - Fixed-size tables, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::scale::{Scale, ScaleQuantizer};
+///
+/// let mut quantizer = ScaleQuantizer::new();
+/// quantizer.set_enabled(true);
+/// quantizer.set_scale(2, Scale::Major.mask(0)); // D major
+///
+/// assert_eq!(quantizer.note_on(65), 66); // F -> F#
+/// assert_eq!(quantizer.note_off(65), 66);
+/// ```
+pub struct ScaleQuantizer {
+    /// Snap keys (off = keys pass through unchanged)
+    enabled: bool,
+
+    /// Pitch class of the scale's root (0 = C)
+    root: u8,
+
+    /// Degrees above the root
+    mask: ScaleMask,
+
+    /// Which way out-of-scale keys move
+    snap: SnapMode,
+
+    /// Note each held key is playing
+    sounding: [Option<u8>; NUM_NOTES],
+}
+
+impl Default for ScaleQuantizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScaleQuantizer {
+    /// Create a quantizer (disabled, C major, nearest)
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            root: 0,
+            mask: Scale::Major.mask(0),
+            snap: SnapMode::Nearest,
+            sounding: [None; NUM_NOTES],
+        }
+    }
+
+    /// Turn snapping on or off (held keys still release what they play)
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Set the root pitch class (0 = C, wraps past 11) and the degrees above it
+    pub fn set_scale(&mut self, root: u8, mask: ScaleMask) {
+        self.root = root % 12;
+        self.mask = mask & FULL_MASK;
+    }
+
+    /// Set which way out-of-scale keys move
+    pub fn set_snap_mode(&mut self, snap: SnapMode) {
+        self.snap = snap;
+    }
+
+    /// Handle a key press, returning the note to play
+    pub fn note_on(&mut self, key: u8) -> u8 {
+        let note = self.quantize(key);
+        self.sounding[usize::from(key)] = Some(note);
+        note
+    }
+
+    /// Handle a key release, returning the note to release
+    pub fn note_off(&mut self, key: u8) -> u8 {
+        self.sounding[usize::from(key)].take().unwrap_or(key)
+    }
+
+    /// The note a key plays with the current settings
+    ///
+    /// Keys pass through unchanged while disabled or with an empty scale. Near
+    /// the ends of the MIDI range, keys with no degree on their side snap the
+    /// other way.
+    #[must_use]
+    pub fn quantize(&self, key: u8) -> u8 {
+        if !self.enabled || self.mask == 0 {
+            return key;
+        }
+
+        let below = (0..12)
+            .map_while(|distance| key.checked_sub(distance))
+            .find(|&note| self.contains(note));
+        let above = (1..12)
+            .map(|distance| key.saturating_add(distance))
+            .take_while(|&note| usize::from(note) < NUM_NOTES)
+            .find(|&note| self.contains(note));
+
+        match (below, above, self.snap) {
+            (Some(below), Some(above), SnapMode::Nearest) => {
+                if key - below < above - key {
+                    below
+                } else {
+                    above
+                }
+            }
+            (Some(note), _, _) | (None, Some(note), _) => note,
+            (None, None, _) => key,
+        }
+    }
+
+    /// Whether a note is a degree of the scale
+    #[must_use]
+    pub fn contains(&self, note: u8) -> bool {
+        in_scale(self.root, self.mask, note)
+    }
+
+    /// Forget every held key
+    pub fn reset(&mut self) {
+        self.sounding = [None; NUM_NOTES];
+    }
+}
+
+/// Whether a note is a degree of the scale with the given root and degrees
+///
+/// Shared with the voices, whose glissando steps through the same scale.
+#[must_use]
+pub fn in_scale(root: u8, mask: ScaleMask, note: u8) -> bool {
+    let degree = (note % 12 + 12 - root % 12) % 12;
+    mask & (1 << degree) != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quantizer(root: u8, scale: Scale, snap: SnapMode) -> ScaleQuantizer {
+        let mut quantizer = ScaleQuantizer::new();
+        quantizer.set_enabled(true);
+        quantizer.set_scale(root, scale.mask(0));
+        quantizer.set_snap_mode(snap);
+        quantizer
+    }
+
+    #[test]
+    fn test_disabled_passes_keys_through() {
+        let mut quantizer = ScaleQuantizer::new();
+        for key in 0..128 {
+            assert_eq!(quantizer.note_on(key), key);
+        }
+    }
+
+    #[test]
+    fn test_nearest_snaps_to_closest_degree() {
+        let q = quantizer(0, Scale::Major, SnapMode::Nearest);
+        let snapped: Vec<u8> = (60..72).map(|key| q.quantize(key)).collect();
+        // Ties (every black key in C major) go up
+        assert_eq!(snapped, [60, 62, 62, 64, 64, 65, 67, 67, 69, 69, 71, 71]);
+
+        // Minor pentatonic on A: B is nearer C than A, F nearer E than G
+        let q = quantizer(9, Scale::MinorPentatonic, SnapMode::Nearest);
+        assert_eq!(q.quantize(59), 60);
+        assert_eq!(q.quantize(53), 52);
+    }
+
+    #[test]
+    fn test_down_snaps_to_degree_below() {
+        let q = quantizer(0, Scale::Major, SnapMode::Down);
+        let snapped: Vec<u8> = (60..72).map(|key| q.quantize(key)).collect();
+        assert_eq!(snapped, [60, 60, 62, 62, 64, 65, 65, 67, 67, 69, 69, 71]);
+    }
+
+    #[test]
+    fn test_scale_notes_are_unchanged_in_every_scale() {
+        for scale in Scale::ALL {
+            for snap in SnapMode::ALL {
+                let q = quantizer(5, scale, snap);
+                for note in (0..128).filter(|&note| q.contains(note)) {
+                    assert_eq!(q.quantize(note), note, "{scale:?} {snap:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_range_ends_snap_inwards() {
+        // B major: C (0) has no degree below it, so it can't snap down
+        let q = quantizer(11, Scale::Major, SnapMode::Down);
+        assert_eq!(q.quantize(0), 1);
+
+        // C# major pentatonic: the nearest degree to G9 (127) would be G#9,
+        // which is out of range
+        let q = quantizer(1, Scale::MajorPentatonic, SnapMode::Nearest);
+        assert_eq!(q.quantize(127), 125);
+    }
+
+    #[test]
+    fn test_custom_mask_and_empty_scale() {
+        let mut q = ScaleQuantizer::new();
+        q.set_enabled(true);
+        q.set_scale(0, Scale::Custom.mask(0b1001_0001)); // C, E, G
+        assert_eq!(q.quantize(62), 64);
+        assert_eq!(q.quantize(66), 67);
+
+        q.set_scale(0, Scale::Custom.mask(0));
+        assert_eq!(q.quantize(61), 61);
+    }
+
+    #[test]
+    fn test_note_off_releases_snapped_note_after_scale_change() {
+        let mut q = quantizer(0, Scale::Major, SnapMode::Nearest);
+        assert_eq!(q.note_on(61), 62);
+
+        q.set_scale(0, Scale::NaturalMinor.mask(0));
+        assert_eq!(q.note_off(61), 62);
+
+        // Untracked keys release themselves
+        assert_eq!(q.note_off(70), 70);
+    }
+}
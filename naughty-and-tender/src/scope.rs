@@ -0,0 +1,553 @@
+//! Oscilloscope and spectrum captures for Naughty and Tender
+//!
+//! The editor's scope panel reads the latest stretch of the tuner's audio tap
+//! (the synth output, or the input in effect mode) once per frame, and shows it
+//! as a waveform and as a spectrum. Freezing the panel keeps the last capture on
+//! screen; any capture can be exported for documentation, as CSV (samples and
+//! spectrum, for a spreadsheet or plotting script), as a PNG of both plots, or
+//! as a 16-bit WAV file of the samples, dithered as chosen in the panel.
+//!
+//! The waveform view starts at the first rising zero crossing, so a steady
+//! periodic signal holds still between frames like a triggered scope. Exports
+//! run on the background thread through the file tasks, and keep the whole
+//! capture rather than only the part drawn.
+//!
+//! The PNG encoder writes uncompressed (stored) deflate blocks: the files are
+//! larger than a compressing encoder's, but any viewer opens them and it needs
+//! no image library.
+//!
+//! # References
+//! - Spectrum via `shared_core::analysis::amplitude_spectrum` (Hann window)
+//! - PNG Specification, Second Edition (W3C 2003): chunk layout, IHDR, CRC-32
+//! - RFC 1950 (zlib, Adler-32) and RFC 1951 (deflate stored blocks)
+//! - WAV export via `shared_core::audio_io::encode_wav_16`
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use std::fmt::Write as _;
+use std::io;
+use std::path::Path;
+
+use shared_core::analysis::{amplitude_spectrum, ratio_to_db};
+use shared_core::audio_io::{encode_wav_16, DitherMode};
+
+use crate::tuner::AudioTap;
+
+/// Samples in each capture
+pub const SCOPE_LEN: usize = 4096;
+
+/// Samples the waveform view shows, from the trigger point
+pub const SCOPE_VIEW_LEN: usize = 1024;
+
+/// Lowest level the spectrum shows, in dB
+pub const SPECTRUM_FLOOR_DB: f32 = -96.0;
+
+/// Lowest frequency the spectrum shows, in Hz
+pub const SPECTRUM_MIN_HZ: f32 = 20.0;
+
+/// Exported image size in pixels: waveform on top, spectrum below
+const PNG_WIDTH: usize = 800;
+const PNG_HEIGHT: usize = 480;
+
+/// Exported image colors (RGB)
+const PNG_BACKGROUND: [u8; 3] = [24, 24, 28];
+const PNG_GRID: [u8; 3] = [64, 64, 72];
+const PNG_TRACE: [u8; 3] = [240, 160, 80];
+
+/// Export file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeFormat {
+    Csv,
+    Png,
+    /// 16-bit WAV, rounded with the given dither
+    Wav(DitherMode),
+}
+
+impl ScopeFormat {
+    /// Format for a path's extension: PNG for `.png`, WAV (default dither) for
+    /// `.wav`, CSV otherwise
+    #[must_use]
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path.extension().unwrap_or_default();
+        if extension.eq_ignore_ascii_case("png") {
+            Self::Png
+        } else if extension.eq_ignore_ascii_case("wav") {
+            Self::Wav(DitherMode::default())
+        } else {
+            Self::Csv
+        }
+    }
+
+    /// This format with a WAV file's dither set to `dither`
+    #[must_use]
+    pub fn with_dither(self, dither: DitherMode) -> Self {
+        match self {
+            Self::Wav(_) => Self::Wav(dither),
+            format => format,
+        }
+    }
+}
+
+/// One capture of the audio tap, with its spectrum
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::scope::ScopeCapture;
+///
+/// let sine: Vec<f32> = (0..4096)
+///     .map(|i| (std::f32::consts::TAU * 1000.0 * i as f32 / 48000.0).sin())
+///     .collect();
+/// let capture = ScopeCapture::from_samples(sine, 48000.0);
+/// let loudest = (0..capture.spectrum_db().len())
+///     .max_by(|&a, &b| capture.spectrum_db()[a].total_cmp(&capture.spectrum_db()[b]))
+///     .unwrap();
+/// assert!((capture.bin_frequency_hz(loudest) - 1000.0).abs() < 12.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScopeCapture {
+    /// Samples, oldest first
+    samples: Vec<f32>,
+
+    /// Sample rate the samples were taken at, in Hz
+    sample_rate: f32,
+
+    /// Level per bin from 0 Hz to Nyquist, in dB (1.0 = full-scale sine)
+    spectrum_db: Vec<f32>,
+}
+
+impl ScopeCapture {
+    /// Capture the latest [`SCOPE_LEN`] samples of the tap
+    #[must_use]
+    pub fn capture(tap: &AudioTap) -> Self {
+        let mut samples = vec![0.0; SCOPE_LEN];
+        tap.copy_latest(&mut samples);
+        Self::from_samples(samples, tap.sample_rate())
+    }
+
+    /// Capture of given samples (oldest first)
+    #[must_use]
+    pub fn from_samples(samples: Vec<f32>, sample_rate: f32) -> Self {
+        let spectrum_db = amplitude_spectrum(&samples)
+            .into_iter()
+            .map(|amplitude| ratio_to_db(amplitude).max(SPECTRUM_FLOOR_DB))
+            .collect();
+        Self {
+            samples,
+            sample_rate,
+            spectrum_db,
+        }
+    }
+
+    /// Every captured sample, oldest first
+    #[must_use]
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    /// Sample rate in Hz
+    #[must_use]
+    pub fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    /// Level per bin in dB, from 0 Hz to Nyquist
+    #[must_use]
+    pub fn spectrum_db(&self) -> &[f32] {
+        &self.spectrum_db
+    }
+
+    /// Center frequency of a spectrum bin, in Hz
+    #[must_use]
+    pub fn bin_frequency_hz(&self, bin: usize) -> f32 {
+        #[allow(clippy::cast_precision_loss)] // Bin counts are small
+        let bins = self.spectrum_db.len().saturating_sub(1).max(1) as f32;
+        #[allow(clippy::cast_precision_loss)] // Bin counts are small
+        let bin = bin as f32;
+        bin * self.sample_rate / (2.0 * bins)
+    }
+
+    /// The part of the capture the waveform view shows: [`SCOPE_VIEW_LEN`]
+    /// samples from the first rising zero crossing (or from the start when
+    /// there's none early enough)
+    #[must_use]
+    pub fn view(&self) -> &[f32] {
+        let view_len = SCOPE_VIEW_LEN.min(self.samples.len());
+        let latest_start = self.samples.len() - view_len;
+        let trigger = self.samples[..=latest_start]
+            .windows(2)
+            .position(|pair| pair[0] < 0.0 && pair[1] >= 0.0)
+            .map_or(0, |index| index + 1);
+        &self.samples[trigger..trigger + view_len]
+    }
+
+    /// Samples and spectrum as CSV: `time_s,sample,frequency_hz,level_db`,
+    /// the spectrum columns empty past its last bin
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("time_s,sample,frequency_hz,level_db\n");
+        for row in 0..self.samples.len().max(self.spectrum_db.len()) {
+            if let Some(sample) = self.samples.get(row) {
+                #[allow(clippy::cast_precision_loss)] // Capture lengths are small
+                let time_s = row as f32 / self.sample_rate;
+                let _ = write!(csv, "{time_s:.6},{sample:.6}");
+            } else {
+                csv.push(',');
+            }
+            if let Some(level_db) = self.spectrum_db.get(row) {
+                let frequency_hz = self.bin_frequency_hz(row);
+                let _ = write!(csv, ",{frequency_hz:.2},{level_db:.2}");
+            } else {
+                csv.push_str(",,");
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Waveform view above the spectrum, as a PNG image
+    #[must_use]
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut canvas = Canvas::new(PNG_WIDTH, PNG_HEIGHT);
+        let plot_height = PNG_HEIGHT / 2;
+
+        // Waveform: zero line, then each column's min to max
+        canvas.fill(0, plot_height / 2, PNG_WIDTH, 1, PNG_GRID);
+        let view = self.view();
+        for x in 0..PNG_WIDTH {
+            let start = x * view.len() / PNG_WIDTH;
+            let end = ((x + 1) * view.len() / PNG_WIDTH)
+                .max(start + 1)
+                .min(view.len());
+            let Some(column) = view.get(start..end) else {
+                break;
+            };
+            let (low, high) = column
+                .iter()
+                .fold((f32::MAX, f32::MIN), |(low, high), &sample| {
+                    (low.min(sample), high.max(sample))
+                });
+            let top = level_to_row(high, plot_height);
+            let bottom = level_to_row(low, plot_height);
+            canvas.fill(x, top, 1, bottom - top + 1, PNG_TRACE);
+        }
+
+        // Spectrum: log frequency across, bars up from the floor
+        canvas.fill(0, plot_height, PNG_WIDTH, 1, PNG_GRID);
+        let nyquist = self.sample_rate / 2.0;
+        for x in 0..PNG_WIDTH {
+            #[allow(clippy::cast_precision_loss)] // Pixel positions
+            let position = x as f32 / PNG_WIDTH as f32;
+            let level_db = self.level_at_db(spectrum_frequency_hz(position, nyquist));
+            let height = (level_db - SPECTRUM_FLOOR_DB) / -SPECTRUM_FLOOR_DB;
+            #[allow(
+                clippy::cast_possible_truncation,
+                clippy::cast_precision_loss,
+                clippy::cast_sign_loss
+            )] // 0 - plot height
+            let bar = (height.clamp(0.0, 1.0) * (plot_height - 1) as f32) as usize;
+            canvas.fill(x, PNG_HEIGHT - bar, 1, bar, PNG_TRACE);
+        }
+
+        encode_png(PNG_WIDTH, PNG_HEIGHT, &canvas.rgb)
+    }
+
+    /// The samples as a 16-bit WAV file
+    #[must_use]
+    pub fn to_wav(&self, dither: DitherMode) -> Vec<u8> {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Audio rates
+        let sample_rate = self.sample_rate.round() as u32;
+        encode_wav_16(&self.samples, sample_rate, dither)
+    }
+
+    /// Spectrum level at a frequency, in dB (the nearest bin)
+    #[must_use]
+    pub fn level_at_db(&self, frequency_hz: f32) -> f32 {
+        let last = self.spectrum_db.len().saturating_sub(1);
+        #[allow(clippy::cast_precision_loss)] // Bin counts are small
+        let bin = frequency_hz / self.sample_rate * 2.0 * last as f32;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Clamped below
+        let bin = (bin.round().max(0.0) as usize).min(last);
+        self.spectrum_db
+            .get(bin)
+            .copied()
+            .unwrap_or(SPECTRUM_FLOOR_DB)
+    }
+
+    /// Write the capture to `path` (blocks on the file system)
+    ///
+    /// # Errors
+    /// Any error writing the file
+    pub fn save(&self, path: &Path, format: ScopeFormat) -> io::Result<()> {
+        match format {
+            ScopeFormat::Csv => std::fs::write(path, self.to_csv()),
+            ScopeFormat::Png => std::fs::write(path, self.to_png()),
+            ScopeFormat::Wav(dither) => std::fs::write(path, self.to_wav(dither)),
+        }
+    }
+}
+
+/// Frequency at a position across the spectrum view (0.0 - 1.0), log-spaced
+/// from [`SPECTRUM_MIN_HZ`] to Nyquist
+#[must_use]
+pub fn spectrum_frequency_hz(position: f32, nyquist_hz: f32) -> f32 {
+    SPECTRUM_MIN_HZ * (nyquist_hz / SPECTRUM_MIN_HZ).powf(position)
+}
+
+/// Pixel row of a sample level in a plot `height` rows tall (+1.0 at the top)
+fn level_to_row(level: f32, height: usize) -> usize {
+    #[allow(clippy::cast_precision_loss)] // Pixel positions
+    let row = (1.0 - level.clamp(-1.0, 1.0)) * 0.5 * (height - 1) as f32;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // 0 - height
+    let row = row.round() as usize;
+    row
+}
+
+/// RGB image being drawn for export
+struct Canvas {
+    width: usize,
+    height: usize,
+    rgb: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            rgb: PNG_BACKGROUND.repeat(width * height),
+        }
+    }
+
+    /// Fill a rectangle, clipped to the image
+    fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, color: [u8; 3]) {
+        for row in y..(y + height).min(self.height) {
+            for column in x..(x + width).min(self.width) {
+                let index = (row * self.width + column) * 3;
+                self.rgb[index..index + 3].copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+/// Encode 8-bit RGB pixels, rows top to bottom, as a PNG file
+fn encode_png(width: usize, height: usize, rgb: &[u8]) -> Vec<u8> {
+    // Scanlines, each behind a "no filter" byte
+    let mut scanlines = Vec::with_capacity((width * 3 + 1) * height);
+    for row in rgb.chunks_exact(width * 3) {
+        scanlines.push(0);
+        scanlines.extend_from_slice(row);
+    }
+
+    // zlib stream of stored deflate blocks
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = scanlines.chunks(usize::from(u16::MAX)).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        zlib.push(u8::from(blocks.peek().is_none()));
+        #[allow(clippy::cast_possible_truncation)] // Blocks are at most u16::MAX long
+        let len = block.len() as u16;
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&scanlines).to_be_bytes());
+
+    #[allow(clippy::cast_possible_truncation)] // Export images are small
+    let (width, height) = (width as u32, height as u32);
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // 8 bits per channel, RGB, deflate, adaptive filtering, not interlaced
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    for (kind, data) in [(b"IHDR", &header), (b"IDAT", &zlib), (b"IEND", &Vec::new())] {
+        #[allow(clippy::cast_possible_truncation)] // Export images are small
+        png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        let start = png.len();
+        png.extend_from_slice(kind);
+        png.extend_from_slice(data);
+        let crc = crc32(&png[start..]);
+        png.extend_from_slice(&crc.to_be_bytes());
+    }
+    png
+}
+
+/// CRC-32 (IEEE, reflected), as PNG chunks use
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = u32::MAX;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Adler-32 checksum, as zlib streams end with
+fn adler32(bytes: &[u8]) -> u32 {
+    const MODULUS: u32 = 65521;
+    let (mut a, mut b) = (1, 0);
+    for &byte in bytes {
+        a = (a + u32::from(byte)) % MODULUS;
+        b = (b + a) % MODULUS;
+    }
+    b << 16 | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sampler::SampleData;
+    use std::f32::consts::TAU;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    #[allow(clippy::cast_precision_loss)] // Short test lengths
+    fn sine(frequency_hz: f32, phase: f32) -> Vec<f32> {
+        (0..SCOPE_LEN)
+            .map(|i| (TAU * frequency_hz * i as f32 / SAMPLE_RATE + phase).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_capture_reads_tap_and_finds_pitch() {
+        let tap = AudioTap::new();
+        tap.set_sample_rate(SAMPLE_RATE);
+        // On a bin, so the level reads true
+        for sample in sine(468.75, 0.0) {
+            tap.push(sample * 0.5);
+        }
+
+        let capture = ScopeCapture::capture(&tap);
+        assert_eq!(capture.samples().len(), SCOPE_LEN);
+        assert_eq!(capture.spectrum_db().len(), SCOPE_LEN / 2 + 1);
+        let level = capture.level_at_db(468.75);
+        assert!((level - ratio_to_db(0.5)).abs() < 1.0, "{level} dB");
+        assert!(capture.level_at_db(5000.0) < -60.0);
+    }
+
+    #[test]
+    fn test_view_starts_on_rising_zero_crossing() {
+        // Starts just past a peak, falling
+        let capture = ScopeCapture::from_samples(sine(100.0, 1.7), SAMPLE_RATE);
+        let view = capture.view();
+        assert_eq!(view.len(), SCOPE_VIEW_LEN);
+        assert!(view[0] >= 0.0 && view[0] < 0.02, "{}", view[0]);
+        assert!(view[1] > view[0]);
+
+        // Silence has nothing to trigger on
+        let silence = ScopeCapture::from_samples(vec![0.0; SCOPE_LEN], SAMPLE_RATE);
+        assert_eq!(silence.view().len(), SCOPE_VIEW_LEN);
+    }
+
+    #[test]
+    fn test_csv_has_samples_and_spectrum() {
+        let capture = ScopeCapture::from_samples(sine(1000.0, 0.0), SAMPLE_RATE);
+        let csv = capture.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "time_s,sample,frequency_hz,level_db");
+        assert_eq!(lines.len(), SCOPE_LEN + 1);
+        assert_eq!(lines[1], "0.000000,0.000000,0.00,-96.00");
+        assert!(lines[2].starts_with("0.000021,0.130526,11.72,"));
+        assert!(lines[SCOPE_LEN].ends_with(",,"), "{}", lines[SCOPE_LEN]);
+        assert!(lines.iter().all(|line| line.split(',').count() == 4));
+    }
+
+    #[test]
+    fn test_png_is_well_formed() {
+        let capture = ScopeCapture::from_samples(sine(1000.0, 0.0), SAMPLE_RATE);
+        let png = capture.to_png();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+
+        // Walk the chunks, checking each CRC and collecting the image data
+        let mut chunks = Vec::new();
+        let mut zlib = Vec::new();
+        let mut offset = 8;
+        while offset < png.len() {
+            let len = u32::from_be_bytes(png[offset..offset + 4].try_into().unwrap()) as usize;
+            let body = &png[offset + 4..offset + 8 + len];
+            let crc =
+                u32::from_be_bytes(png[offset + 8 + len..offset + 12 + len].try_into().unwrap());
+            assert_eq!(crc32(body), crc);
+            chunks.push(body[..4].to_vec());
+            if &body[..4] == b"IHDR" {
+                let width = u32::try_from(PNG_WIDTH).unwrap();
+                let height = u32::try_from(PNG_HEIGHT).unwrap();
+                assert_eq!(&body[4..8], &width.to_be_bytes());
+                assert_eq!(&body[8..12], &height.to_be_bytes());
+            } else if &body[..4] == b"IDAT" {
+                zlib.extend_from_slice(&body[4..]);
+            }
+            offset += 12 + len;
+        }
+        assert_eq!(
+            chunks,
+            [b"IHDR".to_vec(), b"IDAT".to_vec(), b"IEND".to_vec()]
+        );
+
+        // Stored blocks back to scanlines
+        let mut scanlines = Vec::new();
+        let mut position = 2;
+        loop {
+            let last = zlib[position] & 1 == 1;
+            let len = usize::from(u16::from_le_bytes([zlib[position + 1], zlib[position + 2]]));
+            scanlines.extend_from_slice(&zlib[position + 5..position + 5 + len]);
+            position += 5 + len;
+            if last {
+                break;
+            }
+        }
+        let checksum = u32::from_be_bytes(zlib[position..position + 4].try_into().unwrap());
+        assert_eq!(adler32(&scanlines), checksum);
+        assert_eq!(scanlines.len(), (PNG_WIDTH * 3 + 1) * PNG_HEIGHT);
+        assert!(scanlines.chunks(PNG_WIDTH * 3 + 1).all(|row| row[0] == 0));
+        assert!(scanlines.windows(3).any(|pixel| pixel == PNG_TRACE));
+    }
+
+    #[test]
+    fn test_checksums_match_known_values() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn test_format_follows_extension() {
+        assert_eq!(
+            ScopeFormat::from_path(Path::new("scope.PNG")),
+            ScopeFormat::Png
+        );
+        assert_eq!(
+            ScopeFormat::from_path(Path::new("scope.csv")),
+            ScopeFormat::Csv
+        );
+        assert_eq!(ScopeFormat::from_path(Path::new("scope")), ScopeFormat::Csv);
+        assert_eq!(
+            ScopeFormat::from_path(Path::new("scope.Wav")).with_dither(DitherMode::Off),
+            ScopeFormat::Wav(DitherMode::Off)
+        );
+        assert_eq!(
+            ScopeFormat::Png.with_dither(DitherMode::Tpdf),
+            ScopeFormat::Png
+        );
+    }
+
+    #[test]
+    fn test_wav_export_reads_back_as_a_sample() {
+        let capture = ScopeCapture::from_samples(vec![0.0, 0.5, -0.25, 1.0], 48000.0);
+        let sample = SampleData::from_wav(&capture.to_wav(DitherMode::Off)).unwrap();
+
+        assert!((sample.sample_rate() - 48000.0).abs() < f32::EPSILON);
+        assert_eq!(sample.len(), 4);
+        for (read, written) in sample.frames().iter().zip(capture.samples()) {
+            assert!((read - written).abs() < 1e-4, "{read} vs {written}");
+        }
+    }
+}
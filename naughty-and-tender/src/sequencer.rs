@@ -0,0 +1,415 @@
+//! Step sequencer modulation source for Naughty and Tender
+//!
+//! A 16-step sequence of values (0.0 to 1.0) with a gate per step, stepping at a
+//! tempo-synced rate. Gated-off steps output 0.0. A one-pole slew smooths the jumps
+//! between steps, from hard stairs (0 ms) to gliding curves.
+//!
+//! The output is a global modulation source routed through the mod matrix.
+//!
+//! In Euclidean mode a rhythm of so many pulses spread as evenly as they go
+//! over so many steps, turned by a rotation, gates the steps instead of their
+//! own gates, and the sequence is as long as the rhythm. Changes to the rhythm
+//! take over when the next step starts, so a step in progress is never cut.
+//!
+//! # References
+//! - Analog step sequencers (Moog 960, ARP 1601): per-step CV and gate
+//! - One-pole smoothing: `y += (x - y) * (1 - e^(-1 / (time * sample_rate)))`
+//! - Toussaint, "The Euclidean Algorithm Generates Traditional Musical
+//!   Rhythms" (2005); step `k` is a pulse when `k·pulses mod steps < pulses`
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use shared_core::tempo::relock_position;
+
+/// Number of steps in the sequence
+pub const NUM_STEPS: usize = 16;
+
+/// One sequencer step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    /// Output value (0.0 to 1.0)
+    pub value: f32,
+    /// Whether the step outputs its value (false = 0.0)
+    pub gate: bool,
+}
+
+impl Default for Step {
+    fn default() -> Self {
+        Self {
+            value: 0.0,
+            gate: true,
+        }
+    }
+}
+
+/// Euclidean rhythm: `pulses` onsets spread evenly over `steps`, turned later
+/// by `rotation` steps
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::sequencer::Euclid;
+///
+/// // Tresillo: x..x..x.
+/// let gates = Euclid { steps: 8, pulses: 3, rotation: 0 }.gates();
+/// assert_eq!(&gates[..8], &[true, false, false, true, false, false, true, false]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Euclid {
+    /// Sequence length (1 - [`NUM_STEPS`])
+    pub steps: usize,
+    /// Onsets (at most `steps`)
+    pub pulses: usize,
+    /// Steps the rhythm starts late by
+    pub rotation: usize,
+}
+
+impl Euclid {
+    /// Sequence length, clamped to 1 - [`NUM_STEPS`]
+    #[must_use]
+    pub fn length(self) -> usize {
+        self.steps.clamp(1, NUM_STEPS)
+    }
+
+    /// Gate of every step (steps past the length are off)
+    #[must_use]
+    pub fn gates(self) -> [bool; NUM_STEPS] {
+        let steps = self.length();
+        let pulses = self.pulses.min(steps);
+        let rotation = self.rotation % steps;
+        std::array::from_fn(|i| i < steps && ((i + steps - rotation) * pulses) % steps < pulses)
+    }
+}
+
+/// 16-step modulation sequencer
+///
+/// # Real-time Safety
+/// - Fixed-size step array, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::sequencer::StepSequencer;
+///
+/// let mut sequencer = StepSequencer::new(48000.0);
+/// sequencer.set_step_length_ms(125.0); // 1/16 at 120 BPM
+/// sequencer.set_slew_ms(5.0);
+/// let modulation = sequencer.process();
+/// ```
+pub struct StepSequencer {
+    steps: [Step; NUM_STEPS],
+
+    /// Euclidean rhythm gating the steps, if any
+    euclid: Option<Euclid>,
+
+    /// Rhythm to take over when the next step starts
+    next_euclid: Option<Euclid>,
+
+    /// Gates of `euclid`
+    euclid_gates: [bool; NUM_STEPS],
+
+    /// Sequence length in steps
+    length: usize,
+
+    /// Position in steps (0.0 to `length`)
+    position: f64,
+
+    /// Position advance per sample, in steps
+    increment: f64,
+
+    /// Slew filter coefficient (1.0 = no slew)
+    slew_coeff: f32,
+
+    /// Smoothed output
+    output: f32,
+
+    /// Sample rate in Hz
+    sample_rate: f32,
+}
+
+impl StepSequencer {
+    /// Create a new sequencer (all steps at 0.0, gates on, 1/16 at 120 BPM)
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        let mut sequencer = Self {
+            steps: [Step::default(); NUM_STEPS],
+            euclid: None,
+            next_euclid: None,
+            euclid_gates: [true; NUM_STEPS],
+            length: NUM_STEPS,
+            position: 0.0,
+            increment: 0.0,
+            slew_coeff: 1.0,
+            output: 0.0,
+            sample_rate,
+        };
+        sequencer.set_step_length_ms(125.0);
+        sequencer
+    }
+
+    /// Replace all steps
+    pub fn set_steps(&mut self, steps: [Step; NUM_STEPS]) {
+        self.steps = steps;
+    }
+
+    /// Current steps
+    #[must_use]
+    pub fn steps(&self) -> &[Step; NUM_STEPS] {
+        &self.steps
+    }
+
+    /// Gate the steps with a Euclidean rhythm, or with their own gates
+    /// (`None`); the change takes over when the next step starts
+    pub fn set_euclid(&mut self, euclid: Option<Euclid>) {
+        self.next_euclid = euclid;
+    }
+
+    /// Sequence length in steps
+    #[must_use]
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// Set how long each step lasts (usually a tempo-synced note division)
+    pub fn set_step_length_ms(&mut self, step_ms: f32) {
+        let step_samples = f64::from((step_ms / 1000.0 * self.sample_rate).max(1.0));
+        self.increment = 1.0 / step_samples;
+    }
+
+    /// Set slew time in milliseconds (0 = instant steps)
+    pub fn set_slew_ms(&mut self, slew_ms: f32) {
+        self.slew_coeff = if slew_ms <= 0.0 {
+            1.0
+        } else {
+            1.0 - (-1.0 / (slew_ms / 1000.0 * self.sample_rate)).exp()
+        };
+    }
+
+    /// Lock the playhead to the host's song position (call every block; it
+    /// only jumps when the song position has moved away)
+    ///
+    /// # Arguments
+    /// * `position_beats` - Song position in quarter notes
+    /// * `step_beats` - Length of one step in quarter notes
+    pub fn sync_to_beats(&mut self, position_beats: f64, step_beats: f64) {
+        if step_beats > 0.0 {
+            #[allow(clippy::cast_precision_loss)] // At most NUM_STEPS
+            let length = self.length as f64;
+            let target = (position_beats / step_beats).rem_euclid(length);
+            if let Some(position) = relock_position(self.position, target, length, self.increment) {
+                self.position = position;
+            }
+        }
+    }
+
+    /// Index of the step currently playing
+    #[must_use]
+    pub fn current_step(&self) -> usize {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Position is 0..16
+        let step = self.position as usize;
+        step.min(self.length - 1)
+    }
+
+    /// Generate the next modulation value (0.0 to 1.0)
+    #[inline]
+    pub fn process(&mut self) -> f32 {
+        let index = self.current_step();
+        let step = self.steps[index];
+        let gate = if self.euclid.is_some() {
+            self.euclid_gates[index]
+        } else {
+            step.gate
+        };
+        let target = if gate { step.value } else { 0.0 };
+        self.output += (target - self.output) * self.slew_coeff;
+
+        #[allow(clippy::cast_precision_loss)] // At most NUM_STEPS
+        let length = self.length as f64;
+        self.position += self.increment;
+        if self.position >= length {
+            self.position -= length;
+        }
+
+        if self.next_euclid != self.euclid && self.current_step() != index {
+            self.apply_euclid();
+        }
+
+        self.output
+    }
+
+    /// Restart from the first step (a pending rhythm takes over at once)
+    pub fn reset(&mut self) {
+        self.position = 0.0;
+        self.output = 0.0;
+        self.apply_euclid();
+    }
+
+    /// Take over the pending rhythm, keeping the position within its length
+    fn apply_euclid(&mut self) {
+        self.euclid = self.next_euclid;
+        self.euclid_gates = self.euclid.map_or([true; NUM_STEPS], Euclid::gates);
+        self.length = self.euclid.map_or(NUM_STEPS, Euclid::length);
+        #[allow(clippy::cast_precision_loss)] // At most NUM_STEPS
+        let length = self.length as f64;
+        self.position = self.position.rem_euclid(length);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    #[allow(clippy::cast_precision_loss)] // At most NUM_STEPS
+    fn ramp_steps() -> [Step; NUM_STEPS] {
+        std::array::from_fn(|i| Step {
+            value: i as f32 / (NUM_STEPS - 1) as f32,
+            gate: true,
+        })
+    }
+
+    #[test]
+    fn test_steps_advance_at_step_length() {
+        let mut sequencer = StepSequencer::new(SAMPLE_RATE);
+        sequencer.set_steps(ramp_steps());
+        sequencer.set_step_length_ms(10.0); // 480 samples per step
+
+        assert_eq!(sequencer.current_step(), 0);
+        for _ in 0..481 {
+            sequencer.process();
+        }
+        assert_eq!(sequencer.current_step(), 1);
+
+        // Wraps around after 16 steps
+        for _ in 0..(480 * 15) {
+            sequencer.process();
+        }
+        assert_eq!(sequencer.current_step(), 0);
+    }
+
+    #[test]
+    fn test_output_follows_step_values() {
+        let mut sequencer = StepSequencer::new(SAMPLE_RATE);
+        sequencer.set_steps(ramp_steps());
+        sequencer.set_step_length_ms(10.0);
+
+        for _ in 0..(480 * 5 + 10) {
+            sequencer.process();
+        }
+        let expected = 5.0 / 15.0;
+        assert!((sequencer.process() - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_gate_off_outputs_zero() {
+        let mut steps = [Step {
+            value: 1.0,
+            gate: true,
+        }; NUM_STEPS];
+        steps[0].gate = false;
+
+        let mut sequencer = StepSequencer::new(SAMPLE_RATE);
+        sequencer.set_steps(steps);
+        assert!(sequencer.process().abs() < 1e-7);
+    }
+
+    #[test]
+    fn test_slew_smooths_transitions() {
+        let mut steps = [Step::default(); NUM_STEPS];
+        steps[1].value = 1.0;
+
+        let mut sequencer = StepSequencer::new(SAMPLE_RATE);
+        sequencer.set_steps(steps);
+        sequencer.set_step_length_ms(10.0);
+        sequencer.set_slew_ms(2.0);
+
+        for _ in 0..481 {
+            sequencer.process();
+        }
+        let first = sequencer.process();
+        assert!(
+            first > 0.0 && first < 0.1,
+            "Slew should ease into the new step, got {first}"
+        );
+
+        for _ in 0..400 {
+            sequencer.process();
+        }
+        assert!(
+            sequencer.process() > 0.9,
+            "Slewed output should approach the step value"
+        );
+    }
+
+    #[test]
+    fn test_euclidean_gates() {
+        let gates = |steps, pulses, rotation| {
+            Euclid {
+                steps,
+                pulses,
+                rotation,
+            }
+            .gates()
+        };
+        let pulses_at =
+            |gates: [bool; NUM_STEPS]| (0..NUM_STEPS).filter(|&i| gates[i]).collect::<Vec<_>>();
+
+        assert_eq!(pulses_at(gates(8, 3, 0)), vec![0, 3, 6]);
+        assert_eq!(pulses_at(gates(8, 3, 1)), vec![1, 4, 7]);
+        assert_eq!(pulses_at(gates(16, 4, 0)), vec![0, 4, 8, 12]);
+        assert_eq!(pulses_at(gates(5, 2, 7)), vec![0, 2]);
+        assert_eq!(pulses_at(gates(6, 9, 0)), vec![0, 1, 2, 3, 4, 5]);
+        assert!(pulses_at(gates(12, 0, 3)).is_empty());
+
+        // Out-of-range lengths are clamped
+        assert_eq!(pulses_at(gates(0, 1, 0)), vec![0]);
+        assert_eq!(pulses_at(gates(40, 1, 0)), vec![0]);
+    }
+
+    #[test]
+    fn test_euclidean_changes_wait_for_the_next_step() {
+        let mut sequencer = StepSequencer::new(SAMPLE_RATE);
+        sequencer.set_steps(
+            [Step {
+                value: 1.0,
+                gate: true,
+            }; NUM_STEPS],
+        );
+        sequencer.set_step_length_ms(10.0); // 480 samples per step
+
+        // Step 0 plays out with its own gate, then one pulse in four
+        sequencer.set_euclid(Some(Euclid {
+            steps: 4,
+            pulses: 1,
+            rotation: 0,
+        }));
+        let outputs: Vec<f32> = (0..480 * 5).map(|_| sequencer.process()).collect();
+        let on = |outputs: &[f32]| outputs.iter().all(|&output| (output - 1.0).abs() < 1e-6);
+        let off = |outputs: &[f32]| outputs.iter().all(|&output| output.abs() < 1e-6);
+        assert!(on(&outputs[..470]));
+        assert!(off(&outputs[490..480 * 4 - 10]));
+        assert!(on(&outputs[480 * 4 + 10..]), "Four steps long");
+        assert_eq!(sequencer.length(), 4);
+
+        // Back to the steps' own gates and the full length
+        sequencer.set_euclid(None);
+        for _ in 0..480 * 4 + 240 {
+            sequencer.process();
+        }
+        assert_eq!(sequencer.length(), NUM_STEPS);
+        assert_eq!(sequencer.current_step(), 5);
+        assert!((sequencer.process() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_sync_to_host_position() {
+        let mut sequencer = StepSequencer::new(SAMPLE_RATE);
+
+        // 1/16 steps: beat 2.5 is step 10
+        sequencer.sync_to_beats(2.5, 0.25);
+        assert_eq!(sequencer.current_step(), 10);
+
+        // Bar 2 wraps back to the start
+        sequencer.sync_to_beats(4.0, 0.25);
+        assert_eq!(sequencer.current_step(), 0);
+    }
+}
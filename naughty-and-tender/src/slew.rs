@@ -0,0 +1,164 @@
+//! Audio-rate slew of block-rate settings for Naughty and Tender
+//!
+//! The plugin reads its parameters once per block, and the editor sets the
+//! ones driven by MIDI CCs once per frame, so a setting that sweeps arrives as
+//! a staircase. Settings a step would click or zipper on go through a
+//! [`Slew`]: filter cutoff and tuning in every voice, the output gain in the
+//! engine. Each new value is reached in a straight line over a fixed time, one
+//! sample at a time, however far it jumped. Fine steps arrive within the same
+//! short time, so a slow 14-bit sweep comes out as a smooth glide.
+//!
+//! Cutoff is slewed in octaves rather than Hz, so a sweep moves evenly through
+//! the spectrum.
+//!
+//! # References
+//! - Zipper noise: linear ramps from block-rate parameter values to
+//!   audio-rate ones, as in most plugin frameworks' parameter smoothers
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+/// Time the filter cutoff takes to reach each new value, in milliseconds
+pub const CUTOFF_SLEW_MS: f32 = 20.0;
+
+/// Time the tuning takes to reach each new value, in milliseconds
+pub const TUNING_SLEW_MS: f32 = 20.0;
+
+/// Time the output gain takes to reach each new value, in milliseconds
+pub const GAIN_SLEW_MS: f32 = 20.0;
+
+/// Linear slew of one setting towards its latest value
+///
+/// # Real-time Safety
+/// - Fixed state, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::slew::Slew;
+///
+/// let mut slew = Slew::new(48000.0, 10.0, 0.0);
+/// let first = slew.process(1.0);
+/// assert!(first > 0.0 && first < 0.01);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Slew {
+    /// Value being slewed towards
+    target: f32,
+
+    /// Slewed value
+    value: f32,
+
+    /// Change per sample until the target is reached
+    step: f32,
+
+    /// Time each new target takes to reach, in milliseconds
+    ramp_ms: f32,
+
+    /// Samples each new target takes to reach
+    ramp_samples: f32,
+}
+
+impl Slew {
+    /// Create a slew taking `slew_ms` per change, resting at `value`
+    #[must_use]
+    pub fn new(sample_rate: f32, slew_ms: f32, value: f32) -> Self {
+        let mut slew = Self {
+            target: value,
+            value,
+            step: 0.0,
+            ramp_ms: slew_ms,
+            ramp_samples: 1.0,
+        };
+        slew.set_sample_rate(sample_rate);
+        slew
+    }
+
+    /// Set the rate `process()` is called at
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.ramp_samples = (self.ramp_ms / 1000.0 * sample_rate).max(1.0);
+    }
+
+    /// Move one sample towards `target`, returning the slewed value
+    #[inline]
+    #[allow(clippy::float_cmp)] // Exact changes only, to restart the ramp
+    pub fn process(&mut self, target: f32) -> f32 {
+        if target != self.target {
+            self.target = target;
+            self.step = (target - self.value) / self.ramp_samples;
+        }
+
+        let remaining = self.target - self.value;
+        if remaining.abs() <= self.step.abs() {
+            self.value = self.target;
+        } else {
+            self.value += self.step;
+        }
+        self.value
+    }
+
+    /// Slewed value as of the last `process()`
+    #[must_use]
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Jump straight to `target`
+    pub fn snap(&mut self, target: f32) {
+        self.target = target;
+        self.value = target;
+        self.step = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    #[test]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::float_cmp
+    )] // Exact landing, short test lengths
+    fn test_reaches_any_step_in_slew_time() {
+        let slew_samples = (CUTOFF_SLEW_MS / 1000.0 * SAMPLE_RATE) as usize;
+        for step in [1.0 / 64.0, 1.0, -3.0] {
+            let mut slew = Slew::new(SAMPLE_RATE, CUTOFF_SLEW_MS, 0.5);
+            let target = 0.5 + step;
+
+            let mut previous = 0.5;
+            for _ in 0..slew_samples - 1 {
+                let value = slew.process(target);
+                assert!(
+                    (value - previous) * step > 0.0,
+                    "Should move towards {target}"
+                );
+                previous = value;
+            }
+            assert!((previous - target).abs() > 1e-6, "Arrived early");
+            for _ in 0..2 {
+                previous = slew.process(target);
+            }
+            assert_eq!(previous, target, "Should land exactly on the target");
+        }
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // Snapped values are exact
+    fn test_retargets_mid_ramp_and_snaps() {
+        let mut slew = Slew::new(SAMPLE_RATE, TUNING_SLEW_MS, 0.0);
+        for _ in 0..10 {
+            slew.process(1.0);
+        }
+        let halfway = slew.value();
+
+        // A new target starts a fresh ramp from where the slew is
+        let next = slew.process(0.0);
+        assert!(next < halfway);
+
+        slew.snap(0.25);
+        assert_eq!(slew.value(), 0.25);
+        assert_eq!(slew.process(0.25), 0.25);
+    }
+}
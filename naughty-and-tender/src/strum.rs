@@ -0,0 +1,358 @@
+//! Strum (roll) for Naughty and Tender
+//!
+//! Spreads notes that start together across a short window, like a guitar
+//! strum or a rolled piano chord. Note-ons that arrive on the same sample (a
+//! chord from chord memory, or one sequenced on the grid) form one strum: they
+//! are sorted by pitch and started one after another, evenly spaced so the last
+//! starts at the end of the window. Up strums start from the lowest note, down
+//! strums from the highest, and alternate switches direction every strum.
+//!
+//! Like humanization, strumming can only delay notes. Delays are counted in
+//! samples, so strummed notes land sample-accurately in later blocks, and a
+//! note-off never overtakes its note-on: releasing a chord mid-strum starts the
+//! rest of its notes first and releases them straight after.
+//!
+//! # References
+//! - Guitar strumming: downstrokes sound the low strings first
+//! - Arpeggiated ("rolled") chords in keyboard music, and strum modes in
+//!   Omnichord, Roland and Korg chord-memory synths
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use crate::humanize::KeyEvent;
+use crate::key_queue::KeyQueue;
+
+/// Longest strum window, in milliseconds
+pub const MAX_STRUM_MS: f32 = 500.0;
+
+/// Most note events waiting at once
+pub const MAX_PENDING: usize = 1024;
+
+/// Most notes gathered into one strum (extra notes start unstrummed)
+pub const MAX_STRUM_NOTES: usize = 32;
+
+/// Order the notes of a strum start in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StrumDirection {
+    /// Lowest note first
+    #[default]
+    Up,
+    /// Highest note first
+    Down,
+    /// Up, then down, then up again...
+    Alternate,
+}
+
+impl StrumDirection {
+    /// Every direction, in parameter index order
+    pub const ALL: [Self; 3] = [Self::Up, Self::Down, Self::Alternate];
+
+    /// Display names, in parameter index order
+    pub const NAMES: [&'static str; 3] = ["Up", "Down", "Alternate"];
+
+    /// Direction at a parameter index (out-of-range falls back to `Up`)
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or_default()
+    }
+}
+
+/// Spreads the note-ons of each sample across the strum window
+///
+/// Every note event goes in with [`Strummer::note_on`] or
+/// [`Strummer::note_off`]; once per sample, after the sample's events are in,
+/// [`Strummer::pop_due`] hands back the events due, then [`Strummer::advance`]
+/// moves to the next sample. With the window at zero, notes come back out the
+/// same sample they went in, in strum order.
+///
+/// # Real-time Safety
+/// - Queues allocated once at construction, never grow
+/// - When the queue is short of room, note-ons are dropped; their note-offs
+///   always fit
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::humanize::KeyEvent;
+/// use naughty_and_tender::strum::{StrumDirection, Strummer};
+///
+/// let mut strummer = Strummer::new(48000.0);
+/// strummer.set_time_ms(20.0);
+/// strummer.set_direction(StrumDirection::Down);
+/// for note in [60, 64, 67] {
+///     strummer.note_on(note, 0.8, 0, None);
+/// }
+///
+/// // The top note starts right away, the others follow over 20 ms
+/// assert!(matches!(strummer.pop_due(), Some(KeyEvent::NoteOn { note: 67, .. })));
+/// assert!(strummer.pop_due().is_none());
+/// ```
+pub struct Strummer {
+    /// Sample rate in Hz
+    sample_rate: f32,
+
+    /// Time from the first note of a strum to the last, in milliseconds
+    time_ms: f32,
+
+    /// Order notes start in
+    direction: StrumDirection,
+
+    /// Whether the next alternate strum goes down
+    next_down: bool,
+
+    /// Note-ons of the current sample, not yet strummed
+    gathered: Vec<KeyEvent>,
+
+    /// Strummed events waiting for their delay
+    queue: KeyQueue,
+}
+
+impl Strummer {
+    /// Create a strummer with a zero window, strumming up
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            sample_rate,
+            time_ms: 0.0,
+            direction: StrumDirection::Up,
+            next_down: false,
+            gathered: Vec::with_capacity(MAX_STRUM_NOTES),
+            queue: KeyQueue::new(MAX_PENDING),
+        }
+    }
+
+    /// Set sample rate (forgets pending events)
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.reset();
+    }
+
+    /// Set the time from the first note of a strum to the last (0 - [`MAX_STRUM_MS`])
+    pub fn set_time_ms(&mut self, time_ms: f32) {
+        self.time_ms = time_ms.clamp(0.0, MAX_STRUM_MS);
+    }
+
+    /// Set the order the notes of a strum start in
+    pub fn set_direction(&mut self, direction: StrumDirection) {
+        self.direction = direction;
+    }
+
+    /// Add a note-on to this sample's strum
+    pub fn note_on(&mut self, note: u8, velocity: f32, channel: u8, voice_id: Option<i32>) {
+        let event = KeyEvent::NoteOn {
+            note,
+            velocity,
+            channel,
+            voice_id,
+        };
+        if self.gathered.len() < MAX_STRUM_NOTES {
+            self.gathered.push(event);
+        } else {
+            self.queue.push(0, event);
+        }
+    }
+
+    /// Queue a note-off, after its note-on if that hasn't started yet
+    pub fn note_off(&mut self, note: u8, velocity: f32, channel: u8, voice_id: Option<i32>) {
+        // Strum what's gathered so far, so the note-on is queued ahead of this
+        self.strum();
+        self.queue.push(
+            0,
+            KeyEvent::NoteOff {
+                note,
+                velocity,
+                channel,
+                voice_id,
+            },
+        );
+    }
+
+    /// Next event due at the current sample, in arrival order
+    ///
+    /// Strums the notes gathered this sample first, so call it once the
+    /// sample's note events are in.
+    pub fn pop_due(&mut self) -> Option<KeyEvent> {
+        self.strum();
+        self.queue.pop_due()
+    }
+
+    /// Move on to the next sample
+    #[inline]
+    pub fn advance(&mut self) {
+        self.queue.advance();
+    }
+
+    /// Number of events waiting (strummed or not)
+    #[must_use]
+    pub fn pending_count(&self) -> usize {
+        self.queue.len() + self.gathered.len()
+    }
+
+    /// Forget every pending event
+    pub fn reset(&mut self) {
+        self.gathered.clear();
+        self.queue.reset();
+        self.next_down = false;
+    }
+
+    /// Queue the gathered note-ons, spaced across the window in strum order
+    fn strum(&mut self) {
+        let count = self.gathered.len();
+        if count == 0 {
+            return;
+        }
+
+        let down = match self.direction {
+            StrumDirection::Up => false,
+            StrumDirection::Down => true,
+            StrumDirection::Alternate if count > 1 => {
+                let down = self.next_down;
+                self.next_down = !down;
+                down
+            }
+            StrumDirection::Alternate => self.next_down,
+        };
+        // Unstable sort: it never allocates
+        self.gathered.sort_unstable_by_key(|event| event.note());
+        if down {
+            self.gathered.reverse();
+        }
+
+        #[allow(clippy::cast_precision_loss)] // At most MAX_STRUM_NOTES
+        let spacing = if count > 1 {
+            self.time_ms / 1000.0 * self.sample_rate / (count - 1) as f32
+        } else {
+            0.0
+        };
+        for index in 0..count {
+            #[allow(
+                clippy::cast_possible_truncation,
+                clippy::cast_sign_loss,
+                clippy::cast_precision_loss
+            )] // Up to MAX_STRUM_MS of samples
+            let delay = (spacing * index as f32).round() as u64;
+            let event = self.gathered[index];
+            self.queue.push(delay, event);
+        }
+        self.gathered.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    /// Run until the queue empties, collecting (sample, event)
+    fn drain(strummer: &mut Strummer) -> Vec<(u64, KeyEvent)> {
+        let mut events = Vec::new();
+        while strummer.pending_count() > 0 {
+            while let Some(event) = strummer.pop_due() {
+                events.push((strummer.queue.clock(), event));
+            }
+            strummer.advance();
+        }
+        events
+    }
+
+    fn note_ons(events: &[(u64, KeyEvent)]) -> Vec<(u64, u8)> {
+        events
+            .iter()
+            .filter(|(_, event)| matches!(event, KeyEvent::NoteOn { .. }))
+            .map(|(sample, event)| (*sample, event.note()))
+            .collect()
+    }
+
+    fn strum_chord(strummer: &mut Strummer, notes: &[u8]) -> Vec<(u64, u8)> {
+        for &note in notes {
+            strummer.note_on(note, 1.0, 0, None);
+        }
+        note_ons(&drain(strummer))
+    }
+
+    #[test]
+    fn test_zero_window_keeps_timing_in_strum_order() {
+        let mut strummer = Strummer::new(SAMPLE_RATE);
+        strummer.set_direction(StrumDirection::Down);
+        assert_eq!(
+            strum_chord(&mut strummer, &[64, 60, 67]),
+            [(0, 67), (0, 64), (0, 60)]
+        );
+    }
+
+    #[test]
+    fn test_notes_spread_evenly_across_window() {
+        let mut strummer = Strummer::new(SAMPLE_RATE);
+        strummer.set_time_ms(30.0); // 1440 samples
+        assert_eq!(
+            strum_chord(&mut strummer, &[67, 60, 72, 64]),
+            [(0, 60), (480, 64), (960, 67), (1440, 72)]
+        );
+    }
+
+    #[test]
+    fn test_alternate_switches_direction_each_strum() {
+        let mut strummer = Strummer::new(SAMPLE_RATE);
+        strummer.set_direction(StrumDirection::Alternate);
+        let first: Vec<u8> = strum_chord(&mut strummer, &[60, 64])
+            .iter()
+            .map(|(_, note)| *note)
+            .collect();
+        // A single note isn't a strum and doesn't switch direction
+        strum_chord(&mut strummer, &[50]);
+        let second: Vec<u8> = strum_chord(&mut strummer, &[60, 64])
+            .iter()
+            .map(|(_, note)| *note)
+            .collect();
+        assert_eq!(first, [60, 64]);
+        assert_eq!(second, [64, 60]);
+    }
+
+    #[test]
+    fn test_note_off_waits_for_its_note_on() {
+        let mut strummer = Strummer::new(SAMPLE_RATE);
+        strummer.set_time_ms(10.0); // 480 samples
+        strummer.note_on(60, 1.0, 0, None);
+        strummer.note_on(67, 1.0, 0, None);
+        strummer.note_off(67, 0.5, 0, None);
+        strummer.note_off(60, 0.5, 0, None);
+
+        let events = drain(&mut strummer);
+        let release = |note| {
+            events
+                .iter()
+                .find(|(_, event)| {
+                    *event
+                        == KeyEvent::NoteOff {
+                            note,
+                            velocity: 0.5,
+                            channel: 0,
+                            voice_id: None,
+                        }
+                })
+                .map(|(sample, _)| *sample)
+        };
+        assert_eq!(note_ons(&events), [(0, 60), (480, 67)]);
+        assert_eq!(release(60), Some(0));
+        assert_eq!(release(67), Some(480));
+    }
+
+    #[test]
+    fn test_separate_samples_are_separate_strums() {
+        let mut strummer = Strummer::new(SAMPLE_RATE);
+        strummer.set_time_ms(10.0);
+        strummer.note_on(60, 1.0, 0, None);
+        assert!(matches!(
+            strummer.pop_due(),
+            Some(KeyEvent::NoteOn { note: 60, .. })
+        ));
+        strummer.advance();
+
+        // A note a sample later starts its own strum, on time
+        strummer.note_on(64, 1.0, 0, None);
+        assert!(matches!(
+            strummer.pop_due(),
+            Some(KeyEvent::NoteOn { note: 64, .. })
+        ));
+    }
+}
@@ -0,0 +1,286 @@
+//! Sub-blocks and block scratch for Naughty and Tender
+//!
+//! Hosts may send blocks of 8192 samples or more. The plugin still renders
+//! sample by sample, but a few things happen once per sub-block of
+//! [`SUB_BLOCK_SIZE`] samples, however large the host's blocks are: editor
+//! input (the audition pad) is picked up, and the output is checked for NaN or
+//! infinite samples before it reaches the host.
+//!
+//! Sub-blocks are keyed to the running sample position, not to the start of
+//! each host block, so they fall on the same samples whichever way the host
+//! cuts its blocks (a block ending partway through one has its part checked
+//! before it goes back to the host).
+//!
+//! The synth's output for a host block is gathered in scratch buffers
+//! allocated in `initialize()` (to the host's maximum block size), then checked
+//! and blended with the dry input one sub-block at a time.
+//!
+//! # References
+//! - nih-plug `BufferConfig::max_buffer_size`: the largest block a host sends
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use std::ops::Range;
+
+/// Samples in a sub-block
+pub const SUB_BLOCK_SIZE: usize = 512;
+
+/// The synth's output for one host block, checked and blended per sub-block
+///
+/// # Real-time Safety
+/// - Buffers are allocated by [`BlockScratch::allocate`] (from `initialize()`);
+///   [`BlockScratch::begin`] only grows them for a block larger than the host
+///   promised
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::sub_block::{BlockScratch, SUB_BLOCK_SIZE};
+///
+/// let mut scratch = BlockScratch::new();
+/// scratch.allocate(1024);
+///
+/// // A 600-sample block, 100 samples before a sub-block boundary
+/// let mut left = vec![0.0; 600];
+/// let mut right = vec![0.0; 600];
+/// scratch.begin((SUB_BLOCK_SIZE - 100) as u64, 600);
+/// for index in 0..600 {
+///     scratch.write(index, [0.5, -0.5], 1.0);
+///     if let Some(sub_block) = scratch.end_of_sub_block(index) {
+///         assert!(scratch.is_finite(sub_block.clone()));
+///         scratch.blend(sub_block, &mut [&mut left, &mut right], false);
+///     }
+/// }
+/// assert!(scratch.starts_sub_block(100));
+/// assert_eq!((left[599], right[599]), (0.5, -0.5));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BlockScratch {
+    /// Synth output per channel (left, right)
+    wet: [Vec<f32>; 2],
+
+    /// Gain of the synth's output per sample (the dry input takes the rest)
+    wet_gain: Vec<f32>,
+
+    /// Position of the block's first sample within its sub-block
+    offset: usize,
+
+    /// Samples in the current host block
+    len: usize,
+
+    /// First sample of the sub-block in progress
+    start: usize,
+}
+
+impl BlockScratch {
+    /// Create empty scratch (allocate before use)
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allocate for host blocks of up to `max_block_size` samples
+    pub fn allocate(&mut self, max_block_size: usize) {
+        for buffer in self.wet.iter_mut().chain([&mut self.wet_gain]) {
+            buffer.clear();
+            buffer.resize(max_block_size, 0.0);
+        }
+    }
+
+    /// Start a host block
+    ///
+    /// # Arguments
+    /// * `clock` - Running sample position of the block's first sample
+    /// * `len` - Samples in the block
+    pub fn begin(&mut self, clock: u64, len: usize) {
+        if len > self.wet_gain.len() {
+            // The host sent more than its maximum block size: allocating here
+            // beats not producing the block at all
+            self.allocate(len);
+        }
+        #[allow(clippy::cast_possible_truncation)] // Below SUB_BLOCK_SIZE
+        let offset = (clock % SUB_BLOCK_SIZE as u64) as usize;
+        self.offset = offset;
+        self.len = len;
+        self.start = 0;
+    }
+
+    /// Whether a sub-block starts at a sample of the block
+    #[must_use]
+    pub fn starts_sub_block(&self, index: usize) -> bool {
+        (self.offset + index).is_multiple_of(SUB_BLOCK_SIZE)
+    }
+
+    /// Store the synth's output for a sample of the block
+    ///
+    /// # Arguments
+    /// * `index` - Sample within the block
+    /// * `frame` - Left and right output
+    /// * `wet_gain` - Gain of the output against the dry input (1.0 = all synth)
+    #[inline]
+    pub fn write(&mut self, index: usize, frame: [f32; 2], wet_gain: f32) {
+        self.wet[0][index] = frame[0];
+        self.wet[1][index] = frame[1];
+        self.wet_gain[index] = wet_gain;
+    }
+
+    /// The sub-block that ends with a sample of the block, if one does
+    ///
+    /// Sub-blocks end on a boundary of the running sample position, and the
+    /// last one in a block at the end of the block.
+    pub fn end_of_sub_block(&mut self, index: usize) -> Option<Range<usize>> {
+        let end = index + 1;
+        if end != self.len && !self.starts_sub_block(end) {
+            return None;
+        }
+        let sub_block = self.start..end;
+        self.start = end;
+        Some(sub_block)
+    }
+
+    /// Whether the synth's output over a range of the block is all finite
+    #[must_use]
+    pub fn is_finite(&self, range: Range<usize>) -> bool {
+        self.wet.iter().all(|channel| {
+            channel[range.clone()]
+                .iter()
+                .all(|sample| sample.is_finite())
+        })
+    }
+
+    /// Silence the synth's output over a range of the block
+    pub fn silence(&mut self, range: Range<usize>) {
+        for channel in &mut self.wet {
+            channel[range.clone()].fill(0.0);
+        }
+    }
+
+    /// Write the synth's output over a range of the block to the host's
+    /// channels, blended with what they hold
+    ///
+    /// # Arguments
+    /// * `range` - Samples of the block to write
+    /// * `output` - Host channels (past the second, the right output is used)
+    /// * `dry` - Whether the channels hold a dry input to blend with (without
+    ///   one, they're overwritten)
+    pub fn blend(&self, range: Range<usize>, output: &mut [&mut [f32]], dry: bool) {
+        for (channel, channel_samples) in output.iter_mut().enumerate() {
+            let wet = &self.wet[channel.min(1)];
+            for index in range.clone() {
+                let wet_gain = self.wet_gain[index];
+                let dry_gain = if dry { 1.0 - wet_gain } else { 0.0 };
+                channel_samples[index] = wet[index] * wet_gain + channel_samples[index] * dry_gain;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stand-in for a filter that blows up: once a NaN gets in, it stays
+    /// until reset
+    struct Latching {
+        phase: f32,
+        blown: bool,
+    }
+
+    impl Latching {
+        fn next(&mut self, clock: u64) -> f32 {
+            self.phase = (self.phase + 0.01) % 1.0;
+            self.blown |= clock == 3000;
+            if self.blown {
+                f32::NAN
+            } else {
+                self.phase - 0.5
+            }
+        }
+    }
+
+    /// A test tone for the dry input
+    fn dry_input(length: usize) -> Vec<f32> {
+        #[allow(clippy::cast_precision_loss)] // Test positions are short
+        (0..length).map(|i| (i as f32 * 0.003).sin()).collect()
+    }
+
+    /// Render 5000 samples over a dry input, cut into the given block sizes,
+    /// silencing and resetting on a non-finite sub-block as the plugin does
+    fn render(block_sizes: impl Iterator<Item = usize>) -> Vec<f32> {
+        let length = 5000;
+        let mut scratch = BlockScratch::new();
+        scratch.allocate(1024);
+        let mut source = Latching {
+            phase: 0.0,
+            blown: false,
+        };
+        let mut output = dry_input(length);
+        let mut clock = 0;
+        for block_size in block_sizes {
+            let block_size = block_size.min(length - clock);
+            if block_size == 0 {
+                break;
+            }
+            scratch.begin(clock as u64, block_size);
+            let block = &mut output[clock..clock + block_size];
+            for index in 0..block_size {
+                let sample = source.next((clock + index) as u64);
+                scratch.write(index, [sample, sample], 0.75);
+                if let Some(sub_block) = scratch.end_of_sub_block(index) {
+                    if !scratch.is_finite(sub_block.clone()) {
+                        source.blown = false;
+                        scratch.silence(sub_block.clone());
+                    }
+                    scratch.blend(sub_block, &mut [&mut *block], true);
+                }
+            }
+            clock += block_size;
+        }
+        output
+    }
+
+    #[test]
+    fn test_sub_blocks_follow_the_running_clock() {
+        let mut scratch = BlockScratch::new();
+        scratch.allocate(1000);
+        scratch.begin(300, 1000);
+        let ends: Vec<Range<usize>> = (0..1000)
+            .filter_map(|index| scratch.end_of_sub_block(index))
+            .collect();
+        assert_eq!(ends, vec![0..212, 212..724, 724..1000]);
+        assert!(scratch.starts_sub_block(212));
+        assert!(!scratch.starts_sub_block(0));
+    }
+
+    #[test]
+    fn test_large_block_matches_512_sample_blocks() {
+        let reference = render(std::iter::once(5000));
+        assert!(reference.iter().all(|sample| sample.is_finite()));
+
+        // Only the sub-block holding the blow-up is silenced (leaving the dry input)
+        let dry: Vec<f32> = dry_input(5000).iter().map(|sample| sample * 0.25).collect();
+        assert!(reference[2560..3072] == dry[2560..3072]);
+        assert!(reference[2559..2560] != dry[2559..2560]);
+        assert!(reference[3072..3073] != dry[3072..3073]);
+
+        assert!(render(std::iter::repeat(512)) == reference);
+        assert!(render([100, 4096, 804].into_iter()) == reference);
+
+        // A block ending mid sub-block has already gone to the host, so only
+        // the rest is silenced; the reset still lands on the same boundary
+        let uneven = render(std::iter::repeat(333));
+        assert!(uneven[..2560] == reference[..2560]);
+        assert!(uneven[2997..3072] == dry[2997..3072]);
+        assert!(uneven[3072..] == reference[3072..]);
+    }
+
+    #[test]
+    fn test_blocks_over_the_allocation_still_render() {
+        let mut scratch = BlockScratch::new();
+        scratch.allocate(64);
+        scratch.begin(0, 100);
+        scratch.write(99, [1.0, 1.0], 1.0);
+        let mut output = vec![0.0; 100];
+        scratch.blend(0..100, &mut [&mut output], false);
+        assert!((output[99] - 1.0).abs() < f32::EPSILON);
+    }
+}
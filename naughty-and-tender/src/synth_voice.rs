@@ -0,0 +1,157 @@
+//! Voice interface for the voice manager
+//!
+//! `VoiceManager` decides which voice plays which note: allocation, stealing,
+//! note-off matching, the stuck-note watchdog and the crossfades when a sounding
+//! voice restarts. How a note sounds is entirely behind [`SynthVoice`], so the
+//! manager can host any synth engine. The built-in [`Voice`](crate::voice::Voice)
+//! switches between the oscillator, Karplus-Strong, sample and additive engines
+//! with a parameter; an engine with a different voice structure (FM operators,
+//! say) implements the trait itself and gets a `VoiceManager` of its own.
+//!
+//! Settings come in two kinds. Those every voice shares, and that are set once
+//! per block, travel together as a [`SynthVoice::Params`] snapshot; anything
+//! specific to one engine stays on that engine's manager as its own setter.
+//!
+//! # References
+//! - Mutable Instruments Yarns: a voice allocator assigns notes to voice
+//!   slots, and the voices only play them
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use crate::envelope::EnvelopeState;
+use crate::note_expression::pan_gains;
+#[cfg(feature = "debug-outputs")]
+use crate::probe::ProbeTaps;
+use crate::voice::VoiceState;
+
+/// Samples a voice renders at a time when panned by the default
+/// [`SynthVoice::process_block_stereo`]
+const STEREO_CHUNK: usize = 64;
+
+/// One voice of a polyphonic synth engine
+///
+/// The manager only processes voices that aren't idle; a voice goes idle by
+/// itself once its release has finished.
+///
+/// # Real-time Safety
+/// Every method except the constructor runs on the audio thread, so none may
+/// allocate, lock or block.
+pub trait SynthVoice {
+    /// Settings shared by every voice, compared against the last snapshot each
+    /// block and applied when they change
+    type Params: Clone + PartialEq;
+
+    /// Create an idle voice; each voice slot gets a different `seed` for its
+    /// per-voice randomness
+    fn with_seed(sample_rate: f32, seed: u32) -> Self;
+
+    /// Apply the shared settings
+    fn set_params(&mut self, params: &Self::Params);
+
+    /// Start a note (velocity 0.0 - 1.0), from any state
+    fn note_on(&mut self, note: u8, velocity: f32);
+
+    /// Release the note
+    fn note_off(&mut self) {
+        self.note_off_scaled(1.0);
+    }
+
+    /// Release the note with its release time scaled (1.0 = as set)
+    fn note_off_scaled(&mut self, release_scale: f32);
+
+    /// Add the next `output.len()` samples of this voice into `output`
+    fn process_block(&mut self, output: &mut [f32]);
+
+    /// Add the next `left.len()` samples of this voice into `left` and `right`
+    /// (same length), at its pan
+    ///
+    /// The default reads the pan once per chunk of samples. A voice whose pan
+    /// moves overrides this to pan every sample, so the mix doesn't depend on
+    /// how the host split its blocks.
+    fn process_block_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let mut scratch = [0.0; STEREO_CHUNK];
+        let chunks = left
+            .chunks_mut(STEREO_CHUNK)
+            .zip(right.chunks_mut(STEREO_CHUNK));
+        for (left_chunk, right_chunk) in chunks {
+            let block = &mut scratch[..left_chunk.len()];
+            block.fill(0.0);
+            self.process_block(block);
+
+            let (left_gain, right_gain) = pan_gains(self.pan());
+            for ((left, right), sample) in left_chunk.iter_mut().zip(right_chunk).zip(&*block) {
+                *left += sample * left_gain;
+                *right += sample * right_gain;
+            }
+        }
+    }
+
+    /// Stereo position of the voice's output (-1.0 = left, 1.0 = right), read
+    /// by the default [`SynthVoice::process_block_stereo`]
+    fn pan(&self) -> f32 {
+        0.0
+    }
+
+    /// Whether the voice is sounding (held or releasing)
+    fn is_active(&self) -> bool {
+        self.get_state() != VoiceState::Idle
+    }
+
+    /// Current state
+    fn get_state(&self) -> VoiceState;
+
+    /// MIDI note of the current (or last) note
+    fn get_note(&self) -> u8;
+
+    /// Voice age, set by the manager at each note-on
+    fn get_age(&self) -> u64;
+
+    /// Set voice age (for voice stealing and note-off matching)
+    fn set_age(&mut self, age: u64);
+
+    /// Samples spent held since the last note-on
+    fn get_active_samples(&self) -> u64;
+
+    /// Fade the next note in over the restart crossfade
+    fn crossfade_in(&mut self);
+
+    /// Fade out over the restart crossfade, then go idle
+    fn crossfade_out(&mut self);
+
+    /// Carry over anything that should continue from `previous`, the voice this
+    /// one takes over from when restarting (e.g. the pitch to glide from)
+    fn continue_from(&mut self, _previous: &Self) {}
+
+    /// Samples until this voice falls silent, or `None` while its note is held
+    fn tail_samples(&self) -> Option<u32>;
+
+    /// Amplitude envelope stage, for the diagnostics panel
+    fn envelope_stage(&self) -> EnvelopeState;
+
+    /// Amplitude envelope level (0.0 - 1.0) as of the last processed sample
+    ///
+    /// Read-only: for quietest-voice stealing and the per-voice level meters.
+    fn current_level(&self) -> f32;
+
+    /// Whether every sample since the last reset was finite
+    ///
+    /// An unstable filter or resonator that has blown up keeps producing NaN or
+    /// infinity until it's reset.
+    fn is_finite(&self) -> bool;
+
+    /// Largest gain reduction of the voice's peak limiter since the last call,
+    /// in dB (0.0 for voices without one)
+    fn take_limiter_reduction_db(&mut self) -> f32 {
+        0.0
+    }
+
+    /// Signals inside the voice at its last sample, for the debug outputs
+    /// (silence for voices without taps)
+    #[cfg(feature = "debug-outputs")]
+    fn probe(&self) -> ProbeTaps {
+        ProbeTaps::default()
+    }
+
+    /// Go idle immediately and clear all state
+    fn reset(&mut self);
+}
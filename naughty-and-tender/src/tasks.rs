@@ -0,0 +1,370 @@
+//! Background file work for Naughty and Tender
+//!
+//! Reading and writing files can block for a long time (network drives, sleeping
+//! disks), so none of it happens on the audio or GUI threads. The editor sends
+//! [`FileRequest`]s to the host's background thread through nih-plug's
+//! `BackgroundTask`, and each request carries the sender its [`FileResult`] goes
+//! back through. The editor drains its receiver once per frame and applies each
+//! result in one go, so it never sees a half-built index or a half-loaded preset.
+//!
+//! Samples are the exception: the task publishes a decoded sample straight to the
+//! audio thread's [`SampleSlot`], and the result only reports success. Scope
+//! exports, log writes and autosaves only report success too.
+//!
+//! # References
+//! - nih-plug `Plugin::task_executor` and `AsyncExecutor::execute_background`
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+
+use crate::autosave::{self, AutosaveSession, Snapshot};
+use crate::presets::{Favorites, Preset, PresetError, PresetIndex};
+use crate::sampler::{SampleData, SampleSlot, WavError};
+use crate::scope::{ScopeCapture, ScopeFormat};
+
+/// File work to run off the audio and GUI threads
+#[derive(Debug)]
+pub enum FileTask {
+    /// Index the presets directory and read its favorites
+    ScanPresets { dir: PathBuf },
+
+    /// Read and parse one preset
+    LoadPreset { path: PathBuf },
+
+    /// Write a preset
+    SavePreset { preset: Preset, path: PathBuf },
+
+    /// Write the favorites list to the presets directory
+    SaveFavorites { favorites: Favorites, dir: PathBuf },
+
+    /// Decode a WAV file and hand it to the sampler
+    LoadSample {
+        path: PathBuf,
+        slot: Arc<SampleSlot>,
+    },
+
+    /// Write a scope capture as CSV or PNG
+    ExportScope {
+        capture: ScopeCapture,
+        path: PathBuf,
+        format: ScopeFormat,
+    },
+
+    /// Add lines to the end of a log file, creating it if needed
+    AppendLog { lines: Vec<String>, path: PathBuf },
+
+    /// Replace the autosave snapshot
+    Autosave { preset: Preset, path: PathBuf },
+
+    /// Claim the instance's snapshot file (see [`AutosaveSession::claim`]) and
+    /// look for one an earlier session left there
+    FindSnapshot {
+        session: Arc<AutosaveSession>,
+        saved_id: u64,
+    },
+}
+
+impl FileTask {
+    /// Do the work (blocks on the file system)
+    #[must_use]
+    pub fn run(self) -> FileResult {
+        match self {
+            Self::ScanPresets { dir } => FileResult::PresetsScanned {
+                index: PresetIndex::scan(&dir),
+                favorites: Favorites::load(&dir),
+            },
+            Self::LoadPreset { path } => FileResult::PresetLoaded {
+                preset: Preset::load(&path),
+                path,
+            },
+            Self::SavePreset { preset, path } => FileResult::PresetSaved {
+                result: preset.save(&path),
+                path,
+            },
+            Self::SaveFavorites { favorites, dir } => {
+                FileResult::FavoritesSaved(favorites.save(&dir))
+            }
+            Self::LoadSample { path, slot } => FileResult::SampleLoaded {
+                result: SampleData::load(&path).map(|sample| slot.set(Arc::new(sample))),
+                path,
+            },
+            Self::ExportScope {
+                capture,
+                path,
+                format,
+            } => FileResult::ScopeExported {
+                result: capture.save(&path, format),
+                path,
+            },
+            Self::AppendLog { lines, path } => FileResult::LogAppended {
+                result: append_lines(&path, &lines),
+                path,
+            },
+            Self::Autosave { preset, path } => {
+                FileResult::Autosaved(autosave::write_snapshot(&preset, &path))
+            }
+            Self::FindSnapshot { session, saved_id } => FileResult::SnapshotFound(
+                session
+                    .claim(saved_id)
+                    .map(|id| (id, session.earlier_snapshot())),
+            ),
+        }
+    }
+}
+
+/// Outcome of a [`FileTask`]
+#[derive(Debug)]
+pub enum FileResult {
+    PresetsScanned {
+        index: PresetIndex,
+        favorites: Favorites,
+    },
+    PresetLoaded {
+        path: PathBuf,
+        preset: Result<Preset, PresetError>,
+    },
+    PresetSaved {
+        path: PathBuf,
+        result: Result<(), PresetError>,
+    },
+    FavoritesSaved(Result<(), PresetError>),
+    SampleLoaded {
+        path: PathBuf,
+        result: Result<(), WavError>,
+    },
+    ScopeExported {
+        path: PathBuf,
+        result: io::Result<()>,
+    },
+    LogAppended {
+        path: PathBuf,
+        result: io::Result<()>,
+    },
+    Autosaved(Result<(), PresetError>),
+    /// The claimed autosave id, and the earlier session's snapshot if any
+    SnapshotFound(io::Result<(u64, Option<Snapshot>)>),
+}
+
+/// Append each line, newline terminated, to the file at `path`
+fn append_lines(path: &Path, lines: &[String]) -> io::Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    for line in lines {
+        writeln!(file, "{line}")?;
+    }
+    Ok(())
+}
+
+/// A task and where to send its result (the plugin's `BackgroundTask`)
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::tasks::{FileRequest, FileResult, FileTask};
+/// use std::sync::mpsc;
+///
+/// let (reply, results) = mpsc::channel();
+/// let dir = std::env::temp_dir().join("nt-no-such-presets-dir");
+/// FileRequest::new(FileTask::ScanPresets { dir }, reply).run();
+///
+/// let result = results.try_recv().unwrap();
+/// assert!(matches!(result, FileResult::PresetsScanned { index, .. } if index.entries().is_empty()));
+/// ```
+#[derive(Debug)]
+pub struct FileRequest {
+    task: FileTask,
+    reply: Sender<FileResult>,
+}
+
+impl FileRequest {
+    #[must_use]
+    pub fn new(task: FileTask, reply: Sender<FileResult>) -> Self {
+        Self { task, reply }
+    }
+
+    /// Run the task and send back its result
+    pub fn run(self) {
+        // The editor may have closed while the task ran
+        let _ = self.reply.send(self.task.run());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presets::{file_name, PresetInfo};
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_save_load_and_scan_round_trip() {
+        let dir = std::env::temp_dir().join(format!("nt-tasks-{}", std::process::id()));
+        let path = dir.join(file_name("Bell"));
+        let preset = Preset {
+            info: PresetInfo {
+                name: "Bell".to_string(),
+                ..PresetInfo::default()
+            },
+            values: vec![("gain".to_string(), 0.75)],
+        };
+
+        let (reply, results) = mpsc::channel();
+        for task in [
+            FileTask::SavePreset {
+                preset: preset.clone(),
+                path: path.clone(),
+            },
+            FileTask::LoadPreset { path: path.clone() },
+            FileTask::ScanPresets { dir: dir.clone() },
+        ] {
+            FileRequest::new(task, reply.clone()).run();
+        }
+
+        let results: Vec<FileResult> = results.try_iter().collect();
+        assert!(matches!(
+            &results[0],
+            FileResult::PresetSaved { result: Ok(()), .. }
+        ));
+        assert!(
+            matches!(&results[1], FileResult::PresetLoaded { preset: Ok(loaded), .. } if *loaded == preset)
+        );
+        assert!(
+            matches!(&results[2], FileResult::PresetsScanned { index, .. } if index.entries().len() == 1)
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_preset_reports_error() {
+        let path = std::env::temp_dir().join("nt-tasks-missing.ntpreset");
+        let result = FileTask::LoadPreset { path }.run();
+        assert!(matches!(
+            result,
+            FileResult::PresetLoaded {
+                preset: Err(PresetError::Io(_)),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_loaded_sample_goes_to_slot() {
+        let path = std::env::temp_dir().join(format!("nt-tasks-{}.wav", std::process::id()));
+        let mut wav = b"RIFF\0\0\0\0WAVEfmt \x10\0\0\0\x01\0\x01\0".to_vec();
+        wav.extend_from_slice(&44100u32.to_le_bytes());
+        wav.extend_from_slice(&88200u32.to_le_bytes());
+        wav.extend_from_slice(&[2, 0, 16, 0]);
+        wav.extend_from_slice(b"data\x04\0\0\0\0\x40\0\xC0");
+        std::fs::write(&path, wav).unwrap();
+
+        let slot = Arc::new(SampleSlot::new());
+        let result = FileTask::LoadSample {
+            path: path.clone(),
+            slot: slot.clone(),
+        }
+        .run();
+
+        assert!(matches!(
+            result,
+            FileResult::SampleLoaded { result: Ok(()), .. }
+        ));
+
+        assert_eq!(slot.current().unwrap().frames(), &[0.5, -0.5]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_scope_capture_exported() {
+        let path = std::env::temp_dir().join(format!("nt-tasks-{}.csv", std::process::id()));
+        let capture = ScopeCapture::from_samples(vec![0.0, 0.5, -0.5, 0.0], 48000.0);
+        let result = FileTask::ExportScope {
+            capture,
+            path: path.clone(),
+            format: ScopeFormat::from_path(&path),
+        }
+        .run();
+        assert!(matches!(
+            result,
+            FileResult::ScopeExported { result: Ok(()), .. }
+        ));
+
+        let csv = std::fs::read_to_string(&path).unwrap();
+        assert!(csv.starts_with("time_s,sample,frequency_hz,level_db\n"));
+        let second_sample = csv.lines().nth(2).unwrap();
+        assert!(second_sample.starts_with("0.000021,0.500000,12000.00,"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_log_lines_appended() {
+        let path = std::env::temp_dir().join(format!("nt-tasks-{}.log", std::process::id()));
+        for line in ["first", "second"] {
+            let lines = vec![line.to_string()];
+            let result = FileTask::AppendLog {
+                lines,
+                path: path.clone(),
+            }
+            .run();
+            assert!(matches!(
+                result,
+                FileResult::LogAppended { result: Ok(()), .. }
+            ));
+        }
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "first\nsecond\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_autosave_found_by_next_session() {
+        let dir = std::env::temp_dir().join(format!("nt-tasks-autosave-{}", std::process::id()));
+        let crashed = AutosaveSession::with_dir(dir.clone());
+        let id = crashed.claim(0).unwrap();
+        let path = crashed.path().unwrap();
+        let preset = Preset {
+            info: PresetInfo {
+                name: autosave::SNAPSHOT_NAME.to_string(),
+                ..PresetInfo::default()
+            },
+            values: vec![("gain".to_string(), 0.25)],
+        };
+        let result = FileTask::Autosave {
+            preset: preset.clone(),
+            path: path.clone(),
+        }
+        .run();
+        assert!(matches!(result, FileResult::Autosaved(Ok(()))));
+        let earlier = std::time::SystemTime::now() - std::time::Duration::from_mins(1);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(earlier))
+            .unwrap();
+        drop(crashed);
+
+        let result = FileTask::FindSnapshot {
+            session: Arc::new(AutosaveSession::with_dir(dir.clone())),
+            saved_id: id,
+        }
+        .run();
+        assert!(matches!(
+            result,
+            FileResult::SnapshotFound(Ok((found_id, Some(snapshot))))
+                if found_id == id && snapshot.preset == preset
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_closed_editor_is_ignored() {
+        let (reply, results) = mpsc::channel();
+        drop(results);
+        let dir = std::env::temp_dir().join("nt-no-such-presets-dir");
+        FileRequest::new(FileTask::ScanPresets { dir }, reply).run();
+    }
+}
@@ -0,0 +1,269 @@
+//! Editor themes for Naughty and Tender
+//!
+//! A theme is a small set of design tokens (colors, font sizes and spacing)
+//! shared by egui's own widgets and the editor's custom-drawn ones (step grid,
+//! EQ curve, diagnostics). The selected preset is saved with the editor state.
+//!
+//! Two presets are there for accessibility: High Contrast (white on black with
+//! outlined controls and bright data colors) and Color-Blind Safe (the dark
+//! look with colors that stay apart under deuteranopia and protanopia). Both
+//! take their data colors from the Okabe-Ito palette, so envelope stages,
+//! meters and warnings never rely on telling red from green.
+//!
+//! # References
+//! - Okabe & Ito, "Color Universal Design" (2002): eight colors that stay
+//!   distinct for the common color vision deficiencies
+//! - WCAG 2.1 contrast guidance (1.4.3, 1.4.11)
+
+use nih_plug_egui::egui;
+
+use crate::envelope::EnvelopeState;
+
+/// Available theme presets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum ThemeKind {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+    ColorBlindSafe,
+}
+
+impl ThemeKind {
+    /// Every preset, in menu order
+    pub(crate) const ALL: [Self; 4] = [
+        Self::Dark,
+        Self::Light,
+        Self::HighContrast,
+        Self::ColorBlindSafe,
+    ];
+
+    /// Display name (also the persisted value)
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Dark => "Dark",
+            Self::Light => "Light",
+            Self::HighContrast => "High Contrast",
+            Self::ColorBlindSafe => "Color-Blind Safe",
+        }
+    }
+
+    /// Preset with a given name (unknown names fall back to `Dark`)
+    pub(crate) fn from_name(name: &str) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name)
+            .unwrap_or_default()
+    }
+
+    /// Design tokens for this preset
+    pub(crate) fn theme(self) -> &'static Theme {
+        match self {
+            Self::Dark => &Theme::DARK,
+            Self::Light => &Theme::LIGHT,
+            Self::HighContrast => &Theme::HIGH_CONTRAST,
+            Self::ColorBlindSafe => &Theme::COLOR_BLIND_SAFE,
+        }
+    }
+}
+
+/// Colors, font sizes and spacing used across the editor
+pub(crate) struct Theme {
+    /// Start from egui's dark visuals (otherwise light)
+    pub dark: bool,
+
+    /// Window and panel fill
+    pub panel: egui::Color32,
+
+    /// Selected tabs, active controls and highlighted plot data
+    pub accent: egui::Color32,
+
+    /// Background of custom-drawn displays
+    pub plot_background: egui::Color32,
+
+    /// Reference lines and disabled plot data
+    pub plot_muted: egui::Color32,
+
+    /// Clipping, overloads, out-of-tune needles and pending pickups
+    pub warning: egui::Color32,
+
+    /// Envelope stages: attack, decay, sustain, release
+    pub stages: [egui::Color32; 4],
+
+    /// Text color for every widget (`None` keeps egui's)
+    pub text: Option<egui::Color32>,
+
+    /// Outline drawn around every control (`None` keeps egui's)
+    pub outline: Option<egui::Color32>,
+
+    /// Text on selected tabs and controls (`None` keeps egui's)
+    pub selected_text: Option<egui::Color32>,
+
+    /// Heading text size in points
+    pub heading_size: f32,
+
+    /// Body and control text size in points
+    pub body_size: f32,
+
+    /// Gap between widgets
+    pub item_spacing: f32,
+
+    /// Gap between rows of a parameter grid
+    pub row_spacing: f32,
+
+    /// Gap after each section
+    pub section_spacing: f32,
+}
+
+impl Theme {
+    /// Dark preset (the original editor look)
+    pub(crate) const DARK: Self = Self {
+        dark: true,
+        panel: egui::Color32::from_gray(27),
+        accent: egui::Color32::from_rgb(120, 200, 255),
+        plot_background: egui::Color32::from_gray(24),
+        plot_muted: egui::Color32::from_gray(70),
+        warning: egui::Color32::from_rgb(255, 143, 0),
+        stages: [
+            egui::Color32::from_rgb(110, 200, 120),
+            egui::Color32::from_rgb(230, 200, 80),
+            egui::Color32::from_rgb(120, 200, 255),
+            egui::Color32::from_rgb(230, 100, 90),
+        ],
+        text: None,
+        outline: None,
+        selected_text: None,
+        heading_size: 18.0,
+        body_size: 13.0,
+        item_spacing: 8.0,
+        row_spacing: 6.0,
+        section_spacing: 15.0,
+    };
+
+    /// Light preset
+    pub(crate) const LIGHT: Self = Self {
+        dark: false,
+        panel: egui::Color32::from_gray(245),
+        accent: egui::Color32::from_rgb(30, 110, 190),
+        plot_background: egui::Color32::from_gray(230),
+        plot_muted: egui::Color32::from_gray(175),
+        warning: egui::Color32::from_rgb(255, 100, 0),
+        stages: [
+            egui::Color32::from_rgb(40, 140, 60),
+            egui::Color32::from_rgb(180, 130, 0),
+            egui::Color32::from_rgb(30, 110, 190),
+            egui::Color32::from_rgb(190, 50, 40),
+        ],
+        text: None,
+        outline: None,
+        selected_text: None,
+        heading_size: 18.0,
+        body_size: 13.0,
+        item_spacing: 8.0,
+        row_spacing: 6.0,
+        section_spacing: 15.0,
+    };
+
+    /// White on black, outlined controls, larger text
+    pub(crate) const HIGH_CONTRAST: Self = Self {
+        dark: true,
+        panel: egui::Color32::BLACK,
+        accent: egui::Color32::from_rgb(240, 228, 66),
+        plot_background: egui::Color32::BLACK,
+        plot_muted: egui::Color32::from_gray(150),
+        warning: egui::Color32::from_rgb(255, 120, 200),
+        stages: [
+            egui::Color32::from_rgb(86, 180, 233),
+            egui::Color32::from_rgb(230, 159, 0),
+            egui::Color32::WHITE,
+            egui::Color32::from_rgb(213, 94, 0),
+        ],
+        text: Some(egui::Color32::WHITE),
+        outline: Some(egui::Color32::WHITE),
+        selected_text: Some(egui::Color32::BLACK),
+        heading_size: 20.0,
+        body_size: 15.0,
+        item_spacing: 8.0,
+        row_spacing: 6.0,
+        section_spacing: 15.0,
+    };
+
+    /// The dark look with Okabe-Ito colors
+    pub(crate) const COLOR_BLIND_SAFE: Self = Self {
+        dark: true,
+        panel: egui::Color32::from_gray(27),
+        accent: egui::Color32::from_rgb(86, 180, 233),
+        plot_background: egui::Color32::from_gray(24),
+        plot_muted: egui::Color32::from_gray(70),
+        warning: egui::Color32::from_rgb(230, 159, 0),
+        stages: [
+            egui::Color32::from_rgb(0, 158, 115),
+            egui::Color32::from_rgb(240, 228, 66),
+            egui::Color32::from_rgb(86, 180, 233),
+            egui::Color32::from_rgb(213, 94, 0),
+        ],
+        text: None,
+        outline: None,
+        selected_text: None,
+        heading_size: 18.0,
+        body_size: 13.0,
+        item_spacing: 8.0,
+        row_spacing: 6.0,
+        section_spacing: 15.0,
+    };
+
+    /// Color of an envelope stage (idle is muted)
+    pub(crate) fn stage_color(&self, stage: EnvelopeState) -> egui::Color32 {
+        match stage {
+            EnvelopeState::Idle => self.plot_muted,
+            EnvelopeState::Attack => self.stages[0],
+            EnvelopeState::Decay => self.stages[1],
+            EnvelopeState::Sustain => self.stages[2],
+            EnvelopeState::Release => self.stages[3],
+        }
+    }
+
+    /// Apply the theme to egui's visuals, text styles and spacing
+    pub(crate) fn apply(&self, ctx: &egui::Context) {
+        let mut visuals = if self.dark {
+            egui::Visuals::dark()
+        } else {
+            egui::Visuals::light()
+        };
+        visuals.panel_fill = self.panel;
+        visuals.window_fill = self.panel;
+        visuals.selection.bg_fill = self.accent;
+        visuals.hyperlink_color = self.accent;
+        visuals.warn_fg_color = self.warning;
+        visuals.override_text_color = self.text;
+        if let Some(outline) = self.outline {
+            for widget in [
+                &mut visuals.widgets.noninteractive,
+                &mut visuals.widgets.inactive,
+                &mut visuals.widgets.hovered,
+                &mut visuals.widgets.active,
+                &mut visuals.widgets.open,
+            ] {
+                widget.bg_stroke = egui::Stroke::new(1.0, outline);
+            }
+        }
+        if let Some(selected_text) = self.selected_text {
+            visuals.selection.stroke.color = selected_text;
+        }
+
+        let mut style = (*ctx.style()).clone();
+        style.visuals = visuals;
+        style.spacing.item_spacing = egui::vec2(self.item_spacing, self.item_spacing * 0.5);
+        for (text_style, size) in [
+            (egui::TextStyle::Heading, self.heading_size),
+            (egui::TextStyle::Body, self.body_size),
+            (egui::TextStyle::Button, self.body_size),
+        ] {
+            style
+                .text_styles
+                .insert(text_style, egui::FontId::proportional(size));
+        }
+
+        ctx.set_style(style);
+    }
+}
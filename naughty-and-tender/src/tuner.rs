@@ -0,0 +1,300 @@
+//! Tuner for Naughty and Tender
+//!
+//! The audio thread copies what the tuner listens to (the synth output, or the
+//! external input in effect mode) into a lock-free ring; the editor reads the
+//! latest stretch back and runs the shared YIN pitch detector on it. Detection
+//! happens on the GUI thread, so the audio thread only pays for one atomic store
+//! per sample, and nothing at all while the tuner panel is closed.
+//!
+//! Readings are given against 12-TET with A4 = 440 Hz, the voices' default
+//! tuning, so any deviation comes from detune, glide, pitch modulation or the
+//! master tune.
+//!
+//! # References
+//! - de Cheveigné & Kawahara, "YIN, a fundamental frequency estimator for speech
+//!   and music" (JASA 2002), via `shared_core::pitch`
+//! - Lossy audio tap: a ring of relaxed atomic samples the reader copies
+//!   without stopping the writer
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+
+use shared_core::pitch::PitchDetector;
+
+/// Samples kept in the ring (two periods of 30 Hz at 192 kHz fit)
+pub const TAP_LEN: usize = 16384;
+
+/// Lowest pitch the tuner looks for
+const MIN_HZ: f32 = 30.0;
+
+/// Highest pitch the tuner looks for
+const MAX_HZ: f32 = 4200.0;
+
+/// Lowest clarity shown as a pitch (below this the signal is mostly noise)
+const MIN_CLARITY: f32 = 0.8;
+
+/// Audio shared from the audio thread with the editor's tuner
+///
+/// Samples may be overwritten while the editor reads them; a torn window only
+/// costs one noisy reading.
+///
+/// # Real-time Safety
+/// - Ring allocated once at construction
+/// - `push` only performs relaxed atomic loads and stores
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::tuner::AudioTap;
+///
+/// let tap = AudioTap::new();
+/// tap.set_listening(true);
+/// tap.push(0.5);
+///
+/// let mut latest = [0.0; 2];
+/// tap.copy_latest(&mut latest);
+/// assert_eq!(latest, [0.0, 0.5]);
+/// ```
+pub struct AudioTap {
+    /// Sample bits, oldest overwritten first
+    ring: Box<[AtomicU32]>,
+
+    /// Index the next sample is written to
+    write: AtomicUsize,
+
+    /// Host sample rate (`f32` bits)
+    sample_rate: AtomicU32,
+
+    /// Whether the editor is reading (the audio thread skips the copy otherwise)
+    listening: AtomicBool,
+}
+
+impl Default for AudioTap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioTap {
+    /// Create a silent tap at 44.1 kHz, not listening
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            ring: (0..TAP_LEN).map(|_| AtomicU32::new(0)).collect(),
+            write: AtomicUsize::new(0),
+            sample_rate: AtomicU32::new(44100.0f32.to_bits()),
+            listening: AtomicBool::new(false),
+        }
+    }
+
+    /// Record the host sample rate (audio thread, on initialize)
+    pub fn set_sample_rate(&self, sample_rate: f32) {
+        self.sample_rate
+            .store(sample_rate.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Host sample rate
+    #[must_use]
+    pub fn sample_rate(&self) -> f32 {
+        f32::from_bits(self.sample_rate.load(Ordering::Relaxed))
+    }
+
+    /// Start or stop copying audio (editor thread)
+    pub fn set_listening(&self, listening: bool) {
+        self.listening.store(listening, Ordering::Relaxed);
+    }
+
+    /// Whether the editor wants audio (check once per block)
+    #[must_use]
+    pub fn is_listening(&self) -> bool {
+        self.listening.load(Ordering::Relaxed)
+    }
+
+    /// Append one sample (audio thread)
+    #[inline]
+    pub fn push(&self, sample: f32) {
+        let index = self.write.load(Ordering::Relaxed);
+        self.ring[index].store(sample.to_bits(), Ordering::Relaxed);
+        self.write.store((index + 1) % TAP_LEN, Ordering::Relaxed);
+    }
+
+    /// Fill `out` with the most recent samples, oldest first (editor thread)
+    ///
+    /// At most [`TAP_LEN`] samples are available; any extra at the start of `out`
+    /// are older than the ring and read as silence.
+    pub fn copy_latest(&self, out: &mut [f32]) {
+        let write = self.write.load(Ordering::Relaxed);
+        let len = out.len();
+        let available = len.min(TAP_LEN);
+        out[..len - available].fill(0.0);
+
+        for (offset, sample) in out[len - available..].iter_mut().enumerate() {
+            let index = (write + TAP_LEN - available + offset) % TAP_LEN;
+            *sample = f32::from_bits(self.ring[index].load(Ordering::Relaxed));
+        }
+    }
+}
+
+/// A detected pitch, relative to the nearest note
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TunerReading {
+    /// Detected frequency in Hz
+    pub frequency_hz: f32,
+    /// Nearest MIDI note
+    pub note: u8,
+    /// Deviation from the nearest note (-50 to +50 cents)
+    pub cents: f32,
+    /// How periodic the signal is (0.0 - 1.0)
+    pub clarity: f32,
+}
+
+impl TunerReading {
+    /// Reading for a frequency (clarity 1.0)
+    ///
+    /// # Example
+    /// ```
+    /// use naughty_and_tender::tuner::TunerReading;
+    ///
+    /// let reading = TunerReading::from_frequency(446.0);
+    /// assert_eq!(reading.note, 69); // A4
+    /// assert!((reading.cents - 23.45).abs() < 0.01);
+    /// ```
+    #[must_use]
+    pub fn from_frequency(frequency_hz: f32) -> Self {
+        let exact_note = (69.0 + 12.0 * (frequency_hz / 440.0).log2()).clamp(0.0, 127.0);
+        let nearest = exact_note.round();
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Clamped to 0-127
+        let note = nearest as u8;
+        Self {
+            frequency_hz,
+            note,
+            cents: (exact_note - nearest) * 100.0,
+            clarity: 1.0,
+        }
+    }
+}
+
+/// Editor-side pitch tracking of an [`AudioTap`]
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::tuner::{AudioTap, Tuner};
+///
+/// let tap = AudioTap::new();
+/// let mut tuner = Tuner::new();
+/// assert_eq!(tuner.update(&tap), None); // Silence
+/// ```
+pub struct Tuner {
+    detector: PitchDetector,
+
+    /// Sample rate the detector was built for
+    sample_rate: f32,
+
+    /// Latest window copied out of the tap
+    window: Vec<f32>,
+}
+
+impl Default for Tuner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tuner {
+    #[must_use]
+    pub fn new() -> Self {
+        let detector = PitchDetector::new(44100.0, MIN_HZ, MAX_HZ);
+        let window = vec![0.0; detector.window_len()];
+        Self {
+            detector,
+            sample_rate: 44100.0,
+            window,
+        }
+    }
+
+    /// Detect the pitch of the tap's latest audio (`None` for silence or noise)
+    pub fn update(&mut self, tap: &AudioTap) -> Option<TunerReading> {
+        let sample_rate = tap.sample_rate();
+        if sample_rate.to_bits() != self.sample_rate.to_bits() {
+            self.set_sample_rate(sample_rate);
+        }
+
+        tap.copy_latest(&mut self.window);
+        let pitch = self.detector.detect(&self.window)?;
+        if pitch.clarity < MIN_CLARITY {
+            return None;
+        }
+
+        Some(TunerReading {
+            clarity: pitch.clarity,
+            ..TunerReading::from_frequency(pitch.frequency_hz)
+        })
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.detector = PitchDetector::new(sample_rate, MIN_HZ, MAX_HZ);
+        self.sample_rate = sample_rate;
+        self.window
+            .resize(self.detector.window_len().min(TAP_LEN), 0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    fn tap_with_sine(frequency_hz: f32) -> AudioTap {
+        let tap = AudioTap::new();
+        tap.set_sample_rate(SAMPLE_RATE);
+        for i in 0..TAP_LEN + 100 {
+            #[allow(clippy::cast_precision_loss)]
+            let phase = std::f32::consts::TAU * frequency_hz * i as f32 / SAMPLE_RATE;
+            tap.push(0.5 * phase.sin());
+        }
+        tap
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // Samples are copied, not computed
+    fn test_copy_latest_is_oldest_first_after_wrapping() {
+        let tap = AudioTap::new();
+        for i in 0..TAP_LEN + 3 {
+            #[allow(clippy::cast_precision_loss)]
+            tap.push(i as f32);
+        }
+
+        let mut latest = [0.0; 4];
+        tap.copy_latest(&mut latest);
+        #[allow(clippy::cast_precision_loss)] // Test positions are short
+        let newest = (TAP_LEN + 2) as f32;
+        assert_eq!(latest, [newest - 3.0, newest - 2.0, newest - 1.0, newest]);
+    }
+
+    #[test]
+    fn test_tuner_reads_detuned_note() {
+        // A3 plus 10 cents
+        let frequency = 220.0 * 2.0f32.powf(10.0 / 1200.0);
+        let reading = Tuner::new().update(&tap_with_sine(frequency)).unwrap();
+
+        assert_eq!(reading.note, 57);
+        assert!((reading.cents - 10.0).abs() < 0.5, "{reading:?}");
+    }
+
+    #[test]
+    fn test_tuner_follows_sample_rate() {
+        let mut tuner = Tuner::new();
+        let reading = tuner.update(&tap_with_sine(1000.0)).unwrap();
+        assert!((reading.frequency_hz - 1000.0).abs() < 1.0, "{reading:?}");
+    }
+
+    #[test]
+    fn test_cents_wrap_to_nearest_note() {
+        // 60 cents above A4 is 40 cents below A#4
+        let reading = TunerReading::from_frequency(440.0 * 2.0f32.powf(60.0 / 1200.0));
+        assert_eq!(reading.note, 70);
+        assert!((reading.cents + 40.0).abs() < 0.01);
+    }
+}
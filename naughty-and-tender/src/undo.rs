@@ -0,0 +1,194 @@
+//! Undo history for editor parameter edits
+//!
+//! Hosts handle undo for plugin GUIs inconsistently, so the editor keeps its own
+//! history. Each entry is one gesture: every parameter that changed between the
+//! start and end of a drag, click or text edit, stored as normalized values so one
+//! history covers every parameter type.
+//!
+//! The history only stores values; the editor decides what counts as a gesture
+//! and applies the values through the host.
+//!
+//! # References
+//! - Command pattern undo stacks (the redo stack is cleared by new edits)
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+/// Default number of gestures kept
+pub const DEFAULT_UNDO_DEPTH: usize = 100;
+
+/// One parameter's normalized value before and after a gesture
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParamChange {
+    /// Position of the parameter in the editor's parameter list
+    pub index: usize,
+    pub before: f32,
+    pub after: f32,
+}
+
+/// Parameters whose values differ between two snapshots
+///
+/// Snapshots are normalized values in the same parameter order; extra entries in
+/// the longer snapshot are ignored.
+#[must_use]
+pub fn diff(before: &[f32], after: &[f32]) -> Vec<ParamChange> {
+    before
+        .iter()
+        .zip(after)
+        .enumerate()
+        .filter(|(_, (before, after))| (*before - *after).abs() > f32::EPSILON)
+        .map(|(index, (&before, &after))| ParamChange {
+            index,
+            before,
+            after,
+        })
+        .collect()
+}
+
+/// Undo and redo stacks of parameter gestures
+///
+/// # Real-time Safety
+/// - GUI thread only (entries are heap allocated)
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::undo::{diff, UndoHistory};
+///
+/// let mut history = UndoHistory::new(10);
+/// history.record(diff(&[0.0, 0.5], &[1.0, 0.5]));
+///
+/// assert_eq!(history.undo(), Some(vec![(0, 0.0)]));
+/// assert_eq!(history.redo(), Some(vec![(0, 1.0)]));
+/// ```
+pub struct UndoHistory {
+    undo: Vec<Vec<ParamChange>>,
+    redo: Vec<Vec<ParamChange>>,
+
+    /// Most gestures kept (oldest are dropped first)
+    depth: usize,
+}
+
+impl Default for UndoHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_UNDO_DEPTH)
+    }
+}
+
+impl UndoHistory {
+    /// Create an empty history keeping up to `depth` gestures
+    #[must_use]
+    pub fn new(depth: usize) -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            depth: depth.max(1),
+        }
+    }
+
+    /// Record a finished gesture (empty gestures are ignored)
+    ///
+    /// A new gesture clears the redo stack.
+    pub fn record(&mut self, changes: Vec<ParamChange>) {
+        if changes.is_empty() {
+            return;
+        }
+
+        self.redo.clear();
+        self.undo.push(changes);
+        if self.undo.len() > self.depth {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Step back one gesture, returning (index, normalized value) pairs to apply
+    pub fn undo(&mut self) -> Option<Vec<(usize, f32)>> {
+        let changes = self.undo.pop()?;
+        let values = changes.iter().map(|c| (c.index, c.before)).collect();
+        self.redo.push(changes);
+        Some(values)
+    }
+
+    /// Step forward one gesture, returning (index, normalized value) pairs to apply
+    pub fn redo(&mut self) -> Option<Vec<(usize, f32)>> {
+        let changes = self.redo.pop()?;
+        let values = changes.iter().map(|c| (c.index, c.after)).collect();
+        self.undo.push(changes);
+        Some(values)
+    }
+
+    /// Whether there is a gesture to undo
+    #[must_use]
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Whether there is a gesture to redo
+    #[must_use]
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Forget every gesture
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_finds_changed_params() {
+        let changes = diff(&[0.0, 0.5, 1.0], &[0.0, 0.25, 1.0]);
+        assert_eq!(
+            changes,
+            vec![ParamChange {
+                index: 1,
+                before: 0.5,
+                after: 0.25
+            }]
+        );
+    }
+
+    #[test]
+    fn test_multi_param_gesture_undoes_together() {
+        let mut history = UndoHistory::new(10);
+        history.record(diff(&[0.0, 0.0], &[0.3, 0.7]));
+
+        assert_eq!(history.undo(), Some(vec![(0, 0.0), (1, 0.0)]));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), Some(vec![(0, 0.3), (1, 0.7)]));
+        assert_eq!(history.redo(), None);
+    }
+
+    #[test]
+    fn test_new_gesture_clears_redo() {
+        let mut history = UndoHistory::new(10);
+        history.record(diff(&[0.0], &[1.0]));
+        history.undo();
+        assert!(history.can_redo());
+
+        history.record(diff(&[0.0], &[0.5]));
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_empty_gesture_is_ignored() {
+        let mut history = UndoHistory::new(10);
+        history.record(diff(&[0.5], &[0.5]));
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn test_depth_drops_oldest() {
+        let mut history = UndoHistory::new(2);
+        history.record(diff(&[0.0], &[0.1]));
+        history.record(diff(&[0.1], &[0.2]));
+        history.record(diff(&[0.2], &[0.3]));
+
+        assert_eq!(history.undo(), Some(vec![(0, 0.2)]));
+        assert_eq!(history.undo(), Some(vec![(0, 0.1)]));
+        assert_eq!(history.undo(), None);
+    }
+}
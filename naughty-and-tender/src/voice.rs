@@ -6,11 +6,328 @@
 //! # References
 //! - Voice stealing: Steal oldest active voice or releasing voice first
 //! - MIDI note to frequency: f = 440 * 2^((note - 69) / 12)
+//! - Poly glide: each voice slides from the last pitch it played, in semitones
+//!   (exponential in frequency, so every octave of a glide sounds the same).
+//!   Constant time takes the glide time for any interval; constant rate takes
+//!   it per octave, like a fixed-rate analog portamento
+//! - Glissando: the glide steps through whole notes instead of sliding, each
+//!   note held until the smooth glide passes the next one; in scale mode only
+//!   the scale's degrees are played (harp or keyboard glissando)
+//! - Anti-click: notes with (near) instant attacks get a short fade-in, so a
+//!   waveform starting away from zero doesn't step from silence
+//! - Crossfading restart: a stolen or retriggered voice hands its old note to a
+//!   spare "tail" voice, which fades it out while the slot fades the new note in
+//! - Choke groups: drum machine open/closed hi-hat pairs (TR-808, MPC "mute
+//!   groups"), where any note of a group cuts off the others
+//! - Host voice ids: CLAP note ids and note-end events. Voices remember the id
+//!   they were started with (or one made from the note and channel) and report
+//!   it once they fall silent
 
 #![allow(dead_code)] // Some methods may not be used initially
 
-use crate::envelope::ADSREnvelope;
-use crate::oscillators::{Oscillator, WaveformType};
+use crate::additive::{AdditiveOscillator, NUM_PARTIALS};
+use crate::diagnostics::VoiceSnapshot;
+use crate::envelope::{ADSREnvelope, EnvelopeState, FilterEnvelopeSettings};
+use crate::granular::GranularSettings;
+use crate::karplus::{ExcitationType, KarplusStrong};
+use crate::limiter::{SoftLimiter, DEFAULT_CEILING_DB};
+use crate::modulation::{
+    pitch_source, ModMatrix, ModOffsets, ModRange, ModSourceValues, DEFAULT_CONTROL_DIVISOR,
+    MAX_CONTROL_DIVISOR,
+};
+use crate::note_expression::{pan_gains, ExpressionValues};
+use crate::oscillators::{NaiveOscillator, Oscillator, SineMode, WaveformType};
+use crate::pitch_bend::{BendSlew, DEFAULT_BEND_RANGE};
+#[cfg(feature = "debug-outputs")]
+use crate::probe::ProbeTaps;
+use crate::random::SampleAndHold;
+use crate::sampler::{Interpolation, SampleData, SamplePlayer, DEFAULT_ROOT_NOTE};
+use crate::scale::{in_scale, ScaleMask, FULL_MASK, ROOT_NAMES};
+use crate::slew::{Slew, CUTOFF_SLEW_MS, TUNING_SLEW_MS};
+use crate::synth_voice::SynthVoice;
+use shared_core::crossfade::Crossfade;
+use shared_core::effects::waveshaper::{Waveshaper, WaveshaperSettings};
+use shared_core::noise::NoiseGenerator;
+use shared_core::svf::{FilterPrecision, StateVariableFilter, SvfMode};
+use std::sync::Arc;
+
+/// Length of the anti-click fade-in, in milliseconds
+const FADE_IN_MS: f32 = 1.5;
+
+/// Attacks shorter than this (in milliseconds) get the anti-click fade-in
+const FADE_IN_MAX_ATTACK_MS: f32 = 2.0;
+
+/// Length of the crossfade when a sounding voice restarts, in milliseconds
+const CROSSFADE_MS: f32 = 4.0;
+
+/// Spare voices that play out the old notes of restarted voices (keeping
+/// their voice ids until they fall silent)
+pub const CROSSFADE_TAILS: usize = 4;
+
+/// Key presses of one note tracked for note-off matching (older ones are forgotten)
+const MAX_HELD_PER_NOTE: usize = 8;
+
+/// Note ranges that can be assigned to choke groups
+pub const NUM_CHOKE_RANGES: usize = 4;
+
+/// Highest choke group id (0 = no group)
+pub const MAX_CHOKE_GROUP: u8 = 4;
+
+/// Release time multiplier for notes cut off by a fast-release choke
+const CHOKE_RELEASE_SCALE: f32 = 0.1;
+
+/// Time constants per glide for the exponential curve (about 98% of the way
+/// there before the rescaling)
+const GLIDE_CURVE_RATE: f32 = 4.0;
+
+/// Standard pitch reference: A4 in Hz
+pub const STANDARD_A4_HZ: f32 = 440.0;
+
+/// Modulation ramp that holds every destination where it is
+const NO_MODULATION_CHANGE: ModOffsets = ModOffsets {
+    pitch_semitones: 0.0,
+    cutoff_octaves: 0.0,
+    level: 0.0,
+};
+
+/// Sound source used by a voice
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoiceEngine {
+    /// Classic oscillator waveforms
+    Oscillator,
+    /// Karplus-Strong plucked string model
+    KarplusStrong,
+    /// Pitched playback of a loaded sample
+    Sampler,
+    /// Bank of harmonic sine partials
+    Additive,
+}
+
+/// How the voice manager picks a voice for a new note
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VoiceAllocation {
+    /// Lowest-numbered idle voice
+    #[default]
+    FirstIdle,
+    /// Next idle voice after the last one used, wrapping around
+    RoundRobin,
+    /// Strictly the next voice in turn, stealing it if it's still sounding
+    Rotate,
+}
+
+impl VoiceAllocation {
+    /// Every mode, in parameter index order
+    pub const ALL: [Self; 3] = [Self::FirstIdle, Self::RoundRobin, Self::Rotate];
+
+    /// Display names, in parameter index order
+    pub const NAMES: [&'static str; 3] = ["First Idle", "Round Robin", "Rotate"];
+
+    /// Mode at a parameter index (out-of-range falls back to `FirstIdle`)
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or_default()
+    }
+}
+
+/// Which sounding voice a new note takes over when every voice is busy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VoiceStealing {
+    /// Oldest releasing voice, else the oldest held one
+    #[default]
+    Oldest,
+    /// Voice with the lowest envelope level, releasing or held
+    Quietest,
+}
+
+impl VoiceStealing {
+    /// Every mode, in parameter index order
+    pub const ALL: [Self; 2] = [Self::Oldest, Self::Quietest];
+
+    /// Display names, in parameter index order
+    pub const NAMES: [&'static str; 2] = ["Oldest", "Quietest"];
+
+    /// Mode at a parameter index (out-of-range falls back to `Oldest`)
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or_default()
+    }
+}
+
+/// How a note cuts off the other notes of its choke group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChokePolicy {
+    /// Fade out over the restart crossfade (a few milliseconds)
+    #[default]
+    Hard,
+    /// Release at a tenth of the release time, from wherever the note is
+    FastRelease,
+}
+
+impl ChokePolicy {
+    /// Every policy, in parameter index order
+    pub const ALL: [Self; 2] = [Self::Hard, Self::FastRelease];
+
+    /// Display names, in parameter index order
+    pub const NAMES: [&'static str; 2] = ["Hard", "Fast Release"];
+
+    /// Policy at a parameter index (out-of-range falls back to `Hard`)
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or_default()
+    }
+}
+
+/// Voice id of a sounding voice, reported back to the host when the voice ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VoiceTag {
+    /// The host's voice id, or [`fallback_voice_id`] when it sent none
+    pub voice_id: i32,
+    pub channel: u8,
+    pub note: u8,
+}
+
+impl VoiceTag {
+    /// Tag for a note, with the host's voice id if it sent one
+    #[must_use]
+    pub fn new(note: u8, channel: u8, voice_id: Option<i32>) -> Self {
+        Self {
+            voice_id: voice_id.unwrap_or_else(|| fallback_voice_id(note, channel)),
+            channel,
+            note,
+        }
+    }
+}
+
+/// Voice id for a note the host sent without one (stable for the note and channel)
+#[must_use]
+pub fn fallback_voice_id(note: u8, channel: u8) -> i32 {
+    i32::from(note) | (i32::from(channel) << 16)
+}
+
+/// Range of notes (inclusive) that belong to a choke group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ChokeRange {
+    pub low: u8,
+    pub high: u8,
+    /// Group id (1 to `MAX_CHOKE_GROUP`, 0 = no group)
+    pub group: u8,
+}
+
+/// What a note-on does when the same note is already sounding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SameNotePolicy {
+    /// Restart the note's voice
+    #[default]
+    Retrigger,
+    /// Start another voice; each note-off releases the newest one
+    Stack,
+    /// Start another voice and release the previous one, so its tail overlaps
+    MonoPerNote,
+}
+
+impl SameNotePolicy {
+    /// Every policy, in parameter index order
+    pub const ALL: [Self; 3] = [Self::Retrigger, Self::Stack, Self::MonoPerNote];
+
+    /// Display names, in parameter index order
+    pub const NAMES: [&'static str; 3] = ["Retrigger", "Stack", "Mono per Note"];
+
+    /// Policy at a parameter index (out-of-range falls back to `Retrigger`)
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or_default()
+    }
+}
+
+/// How long a glide takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GlideMode {
+    /// The glide time, whatever the interval
+    #[default]
+    ConstantTime,
+    /// The glide time per octave, so wider intervals take longer
+    ConstantRate,
+}
+
+impl GlideMode {
+    /// Every mode, in parameter index order
+    pub const ALL: [Self; 2] = [Self::ConstantTime, Self::ConstantRate];
+
+    /// Display names, in parameter index order
+    pub const NAMES: [&'static str; 2] = ["Constant Time", "Constant Rate"];
+
+    /// Mode at a parameter index (out-of-range falls back to `ConstantTime`)
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or_default()
+    }
+}
+
+/// Shape of the pitch movement over a glide
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GlideCurve {
+    /// Even steps in semitones
+    #[default]
+    Linear,
+    /// Fast at first, easing into the target note (RC-style portamento)
+    Exponential,
+}
+
+impl GlideCurve {
+    /// Every curve, in parameter index order
+    pub const ALL: [Self; 2] = [Self::Linear, Self::Exponential];
+
+    /// Display names, in parameter index order
+    pub const NAMES: [&'static str; 2] = ["Linear", "Exponential"];
+
+    /// Curve at a parameter index (out-of-range falls back to `Linear`)
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or_default()
+    }
+
+    /// Fraction of the interval covered at `progress` (both 0.0 - 1.0)
+    ///
+    /// The exponential curve is an RC charge rescaled to land exactly on the
+    /// target at the end of the glide instead of approaching it forever.
+    #[inline]
+    #[must_use]
+    pub fn shape(self, progress: f32) -> f32 {
+        match self {
+            Self::Linear => progress,
+            Self::Exponential => {
+                (1.0 - (-GLIDE_CURVE_RATE * progress).exp())
+                    / (1.0 - (-GLIDE_CURVE_RATE).exp())
+            }
+        }
+    }
+}
+
+/// Whether a glide slides or steps through notes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Glissando {
+    /// Smooth portamento
+    #[default]
+    Off,
+    /// Steps through every semitone
+    Chromatic,
+    /// Steps through the scale's degrees
+    Scale,
+}
+
+impl Glissando {
+    /// Every mode, in parameter index order
+    pub const ALL: [Self; 3] = [Self::Off, Self::Chromatic, Self::Scale];
+
+    /// Display names, in parameter index order
+    pub const NAMES: [&'static str; 3] = ["Off", "Chromatic", "Scale"];
+
+    /// Mode at a parameter index (out-of-range falls back to `Off`)
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or_default()
+    }
+}
 
 /// Voice state machine
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -20,20 +337,294 @@ pub enum VoiceState {
     Releasing,
 }
 
+/// Settings shared by every voice of a manager
+///
+/// The plugin builds one snapshot per layer each block and hands it to
+/// [`VoiceManager::set_params`], which only passes it on to the voices when
+/// something changed. A new per-voice setting is a field here plus a line in
+/// the voice's `apply_params`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(clippy::struct_excessive_bools)] // Independent settings, not a state machine
+pub struct VoiceParams {
+    /// Sound engine (sounding notes switch at once)
+    pub engine: VoiceEngine,
+    pub waveform: WaveformType,
+    /// `sin()` or a table read for the sine waveform
+    pub sine_mode: SineMode,
+
+    pub attack_ms: f32,
+    pub decay_ms: f32,
+    /// Sustain level (0.0 - 1.0)
+    pub sustain_level: f32,
+    pub release_ms: f32,
+
+    /// Glide time (0 = off); takes effect from the next note
+    pub glide_ms: f32,
+    pub glide_mode: GlideMode,
+    pub glide_curve: GlideCurve,
+    pub glissando: Glissando,
+    /// Scale a scale glissando steps through: root pitch class and degrees
+    pub glissando_root: u8,
+    pub glissando_mask: ScaleMask,
+
+    /// Keep oscillator phase across notes instead of restarting it
+    pub free_running_phase: bool,
+    /// Phase notes restart at, in degrees (0 - 360)
+    pub start_phase_degrees: f32,
+    /// Restart each note at a random phase instead of the start phase
+    pub random_phase: bool,
+
+    pub string_excitation: ExcitationType,
+    /// String loop damping (0.0 - 1.0)
+    pub string_damping: f32,
+    /// String decay time (-60 dB) in milliseconds
+    pub string_decay_ms: f32,
+
+    pub sample_interpolation: Interpolation,
+    pub sample_loop: bool,
+    /// Loop points (fractions of the sample length)
+    pub sample_loop_start: f32,
+    pub sample_loop_end: f32,
+    /// Playback start (fraction of the sample length)
+    pub sample_start: f32,
+    /// Note at which the sample plays at its recorded pitch
+    pub sample_root_note: u8,
+    pub sample_granular: bool,
+    pub granular: GranularSettings,
+
+    /// Gain of each additive partial (see `additive::partial_gains`)
+    pub additive_gains: [f32; NUM_PARTIALS],
+
+    pub waveshaper_enabled: bool,
+    pub waveshaper: WaveshaperSettings,
+
+    pub filter_enabled: bool,
+    pub filter_mode: SvfMode,
+    /// Cutoff before modulation, in Hz
+    pub filter_cutoff_hz: f32,
+    /// Resonance (0.0 - 1.0)
+    pub filter_resonance: f32,
+    /// Filter envelope times and depth (depth takes effect on the next note)
+    pub filter_envelope: FilterEnvelopeSettings,
+    /// f32 or f64 filter arithmetic
+    pub filter_precision: FilterPrecision,
+
+    pub limiter_enabled: bool,
+    /// Peak limiter ceiling in dBFS
+    pub limiter_ceiling_db: f32,
+
+    /// Random source clock rate in Hz
+    pub random_rate_hz: f32,
+    /// Random source slew time in milliseconds
+    pub random_slew_ms: f32,
+
+    pub mod_matrix: ModMatrix,
+
+    /// Samples per control-rate update of the modulation (1 = every sample)
+    pub control_divisor: u32,
+
+    /// Pitch reference: frequency of A4 in Hz
+    pub a4_hz: f32,
+    /// Fine tuning on top of the reference, in cents
+    pub fine_tune_cents: f32,
+
+    /// Pitch change at full pitch bend, in semitones
+    pub bend_range: f32,
+
+    /// Blend from the engine (0.0) to the external input (1.0)
+    pub input_mix: f32,
+
+    /// Groups a sounding voice keeps as they were at its note on
+    pub latch: LatchGroups,
+}
+
+impl Default for VoiceParams {
+    fn default() -> Self {
+        Self {
+            engine: VoiceEngine::Oscillator,
+            waveform: WaveformType::Sine,
+            sine_mode: SineMode::Exact,
+            attack_ms: 10.0,
+            decay_ms: 100.0,
+            sustain_level: 0.7,
+            release_ms: 100.0,
+            glide_ms: 0.0,
+            glide_mode: GlideMode::default(),
+            glide_curve: GlideCurve::default(),
+            glissando: Glissando::default(),
+            glissando_root: 0,
+            glissando_mask: FULL_MASK,
+            free_running_phase: false,
+            start_phase_degrees: 0.0,
+            random_phase: false,
+            string_excitation: ExcitationType::Noise,
+            string_damping: 0.5,
+            string_decay_ms: 2000.0,
+            sample_interpolation: Interpolation::default(),
+            sample_loop: false,
+            sample_loop_start: 0.0,
+            sample_loop_end: 1.0,
+            sample_start: 0.0,
+            sample_root_note: DEFAULT_ROOT_NOTE,
+            sample_granular: false,
+            granular: GranularSettings::default(),
+            additive_gains: [0.0; NUM_PARTIALS],
+            waveshaper_enabled: false,
+            waveshaper: WaveshaperSettings::default(),
+            filter_enabled: false,
+            filter_mode: SvfMode::LowPass,
+            filter_cutoff_hz: 1000.0,
+            filter_resonance: 0.0,
+            filter_envelope: FilterEnvelopeSettings::default(),
+            filter_precision: FilterPrecision::Single,
+            limiter_enabled: false,
+            limiter_ceiling_db: DEFAULT_CEILING_DB,
+            random_rate_hz: 4.0,
+            random_slew_ms: 0.0,
+            mod_matrix: ModMatrix::default(),
+            control_divisor: DEFAULT_CONTROL_DIVISOR,
+            a4_hz: STANDARD_A4_HZ,
+            fine_tune_cents: 0.0,
+            bend_range: DEFAULT_BEND_RANGE,
+            input_mix: 0.0,
+            latch: LatchGroups::default(),
+        }
+    }
+}
+
+impl VoiceParams {
+    /// These settings with the groups they latch taken from `held`, the
+    /// settings a sounding note started with
+    #[must_use]
+    pub fn latched(&self, held: &Self) -> Self {
+        let mut params = *self;
+        if self.latch.envelope {
+            params.attack_ms = held.attack_ms;
+            params.decay_ms = held.decay_ms;
+            params.sustain_level = held.sustain_level;
+            params.release_ms = held.release_ms;
+        }
+        if self.latch.filter {
+            params.filter_enabled = held.filter_enabled;
+            params.filter_mode = held.filter_mode;
+            params.filter_cutoff_hz = held.filter_cutoff_hz;
+            params.filter_resonance = held.filter_resonance;
+            params.filter_envelope = held.filter_envelope;
+        }
+        params
+    }
+}
+
+/// Settings a sounding voice keeps from its note on instead of following the
+/// parameters, so a change only reaches the notes that start after it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LatchGroups {
+    /// Attack, decay, sustain and release
+    pub envelope: bool,
+    /// Filter on/off, mode, cutoff, resonance and envelope
+    pub filter: bool,
+}
+
+impl LatchGroups {
+    /// Whether any group is latched
+    #[must_use]
+    pub fn any(self) -> bool {
+        self.envelope || self.filter
+    }
+}
+
 /// Single synthesizer voice
 ///
-/// Each voice contains an oscillator and envelope, and tracks a MIDI note number.
+/// Each voice contains an oscillator, a plucked string model, a sample player, an
+/// additive partial bank and an envelope, and tracks a MIDI note number. The
+/// active engine decides which source is heard.
+///
+/// Signal flow: source (+ external input) → filter → limiter → drive → envelope →
+/// modulated level
 ///
 /// # Real-time Safety
-/// - All components pre-allocated
+/// - All components pre-allocated (including the string's delay line)
 /// - No allocations in `process()`
+#[allow(clippy::struct_excessive_bools)] // Independent per-voice flags, not a state machine
 pub struct Voice {
     /// Oscillator for generating waveforms
-    oscillator: Oscillator,
+    oscillator: NaiveOscillator,
+
+    /// Karplus-Strong string for physical-modeling plucks
+    string: KarplusStrong,
+
+    /// Sample player (shares the loaded sample with the other voices)
+    sampler: SamplePlayer,
+
+    /// Additive partial bank
+    additive: AdditiveOscillator,
+
+    /// Active sound source
+    engine: VoiceEngine,
+
+    /// Latest external input sample
+    input: f32,
+
+    /// Blend from the engine (0.0) to the external input (1.0)
+    input_mix: f32,
+
+    /// Per-voice filter
+    filter: StateVariableFilter,
+
+    /// Whether the filter is in the signal path
+    filter_enabled: bool,
+
+    /// Filter cutoff before modulation, in octaves above 1 Hz
+    filter_cutoff_octaves: f32,
+
+    /// Filter cutoff slewed towards the latest value, in octaves
+    cutoff_slew: Slew,
+
+    /// ADSR envelope sweeping the filter cutoff (runs at control rate)
+    filter_envelope: ADSREnvelope,
+
+    /// Filter envelope times and depth
+    filter_env: FilterEnvelopeSettings,
+
+    /// Filter envelope depth for the current note, in octaves
+    filter_env_depth: f32,
+
+    /// Peak limiter catching resonant peaks straight after the filter
+    limiter: SoftLimiter,
+
+    /// Whether the limiter is in the signal path
+    limiter_enabled: bool,
+
+    /// Per-voice drive, applied after the filter and before the envelope
+    shaper: Waveshaper,
+
+    /// Whether the per-voice drive is in the signal path
+    shaper_enabled: bool,
 
     /// ADSR envelope for amplitude control
     envelope: ADSREnvelope,
 
+    /// Envelope attack time in milliseconds (decides whether notes fade in)
+    attack_ms: f32,
+
+    /// Anti-click fade-in, or the incoming side of a restart crossfade
+    fade_in: Crossfade,
+
+    /// Outgoing side of a restart crossfade (tail voices only)
+    fade_out: Crossfade,
+
+    /// Keep oscillator phase across notes instead of restarting at zero
+    free_running_phase: bool,
+
+    /// Phase each note restarts at, in cycles (0.0 - 1.0)
+    start_phase: f32,
+
+    /// Restart each note at a random phase instead of `start_phase`
+    random_phase: bool,
+
+    /// Source of random start phases
+    phase_noise: NoiseGenerator,
+
     /// MIDI note number (0-127)
     note: u8,
 
@@ -43,37 +634,193 @@ pub struct Voice {
     /// Current waveform type
     waveform: WaveformType,
 
-    /// Voice age (for voice stealing)
+    /// Voice age (for voice stealing); unique per note-on, so it doubles as the
+    /// note-on's id for note-off matching
     age: u64,
+
+    /// Samples spent in Active since the last note-on (for the stuck-note watchdog)
+    active_samples: u64,
+
+    /// Current pitch in (fractional) MIDI notes, moves towards `note` while gliding
+    pitch: f32,
+
+    /// Pitch the current glide started from
+    glide_start: f32,
+
+    /// Progress through the current glide (0.0 - 1.0)
+    glide_position: f32,
+
+    /// Progress per sample while gliding (0 = not gliding)
+    glide_increment: f32,
+
+    /// Glide (portamento) time in milliseconds, 0 = off
+    glide_ms: f32,
+
+    /// Whether the glide time is per glide or per octave
+    glide_mode: GlideMode,
+
+    /// Shape of the pitch movement
+    glide_curve: GlideCurve,
+
+    /// Whether the glide slides or steps through notes
+    glissando: Glissando,
+
+    /// Root pitch class of the scale a scale glissando steps through
+    glissando_root: u8,
+
+    /// Degrees of the scale a scale glissando steps through
+    glissando_mask: ScaleMask,
+
+    /// Whether `pitch` holds a previously played note to glide from
+    has_played: bool,
+
+    /// Master tuning offset from A4 = 440 Hz, in semitones
+    tuning: f32,
+
+    /// Tuning offset slewed towards the latest value
+    tuning_slew: Slew,
+
+    /// Latest pitch bend (-1.0 to 1.0)
+    bend_target: f32,
+
+    /// Pitch bend slewed towards the latest value
+    bend: BendSlew,
+
+    /// Pitch change at full bend, in semitones
+    bend_range: f32,
+
+    /// Modulation routing (copy of the global matrix)
+    mod_matrix: ModMatrix,
+
+    /// Whether any matrix slot is routed (skips evaluation when not)
+    mod_active: bool,
+
+    /// Latest modulation source values
+    mod_sources: ModSourceValues,
+
+    /// Per-voice sample-and-hold random source (runs at control rate)
+    random: SampleAndHold,
+
+    /// Samples per control-rate update (1 = every sample)
+    control_divisor: u32,
+
+    /// Samples left until the next control-rate update
+    control_countdown: u32,
+
+    /// No NaN or infinite output since the last reset
+    finite: bool,
+
+    /// Modulation at the current sample, ramping towards the latest control
+    /// values (the cutoff offset includes the filter envelope)
+    modulation: ModOffsets,
+
+    /// Change of `modulation` per sample until the next update
+    modulation_step: ModOffsets,
+
+    /// Host polyphonic modulation of this voice, added to the matrix result
+    poly_offsets: ModOffsets,
+
+    /// Host note expressions of the current note
+    expression: ExpressionValues,
+
+    /// Stereo position at the current sample, ramping towards the pan expression
+    pan: f32,
+
+    /// Change of `pan` per sample until the next control update
+    pan_step: f32,
+
+    /// Signals inside the voice at the last sample, for the debug outputs
+    #[cfg(feature = "debug-outputs")]
+    probe: ProbeTaps,
+
+    /// Shared settings last received
+    params: VoiceParams,
+
+    /// Shared settings when the current note started (what it latches)
+    note_params: VoiceParams,
+
+    /// Sample rate in Hz
+    sample_rate: f32,
 }
 
 impl Voice {
     /// Create a new voice
     #[must_use] pub fn new(sample_rate: f32) -> Self {
+        Self::with_seed(sample_rate, 0)
+    }
+
+    /// Create a new voice whose random modulation source uses `seed`
+    #[must_use] pub fn with_seed(sample_rate: f32, seed: u32) -> Self {
+        let control_rate = control_rate(sample_rate, DEFAULT_CONTROL_DIVISOR);
         Self {
-            oscillator: Oscillator::new(sample_rate),
+            oscillator: NaiveOscillator::new(sample_rate),
+            string: KarplusStrong::new(sample_rate),
+            sampler: SamplePlayer::with_seed(sample_rate, seed),
+            additive: AdditiveOscillator::new(sample_rate),
+            engine: VoiceEngine::Oscillator,
+            input: 0.0,
+            input_mix: 0.0,
+            filter: StateVariableFilter::new(sample_rate),
+            filter_enabled: false,
+            filter_cutoff_octaves: 1000.0_f32.log2(),
+            cutoff_slew: Slew::new(sample_rate, CUTOFF_SLEW_MS, 1000.0_f32.log2()),
+            filter_envelope: ADSREnvelope::new(control_rate),
+            filter_env: FilterEnvelopeSettings::default(),
+            filter_env_depth: 0.0,
+            limiter: SoftLimiter::new(sample_rate),
+            limiter_enabled: false,
+            shaper: Waveshaper::new(sample_rate),
+            shaper_enabled: false,
             envelope: ADSREnvelope::new(sample_rate),
+            attack_ms: 10.0,
+            fade_in: Crossfade::new(sample_rate, FADE_IN_MS),
+            fade_out: Crossfade::new(sample_rate, CROSSFADE_MS),
+            free_running_phase: false,
+            start_phase: 0.0,
+            random_phase: false,
+            phase_noise: NoiseGenerator::new(seed.wrapping_add(1).wrapping_mul(0x85EB_CA6B)),
             note: 0,
             state: VoiceState::Idle,
             waveform: WaveformType::Sine,
             age: 0,
+            active_samples: 0,
+            pitch: 0.0,
+            glide_start: 0.0,
+            glide_position: 0.0,
+            glide_increment: 0.0,
+            glide_ms: 0.0,
+            glide_mode: GlideMode::default(),
+            glide_curve: GlideCurve::default(),
+            glissando: Glissando::default(),
+            glissando_root: 0,
+            glissando_mask: FULL_MASK,
+            has_played: false,
+            tuning: 0.0,
+            tuning_slew: Slew::new(sample_rate, TUNING_SLEW_MS, 0.0),
+            bend_target: 0.0,
+            bend: BendSlew::new(sample_rate),
+            bend_range: DEFAULT_BEND_RANGE,
+            mod_matrix: ModMatrix::default(),
+            mod_active: false,
+            mod_sources: ModSourceValues::default(),
+            random: SampleAndHold::new(control_rate, seed),
+            control_divisor: DEFAULT_CONTROL_DIVISOR,
+            control_countdown: 0,
+            finite: true,
+            modulation: ModOffsets::default(),
+            modulation_step: NO_MODULATION_CHANGE,
+            poly_offsets: ModOffsets::default(),
+            expression: ExpressionValues::default(),
+            pan: 0.0,
+            pan_step: 0.0,
+            #[cfg(feature = "debug-outputs")]
+            probe: ProbeTaps::default(),
+            params: VoiceParams::default(),
+            note_params: VoiceParams::default(),
+            sample_rate,
         }
     }
 
-    /// Trigger note on
-    pub fn note_on(&mut self, note: u8, velocity: f32) {
-        self.note = note;
-        self.state = VoiceState::Active;
-        self.envelope.note_on(velocity);
-        self.oscillator.reset();
-    }
-
-    /// Trigger note off
-    pub fn note_off(&mut self) {
-        self.state = VoiceState::Releasing;
-        self.envelope.note_off();
-    }
-
     /// Process one sample
     ///
     /// Returns the output sample (audio * envelope).
@@ -82,53 +829,513 @@ impl Voice {
         // Check if envelope completed release
         if !self.envelope.is_active() {
             self.state = VoiceState::Idle;
+            #[cfg(feature = "debug-outputs")]
+            {
+                self.probe = ProbeTaps::default();
+            }
             return 0.0;
         }
 
-        // Get frequency from MIDI note
-        let frequency = midi_note_to_frequency(self.note);
+        if self.state == VoiceState::Active {
+            self.active_samples += 1;
+        }
+
+        if self.control_countdown == 0 {
+            self.update_control(false);
+        }
+        self.control_countdown -= 1;
+        let modulation = self.advance_modulation();
+        let offset = self.tuning_slew.process(self.tuning)
+            + self.bend.process(self.bend_target) * self.bend_range
+            + modulation.pitch_semitones;
+
+        // Generate audio from the active engine
+        let audio = match self.engine {
+            VoiceEngine::Oscillator => {
+                self.advance_glide();
+                self.oscillator.set_frequency(pitch_to_frequency(self.pitch + offset));
+                self.oscillator.process(self.waveform)
+            }
+            VoiceEngine::KarplusStrong => self.string.process(),
+            VoiceEngine::Sampler => {
+                self.advance_glide();
+                self.sampler.process(pitch_to_frequency(self.pitch + offset))
+            }
+            VoiceEngine::Additive => {
+                self.advance_glide();
+                self.additive.process(pitch_to_frequency(self.pitch + offset))
+            }
+        };
 
-        // Generate waveform
-        let audio = match self.waveform {
-            WaveformType::Sine => self.oscillator.process_sine(frequency),
-            WaveformType::Sawtooth => self.oscillator.process_sawtooth(frequency),
-            WaveformType::Square => self.oscillator.process_square(frequency),
-            WaveformType::Triangle => self.oscillator.process_triangle(frequency),
+        // External input (gated input mode)
+        let audio = if self.input_mix > 0.0 {
+            audio + (self.input - audio) * self.input_mix
+        } else {
+            audio
+        };
+        #[cfg(feature = "debug-outputs")]
+        {
+            self.probe.pre_filter = audio;
+        }
+
+        // Per-voice filter, swept by the filter envelope and the mod matrix
+        let cutoff_octaves = self.cutoff_slew.process(self.filter_cutoff_octaves);
+        let audio = if self.filter_enabled {
+            self.filter.set_cutoff_hz((cutoff_octaves + modulation.cutoff_octaves).exp2());
+            self.filter.process(audio)
+        } else {
+            audio
+        };
+        #[cfg(feature = "debug-outputs")]
+        {
+            self.probe.post_filter = audio;
+        }
+
+        // Per-voice limiter, so a self-oscillating filter can't ring past the ceiling
+        let audio = if self.limiter_enabled {
+            self.limiter.process(audio)
+        } else {
+            audio
+        };
+
+        // Per-voice drive (before the envelope, so the amount of distortion
+        // doesn't change as the note fades)
+        let audio = if self.shaper_enabled {
+            self.shaper.process(audio)
+        } else {
+            audio
         };
 
         // Apply envelope
         let envelope_value = self.envelope.process();
 
-        audio * envelope_value
+        // Anti-click fade-in and restart crossfade
+        let mut fade = 1.0;
+        if self.fade_in.is_active() {
+            fade = self.fade_in.next_gains().1;
+        }
+        if self.fade_out.is_active() {
+            fade *= self.fade_out.next_gains().0;
+            if !self.fade_out.is_active() {
+                self.state = VoiceState::Idle;
+                self.envelope.reset();
+            }
+        }
+
+        let output = audio * envelope_value * fade * modulation.level;
+        self.finite &= output.is_finite();
+        #[cfg(feature = "debug-outputs")]
+        {
+            self.probe.voice = output;
+        }
+        output
     }
 
-    /// Get voice state
-    #[must_use] pub fn get_state(&self) -> VoiceState {
-        self.state
+    /// Advance the control-rate sources, evaluate the mod matrix and ramp
+    /// towards the result over the next control period (or jump straight to it)
+    fn update_control(&mut self, snap: bool) {
+        self.control_countdown = self.control_divisor;
+
+        // Per-voice sources are filled in here, global ones come from the manager
+        self.mod_sources.random = self.random.process();
+        self.mod_sources.pitch = pitch_source(self.sounding_pitch());
+
+        let mut target = self.matrix_offsets();
+        target.cutoff_octaves += self.filter_envelope.process() * self.filter_env_depth;
+        target.pitch_semitones += self.poly_offsets.pitch_semitones;
+        target.cutoff_octaves += self.poly_offsets.cutoff_octaves;
+        target.level *= self.poly_offsets.level;
+        target.pitch_semitones += self.expression.tuning;
+        target.level *= self.expression.gain;
+
+        if snap {
+            self.modulation = target;
+            self.modulation_step = NO_MODULATION_CHANGE;
+            self.pan = self.expression.pan;
+            self.pan_step = 0.0;
+            return;
+        }
+
+        #[allow(clippy::cast_precision_loss)] // At most MAX_CONTROL_DIVISOR
+        let samples = self.control_divisor as f32;
+        self.pan_step = (self.expression.pan - self.pan) / samples;
+        self.modulation_step = ModOffsets {
+            pitch_semitones: (target.pitch_semitones - self.modulation.pitch_semitones) / samples,
+            cutoff_octaves: (target.cutoff_octaves - self.modulation.cutoff_octaves) / samples,
+            level: (target.level - self.modulation.level) / samples,
+        };
     }
 
-    /// Get MIDI note number
-    #[must_use] pub fn get_note(&self) -> u8 {
-        self.note
+    /// Move the modulation one sample along its ramp
+    #[inline]
+    fn advance_modulation(&mut self) -> ModOffsets {
+        self.modulation.pitch_semitones += self.modulation_step.pitch_semitones;
+        self.modulation.cutoff_octaves += self.modulation_step.cutoff_octaves;
+        self.modulation.level += self.modulation_step.level;
+        self.pan += self.pan_step;
+        self.modulation
     }
 
-    /// Get voice age
-    #[must_use] pub fn get_age(&self) -> u64 {
-        self.age
+    /// Current (possibly gliding) pitch in MIDI notes
+    #[must_use]
+    pub fn get_pitch(&self) -> f32 {
+        self.pitch
     }
 
-    /// Set voice age (for voice stealing)
-    pub fn set_age(&mut self, age: u64) {
-        self.age = age;
+    /// Pitch the voice is sounding at, in MIDI notes: the gliding pitch plus
+    /// tuning, bend, host pitch modulation and the note's tuning expression
+    ///
+    /// The mod matrix's own pitch offset is left out, so the pitch source can't
+    /// feed back into itself. A plucked string sounds the key it was plucked on.
+    #[must_use]
+    pub fn sounding_pitch(&self) -> f32 {
+        if self.engine == VoiceEngine::KarplusStrong {
+            return f32::from(self.note) + self.tuning_slew.value();
+        }
+        self.pitch
+            + self.tuning_slew.value()
+            + self.bend.value() * self.bend_range
+            + self.poly_offsets.pitch_semitones
+            + self.expression.tuning
+    }
+
+    /// Move the pitch one sample closer to the target note
+    #[inline]
+    fn advance_glide(&mut self) {
+        if self.glide_increment <= 0.0 {
+            return;
+        }
+
+        let target = f32::from(self.note);
+        self.glide_position += self.glide_increment;
+        if self.glide_position >= 1.0 {
+            self.pitch = target;
+            self.glide_increment = 0.0;
+        } else {
+            let shape = self.glide_curve.shape(self.glide_position);
+            let pitch = self.glide_start + (target - self.glide_start) * shape;
+            self.pitch = self.glissando_step(pitch, target);
+        }
+    }
+
+    /// Hold a gliding pitch on the last note of the glissando it has passed
+    ///
+    /// Rising glides hold the allowed note at or below the pitch, falling ones
+    /// the note at or above it, never going back past where the glide started.
+    fn glissando_step(&self, pitch: f32, target: f32) -> f32 {
+        let mask = match self.glissando {
+            Glissando::Off => return pitch,
+            Glissando::Chromatic => FULL_MASK,
+            // An empty scale has nothing to step through
+            Glissando::Scale if self.glissando_mask == 0 => FULL_MASK,
+            Glissando::Scale => self.glissando_mask,
+        };
+
+        let rising = target > self.glide_start;
+        let (mut note, step) = if rising {
+            (pitch.floor(), -1.0)
+        } else {
+            (pitch.ceil(), 1.0)
+        };
+        // Every scale has a degree within an octave
+        for _ in 0..12 {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Clamped to 0-127
+            let midi_note = note.clamp(0.0, 127.0) as u8;
+            if in_scale(self.glissando_root, mask, midi_note) {
+                break;
+            }
+            note += step;
+        }
+
+        if rising {
+            note.max(self.glide_start)
+        } else {
+            note.min(self.glide_start)
+        }
     }
 
     /// Set waveform type
+    ///
+    /// Also used as the string's excitation waveform in oscillator-excitation mode.
     pub fn set_waveform(&mut self, waveform: WaveformType) {
         self.waveform = waveform;
+        self.string.set_excitation_waveform(waveform);
+    }
+
+    /// Set how the sine waveform is computed
+    pub fn set_sine_mode(&mut self, mode: SineMode) {
+        self.oscillator.set_sine_mode(mode);
+    }
+
+    /// Keep oscillator phase across notes (`true`) or restart it at each note on
+    ///
+    /// Applies to the oscillator and additive engines.
+    pub fn set_free_running_phase(&mut self, free_running: bool) {
+        self.free_running_phase = free_running;
+    }
+
+    /// Set the phase notes restart at, in degrees (0 - 360)
+    ///
+    /// Shapes the attack transient: 0° starts a sine from silence, 90° from its
+    /// peak. Ignored while the phase is free-running.
+    pub fn set_start_phase_degrees(&mut self, degrees: f32) {
+        self.start_phase = (degrees / 360.0).rem_euclid(1.0);
+    }
+
+    /// Restart each note at a random phase (`true`) or at the start phase
+    pub fn set_random_phase(&mut self, random: bool) {
+        self.random_phase = random;
+    }
+
+    /// Set the sound engine
+    ///
+    /// Takes effect at once: a sounding note switched to the string or the
+    /// sampler plucks the string or starts the sample, so it keeps sounding.
+    pub fn set_engine(&mut self, engine: VoiceEngine) {
+        if engine == self.engine {
+            return;
+        }
+        self.engine = engine;
+        if self.state != VoiceState::Idle {
+            self.excite();
+        }
+    }
+
+    /// Pluck the string or start the sample at the note (the other engines
+    /// sound without being started)
+    fn excite(&mut self) {
+        match self.engine {
+            VoiceEngine::KarplusStrong => {
+                let bend = self.bend_target * self.bend_range;
+                self.string.pluck(pitch_to_frequency(f32::from(self.note) + self.tuning + bend));
+            }
+            VoiceEngine::Sampler => self.sampler.trigger(),
+            VoiceEngine::Additive | VoiceEngine::Oscillator => {}
+        }
+    }
+
+    /// Set the string excitation source
+    pub fn set_string_excitation(&mut self, excitation: ExcitationType) {
+        self.string.set_excitation(excitation);
+    }
+
+    /// Set the string loop damping (0.0 to 1.0)
+    pub fn set_string_damping(&mut self, damping: f32) {
+        self.string.set_damping(damping);
+    }
+
+    /// Set the string decay time (-60 dB) in milliseconds
+    pub fn set_string_decay_ms(&mut self, decay_ms: f32) {
+        self.string.set_decay_ms(decay_ms);
+    }
+
+    /// Replace the sample played by the sampler engine (stops its playback)
+    pub fn set_sample(&mut self, sample: Option<Arc<SampleData>>) {
+        self.sampler.set_sample(sample);
+    }
+
+    /// Set the sampler's interpolation
+    pub fn set_sample_interpolation(&mut self, interpolation: Interpolation) {
+        self.sampler.set_interpolation(interpolation);
+    }
+
+    /// Set sampler looping and loop points (fractions of the sample length)
+    pub fn set_sample_loop(&mut self, looping: bool, start: f32, end: f32) {
+        self.sampler.set_loop(looping, start, end);
+    }
+
+    /// Set where sample playback starts (fraction of the sample length)
+    pub fn set_sample_start(&mut self, offset: f32) {
+        self.sampler.set_start_offset(offset);
+    }
+
+    /// Set the note at which the sample plays at its recorded pitch
+    pub fn set_sample_root_note(&mut self, note: u8) {
+        self.sampler.set_root_note(note);
+    }
+
+    /// Switch the sampler's granular mode and update its grain settings
+    pub fn set_sample_granular(&mut self, granular: bool, settings: GranularSettings) {
+        self.sampler.set_granular(granular, settings);
+    }
+
+    /// Set the gain of each additive partial (see `additive::partial_gains`)
+    pub fn set_additive_gains(&mut self, gains: [f32; NUM_PARTIALS]) {
+        self.additive.set_gains(gains);
+    }
+
+    /// Enable or disable the per-voice drive and update its settings
+    pub fn set_waveshaper(&mut self, enabled: bool, settings: WaveshaperSettings) {
+        if enabled && !self.shaper_enabled {
+            // Don't let stale filter state from a previous use leak in
+            self.shaper.reset();
+        }
+        self.shaper_enabled = enabled;
+        self.shaper.set_settings(settings);
+    }
+
+    /// Set the modulation routing
+    pub fn set_mod_matrix(&mut self, matrix: ModMatrix) {
+        self.mod_matrix = matrix;
+        self.mod_active = matrix.is_active();
+    }
+
+    /// Set how many samples pass between control-rate updates (1 - 64)
+    ///
+    /// The random source, filter envelope and mod matrix update once per period
+    /// and are ramped in between; 1 updates them every sample.
+    pub fn set_control_divisor(&mut self, divisor: u32) {
+        let divisor = divisor.clamp(1, MAX_CONTROL_DIVISOR);
+        if divisor == self.control_divisor {
+            return;
+        }
+        self.control_divisor = divisor;
+        self.control_countdown = self.control_countdown.min(divisor);
+        let control_rate = control_rate(self.sample_rate, divisor);
+        self.filter_envelope.set_sample_rate(control_rate);
+        self.random.set_sample_rate(control_rate);
+    }
+
+    /// Update the modulation source values (call before `process`)
+    pub fn set_mod_sources(&mut self, sources: ModSourceValues) {
+        self.mod_sources = sources;
+    }
+
+    /// Mod matrix offsets for the latest source values
+    #[must_use] pub fn matrix_offsets(&self) -> ModOffsets {
+        if self.mod_active {
+            self.mod_matrix.evaluate(&self.mod_sources)
+        } else {
+            ModOffsets::default()
+        }
+    }
+
+    /// Set the external input sample (call before `process`)
+    pub fn set_input(&mut self, input: f32) {
+        self.input = input;
+    }
+
+    /// Blend the external input into the engine (0.0 = engine only, 1.0 = input only)
+    pub fn set_input_mix(&mut self, mix: f32) {
+        self.input_mix = mix.clamp(0.0, 1.0);
+    }
+
+    /// Enable or bypass the per-voice filter
+    pub fn set_filter_enabled(&mut self, enabled: bool) {
+        if enabled && !self.filter_enabled {
+            self.filter.reset();
+        }
+        self.filter_enabled = enabled;
+    }
+
+    /// Enable or bypass the per-voice limiter and set its ceiling in dBFS
+    pub fn set_limiter(&mut self, enabled: bool, ceiling_db: f32) {
+        if enabled && !self.limiter_enabled {
+            self.limiter.reset();
+        }
+        self.limiter_enabled = enabled;
+        self.limiter.set_ceiling_db(ceiling_db);
+    }
+
+    /// Set filter response
+    pub fn set_filter_mode(&mut self, mode: SvfMode) {
+        self.filter.set_mode(mode);
+    }
+
+    /// Set filter cutoff (before modulation) in Hz, reached over the cutoff
+    /// slew time
+    pub fn set_filter_cutoff_hz(&mut self, cutoff_hz: f32) {
+        self.filter_cutoff_octaves = cutoff_hz.max(f32::MIN_POSITIVE).log2();
+    }
+
+    /// Set filter resonance (0.0 - 1.0)
+    pub fn set_filter_resonance(&mut self, resonance: f32) {
+        self.filter.set_resonance(resonance);
+    }
+
+    /// Set the filter arithmetic (f64 for low cutoffs with high resonance)
+    pub fn set_filter_precision(&mut self, precision: FilterPrecision) {
+        self.filter.set_precision(precision);
+    }
+
+    /// Set the filter envelope's times and depth (depth takes effect on the next note)
+    pub fn set_filter_envelope(&mut self, settings: FilterEnvelopeSettings) {
+        self.filter_envelope.set_attack_ms(settings.attack_ms);
+        self.filter_envelope.set_decay_ms(settings.decay_ms);
+        self.filter_envelope.set_sustain_level(settings.sustain_level);
+        self.filter_envelope.set_release_ms(settings.release_ms);
+        self.filter_env = settings;
+    }
+
+    /// Set the random source's clock rate in Hz
+    pub fn set_random_rate_hz(&mut self, rate_hz: f32) {
+        self.random.set_rate_hz(rate_hz);
+    }
+
+    /// Set the random source's slew time in milliseconds
+    pub fn set_random_slew_ms(&mut self, slew_ms: f32) {
+        self.random.set_slew_ms(slew_ms);
+    }
+
+    /// Set the pitch bend (-1.0 to 1.0), reached over the bend slew time
+    ///
+    /// A plucked string keeps the bend it was plucked with.
+    pub fn set_pitch_bend(&mut self, bend: f32) {
+        self.bend_target = bend.clamp(-1.0, 1.0);
+    }
+
+    /// Set the pitch change at full bend, in semitones
+    pub fn set_bend_range(&mut self, semitones: f32) {
+        self.bend_range = semitones;
+    }
+
+    /// Set the host's note expressions for this voice
+    ///
+    /// Ramps in with the next control update; cleared by the next note-on.
+    pub fn set_expression(&mut self, expression: ExpressionValues) {
+        self.expression = expression;
+    }
+
+    /// Set the host's polyphonic modulation of this voice
+    ///
+    /// Ramps in with the next control update; cleared by the next note-on.
+    pub fn set_poly_offsets(&mut self, offsets: ModOffsets) {
+        self.poly_offsets = offsets;
+    }
+
+    /// Set the pitch reference (A4 in Hz) and fine tuning in cents
+    ///
+    /// Sounding notes follow over the tuning slew time, except a plucked
+    /// string, whose pitch is fixed until its next pluck.
+    pub fn set_tuning(&mut self, a4_hz: f32, fine_tune_cents: f32) {
+        self.tuning = tuning_offset_semitones(a4_hz, fine_tune_cents);
+    }
+
+    /// Set glide time (0 = off); takes effect from the next note
+    pub fn set_glide_ms(&mut self, glide_ms: f32) {
+        self.glide_ms = glide_ms.max(0.0);
+    }
+
+    /// Set whether the glide time is per glide or per octave; takes effect from the next note
+    pub fn set_glide_mode(&mut self, mode: GlideMode) {
+        self.glide_mode = mode;
+    }
+
+    /// Set the shape of the pitch movement while gliding
+    pub fn set_glide_curve(&mut self, curve: GlideCurve) {
+        self.glide_curve = curve;
+    }
+
+    /// Set whether glides slide or step, and the scale a scale glissando steps through
+    pub fn set_glissando(&mut self, glissando: Glissando, root: u8, mask: ScaleMask) {
+        self.glissando = glissando;
+        self.glissando_root = root;
+        self.glissando_mask = mask;
     }
 
     /// Set envelope attack time
     pub fn set_envelope_attack_ms(&mut self, attack_ms: f32) {
+        self.attack_ms = attack_ms;
         self.envelope.set_attack_ms(attack_ms);
     }
 
@@ -137,34 +1344,295 @@ impl Voice {
         self.envelope.set_decay_ms(decay_ms);
     }
 
-    /// Set envelope sustain level
-    pub fn set_envelope_sustain_level(&mut self, sustain_level: f32) {
-        self.envelope.set_sustain_level(sustain_level);
+    /// Set envelope sustain level
+    pub fn set_envelope_sustain_level(&mut self, sustain_level: f32) {
+        self.envelope.set_sustain_level(sustain_level);
+    }
+
+    /// Set envelope release time
+    pub fn set_envelope_release_ms(&mut self, release_ms: f32) {
+        self.envelope.set_release_ms(release_ms);
+    }
+
+    /// Pass every shared setting on to its part of the voice
+    fn apply_params(&mut self, params: &VoiceParams) {
+        self.set_engine(params.engine);
+        self.set_waveform(params.waveform);
+        self.set_sine_mode(params.sine_mode);
+        self.set_envelope_attack_ms(params.attack_ms);
+        self.set_envelope_decay_ms(params.decay_ms);
+        self.set_envelope_sustain_level(params.sustain_level);
+        self.set_envelope_release_ms(params.release_ms);
+        self.set_glide_ms(params.glide_ms);
+        self.set_glide_mode(params.glide_mode);
+        self.set_glide_curve(params.glide_curve);
+        self.set_glissando(params.glissando, params.glissando_root, params.glissando_mask);
+        self.set_free_running_phase(params.free_running_phase);
+        self.set_start_phase_degrees(params.start_phase_degrees);
+        self.set_random_phase(params.random_phase);
+        self.set_string_excitation(params.string_excitation);
+        self.set_string_damping(params.string_damping);
+        self.set_string_decay_ms(params.string_decay_ms);
+        self.set_sample_interpolation(params.sample_interpolation);
+        self.set_sample_loop(params.sample_loop, params.sample_loop_start, params.sample_loop_end);
+        self.set_sample_start(params.sample_start);
+        self.set_sample_root_note(params.sample_root_note);
+        self.set_sample_granular(params.sample_granular, params.granular);
+        self.set_additive_gains(params.additive_gains);
+        self.set_waveshaper(params.waveshaper_enabled, params.waveshaper);
+        self.set_filter_enabled(params.filter_enabled);
+        self.set_filter_mode(params.filter_mode);
+        self.set_filter_cutoff_hz(params.filter_cutoff_hz);
+        self.set_filter_resonance(params.filter_resonance);
+        self.set_filter_envelope(params.filter_envelope);
+        self.set_filter_precision(params.filter_precision);
+        self.set_limiter(params.limiter_enabled, params.limiter_ceiling_db);
+        self.set_random_rate_hz(params.random_rate_hz);
+        self.set_random_slew_ms(params.random_slew_ms);
+        self.set_mod_matrix(params.mod_matrix);
+        self.set_control_divisor(params.control_divisor);
+        self.set_tuning(params.a4_hz, params.fine_tune_cents);
+        self.set_bend_range(params.bend_range);
+        self.set_input_mix(params.input_mix);
+    }
+}
+
+impl SynthVoice for Voice {
+    type Params = VoiceParams;
+
+    fn with_seed(sample_rate: f32, seed: u32) -> Self {
+        Self::with_seed(sample_rate, seed)
+    }
+
+    fn set_params(&mut self, params: &VoiceParams) {
+        // A sounding note keeps its latched groups as they were at its note on
+        self.params = *params;
+        let params = if self.is_active() {
+            params.latched(&self.note_params)
+        } else {
+            *params
+        };
+        self.apply_params(&params);
+    }
+
+    fn note_on(&mut self, note: u8, velocity: f32) {
+        // Latched groups held back while the last note sounded catch up here
+        if self.params.latch.any() {
+            let params = self.params;
+            self.apply_params(&params);
+        }
+        self.note_params = self.params;
+        self.note = note;
+        self.state = VoiceState::Active;
+        self.active_samples = 0;
+        self.envelope.note_on(velocity);
+        // Velocity scales the filter envelope's depth, not its shape
+        self.filter_envelope.note_on(1.0);
+        self.filter_env_depth = self.filter_env.depth_octaves(velocity);
+        self.limiter.reset();
+        let start_phase = if self.random_phase {
+            self.phase_noise.next_unipolar()
+        } else {
+            self.start_phase
+        };
+        if !self.free_running_phase {
+            self.oscillator.reset_to(start_phase);
+        }
+        self.fade_out.finish();
+        if self.attack_ms < FADE_IN_MAX_ATTACK_MS {
+            self.fade_in.set_time_ms(FADE_IN_MS);
+            self.fade_in.start();
+        } else {
+            self.fade_in.finish();
+        }
+
+        let target = f32::from(note);
+        let interval = (target - self.pitch).abs();
+        if self.glide_ms > 0.0 && self.has_played && interval > 0.0 {
+            let glide_ms = match self.glide_mode {
+                GlideMode::ConstantTime => self.glide_ms,
+                GlideMode::ConstantRate => self.glide_ms * interval / 12.0,
+            };
+            let glide_samples = (glide_ms / 1000.0 * self.sample_rate).max(1.0);
+            self.glide_start = self.pitch;
+            self.glide_position = 0.0;
+            self.glide_increment = glide_samples.recip();
+        } else {
+            self.pitch = target;
+            self.glide_increment = 0.0;
+        }
+        self.has_played = true;
+
+        self.random.trigger();
+        self.bend.snap(self.bend_target);
+        self.cutoff_slew.snap(self.filter_cutoff_octaves);
+        self.tuning_slew.snap(self.tuning);
+        self.poly_offsets = ModOffsets::default();
+        self.expression = ExpressionValues::default();
+
+        // Start from the new note's modulation rather than ramping over from
+        // the previous note's
+        self.update_control(true);
+
+        // The string's pitch is fixed by its delay line at pluck time, so glide
+        // only affects the oscillator, sampler and additive engines
+        if self.engine == VoiceEngine::Additive && !self.free_running_phase {
+            self.additive.reset_to(start_phase);
+        }
+        self.excite();
+    }
+
+    fn note_off_scaled(&mut self, release_scale: f32) {
+        self.state = VoiceState::Releasing;
+        self.envelope.note_off_scaled(release_scale);
+        self.filter_envelope.note_off_scaled(release_scale);
+    }
+
+    #[inline]
+    fn process_block(&mut self, output: &mut [f32]) {
+        for sample in output {
+            *sample += self.process();
+        }
+    }
+
+    /// Pans every sample, following the pan as it ramps
+    #[inline]
+    fn process_block_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        for (left, right) in left.iter_mut().zip(right) {
+            let sample = self.process();
+            let (left_gain, right_gain) = pan_gains(self.pan);
+            *left += sample * left_gain;
+            *right += sample * right_gain;
+        }
+    }
+
+    fn pan(&self) -> f32 {
+        self.pan
+    }
+
+    fn get_state(&self) -> VoiceState {
+        self.state
+    }
+
+    fn get_note(&self) -> u8 {
+        self.note
+    }
+
+    fn get_age(&self) -> u64 {
+        self.age
+    }
+
+    fn set_age(&mut self, age: u64) {
+        self.age = age;
+    }
+
+    fn get_active_samples(&self) -> u64 {
+        self.active_samples
+    }
+
+    fn crossfade_in(&mut self) {
+        self.fade_in.set_time_ms(CROSSFADE_MS);
+        self.fade_in.start();
+    }
+
+    fn crossfade_out(&mut self) {
+        self.state = VoiceState::Releasing;
+        if !self.fade_out.is_active() {
+            self.fade_out.start();
+        }
+    }
+
+    /// Pick up glide from where `previous` (the voice this one replaces) left off
+    fn continue_from(&mut self, previous: &Self) {
+        self.pitch = previous.pitch;
+        self.has_played = previous.has_played;
+    }
+
+    /// A released voice rings for the rest of its amp release; a voice fading
+    /// out for a restart stops within the crossfade.
+    fn tail_samples(&self) -> Option<u32> {
+        let samples = match self.state {
+            VoiceState::Active => return None,
+            _ if !self.envelope.is_active() => 0.0,
+            VoiceState::Idle => 0.0,
+            VoiceState::Releasing if self.fade_out.is_active() => {
+                CROSSFADE_MS / 1000.0 * self.sample_rate
+            }
+            VoiceState::Releasing => self.envelope.remaining_release_samples(),
+        };
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Seconds of audio at most
+        Some(samples.ceil() as u32)
+    }
+
+    fn envelope_stage(&self) -> EnvelopeState {
+        self.envelope.get_state()
+    }
+
+    fn current_level(&self) -> f32 {
+        self.envelope.current_level()
+    }
+
+    fn is_finite(&self) -> bool {
+        self.finite
+    }
+
+    fn take_limiter_reduction_db(&mut self) -> f32 {
+        self.limiter.take_max_reduction_db()
     }
 
-    /// Set envelope release time
-    pub fn set_envelope_release_ms(&mut self, release_ms: f32) {
-        self.envelope.set_release_ms(release_ms);
+    #[cfg(feature = "debug-outputs")]
+    fn probe(&self) -> ProbeTaps {
+        self.probe
     }
 
-    /// Reset voice to idle state
-    pub fn reset(&mut self) {
+    fn reset(&mut self) {
         self.state = VoiceState::Idle;
         self.envelope.reset();
+        self.filter_envelope.reset();
         self.oscillator.reset();
+        self.string.reset();
+        self.sampler.reset();
+        self.additive.reset();
+        self.shaper.reset();
+        self.filter.reset();
+        self.limiter.reset();
+        self.random.reset();
+        self.bend.snap(self.bend_target);
+        self.cutoff_slew.snap(self.filter_cutoff_octaves);
+        self.tuning_slew.snap(self.tuning);
+        self.control_countdown = 0;
+        self.finite = true;
+        self.modulation = ModOffsets::default();
+        self.modulation_step = NO_MODULATION_CHANGE;
+        self.poly_offsets = ModOffsets::default();
+        self.expression = ExpressionValues::default();
+        self.pan = 0.0;
+        self.pan_step = 0.0;
+        self.active_samples = 0;
+        self.glide_increment = 0.0;
+        self.has_played = false;
+        self.fade_in.finish();
+        self.fade_out.finish();
     }
 }
 
 /// Voice manager for polyphonic synthesis
 ///
 /// Manages a fixed-size pool of voices with voice stealing when limit is reached.
+/// Works with any [`SynthVoice`]; `VoiceManager` on its own is the built-in
+/// multi-engine [`Voice`].
 ///
 /// # Real-time Safety
 /// - Voices pre-allocated at construction
 /// - No dynamic allocation in `note_on/note_off/process`
-pub struct VoiceManager {
+pub struct VoiceManager<V: SynthVoice = Voice> {
     /// Pre-allocated voice pool
-    voices: Vec<Voice>,
+    voices: Vec<V>,
+
+    /// Spare voices that fade out the old notes of restarted voices (not counted
+    /// towards polyphony)
+    tails: Vec<V>,
 
     /// Maximum polyphony
     max_voices: usize,
@@ -172,6 +1640,62 @@ pub struct VoiceManager {
     /// Global voice age counter
     voice_age_counter: u64,
 
+    /// Voices stolen since creation
+    steal_count: u64,
+
+    /// Longest a voice may stay Active without a note-off, in samples (0 = no limit)
+    stuck_timeout_samples: u64,
+
+    /// Voices force-released by the stuck-note watchdog since creation
+    stuck_release_count: u64,
+
+    /// How strongly note-off velocity shortens or lengthens the release (-1.0 to 1.0)
+    release_velocity_amount: f32,
+
+    /// Never steal the voice playing the lowest held note
+    bass_reserve: bool,
+
+    /// How new notes pick a voice
+    allocation: VoiceAllocation,
+
+    /// Which voice to steal when none is idle
+    stealing: VoiceStealing,
+
+    /// Slot after the last one allocated (where round-robin and rotation start)
+    next_slot: usize,
+
+    /// What a note-on does when its note is already sounding
+    same_note: SameNotePolicy,
+
+    /// Choke group of every note (0 = no group)
+    choke_groups: [u8; 128],
+
+    /// How a note cuts off the rest of its choke group
+    choke_policy: ChokePolicy,
+
+    /// Note-on ids of the held keys, for pairing note-offs with note-ons
+    held: HeldNotes,
+
+    /// Voice id of each voice slot (`None` = untagged)
+    voice_tags: Vec<Option<VoiceTag>>,
+
+    /// Voice id of each tail voice, kept until the old note has faded out
+    tail_tags: Vec<Option<VoiceTag>>,
+
+    /// Voice ids of voices that have ended, not yet collected
+    ended: Vec<VoiceTag>,
+
+    /// Settings last passed on to the voices
+    params: Option<V::Params>,
+
+    /// Voice slots keeping the settings they had when pinned, until their
+    /// next note (see [`VoiceManager::pin_sounding_voices`])
+    voice_pinned: Vec<bool>,
+
+    /// The same for each tail voice, which inherits the flag of the note it
+    /// fades out
+    tail_pinned: Vec<bool>,
+
     /// Sample rate
     sample_rate: f32,
 }
@@ -183,15 +1707,113 @@ impl VoiceManager {
     /// * `sample_rate` - Sample rate in Hz
     /// * `max_voices` - Maximum number of simultaneous voices
     #[must_use] pub fn new(sample_rate: f32, max_voices: usize) -> Self {
+        Self::with_max_voices(sample_rate, max_voices)
+    }
+
+    /// Give every voice a new sample (stops sample playback)
+    pub fn set_sample(&mut self, sample: Option<&Arc<SampleData>>) {
+        for voice in self.all_voices_mut() {
+            voice.set_sample(sample.cloned());
+        }
+    }
+
+    /// Update global modulation source values for all voices
+    pub fn set_mod_sources(&mut self, sources: ModSourceValues) {
+        for voice in self.all_voices_mut() {
+            voice.set_mod_sources(sources);
+        }
+    }
+
+    /// Range of mod matrix offsets across the sounding voices, for the editor
+    #[must_use] pub fn matrix_range(&self) -> Option<ModRange> {
+        let mut offsets = self
+            .voices
+            .iter()
+            .filter(|v| v.get_state() != VoiceState::Idle)
+            .map(Voice::matrix_offsets);
+        let first = offsets.next()?;
+        Some(offsets.fold(ModRange::new(first), |mut range, voice| {
+            range.include(voice);
+            range
+        }))
+    }
+
+    /// Set the pitch bend (-1.0 to 1.0) of every voice
+    pub fn set_pitch_bend(&mut self, bend: f32) {
+        for voice in self.all_voices_mut() {
+            voice.set_pitch_bend(bend);
+        }
+    }
+
+    /// Set the host's note expressions for the voices started with `voice_id`
+    pub fn set_expression(&mut self, voice_id: i32, expression: ExpressionValues) {
+        let tags = self.voice_tags.iter().chain(&self.tail_tags);
+        for (voice, tag) in self.voices.iter_mut().chain(&mut self.tails).zip(tags) {
+            if tag.is_some_and(|tag| tag.voice_id == voice_id) {
+                voice.set_expression(expression);
+            }
+        }
+    }
+
+    /// Set the host's polyphonic modulation of the voices started with `voice_id`
+    pub fn set_poly_offsets(&mut self, voice_id: i32, offsets: ModOffsets) {
+        let tags = self.voice_tags.iter().chain(&self.tail_tags);
+        for (voice, tag) in self.voices.iter_mut().chain(&mut self.tails).zip(tags) {
+            if tag.is_some_and(|tag| tag.voice_id == voice_id) {
+                voice.set_poly_offsets(offsets);
+            }
+        }
+    }
+
+    /// Give every voice the latest external input sample
+    pub fn set_input(&mut self, input: f32) {
+        for voice in self.all_voices_mut() {
+            voice.set_input(input);
+        }
+    }
+}
+
+impl<V: SynthVoice> VoiceManager<V> {
+    /// Create a voice manager for any voice type
+    ///
+    /// # Arguments
+    /// * `sample_rate` - Sample rate in Hz
+    /// * `max_voices` - Maximum number of simultaneous voices
+    #[must_use] pub fn with_max_voices(sample_rate: f32, max_voices: usize) -> Self {
         let mut voices = Vec::with_capacity(max_voices);
-        for _ in 0..max_voices {
-            voices.push(Voice::new(sample_rate));
+        for index in 0..max_voices {
+            // Each voice gets its own random sequence
+            #[allow(clippy::cast_possible_truncation)] // Voice counts are tiny
+            voices.push(V::with_seed(sample_rate, index as u32));
         }
+        let tails = (0..CROSSFADE_TAILS)
+            .map(|_| V::with_seed(sample_rate, 0))
+            .collect();
 
         Self {
             voices,
+            tails,
             max_voices,
             voice_age_counter: 0,
+            steal_count: 0,
+            stuck_timeout_samples: 0,
+            stuck_release_count: 0,
+            release_velocity_amount: 0.0,
+            bass_reserve: false,
+            allocation: VoiceAllocation::FirstIdle,
+            stealing: VoiceStealing::Oldest,
+            next_slot: 0,
+            same_note: SameNotePolicy::Retrigger,
+            choke_groups: [0; 128],
+            choke_policy: ChokePolicy::Hard,
+            held: HeldNotes::new(),
+            voice_tags: vec![None; max_voices],
+            tail_tags: vec![None; CROSSFADE_TAILS],
+            // Room for every slot and tail to end, twice over, between collections
+            ended: Vec::with_capacity(2 * (max_voices + CROSSFADE_TAILS)),
+            params: None,
+            voice_pinned: vec![false; max_voices],
+            tail_pinned: vec![false; CROSSFADE_TAILS],
             sample_rate,
         }
     }
@@ -204,28 +1826,108 @@ impl VoiceManager {
     /// * `note` - MIDI note number (0-127)
     /// * `velocity` - Note velocity (0.0-1.0)
     pub fn note_on(&mut self, note: u8, velocity: f32) {
-        // First, check if this note is already playing and reuse it (retrigger)
-        for voice in &mut self.voices {
-            if voice.get_note() == note && voice.get_state() != VoiceState::Idle {
+        self.start_note(note, velocity, None);
+    }
+
+    /// Trigger note on for a voice the host can address by id
+    ///
+    /// The voice reports `tag` through [`VoiceManager::pop_ended_voice`] once it
+    /// has fallen silent.
+    pub fn note_on_tagged(&mut self, note: u8, velocity: f32, tag: VoiceTag) {
+        self.start_note(note, velocity, Some(tag));
+    }
+
+    fn start_note(&mut self, note: u8, velocity: f32, tag: Option<VoiceTag>) {
+        // Whichever voice plays the note gets the next age as its note-on id
+        self.held.push(note, self.voice_age_counter);
+        self.choke(note);
+
+        // First, deal with the note if it's already playing
+        match self.same_note {
+            SameNotePolicy::Retrigger => {
+                if let Some(index) = self.voices.iter().position(|voice| {
+                    voice.get_note() == note && voice.get_state() != VoiceState::Idle
+                }) {
+                    self.restart_voice(index, note, velocity, tag);
+                    return;
+                }
+            }
+            SameNotePolicy::MonoPerNote => {
+                if let Some(voice) = self.newest_held_voice(note) {
+                    voice.note_off();
+                }
+            }
+            SameNotePolicy::Stack => {}
+        }
+
+        let num_voices = self.voices.len();
+        let idle = |voice: &V| !voice.is_active();
+        let slot = match self.allocation {
+            VoiceAllocation::FirstIdle => self.voices.iter().position(idle),
+            VoiceAllocation::RoundRobin => (0..num_voices)
+                .map(|offset| (self.next_slot + offset) % num_voices)
+                .find(|&index| idle(&self.voices[index])),
+            VoiceAllocation::Rotate => {
+                let index = self.next_rotation_slot();
+                if !idle(&self.voices[index]) {
+                    self.steal_count += 1;
+                    self.restart_voice(index, note, velocity, tag);
+                    return;
+                }
+                Some(index)
+            }
+        };
+
+        match slot {
+            Some(index) => {
+                let voice = &mut self.voices[index];
+                refresh_pinned(voice, &mut self.voice_pinned[index], self.params.as_ref());
                 voice.note_on(note, velocity);
                 voice.set_age(self.voice_age_counter);
                 self.voice_age_counter += 1;
-                return;
+                self.next_slot = (index + 1) % num_voices;
+                let old_tag = std::mem::replace(&mut self.voice_tags[index], tag);
+                push_ended(&mut self.ended, old_tag);
             }
+            // No idle voice found - steal one
+            None => self.steal_voice(note, velocity, tag),
+        }
+    }
+
+    /// Cut off the sounding voices of the other notes in `note`'s choke group
+    ///
+    /// Repeats of the note itself are left to the same-note policy.
+    fn choke(&mut self, note: u8) {
+        let group = self.choke_groups[usize::from(note)];
+        if group == 0 {
+            return;
         }
 
-        // Find an idle voice
         for voice in &mut self.voices {
-            if voice.get_state() == VoiceState::Idle {
-                voice.note_on(note, velocity);
-                voice.set_age(self.voice_age_counter);
-                self.voice_age_counter += 1;
-                return;
+            let other = voice.get_note();
+            if voice.is_active() && other != note && self.choke_groups[usize::from(other)] == group
+            {
+                match self.choke_policy {
+                    ChokePolicy::Hard => voice.crossfade_out(),
+                    ChokePolicy::FastRelease => voice.note_off_scaled(CHOKE_RELEASE_SCALE),
+                }
             }
         }
+    }
 
-        // No idle voice found - steal one
-        self.steal_voice(note, velocity);
+    /// Next slot in strict rotation, passing over the bass note under bass reserve
+    fn next_rotation_slot(&self) -> usize {
+        let protected = if self.bass_reserve {
+            self.lowest_held_voice()
+        } else {
+            None
+        };
+        let next = self.next_slot % self.voices.len();
+        if Some(next) == protected && self.voices.len() > 1 {
+            (next + 1) % self.voices.len()
+        } else {
+            next
+        }
     }
 
     /// Trigger note off
@@ -233,13 +1935,108 @@ impl VoiceManager {
     /// # Arguments
     /// * `note` - MIDI note number to release
     pub fn note_off(&mut self, note: u8) {
-        for voice in &mut self.voices {
-            if voice.get_note() == note && voice.get_state() == VoiceState::Active {
-                voice.note_off();
+        if let Some(voice) = self.note_off_target(note) {
+            voice.note_off();
+        }
+    }
+
+    /// Trigger note off, shaping the release with the note-off velocity
+    ///
+    /// # Arguments
+    /// * `note` - MIDI note number to release
+    /// * `velocity` - Note-off velocity (0.0-1.0); 0.5 leaves the release unchanged
+    pub fn note_off_with_velocity(&mut self, note: u8, velocity: f32) {
+        let release_scale = release_time_scale(velocity, self.release_velocity_amount);
+        if let Some(voice) = self.note_off_target(note) {
+            voice.note_off_scaled(release_scale);
+        }
+    }
+
+    /// Trigger note off for a voice the host addressed by id
+    ///
+    /// Only releases the held voice of `note` started with `voice_id`; without an
+    /// id it pairs like any other note-off.
+    pub fn note_off_voice(&mut self, note: u8, velocity: f32, voice_id: Option<i32>) {
+        let Some(voice_id) = voice_id else {
+            self.note_off_with_velocity(note, velocity);
+            return;
+        };
+
+        let release_scale = release_time_scale(velocity, self.release_velocity_amount);
+        let tagged = self.voices.iter().zip(&self.voice_tags).position(|(voice, tag)| {
+            voice.get_note() == note
+                && voice.get_state() == VoiceState::Active
+                && tag.is_some_and(|tag| tag.voice_id == voice_id)
+        });
+        if let Some(index) = tagged {
+            let voice = &mut self.voices[index];
+            self.held.remove(note, voice.get_age());
+            voice.note_off_scaled(release_scale);
+        }
+    }
+
+    /// Cut off voices without a release, for a host choke event
+    ///
+    /// With a voice id, cuts the voices started with it; without one, every
+    /// voice of `note` on `channel`. They still report their ids when the
+    /// crossfade is over.
+    pub fn choke_voices(&mut self, note: u8, channel: u8, voice_id: Option<i32>) {
+        for (voice, tag) in self.voices.iter_mut().zip(&self.voice_tags) {
+            let matches = tag.is_some_and(|tag| match voice_id {
+                Some(voice_id) => tag.voice_id == voice_id,
+                None => tag.note == note && tag.channel == channel,
+            });
+            if matches && voice.is_active() {
+                voice.crossfade_out();
             }
         }
     }
 
+    /// Take the id of a voice that has ended since the last call
+    ///
+    /// Call after every [`VoiceManager::process`] until it returns `None`.
+    pub fn pop_ended_voice(&mut self) -> Option<VoiceTag> {
+        self.ended.pop()
+    }
+
+    /// Whether a sounding voice, or an ended one not yet collected, has `voice_id`
+    #[must_use] pub fn carries_voice_id(&self, voice_id: i32) -> bool {
+        self.voice_tags
+            .iter()
+            .chain(&self.tail_tags)
+            .flatten()
+            .chain(&self.ended)
+            .any(|tag| tag.voice_id == voice_id)
+    }
+
+    /// The most recently started voice holding `note`
+    ///
+    /// Only the stack policy holds one note on several voices.
+    fn newest_held_voice(&mut self, note: u8) -> Option<&mut V> {
+        self.voices
+            .iter_mut()
+            .filter(|voice| voice.get_note() == note && voice.get_state() == VoiceState::Active)
+            .max_by_key(|voice| voice.get_age())
+    }
+
+    /// The voice a note-off for `note` should release, if any
+    ///
+    /// Note-offs pair with the newest unmatched note-on of their note. When that
+    /// note-on's voice has since been stolen, retriggered or released, the
+    /// note-off is used up without touching another key's voice. Without a
+    /// recorded note-on (dropped by overflow, or cleared by a reset) it falls back
+    /// to the newest voice holding the note.
+    fn note_off_target(&mut self, note: u8) -> Option<&mut V> {
+        match self.held.pop(note) {
+            Some(id) => self.voices.iter_mut().find(|voice| {
+                voice.get_age() == id
+                    && voice.get_note() == note
+                    && voice.get_state() == VoiceState::Active
+            }),
+            None => self.newest_held_voice(note),
+        }
+    }
+
     /// Process audio for all voices and fill buffer
     ///
     /// Mixes all active voices into the output buffer.
@@ -250,14 +2047,82 @@ impl VoiceManager {
         // Clear buffer
         buffer.fill(0.0);
 
-        // Mix all voices - process sample-by-sample for sample-accurate mixing
-        // Each sample contains contributions from all voices at that exact time point
-        for sample in buffer.iter_mut() {
-            for voice in &mut self.voices {
-                if voice.get_state() != VoiceState::Idle {
-                    *sample += voice.process();
+        // Each sounding voice adds its block into the mix, up to each stuck-note
+        // release in turn
+        let mut start = 0;
+        while start < buffer.len() {
+            let end = self.stuck_release_end(start, buffer.len());
+            for voice in self.voices.iter_mut().chain(&mut self.tails) {
+                if voice.is_active() {
+                    voice.process_block(&mut buffer[start..end]);
+                }
+            }
+            self.release_stuck_voices();
+            start = end;
+        }
+
+        self.collect_ended_voices();
+    }
+
+    /// Process audio for all voices into a stereo pair, each voice at its pan
+    ///
+    /// Unpanned voices land in both channels at full level, the same as
+    /// [`VoiceManager::process`].
+    ///
+    /// # Arguments
+    /// * `left` - Left output buffer to fill
+    /// * `right` - Right output buffer to fill (same length as `left`)
+    pub fn process_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        left.fill(0.0);
+        right.fill(0.0);
+
+        let mut start = 0;
+        while start < left.len() {
+            let end = self.stuck_release_end(start, left.len());
+            for voice in self.voices.iter_mut().chain(&mut self.tails) {
+                if voice.is_active() {
+                    voice.process_block_stereo(&mut left[start..end], &mut right[start..end]);
                 }
             }
+            self.release_stuck_voices();
+            start = end;
+        }
+
+        self.collect_ended_voices();
+    }
+
+    /// End of the stretch of a block starting at `start` that runs before the
+    /// stuck-note watchdog next releases a voice (or the end of the block)
+    ///
+    /// Splitting there releases the voice on exactly the sample it runs over,
+    /// whatever the block size.
+    fn stuck_release_end(&self, start: usize, len: usize) -> usize {
+        if self.stuck_timeout_samples == 0 {
+            return len;
+        }
+        let release_at = self.stuck_timeout_samples + 1;
+        let next_release = self
+            .voices
+            .iter()
+            .filter(|voice| voice.get_state() == VoiceState::Active)
+            .map(|voice| release_at.saturating_sub(voice.get_active_samples()))
+            .min();
+        let Some(samples) = next_release else {
+            return len;
+        };
+
+        #[allow(clippy::cast_possible_truncation)] // Capped at the block length
+        let samples = samples.clamp(1, len as u64) as usize;
+        (start + samples).min(len)
+    }
+
+    /// Queue the ids of voices that have fallen silent
+    fn collect_ended_voices(&mut self) {
+        let tags = self.voice_tags.iter_mut().chain(&mut self.tail_tags);
+        for (voice, tag) in self.voices.iter().chain(&self.tails).zip(tags) {
+            if !voice.is_active() {
+                push_ended(&mut self.ended, tag.take());
+            }
         }
     }
 
@@ -277,18 +2142,123 @@ impl VoiceManager {
             .count()
     }
 
+    /// Samples until every voice (tails included) falls silent, or `None`
+    /// while any note is held
+    #[must_use] pub fn tail_samples(&self) -> Option<u32> {
+        self.voices
+            .iter()
+            .chain(&self.tails)
+            .try_fold(0, |longest, voice| Some(voice.tail_samples()?.max(longest)))
+    }
+
     /// Get list of active note numbers
     #[must_use] pub fn get_active_notes(&self) -> Vec<u8> {
         self.voices
             .iter()
             .filter(|v| v.get_state() == VoiceState::Active)
-            .map(Voice::get_note)
+            .map(V::get_note)
             .collect()
     }
 
     /// Get voice states (for testing)
     #[must_use] pub fn get_voice_states(&self) -> Vec<VoiceState> {
-        self.voices.iter().map(Voice::get_state).collect()
+        self.voices.iter().map(V::get_state).collect()
+    }
+
+    /// Snapshot of every voice slot, for the diagnostics panel
+    pub fn snapshots(&self) -> impl Iterator<Item = VoiceSnapshot> + '_ {
+        self.voices.iter().map(|voice| VoiceSnapshot {
+            note: voice.get_note(),
+            state: voice.get_state(),
+            stage: voice.envelope_stage(),
+            age: self.voice_age_counter.saturating_sub(voice.get_age() + 1),
+            level: voice.current_level(),
+        })
+    }
+
+    /// Number of voices stolen since creation
+    #[must_use] pub fn steal_count(&self) -> u64 {
+        self.steal_count
+    }
+
+    /// Set how long a voice may stay Active without a note-off (0 = no limit)
+    ///
+    /// Guards against notes left hanging by a missed note-off (host quirks,
+    /// dropped MIDI). Retriggering the note restarts the timeout.
+    pub fn set_stuck_timeout_ms(&mut self, timeout_ms: f32) {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Clamped to >= 0
+        let samples = (timeout_ms.max(0.0) / 1000.0 * self.sample_rate) as u64;
+        self.stuck_timeout_samples = samples;
+    }
+
+    /// Force-release every voice held longer than the stuck-note timeout
+    ///
+    /// Processing calls this on the sample each voice runs over; the releases
+    /// use the normal release stage, so they don't click.
+    pub fn release_stuck_voices(&mut self) {
+        if self.stuck_timeout_samples == 0 {
+            return;
+        }
+
+        for voice in &mut self.voices {
+            if voice.get_state() == VoiceState::Active
+                && voice.get_active_samples() > self.stuck_timeout_samples
+            {
+                voice.note_off();
+                self.stuck_release_count += 1;
+            }
+        }
+    }
+
+    /// Release every held voice through its normal release stage
+    ///
+    /// For the host bypass: the voices wind down instead of stopping dead, and
+    /// note-offs still in flight for them are ignored.
+    pub fn release_all(&mut self) {
+        for voice in &mut self.voices {
+            if voice.get_state() == VoiceState::Active {
+                voice.note_off();
+            }
+        }
+        self.held.clear();
+    }
+
+    /// Reset every sounding voice whose output has gone NaN or infinite
+    ///
+    /// Returns how many were reset. A voice that blows up stays that way, so
+    /// without this one bad note would latch the whole mix at NaN.
+    pub fn reset_non_finite_voices(&mut self) -> usize {
+        let mut count = 0;
+        for voice in self.voices.iter_mut().chain(&mut self.tails) {
+            if voice.is_active() && !voice.is_finite() {
+                voice.reset();
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Largest per-voice limiter gain reduction since the last call, in dB
+    pub fn take_limiter_reduction_db(&mut self) -> f32 {
+        self.all_voices_mut()
+            .map(SynthVoice::take_limiter_reduction_db)
+            .fold(0.0, f32::max)
+    }
+
+    /// Taps of the most recently started sounding voice (silence with none),
+    /// for the debug outputs
+    #[cfg(feature = "debug-outputs")]
+    #[must_use] pub fn probe(&self) -> ProbeTaps {
+        self.voices
+            .iter()
+            .filter(|voice| voice.is_active())
+            .max_by_key(|voice| voice.get_age())
+            .map_or_else(ProbeTaps::default, SynthVoice::probe)
+    }
+
+    /// Number of voices force-released by the stuck-note watchdog since creation
+    #[must_use] pub fn stuck_release_count(&self) -> u64 {
+        self.stuck_release_count
     }
 
     /// Get maximum voice count
@@ -298,53 +2268,147 @@ impl VoiceManager {
 
     /// Reset all voices
     pub fn reset(&mut self) {
-        for voice in &mut self.voices {
+        for voice in self.all_voices_mut() {
             voice.reset();
         }
-    }
+        self.held.clear();
 
-    /// Update waveform type for all voices
-    pub fn set_waveform(&mut self, waveform: WaveformType) {
-        for voice in &mut self.voices {
-            voice.set_waveform(waveform);
+        // Nothing is sounding, so nothing needs its old settings
+        let pinned = self.voice_pinned.iter_mut().chain(&mut self.tail_pinned);
+        for (voice, pinned) in self.voices.iter_mut().chain(&mut self.tails).zip(pinned) {
+            refresh_pinned(voice, pinned, self.params.as_ref());
         }
     }
 
-    /// Update attack time for all voices
-    pub fn set_attack_ms(&mut self, attack_ms: f32) {
-        for voice in &mut self.voices {
-            voice.set_envelope_attack_ms(attack_ms);
+    /// Apply the shared voice settings to every voice
+    ///
+    /// Meant to be called every block; the voices are only updated when the
+    /// settings differ from the last ones applied. Pinned voices keep theirs
+    /// until their next note.
+    pub fn set_params(&mut self, params: &V::Params) {
+        if self.params.as_ref() == Some(params) {
+            return;
+        }
+        let pinned = self.voice_pinned.iter().chain(&self.tail_pinned);
+        for (voice, &pinned) in self.voices.iter_mut().chain(&mut self.tails).zip(pinned) {
+            if !pinned {
+                voice.set_params(params);
+            }
         }
+        self.params = Some(params.clone());
     }
 
-    /// Update decay time for all voices
-    pub fn set_decay_ms(&mut self, decay_ms: f32) {
-        for voice in &mut self.voices {
-            voice.set_envelope_decay_ms(decay_ms);
+    /// Keep the sounding voices on their current settings until they end
+    ///
+    /// For a patch change that leaves the notes already playing as they were:
+    /// later [`VoiceManager::set_params`] calls only reach the other voices, and
+    /// a pinned voice takes the latest settings when it starts its next note.
+    pub fn pin_sounding_voices(&mut self) {
+        let pinned = self.voice_pinned.iter_mut().chain(&mut self.tail_pinned);
+        for (voice, pinned) in self.voices.iter().chain(&self.tails).zip(pinned) {
+            *pinned |= voice.is_active();
         }
     }
 
-    /// Update sustain level for all voices
-    pub fn set_sustain_level(&mut self, sustain_level: f32) {
-        for voice in &mut self.voices {
-            voice.set_envelope_sustain_level(sustain_level);
+    /// Fade every sounding voice out over the restart crossfade
+    ///
+    /// Faded voices still report their voice ids when they end.
+    pub fn fade_out_voices(&mut self) {
+        for voice in self.voices.iter_mut().filter(|voice| voice.is_active()) {
+            voice.crossfade_out();
         }
     }
 
-    /// Update release time for all voices
-    pub fn set_release_ms(&mut self, release_ms: f32) {
-        for voice in &mut self.voices {
-            voice.set_envelope_release_ms(release_ms);
+    /// Set how strongly note-off velocity shapes the release
+    ///
+    /// Positive amounts make fast key releases shorter and slow ones longer;
+    /// negative amounts invert that. 0 ignores note-off velocity.
+    pub fn set_release_velocity_amount(&mut self, amount: f32) {
+        self.release_velocity_amount = amount.clamp(-1.0, 1.0);
+    }
+
+    /// Choose what a note-on does when its note is already sounding
+    pub fn set_same_note_policy(&mut self, policy: SameNotePolicy) {
+        self.same_note = policy;
+    }
+
+    /// Choose how new notes pick a voice
+    ///
+    /// Round-robin and rotation spread notes across every voice, so per-voice
+    /// random properties (the random mod source, free-running phase) cycle evenly.
+    pub fn set_allocation(&mut self, allocation: VoiceAllocation) {
+        self.allocation = allocation;
+    }
+
+    /// Choose which voice a note takes over when every voice is busy
+    ///
+    /// Quietest steals whatever is least audible (a decayed pad note before a
+    /// fresh release), so the cut is hardest to hear.
+    pub fn set_stealing(&mut self, stealing: VoiceStealing) {
+        self.stealing = stealing;
+    }
+
+    /// Assign note ranges to choke groups (no range = no choking)
+    ///
+    /// Where ranges overlap, the later one wins. Notes outside every range, or in
+    /// a range with group 0, never choke or get choked.
+    pub fn set_choke_ranges(&mut self, ranges: &[ChokeRange]) {
+        self.choke_groups = [0; 128];
+        for range in ranges {
+            let high = range.high.min(127);
+            for note in range.low..=high {
+                self.choke_groups[usize::from(note)] = range.group;
+            }
         }
     }
 
+    /// Choose how a note cuts off the rest of its choke group
+    pub fn set_choke_policy(&mut self, policy: ChokePolicy) {
+        self.choke_policy = policy;
+    }
+
+    /// Protect the lowest held note from voice stealing
+    ///
+    /// Keeps a sustained bass line sounding while dense chords on top steal
+    /// voices from each other.
+    pub fn set_bass_reserve(&mut self, enabled: bool) {
+        self.bass_reserve = enabled;
+    }
+
     /// Steal a voice
     ///
     /// Strategy:
     /// 1. Prefer releasing voices over active voices
     /// 2. Among releasing voices, steal oldest
-    /// 3. Among active voices, steal oldest
-    fn steal_voice(&mut self, note: u8, velocity: f32) {
+    /// 3. Among active voices, steal oldest (skipping the lowest held note
+    ///    when bass reserve is on, unless it's the only voice)
+    ///
+    /// With quietest stealing, the voice with the lowest envelope level goes
+    /// instead, releasing or not (oldest first on a tie, bass note still spared).
+    fn steal_voice(&mut self, note: u8, velocity: f32, tag: Option<VoiceTag>) {
+        self.steal_count += 1;
+
+        if self.stealing == VoiceStealing::Quietest {
+            let protected = if self.bass_reserve {
+                self.lowest_held_voice()
+            } else {
+                None
+            };
+            let quietest_index = self
+                .voices
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| Some(i) != protected)
+                .min_by(|(_, a), (_, b)| {
+                    a.current_level()
+                        .total_cmp(&b.current_level())
+                        .then(a.get_age().cmp(&b.get_age()))
+                })
+                .map_or(0, |(i, _)| i);
+            self.restart_voice(quietest_index, note, velocity, tag);
+            return;
+        }
+
         // Find releasing voice with oldest age
         let mut oldest_releasing: Option<usize> = None;
         let mut oldest_releasing_age = u64::MAX;
@@ -359,30 +2423,167 @@ impl VoiceManager {
 
         // If we found a releasing voice, steal it
         if let Some(index) = oldest_releasing {
-            self.voices[index].note_on(note, velocity);
-            self.voices[index].set_age(self.voice_age_counter);
-            self.voice_age_counter += 1;
+            self.restart_voice(index, note, velocity, tag);
             return;
         }
 
-        // No releasing voice - find oldest active voice
-        let mut oldest_active_index = 0;
-        let mut oldest_active_age = self.voices[0].get_age();
+        // No releasing voice - find oldest active voice, sparing the bass note
+        let protected = if self.bass_reserve {
+            self.lowest_held_voice()
+        } else {
+            None
+        };
+        let oldest_active_index = self
+            .voices
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| Some(i) != protected)
+            .min_by_key(|(_, voice)| voice.get_age())
+            .map_or(0, |(i, _)| i);
 
-        for (i, voice) in self.voices.iter().enumerate() {
-            if voice.get_age() < oldest_active_age {
-                oldest_active_index = i;
-                oldest_active_age = voice.get_age();
-            }
+        // Steal oldest active voice
+        self.restart_voice(oldest_active_index, note, velocity, tag);
+    }
+
+    /// Start a note on a sounding voice, crossfading from the note it was playing
+    ///
+    /// The old note moves to an idle tail voice and fades out there, taking its
+    /// voice id along. With every tail busy the voice restarts in place (its
+    /// envelope starts from zero) and the old note's id ends there and then.
+    fn restart_voice(&mut self, index: usize, note: u8, velocity: f32, tag: Option<VoiceTag>) {
+        let old_tag = std::mem::replace(&mut self.voice_tags[index], tag);
+        let tail = self
+            .tails
+            .iter_mut()
+            .zip(&mut self.tail_tags)
+            .zip(&mut self.tail_pinned)
+            .find(|((tail, _), _)| tail.get_state() == VoiceState::Idle);
+
+        if let Some(((tail, tail_tag), tail_pinned)) = tail {
+            // The old note fades out with the settings it had
+            std::mem::swap(&mut self.voices[index], tail);
+            std::mem::swap(&mut self.voice_pinned[index], tail_pinned);
+            tail.crossfade_out();
+            push_ended(&mut self.ended, std::mem::replace(tail_tag, old_tag));
+
+            let voice = &mut self.voices[index];
+            refresh_pinned(voice, &mut self.voice_pinned[index], self.params.as_ref());
+            voice.continue_from(tail);
+            voice.note_on(note, velocity);
+            voice.crossfade_in();
+        } else {
+            push_ended(&mut self.ended, old_tag);
+            let voice = &mut self.voices[index];
+            refresh_pinned(voice, &mut self.voice_pinned[index], self.params.as_ref());
+            voice.note_on(note, velocity);
         }
 
-        // Steal oldest active voice
-        self.voices[oldest_active_index].note_on(note, velocity);
-        self.voices[oldest_active_index].set_age(self.voice_age_counter);
-        self.voice_age_counter += 1;
+        self.voices[index].set_age(self.voice_age_counter);
+        self.voice_age_counter += 1;
+        self.next_slot = (index + 1) % self.voices.len();
+    }
+
+    /// Slot of the voice playing the lowest held (Active) note
+    fn lowest_held_voice(&self) -> Option<usize> {
+        self.voices
+            .iter()
+            .enumerate()
+            .filter(|(_, voice)| voice.get_state() == VoiceState::Active)
+            .min_by_key(|(_, voice)| voice.get_note())
+            .map(|(i, _)| i)
+    }
+
+    /// Every voice slot followed by the tail voices, for settings that apply to all
+    fn all_voices_mut(&mut self) -> impl Iterator<Item = &mut V> {
+        self.voices.iter_mut().chain(&mut self.tails)
+    }
+}
+
+/// Give a pinned voice the latest shared settings and unpin it
+fn refresh_pinned<V: SynthVoice>(voice: &mut V, pinned: &mut bool, params: Option<&V::Params>) {
+    if std::mem::take(pinned) {
+        if let Some(params) = params {
+            voice.set_params(params);
+        }
+    }
+}
+
+/// Note-on ids of the keys still down, per note (newest last)
+///
+/// Fixed-size, so tracking never allocates on the audio thread.
+struct HeldNotes {
+    ids: [[u64; MAX_HELD_PER_NOTE]; 128],
+    counts: [usize; 128],
+}
+
+impl HeldNotes {
+    fn new() -> Self {
+        Self {
+            ids: [[0; MAX_HELD_PER_NOTE]; 128],
+            counts: [0; 128],
+        }
+    }
+
+    /// Record a note-on, forgetting the oldest press of the note when full
+    fn push(&mut self, note: u8, id: u64) {
+        let note = usize::from(note & 0x7F);
+        let ids = &mut self.ids[note];
+        if self.counts[note] == MAX_HELD_PER_NOTE {
+            ids.rotate_left(1);
+            self.counts[note] -= 1;
+        }
+        ids[self.counts[note]] = id;
+        self.counts[note] += 1;
+    }
+
+    /// Forget one note-on of a note (released by voice id rather than paired)
+    fn remove(&mut self, note: u8, id: u64) {
+        let note = usize::from(note & 0x7F);
+        let count = self.counts[note];
+        if let Some(position) = self.ids[note][..count].iter().position(|&held| held == id) {
+            self.ids[note].copy_within(position + 1..count, position);
+            self.counts[note] = count - 1;
+        }
+    }
+
+    /// Take the newest unmatched note-on of a note
+    fn pop(&mut self, note: u8) -> Option<u64> {
+        let note = usize::from(note & 0x7F);
+        let count = self.counts[note].checked_sub(1)?;
+        self.counts[note] = count;
+        Some(self.ids[note][count])
+    }
+
+    fn clear(&mut self) {
+        self.counts = [0; 128];
+    }
+}
+
+/// Queue an ended voice's id, if it had one (dropped when the queue is full)
+fn push_ended(ended: &mut Vec<VoiceTag>, tag: Option<VoiceTag>) {
+    if let Some(tag) = tag {
+        if ended.len() < ended.capacity() {
+            ended.push(tag);
+        }
     }
 }
 
+/// Note name with octave, e.g. 60 → "C4"
+#[must_use] pub fn note_name(note: u8) -> String {
+    let octave = i32::from(note / 12) - 1;
+    format!("{}{octave}", ROOT_NAMES[usize::from(note % 12)])
+}
+
+/// Release time multiplier for a note-off velocity
+///
+/// Centered on velocity 0.5 (what most controllers send when they don't measure
+/// release velocity), so those controllers are unaffected. At full amount the
+/// release ranges from 2x (velocity 0) to 0.5x (velocity 1).
+#[inline]
+#[must_use] pub fn release_time_scale(velocity: f32, amount: f32) -> f32 {
+    4.0f32.powf(amount * (0.5 - velocity.clamp(0.0, 1.0)))
+}
+
 /// Convert MIDI note number to frequency in Hz
 ///
 /// Uses standard MIDI tuning: A4 (note 69) = 440 Hz
@@ -394,12 +2595,36 @@ impl VoiceManager {
 /// Frequency in Hz
 #[inline]
 #[must_use] pub fn midi_note_to_frequency(note: u8) -> f32 {
-    440.0 * 2.0f32.powf((f32::from(note) - 69.0) / 12.0)
+    pitch_to_frequency(f32::from(note))
+}
+
+/// Convert a fractional MIDI pitch (e.g. mid-glide) to frequency
+#[inline]
+#[must_use] pub fn pitch_to_frequency(pitch: f32) -> f32 {
+    STANDARD_A4_HZ * 2.0f32.powf((pitch - 69.0) / 12.0)
+}
+
+/// Pitch offset in semitones that moves A4 from 440 Hz to `a4_hz`, plus
+/// `fine_tune_cents`
+///
+/// Added to a pitch before `pitch_to_frequency`, so it stacks with glide and
+/// pitch modulation like any other offset.
+#[must_use] pub fn tuning_offset_semitones(a4_hz: f32, fine_tune_cents: f32) -> f32 {
+    12.0 * (a4_hz / STANDARD_A4_HZ).log2() + fine_tune_cents / 100.0
+}
+
+/// Rate the control-rate sources run at, in Hz
+fn control_rate(sample_rate: f32, divisor: u32) -> f32 {
+    #[allow(clippy::cast_precision_loss)] // At most MAX_CONTROL_DIVISOR
+    let divisor = divisor as f32;
+    sample_rate / divisor
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::scale::Scale;
+    use shared_core::analysis::estimate_frequency;
 
     const SAMPLE_RATE: f32 = 44100.0;
     const MAX_VOICES: usize = 16;
@@ -537,13 +2762,775 @@ mod tests {
         // Release one voice
         vm.note_off(62);
 
-        // Add new note - should steal the releasing voice (62) not oldest (60)
-        vm.note_on(67, 1.0);
+        // Add new note - should steal the releasing voice (62) not oldest (60)
+        vm.note_on(67, 1.0);
+
+        let notes = vm.get_active_notes();
+        assert!(notes.contains(&60), "Note 60 should still be active");
+        assert!(!notes.contains(&62), "Note 62 (releasing) should be stolen");
+        assert!(notes.contains(&67), "Note 67 should be active");
+    }
+
+    #[test]
+    fn test_bass_reserve_protects_lowest_note() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 4);
+        vm.set_bass_reserve(true);
+
+        // The bass note is the oldest, so it would normally go first
+        vm.note_on(36, 1.0);
+        vm.note_on(60, 1.0);
+        vm.note_on(64, 1.0);
+        vm.note_on(67, 1.0);
+        vm.note_on(72, 1.0);
+        vm.note_on(76, 1.0);
+
+        let notes = vm.get_active_notes();
+        assert!(notes.contains(&36), "Bass note should survive");
+        assert!(!notes.contains(&60), "Oldest chord note should be stolen");
+        assert!(!notes.contains(&64), "Next oldest chord note should be stolen");
+        assert!(notes.contains(&76));
+    }
+
+    #[test]
+    fn test_bass_reserve_off_steals_oldest() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 2);
+        vm.note_on(36, 1.0);
+        vm.note_on(60, 1.0);
+        vm.note_on(64, 1.0);
+        assert!(!vm.get_active_notes().contains(&36));
+    }
+
+    #[test]
+    fn test_bass_reserve_with_one_voice_still_steals() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 1);
+        vm.set_bass_reserve(true);
+        vm.note_on(36, 1.0);
+        vm.note_on(60, 1.0);
+        assert_eq!(vm.get_active_notes(), vec![60]);
+    }
+
+    #[test]
+    fn test_quietest_stealing_takes_lowest_level() {
+        for (stealing, stolen) in [(VoiceStealing::Oldest, 60), (VoiceStealing::Quietest, 64)] {
+            let mut vm = VoiceManager::new(SAMPLE_RATE, 3);
+            vm.set_params(&VoiceParams {
+                attack_ms: 0.0,
+                ..VoiceParams::default()
+            });
+            vm.set_stealing(stealing);
+
+            // The middle note is neither oldest nor newest, but the quietest
+            vm.note_on(60, 1.0);
+            vm.note_on(64, 0.2);
+            vm.note_on(67, 0.9);
+            vm.process(&mut [0.0; 64]);
+
+            vm.note_on(72, 1.0);
+            let notes = vm.get_active_notes();
+            assert!(!notes.contains(&stolen), "{stealing:?}: {notes:?}");
+            assert_eq!(notes.len(), 3, "{stealing:?}");
+        }
+    }
+
+    #[test]
+    fn test_quietest_stealing_spares_bass_note() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 2);
+        vm.set_params(&VoiceParams {
+            attack_ms: 0.0,
+            ..VoiceParams::default()
+        });
+        vm.set_stealing(VoiceStealing::Quietest);
+        vm.set_bass_reserve(true);
+
+        vm.note_on(36, 0.1);
+        vm.note_on(60, 1.0);
+        vm.process(&mut [0.0; 64]);
+        vm.note_on(64, 1.0);
+        assert!(vm.get_active_notes().contains(&36));
+    }
+
+    #[test]
+    fn test_pinned_voices_ring_with_their_settings() {
+        let long_release = VoiceParams {
+            attack_ms: 0.0,
+            release_ms: 500.0,
+            ..VoiceParams::default()
+        };
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 4);
+        vm.set_params(&long_release);
+        vm.note_on(60, 1.0);
+        vm.process(&mut [0.0; 64]);
+
+        // A patch with a short release loads while the note is held
+        vm.pin_sounding_voices();
+        vm.set_params(&VoiceParams {
+            release_ms: 0.0,
+            ..long_release
+        });
+        vm.note_on(64, 1.0);
+        vm.note_off(60);
+        vm.note_off(64);
+        for _ in 0..10 {
+            vm.process(&mut [0.0; 441]);
+        }
+        let sounding: Vec<u8> = vm
+            .snapshots()
+            .filter(|voice| voice.state != VoiceState::Idle)
+            .map(|voice| voice.note)
+            .collect();
+        assert_eq!(sounding, vec![60], "Only the old note is still releasing");
+
+        // Once it has ended, its slot plays the new patch
+        for _ in 0..100 {
+            vm.process(&mut [0.0; 441]);
+        }
+        assert_eq!(vm.active_voice_count(), 0);
+        vm.note_on(67, 1.0);
+        assert_eq!(slot_of(&vm, 67), Some(0));
+        vm.note_off(67);
+        vm.process(&mut [0.0; 441]);
+        assert_eq!(vm.active_voice_count(), 0, "New release on the reused slot");
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Short test lengths
+    fn test_fade_out_voices_ignores_their_release() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 4);
+        vm.set_params(&VoiceParams {
+            release_ms: 1000.0,
+            ..VoiceParams::default()
+        });
+        vm.note_on(60, 1.0);
+        vm.note_on(64, 1.0);
+        vm.process(&mut [0.0; 64]);
+
+        vm.fade_out_voices();
+        for _ in 0..(CROSSFADE_MS / 1000.0 * SAMPLE_RATE) as usize / 64 + 2 {
+            vm.process(&mut [0.0; 64]);
+        }
+        assert_eq!(vm.active_voice_count(), 0);
+    }
+
+    #[test]
+    fn test_latched_envelope_keeps_note_on_release() {
+        for latch in [false, true] {
+            let latch = LatchGroups {
+                envelope: latch,
+                filter: false,
+            };
+            let long_release = VoiceParams {
+                attack_ms: 0.0,
+                release_ms: 500.0,
+                latch,
+                ..VoiceParams::default()
+            };
+            let mut vm = VoiceManager::new(SAMPLE_RATE, 4);
+            vm.set_params(&long_release);
+            vm.note_on(60, 1.0);
+            vm.process(&mut [0.0; 64]);
+
+            // Shorten the release mid-note; only latched voices ignore it
+            vm.set_params(&VoiceParams {
+                release_ms: 0.0,
+                ..long_release
+            });
+            vm.note_on(64, 1.0);
+            vm.note_off(60);
+            vm.note_off(64);
+            for _ in 0..10 {
+                vm.process(&mut [0.0; 441]);
+            }
+            let sounding: Vec<u8> = vm
+                .snapshots()
+                .filter(|voice| voice.state != VoiceState::Idle)
+                .map(|voice| voice.note)
+                .collect();
+            let expected = if latch.envelope { vec![60] } else { vec![] };
+            assert_eq!(sounding, expected, "{latch:?}");
+        }
+    }
+
+    #[test]
+    fn test_latched_takes_only_the_latched_groups() {
+        let held = VoiceParams {
+            attack_ms: 1.0,
+            filter_cutoff_hz: 500.0,
+            ..VoiceParams::default()
+        };
+        let params = VoiceParams {
+            attack_ms: 50.0,
+            filter_cutoff_hz: 4000.0,
+            waveform: WaveformType::Sawtooth,
+            latch: LatchGroups {
+                envelope: false,
+                filter: true,
+            },
+            ..VoiceParams::default()
+        };
+        assert_eq!(
+            params.latched(&held),
+            VoiceParams {
+                filter_cutoff_hz: 500.0,
+                ..params
+            }
+        );
+        let unlatched = VoiceParams {
+            latch: LatchGroups::default(),
+            ..params
+        };
+        assert_eq!(unlatched.latched(&held), unlatched);
+    }
+
+    /// Slot playing `note`, from the voice snapshots
+    fn slot_of(vm: &VoiceManager, note: u8) -> Option<usize> {
+        vm.snapshots()
+            .position(|voice| voice.note == note && voice.state == VoiceState::Active)
+    }
+
+    #[test]
+    fn test_round_robin_cycles_through_slots() {
+        for (allocation, expected) in [
+            (VoiceAllocation::FirstIdle, [0, 0, 0, 0, 0]),
+            (VoiceAllocation::RoundRobin, [0, 1, 2, 3, 0]),
+            (VoiceAllocation::Rotate, [0, 1, 2, 3, 0]),
+        ] {
+            let mut vm = VoiceManager::new(SAMPLE_RATE, 4);
+            vm.set_params(&VoiceParams {
+                release_ms: 0.0,
+                ..VoiceParams::default()
+            });
+            vm.set_allocation(allocation);
+
+            for (note, &slot) in (60..).zip(&expected) {
+                vm.note_on(note, 1.0);
+                assert_eq!(slot_of(&vm, note), Some(slot), "{allocation:?} note {note}");
+                vm.note_off(note);
+                vm.process(&mut [0.0; 4]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_robin_skips_busy_slots_and_rotate_steals_them() {
+        for (allocation, expected_slot, expected_steals) in [
+            (VoiceAllocation::RoundRobin, 1, 0),
+            (VoiceAllocation::Rotate, 0, 1),
+        ] {
+            let mut vm = VoiceManager::new(SAMPLE_RATE, 3);
+            vm.set_params(&VoiceParams {
+                release_ms: 0.0,
+                ..VoiceParams::default()
+            });
+            vm.set_allocation(allocation);
+
+            // Slot 0 held, slot 1 free again, slot 2 held: the turn is back at slot 0
+            vm.note_on(60, 1.0);
+            vm.note_on(62, 1.0);
+            vm.note_off(62);
+            vm.process(&mut [0.0; 4]);
+            vm.note_on(64, 1.0);
+
+            vm.note_on(67, 1.0);
+            assert_eq!(slot_of(&vm, 67), Some(expected_slot), "{allocation:?}");
+            assert_eq!(vm.steal_count(), expected_steals, "{allocation:?}");
+        }
+    }
+
+    #[test]
+    fn test_same_note_policies() {
+        // Voices sounding note 60 after two note-ons, and after one note-off
+        let counts = |policy: SameNotePolicy| {
+            let mut vm = VoiceManager::new(SAMPLE_RATE, 4);
+            vm.set_same_note_policy(policy);
+            vm.note_on(60, 1.0);
+            vm.note_on(60, 1.0);
+            let held = vm.get_active_notes().len();
+            let sounding = vm.active_voice_count();
+            vm.note_off(60);
+            (held, sounding, vm.get_active_notes().len())
+        };
+
+        assert_eq!(counts(SameNotePolicy::Retrigger), (1, 1, 0));
+        assert_eq!(counts(SameNotePolicy::Stack), (2, 2, 1));
+        assert_eq!(counts(SameNotePolicy::MonoPerNote), (1, 2, 0));
+    }
+
+    #[test]
+    fn test_stacked_note_off_releases_newest_voice() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 4);
+        vm.set_same_note_policy(SameNotePolicy::Stack);
+        vm.note_on(60, 0.2);
+        vm.note_on(60, 1.0);
+        vm.note_off(60);
+
+        let held = vm.voices.iter().find(|voice| voice.get_state() == VoiceState::Active);
+        assert_eq!(held.map(Voice::get_age), Some(0), "The first note should still be held");
+    }
+
+    #[test]
+    #[allow(clippy::naive_bytecount)] // A handful of notes
+    fn test_note_off_skips_stolen_stacked_voice() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 2);
+        vm.set_same_note_policy(SameNotePolicy::Stack);
+        vm.set_bass_reserve(true);
+
+        // Two presses of 60, then the newer one's voice is stolen by 64 (bass
+        // reserve protects the older one)
+        vm.note_on(60, 1.0);
+        vm.note_on(60, 1.0);
+        vm.note_on(64, 1.0);
+        assert_eq!(vm.get_active_notes().iter().filter(|&&n| n == 60).count(), 1);
+
+        // The first note-off belongs to the stolen press; the other key still holds
+        vm.note_off(60);
+        assert!(vm.get_active_notes().contains(&60));
+        vm.note_off(60);
+        assert!(!vm.get_active_notes().contains(&60));
+    }
+
+    #[test]
+    fn test_note_off_without_note_on_falls_back() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 4);
+        vm.note_on(60, 1.0);
+        vm.held.clear();
+        vm.note_off(60);
+        assert!(vm.get_active_notes().is_empty());
+    }
+
+    #[test]
+    fn test_held_notes_forget_oldest_when_full() {
+        let mut held = HeldNotes::new();
+        for id in 0..10 {
+            held.push(60, id);
+        }
+        for id in (2..10).rev() {
+            assert_eq!(held.pop(60), Some(id));
+        }
+        assert_eq!(held.pop(60), None);
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)] // Note indices are below 4
+    fn test_fuzz_note_on_off_pairing() {
+        use shared_core::noise::NoiseGenerator;
+
+        for seed in 1..=50u32 {
+            let mut rng = NoiseGenerator::new(seed);
+            let mut vm = VoiceManager::new(SAMPLE_RATE, 3);
+            vm.set_params(&VoiceParams {
+                release_ms: 1.0,
+                ..VoiceParams::default()
+            });
+            vm.set_same_note_policy(SameNotePolicy::from_index(seed as usize % 3));
+            vm.set_allocation(VoiceAllocation::from_index(seed as usize / 3 % 3));
+            vm.set_bass_reserve(seed % 2 == 0);
+
+            // Keys currently down, per note (a handful of notes, so presses collide)
+            let mut keys_down = [0usize; 4];
+            for _ in 0..400 {
+                let note_index = rng.next_u32() as usize % keys_down.len();
+                let note = 60 + note_index as u8;
+                match rng.next_u32() % 3 {
+                    0 | 1 if rng.next_u32().is_multiple_of(2) || keys_down[note_index] == 0 => {
+                        vm.note_on(note, 1.0);
+                        keys_down[note_index] += 1;
+                    }
+                    0 | 1 => {
+                        vm.note_off(note);
+                        keys_down[note_index] -= 1;
+                    }
+                    _ => vm.process(&mut [0.0; 16]),
+                }
+
+                // A voice is only ever held by a key that is down
+                for (index, &down) in keys_down.iter().enumerate() {
+                    let held = vm
+                        .get_active_notes()
+                        .iter()
+                        .filter(|&&n| usize::from(n - 60) == index)
+                        .count();
+                    assert!(
+                        held <= down,
+                        "seed {seed}: note {} held by {held} voices, {down} keys down",
+                        60 + index
+                    );
+                }
+            }
+
+            // Letting go of every key releases every voice
+            for (index, &down) in keys_down.iter().enumerate() {
+                for _ in 0..down {
+                    vm.note_off(60 + index as u8);
+                }
+            }
+            assert!(
+                vm.get_active_notes().is_empty(),
+                "seed {seed}: stuck {:?}",
+                vm.get_active_notes()
+            );
+        }
+    }
+
+    #[test]
+    fn test_allocation_index_lookup() {
+        assert_eq!(VoiceAllocation::from_index(1), VoiceAllocation::RoundRobin);
+        assert_eq!(VoiceAllocation::from_index(5), VoiceAllocation::FirstIdle);
+        assert_eq!(VoiceAllocation::ALL.len(), VoiceAllocation::NAMES.len());
+    }
+
+    #[test]
+    fn test_snapshots_report_age_and_steals() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 2);
+        vm.note_on(60, 1.0);
+        vm.note_on(64, 1.0);
+        vm.note_on(67, 1.0); // Steals 60
+
+        let snapshots: Vec<VoiceSnapshot> = vm.snapshots().collect();
+        assert_eq!(snapshots[0].note, 67);
+        assert_eq!(snapshots[0].age, 0, "Newest voice should have age 0");
+        assert_eq!(snapshots[1].note, 64);
+        assert_eq!(snapshots[1].age, 1);
+        assert!(snapshots.iter().all(|voice| voice.state == VoiceState::Active));
+        assert_eq!(vm.steal_count(), 1);
+    }
+
+    #[test]
+    fn test_steal_crossfades_without_clicks() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 1);
+        vm.set_params(&VoiceParams {
+            waveform: WaveformType::Sine,
+            attack_ms: 0.0,
+            decay_ms: 0.0,
+            sustain_level: 1.0,
+            ..VoiceParams::default()
+        });
+
+        // Steal the only voice a quarter cycle in, at the top of the sine
+        vm.note_on(57, 1.0);
+        let mut buffer = [0.0; 50];
+        vm.process(&mut buffer);
+        let mut previous = buffer[49];
+        vm.note_on(64, 1.0);
+
+        let mut sample = [0.0];
+        for _ in 0..1000 {
+            vm.process(&mut sample);
+            assert!(
+                (sample[0] - previous).abs() < 0.15,
+                "Jump from {previous} to {}",
+                sample[0]
+            );
+            previous = sample[0];
+        }
+
+        assert_eq!(vm.get_active_notes(), vec![64]);
+        assert_eq!(vm.steal_count(), 1);
+        assert!(
+            vm.tails.iter().all(|tail| tail.get_state() == VoiceState::Idle),
+            "The old note should have faded out"
+        );
+    }
+
+    #[test]
+    fn test_stuck_voices_are_released_after_timeout() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 4);
+        vm.set_stuck_timeout_ms(10.0); // 441 samples
+        vm.note_on(60, 1.0);
+
+        let mut buffer = [0.0; 400];
+        vm.process(&mut buffer);
+        vm.release_stuck_voices();
+        assert_eq!(vm.get_active_notes(), vec![60], "Not stuck yet");
+
+        vm.process(&mut buffer);
+        vm.release_stuck_voices();
+        assert!(vm.get_active_notes().is_empty(), "Held past the timeout");
+        assert_eq!(vm.releasing_voice_count(), 1, "Should release, not cut");
+        assert_eq!(vm.stuck_release_count(), 1);
+    }
+
+    #[test]
+    fn test_non_finite_voices_are_reset() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 4);
+        vm.note_on(60, 1.0);
+        vm.note_on(64, 1.0);
+        let mut buffer = [0.0; 64];
+        vm.process(&mut buffer);
+        assert_eq!(vm.reset_non_finite_voices(), 0);
+
+        // An infinite input sample blows up one voice's filter for good
+        vm.voices[1].set_filter_enabled(true);
+        vm.voices[1].set_input_mix(1.0);
+        vm.voices[1].set_input(f32::INFINITY);
+        vm.process(&mut buffer[..1]);
+        vm.voices[1].set_input(0.0);
+        vm.process(&mut buffer);
+        assert!(buffer.iter().all(|sample| sample.is_nan()));
+
+        assert_eq!(vm.reset_non_finite_voices(), 1);
+        assert_eq!(vm.get_active_notes(), vec![60]);
+        vm.process(&mut buffer);
+        assert!(buffer.iter().all(|sample| sample.is_finite()));
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Short test lengths
+    fn test_choke_group_cuts_off_other_notes() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 8);
+        // Closed (42) and open (46) hi-hat choke each other; the snare (38) doesn't
+        vm.set_choke_ranges(&[
+            ChokeRange {
+                low: 42,
+                high: 46,
+                group: 1,
+            },
+            ChokeRange {
+                low: 44,
+                high: 44,
+                group: 0,
+            },
+        ]);
+        let mut buffer = [0.0; 64];
+
+        vm.note_on(38, 1.0);
+        vm.note_on(46, 1.0);
+        vm.process(&mut buffer);
+        vm.note_on(44, 1.0); // Carved out of the group
+        vm.note_on(42, 1.0);
+        let states = |vm: &VoiceManager| {
+            [38, 44, 46, 42].map(|note| {
+                vm.voices
+                    .iter()
+                    .find(|voice| voice.get_note() == note)
+                    .map(SynthVoice::get_state)
+            })
+        };
+        assert_eq!(
+            states(&vm),
+            [
+                Some(VoiceState::Active),
+                Some(VoiceState::Active),
+                Some(VoiceState::Releasing),
+                Some(VoiceState::Active)
+            ]
+        );
+
+        // A hard choke is over within the crossfade
+        for _ in 0..((CROSSFADE_MS / 1000.0 * SAMPLE_RATE) as usize / 64 + 2) {
+            vm.process(&mut buffer);
+        }
+        assert_eq!(states(&vm)[2], Some(VoiceState::Idle));
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Short test lengths
+    fn test_ended_voices_report_their_ids() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 1);
+        vm.set_params(&VoiceParams {
+            attack_ms: 0.0,
+            release_ms: 1.0,
+            ..VoiceParams::default()
+        });
+        let mut buffer = [0.0; 64];
+        let first = VoiceTag::new(60, 0, Some(7));
+        let second = VoiceTag::new(64, 1, None);
+        assert_eq!(second.voice_id, fallback_voice_id(64, 1));
+
+        // A stolen note keeps its id until its tail has faded
+        vm.note_on_tagged(60, 1.0, first);
+        vm.process(&mut buffer);
+        vm.note_on_tagged(64, 1.0, second);
+        assert!(vm.carries_voice_id(7));
+        for _ in 0..((CROSSFADE_MS / 1000.0 * SAMPLE_RATE) as usize / 64 + 2) {
+            vm.process(&mut buffer);
+        }
+        assert_eq!(vm.pop_ended_voice(), Some(first));
+        assert_eq!(vm.pop_ended_voice(), None);
+        assert!(!vm.carries_voice_id(7));
+
+        // A released note's id ends with its release
+        vm.note_off(64);
+        for _ in 0..10 {
+            vm.process(&mut buffer);
+        }
+        assert_eq!(vm.pop_ended_voice(), Some(second));
+
+        // Untagged voices report nothing
+        vm.note_on(60, 1.0);
+        vm.note_off(60);
+        for _ in 0..10 {
+            vm.process(&mut buffer);
+        }
+        assert_eq!(vm.pop_ended_voice(), None);
+    }
+
+    #[test]
+    fn test_note_off_and_choke_by_voice_id() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 4);
+        vm.set_same_note_policy(SameNotePolicy::Stack);
+        vm.note_on_tagged(60, 1.0, VoiceTag::new(60, 0, Some(1)));
+        vm.note_on_tagged(60, 1.0, VoiceTag::new(60, 0, Some(2)));
+        vm.note_on_tagged(62, 1.0, VoiceTag::new(62, 3, None));
+
+        // The older of the two stacked notes, which a plain note-off would skip
+        vm.note_off_voice(60, 0.5, Some(1));
+        vm.note_off_voice(60, 0.5, Some(9));
+        assert_eq!(
+            vm.get_voice_states()[..3],
+            [VoiceState::Releasing, VoiceState::Active, VoiceState::Active]
+        );
+
+        // The remaining press still pairs with a plain note-off
+        vm.note_off_voice(60, 0.5, None);
+        assert_eq!(vm.get_voice_states()[1], VoiceState::Releasing);
+
+        // Choke without an id goes by note and channel
+        vm.choke_voices(62, 0, None);
+        assert_eq!(vm.get_voice_states()[2], VoiceState::Active);
+        vm.choke_voices(62, 3, None);
+        assert_eq!(vm.get_voice_states()[2], VoiceState::Releasing);
+    }
+
+    #[test]
+    fn test_fast_release_choke_shortens_release() {
+        let release_samples = |policy: Option<ChokePolicy>| {
+            let mut vm = VoiceManager::new(SAMPLE_RATE, 4);
+            vm.set_params(&VoiceParams {
+                attack_ms: 0.0,
+                release_ms: 500.0,
+                ..VoiceParams::default()
+            });
+            vm.set_choke_ranges(&[ChokeRange {
+                low: 60,
+                high: 61,
+                group: 2,
+            }]);
+            let mut buffer = [0.0; 32];
+            vm.note_on(60, 1.0);
+            vm.process(&mut buffer);
+            match policy {
+                Some(policy) => {
+                    vm.set_choke_policy(policy);
+                    vm.note_on(61, 1.0);
+                }
+                None => vm.note_off(60),
+            }
+
+            let mut samples = 0;
+            while vm.voices.iter().any(|v| v.get_note() == 60 && v.is_active()) {
+                vm.process(&mut buffer);
+                samples += buffer.len();
+            }
+            samples
+        };
+
+        let released = release_samples(None);
+        let choked = release_samples(Some(ChokePolicy::FastRelease));
+        let hard = release_samples(Some(ChokePolicy::Hard));
+        assert!(choked * 8 < released, "{choked} vs {released}");
+        assert!(choked > hard, "{choked} vs {hard}");
+    }
+
+    #[test]
+    fn test_release_all_releases_every_held_voice() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 4);
+        for note in [48, 60, 67] {
+            vm.note_on(note, 1.0);
+        }
+        let mut buffer = [0.0; 64];
+        vm.process(&mut buffer);
+
+        vm.release_all();
+        assert!(vm.get_active_notes().is_empty());
+        assert_eq!(vm.releasing_voice_count(), 3, "Should release, not cut");
+
+        // A late note-off for a released note doesn't touch a new one
+        vm.note_on(60, 1.0);
+        vm.note_off(48);
+        assert_eq!(vm.get_active_notes(), vec![60]);
+    }
+
+    #[test]
+    fn test_tail_covers_longest_release() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 4);
+        assert_eq!(vm.tail_samples(), Some(0), "Nothing playing");
+
+        vm.note_on(60, 1.0);
+        vm.note_on(64, 1.0);
+        let mut buffer = [0.0; 64];
+        vm.process(&mut buffer);
+        assert_eq!(vm.tail_samples(), None, "Held notes keep going");
+
+        vm.note_off(60);
+        assert_eq!(vm.tail_samples(), None);
+
+        vm.note_off(64);
+        let tail = vm.tail_samples().expect("Every note released");
+        assert!(tail > 0);
+
+        let mut rest = vec![0.0; tail as usize];
+        vm.process(&mut rest);
+        assert_eq!(vm.tail_samples(), Some(0));
+        vm.process(&mut buffer[..1]);
+        assert_eq!(vm.active_voice_count(), 0, "The tail was long enough");
+    }
+
+    #[test]
+    fn test_retrigger_restarts_stuck_timeout() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 4);
+        vm.set_stuck_timeout_ms(10.0);
+        vm.note_on(60, 1.0);
+
+        let mut buffer = [0.0; 400];
+        vm.process(&mut buffer);
+        vm.note_on(60, 1.0);
+        vm.process(&mut buffer);
+        vm.release_stuck_voices();
+
+        assert_eq!(vm.get_active_notes(), vec![60]);
+        assert_eq!(vm.stuck_release_count(), 0);
+    }
+
+    #[test]
+    fn test_note_names() {
+        assert_eq!(note_name(60), "C4");
+        assert_eq!(note_name(69), "A4");
+        assert_eq!(note_name(0), "C-1");
+        assert_eq!(note_name(127), "G9");
+    }
+
+    #[test]
+    fn test_release_time_scale() {
+        assert!((release_time_scale(0.5, 1.0) - 1.0).abs() < 1e-6);
+        assert!((release_time_scale(1.0, 1.0) - 0.5).abs() < 1e-6);
+        assert!((release_time_scale(0.0, 1.0) - 2.0).abs() < 1e-6);
+        assert!((release_time_scale(1.0, -1.0) - 2.0).abs() < 1e-6);
+        assert!((release_time_scale(0.0, 0.0) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_fast_release_velocity_shortens_release() {
+        let mut slow = VoiceManager::new(SAMPLE_RATE, 1);
+        let mut fast = VoiceManager::new(SAMPLE_RATE, 1);
+        for vm in [&mut slow, &mut fast] {
+            vm.set_params(&VoiceParams {
+                attack_ms: 0.0,
+                release_ms: 100.0,
+                ..VoiceParams::default()
+            });
+            vm.set_release_velocity_amount(1.0);
+            vm.note_on(60, 1.0);
+        }
+
+        slow.note_off_with_velocity(60, 0.5);
+        fast.note_off_with_velocity(60, 1.0);
 
-        let notes = vm.get_active_notes();
-        assert!(notes.contains(&60), "Note 60 should still be active");
-        assert!(!notes.contains(&62), "Note 62 (releasing) should be stolen");
-        assert!(notes.contains(&67), "Note 67 should be active");
+        // 60 ms: past the fast (50 ms) release, inside the normal (100 ms) one
+        let mut buffer = [0.0; 2646];
+        slow.process(&mut buffer);
+        fast.process(&mut buffer);
+        assert_eq!(slow.releasing_voice_count(), 1);
+        assert_eq!(fast.active_voice_count(), 0);
     }
 
     #[test]
@@ -661,17 +3648,10 @@ mod tests {
         // Generate 1 second of audio
         let samples: Vec<f32> = (0..44100).map(|_| voice.process()).collect();
 
-        // Count zero crossings to verify frequency
-        let zero_crossings = samples
-            .windows(2)
-            .filter(|w| (w[0] < 0.0 && w[1] >= 0.0) || (w[0] >= 0.0 && w[1] < 0.0))
-            .count();
-
-        // For 440 Hz, expect ~880 zero crossings (2 per cycle)
+        let frequency = estimate_frequency(&samples, SAMPLE_RATE).unwrap();
         assert!(
-            (zero_crossings as i32 - 880).abs() < 10,
-            "Expected ~880 zero crossings for A4, got {}",
-            zero_crossings
+            (frequency - 440.0).abs() < 0.1,
+            "Expected 440 Hz for A4, got {frequency}"
         );
     }
 
@@ -925,4 +3905,803 @@ mod tests {
         // E should be releasing (not in active notes)
         assert!(!notes.contains(&64), "E should be releasing");
     }
+
+    #[test]
+    fn test_karplus_strong_engine_produces_audio() {
+        let mut voice = Voice::new(SAMPLE_RATE);
+        voice.set_engine(VoiceEngine::KarplusStrong);
+        voice.note_on(57, 1.0);
+
+        let samples: Vec<f32> = (0..4410).map(|_| voice.process()).collect();
+
+        assert!(
+            samples.iter().any(|&s| s.abs() > 0.01),
+            "Plucked string voice should produce audio"
+        );
+        assert!(samples.iter().all(|s| s.is_finite()));
+    }
+
+    #[test]
+    fn test_sampler_engine_plays_loaded_sample() {
+        let mut voice = Voice::new(SAMPLE_RATE);
+        voice.set_engine(VoiceEngine::Sampler);
+
+        // No sample loaded: silence
+        voice.note_on(60, 1.0);
+        assert!((0..256).all(|_| voice.process() == 0.0));
+
+        let sample = SampleData::new(vec![0.5; 4410], SAMPLE_RATE);
+        voice.set_sample(Some(Arc::new(sample)));
+        voice.set_sample_loop(true, 0.0, 1.0);
+        voice.note_on(72, 1.0);
+
+        let samples: Vec<f32> = (0..8820).map(|_| voice.process()).collect();
+        assert!(
+            samples[4410..].iter().any(|&s| s.abs() > 0.01),
+            "Looped sample should keep playing"
+        );
+    }
+
+    #[test]
+    fn test_additive_engine_silent_until_partials_set() {
+        let mut voice = Voice::new(SAMPLE_RATE);
+        voice.set_engine(VoiceEngine::Additive);
+        voice.note_on(60, 1.0);
+        assert!((0..256).all(|_| voice.process() == 0.0));
+
+        voice.set_additive_gains(crate::additive::partial_gains(&[0.2; NUM_PARTIALS], -6.0));
+        voice.note_on(60, 1.0);
+        let samples: Vec<f32> = (0..4410).map(|_| voice.process()).collect();
+        assert!(samples.iter().any(|&s| s.abs() > 0.01));
+        assert!(samples.iter().all(|s| s.is_finite() && s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_engine_switch_mid_note_keeps_the_note_sounding() {
+        let mut voice = Voice::new(SAMPLE_RATE);
+        voice.set_engine(VoiceEngine::Additive); // Silent without partials
+        voice.note_on(57, 1.0);
+        assert!((0..441).all(|_| voice.process() == 0.0));
+
+        // The held string is plucked at the note, without a new note on
+        voice.set_engine(VoiceEngine::KarplusStrong);
+        let samples: Vec<f32> = (0..4410).map(|_| voice.process()).collect();
+        assert!(samples.iter().any(|&s| s.abs() > 0.01));
+        let harmonic = estimate_frequency(&samples, SAMPLE_RATE).unwrap() / 220.0;
+        assert!((harmonic - harmonic.round()).abs() < 0.05, "Harmonic {harmonic} of 220 Hz");
+
+        // An idle voice only switches, and is plucked by its next note
+        voice.reset();
+        voice.set_engine(VoiceEngine::Additive);
+        voice.set_engine(VoiceEngine::KarplusStrong);
+        assert!((0..441).all(|_| voice.process() == 0.0));
+    }
+
+    #[test]
+    fn test_instant_attack_fades_in_without_clicks() {
+        let mut voice = Voice::new(SAMPLE_RATE);
+        voice.set_waveform(WaveformType::Sine);
+        voice.set_free_running_phase(true);
+        voice.set_envelope_attack_ms(0.0);
+        voice.set_envelope_decay_ms(0.0);
+        voice.set_envelope_sustain_level(1.0);
+        voice.set_envelope_release_ms(0.0);
+
+        // Leave the phase a quarter cycle in (the top of the sine), then let the
+        // voice go idle
+        voice.note_on(57, 1.0);
+        for _ in 0..50 {
+            voice.process();
+        }
+        voice.note_off();
+        while voice.get_state() != VoiceState::Idle {
+            voice.process();
+        }
+
+        // The next note starts near full scale, but ramps up from silence
+        voice.note_on(57, 1.0);
+        let mut previous = 0.0;
+        for _ in 0..1000 {
+            let sample = voice.process();
+            assert!((sample - previous).abs() < 0.05, "Jump from {previous} to {sample}");
+            previous = sample;
+        }
+        assert!(previous.abs() > 0.0, "The note should still be sounding");
+    }
+
+    #[test]
+    fn test_start_phase_sets_attack() {
+        let render = |voice: &mut Voice| -> Vec<f32> {
+            voice.note_on(69, 1.0);
+            (0..256).map(|_| voice.process()).collect()
+        };
+
+        // Half a cycle in, a sine is the same note upside down
+        let mut zero = Voice::new(SAMPLE_RATE);
+        let mut half = Voice::new(SAMPLE_RATE);
+        half.set_start_phase_degrees(180.0);
+        for (a, b) in render(&mut zero).iter().zip(render(&mut half)) {
+            assert!((a + b).abs() < 1e-4, "{a} vs {b}");
+        }
+
+        // Random phases differ from note to note and from voice to voice
+        let mut first = Voice::with_seed(SAMPLE_RATE, 0);
+        let mut second = Voice::with_seed(SAMPLE_RATE, 1);
+        first.set_random_phase(true);
+        second.set_random_phase(true);
+        let note_a = render(&mut first);
+        let note_b = render(&mut first);
+        assert_ne!(note_a, note_b);
+        assert_ne!(note_a, render(&mut second));
+    }
+
+    #[test]
+    fn test_filter_envelope_sweeps_cutoff() {
+        // Peak level of a sawtooth through a 50 Hz low-pass, with the filter
+        // envelope holding the cutoff `octaves` away
+        let peak_with_envelope = |octaves: f32| {
+            let mut voice = Voice::new(SAMPLE_RATE);
+            voice.set_waveform(WaveformType::Sawtooth);
+            voice.set_envelope_attack_ms(0.0);
+            voice.set_envelope_sustain_level(1.0);
+            voice.set_filter_enabled(true);
+            voice.set_filter_cutoff_hz(50.0);
+            voice.set_filter_envelope(FilterEnvelopeSettings {
+                attack_ms: 0.0,
+                sustain_level: 1.0,
+                amount_octaves: octaves,
+                ..FilterEnvelopeSettings::default()
+            });
+            voice.note_on(81, 1.0);
+            (0..4410)
+                .map(|_| voice.process())
+                .skip(441)
+                .fold(0.0f32, |peak, sample| peak.max(sample.abs()))
+        };
+
+        let closed = peak_with_envelope(0.0);
+        let opened = peak_with_envelope(8.0);
+        let inverted = peak_with_envelope(-4.0);
+        assert!(opened > closed * 4.0, "Opened {opened}, closed {closed}");
+        assert!(inverted < closed, "Inverted {inverted}, closed {closed}");
+    }
+
+    #[cfg(feature = "debug-outputs")]
+    #[test]
+    fn test_probe_taps_follow_the_newest_voice() {
+        use crate::probe::ProbeTaps;
+
+        // A low-pass well below the note takes the edges off a sawtooth
+        let mut voice = Voice::new(SAMPLE_RATE);
+        voice.set_waveform(WaveformType::Sawtooth);
+        voice.set_filter_enabled(true);
+        voice.set_filter_cutoff_hz(100.0);
+        voice.note_on(69, 1.0);
+        let mut filtered = false;
+        for _ in 0..441 {
+            let output = voice.process();
+            let taps = voice.probe();
+            assert_eq!(taps.voice, output);
+            filtered |= (taps.pre_filter - taps.post_filter).abs() > 0.5;
+        }
+        assert!(filtered);
+
+        // The octave above started last, so its pitch is on the probe
+        let mut vm = VoiceManager::new(SAMPLE_RATE, MAX_VOICES);
+        assert_eq!(vm.probe(), ProbeTaps::default());
+        vm.note_on(60, 1.0);
+        vm.note_on(72, 1.0);
+        let mut previous = 0.0;
+        let mut rising_crossings = 0;
+        for _ in 0..4410 {
+            vm.process(&mut [0.0]);
+            let pre_filter = vm.probe().pre_filter;
+            if previous < 0.0 && pre_filter >= 0.0 {
+                rising_crossings += 1;
+            }
+            previous = pre_filter;
+        }
+        assert!((51..=53).contains(&rising_crossings), "{rising_crossings}");
+    }
+
+    #[test]
+    fn test_control_rate_follows_audio_rate_sweep() {
+        // A filter envelope sweep rendered with modulation updated every
+        // sample and every `divisor` samples
+        let render = |divisor: u32| {
+            let mut voice = Voice::new(SAMPLE_RATE);
+            voice.set_waveform(WaveformType::Sawtooth);
+            voice.set_envelope_attack_ms(0.0);
+            voice.set_envelope_sustain_level(1.0);
+            voice.set_filter_enabled(true);
+            voice.set_filter_cutoff_hz(100.0);
+            voice.set_filter_envelope(FilterEnvelopeSettings {
+                attack_ms: 0.0,
+                decay_ms: 50.0,
+                sustain_level: 0.0,
+                amount_octaves: 6.0,
+                ..FilterEnvelopeSettings::default()
+            });
+            voice.set_control_divisor(divisor);
+            voice.note_on(57, 1.0);
+            (0..4410).map(|_| voice.process()).collect::<Vec<f32>>()
+        };
+
+        let reference = render(1);
+        for divisor in [8, DEFAULT_CONTROL_DIVISOR] {
+            let output = render(divisor);
+            let error = reference
+                .iter()
+                .zip(&output)
+                .fold(0.0f32, |worst, (a, b)| worst.max((a - b).abs()));
+            assert!(error < 0.05, "Divisor {divisor}: {error}");
+        }
+    }
+
+    #[test]
+    fn test_control_divisor_is_clamped() {
+        let mut voice = Voice::new(SAMPLE_RATE);
+        voice.set_control_divisor(0);
+        assert_eq!(voice.control_divisor, 1);
+        voice.set_control_divisor(1000);
+        assert_eq!(voice.control_divisor, MAX_CONTROL_DIVISOR);
+    }
+
+    #[test]
+    fn test_gated_input_replaces_engine() {
+        let mut voice = Voice::new(SAMPLE_RATE);
+        voice.set_waveform(WaveformType::Square);
+        voice.set_envelope_attack_ms(0.1);
+        voice.set_envelope_sustain_level(1.0);
+        voice.set_input_mix(1.0);
+        voice.set_input(0.5);
+
+        // Silent until a note opens the envelope
+        assert!(voice.process().abs() < f32::EPSILON);
+
+        voice.note_on(60, 1.0);
+        let samples: Vec<f32> = (0..1000).map(|_| voice.process()).collect();
+        assert!((samples[999] - 0.5).abs() < 1e-3, "Only the input should be heard");
+    }
+
+    #[test]
+    fn test_voice_manager_engine_switch() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 4);
+        vm.set_params(&VoiceParams {
+            engine: VoiceEngine::KarplusStrong,
+            string_damping: 0.8,
+            string_decay_ms: 1000.0,
+            ..VoiceParams::default()
+        });
+
+        vm.note_on(48, 1.0);
+        vm.note_on(55, 1.0);
+
+        let mut buffer = vec![0.0; 512];
+        vm.process(&mut buffer);
+
+        assert_eq!(vm.active_voice_count(), 2);
+        assert!(buffer.iter().any(|&s| s.abs() > 0.01));
+    }
+
+    #[test]
+    fn test_per_voice_drive_changes_output() {
+        let mut clean = Voice::new(SAMPLE_RATE);
+        let mut driven = Voice::new(SAMPLE_RATE);
+        driven.set_waveshaper(
+            true,
+            WaveshaperSettings {
+                drive_db: 24.0,
+                ..WaveshaperSettings::default()
+            },
+        );
+
+        clean.note_on(57, 1.0);
+        driven.note_on(57, 1.0);
+
+        let clean_samples: Vec<f32> = (0..2000).map(|_| clean.process()).collect();
+        let driven_samples: Vec<f32> = (0..2000).map(|_| driven.process()).collect();
+
+        let diff: f32 = clean_samples
+            .iter()
+            .zip(&driven_samples)
+            .map(|(a, b)| (a - b).abs())
+            .sum();
+        assert!(diff > 10.0, "Drive should audibly change the voice");
+        assert!(driven_samples.iter().all(|s| s.is_finite() && s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_limiter_holds_resonant_filter_under_ceiling() {
+        let mut voice = Voice::new(SAMPLE_RATE);
+        voice.set_waveform(WaveformType::Sawtooth);
+        voice.set_envelope_attack_ms(0.0);
+        voice.set_envelope_sustain_level(1.0);
+        voice.set_filter_enabled(true);
+        voice.set_filter_cutoff_hz(800.0);
+        voice.set_filter_resonance(0.98);
+        voice.set_limiter(true, -12.0);
+        voice.note_on(45, 1.0);
+
+        let ceiling = 10.0_f32.powf(-12.0 / 20.0);
+        let peak = (0..4800).map(|_| voice.process().abs()).fold(0.0, f32::max);
+        assert!(peak <= ceiling * 1.0001, "Peak {peak} over {ceiling}");
+        assert!(voice.take_limiter_reduction_db() > 0.0);
+
+        // Bypassed, the resonance rings well past it
+        voice.set_limiter(false, -12.0);
+        let peak = (0..4800).map(|_| voice.process().abs()).fold(0.0, f32::max);
+        assert!(peak > ceiling * 1.5, "Peak {peak}");
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Short test lengths
+    fn test_glide_slides_between_notes() {
+        let mut voice = Voice::new(SAMPLE_RATE);
+        voice.set_glide_ms(100.0);
+
+        // First note has nothing to glide from
+        voice.note_on(57, 1.0);
+        voice.process();
+        assert!((voice.get_pitch() - 57.0).abs() < 1e-4);
+
+        voice.note_on(69, 1.0);
+        for _ in 0..(SAMPLE_RATE as usize / 20) {
+            voice.process();
+        }
+        let halfway = voice.get_pitch();
+        assert!(
+            (halfway - 63.0).abs() < 0.1,
+            "Expected ~63 halfway through a 100 ms glide, got {halfway}"
+        );
+
+        for _ in 0..(SAMPLE_RATE as usize / 20 + 10) {
+            voice.process();
+        }
+        assert!((voice.get_pitch() - 69.0).abs() < 1e-4, "Glide should land on the target note");
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)] // Short test lengths
+    fn test_constant_rate_glide_scales_with_interval() {
+        let glide_samples = |interval: u8| {
+            let mut voice = Voice::new(SAMPLE_RATE);
+            voice.set_glide_ms(100.0);
+            voice.set_glide_mode(GlideMode::ConstantRate);
+            voice.note_on(48, 1.0);
+            voice.process();
+            voice.note_on(48 + interval, 1.0);
+
+            let target = f32::from(48 + interval);
+            let mut samples = 0;
+            while (voice.get_pitch() - target).abs() > 1e-4 {
+                voice.process();
+                samples += 1;
+            }
+            samples
+        };
+
+        // 100 ms per octave
+        let octave = glide_samples(12);
+        let two_octaves = glide_samples(24);
+        let fifth = glide_samples(7);
+        assert!((octave as f32 - SAMPLE_RATE / 10.0).abs() <= 2.0, "octave took {octave}");
+        assert!((two_octaves as f32 - 2.0 * octave as f32).abs() <= 2.0);
+        assert!((fifth as f32 - octave as f32 * 7.0 / 12.0).abs() <= 2.0);
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Short test lengths
+    fn test_exponential_glide_leads_then_lands() {
+        let mut voice = Voice::new(SAMPLE_RATE);
+        voice.set_glide_ms(100.0);
+        voice.set_glide_curve(GlideCurve::Exponential);
+        voice.note_on(57, 1.0);
+        voice.process();
+
+        voice.note_on(69, 1.0);
+        for _ in 0..(SAMPLE_RATE as usize / 20) {
+            voice.process();
+        }
+        let halfway = voice.get_pitch();
+        assert!(
+            halfway > 67.0,
+            "Exponential glide should be most of the way at half time, got {halfway}"
+        );
+        assert!(halfway < 69.0);
+
+        for _ in 0..(SAMPLE_RATE as usize / 20 + 10) {
+            voice.process();
+        }
+        assert!((voice.get_pitch() - 69.0).abs() < 1e-4, "Glide should land on the target note");
+    }
+
+    #[test]
+    fn test_glide_curves_span_unit_range() {
+        for curve in GlideCurve::ALL {
+            assert!(curve.shape(0.0).abs() < 1e-6);
+            assert!((curve.shape(1.0) - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Short test lengths
+    fn test_chromatic_glissando_steps_through_semitones() {
+        let mut voice = Voice::new(SAMPLE_RATE);
+        voice.set_glide_ms(100.0);
+        voice.set_glissando(Glissando::Chromatic, 0, FULL_MASK);
+        voice.note_on(60, 1.0);
+        voice.process();
+
+        // Up a fifth: every pitch is a whole note, passing through each semitone
+        voice.note_on(67, 1.0);
+        let mut heard = Vec::new();
+        for _ in 0..(SAMPLE_RATE as usize / 10 + 10) {
+            voice.process();
+            let pitch = voice.get_pitch();
+            assert!((pitch - pitch.round()).abs() < 1e-4, "Off-note pitch {pitch}");
+            if heard.last() != Some(&pitch) {
+                heard.push(pitch);
+            }
+        }
+        assert_eq!(heard, [60.0, 61.0, 62.0, 63.0, 64.0, 65.0, 66.0, 67.0]);
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Short test lengths
+    fn test_scale_glissando_plays_only_degrees() {
+        let mut voice = Voice::new(SAMPLE_RATE);
+        voice.set_glide_ms(100.0);
+        voice.set_glissando(Glissando::Scale, 0, Scale::Major.mask(0));
+        voice.note_on(72, 1.0);
+        voice.process();
+
+        // Down an octave through C major
+        voice.note_on(60, 1.0);
+        let mut heard = Vec::new();
+        for _ in 0..(SAMPLE_RATE as usize / 10 + 10) {
+            voice.process();
+            let pitch = voice.get_pitch();
+            if heard.last() != Some(&pitch) {
+                heard.push(pitch);
+            }
+        }
+        assert_eq!(heard, [72.0, 71.0, 69.0, 67.0, 65.0, 64.0, 62.0, 60.0]);
+    }
+
+    #[test]
+    fn test_no_glide_jumps_immediately() {
+        let mut voice = Voice::new(SAMPLE_RATE);
+        voice.note_on(57, 1.0);
+        voice.process();
+        voice.note_on(69, 1.0);
+        voice.process();
+        assert!((voice.get_pitch() - 69.0).abs() < 1e-4);
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Short test lengths
+    fn test_pitch_bend_slews_to_range() {
+        let mut voice = Voice::new(SAMPLE_RATE);
+        voice.note_on(69, 1.0);
+        let bent = |voice: &mut Voice, samples: usize| {
+            for _ in 0..samples {
+                voice.process();
+            }
+            (voice.oscillator.frequency() / 440.0).log2() * 12.0
+        };
+
+        // Full bend up reaches the range, but not in one step
+        voice.set_pitch_bend(1.0);
+        let first = bent(&mut voice, 1);
+        assert!(first > 0.0 && first < 0.1, "Bend should slew, got {first}");
+        let settled = bent(&mut voice, SAMPLE_RATE as usize / 100);
+        assert!((settled - DEFAULT_BEND_RANGE).abs() < 1e-3, "{settled}");
+
+        voice.set_bend_range(12.0);
+        voice.set_pitch_bend(-1.0);
+        let settled = bent(&mut voice, SAMPLE_RATE as usize / 100);
+        assert!((settled + 12.0).abs() < 1e-3, "{settled}");
+
+        // New notes start at the current bend
+        voice.note_on(69, 1.0);
+        let started = bent(&mut voice, 1);
+        assert!((started + 12.0).abs() < 1e-3, "{started}");
+    }
+
+    #[test]
+    fn test_poly_offsets_reach_tagged_voices_only() {
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 2);
+        vm.note_on_tagged(69, 1.0, VoiceTag::new(69, 0, Some(1)));
+        vm.note_on_tagged(57, 1.0, VoiceTag::new(57, 0, Some(2)));
+        vm.set_poly_offsets(
+            1,
+            ModOffsets {
+                pitch_semitones: 12.0,
+                ..ModOffsets::default()
+            },
+        );
+        let mut buffer = [0.0; 64];
+        vm.process(&mut buffer);
+
+        let frequency = |vm: &VoiceManager, note| {
+            let voice = vm.voices.iter().find(|voice| voice.get_note() == note).unwrap();
+            voice.oscillator.frequency()
+        };
+        assert!((frequency(&vm, 69) - 880.0).abs() < 0.1);
+        assert!((frequency(&vm, 57) - 220.0).abs() < 0.1);
+
+        // A new note on the voice starts unmodulated
+        vm.note_on_tagged(69, 1.0, VoiceTag::new(69, 0, Some(3)));
+        vm.process(&mut buffer);
+        assert!((frequency(&vm, 69) - 440.0).abs() < 0.1);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // Unpanned channels are identical
+    fn test_note_expressions_pan_scale_and_tune_voices() {
+        use crate::note_expression::NoteExpression;
+
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 2);
+        vm.set_params(&VoiceParams {
+            attack_ms: 0.0,
+            ..VoiceParams::default()
+        });
+        vm.note_on_tagged(69, 1.0, VoiceTag::new(69, 0, Some(5)));
+        let (mut left, mut right) = ([0.0; 256], [0.0; 256]);
+        vm.process_stereo(&mut left, &mut right);
+        assert_eq!(left, right, "Unpanned voices play equally in both channels");
+
+        let mut expression = ExpressionValues::default();
+        expression.apply(NoteExpression::Pan(1.0));
+        expression.apply(NoteExpression::Tuning(12.0));
+        vm.set_expression(5, expression);
+        vm.process_stereo(&mut left, &mut right);
+        vm.process_stereo(&mut left, &mut right);
+        assert!(left.iter().all(|sample| *sample == 0.0));
+        assert!(right.iter().any(|sample| sample.abs() > 0.1));
+        assert!((vm.voices[0].oscillator.frequency() - 880.0).abs() < 0.1);
+
+        let peak = |buffer: &[f32]| buffer.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        let full = peak(&right);
+        expression.apply(NoteExpression::Volume(0.5));
+        vm.set_expression(5, expression);
+        vm.process_stereo(&mut left, &mut right);
+        vm.process_stereo(&mut left, &mut right);
+        assert!((peak(&right) - full * 0.5).abs() < 0.05, "{} vs {full}", peak(&right));
+    }
+
+    #[test]
+    fn test_tuning_offsets() {
+        assert!(tuning_offset_semitones(STANDARD_A4_HZ, 0.0).abs() < 1e-6);
+        // Baroque pitch is a semitone down
+        assert!((tuning_offset_semitones(415.305, 0.0) + 1.0).abs() < 1e-3);
+        assert!((tuning_offset_semitones(STANDARD_A4_HZ, 25.0) - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_tuning_applies_to_every_note() {
+        let mut voice = Voice::new(SAMPLE_RATE);
+        voice.set_tuning(432.0, 0.0);
+        voice.note_on(69, 1.0);
+        voice.process();
+        assert!((voice.oscillator.frequency() - 432.0).abs() < 0.01);
+
+        voice.note_on(81, 1.0);
+        voice.process();
+        assert!((voice.oscillator.frequency() - 864.0).abs() < 0.02);
+
+        // Fine tuning stacks on the reference, gliding there on a sounding note
+        voice.set_tuning(432.0, 100.0);
+        voice.process();
+        assert!((voice.oscillator.frequency() - 864.0).abs() < 0.1);
+        for _ in 0..slew_samples(TUNING_SLEW_MS) {
+            voice.process();
+        }
+        assert!((voice.oscillator.frequency() - 864.0 * 2.0f32.powf(1.0 / 12.0)).abs() < 0.05);
+    }
+
+    /// Samples a slew of `slew_ms` takes, with a sample to spare
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Short test lengths
+    fn slew_samples(slew_ms: f32) -> usize {
+        (slew_ms / 1000.0 * SAMPLE_RATE) as usize + 1
+    }
+
+    #[test]
+    fn test_cutoff_glides_on_sounding_notes() {
+        let mut voice = Voice::new(SAMPLE_RATE);
+        voice.set_filter_enabled(true);
+        voice.set_filter_cutoff_hz(500.0);
+        voice.note_on(60, 1.0);
+        voice.process();
+        assert!((voice.cutoff_slew.value().exp2() - 500.0).abs() < 0.01);
+
+        // A jump is spread over the slew time, evenly in octaves
+        voice.set_filter_cutoff_hz(2000.0);
+        for _ in 0..slew_samples(CUTOFF_SLEW_MS) / 2 {
+            voice.process();
+        }
+        assert!((voice.cutoff_slew.value().exp2() - 1000.0).abs() < 20.0);
+        for _ in 0..slew_samples(CUTOFF_SLEW_MS) {
+            voice.process();
+        }
+        assert!((voice.cutoff_slew.value().exp2() - 2000.0).abs() < 0.1);
+
+        // The next note starts at the new cutoff
+        voice.set_filter_cutoff_hz(300.0);
+        voice.note_on(62, 1.0);
+        voice.process();
+        assert!((voice.cutoff_slew.value().exp2() - 300.0).abs() < 0.01);
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Short test lengths
+    fn test_mod_matrix_pitch_routing() {
+        use crate::modulation::{ModDestination, ModSlot, ModSource};
+
+        let mut voice = Voice::new(SAMPLE_RATE);
+        let mut matrix = ModMatrix::default();
+        matrix.slots[0] = ModSlot {
+            source: ModSource::StepSequencer,
+            destination: ModDestination::Pitch,
+            amount: 0.5,
+        };
+        voice.set_mod_matrix(matrix);
+        voice.set_mod_sources(ModSourceValues {
+            step_sequencer: 1.0,
+            ..ModSourceValues::default()
+        });
+        voice.note_on(57, 1.0); // A3 (220 Hz), +12 semitones = 440 Hz
+
+        let samples: Vec<f32> = (0..SAMPLE_RATE as usize).map(|_| voice.process()).collect();
+        let frequency = estimate_frequency(&samples, SAMPLE_RATE).unwrap();
+        assert!((frequency - 440.0).abs() < 0.1, "Expected 440 Hz, got {frequency}");
+    }
+
+    #[test]
+    fn test_matrix_range_covers_every_sounding_voice() {
+        use crate::modulation::{ModDestination, ModSlot, ModSource};
+
+        let mut manager = VoiceManager::new(SAMPLE_RATE, 4);
+        assert_eq!(manager.matrix_range(), None);
+
+        let mut matrix = ModMatrix::default();
+        matrix.slots[0] = ModSlot {
+            source: ModSource::Random,
+            destination: ModDestination::Cutoff,
+            amount: 1.0,
+        };
+        manager.set_params(&VoiceParams {
+            mod_matrix: matrix,
+            ..VoiceParams::default()
+        });
+        manager.note_on(60, 1.0);
+        manager.note_on(64, 1.0);
+        manager.note_on(67, 1.0);
+        for voice in &mut manager.voices {
+            voice.process();
+        }
+
+        // Every voice's own random cutoff lies within the range
+        let range = manager.matrix_range().unwrap();
+        let cutoffs: Vec<f32> = manager
+            .voices
+            .iter()
+            .filter(|v| v.get_state() != VoiceState::Idle)
+            .map(|v| v.matrix_offsets().cutoff_octaves)
+            .collect();
+        assert_eq!(cutoffs.len(), 3);
+        for cutoff in cutoffs {
+            assert!(range.low.cutoff_octaves <= cutoff && cutoff <= range.high.cutoff_octaves);
+        }
+        assert!(range.high.cutoff_octaves > range.low.cutoff_octaves);
+
+        manager.reset();
+        assert_eq!(manager.matrix_range(), None);
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Short test lengths
+    fn test_pitch_source_tracks_cutoff_through_glide() {
+        use crate::modulation::{ModDestination, ModSlot, ModSource};
+
+        let mut voice = Voice::new(SAMPLE_RATE);
+        let mut matrix = ModMatrix::default();
+        matrix.slots[0] = ModSlot {
+            source: ModSource::Pitch,
+            destination: ModDestination::Cutoff,
+            amount: 1.0,
+        };
+        voice.set_mod_matrix(matrix);
+        voice.set_filter_enabled(true);
+        voice.set_filter_cutoff_hz(500.0);
+        voice.set_glide_ms(100.0);
+
+        // Full tracking: an octave above middle C doubles the cutoff
+        voice.note_on(72, 1.0);
+        voice.process();
+        assert!((voice.filter.cutoff_hz() - 1000.0).abs() < 1.0);
+
+        // Halfway through a glide down to middle C, not at the new key yet
+        voice.note_on(60, 1.0);
+        for _ in 0..(SAMPLE_RATE as usize / 20) {
+            voice.process();
+        }
+        let halfway = 500.0 * 0.5_f32.exp2();
+        assert!(
+            (voice.filter.cutoff_hz() - halfway).abs() < 10.0,
+            "Expected ~{halfway} Hz mid-glide, got {}",
+            voice.filter.cutoff_hz()
+        );
+
+        // Pitch bend counts too
+        voice.set_bend_range(12.0);
+        voice.set_pitch_bend(1.0);
+        for _ in 0..SAMPLE_RATE as usize / 5 {
+            voice.process();
+        }
+        assert!((voice.filter.cutoff_hz() - 1000.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_stuck_release_lands_on_the_same_sample_at_any_block_size() {
+        let render = |block_size: usize| {
+            let mut vm = VoiceManager::new(SAMPLE_RATE, 4);
+            vm.set_stuck_timeout_ms(10.0); // 441 samples
+            vm.set_params(&VoiceParams {
+                release_ms: 20.0,
+                ..VoiceParams::default()
+            });
+            vm.note_on(60, 1.0);
+
+            let mut output = vec![0.0; 4096];
+            for block in output.chunks_mut(block_size) {
+                vm.process(block);
+            }
+            assert_eq!(vm.stuck_release_count(), 1);
+            output
+        };
+
+        let reference = render(1024);
+        for block_size in [1, 17, 64] {
+            assert!(render(block_size) == reference, "Block size {block_size} renders differently");
+        }
+    }
+
+    #[test]
+    fn test_random_cutoff_differs_per_voice() {
+        use crate::modulation::{ModDestination, ModSlot, ModSource};
+
+        let mut manager = VoiceManager::new(SAMPLE_RATE, 2);
+        let mut matrix = ModMatrix::default();
+        matrix.slots[0] = ModSlot {
+            source: ModSource::Random,
+            destination: ModDestination::Cutoff,
+            amount: 1.0,
+        };
+        manager.set_params(&VoiceParams {
+            waveform: WaveformType::Sawtooth,
+            filter_enabled: true,
+            filter_cutoff_hz: 500.0,
+            mod_matrix: matrix,
+            ..VoiceParams::default()
+        });
+
+        manager.note_on(60, 1.0);
+        manager.note_on(60 + 12, 1.0);
+
+        // Each voice's random value is drawn from its own seed
+        let cutoffs: Vec<f32> = manager
+            .voices
+            .iter_mut()
+            .map(|voice| {
+                voice.process();
+                voice.filter.cutoff_hz()
+            })
+            .collect();
+        assert!(
+            (cutoffs[0] - cutoffs[1]).abs() > 1.0,
+            "Voices should get different random cutoffs: {cutoffs:?}"
+        );
+    }
 }
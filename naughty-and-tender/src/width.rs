@@ -0,0 +1,347 @@
+//! Stereo width limiter and mono check for Naughty and Tender
+//!
+//! Voices panned apart, the phaser and detuned layers can leave the two sides
+//! out of phase, and then the mix thins out or cancels when it's summed to
+//! mono (a club system, a phone speaker). The width limiter watches the master
+//! in mid/side form and turns the side down whenever the correlation would
+//! fall below a floor, so the mono sum never loses more than the floor allows.
+//! It narrows at once and widens back slowly, like a compressor's attack and
+//! release.
+//!
+//! Correlation here is `(M² - S²) / (M² + S²)` over a short window: +1 for
+//! mono, 0 for unrelated sides, -1 for sides in antiphase. It's the usual
+//! correlation coefficient normalized by the sides' mean power, which keeps
+//! the side gain that meets a floor a closed form.
+//!
+//! The mono check folds the output to mid so the mix can be auditioned the way
+//! a mono system plays it. It's an editor switch and isn't saved.
+//!
+//! # References
+//! - Mid/side: `M = (L + R)/2`, `S = (L - R)/2`, `L = M + S`, `R = M - S`
+//! - Phase correlation meters (-1 to +1) on mastering consoles
+//! - Side gain for a floor `r`: `g² ≤ M²(1 - r) / (S²(1 + r))`
+
+#![allow(dead_code)] // Some methods may not be used initially
+
+use shared_core::mid_side;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// Default correlation floor: the side never outweighs the mid
+pub const DEFAULT_MIN_CORRELATION: f32 = 0.0;
+
+/// Window of the mid and side energies, in milliseconds
+const ENERGY_WINDOW_MS: f32 = 50.0;
+
+/// Time for the side to widen back, in milliseconds
+const RELEASE_MS: f32 = 300.0;
+
+/// Side energy below which the mix counts as mono
+const SILENCE: f32 = 1e-12;
+
+/// Fold a stereo frame to its mid on both sides
+#[inline]
+#[must_use]
+pub fn fold_to_mono(frame: [f32; 2]) -> [f32; 2] {
+    let (mid, _) = mid_side::encode(frame[0], frame[1]);
+    [mid, mid]
+}
+
+/// Mid/side width limiter for the master
+///
+/// # Real-time Safety
+/// - A few floats of state, no allocations
+///
+/// # Example
+/// ```
+/// use naughty_and_tender::width::WidthLimiter;
+///
+/// let mut limiter = WidthLimiter::new(48000.0);
+/// limiter.set_enabled(true);
+/// limiter.set_min_correlation(0.0);
+///
+/// // Antiphase: all side, no mid
+/// for _ in 0..48000 {
+///     limiter.process([0.5, -0.5]);
+/// }
+/// assert!(limiter.side_reduction_db() > 40.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct WidthLimiter {
+    enabled: bool,
+    min_correlation: f32,
+
+    /// Smoothed mid and side energies
+    mid_energy: f32,
+    side_energy: f32,
+
+    /// Gain on the side (1.0 = untouched)
+    side_gain: f32,
+
+    energy_coeff: f32,
+    release_coeff: f32,
+    sample_rate: f32,
+}
+
+impl WidthLimiter {
+    /// Create a limiter, off, at the default floor
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        let mut limiter = Self {
+            enabled: false,
+            min_correlation: DEFAULT_MIN_CORRELATION,
+            mid_energy: 0.0,
+            side_energy: 0.0,
+            side_gain: 1.0,
+            energy_coeff: 1.0,
+            release_coeff: 1.0,
+            sample_rate,
+        };
+        limiter.set_sample_rate(sample_rate);
+        limiter
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.energy_coeff = smoothing_coeff(ENERGY_WINDOW_MS, sample_rate);
+        self.release_coeff = smoothing_coeff(RELEASE_MS, sample_rate);
+    }
+
+    /// Turn the limiting on or off (the correlation is measured either way)
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Set the correlation floor (-1.0 - 1.0; 1.0 folds everything to mono)
+    pub fn set_min_correlation(&mut self, min_correlation: f32) {
+        self.min_correlation = min_correlation.clamp(-1.0, 1.0);
+    }
+
+    /// Process one stereo frame
+    #[inline]
+    pub fn process(&mut self, frame: [f32; 2]) -> [f32; 2] {
+        let (mid, side) = mid_side::encode(frame[0], frame[1]);
+        self.mid_energy += (mid * mid - self.mid_energy) * self.energy_coeff;
+        self.side_energy += (side * side - self.side_energy) * self.energy_coeff;
+
+        let target = if self.enabled {
+            self.allowed_side_gain()
+        } else {
+            1.0
+        };
+        if target < self.side_gain {
+            self.side_gain = target;
+        } else {
+            self.side_gain += (target - self.side_gain) * self.release_coeff;
+        }
+
+        let (left, right) = mid_side::decode(mid, side * self.side_gain);
+        [left, right]
+    }
+
+    /// Correlation of the output over the last window (+1 = mono)
+    #[must_use]
+    pub fn correlation(&self) -> f32 {
+        let side_energy = self.side_energy * self.side_gain * self.side_gain;
+        let total = self.mid_energy + side_energy;
+        if total > SILENCE {
+            (self.mid_energy - side_energy) / total
+        } else {
+            1.0
+        }
+    }
+
+    /// How far the side is turned down, in dB (0 = untouched)
+    #[must_use]
+    pub fn side_reduction_db(&self) -> f32 {
+        -20.0 * self.side_gain.max(1e-6).log10()
+    }
+
+    /// Clear the energies and restore the full width
+    pub fn reset(&mut self) {
+        self.mid_energy = 0.0;
+        self.side_energy = 0.0;
+        self.side_gain = 1.0;
+    }
+
+    /// Largest side gain that keeps the correlation at or above the floor
+    fn allowed_side_gain(&self) -> f32 {
+        if self.side_energy < SILENCE {
+            return 1.0;
+        }
+        let ratio = self.mid_energy * (1.0 - self.min_correlation)
+            / (self.side_energy * (1.0 + self.min_correlation).max(f32::EPSILON));
+        ratio.sqrt().min(1.0)
+    }
+}
+
+/// Stereo readouts shared between the audio thread and the editor, and the
+/// editor's mono check
+///
+/// # Real-time Safety
+/// - Relaxed atomic loads and stores only
+#[derive(Debug)]
+pub struct StereoMonitor {
+    /// Fold the output to mono
+    mono_check: AtomicBool,
+
+    /// Output correlation at the end of the last block (`f32` bits)
+    correlation: AtomicU32,
+
+    /// Width limiter side reduction in the last block, in dB (`f32` bits)
+    side_reduction: AtomicU32,
+}
+
+impl Default for StereoMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StereoMonitor {
+    /// Mono check off, readouts at mono and no reduction
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            mono_check: AtomicBool::new(false),
+            correlation: AtomicU32::new(1.0f32.to_bits()),
+            side_reduction: AtomicU32::new(0),
+        }
+    }
+
+    /// Turn the mono check on or off (editor thread)
+    pub fn set_mono_check(&self, mono: bool) {
+        self.mono_check.store(mono, Ordering::Relaxed);
+    }
+
+    /// Whether the output is folded to mono
+    #[must_use]
+    pub fn mono_check(&self) -> bool {
+        self.mono_check.load(Ordering::Relaxed)
+    }
+
+    /// Publish the limiter's readouts (audio thread, once per block)
+    pub fn publish(&self, limiter: &WidthLimiter) {
+        self.correlation
+            .store(limiter.correlation().to_bits(), Ordering::Relaxed);
+        self.side_reduction
+            .store(limiter.side_reduction_db().to_bits(), Ordering::Relaxed);
+    }
+
+    /// Output correlation (+1 = mono, -1 = antiphase), before any mono check
+    #[must_use]
+    pub fn correlation(&self) -> f32 {
+        f32::from_bits(self.correlation.load(Ordering::Relaxed))
+    }
+
+    /// Width limiter side reduction, in dB
+    #[must_use]
+    pub fn side_reduction_db(&self) -> f32 {
+        f32::from_bits(self.side_reduction.load(Ordering::Relaxed))
+    }
+}
+
+/// One-pole coefficient for a time constant of `time_ms`
+fn smoothing_coeff(time_ms: f32, sample_rate: f32) -> f32 {
+    1.0 - (-1.0 / (time_ms / 1000.0 * sample_rate).max(1.0)).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    /// A 220 Hz tone on the left and the same tone on the right, `phase`
+    /// cycles later
+    fn run(limiter: &mut WidthLimiter, phase: f32, seconds: f32) -> Vec<[f32; 2]> {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Short times
+        let samples = (seconds * SAMPLE_RATE) as usize;
+        (0..samples)
+            .map(|n| {
+                #[allow(clippy::cast_precision_loss)] // Sample counts are small
+                let t = 220.0 * n as f32 / SAMPLE_RATE;
+                let tau = std::f32::consts::TAU;
+                limiter.process([(tau * t).sin(), (tau * (t + phase)).sin()])
+            })
+            .collect()
+    }
+
+    fn energy(frames: &[[f32; 2]], side: bool) -> f32 {
+        frames
+            .iter()
+            .map(|&[left, right]| {
+                let value = if side { left - right } else { left + right };
+                0.25 * value * value
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_correlated_mix_passes_untouched() {
+        let mut limiter = WidthLimiter::new(SAMPLE_RATE);
+        limiter.set_enabled(true);
+
+        // Slightly wide: the sides are 30° apart
+        let input_side = energy(
+            &run(&mut WidthLimiter::new(SAMPLE_RATE), 1.0 / 12.0, 1.0),
+            true,
+        );
+        let output = run(&mut limiter, 1.0 / 12.0, 1.0);
+        assert!((energy(&output, true) - input_side).abs() < 1e-3 * input_side);
+        assert!(limiter.side_reduction_db() < 0.01);
+        assert!(limiter.correlation() > 0.8);
+    }
+
+    #[test]
+    fn test_wide_mix_narrowed_to_the_floor() {
+        for floor in [0.0, 0.5] {
+            let mut limiter = WidthLimiter::new(SAMPLE_RATE);
+            limiter.set_enabled(true);
+            limiter.set_min_correlation(floor);
+
+            // 150° apart: mostly side, correlation about -0.87
+            let output = run(&mut limiter, 150.0 / 360.0, 1.0);
+            let settled = &output[output.len() / 2..];
+            let (mid, side) = (energy(settled, false), energy(settled, true));
+            let correlation = (mid - side) / (mid + side);
+            assert!(
+                (correlation - floor).abs() < 0.05,
+                "Floor {floor}: {correlation}"
+            );
+            assert!(limiter.side_reduction_db() > 3.0);
+        }
+    }
+
+    #[test]
+    fn test_disabled_limiter_still_measures() {
+        let mut limiter = WidthLimiter::new(SAMPLE_RATE);
+        run(&mut limiter, 0.5, 0.5);
+        assert!(limiter.correlation() < -0.99, "Antiphase");
+        assert!(limiter.side_reduction_db().abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_width_returns_slowly_after_narrowing() {
+        let mut limiter = WidthLimiter::new(SAMPLE_RATE);
+        limiter.set_enabled(true);
+        run(&mut limiter, 0.5, 0.5);
+        let narrowed = limiter.side_reduction_db();
+
+        // Back to mono material: the side opens up over the release
+        run(&mut limiter, 0.0, 0.05);
+        let partway = limiter.side_reduction_db();
+        run(&mut limiter, 0.0, 3.0);
+        assert!(
+            partway < narrowed && partway > 1.0,
+            "{narrowed} -> {partway}"
+        );
+        assert!(limiter.side_reduction_db() < 0.01);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // Halves of binary fractions are exact
+    fn test_fold_to_mono() {
+        assert_eq!(fold_to_mono([1.0, -1.0]), [0.0, 0.0]);
+        assert_eq!(fold_to_mono([0.5, 0.25]), [0.375, 0.375]);
+    }
+}
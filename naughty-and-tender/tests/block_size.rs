@@ -0,0 +1,272 @@
+//! Block-size invariance of the plugin's render path
+//!
+//! The plugin's `process` reads its parameters and the host's playhead once per
+//! block and hands everything else to `Engine::process`. These tests drive the
+//! engine the same way with the host's blocks cut at different sizes (and, as
+//! with sample-accurate automation, wherever a setting changes), and check the
+//! output is bit-identical however the blocks fall.
+//!
+//! The patch runs every stage with timing of its own: humanized notes, chords
+//! strummed, the built-in pattern, both layers with the octaver, the step
+//! sequencer and pump, the master chain, DC blocker and width limiter, and a
+//! host bypass switched on and off again before its fade reaches silence.
+
+use std::collections::VecDeque;
+
+use naughty_and_tender::engine::{
+    Engine, EngineContext, EngineSettings, HostEvent, HostTransport, OutputEvent,
+};
+use naughty_and_tender::envelope::FilterEnvelopeSettings;
+use naughty_and_tender::eq::EqSettings;
+use naughty_and_tender::layers::LayerMode;
+use naughty_and_tender::modulation::{ModDestination, ModMatrix, ModOffsets, ModSlot, ModSource};
+use naughty_and_tender::oscillators::WaveformType;
+use naughty_and_tender::poly_mod::PolyOffsets;
+use naughty_and_tender::sequencer::{Step, NUM_STEPS};
+use naughty_and_tender::voice::VoiceParams;
+use shared_core::tempo::NoteDivision;
+
+const SAMPLE_RATE: f32 = 44100.0;
+
+const TEMPO_BPM: f32 = 120.0;
+
+/// Host song position at the first sample, off the sequencer's and pump's grid
+const START_BEATS: f64 = 7.3;
+
+/// Length of every render
+const LENGTH: usize = 8192;
+
+/// Samples the host bypass is switched on for (shorter than its fade)
+const BYPASSED: std::ops::Range<usize> = 4000..4200;
+
+/// Built-in pattern notes: (start step, note, length in steps)
+const PATTERN: &[(u8, u8, u8)] = &[(0, 72, 2), (3, 79, 1), (6, 76, 3), (11, 74, 2)];
+
+/// The host's side of one block: its events, at offsets into the block
+struct Host {
+    events: VecDeque<(u32, HostEvent)>,
+}
+
+impl EngineContext for Host {
+    fn next_event(&mut self) -> Option<(u32, HostEvent)> {
+        self.events.pop_front()
+    }
+
+    fn send_event(&mut self, _timing: u32, _event: OutputEvent) {}
+
+    fn poly_offsets(&self, _normalized: &PolyOffsets) -> ModOffsets {
+        ModOffsets::default()
+    }
+}
+
+/// A filtered, gliding saw with random cutoff and sequenced pitch modulation
+fn patch() -> VoiceParams {
+    let mut matrix = ModMatrix::default();
+    matrix.slots[0] = ModSlot {
+        source: ModSource::Random,
+        destination: ModDestination::Cutoff,
+        amount: 0.5,
+    };
+    matrix.slots[1] = ModSlot {
+        source: ModSource::StepSequencer,
+        destination: ModDestination::Pitch,
+        amount: 0.1,
+    };
+    VoiceParams {
+        waveform: WaveformType::Sawtooth,
+        filter_enabled: true,
+        filter_cutoff_hz: 800.0,
+        filter_resonance: 0.6,
+        filter_envelope: FilterEnvelopeSettings {
+            amount_octaves: 2.0,
+            ..FilterEnvelopeSettings::default()
+        },
+        glide_ms: 30.0,
+        mod_matrix: matrix,
+        ..VoiceParams::default()
+    }
+}
+
+/// Every stage with timing of its own switched on
+fn settings() -> EngineSettings {
+    #[allow(clippy::cast_precision_loss)] // At most NUM_STEPS
+    let seq_steps = std::array::from_fn(|i| Step {
+        value: i as f32 / NUM_STEPS as f32,
+        gate: i % 3 != 2,
+    });
+    EngineSettings {
+        tempo_bpm: TEMPO_BPM,
+        layer_a: patch(),
+        layer_b: VoiceParams {
+            waveform: WaveformType::Square,
+            filter_cutoff_hz: 2000.0,
+            ..patch()
+        },
+        layer_mode: LayerMode::Layer,
+        layer_b_level: 0.5,
+        master_drive: true,
+        phaser_enabled: true,
+        eq_enabled: true,
+        eq: EqSettings {
+            mid_gain_db: 6.0,
+            ..EqSettings::default()
+        },
+        stereo_width: 1.5,
+        width_limit: true,
+        min_correlation: 0.9,
+        seq_steps,
+        seq_division: NoteDivision::SixtyFourth,
+        seq_slew_ms: 2.0,
+        pattern: true,
+        pump_division: NoteDivision::Sixteenth,
+        pump_depth: 0.6,
+        pump_shape: 0.3,
+        humanize_timing_ms: 4.0,
+        humanize_velocity: 0.2,
+        chord: true,
+        chord_intervals: Some([7, 0, 0, 0, 0]),
+        strum_time_ms: 15.0,
+        octave_level: 0.5,
+        ..EngineSettings::default()
+    }
+}
+
+/// Overlapping notes at odd offsets, two struck together, and one panned by
+/// expression while it sounds
+fn events() -> Vec<(usize, HostEvent)> {
+    let on = |note, velocity, voice_id| HostEvent::NoteOn {
+        voice_id: Some(voice_id),
+        channel: 0,
+        note,
+        velocity,
+    };
+    let off = |note, velocity, voice_id| HostEvent::NoteOff {
+        voice_id: Some(voice_id),
+        channel: 0,
+        note,
+        velocity,
+    };
+    let pan = HostEvent::PolyPan {
+        voice_id: Some(1),
+        channel: 0,
+        note: 48,
+        pan: -0.7,
+    };
+    vec![
+        (37, on(48, 0.8, 1)),
+        (1001, on(55, 0.6, 2)),
+        (1001, on(59, 0.7, 4)),
+        (2000, pan),
+        (2500, off(48, 0.5, 1)),
+        (3333, on(60, 1.0, 3)),
+        (5000, on(64, 0.9, 5)),
+        (6000, off(55, 0.0, 2)),
+        (6000, off(59, 0.0, 4)),
+        (7000, off(64, 0.3, 5)),
+    ]
+}
+
+/// The host's playhead at a block's first sample: running from `START_BEATS`
+/// in 4/4 while `playing`, stopped otherwise
+fn transport(block_start: usize, playing: bool) -> HostTransport {
+    if !playing {
+        return HostTransport::default();
+    }
+
+    #[allow(clippy::cast_precision_loss)] // Test positions are short
+    let position_beats =
+        START_BEATS + block_start as f64 / f64::from(SAMPLE_RATE) * f64::from(TEMPO_BPM) / 60.0;
+    let bar_start_beats = (position_beats / 4.0).floor() * 4.0;
+    #[allow(clippy::cast_possible_truncation)] // A few bars in
+    let bar_number = (bar_start_beats / 4.0) as i32;
+    HostTransport {
+        playing: true,
+        pos_beats: Some(position_beats),
+        bar: Some((bar_number, bar_start_beats)),
+        time_signature: (4, 4),
+    }
+}
+
+/// Render `LENGTH` samples through the engine in host blocks of up to
+/// `block_size`, the way the plugin's `process` does
+fn render(block_size: usize, playing: bool) -> (Vec<f32>, Vec<f32>) {
+    let mut engine = Engine::new(SAMPLE_RATE);
+    engine.initialize(SAMPLE_RATE, block_size, false);
+    engine.set_pattern_notes(PATTERN);
+    let events = events();
+    let (mut left, mut right) = (vec![0.0; LENGTH], vec![0.0; LENGTH]);
+
+    let mut block_start = 0;
+    while block_start < LENGTH {
+        // Blocks also end where the bypass switches
+        let block_end = [
+            block_start + block_size,
+            LENGTH,
+            BYPASSED.start,
+            BYPASSED.end,
+        ]
+        .into_iter()
+        .filter(|&end| end > block_start)
+        .min()
+        .unwrap_or(LENGTH);
+        let settings = EngineSettings {
+            bypassed: BYPASSED.contains(&block_start),
+            ..settings()
+        };
+
+        #[allow(clippy::cast_possible_truncation)] // Offsets within a block
+        let block_events = events
+            .iter()
+            .filter(|(at, _)| (block_start..block_end).contains(at))
+            .map(|&(at, event)| ((at - block_start) as u32, event))
+            .collect();
+        engine.process(
+            &settings,
+            &transport(block_start, playing),
+            &mut [
+                &mut left[block_start..block_end],
+                &mut right[block_start..block_end],
+            ],
+            None,
+            &mut Host {
+                events: block_events,
+            },
+        );
+        block_start = block_end;
+    }
+    (left, right)
+}
+
+#[test]
+fn test_render_is_identical_at_any_block_size() {
+    let reference = render(LENGTH, false);
+    assert!(reference.0.iter().any(|sample| sample.abs() > 0.1));
+    assert!(
+        reference.0 != reference.1,
+        "The expression pan should reach the output"
+    );
+
+    for block_size in [1, 7, 17, 64, 333, 1024] {
+        assert!(
+            render(block_size, false) == reference,
+            "Block size {block_size} renders differently"
+        );
+    }
+}
+
+#[test]
+fn test_synced_render_is_identical_at_any_block_size() {
+    let reference = render(LENGTH, true);
+    assert!(reference.0.iter().any(|sample| sample.abs() > 0.1));
+    assert!(
+        reference != render(LENGTH, false),
+        "The host's playhead should move the sequencer, pump and pattern"
+    );
+
+    for block_size in [1, 17, 64, 1024] {
+        assert!(
+            render(block_size, true) == reference,
+            "Block size {block_size} renders differently"
+        );
+    }
+}
@@ -130,14 +130,18 @@ mod integration_tests {
         let mut samples_c5 = vec![0.0; 44100];
         vm2.process(&mut samples_c5);
 
-        // Count zero crossings
-        let crossings_c4 = count_zero_crossings(&samples_c4);
-        let crossings_c5 = count_zero_crossings(&samples_c5);
+        // Measure pitch after the attack
+        let pitch_c4 = detect_frequency(&samples_c4[4410..]);
+        let pitch_c5 = detect_frequency(&samples_c5[4410..]);
+        assert!(
+            (pitch_c4 - 261.63).abs() < 0.5,
+            "C4 should be ~261.63 Hz, got {pitch_c4}"
+        );
 
-        // C5 should have twice as many zero crossings as C4 (octave = 2x frequency)
-        let ratio = crossings_c5 as f32 / crossings_c4 as f32;
+        // C5 should be twice the frequency of C4 (octave = 2x frequency)
+        let ratio = pitch_c5 / pitch_c4;
         assert!(
-            (ratio - 2.0).abs() < 0.1,
+            (ratio - 2.0).abs() < 0.01,
             "C5 should be octave above C4, ratio: {}",
             ratio
         );
@@ -237,6 +241,7 @@ mod integration_tests {
     #[test]
     fn test_envelope_shapes_amplitude_over_time() {
         // RED: Envelope should control amplitude through ADSR phases
+        use naughty_and_tender::synth_voice::SynthVoice;
         use naughty_and_tender::voice::Voice;
 
         let mut voice = Voice::new(SAMPLE_RATE);
@@ -299,6 +304,7 @@ mod integration_tests {
     #[test]
     fn test_oscillator_waveform_selection() {
         // RED: Should be able to select different waveforms
+        use naughty_and_tender::synth_voice::SynthVoice;
         use naughty_and_tender::voice::Voice;
         use naughty_and_tender::oscillators::WaveformType;
 
@@ -331,6 +337,7 @@ mod integration_tests {
     #[test]
     fn test_no_audio_glitches_on_parameter_changes() {
         // RED: Changing parameters shouldn't cause clicks or glitches
+        use naughty_and_tender::synth_voice::SynthVoice;
         use naughty_and_tender::voice::Voice;
 
         let mut voice = Voice::new(SAMPLE_RATE);
@@ -396,16 +403,58 @@ mod integration_tests {
         }
     }
 
+    #[test]
+    fn test_humanized_note_on_sounds_after_its_delay() {
+        // Notes held back by humanization start their voices on the delayed sample
+        use naughty_and_tender::humanize::{Humanizer, KeyEvent};
+        use naughty_and_tender::voice::VoiceManager;
+
+        let mut humanizer = Humanizer::new(SAMPLE_RATE);
+        humanizer.set_timing_ms(20.0);
+        let mut vm = VoiceManager::new(SAMPLE_RATE, 16);
+        for note in [60, 64, 67] {
+            humanizer.note_on(note, 1.0, 0, None);
+        }
+
+        // The plugin's order: due notes first, then the sample
+        let mut started = Vec::new();
+        let mut samples = vec![0.0; 2048];
+        for (index, sample) in samples.iter_mut().enumerate() {
+            while let Some(event) = humanizer.pop_due() {
+                if let KeyEvent::NoteOn { note, velocity, .. } = event {
+                    vm.note_on(note, velocity);
+                    started.push(index);
+                }
+            }
+            let mut frame = [0.0];
+            vm.process(&mut frame);
+            *sample = frame[0];
+            humanizer.advance();
+        }
+
+        assert_eq!(started.len(), 3, "Every delayed note-on should be emitted");
+        let first = *started.iter().min().unwrap();
+        let max_delay = (0.02 * SAMPLE_RATE) as usize;
+        assert!(first > 0, "Humanized notes shouldn't all start on time");
+        assert!(started.iter().all(|&index| index <= max_delay), "{started:?}");
+
+        // Silent until the first delayed note, sounding once it starts
+        assert!(samples[..first].iter().all(|sample| sample.abs() < 1e-6));
+        assert!(samples[first..first + 256].iter().any(|sample| sample.abs() > 0.01));
+    }
+
     // Helper functions
     fn calculate_rms(samples: &[f32]) -> f32 {
         let sum_squares: f32 = samples.iter().map(|s| s * s).sum();
         (sum_squares / samples.len() as f32).sqrt()
     }
 
-    fn count_zero_crossings(samples: &[f32]) -> usize {
-        samples
-            .windows(2)
-            .filter(|w| (w[0] < 0.0 && w[1] >= 0.0) || (w[0] >= 0.0 && w[1] < 0.0))
-            .count()
+    fn detect_frequency(samples: &[f32]) -> f32 {
+        use shared_core::pitch::PitchDetector;
+
+        PitchDetector::new(SAMPLE_RATE, 50.0, 5000.0)
+            .detect(samples)
+            .expect("Voice output should have a pitch")
+            .frequency_hz
     }
 }
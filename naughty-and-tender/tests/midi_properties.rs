@@ -0,0 +1,187 @@
+//! Property tests for arbitrary MIDI streams
+//!
+//! Generates random interleavings of note-ons, note-offs, CCs and pitch bends
+//! across the full MIDI range, plays them through a `VoiceManager` with random
+//! voice settings, and checks the invariants that must hold for any input:
+//! - Never more sounding voices than the polyphony
+//! - No voice still held once every key is released
+//! - Every output sample is finite
+//! - The output returns to silence after the release
+
+use naughty_and_tender::voice::{
+    SameNotePolicy, VoiceAllocation, VoiceManager, VoiceParams, VoiceState,
+};
+use proptest::prelude::*;
+
+const SAMPLE_RATE: f32 = 44100.0;
+
+/// Longest release any generated patch can use
+const MAX_RELEASE_MS: f32 = 500.0;
+
+/// One incoming MIDI message, processed at the start of a short block
+#[derive(Debug, Clone)]
+enum MidiEvent {
+    NoteOn {
+        note: u8,
+        velocity: f32,
+    },
+    NoteOff {
+        note: u8,
+        velocity: f32,
+    },
+    /// A controller mapped to one of the voice settings
+    Cc {
+        cc: u8,
+        value: f32,
+    },
+    /// -1.0 to 1.0
+    PitchBend {
+        value: f32,
+    },
+}
+
+/// Voice manager settings chosen per test case
+#[derive(Debug, Clone)]
+struct Patch {
+    polyphony: usize,
+    policy: SameNotePolicy,
+    allocation: VoiceAllocation,
+    bass_reserve: bool,
+    attack_ms: f32,
+    release_ms: f32,
+}
+
+impl Patch {
+    fn voice_params(&self) -> VoiceParams {
+        VoiceParams {
+            attack_ms: self.attack_ms,
+            release_ms: self.release_ms,
+            ..VoiceParams::default()
+        }
+    }
+}
+
+fn midi_event() -> impl Strategy<Value = MidiEvent> {
+    prop_oneof![
+        4 => (0u8..128, 0.0f32..=1.0)
+            .prop_map(|(note, velocity)| MidiEvent::NoteOn { note, velocity }),
+        4 => (0u8..128, 0.0f32..=1.0)
+            .prop_map(|(note, velocity)| MidiEvent::NoteOff { note, velocity }),
+        1 => (0u8..128, 0.0f32..=1.0).prop_map(|(cc, value)| MidiEvent::Cc { cc, value }),
+        1 => (-1.0f32..=1.0).prop_map(|value| MidiEvent::PitchBend { value }),
+    ]
+}
+
+fn patch() -> impl Strategy<Value = Patch> {
+    (
+        1usize..=16,
+        0usize..SameNotePolicy::ALL.len(),
+        0usize..VoiceAllocation::ALL.len(),
+        any::<bool>(),
+        0.0f32..50.0,
+        1.0f32..MAX_RELEASE_MS,
+    )
+        .prop_map(
+            |(polyphony, policy, allocation, bass_reserve, attack_ms, release_ms)| Patch {
+                polyphony,
+                policy: SameNotePolicy::from_index(policy),
+                allocation: VoiceAllocation::from_index(allocation),
+                bass_reserve,
+                attack_ms,
+                release_ms,
+            },
+        )
+}
+
+fn build_voice_manager(patch: &Patch) -> VoiceManager {
+    let mut vm = VoiceManager::new(SAMPLE_RATE, patch.polyphony);
+    vm.set_same_note_policy(patch.policy);
+    vm.set_allocation(patch.allocation);
+    vm.set_bass_reserve(patch.bass_reserve);
+    vm.set_params(&patch.voice_params());
+    vm
+}
+
+/// Apply one event; CCs sweep the settings a MIDI-learned controller would
+fn apply(vm: &mut VoiceManager, params: &mut VoiceParams, event: &MidiEvent) {
+    match *event {
+        MidiEvent::NoteOn { note, velocity } => vm.note_on(note, velocity),
+        MidiEvent::NoteOff { note, velocity } => vm.note_off_with_velocity(note, velocity),
+        MidiEvent::Cc { cc, value } => {
+            match cc % 4 {
+                0 => params.filter_cutoff_hz = 20.0 * 1000.0f32.powf(value),
+                1 => params.filter_resonance = value,
+                2 => params.filter_enabled = value >= 0.5,
+                _ => params.glide_ms = value * 200.0,
+            }
+            vm.set_params(params);
+        }
+        MidiEvent::PitchBend { value } => vm.set_pitch_bend(value),
+    }
+}
+
+fn sounding_voices(vm: &VoiceManager) -> usize {
+    vm.get_voice_states()
+        .into_iter()
+        .filter(|&state| state != VoiceState::Idle)
+        .count()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn test_arbitrary_midi_keeps_voice_invariants(
+        patch in patch(),
+        events in prop::collection::vec(midi_event(), 0..200),
+    ) {
+        let mut vm = build_voice_manager(&patch);
+        let mut params = patch.voice_params();
+        let mut buffer = [0.0f32; 32];
+        let mut pressed = [0usize; 128];
+
+        for event in &events {
+            match *event {
+                MidiEvent::NoteOn { note, .. } => pressed[usize::from(note)] += 1,
+                MidiEvent::NoteOff { note, .. } => {
+                    let count = &mut pressed[usize::from(note)];
+                    *count = count.saturating_sub(1);
+                }
+                _ => {}
+            }
+            apply(&mut vm, &mut params, event);
+
+            prop_assert!(sounding_voices(&vm) <= patch.polyphony);
+
+            vm.process(&mut buffer);
+            prop_assert!(
+                buffer.iter().all(|s| s.is_finite()),
+                "Non-finite sample after {:?}",
+                event
+            );
+        }
+
+        // Release every key as often as it's still pressed
+        for (note, &count) in (0u8..128).zip(&pressed) {
+            for _ in 0..count {
+                vm.note_off(note);
+            }
+        }
+        prop_assert!(
+            vm.get_active_notes().is_empty(),
+            "Stuck notes after releasing every key: {:?}",
+            vm.get_active_notes()
+        );
+
+        // Run past the longest release (plus the stealing crossfades)
+        let release_blocks = (MAX_RELEASE_MS * 2.0 / 1000.0 * SAMPLE_RATE) as usize / buffer.len();
+        for _ in 0..release_blocks {
+            vm.process(&mut buffer);
+            prop_assert!(buffer.iter().all(|s| s.is_finite()));
+        }
+        prop_assert_eq!(vm.active_voice_count(), 0, "Voices still sounding after the release");
+
+        vm.process(&mut buffer);
+        prop_assert!(buffer.iter().all(|&s| s == 0.0), "Output not silent after the release");
+    }
+}
@@ -0,0 +1,468 @@
+//! Frequency-domain measurements for DSP tests
+//!
+//! Lets tests assert what a signal contains rather than how often it crosses
+//! zero: its pitch to a fraction of a hertz, the level of any one partial, and
+//! how far it is from a pure sine.
+//! - [`estimate_frequency`]: pitch of the strongest partial
+//! - [`spectral_peaks`]: the strongest partials, loudest first
+//! - [`goertzel_amplitude`]: level at one exact frequency
+//! - [`thd_n`]: everything that isn't the fundamental, relative to it
+//!
+//! Every analysis uses a Hann window, so the signal doesn't need to hold a whole
+//! number of cycles. Amplitudes are peak amplitudes: a sine of amplitude 0.5
+//! measures 0.5.
+//!
+//! These allocate and run in double precision: they're meant for tests and
+//! offline analysis, not the audio thread.
+//!
+//! # References
+//! - Cooley & Tukey, "An Algorithm for the Machine Calculation of Complex Fourier
+//!   Series" (1965), iterative radix-2 form
+//! - Goertzel, "An Algorithm for the Evaluation of Finite Trigonometric Series"
+//!   (1958)
+//! - Gaussian peak interpolation: Gasior & Gonzalez, "Improving FFT Frequency
+//!   Measurement Resolution by Parabolic and Gaussian Interpolation" (CERN 2004)
+//! - THD+N as measured by a notch analyzer: residual RMS over fundamental RMS
+
+use std::f64::consts::TAU;
+
+/// One partial found in a spectrum
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpectralPeak {
+    /// Interpolated frequency in Hz
+    pub frequency_hz: f32,
+    /// Peak amplitude (1.0 = full-scale sine)
+    pub amplitude: f32,
+}
+
+/// Windowed amplitude spectrum, bins 0 to Nyquist
+///
+/// The signal is zero-padded to a power of two; bin `k` sits at
+/// `k * sample_rate / (2 * (len - 1))` Hz for the returned `len`.
+///
+/// # Example
+/// ```
+/// use shared_core::analysis::amplitude_spectrum;
+///
+/// let sine: Vec<f32> = (0..1024)
+///     .map(|i| (std::f32::consts::TAU * 64.0 * i as f32 / 1024.0).sin())
+///     .collect();
+/// let spectrum = amplitude_spectrum(&sine);
+/// assert!((spectrum[64] - 1.0).abs() < 0.01);
+/// ```
+#[must_use]
+pub fn amplitude_spectrum(samples: &[f32]) -> Vec<f32> {
+    if samples.is_empty() {
+        return Vec::new();
+    }
+
+    let size = samples.len().next_power_of_two().max(2);
+    let window = hann(samples.len());
+    let window_sum: f64 = window.iter().sum();
+
+    let mut re = vec![0.0; size];
+    let mut im = vec![0.0; size];
+    for ((value, &sample), weight) in re.iter_mut().zip(samples).zip(&window) {
+        *value = f64::from(sample) * weight;
+    }
+    fft(&mut re, &mut im);
+
+    // Doubled for the negative frequencies folded onto each bin
+    #[allow(clippy::cast_possible_truncation)] // Amplitudes fit in f32
+    re.iter()
+        .zip(&im)
+        .take(size / 2 + 1)
+        .map(|(re, im)| (2.0 * re.hypot(*im) / window_sum) as f32)
+        .collect()
+}
+
+/// The strongest partials, loudest first (at most `max_peaks`)
+///
+/// A peak is a local maximum of the spectrum; DC is never reported. Frequencies
+/// and amplitudes are interpolated between bins.
+///
+/// # Example
+/// ```
+/// use shared_core::analysis::spectral_peaks;
+///
+/// let sample_rate = 48000.0;
+/// let chord: Vec<f32> = (0..4800)
+///     .map(|i| {
+///         let t = i as f32 / sample_rate;
+///         (std::f32::consts::TAU * 440.0 * t).sin() + 0.5 * (std::f32::consts::TAU * 660.0 * t).sin()
+///     })
+///     .collect();
+///
+/// let peaks = spectral_peaks(&chord, sample_rate, 2);
+/// assert!((peaks[0].frequency_hz - 440.0).abs() < 1.0);
+/// assert!((peaks[1].frequency_hz - 660.0).abs() < 1.0);
+/// ```
+#[must_use]
+pub fn spectral_peaks(samples: &[f32], sample_rate: f32, max_peaks: usize) -> Vec<SpectralPeak> {
+    let spectrum = amplitude_spectrum(samples);
+    if spectrum.len() < 3 {
+        return Vec::new();
+    }
+
+    #[allow(clippy::cast_precision_loss)] // Spectrum sizes are far below 2^23
+    let bin_hz = sample_rate / (2 * (spectrum.len() - 1)) as f32;
+
+    let mut peaks: Vec<SpectralPeak> = (1..spectrum.len() - 1)
+        .filter(|&k| {
+            spectrum[k] > 0.0 && spectrum[k] > spectrum[k - 1] && spectrum[k] >= spectrum[k + 1]
+        })
+        .map(|k| {
+            let (offset, amplitude) =
+                interpolate_peak(spectrum[k - 1], spectrum[k], spectrum[k + 1]);
+            #[allow(clippy::cast_precision_loss)] // Bin indices are far below 2^23
+            let bin = k as f32 + offset;
+            SpectralPeak {
+                frequency_hz: bin * bin_hz,
+                amplitude,
+            }
+        })
+        .collect();
+
+    peaks.sort_by(|a, b| b.amplitude.total_cmp(&a.amplitude));
+    peaks.truncate(max_peaks);
+    peaks
+}
+
+/// Frequency in Hz of the strongest partial (`None` for silence)
+///
+/// # Example
+/// ```
+/// use shared_core::analysis::estimate_frequency;
+///
+/// let sample_rate = 44100.0;
+/// let sine: Vec<f32> = (0..44100)
+///     .map(|i| (std::f32::consts::TAU * 261.63 * i as f32 / sample_rate).sin())
+///     .collect();
+/// let frequency = estimate_frequency(&sine, sample_rate).unwrap();
+/// assert!((frequency - 261.63).abs() < 0.05);
+/// ```
+#[must_use]
+pub fn estimate_frequency(samples: &[f32], sample_rate: f32) -> Option<f32> {
+    spectral_peaks(samples, sample_rate, 1)
+        .first()
+        .map(|peak| peak.frequency_hz)
+}
+
+/// Peak amplitude of the component at `frequency_hz`
+///
+/// Uses the Goertzel recurrence, so the frequency doesn't need to fall on an FFT
+/// bin. Other partials closer than about two cycles over the signal length leak
+/// into the result.
+///
+/// # Example
+/// ```
+/// use shared_core::analysis::goertzel_amplitude;
+///
+/// let sample_rate = 48000.0;
+/// let sine: Vec<f32> = (0..4800)
+///     .map(|i| 0.25 * (std::f32::consts::TAU * 1000.0 * i as f32 / sample_rate).sin())
+///     .collect();
+/// assert!((goertzel_amplitude(&sine, sample_rate, 1000.0) - 0.25).abs() < 0.001);
+/// assert!(goertzel_amplitude(&sine, sample_rate, 3000.0) < 0.001);
+/// ```
+#[must_use]
+pub fn goertzel_amplitude(samples: &[f32], sample_rate: f32, frequency_hz: f32) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+
+    let window = hann(samples.len());
+    let window_sum: f64 = window.iter().sum();
+    let coefficient = 2.0 * (TAU * f64::from(frequency_hz) / f64::from(sample_rate)).cos();
+
+    let (mut s1, mut s2) = (0.0, 0.0);
+    for (&sample, weight) in samples.iter().zip(&window) {
+        let s0 = f64::from(sample) * weight + coefficient * s1 - s2;
+        s2 = s1;
+        s1 = s0;
+    }
+    let power = (s1 * s1 + s2 * s2 - coefficient * s1 * s2).max(0.0);
+
+    #[allow(clippy::cast_possible_truncation)] // Amplitudes fit in f32
+    let amplitude = (2.0 * power.sqrt() / window_sum) as f32;
+    amplitude
+}
+
+/// Total harmonic distortion plus noise, as a ratio (0.01 = 1% = -40 dB)
+///
+/// Fits a sine at `fundamental_hz` (plus any DC offset) by least squares,
+/// removes it, and compares the RMS of what's left to the RMS of the fitted
+/// sine. Returns infinity when there's no fundamental at all.
+///
+/// # Example
+/// ```
+/// use shared_core::analysis::thd_n;
+///
+/// let sample_rate = 48000.0;
+/// let clipped: Vec<f32> = (0..4800)
+///     .map(|i| (1.5 * (std::f32::consts::TAU * 500.0 * i as f32 / sample_rate).sin()).clamp(-1.0, 1.0))
+///     .collect();
+/// assert!(thd_n(&clipped, sample_rate, 500.0) > 0.05);
+/// ```
+#[must_use]
+pub fn thd_n(samples: &[f32], sample_rate: f32, fundamental_hz: f32) -> f32 {
+    let omega = TAU * f64::from(fundamental_hz) / f64::from(sample_rate);
+    let basis = |n: usize| {
+        #[allow(clippy::cast_precision_loss)] // Sample indices are far below 2^52
+        let phase = omega * n as f64;
+        [1.0, phase.sin(), phase.cos()]
+    };
+
+    // Normal equations for x ≈ a + b·sin + c·cos
+    let mut gram = [[0.0f64; 3]; 3];
+    let mut projection = [0.0f64; 3];
+    for (n, &sample) in samples.iter().enumerate() {
+        let terms = basis(n);
+        for i in 0..3 {
+            projection[i] += terms[i] * f64::from(sample);
+            for j in 0..3 {
+                gram[i][j] += terms[i] * terms[j];
+            }
+        }
+    }
+    let Some([offset, sin_gain, cos_gain]) = solve_3x3(gram, projection) else {
+        return f32::INFINITY;
+    };
+
+    let (mut fundamental_power, mut residual_power) = (0.0, 0.0);
+    for (n, &sample) in samples.iter().enumerate() {
+        let [_, sin, cos] = basis(n);
+        let fundamental = sin_gain * sin + cos_gain * cos;
+        fundamental_power += fundamental * fundamental;
+        residual_power += (f64::from(sample) - offset - fundamental).powi(2);
+    }
+
+    if fundamental_power <= 0.0 {
+        return f32::INFINITY;
+    }
+    #[allow(clippy::cast_possible_truncation)] // Ratios fit in f32
+    let ratio = (residual_power / fundamental_power).sqrt() as f32;
+    ratio
+}
+
+/// Ratio to decibels (`-inf` for silence)
+#[inline]
+#[must_use]
+pub fn ratio_to_db(ratio: f32) -> f32 {
+    20.0 * ratio.log10()
+}
+
+/// Periodic Hann window
+fn hann(len: usize) -> Vec<f64> {
+    #[allow(clippy::cast_precision_loss)] // Window lengths are far below 2^52
+    let step = TAU / len as f64;
+    (0..len)
+        .map(|n| {
+            #[allow(clippy::cast_precision_loss)]
+            let phase = step * n as f64;
+            0.5 - 0.5 * phase.cos()
+        })
+        .collect()
+}
+
+/// In-place radix-2 FFT (length must be a power of two)
+fn fft(re: &mut [f64], im: &mut [f64]) {
+    let size = re.len();
+    debug_assert!(size.is_power_of_two() && im.len() == size);
+
+    // Bit-reversal permutation
+    let mut j = 0;
+    for i in 1..size {
+        let mut bit = size >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= size {
+        #[allow(clippy::cast_precision_loss)] // FFT sizes are far below 2^52
+        let angle = -TAU / len as f64;
+        for start in (0..size).step_by(len) {
+            for k in 0..len / 2 {
+                #[allow(clippy::cast_precision_loss)]
+                let (twiddle_im, twiddle_re) = (angle * k as f64).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let odd_re = re[b] * twiddle_re - im[b] * twiddle_im;
+                let odd_im = re[b] * twiddle_im + im[b] * twiddle_re;
+                re[b] = re[a] - odd_re;
+                im[b] = im[a] - odd_im;
+                re[a] += odd_re;
+                im[a] += odd_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// Bin offset (-0.5 to 0.5) and amplitude of a peak from its bin and neighbours
+///
+/// Fits a parabola to the log amplitudes (Gaussian interpolation), which is
+/// close to exact for the Hann window's main lobe.
+fn interpolate_peak(left: f32, center: f32, right: f32) -> (f32, f32) {
+    if left <= 0.0 || right <= 0.0 {
+        return (0.0, center);
+    }
+
+    let (l, c, r) = (left.ln(), center.ln(), right.ln());
+    let curvature = l - 2.0 * c + r;
+    if curvature >= 0.0 {
+        return (0.0, center);
+    }
+
+    let offset = (0.5 * (l - r) / curvature).clamp(-0.5, 0.5);
+    // The vertex of the same parabola undoes the scalloping loss between bins
+    let log_peak = c - 0.25 * (l - r) * offset;
+    (offset, log_peak.exp())
+}
+
+/// Solve a symmetric 3×3 system by Gaussian elimination (`None` if singular)
+fn solve_3x3(mut matrix: [[f64; 3]; 3], mut rhs: [f64; 3]) -> Option<[f64; 3]> {
+    for col in 0..3 {
+        let pivot =
+            (col..3).max_by(|&a, &b| matrix[a][col].abs().total_cmp(&matrix[b][col].abs()))?;
+        if matrix[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        matrix.swap(col, pivot);
+        rhs.swap(col, pivot);
+
+        for row in col + 1..3 {
+            let factor = matrix[row][col] / matrix[col][col];
+            let pivot_row = matrix[col];
+            for (value, pivot) in matrix[row].iter_mut().zip(pivot_row).skip(col) {
+                *value -= factor * pivot;
+            }
+            rhs[row] -= factor * rhs[col];
+        }
+    }
+
+    let mut solution = [0.0; 3];
+    for row in (0..3).rev() {
+        let tail: f64 = (row + 1..3).map(|k| matrix[row][k] * solution[k]).sum();
+        solution[row] = (rhs[row] - tail) / matrix[row][row];
+    }
+    Some(solution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    fn sine(frequency_hz: f32, amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| {
+                #[allow(clippy::cast_precision_loss)]
+                let t = f64::from(frequency_hz) * i as f64 / f64::from(SAMPLE_RATE);
+                #[allow(clippy::cast_possible_truncation)]
+                let sample = (f64::from(amplitude) * (TAU * t).sin()) as f32;
+                sample
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_frequency_estimate_between_bins() {
+        for frequency in [27.5, 100.3, 440.0, 1234.5, 15000.7] {
+            let estimate = estimate_frequency(&sine(frequency, 0.8, 48000), SAMPLE_RATE).unwrap();
+            assert!(
+                (estimate - frequency).abs() < 0.02,
+                "{frequency} Hz estimated as {estimate} Hz"
+            );
+        }
+    }
+
+    #[test]
+    fn test_short_signal_estimate_is_within_a_tenth_of_a_bin() {
+        // 2048 samples: 23.4 Hz bins
+        let estimate = estimate_frequency(&sine(1000.0, 1.0, 2048), SAMPLE_RATE).unwrap();
+        assert!((estimate - 1000.0).abs() < 2.3, "estimated {estimate} Hz");
+    }
+
+    #[test]
+    fn test_silence_has_no_frequency() {
+        assert_eq!(estimate_frequency(&[0.0; 1024], SAMPLE_RATE), None);
+        assert_eq!(estimate_frequency(&[], SAMPLE_RATE), None);
+    }
+
+    #[test]
+    fn test_peaks_report_harmonic_amplitudes() {
+        // Square-ish wave: odd harmonics at 1, 1/3, 1/5
+        let fundamental = sine(200.0, 1.0, 24000);
+        let third = sine(600.0, 1.0 / 3.0, 24000);
+        let fifth = sine(1000.0, 0.2, 24000);
+        let signal: Vec<f32> = (0..24000)
+            .map(|i| fundamental[i] + third[i] + fifth[i])
+            .collect();
+
+        let peaks = spectral_peaks(&signal, SAMPLE_RATE, 3);
+        assert_eq!(peaks.len(), 3);
+        for (peak, (frequency, amplitude)) in
+            peaks
+                .iter()
+                .zip([(200.0, 1.0), (600.0, 1.0 / 3.0), (1000.0, 0.2)])
+        {
+            assert!((peak.frequency_hz - frequency).abs() < 0.1, "{peak:?}");
+            assert!((peak.amplitude - amplitude).abs() < 0.01, "{peak:?}");
+        }
+    }
+
+    #[test]
+    fn test_goertzel_matches_spectrum_peak() {
+        let signal = sine(3210.0, 0.4, 9600);
+        let amplitude = goertzel_amplitude(&signal, SAMPLE_RATE, 3210.0);
+        assert!((amplitude - 0.4).abs() < 1e-3, "amplitude {amplitude}");
+
+        let peak = spectral_peaks(&signal, SAMPLE_RATE, 1)[0];
+        assert!((peak.amplitude - amplitude).abs() < 0.01, "{peak:?}");
+    }
+
+    #[test]
+    fn test_thd_n_of_pure_sine_is_negligible() {
+        let thd = thd_n(&sine(997.0, 0.5, 4800), SAMPLE_RATE, 997.0);
+        assert!(ratio_to_db(thd) < -100.0, "{} dB", ratio_to_db(thd));
+    }
+
+    #[test]
+    fn test_thd_n_measures_added_harmonic() {
+        // 1% second harmonic = -40 dB
+        let fundamental = sine(500.0, 1.0, 4800);
+        let harmonic = sine(1000.0, 0.01, 4800);
+        let signal: Vec<f32> = fundamental
+            .iter()
+            .zip(&harmonic)
+            .map(|(f, h)| f + h)
+            .collect();
+
+        let thd = thd_n(&signal, SAMPLE_RATE, 500.0);
+        assert!(
+            (ratio_to_db(thd) + 40.0).abs() < 0.1,
+            "{} dB",
+            ratio_to_db(thd)
+        );
+    }
+
+    #[test]
+    fn test_thd_n_ignores_dc_offset() {
+        let signal: Vec<f32> = sine(440.0, 0.5, 4800).iter().map(|s| s + 0.25).collect();
+        assert!(ratio_to_db(thd_n(&signal, SAMPLE_RATE, 440.0)) < -100.0);
+    }
+
+    #[test]
+    fn test_thd_n_of_silence_is_infinite() {
+        assert!(thd_n(&[0.0; 480], SAMPLE_RATE, 440.0).is_infinite());
+    }
+}
@@ -0,0 +1,597 @@
+//! Biquad filters using the RBJ Audio EQ Cookbook designs
+//!
+//! A biquad is a second-order IIR section:
+//!
+//! H(z) = (b0 + b1·z⁻¹ + b2·z⁻²) / (1 + a1·z⁻¹ + a2·z⁻²)
+//!
+//! Coefficients are normalized so a0 = 1. Processing uses transposed direct form II,
+//! which needs only two state variables and behaves well in floating point.
+//!
+//! # References
+//! - Robert Bristow-Johnson, "Cookbook formulae for audio EQ biquad filter coefficients"
+//! - Julius O. Smith, "Introduction to Digital Filters" (transposed direct form II)
+
+use std::f32::consts::PI;
+
+/// Filter responses available from the cookbook
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BiquadType {
+    LowPass,
+    HighPass,
+    /// Band-pass with constant 0 dB peak gain
+    BandPass,
+    Notch,
+    /// Peaking EQ (uses `gain_db`)
+    Peak,
+    /// Low shelf (uses `gain_db`)
+    LowShelf,
+    /// High shelf (uses `gain_db`)
+    HighShelf,
+    /// Unity magnitude, 360° phase rotation through `frequency`
+    AllPass,
+}
+
+/// Everything needed to design one biquad
+///
+/// Kept alongside the filter so coefficients are only recomputed when
+/// something actually changed (see `Biquad::update`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BiquadParams {
+    pub filter_type: BiquadType,
+    pub sample_rate: f32,
+    /// Cutoff / center / corner frequency in Hz
+    pub frequency: f32,
+    /// Quality factor (0.707 = Butterworth for low/high pass)
+    pub q: f32,
+    /// Gain in dB (peak and shelf types only)
+    pub gain_db: f32,
+}
+
+impl BiquadParams {
+    /// Compute the coefficients for these parameters
+    #[must_use]
+    pub fn coefficients(&self) -> BiquadCoefficients {
+        BiquadCoefficients::design(
+            self.filter_type,
+            self.sample_rate,
+            self.frequency,
+            self.q,
+            self.gain_db,
+        )
+    }
+}
+
+/// Normalized biquad coefficients (a0 = 1)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BiquadCoefficients {
+    pub b0: f32,
+    pub b1: f32,
+    pub b2: f32,
+    pub a1: f32,
+    pub a2: f32,
+}
+
+impl BiquadCoefficients {
+    /// Pass-through coefficients (H(z) = 1)
+    pub const IDENTITY: Self = Self {
+        b0: 1.0,
+        b1: 0.0,
+        b2: 0.0,
+        a1: 0.0,
+        a2: 0.0,
+    };
+
+    /// Design any cookbook filter (`gain_db` is ignored by the non-EQ types)
+    #[must_use]
+    pub fn design(
+        filter_type: BiquadType,
+        sample_rate: f32,
+        frequency: f32,
+        q: f32,
+        gain_db: f32,
+    ) -> Self {
+        match filter_type {
+            BiquadType::LowPass => Self::low_pass(sample_rate, frequency, q),
+            BiquadType::HighPass => Self::high_pass(sample_rate, frequency, q),
+            BiquadType::BandPass => Self::band_pass(sample_rate, frequency, q),
+            BiquadType::Notch => Self::notch(sample_rate, frequency, q),
+            BiquadType::Peak => Self::peaking(sample_rate, frequency, q, gain_db),
+            BiquadType::LowShelf => Self::low_shelf(sample_rate, frequency, q, gain_db),
+            BiquadType::HighShelf => Self::high_shelf(sample_rate, frequency, q, gain_db),
+            BiquadType::AllPass => Self::all_pass(sample_rate, frequency, q),
+        }
+    }
+
+    /// Low-pass: H(s) = 1 / (s² + s/Q + 1)
+    #[must_use]
+    pub fn low_pass(sample_rate: f32, frequency: f32, q: f32) -> Self {
+        let (cos_w0, alpha) = Self::intermediates(sample_rate, frequency, q);
+
+        Self::normalize(
+            (1.0 - cos_w0) * 0.5,
+            1.0 - cos_w0,
+            (1.0 - cos_w0) * 0.5,
+            1.0 + alpha,
+            -2.0 * cos_w0,
+            1.0 - alpha,
+        )
+    }
+
+    /// High-pass: H(s) = s² / (s² + s/Q + 1)
+    #[must_use]
+    pub fn high_pass(sample_rate: f32, frequency: f32, q: f32) -> Self {
+        let (cos_w0, alpha) = Self::intermediates(sample_rate, frequency, q);
+
+        Self::normalize(
+            (1.0 + cos_w0) * 0.5,
+            -(1.0 + cos_w0),
+            (1.0 + cos_w0) * 0.5,
+            1.0 + alpha,
+            -2.0 * cos_w0,
+            1.0 - alpha,
+        )
+    }
+
+    /// Band-pass with 0 dB peak gain: H(s) = (s/Q) / (s² + s/Q + 1)
+    #[must_use]
+    pub fn band_pass(sample_rate: f32, frequency: f32, q: f32) -> Self {
+        let (cos_w0, alpha) = Self::intermediates(sample_rate, frequency, q);
+
+        Self::normalize(alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha)
+    }
+
+    /// Notch: H(s) = (s² + 1) / (s² + s/Q + 1)
+    #[must_use]
+    pub fn notch(sample_rate: f32, frequency: f32, q: f32) -> Self {
+        let (cos_w0, alpha) = Self::intermediates(sample_rate, frequency, q);
+
+        Self::normalize(
+            1.0,
+            -2.0 * cos_w0,
+            1.0,
+            1.0 + alpha,
+            -2.0 * cos_w0,
+            1.0 - alpha,
+        )
+    }
+
+    /// All-pass: H(s) = (s² - s/Q + 1) / (s² + s/Q + 1)
+    ///
+    /// Magnitude is 1 everywhere; the phase passes through -180° at `frequency`.
+    /// Chains of these are the building block of phasers.
+    #[must_use]
+    pub fn all_pass(sample_rate: f32, frequency: f32, q: f32) -> Self {
+        let (cos_w0, alpha) = Self::intermediates(sample_rate, frequency, q);
+
+        Self::normalize(
+            1.0 - alpha,
+            -2.0 * cos_w0,
+            1.0 + alpha,
+            1.0 + alpha,
+            -2.0 * cos_w0,
+            1.0 - alpha,
+        )
+    }
+
+    /// Peaking EQ: boost or cut `gain_db` around `frequency`, bandwidth set by `q`
+    #[must_use]
+    pub fn peaking(sample_rate: f32, frequency: f32, q: f32, gain_db: f32) -> Self {
+        let a = 10.0f32.powf(gain_db / 40.0);
+        let (cos_w0, alpha) = Self::intermediates(sample_rate, frequency, q);
+
+        Self::normalize(
+            1.0 + alpha * a,
+            -2.0 * cos_w0,
+            1.0 - alpha * a,
+            1.0 + alpha / a,
+            -2.0 * cos_w0,
+            1.0 - alpha / a,
+        )
+    }
+
+    /// Low shelf: boost or cut `gain_db` below `frequency` (q = 0.707 gives the steepest
+    /// shelf without overshoot)
+    #[must_use]
+    pub fn low_shelf(sample_rate: f32, frequency: f32, q: f32, gain_db: f32) -> Self {
+        let a = 10.0f32.powf(gain_db / 40.0);
+        let (cos_w0, alpha) = Self::intermediates(sample_rate, frequency, q);
+        let two_sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+
+        Self::normalize(
+            a * ((a + 1.0) - (a - 1.0) * cos_w0 + two_sqrt_a_alpha),
+            2.0 * a * ((a - 1.0) - (a + 1.0) * cos_w0),
+            a * ((a + 1.0) - (a - 1.0) * cos_w0 - two_sqrt_a_alpha),
+            (a + 1.0) + (a - 1.0) * cos_w0 + two_sqrt_a_alpha,
+            -2.0 * ((a - 1.0) + (a + 1.0) * cos_w0),
+            (a + 1.0) + (a - 1.0) * cos_w0 - two_sqrt_a_alpha,
+        )
+    }
+
+    /// High shelf: boost or cut `gain_db` above `frequency`
+    #[must_use]
+    pub fn high_shelf(sample_rate: f32, frequency: f32, q: f32, gain_db: f32) -> Self {
+        let a = 10.0f32.powf(gain_db / 40.0);
+        let (cos_w0, alpha) = Self::intermediates(sample_rate, frequency, q);
+        let two_sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+
+        Self::normalize(
+            a * ((a + 1.0) + (a - 1.0) * cos_w0 + two_sqrt_a_alpha),
+            -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w0),
+            a * ((a + 1.0) + (a - 1.0) * cos_w0 - two_sqrt_a_alpha),
+            (a + 1.0) - (a - 1.0) * cos_w0 + two_sqrt_a_alpha,
+            2.0 * ((a - 1.0) - (a + 1.0) * cos_w0),
+            (a + 1.0) - (a - 1.0) * cos_w0 - two_sqrt_a_alpha,
+        )
+    }
+
+    /// Magnitude response in dB at `frequency`
+    ///
+    /// Evaluates |H(e^jω)| directly from the coefficients, so it is cheap enough
+    /// to draw response curves in a GUI.
+    #[must_use]
+    pub fn magnitude_db(&self, sample_rate: f32, frequency: f32) -> f32 {
+        let ((num_re, num_im), (den_re, den_im)) = self.evaluate(sample_rate, frequency);
+
+        let num = num_re * num_re + num_im * num_im;
+        let den = den_re * den_re + den_im * den_im;
+
+        10.0 * (num / den).max(1e-20).log10()
+    }
+
+    /// Phase response in radians at `frequency` (wrapped to -π..π)
+    #[must_use]
+    pub fn phase(&self, sample_rate: f32, frequency: f32) -> f32 {
+        let ((num_re, num_im), (den_re, den_im)) = self.evaluate(sample_rate, frequency);
+
+        let phase = num_im.atan2(num_re) - den_im.atan2(den_re);
+        (phase + PI).rem_euclid(2.0 * PI) - PI
+    }
+
+    /// Numerator and denominator of H(e^jω) as (re, im) pairs
+    fn evaluate(&self, sample_rate: f32, frequency: f32) -> ((f32, f32), (f32, f32)) {
+        let w = 2.0 * PI * frequency / sample_rate;
+        // e^-jω and e^-j2ω
+        let (c1, s1) = (w.cos(), w.sin());
+        let (c2, s2) = ((2.0 * w).cos(), (2.0 * w).sin());
+
+        (
+            (
+                self.b0 + self.b1 * c1 + self.b2 * c2,
+                -(self.b1 * s1 + self.b2 * s2),
+            ),
+            (
+                1.0 + self.a1 * c1 + self.a2 * c2,
+                -(self.a1 * s1 + self.a2 * s2),
+            ),
+        )
+    }
+
+    /// Shared cookbook terms: cos(ω0) and α = sin(ω0) / 2Q
+    #[inline]
+    fn intermediates(sample_rate: f32, frequency: f32, q: f32) -> (f32, f32) {
+        // Keep ω0 strictly inside (0, π) so the designs stay stable
+        let frequency = frequency.clamp(1.0, sample_rate * 0.49);
+        let w0 = 2.0 * PI * frequency / sample_rate;
+        (w0.cos(), w0.sin() / (2.0 * q.max(0.01)))
+    }
+
+    /// Divide everything by a0
+    #[inline]
+    fn normalize(b0: f32, b1: f32, b2: f32, a0: f32, a1: f32, a2: f32) -> Self {
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+}
+
+impl Default for BiquadCoefficients {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Biquad filter section (transposed direct form II)
+///
+/// y[n]  = b0·x[n] + s1
+/// s1    = b1·x[n] - a1·y[n] + s2
+/// s2    = b2·x[n] - a2·y[n]
+///
+/// # Real-time Safety
+/// - Two floats of state, no allocations
+/// - Coefficients can be swapped at any time without resetting state
+///
+/// # Example
+/// ```
+/// use shared_core::biquad::{Biquad, BiquadParams, BiquadType};
+///
+/// let mut filter = Biquad::default();
+/// filter.update(BiquadParams {
+///     filter_type: BiquadType::LowPass,
+///     sample_rate: 48000.0,
+///     frequency: 1000.0,
+///     q: 0.707,
+///     gain_db: 0.0,
+/// });
+/// let output = filter.process(1.0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Biquad {
+    coefficients: BiquadCoefficients,
+    s1: f32,
+    s2: f32,
+
+    /// Parameters the current coefficients were designed from (if any)
+    params: Option<BiquadParams>,
+}
+
+impl Biquad {
+    /// Create a filter with the given coefficients
+    #[must_use]
+    pub fn new(coefficients: BiquadCoefficients) -> Self {
+        Self {
+            coefficients,
+            s1: 0.0,
+            s2: 0.0,
+            params: None,
+        }
+    }
+
+    /// Create a filter designed from parameters
+    #[must_use]
+    pub fn from_params(params: BiquadParams) -> Self {
+        let mut filter = Self::default();
+        filter.update(params);
+        filter
+    }
+
+    /// Replace the coefficients (state is kept so sweeps stay smooth)
+    pub fn set_coefficients(&mut self, coefficients: BiquadCoefficients) {
+        self.coefficients = coefficients;
+        self.params = None;
+    }
+
+    /// Redesign the filter if `params` differ from the last design
+    ///
+    /// Safe to call every block (or every sample) - the trig in the cookbook
+    /// formulas only runs when something changed.
+    ///
+    /// # Returns
+    /// `true` if the coefficients were recomputed
+    pub fn update(&mut self, params: BiquadParams) -> bool {
+        if self.params == Some(params) {
+            return false;
+        }
+
+        self.coefficients = params.coefficients();
+        self.params = Some(params);
+        true
+    }
+
+    /// Parameters of the current design (`None` if coefficients were set directly)
+    #[must_use]
+    pub fn params(&self) -> Option<BiquadParams> {
+        self.params
+    }
+
+    /// Current coefficients
+    #[must_use]
+    pub fn coefficients(&self) -> BiquadCoefficients {
+        self.coefficients
+    }
+
+    /// Process one sample
+    #[inline]
+    pub fn process(&mut self, input: f32) -> f32 {
+        let c = &self.coefficients;
+        let output = c.b0 * input + self.s1;
+        self.s1 = c.b1 * input - c.a1 * output + self.s2;
+        self.s2 = c.b2 * input - c.a2 * output;
+        output
+    }
+
+    /// Clear the filter state
+    pub fn reset(&mut self) {
+        self.s1 = 0.0;
+        self.s2 = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    #[test]
+    fn test_peaking_gain_at_center() {
+        let coeffs = BiquadCoefficients::peaking(SAMPLE_RATE, 1000.0, 1.0, 6.0);
+
+        assert!((coeffs.magnitude_db(SAMPLE_RATE, 1000.0) - 6.0).abs() < 0.01);
+        assert!(coeffs.magnitude_db(SAMPLE_RATE, 50.0).abs() < 0.2);
+        assert!(coeffs.magnitude_db(SAMPLE_RATE, 15000.0).abs() < 0.2);
+    }
+
+    #[test]
+    fn test_shelves_reach_full_gain() {
+        let low = BiquadCoefficients::low_shelf(SAMPLE_RATE, 200.0, 0.707, -9.0);
+        let high = BiquadCoefficients::high_shelf(SAMPLE_RATE, 4000.0, 0.707, 9.0);
+
+        assert!((low.magnitude_db(SAMPLE_RATE, 20.0) + 9.0).abs() < 0.2);
+        assert!(low.magnitude_db(SAMPLE_RATE, 10000.0).abs() < 0.2);
+        assert!((high.magnitude_db(SAMPLE_RATE, 20000.0) - 9.0).abs() < 0.3);
+        assert!(high.magnitude_db(SAMPLE_RATE, 50.0).abs() < 0.2);
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)] // Short test lengths
+    fn test_identity_passes_signal() {
+        let mut filter = Biquad::default();
+        for i in 0..100 {
+            let x = (i as f32 * 0.1).sin();
+            assert!((filter.process(x) - x).abs() < 1e-7);
+        }
+    }
+
+    // Helper to run a sine through a filter and measure steady-state gain in dB (RMS ratio)
+    #[allow(clippy::cast_precision_loss)] // Short test lengths
+    fn measured_gain_db(filter: &mut Biquad, frequency: f32) -> f32 {
+        let omega = 2.0 * PI * frequency / SAMPLE_RATE;
+        let mut energy_in = 0.0f32;
+        let mut energy_out = 0.0f32;
+        for n in 0..48000 {
+            let input = (omega * n as f32).sin();
+            let output = filter.process(input);
+            if n > 24000 {
+                energy_in += input * input;
+                energy_out += output * output;
+            }
+        }
+        10.0 * (energy_out / energy_in).log10()
+    }
+
+    fn params(filter_type: BiquadType, frequency: f32, q: f32) -> BiquadParams {
+        BiquadParams {
+            filter_type,
+            sample_rate: SAMPLE_RATE,
+            frequency,
+            q,
+            gain_db: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_low_pass_response() {
+        let coeffs = BiquadCoefficients::low_pass(SAMPLE_RATE, 1000.0, 0.707);
+
+        // Unity at DC, -3 dB at cutoff (Butterworth), -12 dB/octave above
+        assert!(coeffs.magnitude_db(SAMPLE_RATE, 10.0).abs() < 0.01);
+        assert!((coeffs.magnitude_db(SAMPLE_RATE, 1000.0) + 3.01).abs() < 0.05);
+        let one_octave = coeffs.magnitude_db(SAMPLE_RATE, 2000.0);
+        let two_octaves = coeffs.magnitude_db(SAMPLE_RATE, 4000.0);
+        assert!((one_octave - two_octaves - 12.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_high_pass_response() {
+        let coeffs = BiquadCoefficients::high_pass(SAMPLE_RATE, 1000.0, 0.707);
+
+        assert!(coeffs.magnitude_db(SAMPLE_RATE, 20000.0).abs() < 0.1);
+        assert!((coeffs.magnitude_db(SAMPLE_RATE, 1000.0) + 3.01).abs() < 0.05);
+        assert!(coeffs.magnitude_db(SAMPLE_RATE, 100.0) < -35.0);
+    }
+
+    #[test]
+    fn test_resonant_low_pass_peaks_near_cutoff() {
+        let coeffs = BiquadCoefficients::low_pass(SAMPLE_RATE, 1000.0, 10.0);
+
+        // Gain at cutoff equals Q (20 dB for Q = 10)
+        assert!((coeffs.magnitude_db(SAMPLE_RATE, 1000.0) - 20.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_band_pass_and_notch() {
+        let band = BiquadCoefficients::band_pass(SAMPLE_RATE, 2000.0, 2.0);
+        let notch = BiquadCoefficients::notch(SAMPLE_RATE, 2000.0, 2.0);
+
+        assert!(band.magnitude_db(SAMPLE_RATE, 2000.0).abs() < 0.01);
+        assert!(band.magnitude_db(SAMPLE_RATE, 100.0) < -20.0);
+        assert!(band.magnitude_db(SAMPLE_RATE, 20000.0) < -15.0);
+
+        assert!(notch.magnitude_db(SAMPLE_RATE, 2000.0) < -60.0);
+        assert!(notch.magnitude_db(SAMPLE_RATE, 100.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_all_pass_unity_magnitude_and_phase() {
+        let coeffs = BiquadCoefficients::all_pass(SAMPLE_RATE, 1000.0, 0.707);
+
+        for frequency in [20.0, 200.0, 1000.0, 5000.0, 20000.0] {
+            assert!(
+                coeffs.magnitude_db(SAMPLE_RATE, frequency).abs() < 0.01,
+                "All-pass should be flat at {frequency} Hz"
+            );
+        }
+
+        // -180° at the center frequency, ~0° far below it
+        assert!((coeffs.phase(SAMPLE_RATE, 1000.0).abs() - PI).abs() < 0.01);
+        assert!(coeffs.phase(SAMPLE_RATE, 10.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_design_matches_direct_constructors() {
+        let designed = BiquadCoefficients::design(BiquadType::Peak, SAMPLE_RATE, 500.0, 2.0, 4.0);
+        assert_eq!(
+            designed,
+            BiquadCoefficients::peaking(SAMPLE_RATE, 500.0, 2.0, 4.0)
+        );
+
+        let designed = BiquadCoefficients::design(BiquadType::Notch, SAMPLE_RATE, 500.0, 2.0, 4.0);
+        assert_eq!(designed, BiquadCoefficients::notch(SAMPLE_RATE, 500.0, 2.0));
+    }
+
+    #[test]
+    fn test_processing_matches_analytic_response() {
+        for filter_type in [
+            BiquadType::LowPass,
+            BiquadType::HighPass,
+            BiquadType::BandPass,
+            BiquadType::Peak,
+            BiquadType::LowShelf,
+            BiquadType::HighShelf,
+        ] {
+            let p = BiquadParams {
+                gain_db: 6.0,
+                ..params(filter_type, 1500.0, 1.0)
+            };
+            let mut filter = Biquad::from_params(p);
+
+            for frequency in [300.0, 1500.0, 6000.0] {
+                let expected = p.coefficients().magnitude_db(SAMPLE_RATE, frequency);
+                let measured = measured_gain_db(&mut filter, frequency);
+                assert!(
+                    (expected - measured).abs() < 0.2,
+                    "{filter_type:?} at {frequency} Hz: expected {expected} dB, measured {measured} dB"
+                );
+                filter.reset();
+            }
+        }
+    }
+
+    #[test]
+    fn test_update_only_recomputes_on_change() {
+        let mut filter = Biquad::default();
+        let p = params(BiquadType::LowPass, 800.0, 0.707);
+
+        assert!(filter.update(p));
+        assert!(!filter.update(p), "Unchanged params should not recompute");
+        assert!(filter.update(BiquadParams {
+            frequency: 900.0,
+            ..p
+        }));
+        assert_eq!(filter.params().map(|p| p.frequency), Some(900.0));
+
+        filter.set_coefficients(BiquadCoefficients::IDENTITY);
+        assert!(filter.params().is_none());
+    }
+
+    #[test]
+    fn test_extreme_frequencies_are_stable() {
+        for filter_type in [
+            BiquadType::LowPass,
+            BiquadType::HighPass,
+            BiquadType::AllPass,
+        ] {
+            for frequency in [0.0, 1.0, 23999.0, 30000.0] {
+                let mut filter = Biquad::from_params(params(filter_type, frequency, 20.0));
+                for n in 0..10000 {
+                    let input = if n % 500 == 0 { 1.0 } else { 0.0 };
+                    assert!(filter.process(input).is_finite());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! Constant-power crossfades
+//!
+//! Blends an outgoing signal into an incoming one over a short time, for any
+//! place a signal would otherwise jump: a voice restarting on a new note, a
+//! patch switch, an effect swapping its state. The gains follow the equal-power
+//! pan law, so uncorrelated signals (two different notes) keep a steady level
+//! through the fade instead of dipping by 3 dB in the middle.
+//!
+//! # References
+//! - Equal-power gains: `out = cos(t·π/2)`, `in = sin(t·π/2)`, `t` from 0 to 1
+//! - See [`crate::pan`] for the same curve used as a pan law
+
+use crate::pan;
+
+/// Outgoing and incoming gains at `position` (0.0 = all outgoing, 1.0 = all incoming)
+///
+/// # Example
+/// ```
+/// use shared_core::crossfade::equal_power_gains;
+///
+/// let (outgoing, incoming) = equal_power_gains(0.5);
+/// assert!((outgoing * outgoing + incoming * incoming - 1.0).abs() < 1e-6);
+/// ```
+#[inline]
+#[must_use]
+pub fn equal_power_gains(position: f32) -> (f32, f32) {
+    pan::equal_power(position.clamp(0.0, 1.0) * 2.0 - 1.0)
+}
+
+/// Timed constant-power crossfade
+///
+/// Starts finished (all incoming); [`Crossfade::start`] runs it again from the
+/// outgoing side.
+///
+/// # Real-time Safety
+/// - Fixed state, no allocations
+///
+/// # Example
+/// ```
+/// use shared_core::crossfade::Crossfade;
+///
+/// let mut fade = Crossfade::new(48000.0, 5.0);
+/// fade.start();
+/// while fade.is_active() {
+///     let sample = fade.mix(0.3, -0.2);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Crossfade {
+    /// Progress through the fade (0.0 - 1.0)
+    position: f32,
+
+    /// Position change per sample
+    step: f32,
+
+    /// Sample rate in Hz
+    sample_rate: f32,
+}
+
+impl Crossfade {
+    /// Create a finished crossfade lasting `time_ms` when started
+    #[must_use]
+    pub fn new(sample_rate: f32, time_ms: f32) -> Self {
+        let mut fade = Self {
+            position: 1.0,
+            step: 1.0,
+            sample_rate,
+        };
+        fade.set_time_ms(time_ms);
+        fade
+    }
+
+    /// Set the fade length in milliseconds (0 = switch instantly)
+    pub fn set_time_ms(&mut self, time_ms: f32) {
+        let samples = time_ms / 1000.0 * self.sample_rate;
+        self.step = if samples > 1.0 { 1.0 / samples } else { 1.0 };
+    }
+
+    /// Restart the fade from all outgoing
+    pub fn start(&mut self) {
+        self.position = 0.0;
+    }
+
+    /// Jump to the end of the fade (all incoming)
+    pub fn finish(&mut self) {
+        self.position = 1.0;
+    }
+
+    /// Whether the fade is still running
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.position < 1.0
+    }
+
+    /// Outgoing and incoming gains for this sample, then advance
+    #[inline]
+    pub fn next_gains(&mut self) -> (f32, f32) {
+        let gains = equal_power_gains(self.position);
+        self.position = (self.position + self.step).min(1.0);
+        gains
+    }
+
+    /// Blend one sample of each signal, then advance
+    #[inline]
+    pub fn mix(&mut self, outgoing: f32, incoming: f32) -> f32 {
+        let (outgoing_gain, incoming_gain) = self.next_gains();
+        outgoing * outgoing_gain + incoming * incoming_gain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gains_keep_power_constant() {
+        for i in 0..=10u8 {
+            let (outgoing, incoming) = equal_power_gains(f32::from(i) / 10.0);
+            assert!((outgoing * outgoing + incoming * incoming - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_fade_runs_from_outgoing_to_incoming() {
+        // 10 samples at 1 kHz
+        let mut fade = Crossfade::new(1000.0, 10.0);
+        assert!(!fade.is_active(), "Should start finished");
+
+        fade.start();
+        assert!(
+            (fade.mix(1.0, 0.0) - 1.0).abs() < 1e-6,
+            "Starts all outgoing"
+        );
+        for _ in 0..9 {
+            assert!(fade.is_active());
+            fade.mix(1.0, 0.0);
+        }
+        assert!(!fade.is_active());
+        assert!((fade.mix(0.0, 1.0) - 1.0).abs() < 1e-6, "Ends all incoming");
+    }
+
+    #[test]
+    fn test_zero_time_switches_instantly() {
+        let mut fade = Crossfade::new(48000.0, 0.0);
+        fade.start();
+        fade.next_gains();
+        assert!(!fade.is_active());
+    }
+
+    #[test]
+    fn test_fade_is_smooth() {
+        let mut fade = Crossfade::new(48000.0, 5.0);
+        fade.start();
+        let mut previous = fade.mix(1.0, -1.0);
+        while fade.is_active() {
+            let sample = fade.mix(1.0, -1.0);
+            assert!((sample - previous).abs() < 0.02);
+            previous = sample;
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! DC-blocking high-pass filter
+//!
+//! Asymmetric waveshaping, pulse waves away from 50% and some resonant filters
+//! leave a constant offset in the signal. It's inaudible, but it eats headroom
+//! and clicks whenever the signal stops or is switched. A one-pole/one-zero
+//! high-pass with its corner well below hearing removes it without touching the
+//! bass.
+//!
+//! # References
+//! - J. O. Smith, "Introduction to Digital Filters", DC Blocker:
+//!   `y[n] = x[n] - x[n-1] + R·y[n-1]`
+//! - Pole radius for a corner of `fc` Hz: `R = e^(-2π·fc/fs)`
+
+use std::f32::consts::TAU;
+
+/// Corner frequency used by [`DcBlocker::new`], in Hz
+pub const DEFAULT_CUTOFF_HZ: f32 = 5.0;
+
+/// One-pole DC-blocking high-pass with a bypass
+///
+/// # Real-time Safety
+/// - Fixed state, no allocations
+///
+/// # Example
+/// ```
+/// use shared_core::dc_blocker::DcBlocker;
+///
+/// let mut blocker = DcBlocker::new(48000.0);
+/// let mut output = 0.0;
+/// for _ in 0..48000 {
+///     output = blocker.process(0.5);
+/// }
+/// assert!(output.abs() < 1e-3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DcBlocker {
+    /// Pole radius (just below 1.0)
+    pole: f32,
+
+    /// Previous input
+    x1: f32,
+
+    /// Previous output
+    y1: f32,
+
+    /// Corner frequency in Hz
+    cutoff_hz: f32,
+
+    /// Sample rate in Hz
+    sample_rate: f32,
+
+    /// Pass the signal through untouched
+    bypassed: bool,
+}
+
+impl DcBlocker {
+    /// Create an active blocker at [`DEFAULT_CUTOFF_HZ`]
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        let mut blocker = Self {
+            pole: 0.0,
+            x1: 0.0,
+            y1: 0.0,
+            cutoff_hz: DEFAULT_CUTOFF_HZ,
+            sample_rate,
+            bypassed: false,
+        };
+        blocker.update_pole();
+        blocker
+    }
+
+    /// Set sample rate (the corner frequency is kept)
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.update_pole();
+    }
+
+    /// Set the corner frequency in Hz (5-10 Hz keeps it below hearing)
+    pub fn set_cutoff_hz(&mut self, cutoff_hz: f32) {
+        self.cutoff_hz = cutoff_hz.max(0.0);
+        self.update_pole();
+    }
+
+    /// Bypass the filter
+    ///
+    /// Switching it back in starts from a clean state, so the output carries on
+    /// from the input and the offset then decays away instead of jumping.
+    pub fn set_bypassed(&mut self, bypassed: bool) {
+        if self.bypassed && !bypassed {
+            self.reset();
+        }
+        self.bypassed = bypassed;
+    }
+
+    /// Whether the filter is bypassed
+    #[must_use]
+    pub fn is_bypassed(&self) -> bool {
+        self.bypassed
+    }
+
+    /// Filter one sample
+    #[inline]
+    pub fn process(&mut self, input: f32) -> f32 {
+        if self.bypassed {
+            return input;
+        }
+        let output = input - self.x1 + self.pole * self.y1;
+        self.x1 = input;
+        self.y1 = output;
+        output
+    }
+
+    /// Clear the filter state
+    pub fn reset(&mut self) {
+        self.x1 = 0.0;
+        self.y1 = 0.0;
+    }
+
+    fn update_pole(&mut self) {
+        self.pole = (-TAU * self.cutoff_hz / self.sample_rate).exp();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    fn mean(samples: &[f32]) -> f32 {
+        #[allow(clippy::cast_precision_loss)] // Test buffer lengths
+        let len = samples.len() as f32;
+        samples.iter().sum::<f32>() / len
+    }
+
+    #[test]
+    fn test_removes_offset() {
+        let mut blocker = DcBlocker::new(SAMPLE_RATE);
+
+        // A 100 Hz sine riding on +0.3
+        #[allow(clippy::cast_precision_loss)] // Sample indices fit exactly
+        let signal: Vec<f32> = (0..96000u32)
+            .map(|i| 0.3 + (TAU * 100.0 * i as f32 / SAMPLE_RATE).sin() * 0.5)
+            .collect();
+        let output: Vec<f32> = signal.iter().map(|&x| blocker.process(x)).collect();
+
+        // After a second to settle, the last half second is centered on zero
+        let settled = &output[72000..];
+        assert!(mean(settled).abs() < 1e-3, "DC left: {}", mean(settled));
+
+        // and the sine itself comes through at full level
+        let peak = settled.iter().fold(0.0f32, |peak, x| peak.max(x.abs()));
+        assert!((peak - 0.5).abs() < 0.01, "Peak {peak}");
+    }
+
+    #[test]
+    fn test_bypass_passes_through() {
+        let mut blocker = DcBlocker::new(SAMPLE_RATE);
+        blocker.set_bypassed(true);
+        for _ in 0..1000 {
+            assert!((blocker.process(0.25) - 0.25).abs() < f32::EPSILON);
+        }
+
+        // Back in, it picks up from the input rather than jumping
+        blocker.set_bypassed(false);
+        assert!((blocker.process(0.25) - 0.25).abs() < f32::EPSILON);
+        let mut output = 0.0;
+        for _ in 0..48000 {
+            output = blocker.process(0.25);
+        }
+        assert!(output.abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_cutoff_sets_settling_time() {
+        let settle = |cutoff_hz: f32| {
+            let mut blocker = DcBlocker::new(SAMPLE_RATE);
+            blocker.set_cutoff_hz(cutoff_hz);
+            (1..48000).find(|_| blocker.process(1.0) < 0.5).unwrap_or(0)
+        };
+
+        // Half-life is ln(2) / (2π·fc) seconds: ~22 ms at 5 Hz
+        let slow: u32 = settle(5.0);
+        let fast: u32 = settle(10.0);
+        assert!((1000..1100).contains(&slow), "{slow} samples");
+        assert!(fast.abs_diff(slow / 2) <= 2, "{fast} samples");
+    }
+}
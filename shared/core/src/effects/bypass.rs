@@ -0,0 +1,176 @@
+//! Click-free bypass switching
+//!
+//! Switching an effect in or out instantly swaps one waveform for another, which
+//! is heard as a click whenever the two differ at that sample. Instead, the dry
+//! and processed signals are crossfaded over a few milliseconds.
+//!
+//! The fade uses equal-power gains (cos/sin of a quarter turn), so the level holds
+//! steady when dry and wet are uncorrelated (e.g. a phaser or heavy drive).
+//!
+//! # References
+//! - Equal-power law: `g_dry² + g_wet² = 1`
+
+use std::f32::consts::FRAC_PI_2;
+
+/// Default fade length in milliseconds
+pub const DEFAULT_FADE_MS: f32 = 10.0;
+
+/// Equal-power crossfade between a dry (bypassed) and wet (engaged) signal
+///
+/// # Real-time Safety
+/// - Two multiplies and one sin/cos pair per sample while fading, none when settled
+///
+/// # Example
+/// ```
+/// use shared_core::effects::BypassCrossfade;
+///
+/// let mut bypass = BypassCrossfade::new(48000.0, 10.0);
+/// bypass.set_engaged(true);
+///
+/// let dry = 0.5f32;
+/// let wet = dry.tanh();
+/// let output = bypass.process(dry, wet); // Starts fading towards `wet`
+/// ```
+#[derive(Debug, Clone)]
+pub struct BypassCrossfade {
+    /// Fade position: 0.0 = fully bypassed, 1.0 = fully engaged
+    position: f32,
+    /// Whether we are heading towards engaged
+    engaged: bool,
+    /// Position change per sample
+    step: f32,
+}
+
+impl BypassCrossfade {
+    /// Create a crossfade that starts fully bypassed
+    #[must_use]
+    pub fn new(sample_rate: f32, fade_ms: f32) -> Self {
+        let fade_samples = (fade_ms * 0.001 * sample_rate).max(1.0);
+        Self {
+            position: 0.0,
+            engaged: false,
+            step: 1.0 / fade_samples,
+        }
+    }
+
+    /// Start fading in (true) or out (false); no-op if already heading there
+    pub fn set_engaged(&mut self, engaged: bool) {
+        self.engaged = engaged;
+    }
+
+    /// Target state (true = engaged, even if still fading in)
+    #[must_use]
+    pub fn is_engaged(&self) -> bool {
+        self.engaged
+    }
+
+    /// Whether the wet signal is needed at all (engaged or still fading out)
+    ///
+    /// When this is false, `process` returns the dry signal unchanged, so callers
+    /// can skip running the effect entirely.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.engaged || self.position > 0.0
+    }
+
+    /// Whether a fade is in progress
+    #[must_use]
+    pub fn is_fading(&self) -> bool {
+        if self.engaged {
+            self.position < 1.0
+        } else {
+            self.position > 0.0
+        }
+    }
+
+    /// Jump straight to a state without fading (e.g. on plugin reset)
+    pub fn snap(&mut self, engaged: bool) {
+        self.engaged = engaged;
+        self.position = if engaged { 1.0 } else { 0.0 };
+    }
+
+    /// Mix one dry/wet pair and advance the fade
+    #[inline]
+    pub fn process(&mut self, dry: f32, wet: f32) -> f32 {
+        if !self.is_fading() {
+            return if self.engaged { wet } else { dry };
+        }
+
+        self.position = if self.engaged {
+            (self.position + self.step).min(1.0)
+        } else {
+            (self.position - self.step).max(0.0)
+        };
+
+        let (wet_gain, dry_gain) = (self.position * FRAC_PI_2).sin_cos();
+        dry * dry_gain + wet * wet_gain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    #[test]
+    fn test_starts_bypassed() {
+        let mut bypass = BypassCrossfade::new(SAMPLE_RATE, 10.0);
+        assert!(!bypass.is_active());
+        assert!((bypass.process(0.3, 0.9) - 0.3).abs() < 1e-7);
+    }
+
+    #[test]
+    fn test_fade_reaches_wet_after_fade_time() {
+        let mut bypass = BypassCrossfade::new(SAMPLE_RATE, 10.0);
+        bypass.set_engaged(true);
+
+        // 10 ms at 48 kHz = 480 samples
+        for _ in 0..480 {
+            bypass.process(0.0, 1.0);
+        }
+        assert!(!bypass.is_fading());
+        assert!((bypass.process(0.0, 1.0) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_fade_has_no_jumps() {
+        // Switching between two constant signals must ramp, not step
+        let mut bypass = BypassCrossfade::new(SAMPLE_RATE, 10.0);
+        let mut previous = bypass.process(-1.0, 1.0);
+
+        bypass.set_engaged(true);
+        for n in 0..2000 {
+            if n == 240 {
+                // Reverse mid-fade
+                bypass.set_engaged(false);
+            }
+            let output = bypass.process(-1.0, 1.0);
+            assert!((output - previous).abs() < 0.02, "Jump at sample {n}");
+            previous = output;
+        }
+    }
+
+    #[test]
+    fn test_equal_power_midpoint() {
+        let mut bypass = BypassCrossfade::new(SAMPLE_RATE, 10.0);
+        bypass.set_engaged(true);
+        for _ in 0..239 {
+            bypass.process(0.0, 0.0);
+        }
+
+        // Halfway through, both gains are ~0.707
+        let wet_only = bypass.clone().process(0.0, 1.0);
+        let dry_only = bypass.process(1.0, 0.0);
+        assert!((wet_only - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.01);
+        assert!((dry_only - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_snap_skips_fade() {
+        let mut bypass = BypassCrossfade::new(SAMPLE_RATE, 10.0);
+        bypass.snap(true);
+        assert!(!bypass.is_fading());
+        assert!((bypass.process(0.0, 1.0) - 1.0).abs() < 1e-7);
+    }
+}
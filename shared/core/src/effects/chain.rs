@@ -0,0 +1,458 @@
+//! Insert effect chain with reorderable, bypassable slots
+//!
+//! A chain owns a fixed array of effects (one per slot) plus a processing order.
+//! Plugins typically wrap their available effects in an enum that implements
+//! [`Effect`], so the chain stays statically dispatched and allocation-free while
+//! each slot can still hold a different kind of effect.
+//!
+//! Signal flow: input → order[0] → order[1] → ... → order[N - 1] → output
+//! (bypassed slots pass their input straight through)
+//!
+//! Every slot has its own dry/wet mix, and both per-slot and whole-chain bypass
+//! go through a [`BypassCrossfade`], so toggling them during playback never clicks.
+
+use super::bypass::{BypassCrossfade, DEFAULT_FADE_MS};
+
+/// A mono, sample-by-sample insert effect
+pub trait Effect {
+    /// Process one sample
+    fn process(&mut self, input: f32) -> f32;
+
+    /// Clear all internal state (delay lines, filter memory, LFO phase)
+    fn reset(&mut self);
+
+    /// Samples the effect keeps ringing after its input falls silent
+    ///
+    /// Effects whose filters settle within a few milliseconds can leave this at
+    /// zero; ones with feedback or delay lines should report their decay to
+    /// around -60 dB, so hosts don't suspend processing while they ring out.
+    fn tail_samples(&self) -> u32 {
+        0
+    }
+}
+
+/// Fixed-size chain of `N` effect slots
+///
+/// # Real-time Safety
+/// - Slots and order live in fixed arrays, no allocations
+/// - Reordering and bypass are plain array writes, safe to call every block
+/// - Fully bypassed slots (fade finished) are skipped, costing nothing
+///
+/// # Example
+/// ```
+/// use shared_core::effects::{Effect, EffectChain, Phaser, Waveshaper};
+///
+/// enum Insert {
+///     Drive(Waveshaper),
+///     Phaser(Phaser),
+/// }
+///
+/// impl Effect for Insert {
+///     fn process(&mut self, input: f32) -> f32 {
+///         match self {
+///             Insert::Drive(effect) => effect.process(input),
+///             Insert::Phaser(effect) => effect.process(input),
+///         }
+///     }
+///
+///     fn reset(&mut self) {
+///         match self {
+///             Insert::Drive(effect) => effect.reset(),
+///             Insert::Phaser(effect) => effect.reset(),
+///         }
+///     }
+/// }
+///
+/// let mut chain = EffectChain::new(
+///     [
+///         Insert::Drive(Waveshaper::new(48000.0)),
+///         Insert::Phaser(Phaser::new(48000.0)),
+///     ],
+///     48000.0,
+/// );
+/// chain.set_order([1, 0]); // Phaser into drive
+/// chain.set_mix(1, 0.5); // Half-wet phaser
+/// let output = chain.process(0.5);
+/// ```
+pub struct EffectChain<E: Effect, const N: usize> {
+    slots: [E; N],
+    /// Per-slot bypass fades
+    bypass: [BypassCrossfade; N],
+    /// Per-slot dry/wet mix (0.0 to 1.0)
+    mix: [f32; N],
+    /// Whole-chain bypass fade
+    chain_bypass: BypassCrossfade,
+    /// Slot indices in processing order (always a permutation of 0..N)
+    order: [usize; N],
+}
+
+impl<E: Effect, const N: usize> EffectChain<E, N> {
+    /// Create a chain processing the slots in array order, nothing bypassed
+    #[must_use]
+    pub fn new(slots: [E; N], sample_rate: f32) -> Self {
+        let engaged = || {
+            let mut fade = BypassCrossfade::new(sample_rate, DEFAULT_FADE_MS);
+            fade.snap(true);
+            fade
+        };
+
+        Self {
+            slots,
+            bypass: std::array::from_fn(|_| engaged()),
+            mix: [1.0; N],
+            chain_bypass: engaged(),
+            order: std::array::from_fn(|i| i),
+        }
+    }
+
+    /// Number of slots in the chain
+    #[must_use]
+    pub fn len(&self) -> usize {
+        N
+    }
+
+    /// Whether the chain has no slots
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// Set the processing order as a list of slot indices
+    ///
+    /// The order is normalized into a permutation so every slot runs exactly once:
+    /// out-of-range or repeated entries are replaced by the lowest slot index not
+    /// yet used. This keeps per-position "which effect goes here" parameters safe
+    /// even while the user is mid-way through swapping two positions.
+    pub fn set_order(&mut self, order: [usize; N]) {
+        let mut used = [false; N];
+        let mut normalized = [usize::MAX; N];
+
+        for (position, &slot) in order.iter().enumerate() {
+            if slot < N && !used[slot] {
+                used[slot] = true;
+                normalized[position] = slot;
+            }
+        }
+
+        for entry in &mut normalized {
+            if *entry == usize::MAX {
+                // There is always a free slot here: positions and slots are both N
+                let free = used.iter().position(|u| !u).unwrap_or(0);
+                used[free] = true;
+                *entry = free;
+            }
+        }
+
+        self.order = normalized;
+    }
+
+    /// Current processing order (slot indices)
+    #[must_use]
+    pub fn order(&self) -> [usize; N] {
+        self.order
+    }
+
+    /// Bypass or engage one slot with a short crossfade (out-of-range indices are ignored)
+    pub fn set_bypassed(&mut self, slot: usize, bypassed: bool) {
+        let (Some(fade), Some(effect)) = (self.bypass.get_mut(slot), self.slots.get_mut(slot))
+        else {
+            return;
+        };
+
+        if !bypassed && !fade.is_active() {
+            // The effect hasn't run since it faded out; don't fade in its stale tail
+            effect.reset();
+        }
+        fade.set_engaged(!bypassed);
+    }
+
+    /// Whether a slot is bypassed (or fading out towards bypass)
+    #[must_use]
+    pub fn is_bypassed(&self, slot: usize) -> bool {
+        self.bypass.get(slot).is_none_or(|fade| !fade.is_engaged())
+    }
+
+    /// Set one slot's dry/wet mix (0.0 to 1.0, out-of-range indices are ignored)
+    pub fn set_mix(&mut self, slot: usize, mix: f32) {
+        if let Some(value) = self.mix.get_mut(slot) {
+            *value = mix.clamp(0.0, 1.0);
+        }
+    }
+
+    /// One slot's dry/wet mix
+    #[must_use]
+    pub fn mix(&self, slot: usize) -> f32 {
+        self.mix.get(slot).copied().unwrap_or(0.0)
+    }
+
+    /// Bypass or engage the whole chain with a short crossfade
+    pub fn set_chain_bypassed(&mut self, bypassed: bool) {
+        if !bypassed && !self.chain_bypass.is_active() {
+            for effect in &mut self.slots {
+                effect.reset();
+            }
+        }
+        self.chain_bypass.set_engaged(!bypassed);
+    }
+
+    /// Whether the whole chain is bypassed (or fading out towards bypass)
+    #[must_use]
+    pub fn is_chain_bypassed(&self) -> bool {
+        !self.chain_bypass.is_engaged()
+    }
+
+    /// Effect held in a slot
+    #[must_use]
+    pub fn slot(&self, slot: usize) -> Option<&E> {
+        self.slots.get(slot)
+    }
+
+    /// Mutable access to the effect in a slot (for updating its settings)
+    pub fn slot_mut(&mut self, slot: usize) -> Option<&mut E> {
+        self.slots.get_mut(slot)
+    }
+
+    /// Mutable access to all slots, in slot (not processing) order
+    pub fn slots_mut(&mut self) -> impl Iterator<Item = &mut E> {
+        self.slots.iter_mut()
+    }
+
+    /// Process one sample through every active slot in order
+    #[inline]
+    pub fn process(&mut self, input: f32) -> f32 {
+        if !self.chain_bypass.is_active() {
+            return input;
+        }
+
+        let mut sample = input;
+        for &slot in &self.order {
+            let fade = &mut self.bypass[slot];
+            if fade.is_active() {
+                let dry = sample;
+                let processed = self.slots[slot].process(dry);
+                let wet = dry + (processed - dry) * self.mix[slot];
+                sample = fade.process(dry, wet);
+            }
+        }
+
+        self.chain_bypass.process(input, sample)
+    }
+
+    /// Samples the chain keeps ringing after its input falls silent
+    ///
+    /// Tails add up through the chain, since each slot rings on the one before
+    /// it. Bypassed slots (fade finished) and a bypassed chain add nothing.
+    #[must_use]
+    pub fn tail_samples(&self) -> u32 {
+        if !self.chain_bypass.is_active() {
+            return 0;
+        }
+
+        self.slots
+            .iter()
+            .zip(&self.bypass)
+            .filter(|(_, fade)| fade.is_active())
+            .map(|(effect, _)| effect.tail_samples())
+            .fold(0, u32::saturating_add)
+    }
+
+    /// Reset every slot, bypassed or not, and finish any bypass fades instantly
+    pub fn reset(&mut self) {
+        for slot in &mut self.slots {
+            slot.reset();
+        }
+        for fade in &mut self.bypass {
+            fade.snap(fade.is_engaged());
+        }
+        self.chain_bypass.snap(self.chain_bypass.is_engaged());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    /// Enough samples for any bypass fade to finish
+    const FADE_SAMPLES: usize = 1000;
+
+    /// Test effect: y = x * gain + offset, so order is observable
+    struct Affine {
+        gain: f32,
+        offset: f32,
+        resets: usize,
+        tail: u32,
+    }
+
+    impl Affine {
+        fn new(gain: f32, offset: f32) -> Self {
+            Self {
+                gain,
+                offset,
+                resets: 0,
+                tail: 0,
+            }
+        }
+    }
+
+    impl Effect for Affine {
+        fn process(&mut self, input: f32) -> f32 {
+            input * self.gain + self.offset
+        }
+
+        fn reset(&mut self) {
+            self.resets += 1;
+        }
+
+        fn tail_samples(&self) -> u32 {
+            self.tail
+        }
+    }
+
+    #[test]
+    fn test_default_order_is_slot_order() {
+        let mut chain =
+            EffectChain::new([Affine::new(2.0, 0.0), Affine::new(1.0, 1.0)], SAMPLE_RATE);
+        assert_eq!(chain.order(), [0, 1]);
+        // (1 * 2) + 1
+        assert!((chain.process(1.0) - 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_reordering_changes_result() {
+        let mut chain =
+            EffectChain::new([Affine::new(2.0, 0.0), Affine::new(1.0, 1.0)], SAMPLE_RATE);
+        chain.set_order([1, 0]);
+        // (1 + 1) * 2
+        assert!((chain.process(1.0) - 4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_bypassed_slot_passes_through() {
+        let mut chain =
+            EffectChain::new([Affine::new(2.0, 0.0), Affine::new(1.0, 1.0)], SAMPLE_RATE);
+        chain.set_bypassed(0, true);
+        assert!(chain.is_bypassed(0));
+        for _ in 0..FADE_SAMPLES {
+            chain.process(1.0);
+        }
+        assert!((chain.process(1.0) - 2.0).abs() < 1e-6);
+
+        chain.set_bypassed(1, true);
+        for _ in 0..FADE_SAMPLES {
+            chain.process(1.0);
+        }
+        assert!(
+            (chain.process(1.0) - 1.0).abs() < 1e-6,
+            "Fully bypassed chain is identity"
+        );
+    }
+
+    #[test]
+    fn test_order_is_normalized_to_permutation() {
+        let mut chain = EffectChain::new(
+            [
+                Affine::new(1.0, 0.0),
+                Affine::new(1.0, 0.0),
+                Affine::new(1.0, 0.0),
+            ],
+            SAMPLE_RATE,
+        );
+
+        chain.set_order([2, 2, 0]);
+        assert_eq!(
+            chain.order(),
+            [2, 1, 0],
+            "Duplicate should take the free slot"
+        );
+
+        chain.set_order([7, 0, 0]);
+        assert_eq!(
+            chain.order(),
+            [1, 0, 2],
+            "Out-of-range entries should be filled in"
+        );
+    }
+
+    #[test]
+    fn test_reset_reaches_every_slot() {
+        let mut chain =
+            EffectChain::new([Affine::new(1.0, 0.0), Affine::new(1.0, 0.0)], SAMPLE_RATE);
+        chain.set_bypassed(1, true);
+        chain.reset();
+
+        assert!(chain.slots_mut().all(|slot| slot.resets == 1));
+    }
+
+    #[test]
+    fn test_bypass_toggle_is_click_free() {
+        // Slot 0 adds a large DC offset; switching it in or out must ramp
+        let mut chain = EffectChain::new([Affine::new(1.0, 1.0)], SAMPLE_RATE);
+        let mut previous = chain.process(0.0);
+
+        for n in 0..4000 {
+            match n {
+                500 => chain.set_bypassed(0, true),
+                1500 | 3500 => chain.set_chain_bypassed(false),
+                2500 => chain.set_chain_bypassed(true),
+                3000 => chain.set_bypassed(0, false),
+                _ => {}
+            }
+            let output = chain.process(0.0);
+            assert!((output - previous).abs() < 0.01, "Jump at sample {n}");
+            previous = output;
+        }
+    }
+
+    #[test]
+    fn test_slot_mix() {
+        let mut chain = EffectChain::new([Affine::new(3.0, 0.0)], SAMPLE_RATE);
+        chain.set_mix(0, 0.5);
+        // Halfway between dry (1) and wet (3)
+        assert!((chain.process(1.0) - 2.0).abs() < 1e-6);
+
+        chain.set_mix(0, 0.0);
+        assert!((chain.process(1.0) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_chain_bypass() {
+        let mut chain =
+            EffectChain::new([Affine::new(2.0, 0.0), Affine::new(2.0, 0.0)], SAMPLE_RATE);
+        chain.set_chain_bypassed(true);
+        assert!(chain.is_chain_bypassed());
+        for _ in 0..FADE_SAMPLES {
+            chain.process(1.0);
+        }
+        assert!((chain.process(1.0) - 1.0).abs() < 1e-6);
+
+        chain.set_chain_bypassed(false);
+        assert!(
+            chain.slots_mut().all(|slot| slot.resets == 1),
+            "Re-engaging a silent chain should clear stale state"
+        );
+    }
+
+    #[test]
+    fn test_tail_sums_active_slots() {
+        let mut chain =
+            EffectChain::new([Affine::new(1.0, 0.0), Affine::new(1.0, 0.0)], SAMPLE_RATE);
+        for (slot, tail) in chain.slots_mut().zip([100, 250]) {
+            slot.tail = tail;
+        }
+        assert_eq!(chain.tail_samples(), 350);
+
+        // A slot fading out still rings; once bypassed it doesn't
+        chain.set_bypassed(1, true);
+        assert_eq!(chain.tail_samples(), 350);
+        for _ in 0..FADE_SAMPLES {
+            chain.process(1.0);
+        }
+        assert_eq!(chain.tail_samples(), 100);
+
+        chain.set_chain_bypassed(true);
+        chain.reset();
+        assert_eq!(chain.tail_samples(), 0);
+    }
+}
@@ -0,0 +1,14 @@
+//! Audio effects shared between plugins
+//!
+//! Each effect is a self-contained, allocation-free processor with plain setters,
+//! so plugins only have to map their parameters onto it.
+
+pub mod bypass;
+pub mod chain;
+pub mod phaser;
+pub mod waveshaper;
+
+pub use bypass::BypassCrossfade;
+pub use chain::{Effect, EffectChain};
+pub use phaser::Phaser;
+pub use waveshaper::Waveshaper;
@@ -0,0 +1,381 @@
+//! Phaser effect
+//!
+//! A chain of all-pass filters shifts the phase of the signal without changing its
+//! magnitude. Mixing that phase-shifted copy back with the dry signal cancels the
+//! frequencies where the two are 180° apart, producing a row of notches. An LFO sweeps
+//! the all-pass frequency so the notches move, giving the classic "swoosh".
+//!
+//! # References
+//! - Smith, "Physical Audio Signal Processing", Phasing with 2nd-order allpass filters
+//! - Each 2nd-order all-pass contributes -360° total, passing -180° at its center frequency
+
+use crate::biquad::{Biquad, BiquadCoefficients};
+use crate::effects::Effect;
+use std::f32::consts::PI;
+
+/// Minimum number of all-pass stages
+pub const MIN_STAGES: usize = 4;
+
+/// Maximum number of all-pass stages (pre-allocated)
+pub const MAX_STAGES: usize = 8;
+
+/// Lowest frequency the sweep reaches at full depth (Hz)
+pub const SWEEP_MIN_HZ: f32 = 200.0;
+
+/// Highest frequency the sweep reaches at full depth (Hz)
+pub const SWEEP_MAX_HZ: f32 = 4000.0;
+
+/// All-pass coefficients are recomputed every this many samples
+///
+/// The LFO moves slowly, so updating the trig-heavy designs at a reduced rate
+/// is inaudible and saves most of the cost.
+const CONTROL_INTERVAL: u32 = 16;
+
+/// Q of each all-pass stage (wider = smoother notches)
+const STAGE_Q: f32 = 0.707;
+
+/// Level the feedback tail has to fall to before it counts as over (-60 dB)
+const TAIL_FLOOR: f32 = 0.001;
+
+/// LFO-swept all-pass phaser
+///
+/// # Real-time Safety
+/// - All stages pre-allocated (fixed array of `MAX_STAGES`)
+/// - No allocations in `process()`
+///
+/// # Example
+/// ```
+/// use shared_core::effects::Phaser;
+///
+/// let mut phaser = Phaser::new(48000.0);
+/// phaser.set_rate_hz(0.5);
+/// phaser.set_mix(0.5);
+/// let output = phaser.process(0.25);
+/// ```
+pub struct Phaser {
+    /// All-pass stages (only the first `num_stages` are used)
+    stages: [Biquad; MAX_STAGES],
+
+    /// Number of active stages
+    num_stages: usize,
+
+    /// Sample rate in Hz
+    sample_rate: f32,
+
+    /// LFO phase (0.0 to 1.0)
+    lfo_phase: f32,
+
+    /// LFO rate in Hz
+    rate_hz: f32,
+
+    /// Sweep depth (0.0 = fixed at center, 1.0 = full sweep range)
+    depth: f32,
+
+    /// Feedback from the end of the chain back into its input (-0.95 to 0.95)
+    feedback: f32,
+
+    /// Dry/wet mix (0.5 gives the deepest notches)
+    mix: f32,
+
+    /// Last wet output (for feedback)
+    last_wet: f32,
+
+    /// Samples until the next coefficient update
+    control_counter: u32,
+}
+
+impl Phaser {
+    /// Create a new phaser
+    ///
+    /// # Default Settings
+    /// - Stages: 4
+    /// - Rate: 0.5 Hz
+    /// - Depth: 70%
+    /// - Feedback: 0%
+    /// - Mix: 50%
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            stages: std::array::from_fn(|_| Biquad::default()),
+            num_stages: MIN_STAGES,
+            sample_rate,
+            lfo_phase: 0.0,
+            rate_hz: 0.5,
+            depth: 0.7,
+            feedback: 0.0,
+            mix: 0.5,
+            last_wet: 0.0,
+            control_counter: 0,
+        }
+    }
+
+    /// Set the number of all-pass stages (clamped to 4-8)
+    pub fn set_stages(&mut self, stages: usize) {
+        self.num_stages = stages.clamp(MIN_STAGES, MAX_STAGES);
+    }
+
+    /// Set LFO rate in Hz
+    pub fn set_rate_hz(&mut self, rate_hz: f32) {
+        self.rate_hz = rate_hz.max(0.0);
+    }
+
+    /// Set sweep depth (0.0 to 1.0)
+    pub fn set_depth(&mut self, depth: f32) {
+        self.depth = depth.clamp(0.0, 1.0);
+    }
+
+    /// Set feedback amount (-0.95 to 0.95)
+    pub fn set_feedback(&mut self, feedback: f32) {
+        self.feedback = feedback.clamp(-0.95, 0.95);
+    }
+
+    /// Set dry/wet mix (0.0 to 1.0)
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix = mix.clamp(0.0, 1.0);
+    }
+
+    /// Process one sample
+    #[inline]
+    pub fn process(&mut self, input: f32) -> f32 {
+        if self.control_counter == 0 {
+            self.update_stages();
+            self.control_counter = CONTROL_INTERVAL;
+        }
+        self.control_counter -= 1;
+
+        // Advance the LFO once per sample so the rate is exact
+        self.lfo_phase += self.rate_hz / self.sample_rate;
+        if self.lfo_phase >= 1.0 {
+            self.lfo_phase -= self.lfo_phase.floor();
+        }
+
+        let mut wet = input + self.feedback * self.last_wet;
+        for stage in &mut self.stages[..self.num_stages] {
+            wet = stage.process(wet);
+        }
+        self.last_wet = wet;
+
+        input * (1.0 - self.mix) + wet * self.mix
+    }
+
+    /// Clear all filter and LFO state
+    pub fn reset(&mut self) {
+        for stage in &mut self.stages {
+            stage.reset();
+        }
+        self.lfo_phase = 0.0;
+        self.last_wet = 0.0;
+        self.control_counter = 0;
+    }
+
+    /// Current all-pass center frequency (Hz) for the LFO position
+    ///
+    /// Sweeps exponentially so the notches move evenly in pitch.
+    #[must_use]
+    pub fn sweep_frequency(&self) -> f32 {
+        let lfo = (2.0 * PI * self.lfo_phase).sin();
+        let position = 0.5 + 0.5 * self.depth * lfo;
+        SWEEP_MIN_HZ * (SWEEP_MAX_HZ / SWEEP_MIN_HZ).powf(position)
+    }
+
+    /// Samples the phaser keeps ringing after its input falls silent
+    ///
+    /// A conservative estimate to -60 dB: every trip through the stages is taken
+    /// to be as slow as at the bottom of the sweep, where a 2nd-order all-pass
+    /// delays by `2Q / (π·f)`, and the feedback path repeats the trip until it
+    /// has decayed.
+    #[must_use]
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )] // A few seconds of samples at most
+    pub fn tail_samples(&self) -> u32 {
+        let trip_seconds = self.num_stages as f32 * 2.0 * STAGE_Q / (PI * SWEEP_MIN_HZ);
+        let feedback = self.feedback.abs();
+        let trips = if feedback > 0.0 {
+            1.0 + TAIL_FLOOR.ln() / feedback.ln()
+        } else {
+            1.0
+        };
+
+        (trip_seconds * trips * self.sample_rate).ceil() as u32
+    }
+
+    /// Recompute all-pass coefficients for the current LFO position
+    fn update_stages(&mut self) {
+        let coefficients =
+            BiquadCoefficients::all_pass(self.sample_rate, self.sweep_frequency(), STAGE_Q);
+        for stage in &mut self.stages {
+            stage.set_coefficients(coefficients);
+        }
+    }
+}
+
+impl Effect for Phaser {
+    #[inline]
+    fn process(&mut self, input: f32) -> f32 {
+        Phaser::process(self, input)
+    }
+
+    fn reset(&mut self) {
+        Phaser::reset(self);
+    }
+
+    fn tail_samples(&self) -> u32 {
+        Phaser::tail_samples(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    // Steady-state gain (dB) of a sine through a static phaser
+    #[allow(clippy::cast_precision_loss)] // Short test lengths
+    fn static_gain_db(phaser: &mut Phaser, frequency: f32) -> f32 {
+        phaser.reset();
+        let omega = 2.0 * PI * frequency / SAMPLE_RATE;
+        let mut energy_in = 0.0f32;
+        let mut energy_out = 0.0f32;
+        for n in 0..24000 {
+            let input = (omega * n as f32).sin();
+            let output = phaser.process(input);
+            if n > 12000 {
+                energy_in += input * input;
+                energy_out += output * output;
+            }
+        }
+        10.0 * (energy_out / energy_in).log10()
+    }
+
+    fn static_phaser() -> Phaser {
+        let mut phaser = Phaser::new(SAMPLE_RATE);
+        phaser.set_rate_hz(0.0);
+        phaser.set_depth(0.0);
+        phaser.set_mix(0.5);
+        phaser
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)] // Short test lengths
+    fn test_dry_mix_is_transparent() {
+        let mut phaser = Phaser::new(SAMPLE_RATE);
+        phaser.set_mix(0.0);
+
+        for n in 0..1000 {
+            let input = (n as f32 * 0.05).sin();
+            assert!((phaser.process(input) - input).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_static_phaser_creates_notches() {
+        let mut phaser = static_phaser();
+
+        // Sweep a range of frequencies and look for deep cancellation
+        let gains: Vec<f32> = (0..60)
+            .map(|i| 50.0 * 1.1f32.powi(i))
+            .filter(|f| *f < 20000.0)
+            .map(|f| static_gain_db(&mut phaser, f))
+            .collect();
+
+        let min = gains.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = gains.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        assert!(min < -15.0, "Expected a notch, deepest point was {min} dB");
+        assert!(
+            max > -1.0,
+            "Unaffected frequencies should pass, max was {max} dB"
+        );
+    }
+
+    #[test]
+    fn test_more_stages_more_notches() {
+        fn count_notches(phaser: &mut Phaser) -> usize {
+            let gains: Vec<f32> = (0..200)
+                .map(|i| 20.0 * 1.035f32.powi(i))
+                .filter(|f| *f < 20000.0)
+                .map(|f| static_gain_db(phaser, f))
+                .collect();
+            gains
+                .windows(3)
+                .filter(|w| w[1] < w[0] && w[1] < w[2] && w[1] < -6.0)
+                .count()
+        }
+
+        let mut four = static_phaser();
+        four.set_stages(4);
+        let mut eight = static_phaser();
+        eight.set_stages(8);
+
+        assert!(count_notches(&mut eight) > count_notches(&mut four));
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Short test lengths
+    fn test_lfo_sweeps_frequency() {
+        let mut phaser = Phaser::new(SAMPLE_RATE);
+        phaser.set_rate_hz(1.0);
+        phaser.set_depth(1.0);
+
+        let mut min = f32::INFINITY;
+        let mut max = f32::NEG_INFINITY;
+        for _ in 0..SAMPLE_RATE as usize {
+            phaser.process(0.0);
+            let frequency = phaser.sweep_frequency();
+            min = min.min(frequency);
+            max = max.max(frequency);
+        }
+
+        assert!(
+            min < SWEEP_MIN_HZ * 1.05,
+            "Sweep should reach the bottom, got {min}"
+        );
+        assert!(
+            max > SWEEP_MAX_HZ * 0.95,
+            "Sweep should reach the top, got {max}"
+        );
+    }
+
+    #[test]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )] // Short test lengths
+    fn test_high_feedback_is_stable() {
+        let mut phaser = Phaser::new(SAMPLE_RATE);
+        phaser.set_stages(8);
+        phaser.set_feedback(1.5); // Clamped to 0.95
+        phaser.set_rate_hz(5.0);
+        phaser.set_depth(1.0);
+
+        for n in 0..(SAMPLE_RATE as usize * 2) {
+            let input = if n % 1000 == 0 { 1.0 } else { 0.0 };
+            let output = phaser.process(input);
+            assert!(output.is_finite() && output.abs() < 100.0);
+        }
+    }
+
+    #[test]
+    fn test_tail_covers_feedback_decay() {
+        let mut phaser = Phaser::new(SAMPLE_RATE);
+        phaser.set_stages(8);
+        phaser.set_depth(0.0);
+        let dry_tail = phaser.tail_samples();
+
+        phaser.set_feedback(-0.9);
+        let tail = phaser.tail_samples();
+        assert!(tail > dry_tail * 10, "Feedback should lengthen the tail");
+
+        let peak = (0..tail)
+            .map(|n| phaser.process(if n == 0 { 1.0 } else { 0.0 }).abs())
+            .fold(0.0f32, f32::max);
+        let after = (0..1000)
+            .map(|_| phaser.process(0.0).abs())
+            .fold(0.0f32, f32::max);
+        assert!(after < peak * TAIL_FLOOR, "{after} after a peak of {peak}");
+    }
+}
@@ -0,0 +1,355 @@
+//! Waveshaper distortion effect
+//!
+//! A waveshaper maps each input sample through a static curve. Pushing the signal
+//! harder into the curve (drive) bends more of the waveform, adding harmonics:
+//! symmetric curves add odd harmonics, asymmetric curves add even harmonics too.
+//!
+//! Signal flow: [tone (pre)] → drive → curve (oversampled) → [tone (post)] → mix
+//!
+//! # References
+//! - Pakarinen & Yeh, "A Review of Digital Techniques for Modeling Vacuum-Tube Guitar
+//!   Amplifiers" (2009)
+//! - musicdsp.org, "Foldback Distortion"
+
+use crate::biquad::{Biquad, BiquadCoefficients};
+use crate::effects::Effect;
+use crate::oversampling::{Oversampler, OversamplingFactor};
+
+/// Shaping curve
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaperCurve {
+    /// Smooth symmetric saturation (odd harmonics)
+    Tanh,
+    /// Brick-wall clipping at ±1 (harsh, lots of high harmonics)
+    HardClip,
+    /// Signal above ±1 is reflected back down (metallic, synth-y)
+    Foldback,
+    /// Soft positive half, harder negative half, like a diode clipper (even harmonics)
+    AsymmetricDiode,
+}
+
+/// Where the tone filter sits relative to the curve
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TonePosition {
+    /// Filter before shaping - darker input, distortion stays bright
+    Pre,
+    /// Filter after shaping - tames the fizz of the added harmonics
+    Post,
+}
+
+/// Apply a shaping curve to one sample
+#[inline]
+#[must_use]
+pub fn shape(curve: ShaperCurve, x: f32) -> f32 {
+    match curve {
+        ShaperCurve::Tanh => x.tanh(),
+        ShaperCurve::HardClip => x.clamp(-1.0, 1.0),
+        // Triangle-fold: identity on [-1, 1], reflected at every boundary beyond it
+        ShaperCurve::Foldback => ((x - 1.0).rem_euclid(4.0) - 2.0).abs() - 1.0,
+        // Both halves have unit slope at zero; negative side saturates at -0.5
+        ShaperCurve::AsymmetricDiode => {
+            if x >= 0.0 {
+                1.0 - (-x).exp()
+            } else {
+                -0.5 * (1.0 - (2.0 * x).exp())
+            }
+        }
+    }
+}
+
+/// Waveshaper settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WaveshaperSettings {
+    pub curve: ShaperCurve,
+    /// Input gain into the curve in dB
+    pub drive_db: f32,
+    /// Tone low-pass cutoff in Hz
+    pub tone_hz: f32,
+    pub tone_position: TonePosition,
+    /// Dry/wet mix (0.0 to 1.0)
+    pub mix: f32,
+    pub oversampling: OversamplingFactor,
+}
+
+impl Default for WaveshaperSettings {
+    fn default() -> Self {
+        Self {
+            curve: ShaperCurve::Tanh,
+            drive_db: 12.0,
+            tone_hz: 8000.0,
+            tone_position: TonePosition::Post,
+            mix: 1.0,
+            oversampling: OversamplingFactor::X2,
+        }
+    }
+}
+
+/// Waveshaper with drive, tone filter and dry/wet mix
+///
+/// # Real-time Safety
+/// - Fixed state (tone biquad, oversampler filters), no allocations
+///
+/// # Example
+/// ```
+/// use shared_core::effects::waveshaper::{ShaperCurve, Waveshaper, WaveshaperSettings};
+///
+/// let mut shaper = Waveshaper::new(48000.0);
+/// shaper.set_settings(WaveshaperSettings {
+///     curve: ShaperCurve::Foldback,
+///     ..WaveshaperSettings::default()
+/// });
+/// let output = shaper.process(0.5);
+/// ```
+pub struct Waveshaper {
+    settings: WaveshaperSettings,
+    /// Linear drive gain derived from `drive_db`
+    drive_gain: f32,
+    tone: Biquad,
+    oversampler: Oversampler,
+    sample_rate: f32,
+}
+
+impl Waveshaper {
+    /// Create a new waveshaper with default settings
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        let settings = WaveshaperSettings::default();
+        let mut shaper = Self {
+            settings,
+            drive_gain: 1.0,
+            tone: Biquad::default(),
+            oversampler: Oversampler::new(sample_rate, settings.oversampling),
+            sample_rate,
+        };
+        shaper.apply_settings();
+        shaper
+    }
+
+    /// Apply new settings (filters only recomputed when something changed)
+    pub fn set_settings(&mut self, settings: WaveshaperSettings) {
+        if settings != self.settings {
+            self.settings = settings;
+            self.apply_settings();
+        }
+    }
+
+    /// Current settings
+    #[must_use]
+    pub fn settings(&self) -> WaveshaperSettings {
+        self.settings
+    }
+
+    /// Process one sample
+    #[inline]
+    pub fn process(&mut self, input: f32) -> f32 {
+        let pre = match self.settings.tone_position {
+            TonePosition::Pre => self.tone.process(input),
+            TonePosition::Post => input,
+        };
+
+        let curve = self.settings.curve;
+        let drive = self.drive_gain;
+        let shaped = self.oversampler.process(pre, |x| shape(curve, x * drive));
+
+        let wet = match self.settings.tone_position {
+            TonePosition::Pre => shaped,
+            TonePosition::Post => self.tone.process(shaped),
+        };
+
+        input + (wet - input) * self.settings.mix
+    }
+
+    /// Clear filter state
+    pub fn reset(&mut self) {
+        self.tone.reset();
+        self.oversampler.reset();
+    }
+
+    fn apply_settings(&mut self) {
+        self.drive_gain = 10.0f32.powf(self.settings.drive_db / 20.0);
+        self.tone.set_coefficients(BiquadCoefficients::low_pass(
+            self.sample_rate,
+            self.settings.tone_hz,
+            0.707,
+        ));
+        self.oversampler.set_factor(self.settings.oversampling);
+    }
+}
+
+impl Effect for Waveshaper {
+    #[inline]
+    fn process(&mut self, input: f32) -> f32 {
+        Waveshaper::process(self, input)
+    }
+
+    fn reset(&mut self) {
+        Waveshaper::reset(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    // Goertzel magnitude of one frequency bin
+    #[allow(clippy::cast_precision_loss)] // Short test lengths
+    fn goertzel(samples: &[f32], frequency: f32) -> f32 {
+        let coeff = 2.0 * (2.0 * PI * frequency / SAMPLE_RATE).cos();
+        let (mut s1, mut s2) = (0.0f32, 0.0f32);
+        for &x in samples {
+            let s0 = x + coeff * s1 - s2;
+            s2 = s1;
+            s1 = s0;
+        }
+        (s1 * s1 + s2 * s2 - coeff * s1 * s2).max(0.0).sqrt() / samples.len() as f32
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)] // Small test inputs
+    fn test_curves_are_bounded() {
+        for curve in [
+            ShaperCurve::Tanh,
+            ShaperCurve::HardClip,
+            ShaperCurve::Foldback,
+            ShaperCurve::AsymmetricDiode,
+        ] {
+            for i in -1000..=1000 {
+                let x = i as f32 * 0.05; // -50 to +50
+                let y = shape(curve, x);
+                assert!(y.is_finite() && y.abs() <= 1.0, "{curve:?}({x}) = {y}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_curves_are_near_linear_for_small_signals() {
+        for curve in [
+            ShaperCurve::Tanh,
+            ShaperCurve::HardClip,
+            ShaperCurve::Foldback,
+            ShaperCurve::AsymmetricDiode,
+        ] {
+            assert!(
+                (shape(curve, 0.01) - 0.01).abs() < 0.001,
+                "{curve:?} slope at zero"
+            );
+            assert!(shape(curve, 0.0).abs() < 1e-7);
+        }
+    }
+
+    #[test]
+    fn test_foldback_reflects() {
+        assert!((shape(ShaperCurve::Foldback, 1.5) - 0.5).abs() < 1e-6);
+        assert!((shape(ShaperCurve::Foldback, -1.5) + 0.5).abs() < 1e-6);
+        assert!((shape(ShaperCurve::Foldback, 3.0) + 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_diode_is_asymmetric() {
+        let positive = shape(ShaperCurve::AsymmetricDiode, 3.0);
+        let negative = shape(ShaperCurve::AsymmetricDiode, -3.0);
+        assert!(
+            positive > -negative * 1.5,
+            "Negative half should clip harder"
+        );
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)] // Short test lengths
+    fn test_zero_mix_is_transparent() {
+        let mut shaper = Waveshaper::new(SAMPLE_RATE);
+        shaper.set_settings(WaveshaperSettings {
+            mix: 0.0,
+            drive_db: 36.0,
+            ..WaveshaperSettings::default()
+        });
+
+        for n in 0..1000 {
+            let input = (n as f32 * 0.03).sin();
+            assert!((shaper.process(input) - input).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)] // Short test lengths
+    fn test_drive_adds_harmonics() {
+        let mut shaper = Waveshaper::new(SAMPLE_RATE);
+        shaper.set_settings(WaveshaperSettings {
+            drive_db: 24.0,
+            tone_hz: 20000.0,
+            ..WaveshaperSettings::default()
+        });
+
+        let omega = 2.0 * PI * 500.0 / SAMPLE_RATE;
+        let output: Vec<f32> = (0..9600)
+            .map(|n| shaper.process(0.5 * (omega * n as f32).sin()))
+            .skip(4800)
+            .collect();
+
+        let fundamental = goertzel(&output, 500.0);
+        let third = goertzel(&output, 1500.0);
+        assert!(
+            third > fundamental * 0.1,
+            "Tanh drive should add a strong 3rd harmonic"
+        );
+    }
+
+    #[test]
+    fn test_oversampling_reduces_aliasing() {
+        // A 7 kHz sine through a hard clipper makes a 35 kHz 5th harmonic,
+        // which aliases to 13 kHz at 48 kHz without oversampling.
+        #[allow(clippy::cast_precision_loss)] // Short test lengths
+        fn alias_level(oversampling: OversamplingFactor) -> f32 {
+            let mut shaper = Waveshaper::new(SAMPLE_RATE);
+            shaper.set_settings(WaveshaperSettings {
+                curve: ShaperCurve::HardClip,
+                drive_db: 20.0,
+                tone_hz: 23000.0,
+                oversampling,
+                ..WaveshaperSettings::default()
+            });
+
+            let omega = 2.0 * PI * 7000.0 / SAMPLE_RATE;
+            let output: Vec<f32> = (0..19200)
+                .map(|n| shaper.process(0.5 * (omega * n as f32).sin()))
+                .skip(9600)
+                .collect();
+            goertzel(&output, 13000.0)
+        }
+
+        let naive = alias_level(OversamplingFactor::None);
+        let oversampled = alias_level(OversamplingFactor::X4);
+        assert!(
+            oversampled < naive * 0.25,
+            "4x oversampling should cut the alias by >12 dB: {naive} vs {oversampled}"
+        );
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)] // Short test lengths
+    fn test_tone_position_changes_output() {
+        let render = |tone_position| {
+            let mut shaper = Waveshaper::new(SAMPLE_RATE);
+            shaper.set_settings(WaveshaperSettings {
+                tone_hz: 1000.0,
+                tone_position,
+                ..WaveshaperSettings::default()
+            });
+            let omega = 2.0 * PI * 300.0 / SAMPLE_RATE;
+            (0..4800)
+                .map(|n| shaper.process((omega * n as f32).sin()))
+                .collect::<Vec<f32>>()
+        };
+
+        let pre = render(TonePosition::Pre);
+        let post = render(TonePosition::Post);
+        let diff: f32 = pre.iter().zip(&post).map(|(a, b)| (a - b).abs()).sum();
+        assert!(
+            diff > 1.0,
+            "Pre and post tone placement should sound different"
+        );
+    }
+}
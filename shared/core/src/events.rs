@@ -0,0 +1,325 @@
+//! Timestamped note events and a lock-free queue to pass them between threads
+//!
+//! Notes and controllers that don't come from the host's MIDI input (an
+//! on-screen keyboard, the computer keyboard, a MIDI file rendered offline, a
+//! sequencer) travel as [`TimedEvent`]s: a note-on, note-off or control change
+//! at a sample offset. Events made on another thread (the GUI) cross to the
+//! audio thread through an [`EventQueue`], a bounded single-producer,
+//! single-consumer ring the audio thread drains without locking or allocating.
+//!
+//! Each slot holds an event packed into two `AtomicU64`s, so the queue needs no
+//! unsafe code: the producer fills a slot, then publishes it with a release
+//! store of the write count; the consumer's acquire load of that count makes
+//! the slot's contents visible. Any other small record that implements
+//! [`Packed`] can travel through the same queue.
+//!
+//! # References
+//! - Lamport, "Specifying Concurrent Program Modules" (1983): the
+//!   single-producer, single-consumer ring buffer
+//! - MIDI 1.0: note on, note off and control change messages
+
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// A note or controller message
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    /// Velocity 0.0 - 1.0
+    NoteOn {
+        channel: u8,
+        note: u8,
+        velocity: f32,
+    },
+    /// Release velocity 0.0 - 1.0
+    NoteOff {
+        channel: u8,
+        note: u8,
+        velocity: f32,
+    },
+    /// Control change, value 0.0 - 1.0
+    Cc { channel: u8, cc: u8, value: f32 },
+}
+
+/// A record that fits in two words, so it can travel through an [`EventQueue`]
+pub trait Packed: Copy {
+    /// Pack into two words
+    fn pack(self) -> [u64; 2];
+
+    /// Unpack from [`Packed::pack`]'s words
+    fn unpack(words: [u64; 2]) -> Self;
+}
+
+/// An event at a sample offset
+///
+/// The offset counts from the start of the block the event is played in, or
+/// from the start of an offline render.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimedEvent {
+    pub timing: u32,
+    pub event: Event,
+}
+
+impl TimedEvent {
+    /// Event at sample offset `timing`
+    #[must_use]
+    pub fn new(timing: u32, event: Event) -> Self {
+        Self { timing, event }
+    }
+}
+
+impl Packed for TimedEvent {
+    /// Pack into two words: timing, kind, channel and number, then the value
+    fn pack(self) -> [u64; 2] {
+        let (kind, channel, number, value) = match self.event {
+            Event::NoteOn {
+                channel,
+                note,
+                velocity,
+            } => (0, channel, note, velocity),
+            Event::NoteOff {
+                channel,
+                note,
+                velocity,
+            } => (1, channel, note, velocity),
+            Event::Cc { channel, cc, value } => (2, channel, cc, value),
+        };
+        let header = u64::from(self.timing)
+            | kind << 32
+            | u64::from(channel & 0x0F) << 34
+            | u64::from(number & 0x7F) << 38;
+        [header, u64::from(value.to_bits())]
+    }
+
+    /// Unpack from [`TimedEvent::pack`](Packed::pack)'s words
+    fn unpack([header, value]: [u64; 2]) -> Self {
+        #[allow(clippy::cast_possible_truncation)] // Each field is masked to its width
+        let (timing, channel, number, value) = (
+            header as u32,
+            ((header >> 34) & 0x0F) as u8,
+            ((header >> 38) & 0x7F) as u8,
+            f32::from_bits(value as u32),
+        );
+        let event = match (header >> 32) & 0x03 {
+            0 => Event::NoteOn {
+                channel,
+                note: number,
+                velocity: value,
+            },
+            1 => Event::NoteOff {
+                channel,
+                note: number,
+                velocity: value,
+            },
+            _ => Event::Cc {
+                channel,
+                cc: number,
+                value,
+            },
+        };
+        Self { timing, event }
+    }
+}
+
+/// Bounded lock-free queue of timed events (or other [`Packed`] records) from
+/// one thread to another
+///
+/// One thread pushes and one thread pops. More than one of either can't cause
+/// undefined behavior, but may lose or garble events.
+///
+/// # Real-time Safety
+/// - Slots allocated once at construction
+/// - `push` and `pop` only perform atomic loads and stores
+/// - When the queue is full, new events are dropped
+///
+/// # Example
+/// ```
+/// use shared_core::events::{Event, EventQueue, TimedEvent};
+///
+/// let queue = EventQueue::new(64);
+/// let note_on = Event::NoteOn { channel: 0, note: 60, velocity: 0.8 };
+/// assert!(queue.push(TimedEvent::new(0, note_on))); // GUI thread
+/// assert_eq!(queue.pop().map(|event| event.event), Some(note_on)); // Audio thread
+/// assert_eq!(queue.pop(), None);
+/// ```
+pub struct EventQueue<T: Packed = TimedEvent> {
+    /// Packed events
+    slots: Box<[[AtomicU64; 2]]>,
+
+    /// Events pushed so far (wrapping); the next slot written is this modulo
+    /// the capacity
+    written: AtomicUsize,
+
+    /// Events popped so far (wrapping)
+    read: AtomicUsize,
+
+    /// The record type the slots hold
+    record: PhantomData<T>,
+}
+
+impl<T: Packed> EventQueue<T> {
+    /// Create an empty queue holding up to `capacity` events (at least one)
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            slots: (0..capacity.max(1))
+                .map(|_| [AtomicU64::new(0), AtomicU64::new(0)])
+                .collect(),
+            written: AtomicUsize::new(0),
+            read: AtomicUsize::new(0),
+            record: PhantomData,
+        }
+    }
+
+    /// Most events the queue holds at once
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Events waiting to be popped
+    #[must_use]
+    pub fn len(&self) -> usize {
+        let written = self.written.load(Ordering::Acquire);
+        written.wrapping_sub(self.read.load(Ordering::Acquire))
+    }
+
+    /// Whether no events are waiting
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add an event (producer thread), returning false if the queue was full
+    /// and the event was dropped
+    pub fn push(&self, event: T) -> bool {
+        let written = self.written.load(Ordering::Relaxed);
+        if written.wrapping_sub(self.read.load(Ordering::Acquire)) >= self.capacity() {
+            return false;
+        }
+
+        let slot = &self.slots[written % self.capacity()];
+        for (word, packed) in slot.iter().zip(event.pack()) {
+            word.store(packed, Ordering::Relaxed);
+        }
+        self.written
+            .store(written.wrapping_add(1), Ordering::Release);
+        true
+    }
+
+    /// Take the oldest event (consumer thread)
+    pub fn pop(&self) -> Option<T> {
+        let read = self.read.load(Ordering::Relaxed);
+        if read == self.written.load(Ordering::Acquire) {
+            return None;
+        }
+
+        let slot = &self.slots[read % self.capacity()];
+        let event = T::unpack([
+            slot[0].load(Ordering::Relaxed),
+            slot[1].load(Ordering::Relaxed),
+        ]);
+        self.read.store(read.wrapping_add(1), Ordering::Release);
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_events_survive_packing() {
+        let events = [
+            TimedEvent::new(
+                0,
+                Event::NoteOn {
+                    channel: 15,
+                    note: 127,
+                    velocity: 1.0,
+                },
+            ),
+            TimedEvent::new(
+                u32::MAX,
+                Event::NoteOff {
+                    channel: 0,
+                    note: 0,
+                    velocity: 0.25,
+                },
+            ),
+            TimedEvent::new(
+                4410,
+                Event::Cc {
+                    channel: 9,
+                    cc: 74,
+                    value: 0.5,
+                },
+            ),
+        ];
+        for event in events {
+            assert_eq!(TimedEvent::unpack(event.pack()), event);
+        }
+    }
+
+    #[test]
+    fn test_queue_is_first_in_first_out_and_drops_when_full() {
+        let queue = EventQueue::new(3);
+        let cc = |value| {
+            TimedEvent::new(
+                0,
+                Event::Cc {
+                    channel: 0,
+                    cc: 1,
+                    value,
+                },
+            )
+        };
+
+        for value in [0.1, 0.2, 0.3] {
+            assert!(queue.push(cc(value)));
+        }
+        assert!(!queue.push(cc(0.4)), "Full queue should drop");
+        assert_eq!(queue.len(), 3);
+
+        assert_eq!(queue.pop(), Some(cc(0.1)));
+        assert!(queue.push(cc(0.5)));
+        let values: Vec<TimedEvent> = std::iter::from_fn(|| queue.pop()).collect();
+        assert_eq!(values, vec![cc(0.2), cc(0.3), cc(0.5)]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_events_cross_threads_in_order() {
+        let queue = Arc::new(EventQueue::new(16));
+        let producer = {
+            let queue = Arc::clone(&queue);
+            std::thread::spawn(move || {
+                for timing in 0..10_000 {
+                    let event = TimedEvent::new(
+                        timing,
+                        Event::NoteOn {
+                            channel: 0,
+                            note: 60,
+                            velocity: 1.0,
+                        },
+                    );
+                    while !queue.push(event) {
+                        std::thread::yield_now();
+                    }
+                }
+            })
+        };
+
+        let mut expected = 0;
+        while expected < 10_000 {
+            match queue.pop() {
+                Some(event) => {
+                    assert_eq!(event.timing, expected);
+                    expected += 1;
+                }
+                None => std::thread::yield_now(),
+            }
+        }
+        producer.join().unwrap();
+    }
+}
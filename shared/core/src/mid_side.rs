@@ -0,0 +1,129 @@
+//! Mid/side encoding and stereo width
+//!
+//! A stereo pair can be written as its mid (what both sides share) and its
+//! side (what differs between them). Scaling the side before decoding widens
+//! or narrows the image without moving anything off center: no side is mono,
+//! double the side is twice as wide. The halved encoding is used here, so
+//! decoding is a plain sum and difference and a mono signal has a mid equal to
+//! either channel.
+//!
+//! [`apply_width`] at 100% returns its input untouched, bit for bit, rather
+//! than an encode/decode round trip that can round the last bit.
+//!
+//! # References
+//! - Blumlein, British Patent 394,325 (1931): sum and difference stereo
+//! - `M = (L + R)/2`, `S = (L - R)/2`, `L = M + S`, `R = M - S`
+
+/// Width that leaves the image untouched (100%)
+pub const UNITY_WIDTH: f32 = 1.0;
+
+/// Widest setting (200%)
+pub const MAX_WIDTH: f32 = 2.0;
+
+/// Mid and side of a left/right pair
+///
+/// # Example
+/// ```
+/// use shared_core::mid_side::{decode, encode};
+///
+/// let (mid, side) = encode(1.0, 0.5);
+/// assert_eq!((mid, side), (0.75, 0.25));
+/// assert_eq!(decode(mid, side), (1.0, 0.5));
+/// ```
+#[inline]
+#[must_use]
+pub fn encode(left: f32, right: f32) -> (f32, f32) {
+    (0.5 * (left + right), 0.5 * (left - right))
+}
+
+/// Left and right of a mid/side pair
+#[inline]
+#[must_use]
+pub fn decode(mid: f32, side: f32) -> (f32, f32) {
+    (mid + side, mid - side)
+}
+
+/// Scale the side of a left/right pair by `width` (0.0 = mono, 1.0 = as is,
+/// 2.0 = twice as wide)
+///
+/// `width` is clamped to 0.0 - [`MAX_WIDTH`].
+///
+/// # Example
+/// ```
+/// use shared_core::mid_side::apply_width;
+///
+/// assert_eq!(apply_width(1.0, 0.0, 0.0), (0.5, 0.5)); // Mono
+/// assert_eq!(apply_width(1.0, 0.0, 2.0), (1.5, -0.5));
+/// ```
+#[inline]
+#[must_use]
+#[allow(clippy::float_cmp)] // Exactly 100% passes through untouched
+pub fn apply_width(left: f32, right: f32, width: f32) -> (f32, f32) {
+    if width == UNITY_WIDTH {
+        return (left, right);
+    }
+    let (mid, side) = encode(left, right);
+    decode(mid, side * width.clamp(0.0, MAX_WIDTH))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pseudo-random stereo frames from every corner of the float range
+    fn frames() -> Vec<(f32, f32)> {
+        let mut state = 0x2545_f491_u32;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            f32::from_bits(state)
+        };
+        (0..10_000)
+            .map(|_| (next(), next()))
+            .filter(|(left, right)| left.is_finite() && right.is_finite())
+            .collect()
+    }
+
+    #[test]
+    fn test_unity_width_is_bit_transparent() {
+        let specials = [
+            (-0.0, 0.0),
+            (f32::MAX, -f32::MAX),
+            (1e-45, -1e-45),
+            (0.1, 0.7),
+        ];
+        for (left, right) in frames().into_iter().chain(specials) {
+            let (out_left, out_right) = apply_width(left, right, UNITY_WIDTH);
+            assert_eq!(out_left.to_bits(), left.to_bits(), "{left} {right}");
+            assert_eq!(out_right.to_bits(), right.to_bits(), "{left} {right}");
+        }
+    }
+
+    #[test]
+    fn test_round_trip_is_close() {
+        for (left, right) in [(0.1, 0.7), (-0.3, 0.9), (1.0, -1.0)] {
+            let (mid, side) = encode(left, right);
+            let (out_left, out_right) = decode(mid, side);
+            assert!((out_left - left).abs() < 1e-6);
+            assert!((out_right - right).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_width_scales_only_the_side() {
+        let (left, right) = (0.8, 0.2);
+        let (mid, side) = encode(left, right);
+        for width in [0.0, 0.5, 1.5, 2.0] {
+            let (out_mid, out_side) = {
+                let (out_left, out_right) = apply_width(left, right, width);
+                encode(out_left, out_right)
+            };
+            assert!((out_mid - mid).abs() < 1e-6, "Width {width}");
+            assert!((out_side - side * width).abs() < 1e-6, "Width {width}");
+        }
+        // Out of range is clamped
+        assert_eq!(apply_width(left, right, 5.0), apply_width(left, right, 2.0));
+        assert_eq!(apply_width(left, right, -1.0), (0.5, 0.5));
+    }
+}
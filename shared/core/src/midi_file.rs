@@ -0,0 +1,866 @@
+//! Standard MIDI File reading and playback
+//!
+//! [`MidiFile`] parses a format 0 (one track) or format 1 (several tracks
+//! heard together) file into a single list of note and controller events in
+//! tick order, plus the file's tempo map. [`MidiPlayer`] turns that into
+//! [`TimedEvent`]s block by block at the output sample rate, so a standalone
+//! app or offline renderer can play a `.mid` file through a synth the same way
+//! the host's MIDI input would.
+//!
+//! Ticks are converted to samples by following the tempo map, or at a fixed
+//! tempo when overridden. The player counts samples from the last tempo change
+//! rather than adding up a per-sample increment, so events land on the same
+//! samples whatever the block size and however long the file runs.
+//!
+//! # References
+//! - MIDI Manufacturers Association, "Standard MIDI Files 1.0" (1996): `MThd`
+//!   and `MTrk` chunks, variable-length delta times, running status, the
+//!   set tempo meta event (microseconds per quarter note, 120 BPM if absent)
+//! - Seconds per tick = microseconds per quarter / (10⁶ · ticks per quarter)
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::events::{Event, TimedEvent};
+
+/// Tempo of a file with no set tempo event, in microseconds per quarter note
+const DEFAULT_MICROS_PER_QUARTER: u32 = 500_000;
+
+/// Release velocity of a note-on with velocity 0 (the usual note-off shorthand)
+const DEFAULT_RELEASE_VELOCITY: u8 = 64;
+
+/// Why a MIDI file couldn't be loaded
+#[derive(Debug)]
+pub enum MidiFileError {
+    Io(io::Error),
+    /// Not a MIDI file, or a damaged one
+    Invalid(&'static str),
+    /// A valid MIDI file the player can't play
+    Unsupported(String),
+}
+
+impl fmt::Display for MidiFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Invalid(reason) => write!(f, "not a valid MIDI file: {reason}"),
+            Self::Unsupported(feature) => write!(f, "unsupported MIDI file: {feature}"),
+        }
+    }
+}
+
+impl std::error::Error for MidiFileError {}
+
+impl From<io::Error> for MidiFileError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// A note or controller event at a tick
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MidiEvent {
+    pub tick: u64,
+    pub event: Event,
+}
+
+/// A set tempo event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TempoChange {
+    pub tick: u64,
+    pub micros_per_quarter: u32,
+}
+
+impl TempoChange {
+    /// Tempo in beats (quarter notes) per minute
+    #[must_use]
+    pub fn bpm(&self) -> f64 {
+        60_000_000.0 / f64::from(self.micros_per_quarter.max(1))
+    }
+}
+
+/// A parsed Standard MIDI File
+///
+/// # Example
+/// ```no_run
+/// use shared_core::midi_file::{MidiFile, MidiPlayer};
+/// use std::path::Path;
+/// use std::sync::Arc;
+///
+/// let file = MidiFile::load(Path::new("song.mid")).unwrap();
+/// println!("{:.1} s, {} events", file.duration_s(), file.events().len());
+/// let mut player = MidiPlayer::new(Arc::new(file), 48000.0);
+/// player.process(512, |event| println!("{event:?}"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MidiFile {
+    /// 0 = one track, 1 = simultaneous tracks
+    format: u16,
+
+    /// Resolution of the tick clock
+    ticks_per_quarter: u16,
+
+    /// Every track's notes and controllers, merged in tick order
+    events: Vec<MidiEvent>,
+
+    /// Tempo changes in tick order, the first at tick 0
+    tempo_map: Vec<TempoChange>,
+
+    /// Tick of the last track's end
+    length_ticks: u64,
+}
+
+impl MidiFile {
+    /// Read and parse a MIDI file
+    ///
+    /// # Errors
+    /// [`MidiFileError::Io`] if the file can't be read, otherwise as [`Self::parse`].
+    pub fn load(path: &Path) -> Result<Self, MidiFileError> {
+        Self::parse(&fs::read(path)?)
+    }
+
+    /// Parse a MIDI file held in memory
+    ///
+    /// Keeps note on/off and control change events from every channel, and the
+    /// tempo map; other messages, system exclusive and other meta events are
+    /// skipped. A note-on with velocity 0 becomes a note-off.
+    ///
+    /// # Errors
+    /// [`MidiFileError::Invalid`] if the data isn't a well-formed MIDI file;
+    /// [`MidiFileError::Unsupported`] for format 2 (independent sequences) and
+    /// SMPTE time division.
+    pub fn parse(bytes: &[u8]) -> Result<Self, MidiFileError> {
+        if bytes.len() < 14 || &bytes[0..4] != b"MThd" {
+            return Err(MidiFileError::Invalid("missing MThd header"));
+        }
+        let header_size = usize::try_from(read_u32(&bytes[4..8])).unwrap_or(usize::MAX);
+        if header_size < 6 {
+            return Err(MidiFileError::Invalid("header chunk too short"));
+        }
+
+        let format = read_u16(&bytes[8..10]);
+        let division = read_u16(&bytes[12..14]);
+        if format > 1 {
+            return Err(MidiFileError::Unsupported(format!("format {format}")));
+        }
+        if division & 0x8000 != 0 {
+            return Err(MidiFileError::Unsupported("SMPTE time division".into()));
+        }
+        if division == 0 {
+            return Err(MidiFileError::Invalid("zero ticks per quarter note"));
+        }
+
+        let mut file = Self {
+            format,
+            ticks_per_quarter: division,
+            events: Vec::new(),
+            tempo_map: Vec::new(),
+            length_ticks: 0,
+        };
+        let mut tracks = 0;
+        let mut rest = bytes
+            .get(8usize.saturating_add(header_size)..)
+            .unwrap_or_default();
+        while rest.len() >= 8 {
+            let size = usize::try_from(read_u32(&rest[4..8])).unwrap_or(usize::MAX);
+            let body = rest
+                .get(8..8usize.saturating_add(size))
+                .ok_or(MidiFileError::Invalid(
+                    "chunk runs past the end of the file",
+                ))?;
+
+            // Unknown chunk types are skipped, as the specification asks
+            if &rest[0..4] == b"MTrk" {
+                let end = file.parse_track(body)?;
+                file.length_ticks = file.length_ticks.max(end);
+                tracks += 1;
+            }
+            rest = &rest[8 + body.len()..];
+        }
+        if tracks == 0 {
+            return Err(MidiFileError::Invalid("no tracks"));
+        }
+
+        // Stable sorts keep same-tick events in track order, then file order
+        file.events.sort_by_key(|event| event.tick);
+        file.tempo_map.sort_by_key(|change| change.tick);
+        if file.tempo_map.first().is_none_or(|change| change.tick > 0) {
+            file.tempo_map.insert(
+                0,
+                TempoChange {
+                    tick: 0,
+                    micros_per_quarter: DEFAULT_MICROS_PER_QUARTER,
+                },
+            );
+        }
+        Ok(file)
+    }
+
+    /// Read one track's events, returning the tick it ends on
+    fn parse_track(&mut self, track: &[u8]) -> Result<u64, MidiFileError> {
+        let mut reader = Reader { bytes: track };
+        let mut tick = 0u64;
+        let mut running_status = None;
+
+        while !reader.bytes.is_empty() {
+            tick += reader.variable_length()? as u64;
+
+            // A data byte in place of a status repeats the last channel status
+            let status = match reader.bytes[0] {
+                status if status & 0x80 != 0 => {
+                    reader.take(1)?;
+                    status
+                }
+                _ => running_status.ok_or(MidiFileError::Invalid("data byte without a status"))?,
+            };
+
+            match status {
+                0xFF => {
+                    let kind = reader.take(1)?[0];
+                    let length = reader.variable_length()?;
+                    let data = reader.take(length)?;
+                    match kind {
+                        0x2F => return Ok(tick),
+                        0x51 if data.len() >= 3 => self.tempo_map.push(TempoChange {
+                            tick,
+                            micros_per_quarter: u32::from_be_bytes([0, data[0], data[1], data[2]]),
+                        }),
+                        _ => {}
+                    }
+                    running_status = None;
+                }
+                0xF0 | 0xF7 => {
+                    let length = reader.variable_length()?;
+                    reader.take(length)?;
+                    running_status = None;
+                }
+                0x80..=0xEF => {
+                    running_status = Some(status);
+                    let data_bytes = if matches!(status & 0xF0, 0xC0 | 0xD0) {
+                        1
+                    } else {
+                        2
+                    };
+                    let data = reader.take(data_bytes)?;
+                    if let Some(event) = channel_event(status, data) {
+                        self.events.push(MidiEvent { tick, event });
+                    }
+                }
+                _ => return Err(MidiFileError::Invalid("system message inside a track")),
+            }
+        }
+
+        // Tolerate a missing end of track event
+        Ok(tick)
+    }
+
+    /// 0 = one track, 1 = simultaneous tracks
+    #[must_use]
+    pub fn format(&self) -> u16 {
+        self.format
+    }
+
+    #[must_use]
+    pub fn ticks_per_quarter(&self) -> u16 {
+        self.ticks_per_quarter
+    }
+
+    /// Notes and controllers from every track, in tick order
+    #[must_use]
+    pub fn events(&self) -> &[MidiEvent] {
+        &self.events
+    }
+
+    /// Tempo changes in tick order; the first is always at tick 0
+    #[must_use]
+    pub fn tempo_map(&self) -> &[TempoChange] {
+        &self.tempo_map
+    }
+
+    /// Length of the longest track, in ticks
+    #[must_use]
+    pub fn length_ticks(&self) -> u64 {
+        self.length_ticks
+    }
+
+    /// Time of a tick in seconds, following the tempo map
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // Ticks stay far below 2^52
+    pub fn tick_to_seconds(&self, tick: u64) -> f64 {
+        let mut seconds = 0.0;
+        for (index, change) in self.tempo_map.iter().enumerate() {
+            if change.tick >= tick {
+                break;
+            }
+            let end = self
+                .tempo_map
+                .get(index + 1)
+                .map_or(tick, |next| next.tick.min(tick));
+            seconds += (end - change.tick) as f64 * self.seconds_per_tick(change.bpm());
+        }
+        seconds
+    }
+
+    /// Length of the file in seconds, following the tempo map
+    #[must_use]
+    pub fn duration_s(&self) -> f64 {
+        self.tick_to_seconds(self.length_ticks)
+    }
+
+    /// Seconds per tick at a tempo
+    fn seconds_per_tick(&self, bpm: f64) -> f64 {
+        60.0 / (bpm * f64::from(self.ticks_per_quarter))
+    }
+}
+
+/// Note or controller event for a channel message (others are skipped)
+fn channel_event(status: u8, data: &[u8]) -> Option<Event> {
+    let channel = status & 0x0F;
+    let first = data[0] & 0x7F;
+    let second = data.get(1).map_or(0, |byte| byte & 0x7F);
+    let unit = |value: u8| f32::from(value) / 127.0;
+
+    match status & 0xF0 {
+        0x80 => Some(Event::NoteOff {
+            channel,
+            note: first,
+            velocity: unit(second),
+        }),
+        0x90 if second == 0 => Some(Event::NoteOff {
+            channel,
+            note: first,
+            velocity: unit(DEFAULT_RELEASE_VELOCITY),
+        }),
+        0x90 => Some(Event::NoteOn {
+            channel,
+            note: first,
+            velocity: unit(second),
+        }),
+        0xB0 => Some(Event::Cc {
+            channel,
+            cc: first,
+            value: unit(second),
+        }),
+        _ => None,
+    }
+}
+
+/// Cursor over a track chunk
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Take the next `count` bytes
+    fn take(&mut self, count: usize) -> Result<&'a [u8], MidiFileError> {
+        if count > self.bytes.len() {
+            return Err(MidiFileError::Invalid("track ends inside an event"));
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    /// Read a variable-length quantity (7 bits per byte, at most 4 bytes)
+    fn variable_length(&mut self) -> Result<usize, MidiFileError> {
+        let mut value = 0usize;
+        for _ in 0..4 {
+            let byte = self.take(1)?[0];
+            value = (value << 7) | usize::from(byte & 0x7F);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(MidiFileError::Invalid(
+            "variable-length number over 4 bytes",
+        ))
+    }
+}
+
+fn read_u16(bytes: &[u8]) -> u16 {
+    u16::from_be_bytes([bytes[0], bytes[1]])
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// Plays a [`MidiFile`] as timed events, block by block
+///
+/// Notes still held when the file ends, loops or is stopped get a note-off, so
+/// nothing hangs.
+///
+/// # Real-time Safety
+/// - The file is shared through an `Arc` and parsed beforehand
+/// - `process` doesn't allocate; events go straight to a callback
+///
+/// # Example
+/// ```
+/// use shared_core::midi_file::{MidiFile, MidiPlayer};
+/// use std::sync::Arc;
+///
+/// # let bytes = [
+/// #     b'M', b'T', b'h', b'd', 0, 0, 0, 6, 0, 0, 0, 1, 0x01, 0xE0,
+/// #     b'M', b'T', b'r', b'k', 0, 0, 0, 4, 0x00, 0xFF, 0x2F, 0x00,
+/// # ];
+/// let file = Arc::new(MidiFile::parse(&bytes).unwrap());
+/// let mut player = MidiPlayer::new(file, 48000.0);
+/// player.set_looping(true);
+/// player.set_tempo_override(Some(90.0));
+///
+/// let mut events = Vec::new();
+/// player.process(512, |event| events.push(event));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MidiPlayer {
+    file: std::sync::Arc<MidiFile>,
+    sample_rate: f64,
+
+    /// Start again from the top at the end
+    looping: bool,
+
+    /// Fixed tempo in BPM in place of the tempo map
+    tempo_override: Option<f64>,
+
+    /// Tick position at the last tempo change (or loop, seek, override)
+    anchor_tick: f64,
+
+    /// Samples heard since the anchor
+    anchor_samples: u64,
+
+    /// Ticks per sample at the current tempo
+    ticks_per_sample: f64,
+
+    /// Next event to play
+    next_event: usize,
+
+    /// Next tempo change to apply
+    next_tempo: usize,
+
+    /// Notes sounding, one bit per note per channel
+    held: [u128; 16],
+
+    /// Reached the end without looping
+    finished: bool,
+}
+
+impl MidiPlayer {
+    /// Create a player at the start of the file
+    #[must_use]
+    pub fn new(file: std::sync::Arc<MidiFile>, sample_rate: f32) -> Self {
+        let mut player = Self {
+            file,
+            sample_rate: f64::from(sample_rate),
+            looping: false,
+            tempo_override: None,
+            anchor_tick: 0.0,
+            anchor_samples: 0,
+            ticks_per_sample: 0.0,
+            next_event: 0,
+            next_tempo: 0,
+            held: [0; 16],
+            finished: false,
+        };
+        player.update_rate();
+        player
+    }
+
+    /// Set the sample rate in Hz
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.reanchor();
+        self.sample_rate = f64::from(sample_rate);
+        self.update_rate();
+    }
+
+    /// Loop back to the start at the end of the file
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    /// Play at a fixed tempo in BPM (`None` follows the file's tempo map)
+    pub fn set_tempo_override(&mut self, bpm: Option<f64>) {
+        self.reanchor();
+        self.tempo_override = bpm.filter(|bpm| *bpm > 0.0);
+        self.update_rate();
+    }
+
+    /// Current position in ticks
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // Sample counts stay far below 2^52
+    pub fn position_ticks(&self) -> f64 {
+        self.anchor_tick + self.anchor_samples as f64 * self.ticks_per_sample
+    }
+
+    /// Whether the file has heard to its end (never while looping)
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Play the next `len` samples, passing each event due in them to `emit`
+    /// with its offset in the block
+    pub fn process(&mut self, len: usize, mut emit: impl FnMut(TimedEvent)) {
+        for offset in 0..len {
+            if self.finished {
+                return;
+            }
+            let timing = u32::try_from(offset).unwrap_or(u32::MAX);
+            self.play_due(timing, &mut emit);
+
+            #[allow(clippy::cast_precision_loss)] // Ticks stay far below 2^52
+            let length = self.file.length_ticks as f64;
+            if self.next_event == self.file.events.len() && self.position_ticks() >= length {
+                self.release_held(timing, &mut emit);
+                if self.looping && self.file.length_ticks > 0 {
+                    let position = self.position_ticks() - length;
+                    self.seek_start();
+                    self.anchor_tick = position;
+                    self.play_due(timing, &mut emit);
+                } else {
+                    self.finished = true;
+                    return;
+                }
+            }
+            self.anchor_samples += 1;
+        }
+    }
+
+    /// Release held notes at `timing` and go back to the start
+    pub fn stop(&mut self, timing: u32, mut emit: impl FnMut(TimedEvent)) {
+        self.release_held(timing, &mut emit);
+        self.seek_start();
+    }
+
+    /// Play the events and tempo changes the position has reached
+    fn play_due(&mut self, timing: u32, emit: &mut impl FnMut(TimedEvent)) {
+        let file = std::sync::Arc::clone(&self.file);
+        #[allow(clippy::cast_precision_loss)] // Ticks stay far below 2^52
+        while let Some(change) = file
+            .tempo_map
+            .get(self.next_tempo)
+            .filter(|change| change.tick as f64 <= self.position_ticks())
+        {
+            self.reanchor();
+            self.next_tempo += 1;
+            if self.tempo_override.is_none() {
+                self.ticks_per_sample = self.ticks_per_sample_at(change.bpm());
+            }
+        }
+
+        #[allow(clippy::cast_precision_loss)] // Ticks stay far below 2^52
+        while let Some(event) = file
+            .events
+            .get(self.next_event)
+            .filter(|event| event.tick as f64 <= self.position_ticks())
+        {
+            self.next_event += 1;
+            match event.event {
+                Event::NoteOn { channel, note, .. } => self.held[usize::from(channel)] |= 1 << note,
+                Event::NoteOff { channel, note, .. } => {
+                    self.held[usize::from(channel)] &= !(1 << note);
+                }
+                Event::Cc { .. } => {}
+            }
+            emit(TimedEvent::new(timing, event.event));
+        }
+    }
+
+    /// Note-off for every held note
+    fn release_held(&mut self, timing: u32, emit: &mut impl FnMut(TimedEvent)) {
+        for (channel, notes) in (0u8..).zip(&mut self.held) {
+            while *notes != 0 {
+                #[allow(clippy::cast_possible_truncation)] // Below 128
+                let note = notes.trailing_zeros() as u8;
+                *notes &= !(1 << note);
+                let velocity = f32::from(DEFAULT_RELEASE_VELOCITY) / 127.0;
+                emit(TimedEvent::new(
+                    timing,
+                    Event::NoteOff {
+                        channel,
+                        note,
+                        velocity,
+                    },
+                ));
+            }
+        }
+    }
+
+    /// Rewind to tick 0 (held notes are forgotten, not released)
+    fn seek_start(&mut self) {
+        self.anchor_tick = 0.0;
+        self.anchor_samples = 0;
+        self.next_event = 0;
+        self.next_tempo = 0;
+        self.held = [0; 16];
+        self.finished = false;
+        self.update_rate();
+    }
+
+    /// Start counting samples from the current position
+    fn reanchor(&mut self) {
+        self.anchor_tick = self.position_ticks();
+        self.anchor_samples = 0;
+    }
+
+    /// Ticks per sample for the override or the tempo in force
+    fn update_rate(&mut self) {
+        let bpm = self.tempo_override.unwrap_or_else(|| {
+            let current = self.next_tempo.saturating_sub(1);
+            self.file
+                .tempo_map
+                .get(current)
+                .map_or(120.0, TempoChange::bpm)
+        });
+        self.ticks_per_sample = self.ticks_per_sample_at(bpm);
+    }
+
+    fn ticks_per_sample_at(&self, bpm: f64) -> f64 {
+        1.0 / (self.file.seconds_per_tick(bpm) * self.sample_rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    /// Encode a variable-length quantity
+    fn variable_length(mut value: u32) -> Vec<u8> {
+        let mut bytes = vec![(value & 0x7F) as u8];
+        value >>= 7;
+        while value > 0 {
+            bytes.insert(0, (value & 0x7F) as u8 | 0x80);
+            value >>= 7;
+        }
+        bytes
+    }
+
+    /// A chunk with its header
+    fn chunk(id: [u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut bytes = id.to_vec();
+        bytes.extend_from_slice(&u32::try_from(body.len()).unwrap().to_be_bytes());
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    /// A track of (delta, message) pairs, ended after `end_delta`
+    fn track(events: &[(u32, &[u8])], end_delta: u32) -> Vec<u8> {
+        let mut body = Vec::new();
+        for (delta, message) in events {
+            body.extend(variable_length(*delta));
+            body.extend_from_slice(message);
+        }
+        body.extend(variable_length(end_delta));
+        body.extend_from_slice(&[0xFF, 0x2F, 0x00]);
+        chunk(*b"MTrk", &body)
+    }
+
+    fn file(format: u16, division: u16, tracks: &[&[u8]]) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend_from_slice(&format.to_be_bytes());
+        header.extend_from_slice(&u16::try_from(tracks.len()).unwrap().to_be_bytes());
+        header.extend_from_slice(&division.to_be_bytes());
+        let mut bytes = chunk(*b"MThd", &header);
+        for track in tracks {
+            bytes.extend_from_slice(track);
+        }
+        bytes
+    }
+
+    /// Set tempo meta event
+    fn tempo(bpm: u32) -> Vec<u8> {
+        let micros = (60_000_000 / bpm).to_be_bytes();
+        vec![0xFF, 0x51, 0x03, micros[1], micros[2], micros[3]]
+    }
+
+    fn note_on(channel: u8, note: u8, velocity: f32) -> Event {
+        Event::NoteOn {
+            channel,
+            note,
+            velocity,
+        }
+    }
+
+    fn note_off(channel: u8, note: u8, velocity: f32) -> Event {
+        Event::NoteOff {
+            channel,
+            note,
+            velocity,
+        }
+    }
+
+    /// Format 1: a tempo track (100 BPM, 150 BPM from beat 2) and a note track
+    fn two_track_file() -> MidiFile {
+        let tempo_track = track(&[(0, &tempo(100)), (960, &tempo(150))], 960);
+        let note_track = track(
+            &[
+                (0, &[0x90, 60, 127]),
+                // Running status, then a note-on at velocity 0 as note-off
+                (480, &[64, 127]),
+                (480, &[60, 0]),
+                (0, &[0xB1, 74, 0]),
+                (0, &[0xF0, 0x01, 0xF7]),
+                (480, &[0x80, 64, 127]),
+                (0, &[0xC0, 5]),
+            ],
+            0,
+        );
+        MidiFile::parse(&file(1, 480, &[&tempo_track, &note_track])).unwrap()
+    }
+
+    /// Render the player in blocks of `block_size`, as (sample, event) pairs
+    fn render(player: &mut MidiPlayer, samples: usize, block_size: usize) -> Vec<(usize, Event)> {
+        let mut heard = Vec::new();
+        for start in (0..samples).step_by(block_size) {
+            let len = block_size.min(samples - start);
+            player.process(len, |event| {
+                heard.push((start + event.timing as usize, event.event));
+            });
+        }
+        heard
+    }
+
+    #[test]
+    fn test_parses_tracks_tempo_map_and_running_status() {
+        let file = two_track_file();
+        assert_eq!(file.format(), 1);
+        assert_eq!(file.ticks_per_quarter(), 480);
+        assert_eq!(file.length_ticks(), 1920);
+
+        let events: Vec<(u64, Event)> = file
+            .events()
+            .iter()
+            .map(|event| (event.tick, event.event))
+            .collect();
+        let release = 64.0 / 127.0;
+        assert_eq!(
+            events,
+            vec![
+                (0, note_on(0, 60, 1.0)),
+                (480, note_on(0, 64, 1.0)),
+                (960, note_off(0, 60, release)),
+                (
+                    960,
+                    Event::Cc {
+                        channel: 1,
+                        cc: 74,
+                        value: 0.0
+                    }
+                ),
+                (1440, note_off(0, 64, 1.0)),
+            ]
+        );
+
+        let bpms: Vec<(u64, f64)> = file
+            .tempo_map()
+            .iter()
+            .map(|change| (change.tick, change.bpm()))
+            .collect();
+        assert_eq!(bpms, vec![(0, 100.0), (960, 150.0)]);
+
+        // Two beats at 100 BPM, two at 150
+        assert!((file.duration_s() - (1.2 + 0.8)).abs() < 1e-9);
+        assert!((file.tick_to_seconds(480) - 0.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rejects_bad_and_unsupported_files() {
+        let empty_track = track(&[], 0);
+        let error = |bytes: &[u8]| MidiFile::parse(bytes).unwrap_err();
+
+        assert!(matches!(
+            error(b"RIFF0000WAVEfmt "),
+            MidiFileError::Invalid(_)
+        ));
+        assert!(matches!(
+            error(&file(2, 480, &[&empty_track])),
+            MidiFileError::Unsupported(_)
+        ));
+        assert!(matches!(
+            error(&file(0, 0xE728, &[&empty_track])),
+            MidiFileError::Unsupported(_)
+        ));
+        assert!(matches!(
+            error(&file(0, 480, &[])),
+            MidiFileError::Invalid(_)
+        ));
+
+        // Note-on cut off after its key
+        let truncated = chunk(*b"MTrk", &[0x00, 0x90, 60]);
+        assert!(matches!(
+            error(&file(0, 480, &[&truncated])),
+            MidiFileError::Invalid(_)
+        ));
+
+        // No tempo event: 120 BPM
+        let file = MidiFile::parse(&file(0, 480, &[&empty_track])).unwrap();
+        assert_eq!(file.tempo_map()[0].micros_per_quarter, 500_000);
+    }
+
+    #[test]
+    fn test_events_land_on_tempo_mapped_samples() {
+        let mut player = MidiPlayer::new(Arc::new(two_track_file()), SAMPLE_RATE);
+        let heard = render(&mut player, 200_000, 512);
+
+        // 100 BPM: a beat is 28800 samples; after beat 2, 150 BPM: 19200
+        let samples: Vec<usize> = heard.iter().map(|(sample, _)| *sample).collect();
+        assert_eq!(samples, vec![0, 28800, 57600, 57600, 76800]);
+        assert!(player.is_finished());
+    }
+
+    #[test]
+    fn test_playback_ignores_block_size() {
+        let reference = {
+            let mut player = MidiPlayer::new(Arc::new(two_track_file()), 44100.0);
+            player.set_looping(true);
+            render(&mut player, 500_000, 500_000)
+        };
+        assert!(reference.len() > 20);
+        for block_size in [1, 7, 64, 333, 4096] {
+            let mut player = MidiPlayer::new(Arc::new(two_track_file()), 44100.0);
+            player.set_looping(true);
+            assert_eq!(render(&mut player, 500_000, block_size), reference);
+        }
+    }
+
+    #[test]
+    fn test_tempo_override_ignores_tempo_map() {
+        let mut player = MidiPlayer::new(Arc::new(two_track_file()), SAMPLE_RATE);
+        player.set_tempo_override(Some(120.0));
+        let heard = render(&mut player, 200_000, 256);
+
+        let samples: Vec<usize> = heard.iter().map(|(sample, _)| *sample).collect();
+        assert_eq!(samples, vec![0, 24000, 48000, 48000, 72000]);
+    }
+
+    #[test]
+    fn test_loop_releases_held_notes() {
+        // A note that never ends, in a one-beat file
+        let bytes = file(0, 480, &[&track(&[(0, &[0x92, 48, 100])], 480)]);
+        let mut player = MidiPlayer::new(Arc::new(MidiFile::parse(&bytes).unwrap()), SAMPLE_RATE);
+        player.set_looping(true);
+        let heard = render(&mut player, 50_000, 1000);
+
+        let velocity = 100.0 / 127.0;
+        let release = 64.0 / 127.0;
+        assert_eq!(
+            heard,
+            vec![
+                (0, note_on(2, 48, velocity)),
+                (24000, note_off(2, 48, release)),
+                (24000, note_on(2, 48, velocity)),
+                (48000, note_off(2, 48, release)),
+                (48000, note_on(2, 48, velocity)),
+            ]
+        );
+        assert!(!player.is_finished());
+
+        let mut stopped = Vec::new();
+        player.stop(3, |event| stopped.push(event));
+        assert_eq!(stopped, vec![TimedEvent::new(3, note_off(2, 48, release))]);
+    }
+}
@@ -0,0 +1,162 @@
+//! Stereo pan laws
+//!
+//! Turns a pan position (-1.0 = hard left, 0.0 = center, 1.0 = hard right) into
+//! left/right channel gains. The laws differ in how much a centered signal is
+//! attenuated, which decides whether a sound keeps its loudness as it moves:
+//! - Linear: -6 dB at center, gains sum to 1 (constant amplitude, dips in power)
+//! - Equal power: -3 dB at center, squared gains sum to 1 (constant power)
+//! - Compromise: -4.5 dB at center, the geometric mean of the two
+//!
+//! Hard left and hard right are unity gain on one side and silence on the other
+//! for every law.
+//!
+//! # References
+//! - Griesinger, "Stereo and Surround Panning in Practice" (AES 2002)
+//! - Equal power: `L = cos(θ)`, `R = sin(θ)`, `θ = (pan + 1)·π/4`
+//! - -4.5 dB law: `sqrt(linear · equal_power)` per channel
+
+use std::f32::consts::FRAC_PI_2;
+
+/// How channel gains trade off as a signal moves across the stereo field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PanLaw {
+    /// -6 dB at center
+    Linear,
+    /// -3 dB at center
+    #[default]
+    EqualPower,
+    /// -4.5 dB at center
+    Compromise,
+}
+
+impl PanLaw {
+    /// Every law, in parameter index order
+    pub const ALL: [Self; 3] = [Self::Linear, Self::EqualPower, Self::Compromise];
+
+    /// Display names, in parameter index order
+    pub const NAMES: [&'static str; 3] = ["Linear (-6 dB)", "Equal Power (-3 dB)", "-4.5 dB"];
+
+    /// Law at a parameter index (out-of-range falls back to `EqualPower`)
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or_default()
+    }
+
+    /// Left and right gains for a pan position
+    ///
+    /// `pan` runs from -1.0 (hard left) to 1.0 (hard right) and is clamped.
+    ///
+    /// # Example
+    /// ```
+    /// use shared_core::pan::PanLaw;
+    ///
+    /// let (left, right) = PanLaw::EqualPower.gains(0.0);
+    /// assert!((left - right).abs() < 1e-6);
+    /// assert!((left * left + right * right - 1.0).abs() < 1e-6);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn gains(self, pan: f32) -> (f32, f32) {
+        match self {
+            Self::Linear => linear(pan),
+            Self::EqualPower => equal_power(pan),
+            Self::Compromise => compromise(pan),
+        }
+    }
+}
+
+/// Linear (-6 dB center) pan gains
+#[inline]
+#[must_use]
+pub fn linear(pan: f32) -> (f32, f32) {
+    let position = (pan.clamp(-1.0, 1.0) + 1.0) * 0.5;
+    (1.0 - position, position)
+}
+
+/// Equal-power (-3 dB center) pan gains
+#[inline]
+#[must_use]
+pub fn equal_power(pan: f32) -> (f32, f32) {
+    let angle = (pan.clamp(-1.0, 1.0) + 1.0) * 0.5 * FRAC_PI_2;
+    let (right, left) = angle.sin_cos();
+    (left, right)
+}
+
+/// Compromise (-4.5 dB center) pan gains
+#[inline]
+#[must_use]
+pub fn compromise(pan: f32) -> (f32, f32) {
+    let (linear_left, linear_right) = linear(pan);
+    let (power_left, power_right) = equal_power(pan);
+    // max(0.0) guards against cos(π/2) landing a hair below zero
+    (
+        (linear_left * power_left).max(0.0).sqrt(),
+        (linear_right * power_right).max(0.0).sqrt(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_db(gain: f32) -> f32 {
+        20.0 * gain.log10()
+    }
+
+    #[test]
+    fn test_center_attenuation() {
+        for (law, expected_db) in [
+            (PanLaw::Linear, -6.02),
+            (PanLaw::EqualPower, -3.01),
+            (PanLaw::Compromise, -4.52),
+        ] {
+            let (left, right) = law.gains(0.0);
+            assert!((left - right).abs() < 1e-6, "{law:?} should be centered");
+            assert!(
+                (to_db(left) - expected_db).abs() < 0.01,
+                "{law:?} center gain {} dB",
+                to_db(left)
+            );
+        }
+    }
+
+    #[test]
+    fn test_extremes_are_unity_and_silence() {
+        for law in PanLaw::ALL {
+            let (left, right) = law.gains(-1.0);
+            assert!((left - 1.0).abs() < 1e-6, "{law:?} hard left");
+            assert!(right.abs() < 1e-6, "{law:?} hard left");
+
+            let (left, right) = law.gains(1.0);
+            assert!(left.abs() < 1e-6, "{law:?} hard right");
+            assert!((right - 1.0).abs() < 1e-6, "{law:?} hard right");
+        }
+    }
+
+    #[test]
+    fn test_out_of_range_pan_is_clamped() {
+        for law in PanLaw::ALL {
+            assert_eq!(law.gains(-3.0), law.gains(-1.0));
+            assert_eq!(law.gains(2.5), law.gains(1.0));
+        }
+    }
+
+    #[test]
+    fn test_equal_power_keeps_power_constant() {
+        for i in 0..=20u8 {
+            let pan = f32::from(i) / 10.0 - 1.0;
+            let (left, right) = equal_power(pan);
+            assert!(
+                (left * left + right * right - 1.0).abs() < 1e-5,
+                "pan {pan}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_index_lookup_falls_back_to_equal_power() {
+        assert_eq!(PanLaw::from_index(2), PanLaw::Compromise);
+        assert_eq!(PanLaw::from_index(7), PanLaw::EqualPower);
+        assert_eq!(PanLaw::ALL.len(), PanLaw::NAMES.len());
+    }
+}
@@ -0,0 +1,257 @@
+//! Time-domain pitch detection (YIN)
+//!
+//! Finds the period of a signal by comparing it with delayed copies of itself:
+//! the delay where the signal best matches itself is one period. Unlike counting
+//! zero crossings, this doesn't care how many times a cycle crosses zero, so it
+//! holds up with DC offsets, noise, and waveforms with extra crossings (saws,
+//! pulses, rich harmonics).
+//!
+//! Steps, per the paper:
+//! 1. Difference function: `d(τ) = Σ (x[j] - x[j + τ])²`
+//! 2. Cumulative mean normalization, so `d'(τ)` starts at 1 and dips towards 0
+//!    at each multiple of the period
+//! 3. The first dip below the threshold is the period (avoids octave errors
+//!    from the deeper dips at two or three periods)
+//! 4. Parabolic interpolation for sub-sample precision
+//!
+//! # References
+//! - de Cheveigné & Kawahara, "YIN, a fundamental frequency estimator for speech
+//!   and music" (JASA 2002)
+
+/// Detected pitch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PitchEstimate {
+    /// Fundamental frequency in Hz
+    pub frequency_hz: f32,
+    /// How periodic the signal is (1.0 = perfectly, 0.0 = not at all)
+    pub clarity: f32,
+}
+
+/// YIN pitch detector for a fixed frequency range
+///
+/// Analyzes the first [`PitchDetector::window_len`] samples it's given: two
+/// periods of the lowest frequency it looks for.
+///
+/// # Real-time Safety
+/// - Buffers sized in `new()`, no allocations in `detect()`
+/// - Cost grows with the square of the window, so keep the lowest frequency as
+///   high as the use allows
+///
+/// # Example
+/// ```
+/// use shared_core::pitch::PitchDetector;
+///
+/// let sample_rate = 48000.0;
+/// let mut detector = PitchDetector::new(sample_rate, 50.0, 2000.0);
+/// let saw: Vec<f32> = (0..detector.window_len())
+///     .map(|i| 2.0 * (i as f32 * 220.0 / sample_rate).fract() - 1.0)
+///     .collect();
+///
+/// let pitch = detector.detect(&saw).unwrap();
+/// assert!((pitch.frequency_hz - 220.0).abs() < 0.5);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PitchDetector {
+    /// Sample rate in Hz
+    sample_rate: f32,
+
+    /// Shortest period searched, in samples
+    min_lag: usize,
+
+    /// Longest period searched, in samples (also the integration window)
+    max_lag: usize,
+
+    /// Largest normalized difference accepted as a period
+    threshold: f32,
+
+    /// Difference function by lag
+    difference: Vec<f32>,
+
+    /// Cumulative-mean-normalized difference by lag
+    normalized: Vec<f32>,
+}
+
+impl PitchDetector {
+    /// Threshold from the paper: a good trade between misses and octave errors
+    pub const DEFAULT_THRESHOLD: f32 = 0.1;
+
+    /// Create a detector for pitches between `min_hz` and `max_hz`
+    #[must_use]
+    pub fn new(sample_rate: f32, min_hz: f32, max_hz: f32) -> Self {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Positive lags
+        let (min_lag, max_lag) = (
+            ((sample_rate / max_hz).floor() as usize).max(2),
+            (sample_rate / min_hz).ceil() as usize,
+        );
+        let max_lag = max_lag.max(min_lag + 2);
+
+        Self {
+            sample_rate,
+            min_lag,
+            max_lag,
+            threshold: Self::DEFAULT_THRESHOLD,
+            difference: vec![0.0; max_lag + 1],
+            normalized: vec![0.0; max_lag + 1],
+        }
+    }
+
+    /// Set the largest normalized difference accepted as a period (0.0 - 1.0)
+    ///
+    /// Higher values find a pitch in noisier signals but risk octave errors.
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold.clamp(0.0, 1.0);
+    }
+
+    /// Number of samples one detection reads
+    #[must_use]
+    pub fn window_len(&self) -> usize {
+        2 * self.max_lag
+    }
+
+    /// Pitch of the first [`PitchDetector::window_len`] samples
+    ///
+    /// Returns `None` for silence, unpitched signals, and input shorter than
+    /// the window.
+    pub fn detect(&mut self, samples: &[f32]) -> Option<PitchEstimate> {
+        if samples.len() < self.window_len() {
+            return None;
+        }
+        let window = &samples[..self.max_lag];
+
+        // 1. Difference function
+        for (lag, difference) in self.difference.iter_mut().enumerate().skip(1) {
+            *difference = window
+                .iter()
+                .zip(&samples[lag..])
+                .map(|(a, b)| (a - b) * (a - b))
+                .sum();
+        }
+
+        // 2. Cumulative mean normalization
+        self.normalized[0] = 1.0;
+        let mut running_sum = 0.0;
+        for lag in 1..=self.max_lag {
+            running_sum += self.difference[lag];
+            #[allow(clippy::cast_precision_loss)] // Lags are far below 2^23
+            let normalized = if running_sum > 0.0 {
+                self.difference[lag] * lag as f32 / running_sum
+            } else {
+                1.0
+            };
+            self.normalized[lag] = normalized;
+        }
+
+        // 3. First dip under the threshold, followed to its bottom
+        let mut lag =
+            (self.min_lag..self.max_lag).find(|&lag| self.normalized[lag] < self.threshold)?;
+        while lag + 1 < self.max_lag && self.normalized[lag + 1] < self.normalized[lag] {
+            lag += 1;
+        }
+
+        // 4. Parabolic interpolation around the dip
+        let (left, center, right) = (
+            self.normalized[lag - 1],
+            self.normalized[lag],
+            self.normalized[lag + 1],
+        );
+        let curvature = left - 2.0 * center + right;
+        let offset = if curvature > 0.0 {
+            (0.5 * (left - right) / curvature).clamp(-0.5, 0.5)
+        } else {
+            0.0
+        };
+
+        #[allow(clippy::cast_precision_loss)] // Lags are far below 2^23
+        let period = lag as f32 + offset;
+        Some(PitchEstimate {
+            frequency_hz: self.sample_rate / period,
+            clarity: (1.0 - center).clamp(0.0, 1.0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::noise::NoiseGenerator;
+
+    const SAMPLE_RATE: f32 = 44100.0;
+
+    fn saw(frequency_hz: f32, len: usize) -> Vec<f32> {
+        #[allow(clippy::cast_precision_loss)]
+        (0..len)
+            .map(|i| 2.0 * (i as f32 * frequency_hz / SAMPLE_RATE).fract() - 1.0)
+            .collect()
+    }
+
+    #[test]
+    fn test_detects_sine_across_range() {
+        let mut detector = PitchDetector::new(SAMPLE_RATE, 40.0, 4000.0);
+        for frequency in [41.2, 110.0, 261.63, 440.0, 1046.5, 3520.0] {
+            #[allow(clippy::cast_precision_loss)]
+            let sine: Vec<f32> = (0..detector.window_len())
+                .map(|i| (std::f32::consts::TAU * frequency * i as f32 / SAMPLE_RATE).sin())
+                .collect();
+            let pitch = detector.detect(&sine).unwrap();
+            assert!(
+                (pitch.frequency_hz / frequency - 1.0).abs() < 0.001,
+                "{frequency} Hz detected as {pitch:?}"
+            );
+            assert!(pitch.clarity > 0.95);
+        }
+    }
+
+    #[test]
+    fn test_saw_with_dc_and_noise() {
+        // Offset and noise scramble the zero crossings but not the period
+        let mut detector = PitchDetector::new(SAMPLE_RATE, 50.0, 2000.0);
+        let mut noise = NoiseGenerator::new(7);
+        let signal: Vec<f32> = saw(330.0, detector.window_len())
+            .into_iter()
+            .map(|s| s + 0.4 + 0.1 * noise.next_bipolar())
+            .collect();
+
+        let pitch = detector.detect(&signal).unwrap();
+        assert!(
+            (pitch.frequency_hz - 330.0).abs() < 1.0,
+            "detected {pitch:?}"
+        );
+    }
+
+    #[test]
+    fn test_strong_second_harmonic_is_not_an_octave_error() {
+        let mut detector = PitchDetector::new(SAMPLE_RATE, 50.0, 2000.0);
+        #[allow(clippy::cast_precision_loss)]
+        let signal: Vec<f32> = (0..detector.window_len())
+            .map(|i| {
+                let phase = std::f32::consts::TAU * 200.0 * i as f32 / SAMPLE_RATE;
+                0.3 * phase.sin() + (2.0 * phase).sin()
+            })
+            .collect();
+
+        let pitch = detector.detect(&signal).unwrap();
+        assert!(
+            (pitch.frequency_hz - 200.0).abs() < 0.5,
+            "detected {pitch:?}"
+        );
+    }
+
+    #[test]
+    fn test_silence_and_noise_have_no_pitch() {
+        let mut detector = PitchDetector::new(SAMPLE_RATE, 50.0, 2000.0);
+        assert_eq!(detector.detect(&vec![0.0; detector.window_len()]), None);
+
+        let mut noise = NoiseGenerator::new(3);
+        let white: Vec<f32> = (0..detector.window_len())
+            .map(|_| noise.next_bipolar())
+            .collect();
+        assert_eq!(detector.detect(&white), None);
+    }
+
+    #[test]
+    fn test_short_input_has_no_pitch() {
+        let mut detector = PitchDetector::new(SAMPLE_RATE, 50.0, 2000.0);
+        let signal = saw(440.0, detector.window_len() - 1);
+        assert_eq!(detector.detect(&signal), None);
+    }
+}
@@ -0,0 +1,176 @@
+//! Tempo-synced note divisions
+//!
+//! Converts musical lengths (1/16, dotted 1/8, quarter-note triplet, ...) into
+//! milliseconds or Hz at the host tempo, so time and rate parameters can lock to
+//! the song.
+//!
+//! Lengths are measured in beats, where one beat is a quarter note:
+//! - Dotted = 1.5 × the straight length
+//! - Triplet = 2/3 × the straight length
+//!
+//! The longest division is a whole note (one bar of 4/4).
+//!
+//! Cycles locked to the host playhead count their own samples between blocks
+//! and only re-lock ([`relock_position`]) when the playhead has really moved
+//! away, so the output doesn't depend on how the host sized its blocks.
+
+/// Tempo used when the host doesn't report one
+pub const DEFAULT_TEMPO_BPM: f32 = 120.0;
+
+/// Drift from the host playhead a synced cycle rides out, in samples
+pub const SYNC_TOLERANCE_SAMPLES: f64 = 1.0;
+
+/// Position a synced cycle has to jump to, if it has drifted off the host's
+///
+/// # Arguments
+/// * `position` - The cycle's own position (0.0 up to `length`)
+/// * `target` - Position the host's playhead puts it at
+/// * `length` - Length of the cycle, in the same units
+/// * `increment` - How far the cycle moves per sample
+///
+/// The host's playhead at each block and the cycle's sample count round
+/// differently; left to re-lock every block, the cycle would land on slightly
+/// different positions for different block sizes. Seeks, loops and tempo jumps
+/// move it further than `SYNC_TOLERANCE_SAMPLES` and re-lock it.
+#[must_use]
+pub fn relock_position(position: f64, target: f64, length: f64, increment: f64) -> Option<f64> {
+    let drift = (target - position).rem_euclid(length);
+    let drift = drift.min(length - drift);
+    (drift > increment.abs() * SYNC_TOLERANCE_SAMPLES).then_some(target)
+}
+
+/// A musical note length, shortest to longest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteDivision {
+    SixtyFourth,
+    ThirtySecondTriplet,
+    ThirtySecond,
+    SixteenthTriplet,
+    Sixteenth,
+    EighthTriplet,
+    SixteenthDotted,
+    Eighth,
+    QuarterTriplet,
+    EighthDotted,
+    Quarter,
+    QuarterDotted,
+    Half,
+    HalfDotted,
+    Whole,
+}
+
+impl NoteDivision {
+    /// Every division, shortest to longest (index order used by plugin parameters)
+    pub const ALL: [Self; 15] = [
+        Self::SixtyFourth,
+        Self::ThirtySecondTriplet,
+        Self::ThirtySecond,
+        Self::SixteenthTriplet,
+        Self::Sixteenth,
+        Self::EighthTriplet,
+        Self::SixteenthDotted,
+        Self::Eighth,
+        Self::QuarterTriplet,
+        Self::EighthDotted,
+        Self::Quarter,
+        Self::QuarterDotted,
+        Self::Half,
+        Self::HalfDotted,
+        Self::Whole,
+    ];
+
+    /// Display names, in the same order as [`NoteDivision::ALL`]
+    pub const NAMES: [&'static str; 15] = [
+        "1/64", "1/32T", "1/32", "1/16T", "1/16", "1/8T", "1/16D", "1/8", "1/4T", "1/8D", "1/4",
+        "1/4D", "1/2", "1/2D", "1/1",
+    ];
+
+    /// Division at `index` in [`NoteDivision::ALL`], clamped to the valid range
+    #[must_use]
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+
+    /// Length in beats (quarter notes)
+    #[must_use]
+    pub fn beats(self) -> f32 {
+        const TRIPLET: f32 = 2.0 / 3.0;
+        const DOTTED: f32 = 1.5;
+
+        match self {
+            Self::SixtyFourth => 0.0625,
+            Self::ThirtySecondTriplet => 0.125 * TRIPLET,
+            Self::ThirtySecond => 0.125,
+            Self::SixteenthTriplet => 0.25 * TRIPLET,
+            Self::Sixteenth => 0.25,
+            Self::SixteenthDotted => 0.25 * DOTTED,
+            Self::EighthTriplet => 0.5 * TRIPLET,
+            Self::Eighth => 0.5,
+            Self::EighthDotted => 0.5 * DOTTED,
+            Self::QuarterTriplet => TRIPLET,
+            Self::Quarter => 1.0,
+            Self::QuarterDotted => DOTTED,
+            Self::Half => 2.0,
+            Self::HalfDotted => 2.0 * DOTTED,
+            Self::Whole => 4.0,
+        }
+    }
+
+    /// Length in milliseconds at `tempo_bpm`
+    #[must_use]
+    pub fn duration_ms(self, tempo_bpm: f32) -> f32 {
+        self.beats() * 60_000.0 / tempo_bpm.max(1.0)
+    }
+
+    /// Repeat rate in Hz at `tempo_bpm` (one cycle per division)
+    #[must_use]
+    pub fn rate_hz(self, tempo_bpm: f32) -> f32 {
+        1000.0 / self.duration_ms(tempo_bpm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quarter_note_at_120_bpm() {
+        assert!((NoteDivision::Quarter.duration_ms(120.0) - 500.0).abs() < 1e-3);
+        assert!((NoteDivision::Quarter.rate_hz(120.0) - 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_dotted_and_triplet_lengths() {
+        let eighth = NoteDivision::Eighth.duration_ms(100.0);
+        assert!((NoteDivision::EighthDotted.duration_ms(100.0) - eighth * 1.5).abs() < 1e-3);
+        assert!((NoteDivision::EighthTriplet.duration_ms(100.0) - eighth * 2.0 / 3.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_divisions_are_sorted_and_named() {
+        for pair in NoteDivision::ALL.windows(2) {
+            assert!(pair[0].beats() < pair[1].beats(), "{pair:?} out of order");
+        }
+        assert_eq!(NoteDivision::ALL.len(), NoteDivision::NAMES.len());
+        assert_eq!(NoteDivision::from_index(0), NoteDivision::SixtyFourth);
+        assert_eq!(NoteDivision::from_index(999), NoteDivision::Whole);
+    }
+
+    #[test]
+    fn test_relock_ignores_rounding_but_follows_seeks() {
+        let increment = 1.0 / 24_000.0;
+        assert_eq!(relock_position(0.5, 0.5 + 1e-12, 1.0, increment), None);
+
+        // Drift across the wrap is measured the short way round
+        assert_eq!(relock_position(0.999_999, 1e-7, 1.0, increment), None);
+
+        // A loop back to the start
+        assert_eq!(relock_position(0.75, 0.0, 1.0, increment), Some(0.0));
+    }
+
+    #[test]
+    fn test_whole_note_is_one_bar_of_four_four() {
+        // 4 beats at 60 BPM
+        assert!((NoteDivision::Whole.duration_ms(60.0) - 4000.0).abs() < 1e-2);
+    }
+}
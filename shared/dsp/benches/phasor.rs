@@ -0,0 +1,88 @@
+//! Phase accumulator under heavy frequency modulation
+//!
+//! Compares [`Phasor::advance`] (one conditional wrap, increment clamped to
+//! ±Nyquist) against the wrap loop it replaced, fed the same instantaneous
+//! frequencies: a 220 Hz carrier modulated at audio rate with indices from
+//! gentle to absurd. Run with `cargo bench -p shared-dsp`.
+//!
+//! # References
+//! - Chowning, "The Synthesis of Complex Audio Spectra by Means of Frequency
+//!   Modulation" (JAES, 1973): instantaneous frequency `fc + I·fm·cos(2π·fm·t)`
+
+use std::f64::consts::PI;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use shared_dsp::oscillators::Phasor;
+
+const SAMPLE_RATE: f32 = 48000.0;
+const SAMPLES: usize = 1 << 20;
+
+/// The replaced accumulator: wraps one cycle at a time
+struct LoopPhasor {
+    phase: f64,
+}
+
+impl LoopPhasor {
+    fn advance(&mut self, increment: f64) {
+        self.phase += increment;
+        while self.phase >= 1.0 {
+            self.phase -= 1.0;
+        }
+        while self.phase < 0.0 {
+            self.phase += 1.0;
+        }
+    }
+}
+
+/// Instantaneous frequencies of an FM voice with modulation `index`
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)] // Bench signal
+fn fm_frequencies(index: f64) -> Vec<f32> {
+    let (carrier, modulator) = (220.0, 330.0);
+    (0..SAMPLES)
+        .map(|n| {
+            let t = n as f64 / f64::from(SAMPLE_RATE);
+            (carrier + index * modulator * (2.0 * PI * modulator * t).cos()) as f32
+        })
+        .collect()
+}
+
+fn time(run: impl FnOnce()) -> Duration {
+    let started = Instant::now();
+    run();
+    started.elapsed()
+}
+
+#[allow(clippy::cast_precision_loss)] // Reporting only
+fn main() {
+    println!(
+        "{:>10} {:>14} {:>14}",
+        "FM index", "loop ns/smp", "phasor ns/smp"
+    );
+    for index in [1.0, 100.0, 10_000.0, 1_000_000.0] {
+        let frequencies = fm_frequencies(index);
+
+        let looped = time(|| {
+            let mut phasor = LoopPhasor { phase: 0.0 };
+            for &frequency in &frequencies {
+                phasor.advance(f64::from(frequency) / f64::from(SAMPLE_RATE));
+            }
+            black_box(phasor.phase);
+        });
+        let clamped = time(|| {
+            let mut phasor = Phasor::new(SAMPLE_RATE);
+            for &frequency in &frequencies {
+                phasor.set_frequency(frequency);
+                phasor.advance();
+            }
+            black_box(phasor.phase());
+        });
+
+        let per_sample = |elapsed: Duration| elapsed.as_nanos() as f64 / SAMPLES as f64;
+        println!(
+            "{index:>10} {:>14.2} {:>14.2}",
+            per_sample(looped),
+            per_sample(clamped)
+        );
+    }
+}
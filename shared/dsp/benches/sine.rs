@@ -0,0 +1,45 @@
+//! Sine oscillators at full polyphony
+//!
+//! Times 16 naive oscillators (one layer's voices) rendering sines through
+//! each [`SineMode`]: `sin()` against the linear and cubic table reads. Run
+//! with `cargo bench -p shared-dsp`.
+//!
+//! # References
+//! - `oscillators::sine_table` for the accuracy of each mode
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use shared_dsp::oscillators::{NaiveOscillator, Oscillator, SineMode};
+
+const SAMPLE_RATE: f32 = 48000.0;
+const VOICES: usize = 16;
+const SAMPLES: usize = 1 << 18;
+
+#[allow(clippy::cast_precision_loss)] // Voice indices and timings for reporting
+fn main() {
+    println!("{:>16} {:>18}", "Sine mode", "ns per sample (16)");
+    for mode in SineMode::ALL {
+        // A spread chord, two octaves and a bit
+        let mut voices: Vec<NaiveOscillator> = (0..VOICES)
+            .map(|voice| {
+                let mut osc = NaiveOscillator::new(SAMPLE_RATE);
+                osc.set_frequency(110.0 * 2.0_f32.powf(voice as f32 / 7.0));
+                osc.set_sine_mode(mode);
+                osc
+            })
+            .collect();
+
+        let started = Instant::now();
+        let mut mix = 0.0;
+        for _ in 0..SAMPLES {
+            for osc in &mut voices {
+                mix += osc.process_sine();
+            }
+        }
+        black_box(mix);
+
+        let per_sample = started.elapsed().as_nanos() as f64 / SAMPLES as f64;
+        println!("{:>16} {per_sample:>18.2}", format!("{mode:?}"));
+    }
+}
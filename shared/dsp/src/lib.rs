@@ -0,0 +1,11 @@
+//! Shared sound sources for audio DSP experiments
+//!
+//! Signal generators promoted out of individual plugins once a second plugin
+//! needs them. Unlike `shared_core`, which holds the processing utilities, this
+//! crate holds the things that make sound in the first place.
+
+#![warn(clippy::all)]
+#![warn(clippy::pedantic)]
+#![allow(clippy::module_name_repetitions)]
+
+pub mod oscillators;
@@ -0,0 +1,621 @@
+//! Naive oscillator: waveforms computed straight from the phase
+//!
+//! Exact at low pitches and the cheapest of the oscillators, but the saw and
+//! square jump between samples, so their harmonics above Nyquist fold back as
+//! aliasing as the pitch rises.
+//!
+//! # References
+//! - Standard oscillator equations from digital audio synthesis
+
+use super::{Oscillator, Phasor, SineMode, SineTable, WaveformType};
+
+/// Multi-waveform oscillator without band-limiting
+///
+/// # Real-time Safety
+/// - No allocations in process methods
+/// - All state pre-initialized in `new()` (the first one builds the shared
+///   sine table)
+/// - Uses inline functions for hot path
+///
+/// # Example
+/// ```
+/// use shared_dsp::oscillators::{NaiveOscillator, Oscillator};
+///
+/// let mut osc = NaiveOscillator::new(44100.0);
+/// osc.set_frequency(440.0); // A4
+/// let sample = osc.process_sine();
+/// ```
+#[derive(Debug, Clone)]
+pub struct NaiveOscillator {
+    phasor: Phasor,
+
+    /// How the sine is computed
+    sine_mode: SineMode,
+    sine_table: &'static SineTable,
+}
+
+impl NaiveOscillator {
+    /// Create a new oscillator at 0 Hz
+    ///
+    /// # Arguments
+    /// * `sample_rate` - Sample rate in Hz (e.g., 44100.0, 48000.0)
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            phasor: Phasor::new(sample_rate),
+            sine_mode: SineMode::Exact,
+            sine_table: SineTable::shared(),
+        }
+    }
+
+    /// Set how the sine is computed: `sin()` or a table read
+    pub fn set_sine_mode(&mut self, mode: SineMode) {
+        self.sine_mode = mode;
+    }
+
+    /// Process one sample of sine waveform
+    ///
+    /// Uses standard sine formula: sin(2π * phase), or a table read of it
+    /// (see [`NaiveOscillator::set_sine_mode`])
+    ///
+    /// # Returns
+    /// Sine wave sample (-1.0 to 1.0)
+    #[inline]
+    pub fn process_sine(&mut self) -> f32 {
+        // Calculate sine value at current phase
+        let output = self.sine_mode.sin(self.sine_table, self.phasor.phase());
+
+        // Advance phase
+        self.phasor.advance();
+
+        output
+    }
+
+    /// Process one sample of sawtooth waveform
+    ///
+    /// Rising sawtooth from -1 to almost +1, then wraps.
+    /// Formulated to have only 1 zero crossing per cycle (upward during the ramp).
+    /// The discontinuity goes from positive back to negative, creating the second transition
+    /// but not a zero crossing since it doesn't pass through zero.
+    ///
+    /// Note: This is a naive implementation that will alias at high frequencies;
+    /// [`PolyBlepOscillator`](super::PolyBlepOscillator) doesn't.
+    ///
+    /// # Returns
+    /// Sawtooth sample (-1.0 to ~1.0)
+    #[inline]
+    #[allow(clippy::cast_possible_truncation)] // f64 phase -> f32 output is intentional
+    pub fn process_sawtooth(&mut self) -> f32 {
+        // Generate sawtooth with 1 zero crossing per cycle
+        // Ramp from -1.0 to +1.0, but we need to ensure the discontinuity doesn't create
+        // a second zero crossing. Standard approach: ramp from -1 to just under 0, then wrap
+        // This gives one upward zero crossing during the ramp
+        //
+        // Phase 0.0 → -1.0
+        // Phase 0.5 → 0.0  (zero crossing here)
+        // Phase 1.0 → +1.0 (wrap back to -1.0, crossing zero again!)
+        //
+        // To avoid double crossing, offset the waveform so it doesn't cross during ramp:
+        // Ramp from 0.1 to 2.1, then scale/shift to audio range
+        //
+        // Actually, simpler: just use phase directly (0 to 1), scale to -1 to +1
+        // This crosses zero at phase=0.5 (once) and at the wrap (going from +1 to -1)
+        // So we get 2 crossings, which is what we're seeing (880 instead of 440)
+        //
+        // For 1 crossing only: DON'T cross zero during the ramp
+        // Map phase 0-1 to output 0-2, then subtract 1 → range -1 to +1, crossing at 0.5
+        // That still crosses twice!
+        //
+        // Solution: Map to asymmetric range that doesn't include zero discontinuity
+        // Phase 0-1 → -1 to -0.001 (never reaches positive, never crosses zero at wrap)
+        // But then where's the zero crossing? We need it during the ramp!
+        //
+        // Better: Phase 0-1 → -0.999 to +0.999, crosses zero once at phase~=0.5
+        // Discontinuity: +0.999 → -0.999 (doesn't cross zero, stays away from it)
+
+        // Standard sawtooth: linear ramp from -1 to +1
+        // This creates 2 zero crossings per cycle: one during the ramp (at phase ~0.5)
+        // and one at the discontinuity (from +1 wrapping back to -1)
+        let output = (2.0 * self.phasor.phase() as f32) - 1.0;
+
+        // Advance phase
+        self.phasor.advance();
+
+        output
+    }
+
+    /// Process one sample of square waveform
+    ///
+    /// Output is -1 or +1 based on phase being below or above 0.5 (50% duty cycle).
+    /// Note: Naive implementation will alias.
+    ///
+    /// # Returns
+    /// Square wave sample (-1.0 or 1.0)
+    #[inline]
+    pub fn process_square(&mut self) -> f32 {
+        // Square wave: -1 for first half of cycle, +1 for second half
+        let output = if self.phasor.phase() < 0.5 { -1.0 } else { 1.0 };
+
+        // Advance phase
+        self.phasor.advance();
+
+        output
+    }
+
+    /// Process one sample of triangle waveform
+    ///
+    /// Tent function: rises from -1 to +1 in first half, falls from +1 to -1 in second half.
+    ///
+    /// # Returns
+    /// Triangle wave sample (-1.0 to 1.0)
+    #[inline]
+    #[allow(clippy::cast_possible_truncation)] // f64 phase -> f32 output is intentional
+    pub fn process_triangle(&mut self) -> f32 {
+        // Triangle wave: linear interpolation up then down
+        let output = if self.phasor.phase() < 0.5 {
+            // Rising: -1 to +1 (phase 0.0 to 0.5)
+            -1.0 + (4.0 * self.phasor.phase() as f32)
+        } else {
+            // Falling: +1 to -1 (phase 0.5 to 1.0)
+            3.0 - (4.0 * self.phasor.phase() as f32)
+        };
+
+        // Advance phase
+        self.phasor.advance();
+
+        output
+    }
+}
+
+impl Oscillator for NaiveOscillator {
+    #[inline]
+    fn set_frequency(&mut self, frequency: f32) {
+        self.phasor.set_frequency(frequency);
+    }
+
+    fn frequency(&self) -> f32 {
+        self.phasor.frequency()
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.phasor.set_sample_rate(sample_rate);
+    }
+
+    /// Used at note on to set the attack's start point: 0.25 starts a sine at
+    /// its peak for a click on the transient, 0.0 starts it silent.
+    fn reset_to(&mut self, phase: f32) {
+        self.phasor.reset_to(phase);
+    }
+
+    #[inline]
+    fn process(&mut self, waveform: WaveformType) -> f32 {
+        match waveform {
+            WaveformType::Sine => self.process_sine(),
+            WaveformType::Sawtooth => self.process_sawtooth(),
+            WaveformType::Square => self.process_square(),
+            WaveformType::Triangle => self.process_triangle(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_core::pitch::PitchDetector;
+
+    // Helper to measure the pitch of a waveform (works for any number of zero crossings)
+    fn detect_frequency(samples: &[f32], sample_rate: f32) -> f32 {
+        PitchDetector::new(sample_rate, 50.0, 5000.0)
+            .detect(samples)
+            .expect("Waveform should have a pitch")
+            .frequency_hz
+    }
+
+    // Helper to calculate RMS of a signal
+    #[allow(clippy::cast_precision_loss)] // Test buffer lengths
+    fn calculate_rms(samples: &[f32]) -> f32 {
+        let sum_squares: f32 = samples.iter().map(|s| s * s).sum();
+        (sum_squares / samples.len() as f32).sqrt()
+    }
+
+    // Helper to convert MIDI note to frequency
+    fn midi_note_to_frequency(note: u8) -> f32 {
+        440.0 * 2.0f32.powf((f32::from(note) - 69.0) / 12.0)
+    }
+
+    #[test]
+    fn test_oscillator_creation() {
+        let _osc = NaiveOscillator::new(44100.0);
+    }
+
+    #[test]
+    fn test_sine_wave_frequency_accuracy() {
+        // RED: Validate that a sine oscillator produces the correct frequency
+        let sample_rate = 44100.0;
+        let frequency = 440.0; // A4
+        let mut osc = NaiveOscillator::new(sample_rate);
+        osc.set_frequency(frequency);
+
+        // Generate 1 second of audio
+        let samples: Vec<f32> = (0..44100).map(|_| osc.process_sine()).collect();
+
+        let detected = detect_frequency(&samples, sample_rate);
+        assert!(
+            (detected - frequency).abs() < 0.5,
+            "Expected 440 Hz, got {detected}"
+        );
+    }
+
+    #[test]
+    fn test_sine_wave_amplitude() {
+        // RED: Sine wave should have peak amplitude of 1.0
+        let mut osc = NaiveOscillator::new(44100.0);
+        osc.set_frequency(440.0);
+
+        let samples: Vec<f32> = (0..1000).map(|_| osc.process_sine()).collect();
+
+        let max_amplitude = samples.iter().map(|s| s.abs()).fold(0.0f32, f32::max);
+
+        // Should be close to 1.0
+        assert!(
+            (max_amplitude - 1.0).abs() < 0.01,
+            "Expected max amplitude ~1.0, got {max_amplitude}"
+        );
+
+        // RMS should be approximately 1/sqrt(2) = 0.707 for sine wave
+        let rms = calculate_rms(&samples);
+        assert!((rms - 0.707).abs() < 0.05, "Expected RMS ~0.707, got {rms}");
+    }
+
+    #[test]
+    fn test_sawtooth_wave_frequency_accuracy() {
+        // RED: Validate sawtooth frequency
+        let sample_rate = 44100.0;
+        let frequency = 440.0;
+        let mut osc = NaiveOscillator::new(sample_rate);
+        osc.set_frequency(frequency);
+
+        let samples: Vec<f32> = (0..44100).map(|_| osc.process_sawtooth()).collect();
+
+        let detected = detect_frequency(&samples, sample_rate);
+        assert!(
+            (detected - frequency).abs() < 0.5,
+            "Expected 440 Hz sawtooth, got {detected}"
+        );
+    }
+
+    #[test]
+    fn test_sawtooth_wave_range() {
+        // RED: Sawtooth should ramp from -1 to +1
+        let mut osc = NaiveOscillator::new(44100.0);
+        osc.set_frequency(100.0);
+
+        let samples: Vec<f32> = (0..1000).map(|_| osc.process_sawtooth()).collect();
+
+        let max = samples.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+        let min = samples.iter().fold(f32::INFINITY, |a, &b| a.min(b));
+
+        assert!(max <= 1.0, "Sawtooth max should be <= 1.0, got {max}");
+        assert!(min >= -1.0, "Sawtooth min should be >= -1.0, got {min}");
+        assert!(max > 0.9, "Sawtooth should reach close to 1.0");
+        assert!(min < -0.9, "Sawtooth should reach close to -1.0");
+    }
+
+    #[test]
+    fn test_square_wave_frequency_accuracy() {
+        // RED: Validate square wave frequency
+        let sample_rate = 44100.0;
+        let frequency = 440.0;
+        let mut osc = NaiveOscillator::new(sample_rate);
+        osc.set_frequency(frequency);
+
+        let samples: Vec<f32> = (0..44100).map(|_| osc.process_square()).collect();
+
+        let detected = detect_frequency(&samples, sample_rate);
+        assert!(
+            (detected - frequency).abs() < 0.5,
+            "Expected 440 Hz square, got {detected}"
+        );
+    }
+
+    #[test]
+    fn test_square_wave_duty_cycle() {
+        // RED: Square wave should be 50% high, 50% low
+        let mut osc = NaiveOscillator::new(44100.0);
+        osc.set_frequency(100.0);
+
+        let samples: Vec<f32> = (0..10000).map(|_| osc.process_square()).collect();
+
+        // Count samples above and below zero
+        let high_samples = samples.iter().filter(|&&s| s > 0.0).count();
+        let low_samples = samples.iter().filter(|&&s| s < 0.0).count();
+
+        #[allow(clippy::cast_precision_loss)] // Test sample counts
+        let ratio = high_samples as f32 / low_samples as f32;
+        assert!(
+            (ratio - 1.0).abs() < 0.1,
+            "Square wave duty cycle should be ~50%, got ratio {ratio}"
+        );
+
+        // Values should be exactly +1 or -1
+        for &sample in &samples {
+            assert!(
+                (sample.abs() - 1.0).abs() < 0.01,
+                "Square wave samples should be ±1, got {sample}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_triangle_wave_frequency_accuracy() {
+        // RED: Validate triangle wave frequency
+        let sample_rate = 44100.0;
+        let frequency = 440.0;
+        let mut osc = NaiveOscillator::new(sample_rate);
+        osc.set_frequency(frequency);
+
+        let samples: Vec<f32> = (0..44100).map(|_| osc.process_triangle()).collect();
+
+        let detected = detect_frequency(&samples, sample_rate);
+        assert!(
+            (detected - frequency).abs() < 0.5,
+            "Expected 440 Hz triangle, got {detected}"
+        );
+    }
+
+    #[test]
+    fn test_triangle_wave_symmetry() {
+        // RED: Triangle wave should be symmetric
+        let mut osc = NaiveOscillator::new(44100.0);
+        osc.set_frequency(100.0);
+
+        let samples: Vec<f32> = (0..1000).map(|_| osc.process_triangle()).collect();
+
+        let max = samples.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+        let min = samples.iter().fold(f32::INFINITY, |a, &b| a.min(b));
+
+        assert!(
+            (max - 1.0).abs() < 0.01,
+            "Triangle max should be ~1.0, got {max}"
+        );
+        assert!(
+            (min + 1.0).abs() < 0.01,
+            "Triangle min should be ~-1.0, got {min}"
+        );
+    }
+
+    #[test]
+    fn test_frequency_control_midi_note() {
+        // RED: Test MIDI note to frequency conversion
+        let mut osc = NaiveOscillator::new(44100.0);
+
+        // MIDI note 69 = A4 = 440 Hz
+        let a4 = midi_note_to_frequency(69);
+        assert!((a4 - 440.0).abs() < 0.01, "A4 should be 440 Hz");
+
+        // MIDI note 60 = C4 = 261.63 Hz
+        let c4 = midi_note_to_frequency(60);
+        assert!(
+            (c4 - 261.63).abs() < 0.1,
+            "C4 should be ~261.63 Hz, got {c4}"
+        );
+
+        // Generate audio at C4 frequency
+        osc.set_frequency(c4);
+        let samples: Vec<f32> = (0..44100).map(|_| osc.process_sine()).collect();
+
+        // Verify frequency is correct
+        let detected = detect_frequency(&samples, 44100.0);
+        assert!(
+            (detected - c4).abs() < 0.5,
+            "Expected ~{c4} Hz, got {detected}"
+        );
+    }
+
+    #[test]
+    fn test_phase_accumulation_wraps_correctly() {
+        // RED: Phase should wrap at 2π and not accumulate indefinitely
+        let mut osc = NaiveOscillator::new(44100.0);
+        osc.set_frequency(10000.0);
+
+        // Run for a long time at high frequency
+        for _ in 0..100_000 {
+            let sample = osc.process_sine();
+            assert!(
+                sample.is_finite(),
+                "Sample should be finite (phase wrapping working)"
+            );
+        }
+
+        // If phase wrapping is broken, we'd get NaN or inf
+        // This is a basic sanity check
+    }
+
+    #[test]
+    fn test_zero_frequency_edge_case() {
+        // RED: Zero frequency should not crash or produce NaN
+        let mut osc = NaiveOscillator::new(44100.0);
+        osc.set_frequency(0.0);
+
+        for _ in 0..100 {
+            let sample = osc.process_sine();
+            assert!(
+                sample.is_finite(),
+                "Zero frequency should produce finite output"
+            );
+        }
+    }
+
+    #[test]
+    fn test_negative_frequency_edge_case() {
+        // RED: Negative frequency should either work (reverse) or be clamped
+        let mut osc = NaiveOscillator::new(44100.0);
+        osc.set_frequency(-440.0);
+
+        for _ in 0..100 {
+            let sample = osc.process_sine();
+            assert!(
+                sample.is_finite(),
+                "Negative frequency should produce finite output"
+            );
+        }
+    }
+
+    #[test]
+    fn test_nyquist_frequency_edge_case() {
+        // RED: Frequency at Nyquist (sample_rate / 2) is the edge of valid range
+        let sample_rate = 44100.0;
+        let mut osc = NaiveOscillator::new(sample_rate);
+        osc.set_frequency(sample_rate / 2.0);
+
+        let samples: Vec<f32> = (0..1000).map(|_| osc.process_sine()).collect();
+
+        // Should produce alternating +1, -1 (or close to it)
+        for sample in samples {
+            assert!(sample.is_finite(), "Nyquist frequency should be stable");
+            assert!(sample.abs() <= 1.1, "Amplitude should be reasonable");
+        }
+    }
+
+    #[test]
+    fn test_above_nyquist_frequency_edge_case() {
+        // RED: Frequencies above Nyquist will alias
+        // We document this behavior but don't crash
+        let sample_rate = 44100.0;
+        let mut osc = NaiveOscillator::new(sample_rate);
+        osc.set_frequency(30000.0);
+
+        for _ in 0..100 {
+            let sample = osc.process_sine(); // Above Nyquist
+            assert!(
+                sample.is_finite(),
+                "Above-Nyquist frequency should not crash (will alias)"
+            );
+        }
+    }
+
+    #[test]
+    fn test_extreme_fm_frequencies_wrap_in_one_step() {
+        // Heavy FM can push the instantaneous frequency anywhere; a wrap loop
+        // would spin (or never finish) on increments this large
+        let mut osc = NaiveOscillator::new(44100.0);
+        for frequency in [1e30, -1e30, f32::INFINITY, f32::NEG_INFINITY, f32::NAN, 1e6] {
+            osc.set_frequency(frequency);
+            for _ in 0..100 {
+                assert!(osc.process_sine().is_finite(), "{frequency} Hz");
+                let phase = osc.phasor.phase();
+                assert!((0.0..1.0).contains(&phase), "{frequency} Hz: phase {phase}");
+            }
+            assert!(osc.phasor.increment().abs() <= 0.5, "Clamped to Nyquist");
+        }
+        assert!(
+            (osc.frequency() - 1e6).abs() < 1.0,
+            "The set frequency is kept"
+        );
+    }
+
+    #[test]
+    fn test_tiny_negative_phase_wraps_below_one() {
+        let mut osc = NaiveOscillator::new(44100.0);
+        osc.set_frequency(-1e-20);
+        osc.process_sine();
+        assert!(osc.phasor.phase() < 1.0);
+    }
+
+    #[test]
+    fn test_table_sine_follows_exact_sine() {
+        let mut exact = NaiveOscillator::new(44100.0);
+        exact.set_frequency(440.0);
+        for mode in [SineMode::LinearTable, SineMode::CubicTable] {
+            let mut table = NaiveOscillator::new(44100.0);
+            table.set_frequency(440.0);
+            table.set_sine_mode(mode);
+            exact.reset();
+
+            for _ in 0..4410 {
+                let (expected, sample) = (exact.process_sine(), table.process_sine());
+                assert!((sample - expected).abs() < 1e-5, "{mode:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_oscillator_reset() {
+        // RED: Oscillator should have a reset method to zero phase
+        let mut osc = NaiveOscillator::new(44100.0);
+        osc.set_frequency(440.0);
+
+        // Generate some samples
+        for _ in 0..1000 {
+            osc.process_sine();
+        }
+
+        // Reset phase
+        osc.reset();
+
+        // After reset, we should start from phase 0
+        let first_sample = osc.process_sine();
+
+        // For sine wave at phase 0, value should be close to 0
+        assert!(
+            first_sample.abs() < 0.1,
+            "After reset, sine should start near 0, got {first_sample}"
+        );
+    }
+
+    #[test]
+    fn test_reset_to_start_phase() {
+        let mut osc = NaiveOscillator::new(44100.0);
+        osc.set_frequency(440.0);
+        osc.process_sine();
+
+        osc.reset_to(0.25);
+        assert!(
+            (osc.process_sine() - 1.0).abs() < 1e-6,
+            "Quarter cycle is the peak"
+        );
+
+        // Whole cycles wrap away
+        osc.reset_to(1.75);
+        assert!((osc.process_sine() + 1.0).abs() < 1e-6);
+        osc.reset_to(-0.25);
+        assert!((osc.process_sine() + 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_frequency_is_kept_across_sample_rates() {
+        let mut osc = NaiveOscillator::new(44100.0);
+        osc.set_frequency(440.0);
+        osc.set_sample_rate(96000.0);
+        assert!((osc.frequency() - 440.0).abs() < f32::EPSILON);
+
+        let samples: Vec<f32> = (0..96000)
+            .map(|_| osc.process(WaveformType::Sine))
+            .collect();
+        let detected = detect_frequency(&samples, 96000.0);
+        assert!(
+            (detected - 440.0).abs() < 0.5,
+            "Expected 440 Hz, got {detected}"
+        );
+    }
+
+    #[test]
+    fn test_multiple_oscillators_independent() {
+        // RED: Multiple oscillator instances should not interfere
+        let mut osc1 = NaiveOscillator::new(44100.0);
+        osc1.set_frequency(440.0);
+        let mut osc2 = NaiveOscillator::new(44100.0);
+        osc2.set_frequency(440.0);
+
+        let samples1: Vec<f32> = (0..100).map(|_| osc1.process_sine()).collect();
+        let samples2: Vec<f32> = (0..100).map(|_| osc2.process_sine()).collect();
+
+        // Both should produce identical output (starting from same phase)
+        for (s1, s2) in samples1.iter().zip(samples2.iter()) {
+            assert!(
+                (s1 - s2).abs() < 0.0001,
+                "Independent oscillators should produce same output"
+            );
+        }
+    }
+}
@@ -0,0 +1,303 @@
+//! Wavetable oscillator reading band-limited tables
+//!
+//! Each waveform is stored as a set of single-cycle tables built by additive
+//! synthesis, one per octave of harmonic content: the first holds only the
+//! fundamental, the next two harmonics, then four, and so on. At each pitch the
+//! oscillator reads the richest table whose top harmonic still fits below
+//! Nyquist, so nothing aliases; the price is that between one table and the
+//! next the top octave of harmonics is missing.
+//!
+//! The tables don't depend on the sample rate, so one bank serves every
+//! oscillator. It's built the first time one is created (a few milliseconds
+//! and under half a megabyte), which should be at plugin init rather than on the
+//! audio thread.
+//!
+//! # References
+//! - Mipmapped wavetables: one band-limited table per octave
+//! - Fourier series: saw `-(2/π)·Σ sin(2πhp)/h`, square `-(4/π)·Σ_odd sin(2πhp)/h`,
+//!   triangle `-(8/π²)·Σ_odd cos(2πhp)/h²` (phases matching the naive waveforms)
+
+use super::{Oscillator, Phasor, WaveformType};
+use std::f32::consts::PI;
+use std::sync::OnceLock;
+
+/// Samples per table (a power of two)
+pub const TABLE_SIZE: usize = 2048;
+
+/// Tables per waveform; the last holds `2^(TABLE_COUNT - 1)` harmonics
+pub const TABLE_COUNT: usize = 10;
+
+/// Each table carries one guard sample, a copy of its first, for interpolation
+const STRIDE: usize = TABLE_SIZE + 1;
+
+/// Band-limited single-cycle tables for every waveform
+///
+/// # Example
+/// ```
+/// use shared_dsp::oscillators::{WaveformType, WavetableBank};
+///
+/// let bank = WavetableBank::shared();
+/// // One harmonic: a plain sine whatever the waveform
+/// let fundamental = bank.table(WaveformType::Sawtooth, 0);
+/// ```
+#[derive(Debug)]
+pub struct WavetableBank {
+    /// Tables of each waveform in `WaveformType::ALL` order, fewest harmonics first
+    samples: Vec<f32>,
+}
+
+impl WavetableBank {
+    /// Build every table (allocates; see [`WavetableBank::shared`])
+    #[must_use]
+    pub fn new() -> Self {
+        #[allow(clippy::cast_precision_loss)] // Table indices are exact in f32
+        let sine: Vec<f32> = (0..TABLE_SIZE)
+            .map(|i| (2.0 * PI * i as f32 / TABLE_SIZE as f32).sin())
+            .collect();
+
+        let mut samples = Vec::with_capacity(WaveformType::ALL.len() * TABLE_COUNT * STRIDE);
+        let mut sum = vec![0.0f32; TABLE_SIZE];
+        for waveform in WaveformType::ALL {
+            sum.fill(0.0);
+            let mut harmonics = 0;
+            for level in 0..TABLE_COUNT {
+                // Add the harmonics this level has over the previous one
+                let limit = 1 << level;
+                for harmonic in (harmonics + 1)..=limit {
+                    let Some((amplitude, quarter_turn)) = partial(waveform, harmonic) else {
+                        continue;
+                    };
+                    let offset = if quarter_turn { TABLE_SIZE / 4 } else { 0 };
+                    for (i, value) in sum.iter_mut().enumerate() {
+                        *value += amplitude * sine[(harmonic * i + offset) % TABLE_SIZE];
+                    }
+                }
+                harmonics = limit;
+
+                samples.extend_from_slice(&sum);
+                samples.push(sum[0]);
+            }
+        }
+        Self { samples }
+    }
+
+    /// The bank shared by every oscillator, built on first use
+    pub fn shared() -> &'static Self {
+        static BANK: OnceLock<WavetableBank> = OnceLock::new();
+        BANK.get_or_init(Self::new)
+    }
+
+    /// Table of `waveform` at `level` (`2^level` harmonics, clamped to the
+    /// richest), including the guard sample
+    #[must_use]
+    pub fn table(&self, waveform: WaveformType, level: usize) -> &[f32] {
+        let start = (waveform_index(waveform) * TABLE_COUNT + level.min(TABLE_COUNT - 1)) * STRIDE;
+        &self.samples[start..start + STRIDE]
+    }
+}
+
+impl Default for WavetableBank {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Amplitude of one harmonic of `waveform`, and whether it's a cosine (`None`
+/// if the waveform has no such harmonic)
+#[allow(clippy::cast_precision_loss)] // Harmonic numbers are small
+fn partial(waveform: WaveformType, harmonic: usize) -> Option<(f32, bool)> {
+    let h = harmonic as f32;
+    let odd = harmonic % 2 == 1;
+    match waveform {
+        WaveformType::Sine => (harmonic == 1).then_some((1.0, false)),
+        WaveformType::Sawtooth => Some((-2.0 / (PI * h), false)),
+        WaveformType::Square => odd.then_some((-4.0 / (PI * h), false)),
+        WaveformType::Triangle => odd.then_some((-8.0 / (PI * PI * h * h), true)),
+    }
+}
+
+fn waveform_index(waveform: WaveformType) -> usize {
+    match waveform {
+        WaveformType::Sine => 0,
+        WaveformType::Sawtooth => 1,
+        WaveformType::Square => 2,
+        WaveformType::Triangle => 3,
+    }
+}
+
+/// Oscillator reading the shared band-limited wavetables
+///
+/// # Real-time Safety
+/// - Creating the first one builds the shared bank (allocates)
+/// - No allocations in process methods
+///
+/// # Example
+/// ```
+/// use shared_dsp::oscillators::{Oscillator, WaveformType, WavetableOscillator};
+///
+/// let mut osc = WavetableOscillator::new(48000.0);
+/// osc.set_frequency(5000.0);
+/// let sample = osc.process(WaveformType::Square);
+/// ```
+#[derive(Debug, Clone)]
+pub struct WavetableOscillator {
+    phasor: Phasor,
+
+    bank: &'static WavetableBank,
+
+    /// Table level for the current frequency
+    level: usize,
+}
+
+impl WavetableOscillator {
+    /// Create a new oscillator at 0 Hz
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            phasor: Phasor::new(sample_rate),
+            bank: WavetableBank::shared(),
+            level: TABLE_COUNT - 1,
+        }
+    }
+
+    /// Table level in use: `2^level` harmonics
+    #[must_use]
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// Richest level whose top harmonic stays below Nyquist
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Positive, clamped
+    fn update_level(&mut self) {
+        let increment = self.phasor.increment().abs();
+        let harmonics = if increment > 0.0 {
+            (0.5 / increment).min(f64::from(u32::MAX)) as u32
+        } else {
+            u32::MAX
+        };
+        self.level = if harmonics == 0 {
+            // Above Nyquist, nothing can help
+            0
+        } else {
+            (harmonics.ilog2() as usize).min(TABLE_COUNT - 1)
+        };
+    }
+}
+
+impl Oscillator for WavetableOscillator {
+    #[inline]
+    #[allow(clippy::float_cmp)] // Exact repeats only, to skip the table choice
+    fn set_frequency(&mut self, frequency: f32) {
+        if frequency != self.phasor.frequency() {
+            self.phasor.set_frequency(frequency);
+            self.update_level();
+        }
+    }
+
+    fn frequency(&self) -> f32 {
+        self.phasor.frequency()
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.phasor.set_sample_rate(sample_rate);
+        self.update_level();
+    }
+
+    fn reset_to(&mut self, phase: f32) {
+        self.phasor.reset_to(phase);
+    }
+
+    #[inline]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )] // Phase is 0.0-1.0, positions are below TABLE_SIZE
+    fn process(&mut self, waveform: WaveformType) -> f32 {
+        let table = self.bank.table(waveform, self.level);
+        let position = self.phasor.phase() * TABLE_SIZE as f64;
+        let index = (position as usize).min(TABLE_SIZE - 1);
+        let fraction = (position - index as f64) as f32;
+
+        self.phasor.advance();
+        table[index] + (table[index + 1] - table[index]) * fraction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::NaiveOscillator;
+    use super::*;
+    use shared_core::analysis::{estimate_frequency, goertzel_amplitude};
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    fn render(osc: &mut impl Oscillator, waveform: WaveformType, frequency: f32) -> Vec<f32> {
+        osc.set_frequency(frequency);
+        (0..8192).map(|_| osc.process(waveform)).collect()
+    }
+
+    #[test]
+    fn test_tables_match_naive_waveforms() {
+        let bank = WavetableBank::shared();
+        // One cycle per table length
+        #[allow(clippy::cast_precision_loss)] // 2048 is exact
+        let mut naive = NaiveOscillator::new(TABLE_SIZE as f32);
+        naive.set_frequency(1.0);
+        for waveform in WaveformType::ALL {
+            naive.reset();
+            let richest = bank.table(waveform, TABLE_COUNT - 1);
+            // Compare away from the jumps, where the Gibbs ripple lives
+            let error = (0..TABLE_SIZE)
+                .map(|i| (naive.process(waveform) - richest[i]).abs())
+                .enumerate()
+                .filter(|(i, _)| (TABLE_SIZE / 16..TABLE_SIZE * 7 / 16).contains(i))
+                .fold(0.0f32, |max, (_, e)| max.max(e));
+            assert!(error < 0.01, "{waveform:?}: {error}");
+            assert!((richest[TABLE_SIZE] - richest[0]).abs() < f32::EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_level_follows_pitch() {
+        let mut osc = WavetableOscillator::new(SAMPLE_RATE);
+        osc.set_frequency(20.0);
+        assert_eq!(osc.level(), TABLE_COUNT - 1);
+        // 24000 / 5000 = 4.8 harmonics fit: the four-harmonic table
+        osc.set_frequency(5000.0);
+        assert_eq!(osc.level(), 2);
+        osc.set_frequency(-5000.0);
+        assert_eq!(osc.level(), 2);
+        osc.set_frequency(30000.0);
+        assert_eq!(osc.level(), 0);
+        // Twice the sample rate fits twice the harmonics
+        osc.set_frequency(5000.0);
+        osc.set_sample_rate(96000.0);
+        assert_eq!(osc.level(), 3);
+    }
+
+    #[test]
+    fn test_no_aliasing() {
+        // The 11th harmonic of 4567 Hz would fold back to 2237 Hz
+        let alias_hz = 11.0 * 4567.0 - SAMPLE_RATE;
+        for waveform in [WaveformType::Sawtooth, WaveformType::Square] {
+            let table = render(&mut WavetableOscillator::new(SAMPLE_RATE), waveform, 4567.0);
+            let alias = goertzel_amplitude(&table, SAMPLE_RATE, alias_hz);
+            assert!(alias < 1e-3, "{waveform:?}: {alias}");
+
+            // The fundamental is the full Fourier amplitude
+            let fundamental = goertzel_amplitude(&table, SAMPLE_RATE, 4567.0);
+            let expected = partial(waveform, 1).unwrap().0.abs();
+            assert!((fundamental - expected).abs() < 0.01, "{waveform:?}");
+        }
+    }
+
+    #[test]
+    fn test_pitch_accuracy() {
+        for waveform in WaveformType::ALL {
+            let samples = render(&mut WavetableOscillator::new(SAMPLE_RATE), waveform, 440.0);
+            let detected = estimate_frequency(&samples, SAMPLE_RATE).unwrap();
+            assert!((detected - 440.0).abs() < 0.5, "{waveform:?}: {detected}");
+        }
+    }
+}